@@ -0,0 +1,169 @@
+//! The flags given on the command line, which override the settings of the config file. See
+//! `help_message()` in main.rs for what each of them does.
+
+use crate::config::{parse_size, Config};
+use crate::style::computed::ColorScheme;
+use crate::url::ParsedUrl;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use liumlib::log::Filter;
+
+/// The ways to print a page instead of showing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpMode {
+    Dom,
+    DomJson,
+    Text,
+    Layout,
+}
+
+/// The HTML shown instead of fetching the URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// The HTML given with `--html`.
+    Html(String),
+    /// The HTML read from the standard input with `--stdin`.
+    Stdin,
+}
+
+/// The flags followed by a value, which isn't a URL.
+const VALUE_FLAGS: [&str; 15] = [
+    "-u",
+    "--url",
+    "--config",
+    "--width",
+    "--height",
+    "--color-scheme",
+    "--html",
+    "--screenshot",
+    "-o",
+    "--output",
+    "--bookmarks",
+    "--user-agent",
+    "--proxy",
+    "--log",
+    "--log-file",
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct Args {
+    /// True if the usage is asked for with `--help`.
+    pub help: bool,
+    /// The URLs to open, or the homepage if it's empty.
+    pub urls: Vec<String>,
+    /// The config file given with `--config`.
+    pub config_file: Option<String>,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+    pub color_scheme: Option<ColorScheme>,
+    pub dump_mode: Option<DumpMode>,
+    pub source: Option<Source>,
+    /// The file to write the rendered page to instead of showing it.
+    pub screenshot: Option<String>,
+    /// The file to save the body of the response to instead of parsing it.
+    pub output: Option<String>,
+    pub frame_time: bool,
+    pub timing: bool,
+    pub bookmarks_file: Option<String>,
+    pub no_cache: bool,
+    pub no_refresh: bool,
+    pub user_agent: Option<String>,
+    pub proxy: Option<ParsedUrl>,
+    /// The levels of the messages to log, which are the ones of the config if it's None.
+    pub log: Option<Filter>,
+    pub log_file: Option<String>,
+}
+
+impl Args {
+    /// Parses `args`, whose first one is the name of the program, or returns an error message if
+    /// a flag misses its value or the value is invalid. The flags given later win, e.g. the last
+    /// of `--html` and `--stdin`, except that the dump modes are chosen in the order of `--dump-dom`,
+    /// `--dump-dom-json`, `--dump` and `--dump-layout`.
+    pub fn parse(args: &[&str]) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut dump_modes = Vec::new();
+        for i in 1..args.len() {
+            let flag = args[i];
+            if !flag.starts_with('-') {
+                if !VALUE_FLAGS.contains(&args[i - 1]) {
+                    parsed.urls.push(String::from(flag));
+                }
+                continue;
+            }
+            let value = || match args.get(i + 1) {
+                Some(value) => Ok(*value),
+                None => Err(format!("{} needs a value", flag)),
+            };
+            let invalid = || format!("invalid {}: {}", flag, args.get(i + 1).unwrap_or(&""));
+            match flag {
+                "--help" => parsed.help = true,
+                "-u" | "--url" => parsed.urls.push(String::from(value()?)),
+                "--config" => parsed.config_file = Some(String::from(value()?)),
+                "--width" => parsed.width = Some(parse_size(value()?).ok_or_else(invalid)?),
+                "--height" => parsed.height = Some(parse_size(value()?).ok_or_else(invalid)?),
+                "--color-scheme" => {
+                    parsed.color_scheme = Some(ColorScheme::parse(value()?).ok_or_else(invalid)?)
+                }
+                "--dump-dom" => dump_modes.push(DumpMode::Dom),
+                "--dump-dom-json" => dump_modes.push(DumpMode::DomJson),
+                "--dump" => dump_modes.push(DumpMode::Text),
+                "--dump-layout" => dump_modes.push(DumpMode::Layout),
+                "--html" => parsed.source = Some(Source::Html(String::from(value()?))),
+                "--stdin" => parsed.source = Some(Source::Stdin),
+                "--screenshot" => parsed.screenshot = Some(String::from(value()?)),
+                "-o" | "--output" => parsed.output = Some(String::from(value()?)),
+                "--frame-time" => parsed.frame_time = true,
+                "--timing" => parsed.timing = true,
+                "--bookmarks" => parsed.bookmarks_file = Some(String::from(value()?)),
+                "--no-cache" => parsed.no_cache = true,
+                "--no-refresh" => parsed.no_refresh = true,
+                "--user-agent" => parsed.user_agent = Some(String::from(value()?)),
+                "--proxy" => parsed.proxy = Some(ParsedUrl::parse(value()?).ok_or_else(invalid)?),
+                "--log" => {
+                    let filter =
+                        Filter::parse(value()?).map_err(|e| format!("invalid --log: {}", e));
+                    parsed.log = Some(filter?);
+                }
+                "--log-file" => parsed.log_file = Some(String::from(value()?)),
+                // The unknown flags are ignored.
+                _ => {}
+            }
+        }
+        parsed.dump_mode = [
+            DumpMode::Dom,
+            DumpMode::DomJson,
+            DumpMode::Text,
+            DumpMode::Layout,
+        ]
+        .iter()
+        .copied()
+        .find(|mode| dump_modes.contains(mode));
+        Ok(parsed)
+    }
+
+    /// Overrides the settings of `config` with the flags which are given.
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(width) = self.width {
+            config.viewport.width = width;
+        }
+        if let Some(height) = self.height {
+            config.viewport.height = height;
+        }
+        if let Some(color_scheme) = self.color_scheme {
+            config.viewport.color_scheme = color_scheme;
+        }
+        if let Some(file) = &self.bookmarks_file {
+            config.bookmarks_file = file.clone();
+        }
+        if self.no_refresh {
+            config.refresh = false;
+        }
+        if let Some(user_agent) = &self.user_agent {
+            config.user_agent = Some(user_agent.clone());
+        }
+        if let Some(proxy) = &self.proxy {
+            config.proxy = Some(proxy.clone());
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Bookmarks of pages, saved in a text file so that they're kept across runs. Each line of the
+//! file is a URL and a title separated by a tab.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[allow(unused_imports)]
+use liumlib::*;
+
+/// The file where bookmarks are saved unless `--bookmarks` is given.
+pub const DEFAULT_FILE: &str = "bookmarks.txt";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bookmark {
+    pub url: String,
+    /// The title of the document, or an empty string if it has none.
+    pub title: String,
+}
+
+/// The bookmarks in the order they were added.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bookmarks {
+    entries: Vec<Bookmark>,
+}
+
+#[allow(dead_code)]
+impl Bookmarks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses the content of a bookmarks file. Empty lines are skipped.
+    pub fn parse(text: &str) -> Self {
+        let entries = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut parts = line.splitn(2, '\t');
+                Bookmark {
+                    url: String::from(parts.next().unwrap_or("").trim()),
+                    title: String::from(parts.next().unwrap_or("").trim()),
+                }
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Returns the content of a bookmarks file which `parse()` reads back.
+    pub fn to_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|b| format!("{}\t{}\n", b.url, b.title))
+            .collect()
+    }
+
+    /// Reads the bookmarks from `filename`. There are no bookmarks if the file can't be read,
+    /// e.g. it's not created yet.
+    pub fn load(filename: &str) -> Self {
+        match read_file(filename) {
+            Some(data) => Self::parse(&String::from_utf8_lossy(&data)),
+            None => Self::new(),
+        }
+    }
+
+    /// Writes the bookmarks to `filename`. Returns None if it can't be written.
+    pub fn save(&self, filename: &str) -> Option<()> {
+        write_file(filename, self.to_text().as_bytes())
+    }
+
+    pub fn entries(&self) -> &Vec<Bookmark> {
+        &self.entries
+    }
+
+    /// Adds a bookmark of `url` titled `title`. Returns false if `url` is already bookmarked, in
+    /// which case its title is updated.
+    pub fn add(&mut self, url: &str, title: &str) -> bool {
+        // A title can't span lines or contain the separator in the file.
+        let title: String = title
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .collect();
+        if let Some(bookmark) = self.entries.iter_mut().find(|b| b.url == url) {
+            bookmark.title = title;
+            return false;
+        }
+        self.entries.push(Bookmark {
+            url: String::from(url),
+            title,
+        });
+        true
+    }
+}
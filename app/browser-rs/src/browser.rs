@@ -0,0 +1,536 @@
+//! The browser, which keeps the state shared by all the pages, e.g. the settings, and navigates
+//! its tabs. The window and its keys are handled by `run_window()` in window.rs, which asks the
+//! browser to load pages and paints the active tab.
+
+pub mod window;
+
+use crate::config::Config;
+use crate::error_page::{http_error_page, network_error_page};
+use crate::form::FormBody;
+use crate::http::{HttpResponse, Method};
+use crate::image::loader::load_images;
+use crate::js::dialog::DialogHandler;
+use crate::js::engine::{Engine, ScriptFetcher};
+use crate::net::{get, get_bytes, new_request, send, store_cookies, RequestOptions};
+use crate::page::Page;
+use crate::parser::dom::Document;
+use crate::parser::{parse, parse_with_engine, render, render_with_timing};
+use crate::refresh::{Refresh, ScheduledRefresh, MAX_HOPS};
+use crate::style::computed::Viewport;
+use crate::style::loader::load_style_sheets;
+use crate::tab::{Tab, Tabs};
+use crate::timing::{nanos_since, Stage, Timing};
+use crate::url::ParsedUrl;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use core::fmt;
+use liumlib::time::Instant;
+use liumlib::*;
+
+/// The redirects followed for a request at most, so that a loop of redirects ends.
+const MAX_REDIRECTS: usize = 20;
+
+/// The settings and the open tabs.
+#[derive(Debug)]
+pub struct Browser {
+    config: Config,
+    options: RequestOptions,
+    /// True if the time taken by each stage of loading a page is reported.
+    show_timing: bool,
+    tabs: Tabs,
+    dialogs: DialogSlot,
+}
+
+/// Shows the dialogs of scripts, e.g. `alert()`, which are canceled at once if it's None.
+#[derive(Clone, Default)]
+struct DialogSlot(Option<DialogHandler>);
+
+impl fmt::Debug for DialogSlot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = if self.0.is_some() {
+            "shown"
+        } else {
+            "canceled"
+        };
+        write!(f, "Dialogs({})", state)
+    }
+}
+
+#[allow(dead_code)]
+impl Browser {
+    /// Creates a browser with `config`, which sends requests with `options`. It has a blank tab
+    /// at the homepage until a page is loaded in it.
+    pub fn new(config: Config, options: RequestOptions, show_timing: bool) -> Self {
+        let blank = Page::new(parse(String::new()), config.viewport);
+        let tab = Tab::new(blank, ParsedUrl::new(config.homepage.clone()));
+        Self {
+            config,
+            options,
+            show_timing,
+            tabs: Tabs::new(tab),
+            dialogs: DialogSlot::default(),
+        }
+    }
+
+    /// Makes the scripts of the pages loaded from now on show their dialogs with `handler`.
+    pub fn set_dialog_handler(&mut self, handler: DialogHandler) {
+        self.dialogs = DialogSlot(Some(handler));
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn options(&self) -> &RequestOptions {
+        &self.options
+    }
+
+    /// Returns the viewport which every page is rendered in.
+    pub fn viewport(&self) -> Viewport {
+        self.config.viewport
+    }
+
+    pub fn tabs(&self) -> &Tabs {
+        &self.tabs
+    }
+
+    pub fn tabs_mut(&mut self) -> &mut Tabs {
+        &mut self.tabs
+    }
+
+    pub fn active_tab(&self) -> &Tab {
+        self.tabs.active()
+    }
+
+    pub fn active_tab_mut(&mut self) -> &mut Tab {
+        self.tabs.active_mut()
+    }
+
+    /// Requests `url` following redirects. Returns the URL of the last response and the
+    /// response as it's received, or an error message.
+    pub fn fetch_document(&self, url: &ParsedUrl) -> Result<(ParsedUrl, Vec<u8>), String> {
+        fetch_document(url, None, &self.options)
+    }
+
+    /// Parses `response` for the document at `url` like `navigate()`, without rendering it.
+    pub fn load_document(&self, url: &ParsedUrl, response: &HttpResponse) -> Document {
+        load_document(
+            url,
+            response,
+            &self.options,
+            &self.dialogs.0,
+            &mut Timing::new(),
+            |_| {},
+        )
+    }
+
+    /// Shows `document` loaded from `url` in the active tab, e.g. HTML given without fetching
+    /// it. The time taken by rendering it is added to `timing`, which may have the ones of
+    /// the earlier stages, and they're reported if the browser is asked to.
+    pub fn show(&mut self, url: ParsedUrl, document: Document, mut timing: Timing) {
+        let refresh = Refresh::find(None, &document);
+        let page = render_with_timing(document, self.viewport(), &mut timing);
+        let tab = self.tabs.active_mut();
+        tab.page = page;
+        if let Some(fragment) = url.fragment() {
+            tab.page.scroll_to_fragment(fragment);
+        }
+        tab.status.start(&url.to_string());
+        tab.status.finish(tab.page.document().title());
+        if self.show_timing {
+            report_timing(&url, &timing, tab);
+        }
+        tab.refresh = match tab.page.document().take_navigation() {
+            Some(next) => schedule_navigation(next, 0),
+            None => schedule(refresh, &url, 0, self.config.refresh),
+        };
+        tab.url = url;
+    }
+
+    /// Shows `document` loaded from `url` in the active tab like `show()` if it's given, or loads
+    /// `url` in it like `follow()` otherwise.
+    pub fn show_or_follow(&mut self, url: ParsedUrl, document: Option<Document>, timing: Timing) {
+        match document {
+            Some(document) => self.show(url, document, timing),
+            None => {
+                self.follow(url, None, |_| {});
+            }
+        }
+    }
+
+    /// Replaces the page of the active tab with the document at `url`, or with an error page if
+    /// it can't be loaded. The document is requested by POST with `body` if it's given. The
+    /// status bar of the tab follows the loading, and `paint` shows each step of it. Returns true
+    /// if the document is loaded.
+    pub fn follow<P: FnMut(&mut Tab)>(
+        &mut self,
+        url: ParsedUrl,
+        body: Option<FormBody>,
+        paint: P,
+    ) -> bool {
+        self.load(url, body, 0, paint)
+    }
+
+    /// Loads `url` in the active tab like `follow()`, where it's the `hops`-th refresh in a row.
+    fn load<P: FnMut(&mut Tab)>(
+        &mut self,
+        url: ParsedUrl,
+        body: Option<FormBody>,
+        hops: usize,
+        mut paint: P,
+    ) -> bool {
+        let viewport = self.viewport();
+        let options = &self.options;
+        let dialogs = &self.dialogs.0;
+        let tab = self.tabs.active_mut();
+        tab.refresh = None;
+        tab.status.start(&url.to_string());
+        paint(tab);
+        let mut timing = Timing::new();
+        let next_page = navigate(
+            &url,
+            body.as_ref(),
+            viewport,
+            options,
+            dialogs,
+            &mut timing,
+            |fetched| {
+                tab.status.fetched(fetched);
+                paint(tab);
+            },
+        );
+        let loaded = match next_page {
+            Ok(loaded) => {
+                // The URL which the request is redirected to is shown.
+                let url = loaded.url;
+                tab.page = loaded.page;
+                if let Some(fragment) = url.fragment() {
+                    tab.page.scroll_to_fragment(fragment);
+                }
+                tab.status.start(&url.to_string());
+                tab.status.finish(tab.page.document().title());
+                if self.show_timing {
+                    report_timing(&url, &timing, tab);
+                }
+                tab.refresh = match tab.page.document().take_navigation() {
+                    Some(next) => schedule_navigation(next, hops),
+                    None => schedule(loaded.refresh, &url, hops, self.config.refresh),
+                };
+                tab.url = url;
+                true
+            }
+            Err(e) => {
+                warn!("failed to load {}: {}", url, e);
+                tab.page = render(parse(network_error_page(&url, &e)), viewport);
+                tab.url = url;
+                tab.status.fail();
+                false
+            }
+        };
+        paint(tab);
+        loaded
+    }
+
+    /// Loads the page of the active tab again like `follow()`, asking caches to revalidate it if
+    /// `bypass_cache` is true. The scroll position is restored as far as the new document is
+    /// long. Returns true if the page is loaded and scrolled.
+    pub fn reload<P: FnMut(&mut Tab)>(&mut self, bypass_cache: bool, paint: P) -> bool {
+        let url = self.tabs.active().url.clone();
+        let scroll_y = self.tabs.active().page.scroll_y();
+        let no_cache = self.options.no_cache;
+        self.options.no_cache = no_cache || bypass_cache;
+        let loaded = self.follow(url, None, paint);
+        self.options.no_cache = no_cache;
+        loaded && self.tabs.active_mut().page.scroll_to(scroll_y)
+    }
+
+    /// Follows the refresh of the active tab if it's due like `follow()`. Returns true if it's
+    /// followed.
+    pub fn refresh_if_due<P: FnMut(&mut Tab)>(&mut self, paint: P) -> bool {
+        let refresh = match &self.tabs.active().refresh {
+            Some(refresh) if refresh.is_due() => refresh.clone(),
+            _ => return false,
+        };
+        info!("refreshing to {}", refresh.url);
+        self.load(refresh.url, None, refresh.hops + 1, paint);
+        true
+    }
+
+    /// Returns the milliseconds until the refresh or a task of the scripts of the active tab is
+    /// due, which is how long the browser can wait for a key, or None if nothing is waiting.
+    pub fn until_next_task(&self) -> Option<u64> {
+        let tab = self.tabs.active();
+        let refresh = tab.refresh.as_ref().and_then(|r| r.until_due());
+        match (refresh, tab.page.until_next_task()) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Runs the tasks of the scripts of the active tab which are due, e.g. timers, and paints it
+    /// with `paint` if they ran. A navigation which they ask for is followed. The pages in the
+    /// other tabs wait until they're selected.
+    pub fn run_due_tasks<P: FnMut(&mut Tab)>(&mut self, mut paint: P) -> bool {
+        let tab = self.tabs.active_mut();
+        if !tab.page.run_due_tasks() {
+            return false;
+        }
+        paint(tab);
+        self.follow_navigation(paint);
+        true
+    }
+
+    /// Follows the navigation which the scripts of the active tab asked for, e.g. by setting
+    /// `location.href` in a listener, like `follow()`. Only the fragment changes if that's the
+    /// only difference. Returns true if there's one.
+    pub fn follow_navigation<P: FnMut(&mut Tab)>(&mut self, mut paint: P) -> bool {
+        let url = match self.tabs.active().page.document().take_navigation() {
+            Some(url) => url,
+            None => return false,
+        };
+        if self.go_to_fragment(&url) {
+            paint(self.tabs.active_mut());
+        } else {
+            self.follow(url, None, paint);
+        }
+        true
+    }
+
+    /// Scrolls the page of the active tab to the fragment of `url` without loading it again if
+    /// `url` is the URL of the page with another fragment. Returns false if `url` needs loading.
+    pub fn go_to_fragment(&mut self, url: &ParsedUrl) -> bool {
+        let tab = self.tabs.active_mut();
+        let fragment = match url.fragment() {
+            Some(fragment) if url.equals_except_fragment(&tab.url) => fragment,
+            _ => return false,
+        };
+        tab.page.scroll_to_fragment(fragment);
+        tab.url = url.clone();
+        tab.status.start(&url.to_string());
+        tab.status.finish(tab.page.document().title());
+        true
+    }
+
+    /// Saves the cookies which outlast the browser to the file in the config.
+    pub fn save_cookies(&self) {
+        if let Some(cookies) = &self.options.cookies {
+            if cookies.save(&self.config.cookies_file).is_none() {
+                error!("failed to write {}", self.config.cookies_file);
+            }
+        }
+    }
+
+    /// Opens a tab next to the active one and loads `url` in it like `follow()`.
+    pub fn open_tab<P: FnMut(&mut Tab)>(&mut self, url: ParsedUrl, paint: P) {
+        let blank = Page::new(parse(String::new()), self.viewport());
+        self.tabs.open(Tab::new(blank, url.clone()));
+        self.follow(url, None, paint);
+    }
+}
+
+/// Returns the body of the resource at `url`, or None if it can't be fetched.
+fn fetch(url: &ParsedUrl, options: &RequestOptions) -> Option<String> {
+    let response = HttpResponse::new(get(url, options).ok()?);
+    store_cookies(url, &response, options);
+    if (200..300).contains(&response.status_code()) {
+        Some(response.body().to_string())
+    } else {
+        None
+    }
+}
+
+/// Returns the body of the resource at `url` as bytes, or None if it can't be fetched.
+fn fetch_bytes(url: &ParsedUrl, options: &RequestOptions) -> Option<Vec<u8>> {
+    let response = HttpResponse::from_bytes(&get_bytes(url, options).ok()?);
+    store_cookies(url, &response, options);
+    if (200..300).contains(&response.status_code()) {
+        Some(response.body_bytes().to_vec())
+    } else {
+        None
+    }
+}
+
+/// Sends a request for `url`, by POST with `body` if it's given, and follows the redirects of
+/// the responses. Returns the URL of the last response and the response as it's received, or an
+/// error message.
+fn fetch_document(
+    url: &ParsedUrl,
+    body: Option<&FormBody>,
+    options: &RequestOptions,
+) -> Result<(ParsedUrl, Vec<u8>), String> {
+    let mut url = url.clone();
+    let mut body = body;
+    for _ in 0..=MAX_REDIRECTS {
+        let raw_response = match body {
+            Some(body) => {
+                let mut request = new_request(Method::Post, &url, options);
+                request.set_body(&body.content_type, body.data.clone());
+                send(&request, &url, options)?
+            }
+            None => get_bytes(&url, options)?,
+        };
+        let response = HttpResponse::from_bytes(&raw_response);
+        // The cookies set by a redirect are sent with the request it redirects to.
+        store_cookies(&url, &response, options);
+        let next = match response.redirect_url(&url)? {
+            Some(next) => next,
+            None => return Ok((url, raw_response)),
+        };
+        // Only 307 and 308 keep the method and the body. The others are followed by GET like
+        // the other browsers do.
+        if !matches!(response.status_code(), 307 | 308) {
+            body = None;
+        }
+        url = next;
+        info!("redirected to {}", url);
+    }
+    Err(format!("too many redirects to {}", url))
+}
+
+/// Parses `response` for the document at `url` with its scripts, and loads its style sheets and
+/// images. An HTTP error without a body is shown as an error page. The scripts show their
+/// dialogs with `dialogs`. The time taken by fetching the resources and parsing is added to
+/// `timing`. `progress` is called with the number of the resources fetched so far after each of
+/// them, or after parsing for the scripts.
+fn load_document<F: FnMut(usize)>(
+    url: &ParsedUrl,
+    response: &HttpResponse,
+    options: &RequestOptions,
+    dialogs: &Option<DialogHandler>,
+    timing: &mut Timing,
+    progress: F,
+) -> Document {
+    // The loaders take shared closures, so the counts and the callback are kept in cells.
+    let fetched = Cell::new(0);
+    let fetch_time = Cell::new(0);
+    let progress = RefCell::new(progress);
+    // Called after a resource is fetched from `start`, before it's parsed or decoded.
+    let count = |start: Instant| {
+        fetch_time.set(fetch_time.get() + nanos_since(start));
+        fetched.set(fetched.get() + 1);
+        (progress.borrow_mut())(fetched.get());
+    };
+    let source = http_error_page(url, response).unwrap_or_else(|| response.body().to_string());
+    // The scripts are fetched while the parser waits for them, so they're counted after parsing.
+    let scripts = Rc::new(Cell::new(0));
+    let fetch_script: ScriptFetcher = {
+        let (url, options, scripts) = (url.clone(), options.clone(), scripts.clone());
+        Rc::new(move |src| {
+            scripts.set(scripts.get() + 1);
+            fetch(&url.join(src)?, &options)
+        })
+    };
+    let mut engine = Engine::with_url(&url.to_string());
+    engine.set_fetcher(fetch_script);
+    if let Some(dialogs) = dialogs {
+        engine.set_dialog_handler(dialogs.clone());
+    }
+    if let Some(cookies) = &options.cookies {
+        engine.set_cookie_jar(cookies.clone());
+    }
+    let mut document = parse_with_engine(source, timing, engine);
+    if scripts.get() > 0 {
+        fetched.set(fetched.get() + scripts.get());
+        (progress.borrow_mut())(fetched.get());
+    }
+    load_style_sheets(&mut document, |href| {
+        let start = Instant::now();
+        let body = fetch(&url.join(href)?, options);
+        count(start);
+        body
+    });
+    load_images(&mut document, |src| {
+        let start = Instant::now();
+        let body = fetch_bytes(&url.join(src)?, options);
+        count(start);
+        body
+    });
+    timing.add(Stage::Fetch, fetch_time.get());
+    document
+}
+
+/// A document fetched and rendered by `navigate()`.
+struct Loaded {
+    /// The URL which the request is redirected to at last.
+    url: ParsedUrl,
+    page: Page,
+    /// The refresh which the document asks for.
+    refresh: Option<Refresh>,
+}
+
+/// Fetches the document at `url` with its resources and renders it in `viewport`, or returns
+/// the error message if it can't be fetched. The document is requested by POST with `body` if
+/// it's given, e.g. a submitted form. The time taken by each stage is added to `timing`.
+/// `dialogs` and `progress` are used like `load_document()`.
+fn navigate<F: FnMut(usize)>(
+    url: &ParsedUrl,
+    body: Option<&FormBody>,
+    viewport: Viewport,
+    options: &RequestOptions,
+    dialogs: &Option<DialogHandler>,
+    timing: &mut Timing,
+    progress: F,
+) -> Result<Loaded, String> {
+    let (url, raw_response) =
+        timing.measure(Stage::Fetch, || fetch_document(url, body, options))?;
+    let response = HttpResponse::from_bytes(&raw_response);
+    let document = load_document(&url, &response, options, dialogs, timing, progress);
+    let refresh = Refresh::find(Some(&response), &document);
+    Ok(Loaded {
+        page: render_with_timing(document, viewport, timing),
+        url,
+        refresh,
+    })
+}
+
+/// Returns `refresh` of the page at `url` scheduled if refreshes are `enabled`, where it's the
+/// `hops`-th refresh in a row. A refresh after too many in a row isn't scheduled.
+fn schedule(
+    refresh: Option<Refresh>,
+    url: &ParsedUrl,
+    hops: usize,
+    enabled: bool,
+) -> Option<ScheduledRefresh> {
+    let refresh = refresh?;
+    let scheduled = match ScheduledRefresh::new(&refresh, url, hops) {
+        Some(scheduled) => scheduled,
+        None => {
+            warn!("invalid refresh to {}", refresh.url.unwrap_or_default());
+            return None;
+        }
+    };
+    if !enabled {
+        info!("ignored the refresh to {}", scheduled.url);
+        return None;
+    }
+    if hops >= MAX_HOPS {
+        warn!("stopped refreshing after {} times", hops);
+        return None;
+    }
+    Some(scheduled)
+}
+
+/// Schedules the navigation to `url` which the scripts of a page asked for while it's loaded,
+/// which is followed at once like a refresh without a delay. It's the `hops`-th in a row, and
+/// isn't scheduled after too many in a row.
+fn schedule_navigation(url: ParsedUrl, hops: usize) -> Option<ScheduledRefresh> {
+    if hops >= MAX_HOPS {
+        warn!("stopped navigating after {} times", hops);
+        return None;
+    }
+    Some(ScheduledRefresh {
+        url,
+        due: Some(Instant::now()),
+        hops,
+    })
+}
+
+/// Prints the time taken by each stage of loading `url`, and shows their total in the status
+/// bar of `tab`.
+fn report_timing(url: &ParsedUrl, timing: &Timing, tab: &mut Tab) {
+    println!("timing {}: {}", url, timing.summary());
+    tab.status.set_load_time(timing.total());
+}
@@ -0,0 +1,308 @@
+//! The window of the browser, which shows the active tab between the tab strip and the status
+//! bar, and the loop handling its keys and clicks until the browser quits.
+
+use crate::bookmarks::Bookmarks;
+use crate::browser::Browser;
+use crate::download::{download, file_name};
+use crate::form::FormMethod;
+use crate::keyboard::{edit_with_key, scroll_with_key, Input, InputReader, Key};
+use crate::paint::double_buffer::DoubleBuffer;
+use crate::paint::frame_timer::FrameTimer;
+use crate::parser::dom::Document;
+use crate::tab::Tab;
+use crate::timing::{format_nanos, Timing};
+use crate::ui::address_bar::AddressBar;
+use crate::ui::bookmark_menu::BookmarkMenu;
+use crate::ui::dialog::Dialog;
+use crate::ui::status_bar::StatusBar;
+use crate::ui::tab_strip::TabStrip;
+use crate::ui::{Action, Popup};
+use crate::url::ParsedUrl;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use core::cell::RefCell;
+use liumlib::surface::Window;
+use liumlib::*;
+
+#[allow(dead_code)]
+impl Browser {
+    /// Opens a window showing `urls` in tabs, the first of which is `document` given instead of
+    /// fetching it if it's given, and handles the keys and clicks until it's quit. The time taken
+    /// by each frame is printed if `frame_time` is true. Returns an error message if the window
+    /// can't be created.
+    pub fn run_window(
+        &mut self,
+        urls: &[ParsedUrl],
+        document: Option<Document>,
+        timing: Timing,
+        frame_time: bool,
+    ) -> Result<(), String> {
+        let viewport = self.viewport();
+        // The tab strip is above the page, and the status bar is below it.
+        let page_top = TabStrip::height();
+        let window_height = page_top + viewport.height + StatusBar::height();
+        let window = Window::create(viewport.width as usize, window_height as usize)
+            .map_err(|e| format!("failed to create a window: {:?}", e))?;
+        let buffer = Rc::new(RefCell::new(DoubleBuffer::with_page_top(
+            window,
+            page_top as i64,
+        )));
+        let input = Rc::new(RefCell::new(InputReader::new()));
+        // A dialog of a script is shown over the window until it's answered, and the script
+        // waits for it meanwhile.
+        let dialog_buffer = buffer.clone();
+        let dialog_input = input.clone();
+        self.set_dialog_handler(Rc::new(move |kind, message| {
+            let dialog = Dialog::new(kind, message);
+            let mut buffer = dialog_buffer.borrow_mut();
+            buffer.paint_over_front(&dialog.display_list(viewport.width, window_height));
+            let answer = loop {
+                let next = dialog_input.borrow_mut().read();
+                if let Some(answer) = match next {
+                    Some(Input::Key(key)) => dialog.handle_key(key),
+                    _ => None,
+                } {
+                    break answer;
+                }
+            };
+            buffer.restore_front();
+            answer
+        }));
+        self.show_or_follow(urls[0].clone(), document, timing);
+
+        let mut timer = FrameTimer::new();
+        let mut paint_frame = |tab: &mut Tab, popup: &Option<Popup>, strip: &TabStrip| {
+            let mut overlay = strip.display_list(viewport.width);
+            overlay.extend(
+                tab.status
+                    .display_list(viewport.width, page_top + viewport.height),
+            );
+            if let Some(popup) = popup {
+                overlay.extend(popup.display_list(viewport.width));
+            }
+            timer.start();
+            let mut buffer = buffer.borrow_mut();
+            let regions = buffer.repaint_with_overlay(&mut tab.page, &overlay);
+            let nanos = timer.finish();
+            if frame_time {
+                println!(
+                    "frame {}: {}, {} regions",
+                    timer.frames(),
+                    format_nanos(nanos),
+                    regions
+                );
+            }
+        };
+        let mut popup = None;
+        // The address bar opens the URL typed in it in a new tab if it's opened to do so.
+        let mut new_tab = false;
+        for url in &urls[1..] {
+            self.open_tab(url.clone(), |_| {});
+        }
+        self.tabs_mut().select(0);
+        let strip = self.tabs().strip();
+        paint_frame(self.active_tab_mut(), &popup, &strip);
+        let bookmarks_file = self.config().bookmarks_file.clone();
+        let mut bookmarks = Bookmarks::load(&bookmarks_file);
+
+        loop {
+            // A refresh which the page asked for is followed once it's due, and so are the timers
+            // of its scripts. A key is waited for until the next of them is due.
+            let mut until_next_task = None;
+            if popup.is_none() {
+                let strip = self.tabs().strip();
+                if self.refresh_if_due(|tab| paint_frame(tab, &None, &strip)) {
+                    continue;
+                }
+                self.run_due_tasks(|tab| paint_frame(tab, &None, &strip));
+                until_next_task = self.until_next_task();
+            }
+            if !input.borrow().wait(until_next_task) {
+                continue;
+            }
+
+            let next = input.borrow_mut().read();
+            let key = match next {
+                Some(Input::Key(key)) => key,
+                // A click on the page focuses what's there, and activates it like Enter unless
+                // it's a text control, which takes the keys typed next.
+                Some(Input::Click { x, y }) => {
+                    if popup.is_some() || y < page_top || y >= page_top + viewport.height {
+                        continue;
+                    }
+                    let page = &mut self.active_tab_mut().page;
+                    let target = page.focusable_at(x, y - page_top);
+                    let changed = page.focus(target);
+                    match target {
+                        Some(element) if page.focused_text_control() != Some(element) => Key::Enter,
+                        _ => {
+                            if changed {
+                                let strip = self.tabs().strip();
+                                paint_frame(self.active_tab_mut(), &popup, &strip);
+                            }
+                            continue;
+                        }
+                    }
+                }
+                None => continue,
+            };
+            // The labels don't change until a key is handled, so the strip is painted as it is
+            // while a page is loading.
+            let strip = self.tabs().strip();
+
+            // A popup takes the keys while it's open.
+            if let Some(open) = &mut popup {
+                match open.handle_key(key) {
+                    Some(Action::Edit) => {}
+                    Some(Action::Navigate(text)) => {
+                        popup = None;
+                        let paint = |tab: &mut Tab| paint_frame(tab, &None, &strip);
+                        match ParsedUrl::parse(&text) {
+                            Some(next_url) if new_tab => self.open_tab(next_url, paint),
+                            Some(next_url) if self.go_to_fragment(&next_url) => {}
+                            Some(next_url) => {
+                                self.follow(next_url, None, paint);
+                            }
+                            None => warn!("invalid URL: {}", text),
+                        }
+                    }
+                    Some(Action::Cancel) => popup = None,
+                    None => continue,
+                }
+                if popup.is_none() {
+                    new_tab = false;
+                }
+                let strip = self.tabs().strip();
+                paint_frame(self.active_tab_mut(), &popup, &strip);
+                continue;
+            }
+
+            // A focused text control takes the keys which edit text.
+            if let Some(changed) = edit_with_key(&mut self.active_tab_mut().page, key) {
+                if changed {
+                    paint_frame(self.active_tab_mut(), &popup, &strip);
+                }
+                continue;
+            }
+
+            let changed = match key {
+                Key::Char(b'q') => break,
+                Key::Char(b'g') | Key::Char(0x0c) => {
+                    let url = self.active_tab().url.to_string();
+                    popup = Some(Popup::AddressBar(AddressBar::new(&url)));
+                    true
+                }
+                Key::Char(b't') => {
+                    popup = Some(Popup::AddressBar(AddressBar::new("")));
+                    new_tab = true;
+                    true
+                }
+                // Closing the last tab quits.
+                Key::Char(b'w') => {
+                    if !self.tabs_mut().close_active() {
+                        break;
+                    }
+                    true
+                }
+                Key::Char(c @ b']') | Key::Char(c @ b'[') => self.tabs_mut().select_next(c == b'['),
+                Key::Char(c @ b'1'..=b'9') => self.tabs_mut().select((c - b'1') as usize),
+                // The target of the focused link is saved without following it, so it may be
+                // anything, e.g. an image.
+                Key::Char(b's') => {
+                    let tab = self.active_tab();
+                    let url = match tab.page.focused_href() {
+                        Some(href) => tab.url.join(&href).ok_or(href),
+                        None => Ok(tab.url.clone()),
+                    };
+                    match url {
+                        Ok(url) => {
+                            let name = file_name(&url);
+                            match download(&url, &name, self.options(), |size| {
+                                debug!("received {} bytes of {}", size, url)
+                            }) {
+                                Ok(size) => info!("saved {} bytes to {}", size, name),
+                                Err(e) => error!("{}", e),
+                            }
+                        }
+                        Err(href) => warn!("invalid URL: {}", href),
+                    }
+                    false
+                }
+                Key::Char(b'b') => {
+                    let tab = self.active_tab();
+                    let url = tab.url.to_string();
+                    bookmarks.add(&url, &tab.page.document().title());
+                    match bookmarks.save(&bookmarks_file) {
+                        Some(()) => info!("bookmarked {}", url),
+                        None => error!("failed to write {}", bookmarks_file),
+                    }
+                    false
+                }
+                Key::Char(b'B') => {
+                    popup = Some(Popup::BookmarkMenu(BookmarkMenu::new(&bookmarks)));
+                    true
+                }
+                Key::Char(c @ b'r') | Key::Char(c @ b'R') => {
+                    self.reload(c == b'R', |tab| paint_frame(tab, &None, &strip))
+                }
+                Key::Tab | Key::BackTab => {
+                    let page = &mut self.active_tab_mut().page;
+                    page.focus_next(key == Key::BackTab)
+                }
+                Key::Enter => {
+                    // The activation clicks the focused element first, and a listener may cancel
+                    // following it.
+                    let page = &mut self.active_tab_mut().page;
+                    let clicked = match page.focused() {
+                        Some(focused) => page.click(focused),
+                        None => true,
+                    };
+                    // A listener may navigate instead, e.g. by setting `location.href`.
+                    let navigated = self.follow_navigation(|tab| paint_frame(tab, &None, &strip));
+                    let tab = self.active_tab();
+                    let next = match (tab.page.focused_href(), tab.page.focused_submission()) {
+                        _ if !clicked || navigated => None,
+                        (Some(href), _) => Some((tab.url.join(&href).ok_or(href), None)),
+                        (None, Some(submission)) => {
+                            let action = submission.action.clone();
+                            match submission.method {
+                                FormMethod::Get => {
+                                    Some((submission.url(&tab.url).ok_or(action), None))
+                                }
+                                FormMethod::Post => Some((
+                                    submission.action_url(&tab.url).ok_or(action),
+                                    Some(submission.body()),
+                                )),
+                            }
+                        }
+                        (None, None) => None,
+                    };
+                    let next = match next {
+                        Some((Ok(next_url), body)) => Some((next_url, body)),
+                        Some((Err(href), _)) => {
+                            warn!("invalid URL: {}", href);
+                            None
+                        }
+                        None => None,
+                    };
+                    match next {
+                        Some((next_url, None)) if self.go_to_fragment(&next_url) => true,
+                        Some((next_url, body)) => {
+                            self.follow(next_url, body, |tab| paint_frame(tab, &None, &strip));
+                            false
+                        }
+                        // The listeners of the click may have changed the page.
+                        None => true,
+                    }
+                }
+                _ => scroll_with_key(&mut self.active_tab_mut().page, key),
+            };
+            if changed {
+                let strip = self.tabs().strip();
+                paint_frame(self.active_tab_mut(), &popup, &strip);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+//! The settings of the browser, read from a text file at startup. Each line of the file is a key
+//! and a value separated by `=`, e.g. `homepage = http://127.0.0.1:8888/`, and lines starting
+//! with `#` are comments. The environment variables override the settings, e.g. http_proxy, and
+//! the flags override both.
+
+use crate::style::computed::{ColorScheme, Viewport};
+use crate::url::ParsedUrl;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[allow(unused_imports)]
+use liumlib::*;
+
+/// The file where the settings are read from unless `--config` is given.
+pub const DEFAULT_FILE: &str = "browser.conf";
+
+/// The page shown when no URL is given.
+pub const DEFAULT_HOMEPAGE: &str = "http://127.0.0.1:8888/index.html";
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// The URL shown when no URL is given.
+    pub homepage: String,
+    /// The size of the viewport and the color scheme which pages are shown in.
+    pub viewport: Viewport,
+    /// The value of User-Agent, which isn't sent if it's None.
+    pub user_agent: Option<String>,
+    /// The proxy which requests are sent to, e.g. "10.0.2.2:8080".
+    pub proxy: Option<ParsedUrl>,
+    /// The hosts which requests are sent to directly instead of the proxy, with their subdomains.
+    pub no_proxy: Vec<String>,
+    /// The file to keep bookmarks in.
+    pub bookmarks_file: String,
+    /// The file to keep the cookies which outlast the browser in.
+    pub cookies_file: String,
+    /// True if the refreshes which pages ask for are followed.
+    pub refresh: bool,
+    /// True if the details of loading pages are logged, e.g. the requests sent, unless the levels
+    /// of the log are given by a flag.
+    pub debug: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            homepage: String::from(DEFAULT_HOMEPAGE),
+            viewport: Viewport::default(),
+            user_agent: None,
+            proxy: None,
+            no_proxy: Vec::new(),
+            bookmarks_file: String::from(crate::bookmarks::DEFAULT_FILE),
+            cookies_file: String::from(crate::cookie::DEFAULT_FILE),
+            refresh: true,
+            debug: false,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl Config {
+    /// Parses the content of a config file. The settings which aren't in it are the defaults.
+    /// Returns the config and the messages about the lines which are ignored, e.g. unknown keys
+    /// or invalid values.
+    pub fn parse(text: &str) -> (Self, Vec<String>) {
+        let mut config = Self::default();
+        let mut errors = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => {
+                    errors.push(format!("line {}: expected key = value", i + 1));
+                    continue;
+                }
+            };
+            if let Err(e) = config.set(key, value) {
+                errors.push(format!("line {}: {}", i + 1, e));
+            }
+        }
+        (config, errors)
+    }
+
+    /// Reads the config from `filename` like `parse()`. The defaults are used if the file can't
+    /// be read, e.g. it's not created.
+    pub fn load(filename: &str) -> (Self, Vec<String>) {
+        match read_file(filename) {
+            Some(data) => Self::parse(&String::from_utf8_lossy(&data)),
+            None => (Self::default(), Vec::new()),
+        }
+    }
+
+    /// Applies the environment variables which `var` returns the values of: http_proxy, no_proxy,
+    /// which is a list of hosts separated by commas or `*` for all, and BROWSER_DEBUG, which
+    /// turns on `debug` unless it's empty or 0. The names in upper case are read if the ones in
+    /// lower case aren't set, like curl. Returns the messages about the invalid values, which are
+    /// ignored.
+    pub fn apply_env<'a>(&mut self, var: impl Fn(&str) -> Option<&'a str>) -> Vec<String> {
+        let mut errors = Vec::new();
+        let either = |name: &str| var(name).or_else(|| var(&name.to_ascii_uppercase()));
+        match either("http_proxy") {
+            // An empty value turns off the proxy in the config file.
+            Some("") => self.proxy = None,
+            Some(value) => match ParsedUrl::parse(value) {
+                Some(proxy) => self.proxy = Some(proxy),
+                None => errors.push(format!("invalid http_proxy: {}", value)),
+            },
+            None => {}
+        }
+        if let Some(value) = either("no_proxy") {
+            self.no_proxy = value
+                .split(',')
+                .map(|host| host.trim())
+                .filter(|host| !host.is_empty())
+                .map(String::from)
+                .collect();
+        }
+        if let Some(value) = var("BROWSER_DEBUG") {
+            self.debug = !matches!(value, "" | "0");
+        }
+        errors
+    }
+
+    /// Sets the setting `key` to `value`, or returns an error message if the key is unknown or
+    /// the value is invalid.
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let invalid = || format!("invalid {}: {}", key, value);
+        match key {
+            "homepage" => {
+                ParsedUrl::parse(value).ok_or_else(invalid)?;
+                self.homepage = String::from(value);
+            }
+            "width" => self.viewport.width = parse_size(value).ok_or_else(invalid)?,
+            "height" => self.viewport.height = parse_size(value).ok_or_else(invalid)?,
+            "color_scheme" => {
+                self.viewport.color_scheme = ColorScheme::parse(value).ok_or_else(invalid)?
+            }
+            "user_agent" => self.user_agent = Some(String::from(value)),
+            "proxy" => self.proxy = Some(ParsedUrl::parse(value).ok_or_else(invalid)?),
+            "bookmarks" if !value.is_empty() => self.bookmarks_file = String::from(value),
+            "bookmarks" => return Err(invalid()),
+            "cookies" if !value.is_empty() => self.cookies_file = String::from(value),
+            "cookies" => return Err(invalid()),
+            "refresh" => {
+                self.refresh = match value {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Err(invalid()),
+                }
+            }
+            _ => return Err(format!("unknown key: {}", key)),
+        }
+        Ok(())
+    }
+}
+
+/// Parses the size of the viewport, which must be a positive number of px.
+pub fn parse_size(value: &str) -> Option<f64> {
+    match value.parse::<f64>() {
+        Ok(size) if size > 0.0 => Some(size),
+        _ => None,
+    }
+}
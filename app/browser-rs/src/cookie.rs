@@ -0,0 +1,486 @@
+//! The cookie jar, which stores the cookies set by servers and scripts and sends them back with
+//! the requests to the same site, e.g. the ID of a session. The cookies with an expiry are saved
+//! to a file and loaded the next time the browser runs, and the others are kept only while it
+//! runs.
+//! https://httpwg.org/specs/rfc6265.html
+
+use crate::http::HttpResponse;
+use crate::timing::milliseconds;
+use crate::url::ParsedUrl;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use core::cmp::Reverse;
+use liumlib::time::wall_clock;
+use liumlib::{read_file, write_file};
+
+/// The file where the cookies are saved.
+pub const DEFAULT_FILE: &str = "cookies.txt";
+
+/// The prefix of the domain of an HttpOnly cookie in a cookies file.
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+/// Returns the current time in milliseconds since an arbitrary point.
+pub type Clock = fn() -> u64;
+
+/// The cookies kept at most. The oldest one is evicted when another one is stored.
+const MAX_COOKIES: usize = 300;
+
+/// The time assumed until a response tells it, 2000-01-01T00:00:00Z in seconds since the Unix
+/// epoch. liumOS has no clock of the date, so the time is learned from Date of responses there.
+/// The wall clock is used instead if the system has one.
+const DEFAULT_TIME: i64 = 946_684_800;
+
+/// Where a cookie is set from. HttpOnly cookies can't be set, read or replaced by scripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// Set-Cookie of a response.
+    Http,
+    /// `document.cookie` of a script.
+    Script,
+}
+
+/// https://httpwg.org/specs/rfc6265.html#storage-model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    /// The host without the port, or the domain of the Domain attribute.
+    pub domain: String,
+    /// True if it's sent only to `domain` but not to its subdomains.
+    pub host_only: bool,
+    pub path: String,
+    /// When it expires in seconds since the Unix epoch, or None if it lasts while the browser
+    /// runs.
+    pub expiry: Option<i64>,
+    pub http_only: bool,
+}
+
+/// The cookies of all the sites.
+#[derive(Debug)]
+pub struct CookieJar {
+    /// The cookies in the order they're created, which a replaced cookie keeps.
+    cookies: RefCell<Vec<Cookie>>,
+    clock: Clock,
+    /// The time in seconds since the Unix epoch at a time of `clock`, which is the time the jar
+    /// knows last.
+    anchor: Cell<(i64, u64)>,
+}
+
+impl Default for CookieJar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CookieJar {
+    pub fn new() -> Self {
+        let jar = Self::with_clock(milliseconds);
+        if let Some(time) = wall_clock() {
+            jar.anchor.set((time.as_secs() as i64, milliseconds()));
+        }
+        jar
+    }
+
+    /// Creates a jar which counts the time by `clock` to expire the cookies.
+    pub fn with_clock(clock: Clock) -> Self {
+        Self {
+            cookies: RefCell::new(Vec::new()),
+            clock,
+            anchor: Cell::new((DEFAULT_TIME, clock())),
+        }
+    }
+
+    /// Returns the current time in seconds since the Unix epoch.
+    fn now(&self) -> i64 {
+        let (time, at) = self.anchor.get();
+        time + ((self.clock)().saturating_sub(at) / 1000) as i64
+    }
+
+    /// Stores the cookies which `response` for `url` sets, after learning the time from its
+    /// Date.
+    pub fn store_response(&self, url: &ParsedUrl, response: &HttpResponse) {
+        if let Some(time) = response.header("Date").and_then(|d| parse_date(&d)) {
+            self.anchor.set((time, (self.clock)()));
+        }
+        for header in response.header_values("Set-Cookie") {
+            self.set(url, &header, Source::Http);
+        }
+    }
+
+    /// Stores the cookie which a Set-Cookie header or a script sets for `url`, or removes it if
+    /// it's expired. A cookie which `url` can't set is ignored.
+    /// https://httpwg.org/specs/rfc6265.html#storage-model
+    pub fn set(&self, url: &ParsedUrl, set_cookie: &str, source: Source) {
+        let cookie = match self.parse(url, set_cookie) {
+            Some(cookie) => cookie,
+            None => return,
+        };
+        if cookie.http_only && source == Source::Script {
+            return;
+        }
+        let mut cookies = self.cookies.borrow_mut();
+        let old = cookies.iter().position(|c| {
+            c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path
+        });
+        if let Some(i) = old {
+            if cookies[i].http_only && source == Source::Script {
+                return;
+            }
+        }
+        let expired = matches!(cookie.expiry, Some(expiry) if expiry <= self.now());
+        match old {
+            Some(i) if expired => {
+                cookies.remove(i);
+            }
+            Some(i) => cookies[i] = cookie,
+            None if expired => {}
+            None => {
+                if cookies.len() >= MAX_COOKIES {
+                    cookies.remove(0);
+                }
+                cookies.push(cookie);
+            }
+        }
+    }
+
+    /// Returns the value of Cookie for a request to `url`, or None if no cookie is sent.
+    pub fn header_for(&self, url: &ParsedUrl) -> Option<String> {
+        let value = self.serialize(url, Source::Http);
+        if value.is_empty() {
+            None
+        } else {
+            Some(value)
+        }
+    }
+
+    /// Returns the cookies for `url` which `source` can read, e.g. "a=1; b=2". The ones with
+    /// longer paths come first, and the ones created earlier come first among the others.
+    /// https://httpwg.org/specs/rfc6265.html#cookie
+    pub fn serialize(&self, url: &ParsedUrl, source: Source) -> String {
+        let now = self.now();
+        let mut cookies = self.cookies.borrow_mut();
+        cookies.retain(|c| !matches!(c.expiry, Some(expiry) if expiry <= now));
+        let host = host_of(url);
+        let path = path_of(url);
+        let mut matched: Vec<&Cookie> = cookies
+            .iter()
+            .filter(|c| {
+                let domain_matches = if c.host_only {
+                    host == c.domain
+                } else {
+                    domain_matches(&host, &c.domain)
+                };
+                domain_matches
+                    && path_matches(path, &c.path)
+                    && !(c.http_only && source == Source::Script)
+            })
+            .collect();
+        // The sort is stable, so the order of creation is kept among the same lengths.
+        matched.sort_by_key(|c| Reverse(c.path.len()));
+        let pairs: Vec<String> = matched
+            .iter()
+            .map(|c| format!("{}={}", c.name, c.value))
+            .collect();
+        pairs.join("; ")
+    }
+
+    /// Returns the cookies which outlast the browser, which are the ones with an expiry, in the
+    /// format of cookies.txt of Netscape, which `load_text()` reads back. Each line is the
+    /// domain, whether its subdomains match, the path, whether it's Secure, the expiry, the name
+    /// and the value separated by tabs.
+    pub fn to_text(&self) -> String {
+        let now = self.now();
+        let mut text = String::from("# Netscape HTTP Cookie File\n");
+        for cookie in self.cookies.borrow().iter() {
+            let expiry = match cookie.expiry {
+                Some(expiry) if expiry > now => expiry,
+                _ => continue,
+            };
+            let prefix = if cookie.http_only {
+                HTTP_ONLY_PREFIX
+            } else {
+                ""
+            };
+            let dot = if cookie.host_only { "" } else { "." };
+            let subdomains = if cookie.host_only { "FALSE" } else { "TRUE" };
+            text.push_str(&format!(
+                "{}{}{}\t{}\t{}\tFALSE\t{}\t{}\t{}\n",
+                prefix,
+                dot,
+                cookie.domain,
+                subdomains,
+                cookie.path,
+                expiry,
+                cookie.name,
+                cookie.value
+            ));
+        }
+        text
+    }
+
+    /// Stores the cookies in `text`, which `to_text()` returns, unless the jar is full. The
+    /// comments, the lines which can't be parsed and the expired cookies are ignored.
+    pub fn load_text(&self, text: &str) {
+        let now = self.now();
+        let mut cookies = self.cookies.borrow_mut();
+        for line in text.lines() {
+            let (http_only, line) = match line.strip_prefix(HTTP_ONLY_PREFIX) {
+                Some(line) => (true, line),
+                None if line.starts_with('#') => continue,
+                None => (false, line),
+            };
+            let fields: Vec<&str> = line.split('\t').collect();
+            let (domain, subdomains, path, expiry, name, value) = match fields.as_slice() {
+                [domain, subdomains, path, _, expiry, name, value] => {
+                    (*domain, *subdomains, *path, *expiry, *name, *value)
+                }
+                _ => continue,
+            };
+            let expiry = match expiry.parse::<i64>() {
+                Ok(expiry) if expiry > now => expiry,
+                _ => continue,
+            };
+            let domain = domain.trim_start_matches('.');
+            if domain.is_empty() || name.is_empty() || !path.starts_with('/') {
+                continue;
+            }
+            let cookie = Cookie {
+                name: String::from(name),
+                value: String::from(value),
+                domain: domain.to_ascii_lowercase(),
+                host_only: subdomains != "TRUE",
+                path: String::from(path),
+                expiry: Some(expiry),
+                http_only,
+            };
+            // A cookie which is already in the jar is newer than the saved one.
+            let stored = cookies.iter().any(|c| {
+                c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path
+            });
+            if !stored && cookies.len() < MAX_COOKIES {
+                cookies.push(cookie);
+            }
+        }
+    }
+
+    /// Loads the cookies saved in `filename`. Nothing is loaded if the file can't be read, e.g.
+    /// it's not created yet.
+    pub fn load(&self, filename: &str) {
+        if let Some(data) = read_file(filename) {
+            self.load_text(&String::from_utf8_lossy(&data));
+        }
+    }
+
+    /// Saves the cookies which outlast the browser to `filename`. Returns None if it can't be
+    /// written.
+    pub fn save(&self, filename: &str) -> Option<()> {
+        write_file(filename, self.to_text().as_bytes())
+    }
+
+    /// Parses a Set-Cookie header for `url`, or returns None if it's ignored, e.g. its Domain is
+    /// another site.
+    /// https://httpwg.org/specs/rfc6265.html#set-cookie-algorithm
+    fn parse(&self, url: &ParsedUrl, set_cookie: &str) -> Option<Cookie> {
+        let mut parts = set_cookie.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let host = host_of(url);
+        let mut cookie = Cookie {
+            name: String::from(name),
+            value: String::from(value.trim()),
+            domain: host.clone(),
+            host_only: true,
+            path: default_path(path_of(url)),
+            expiry: None,
+            http_only: false,
+        };
+        let mut max_age = None;
+        let mut expires = None;
+        for attribute in parts {
+            let (key, value) = match attribute.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => (attribute.trim(), ""),
+            };
+            match key.to_ascii_lowercase().as_str() {
+                "expires" => {
+                    if let Some(time) = parse_date(value) {
+                        expires = Some(time);
+                    }
+                }
+                // https://httpwg.org/specs/rfc6265.html#max-age-attribute
+                "max-age" => {
+                    let digits = value.strip_prefix('-').unwrap_or(value);
+                    if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+                        // A number too large is as long as it can be.
+                        let limit = if digits == value { i64::MAX } else { i64::MIN };
+                        max_age = Some(value.parse::<i64>().unwrap_or(limit));
+                    }
+                }
+                // https://httpwg.org/specs/rfc6265.html#domain-attribute
+                "domain" => {
+                    let domain = value.trim_start_matches('.').to_ascii_lowercase();
+                    if domain.is_empty() {
+                        continue;
+                    }
+                    if !domain_matches(&host, &domain) {
+                        return None;
+                    }
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+                "path" if value.starts_with('/') => cookie.path = String::from(value),
+                "path" => cookie.path = default_path(path_of(url)),
+                // Only http is supported, so a cookie only for secure connections can't be set.
+                "secure" => return None,
+                "httponly" => cookie.http_only = true,
+                _ => {}
+            }
+        }
+        // Max-Age takes precedence over Expires.
+        cookie.expiry = match max_age {
+            Some(seconds) if seconds <= 0 => Some(i64::MIN),
+            Some(seconds) => Some(self.now().saturating_add(seconds)),
+            None => expires,
+        };
+        Some(cookie)
+    }
+}
+
+/// Returns the host of `url` without the port, which cookies are set for.
+fn host_of(url: &ParsedUrl) -> String {
+    let host = url.host.split(':').next().unwrap_or("");
+    host.to_ascii_lowercase()
+}
+
+/// Returns the path of `url` without the query.
+fn path_of(url: &ParsedUrl) -> &str {
+    let path = url.request_path();
+    match path.find('?') {
+        Some(i) => &path[..i],
+        None => path,
+    }
+}
+
+/// Returns true if `host` is `domain` or its subdomain. An IP address has no subdomain.
+/// https://httpwg.org/specs/rfc6265.html#cookie-domain
+pub fn domain_matches(host: &str, domain: &str) -> bool {
+    if host == domain {
+        return true;
+    }
+    let is_ip = host.bytes().all(|b| b.is_ascii_digit() || b == b'.');
+    !is_ip
+        && host.len() > domain.len()
+        && host.ends_with(domain)
+        && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+}
+
+/// Returns the path of the cookies without Path, which is the directory of the request path.
+/// https://httpwg.org/specs/rfc6265.html#cookie-path
+fn default_path(path: &str) -> String {
+    match path.rfind('/') {
+        Some(i) if i > 0 && path.starts_with('/') => String::from(&path[..i]),
+        _ => String::from("/"),
+    }
+}
+
+/// Returns true if a cookie of `cookie_path` is sent for a request to `path`.
+/// https://httpwg.org/specs/rfc6265.html#cookie-path
+fn path_matches(path: &str, cookie_path: &str) -> bool {
+    path == cookie_path
+        || (path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || path.as_bytes()[cookie_path.len()] == b'/'))
+}
+
+/// Parses a date of a cookie or Date, e.g. "Wed, 21 Oct 2015 07:28:00 GMT", into seconds since
+/// the Unix epoch. It's as lenient as browsers are, e.g. "21-Oct-15 7:28:00" is the same date.
+/// https://httpwg.org/specs/rfc6265.html#cookie-date
+pub fn parse_date(date: &str) -> Option<i64> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let is_delimiter = |c: char| {
+        matches!(c, '\x09' | '\x20'..='\x2f' | '\x3b'..='\x40' | '\x5b'..='\x60')
+            || matches!(c, '\x7b'..='\x7e')
+    };
+    let mut time = None;
+    let mut day = None;
+    let mut month = None;
+    let mut year = None;
+    for token in date.split(is_delimiter).filter(|t| !t.is_empty()) {
+        if time.is_none() {
+            if let Some(t) = parse_time(token) {
+                time = Some(t);
+                continue;
+            }
+        }
+        if day.is_none() {
+            if let Some(d) = leading_digits(token, 1, 2) {
+                day = Some(d);
+                continue;
+            }
+        }
+        if month.is_none() && token.len() >= 3 {
+            let name = token[..3].to_ascii_lowercase();
+            if let Some(m) = MONTHS.iter().position(|&m| m == name) {
+                month = Some(m as i64 + 1);
+                continue;
+            }
+        }
+        if year.is_none() {
+            if let Some(y) = leading_digits(token, 2, 4) {
+                year = Some(y);
+            }
+        }
+    }
+    let (hour, minute, second) = time?;
+    let year = match year? {
+        y @ 70..=99 => y + 1900,
+        y @ 0..=69 => y + 2000,
+        y => y,
+    };
+    let day = day?;
+    if !(1..=31).contains(&day) || year < 1601 || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    Some(days_from_civil(year, month?, day) * 86_400 + hour * 3600 + minute * 60 + second)
+}
+
+/// Parses "hh:mm:ss" at the start of `token`, where each part has 1 or 2 digits.
+fn parse_time(token: &str) -> Option<(i64, i64, i64)> {
+    let mut parts = token.splitn(3, ':');
+    let hour = all_digits(parts.next()?, 1, 2)?;
+    let minute = all_digits(parts.next()?, 1, 2)?;
+    let second = leading_digits(parts.next()?, 1, 2)?;
+    Some((hour, minute, second))
+}
+
+/// Parses `token` if it's `min` to `max` digits.
+fn all_digits(token: &str, min: usize, max: usize) -> Option<i64> {
+    if token.len() < min || token.len() > max || !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+/// Parses `min` to `max` digits at the start of `token`, which may be followed by non-digits.
+fn leading_digits(token: &str, min: usize, max: usize) -> Option<i64> {
+    let len = token.bytes().take_while(|b| b.is_ascii_digit()).count();
+    all_digits(&token[..len], min, max)
+}
+
+/// Returns the days from 1970-01-01 to the date in the proleptic Gregorian calendar.
+/// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let month_from_march = (month + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
@@ -0,0 +1,51 @@
+//! Downloads, which save the body of a response to a file as it is instead of rendering it.
+
+use crate::http::HttpResponse;
+use crate::net::{get_bytes, RequestOptions};
+use crate::url::ParsedUrl;
+use alloc::format;
+use alloc::string::String;
+use liumlib::write_file;
+
+/// The file name of a URL whose path ends with a slash, e.g. "http://127.0.0.1:8888/".
+const DEFAULT_NAME: &str = "index.html";
+
+/// Returns the name of the file to save the resource at `url` to, which is the last segment of
+/// its path without the query.
+pub fn file_name(url: &ParsedUrl) -> String {
+    let path = url.request_path();
+    let path = match path.find('?') {
+        Some(i) => &path[..i],
+        None => path,
+    };
+    match path.rsplit('/').next() {
+        Some(name) if !name.is_empty() => String::from(name),
+        _ => String::from(DEFAULT_NAME),
+    }
+}
+
+/// Fetches the resource at `url` with `options` and writes its body to the file `path`.
+/// `progress` is called with the size of the body in bytes once it's received, before it's
+/// written. Returns the size, or an error message if the resource can't be fetched or written.
+pub fn download<F: FnOnce(usize)>(
+    url: &ParsedUrl,
+    path: &str,
+    options: &RequestOptions,
+    progress: F,
+) -> Result<usize, String> {
+    let response = HttpResponse::from_bytes(&get_bytes(url, options)?);
+    if !(200..300).contains(&response.status_code()) {
+        return Err(format!(
+            "{} responded with {} {}",
+            url,
+            response.status_code(),
+            response.reason()
+        ));
+    }
+    let body = response.body_bytes();
+    progress(body.len());
+    match write_file(path, body) {
+        Some(()) => Ok(body.len()),
+        None => Err(format!("failed to write {}", path)),
+    }
+}
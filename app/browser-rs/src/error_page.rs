@@ -0,0 +1,79 @@
+//! Error pages, which are documents made by the browser to show why a page can't be loaded, e.g.
+//! the server doesn't respond. They're rendered like any other document.
+
+use crate::http::HttpResponse;
+use crate::url::ParsedUrl;
+use alloc::format;
+use alloc::string::{String, ToString};
+
+/// The HTML of an error page, where `{title}`, `{url}` and `{detail}` are replaced.
+const TEMPLATE: &str = "<html><head><title>{title}</title>\
+<style>body { margin: 16px; color: #333333 } h1 { color: #aa0000 } \
+p.url { color: #666666 }</style></head>\
+<body><h1>{title}</h1><p class=url>{url}</p><p>{detail}</p>\
+<p>Press r to try again.</p></body></html>";
+
+/// Returns the HTML of a page telling that the document at `url` can't be loaded. `title` says
+/// what happened and `detail` says why, e.g. an error message of the network.
+pub fn error_page(url: &ParsedUrl, title: &str, detail: &str) -> String {
+    let url = url.to_string();
+    let values = [
+        ("{title}", title),
+        ("{url}", url.as_str()),
+        ("{detail}", detail),
+    ];
+    // The template is scanned once so that a value containing a placeholder is kept as it is.
+    let mut out = String::new();
+    let mut rest = TEMPLATE;
+    while let Some(i) = rest.find('{') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        match values.iter().find(|(name, _)| rest.starts_with(name)) {
+            Some((name, value)) => {
+                out.push_str(&escape(value));
+                rest = &rest[name.len()..];
+            }
+            None => {
+                out.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Returns the HTML of a page for a request to `url` which failed with the network error
+/// `message`, e.g. no response from the server.
+pub fn network_error_page(url: &ParsedUrl, message: &str) -> String {
+    error_page(url, "This page can't be reached", message)
+}
+
+/// Returns the HTML of a page for `response` if it's an HTTP error without a body to show,
+/// or None if the body of the response should be shown as it is.
+pub fn http_error_page(url: &ParsedUrl, response: &HttpResponse) -> Option<String> {
+    if response.status_code() < 400 || !response.body().trim().is_empty() {
+        return None;
+    }
+    let title = format!("{} {}", response.status_code(), response.reason());
+    Some(error_page(
+        url,
+        title.trim(),
+        "The server responded with an error.",
+    ))
+}
+
+/// Escapes the characters which have meanings in HTML.
+fn escape(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
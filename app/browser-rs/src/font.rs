@@ -0,0 +1,116 @@
+//! Fonts to measure and draw text with. Text is rendered with the bitmap font of liumlib, which is
+//! scaled by an integer to get larger font sizes.
+
+use crate::style::computed::*;
+use liumlib::font::{glyph, GLYPH_HEIGHT, GLYPH_WIDTH};
+
+/// The font weight from which text is drawn in bold.
+/// https://www.w3.org/TR/css-fonts-4/#font-weight-numeric-values
+const BOLD_THRESHOLD: u16 = 600;
+
+/// The row of the glyphs where an underline is drawn, which is the row of the underscore.
+const UNDERLINE_ROW: usize = 14;
+
+/// The bitmap font at a size.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Font {
+    /// How many times the glyphs are enlarged. 1 is the original 8x16 glyphs.
+    scale: usize,
+    /// Bold glyphs are drawn twice, shifted by a pixel.
+    bold: bool,
+}
+
+#[allow(dead_code)]
+impl Font {
+    pub fn new(scale: usize, bold: bool) -> Self {
+        Self {
+            scale: if scale == 0 { 1 } else { scale },
+            bold,
+        }
+    }
+
+    /// Returns the font for text in `style`. The font size is rounded to a multiple of the glyph
+    /// height, e.g. 16px and 20px are drawn at scale 1, and 24px and 32px at scale 2, since the
+    /// bitmap glyphs can't be scaled smoothly.
+    pub fn from_style(style: &ComputedStyle) -> Self {
+        let scale = (style.font_size() / GLYPH_HEIGHT as f64 + 0.5) as usize;
+        Self::new(scale, style.font_weight() >= BOLD_THRESHOLD)
+    }
+
+    pub fn scale(&self) -> usize {
+        self.scale
+    }
+
+    pub fn is_bold(&self) -> bool {
+        self.bold
+    }
+
+    /// Returns the width of a glyph in px.
+    pub fn glyph_width(&self) -> f64 {
+        (GLYPH_WIDTH * self.scale) as f64
+    }
+
+    /// Returns the height of a glyph in px, which is also the height of a line.
+    pub fn glyph_height(&self) -> f64 {
+        (GLYPH_HEIGHT * self.scale) as f64
+    }
+
+    /// Returns the distance from the top of a line of text to the top of its underline.
+    /// https://www.w3.org/TR/css-text-decor-3/#text-underline-position-property
+    pub fn underline_offset(&self) -> f64 {
+        (UNDERLINE_ROW * self.scale) as f64
+    }
+
+    /// Returns the thickness of an underline, which is as thick as the strokes of the glyphs.
+    pub fn underline_thickness(&self) -> f64 {
+        self.scale as f64
+    }
+
+    /// Returns how far the pen moves after drawing `c`. The font is monospaced.
+    pub fn advance(&self, _c: char) -> f64 {
+        self.glyph_width()
+    }
+
+    /// Returns the width of `text` in px.
+    pub fn text_width(&self, text: &str) -> f64 {
+        text.chars().map(|c| self.advance(c)).sum()
+    }
+
+    /// Calls `plot` with the position of each pixel of the glyph of `c`, relative to the top-left
+    /// corner of the glyph. A character which the font doesn't have is drawn as `?`.
+    pub fn for_each_pixel<F: FnMut(usize, usize)>(&self, c: char, mut plot: F) {
+        let rows = glyph_rows(c);
+        let is_set = |x: usize, y: usize| (rows[y] >> (GLYPH_WIDTH - 1 - x)) & 1 == 1;
+        for y in 0..GLYPH_HEIGHT {
+            for x in 0..GLYPH_WIDTH {
+                let set = is_set(x, y) || (self.bold && x > 0 && is_set(x - 1, y));
+                if !set {
+                    continue;
+                }
+                for dy in 0..self.scale {
+                    for dx in 0..self.scale {
+                        plot(x * self.scale + dx, y * self.scale + dy);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns the rows of the glyph of `c`. The font has the bullets of list markers in the control
+/// characters like code page 437, except for the square one, which is made here.
+fn glyph_rows(c: char) -> [u8; GLYPH_HEIGHT] {
+    match c {
+        '\u{2022}' => *glyph(0x07),
+        '\u{25e6}' => *glyph(0x09),
+        '\u{25aa}' => {
+            let mut rows = [0; GLYPH_HEIGHT];
+            for row in rows.iter_mut().skip(6).take(4) {
+                *row = 0b0011_1100;
+            }
+            rows
+        }
+        c if (c as u32) < 0x100 => *glyph(c as u8),
+        _ => *glyph(b'?'),
+    }
+}
@@ -0,0 +1,421 @@
+//! Form submission, which turns the controls of a form into a request to navigate with.
+//! https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#form-submission-2
+
+use crate::parser::dom::{Document, Element, ElementKind, NodeId};
+use crate::url::ParsedUrl;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use liumlib::rand::Rng;
+
+/// The content type of a form sent as the body of a POST request.
+/// https://url.spec.whatwg.org/#application/x-www-form-urlencoded
+pub const URLENCODED: &str = "application/x-www-form-urlencoded";
+
+/// The content type of a form whose entries are separated by a boundary, which is sent if the
+/// form asks for it by `enctype`.
+/// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#multipart/form-data-encoding-algorithm
+pub const MULTIPART: &str = "multipart/form-data";
+
+/// The characters of the random part of a boundary.
+const BOUNDARY_CHARS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// A name and a value of a control sent with a form.
+/// https://xhr.spec.whatwg.org/#concept-formdata-entry
+pub type Entry = (String, String);
+
+/// The HTTP method to submit a form with.
+/// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#attr-fs-method
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormMethod {
+    Get,
+    Post,
+}
+
+/// How the entries of a POST submission are encoded. text/plain isn't supported, so it's sent as
+/// `Urlencoded` like an invalid value.
+/// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#attr-fs-enctype
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormEnctype {
+    Urlencoded,
+    Multipart,
+}
+
+/// The body of a POST submission and its content type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormBody {
+    pub content_type: String,
+    pub data: String,
+}
+
+/// What a form sends, which is built when it's submitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submission {
+    pub method: FormMethod,
+    pub enctype: FormEnctype,
+    /// The action URL as it's written, which is relative to the document. It's empty if the
+    /// form is submitted to the document's URL.
+    pub action: String,
+    pub entries: Vec<Entry>,
+}
+
+impl Submission {
+    /// Returns the action URL relative to `base`, to which a POST submission sends `body()`, or
+    /// None if it's invalid.
+    pub fn action_url(&self, base: &ParsedUrl) -> Option<ParsedUrl> {
+        if self.action.is_empty() {
+            Some(base.clone())
+        } else {
+            base.join(&self.action)
+        }
+    }
+
+    /// Returns the body of a POST submission, which is of the type `URLENCODED`, or `MULTIPART`
+    /// with a random boundary.
+    /// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#submit-body
+    pub fn body(&self) -> FormBody {
+        match self.enctype {
+            FormEnctype::Urlencoded => FormBody {
+                content_type: String::from(URLENCODED),
+                data: urlencode(&self.entries),
+            },
+            FormEnctype::Multipart => {
+                let boundary = new_boundary(&mut Rng::new());
+                FormBody {
+                    content_type: format!("{}; boundary={}", MULTIPART, boundary),
+                    data: multipart_encode(&self.entries, &boundary),
+                }
+            }
+        }
+    }
+
+    /// Returns the URL which a GET submission navigates to, i.e. the action URL relative to
+    /// `base` whose query is replaced with the entries, or None if the action URL is invalid.
+    /// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#submit-mutate-action
+    pub fn url(&self, base: &ParsedUrl) -> Option<ParsedUrl> {
+        let mut url = self.action_url(base)?;
+        let (path, fragment) = match url.path.find('#') {
+            Some(i) => url.path.split_at(i),
+            None => (url.path.as_str(), ""),
+        };
+        let path = match path.find('?') {
+            Some(i) => &path[..i],
+            None => path,
+        };
+        url.path = format!("{}?{}{}", path, urlencode(&self.entries), fragment);
+        Some(url)
+    }
+}
+
+/// Returns the type of an input element in lowercase, which is "text" if it's missing.
+/// https://html.spec.whatwg.org/multipage/input.html#attr-input-type
+fn input_type(element: &Element) -> String {
+    element
+        .get_attribute("type")
+        .map(|t| t.to_ascii_lowercase())
+        .unwrap_or_else(|| String::from("text"))
+}
+
+/// Returns true if `element` is a submit button, i.e. a button element whose type is submit or
+/// missing, or an input element whose type is submit or image.
+/// https://html.spec.whatwg.org/multipage/forms.html#concept-submit-button
+pub fn is_submit_button(element: &Element) -> bool {
+    match element.kind() {
+        ElementKind::Button => !matches!(
+            element.get_attribute("type").map(|t| t.to_ascii_lowercase()),
+            Some(t) if t == "reset" || t == "button"
+        ),
+        ElementKind::Input => matches!(input_type(element).as_str(), "submit" | "image"),
+        _ => false,
+    }
+}
+
+/// Returns true if `element` is a button of any type, which is sent only when it submits the
+/// form.
+fn is_button(element: &Element) -> bool {
+    match element.kind() {
+        ElementKind::Button => true,
+        ElementKind::Input => matches!(
+            input_type(element).as_str(),
+            "submit" | "image" | "reset" | "button"
+        ),
+        _ => false,
+    }
+}
+
+/// Returns true if `element` is an input element where a line of text is typed, on which Enter
+/// submits the form.
+/// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#field-that-blocks-implicit-submission
+pub fn is_text_field(element: &Element) -> bool {
+    element.kind() == ElementKind::Input
+        && matches!(
+            input_type(element).as_str(),
+            "text"
+                | "search"
+                | "url"
+                | "tel"
+                | "email"
+                | "password"
+                | "date"
+                | "month"
+                | "week"
+                | "time"
+                | "datetime-local"
+                | "number"
+        )
+}
+
+/// Returns true if `element` is a text field or a textarea, into which the user types text.
+pub fn is_text_control(element: &Element) -> bool {
+    is_text_field(element) || element.kind() == ElementKind::Textarea
+}
+
+/// Returns true if `element` is a password field, whose value is hidden.
+pub fn is_password(element: &Element) -> bool {
+    element.kind() == ElementKind::Input && input_type(element) == "password"
+}
+
+/// Returns the value of the control `id`, which is the one edited by the user, or otherwise its
+/// default value.
+/// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#concept-fe-value
+pub fn control_value(document: &Document, id: NodeId) -> String {
+    let element = match document.node(id).element() {
+        Some(element) => element,
+        None => return String::new(),
+    };
+    if let Some(value) = element.value() {
+        return String::from(value);
+    }
+    match element.kind() {
+        ElementKind::Textarea => document.text_content(id),
+        _ => element.get_attribute("value").unwrap_or_default(),
+    }
+}
+
+/// Returns true if `element` is a control sent with its form.
+/// https://html.spec.whatwg.org/multipage/forms.html#category-submit
+fn is_submittable(element: &Element) -> bool {
+    matches!(
+        element.kind(),
+        ElementKind::Button | ElementKind::Input | ElementKind::Textarea
+    )
+}
+
+/// Returns the form which `control` belongs to, which is the one whose id is its form attribute
+/// if it has one, or otherwise the nearest ancestor form.
+/// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#form-owner
+pub fn form_owner(document: &Document, control: NodeId) -> Option<NodeId> {
+    let is_form = |id: NodeId| document.node(id).element_kind() == Some(ElementKind::Form);
+    if let Some(form) = document.node(control).element()?.get_attribute("form") {
+        return document
+            .descendants(document.root())
+            .find(|&n| {
+                document
+                    .node(n)
+                    .element()
+                    .and_then(|e| e.get_attribute("id"))
+                    .is_some_and(|id| id == form)
+            })
+            .filter(|&n| is_form(n));
+    }
+    let mut current = document.node(control).parent();
+    while let Some(id) = current {
+        if is_form(id) {
+            return Some(id);
+        }
+        current = document.node(id).parent();
+    }
+    None
+}
+
+/// Returns the submittable controls of `form` in tree order.
+fn controls(document: &Document, form: NodeId) -> Vec<NodeId> {
+    document
+        .descendants(document.root())
+        .filter(|&n| {
+            document.node(n).element().is_some_and(is_submittable)
+                && form_owner(document, n) == Some(form)
+        })
+        .collect()
+}
+
+/// Returns the names and the values of the controls of `form`. Buttons other than `submitter`,
+/// disabled controls, unchecked checkboxes and radio buttons, and controls without a name are
+/// left out.
+/// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#constructing-the-form-data-set
+pub fn entry_list(document: &Document, form: NodeId, submitter: Option<NodeId>) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for id in controls(document, form) {
+        let element = match document.node(id).element() {
+            Some(element) => element,
+            None => continue,
+        };
+        if element.get_attribute("disabled").is_some()
+            || (is_button(element) && Some(id) != submitter)
+        {
+            continue;
+        }
+        let kind = if element.kind() == ElementKind::Input {
+            input_type(element)
+        } else {
+            String::new()
+        };
+        if (kind == "checkbox" || kind == "radio") && element.get_attribute("checked").is_none() {
+            continue;
+        }
+        let name = element.get_attribute("name").unwrap_or_default();
+        // An image button sends the position clicked, which is always the origin with keys.
+        if kind == "image" {
+            let prefix = if name.is_empty() {
+                String::new()
+            } else {
+                format!("{}.", name)
+            };
+            entries.push((format!("{}x", prefix), String::from("0")));
+            entries.push((format!("{}y", prefix), String::from("0")));
+            continue;
+        }
+        if name.is_empty() || kind == "file" {
+            continue;
+        }
+        let value = if (kind == "checkbox" || kind == "radio")
+            && element.get_attribute("value").is_none()
+        {
+            String::from("on")
+        } else {
+            control_value(document, id)
+        };
+        entries.push((name, value));
+    }
+    entries
+}
+
+/// Builds the submission of `form` by `submitter`, which is a submit button or None if the form
+/// is submitted by Enter in a text field without one. The attributes of the submitter override
+/// the ones of the form.
+pub fn submission(document: &Document, form: NodeId, submitter: Option<NodeId>) -> Submission {
+    let attribute = |name: &str, override_name: &str| {
+        submitter
+            .and_then(|s| document.node(s).element()?.get_attribute(override_name))
+            .or_else(|| document.node(form).element()?.get_attribute(name))
+            .unwrap_or_default()
+    };
+    let method = if attribute("method", "formmethod").eq_ignore_ascii_case("post") {
+        FormMethod::Post
+    } else {
+        FormMethod::Get
+    };
+    let enctype = if attribute("enctype", "formenctype").eq_ignore_ascii_case(MULTIPART) {
+        FormEnctype::Multipart
+    } else {
+        FormEnctype::Urlencoded
+    };
+    Submission {
+        method,
+        enctype,
+        action: String::from(attribute("action", "formaction").trim()),
+        entries: entry_list(document, form, submitter),
+    }
+}
+
+/// Submits the form of `control` when it's activated, e.g. by Enter. A submit button submits
+/// its form, and a text field submits its form by the first submit button in it, or without one
+/// if the text field is the only one in the form. Returns None if nothing is submitted.
+/// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#implicit-submission
+pub fn submit(document: &Document, control: NodeId) -> Option<Submission> {
+    let element = document.node(control).element()?;
+    if element.get_attribute("disabled").is_some() {
+        return None;
+    }
+    let form = form_owner(document, control)?;
+    if is_submit_button(element) {
+        return Some(submission(document, form, Some(control)));
+    }
+    if !is_text_field(element) {
+        return None;
+    }
+    let controls = controls(document, form);
+    let default_button = controls
+        .iter()
+        .find(|&&n| document.node(n).element().is_some_and(is_submit_button));
+    match default_button {
+        Some(&button) => submit(document, button),
+        None => {
+            let fields = controls
+                .iter()
+                .filter(|&&n| document.node(n).element().is_some_and(is_text_field))
+                .count();
+            if fields == 1 {
+                Some(submission(document, form, None))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Serializes `entries` as application/x-www-form-urlencoded, e.g. "q=a+b&lang=%E6%97%A5".
+/// Newlines are normalized to CRLF first.
+/// https://url.spec.whatwg.org/#concept-urlencoded-serializer
+pub fn urlencode(entries: &[Entry]) -> String {
+    entries
+        .iter()
+        .map(|(name, value)| format!("{}={}", encode_component(name), encode_component(value)))
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+/// Returns a boundary of multipart/form-data, which is unlikely to be in the entries since its
+/// end is random, like "----liumosFormBoundaryX7Ab09...".
+pub fn new_boundary(rng: &mut Rng) -> String {
+    let mut boundary = String::from("----liumosFormBoundary");
+    for _ in 0..16 {
+        let i = rng.below(BOUNDARY_CHARS.len() as u64) as usize;
+        boundary.push(BOUNDARY_CHARS[i] as char);
+    }
+    boundary
+}
+
+/// Serializes `entries` as multipart/form-data, where each entry is a part after `boundary`.
+/// Newlines in the values are normalized to CRLF, and the names escape `"` and newlines.
+/// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#multipart/form-data-encoding-algorithm
+pub fn multipart_encode(entries: &[Entry], boundary: &str) -> String {
+    let mut out = String::new();
+    for (name, value) in entries {
+        let name = name
+            .replace('"', "%22")
+            .replace('\r', "%0D")
+            .replace('\n', "%0A");
+        out.push_str(&format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+            boundary,
+            name,
+            normalize_newlines(value)
+        ));
+    }
+    out.push_str(&format!("--{}--\r\n", boundary));
+    out
+}
+
+/// Replaces CR, LF and CRLF in `s` with CRLF.
+fn normalize_newlines(s: &str) -> String {
+    s.replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .replace('\n', "\r\n")
+}
+
+/// https://url.spec.whatwg.org/#concept-urlencoded-byte-serializer
+fn encode_component(s: &str) -> String {
+    let normalized = normalize_newlines(s);
+    let mut out = String::new();
+    for &b in normalized.as_bytes() {
+        match b {
+            b' ' => out.push('+'),
+            b'*' | b'-' | b'.' | b'_' | b'0'..=b'9' | b'A'..=b'Z' | b'a'..=b'z' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
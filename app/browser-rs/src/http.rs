@@ -1,4 +1,6 @@
+use alloc::format;
 use alloc::string::String;
+use alloc::string::ToString;
 use alloc::vec::Vec;
 
 use crate::url::ParsedUrl;
@@ -6,12 +8,14 @@ use crate::url::ParsedUrl;
 #[derive(Debug)]
 pub enum Method {
     Get,
+    Post,
 }
 
 impl Method {
     fn name(&self) -> String {
         match self {
             Method::Get => String::from("GET"),
+            Method::Post => String::from("POST"),
         }
     }
 }
@@ -41,7 +45,7 @@ impl HttpRequest {
     pub fn new(method: Method, url: &ParsedUrl) -> Self {
         let mut req = Self {
             method,
-            path: String::from(&url.path),
+            path: String::from(url.request_path()),
             version: String::from("HTTP/1.1"),
             headers: Vec::new(),
             body: String::from("sending a request"),
@@ -56,6 +60,32 @@ impl HttpRequest {
         self.headers.push(Header::new(key, value));
     }
 
+    /// Sends `body` of `content_type`, e.g. a form, with the request.
+    pub fn set_body(&mut self, content_type: &str, body: String) {
+        self.add_header(String::from("Content-Type"), String::from(content_type));
+        self.add_header(String::from("Content-Length"), body.len().to_string());
+        self.body = body;
+    }
+
+    /// Sends the whole URL except the fragment as the request target, which a proxy needs to
+    /// know the origin server.
+    /// https://httpwg.org/specs/rfc9112.html#absolute-form
+    pub fn set_absolute_target(&mut self, url: &ParsedUrl) {
+        let url = url.to_string();
+        self.path = match url.find('#') {
+            Some(i) => String::from(&url[..i]),
+            None => url,
+        };
+    }
+
+    /// Asks the caches on the way, e.g. proxies, to revalidate the response with the origin
+    /// server instead of returning a stored one. `Pragma` is for HTTP/1.0 caches.
+    /// https://httpwg.org/specs/rfc9111.html#cache-request-directive.no-cache
+    pub fn bypass_cache(&mut self) {
+        self.add_header(String::from("Cache-Control"), String::from("no-cache"));
+        self.add_header(String::from("Pragma"), String::from("no-cache"));
+    }
+
     pub fn string(&self) -> String {
         // request line
         let mut request = self.method.name();
@@ -80,3 +110,148 @@ impl HttpRequest {
         request
     }
 }
+
+/// https://tools.ietf.org/html/rfc7230#section-3
+/// HTTP-message = start-line
+///                *( header-field CRLF )
+///                CRLF
+///                [ message-body ]
+#[allow(dead_code)]
+#[derive(Debug)]
+pub struct HttpResponse {
+    version: String,
+    status_code: u32,
+    reason: String,
+    headers: Vec<Header>,
+    body: String,
+    /// The body before being decoded as UTF-8, which is needed for binary resources.
+    body_bytes: Vec<u8>,
+}
+
+#[allow(dead_code)]
+impl HttpResponse {
+    pub fn new(raw_response: String) -> Self {
+        // Accept both CRLF and LF as a line terminator.
+        let preprocessed = raw_response.replace("\r\n", "\n");
+
+        let (status_line, remaining) = match preprocessed.split_once('\n') {
+            Some((s, r)) => (s, r),
+            None => (preprocessed.as_str(), ""),
+        };
+
+        let (headers, body) = match remaining.split_once("\n\n") {
+            Some((h, b)) => {
+                let mut headers = Vec::new();
+                for header in h.split('\n') {
+                    if let Some((key, value)) = header.split_once(':') {
+                        headers.push(Header::new(
+                            String::from(key.trim()),
+                            String::from(value.trim()),
+                        ));
+                    }
+                }
+                (headers, String::from(b))
+            }
+            None => (Vec::new(), String::from(remaining)),
+        };
+
+        let statuses: Vec<&str> = status_line.splitn(3, ' ').collect();
+        Self {
+            version: String::from(statuses[0]),
+            status_code: statuses.get(1).and_then(|s| s.parse().ok()).unwrap_or(0),
+            reason: String::from(*statuses.get(2).unwrap_or(&"")),
+            headers,
+            body_bytes: body.clone().into_bytes(),
+            body,
+        }
+    }
+
+    /// Parses a response whose body may be binary, e.g. an image. Invalid UTF-8 sequences in the
+    /// start-line, the headers and `body()` are replaced with U+FFFD, while `body_bytes()` has
+    /// the body as it is.
+    pub fn from_bytes(raw_response: &[u8]) -> Self {
+        let (head, body) = split_at_body(raw_response);
+        let mut response = Self::new(String::from_utf8_lossy(head).into_owned());
+        response.body = String::from_utf8_lossy(body).into_owned();
+        response.body_bytes = body.to_vec();
+        response
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn status_code(&self) -> u32 {
+        self.status_code
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    /// Returns the value of the first header whose name matches `name` case-insensitively.
+    pub fn header(&self, name: &str) -> Option<String> {
+        self.headers
+            .iter()
+            .find(|h| h.key.eq_ignore_ascii_case(name))
+            .map(|h| h.value.clone())
+    }
+
+    /// Returns the values of all the headers whose names match `name` case-insensitively, e.g.
+    /// Set-Cookie, which can't be combined into one.
+    pub fn header_values(&self, name: &str) -> Vec<String> {
+        self.headers
+            .iter()
+            .filter(|h| h.key.eq_ignore_ascii_case(name))
+            .map(|h| h.value.clone())
+            .collect()
+    }
+
+    /// Returns the value of Location if the response redirects to it, i.e. asks to request it
+    /// instead.
+    /// https://fetch.spec.whatwg.org/#redirect-status
+    pub fn redirect_location(&self) -> Option<String> {
+        match self.status_code {
+            301 | 302 | 303 | 307 | 308 => self.header("Location"),
+            _ => None,
+        }
+    }
+
+    /// Returns the URL relative to `url` which the response for `url` redirects to, None if it
+    /// doesn't redirect, or an error message if the location is invalid.
+    pub fn redirect_url(&self, url: &ParsedUrl) -> Result<Option<ParsedUrl>, String> {
+        match self.redirect_location() {
+            Some(location) => match url.join(&location) {
+                Some(next) => Ok(Some(next)),
+                None => Err(format!("invalid redirect to {}", location)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    pub fn body_bytes(&self) -> &[u8] {
+        &self.body_bytes
+    }
+}
+
+/// Splits a raw response after the empty line which ends the header section. The head is the
+/// whole response if there's no empty line.
+fn split_at_body(raw_response: &[u8]) -> (&[u8], &[u8]) {
+    let end = (0..raw_response.len()).find_map(|i| {
+        if raw_response[i..].starts_with(b"\r\n\r\n") {
+            Some(i + 4)
+        } else if raw_response[i..].starts_with(b"\n\n") {
+            Some(i + 2)
+        } else {
+            None
+        }
+    });
+    match end {
+        Some(end) => raw_response.split_at(end),
+        None => (raw_response, &[]),
+    }
+}
@@ -0,0 +1,80 @@
+//! Images: decoders of image files into pixels, and loading of the images of img elements.
+//! https://html.spec.whatwg.org/multipage/images.html
+
+pub mod bmp;
+pub mod inflate;
+pub mod loader;
+pub mod png;
+pub mod ppm;
+
+use crate::style::values::*;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// The largest width and height of an image which is decoded. Files which claim a larger one are
+/// treated as broken, so a broken header doesn't make a decoder allocate all the memory.
+pub const MAX_SIZE: usize = 8192;
+
+/// Returns whether the decoders accept an image of `width` x `height` pixels.
+pub fn is_valid_size(width: usize, height: usize) -> bool {
+    (1..=MAX_SIZE).contains(&width) && (1..=MAX_SIZE).contains(&height)
+}
+
+/// A decoded image of `width` x `height` pixels.
+#[derive(Clone, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    /// The pixels from the top-left one in row-major order.
+    pixels: Vec<Color>,
+}
+
+impl fmt::Debug for Image {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Image({}x{})", self.width, self.height)
+    }
+}
+
+#[allow(dead_code)]
+impl Image {
+    /// Creates an image from its pixels. Returns None if the number of the pixels doesn't match
+    /// the size.
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Option<Self> {
+        if pixels.len() != width.checked_mul(height)? {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the color of the pixel at (`x`, `y`).
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+}
+
+/// Decodes `data` in the format detected from its signature. Returns None if the format isn't
+/// supported or the data is broken.
+/// https://mimesniff.spec.whatwg.org/#matching-an-image-type-pattern
+pub fn decode(data: &[u8]) -> Option<Image> {
+    if data.starts_with(&png::SIGNATURE) {
+        png::decode(data)
+    } else if data.starts_with(b"BM") {
+        bmp::decode(data)
+    } else if data.starts_with(b"P3") || data.starts_with(b"P6") {
+        ppm::decode(data)
+    } else {
+        None
+    }
+}
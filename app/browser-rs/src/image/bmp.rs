@@ -0,0 +1,79 @@
+//! A decoder of uncompressed BMP files with 24 or 32 bits per pixel.
+//! https://docs.microsoft.com/en-us/windows/win32/gdi/bitmap-storage
+
+use crate::image::{is_valid_size, Image};
+use crate::style::values::*;
+use alloc::vec::Vec;
+
+/// The size of BITMAPFILEHEADER.
+const FILE_HEADER_SIZE: usize = 14;
+/// The size of BITMAPINFOHEADER. Later versions of the header extend it.
+const INFO_HEADER_SIZE: usize = 40;
+/// BI_RGB, which means no compression.
+const BI_RGB: u32 = 0;
+
+/// Decodes a BMP file. Returns None if it's compressed, has a color table, is larger than
+/// `MAX_SIZE`, or is broken.
+pub fn decode(data: &[u8]) -> Option<Image> {
+    if !data.starts_with(b"BM") {
+        return None;
+    }
+    let offset = u32_at(data, 10)? as usize;
+    let header_size = u32_at(data, FILE_HEADER_SIZE)? as usize;
+    // The pixels follow the headers.
+    if header_size < INFO_HEADER_SIZE || offset < FILE_HEADER_SIZE.checked_add(header_size)? {
+        return None;
+    }
+    let width = i32_at(data, FILE_HEADER_SIZE + 4)?;
+    let height = i32_at(data, FILE_HEADER_SIZE + 8)?;
+    let bits_per_pixel = u16_at(data, FILE_HEADER_SIZE + 14)?;
+    let compression = u32_at(data, FILE_HEADER_SIZE + 16)?;
+    if width <= 0 || !is_valid_size(width as usize, height.unsigned_abs() as usize) {
+        return None;
+    }
+    if compression != BI_RGB {
+        return None;
+    }
+    let bytes_per_pixel = match bits_per_pixel {
+        24 => 3,
+        32 => 4,
+        _ => return None,
+    };
+
+    // "If biHeight is positive, the bitmap is a bottom-up DIB and its origin is the lower-left
+    // corner. If biHeight is negative, the bitmap is a top-down DIB". Rows are padded to
+    // multiples of 4 bytes.
+    let width = width as usize;
+    let bottom_up = height > 0;
+    let height = height.unsigned_abs() as usize;
+    let row_size = width.checked_mul(bytes_per_pixel)?.checked_add(3)? & !3;
+    let end = offset.checked_add(row_size.checked_mul(height)?)?;
+    let rows = data.get(offset..end)?;
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let row = if bottom_up { height - 1 - y } else { y };
+        let row = &rows[row * row_size..];
+        for x in 0..width {
+            // The pixels are in BGR order. The 4th byte of a 32-bit pixel is unused without
+            // BI_BITFIELDS, so the pixels are opaque.
+            let p = &row[x * bytes_per_pixel..];
+            pixels.push(Color::rgb(p[2], p[1], p[0]));
+        }
+    }
+    Image::new(width, height, pixels)
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn i32_at(data: &[u8], offset: usize) -> Option<i32> {
+    u32_at(data, offset).map(|v| v as i32)
+}
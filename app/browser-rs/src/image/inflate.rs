@@ -0,0 +1,290 @@
+//! A decoder of the zlib format and the DEFLATE compressed data in it.
+//! https://datatracker.ietf.org/doc/html/rfc1950
+//! https://datatracker.ietf.org/doc/html/rfc1951
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The base lengths of the length codes 257..285 and the numbers of their extra bits.
+/// https://datatracker.ietf.org/doc/html/rfc1951#section-3.2.5
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// The base distances of the distance codes 0..29 and the numbers of their extra bits.
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// The order in which the lengths of the code length codes are stored.
+/// https://datatracker.ietf.org/doc/html/rfc1951#section-3.2.7
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+const MAX_BITS: usize = 15;
+
+/// Decompresses a zlib stream. Returns None if it's broken, uses a preset dictionary, its
+/// checksum doesn't match, or it decompresses to more than `limit` bytes.
+pub fn zlib_decompress(data: &[u8], limit: usize) -> Option<Vec<u8>> {
+    let cmf = *data.first()?;
+    let flg = *data.get(1)?;
+    // "CM = 8 denotes the "deflate" compression method". "The FCHECK value must be such that CMF
+    // and FLG, when viewed as a 16-bit unsigned integer stored in MSB order (CMF*256 + FLG), is a
+    // multiple of 31." FDICT is bit 5 of FLG.
+    if cmf & 0x0f != 8 || !(cmf as u16 * 256 + flg as u16).is_multiple_of(31) || flg & 0x20 != 0 {
+        return None;
+    }
+    let mut reader = BitReader::new(&data[2..]);
+    let out = inflate(&mut reader, limit)?;
+
+    let checksum = reader.data.get(reader.pos..reader.pos + 4)?;
+    let checksum = u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+    if checksum != adler32(&out) {
+        return None;
+    }
+    Some(out)
+}
+
+/// https://datatracker.ietf.org/doc/html/rfc1950#section-9
+fn adler32(data: &[u8]) -> u32 {
+    const BASE: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % BASE;
+        b = (b + a) % BASE;
+    }
+    (b << 16) | a
+}
+
+/// Decompresses DEFLATE blocks until the final one, up to `limit` bytes. The reader is left at
+/// the byte after the last block.
+/// https://datatracker.ietf.org/doc/html/rfc1951#section-3.2.3
+fn inflate(reader: &mut BitReader, limit: usize) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => stored(reader, &mut out, limit)?,
+            1 => {
+                let (literals, distances) = fixed_codes();
+                codes(reader, &mut out, limit, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(reader)?;
+                codes(reader, &mut out, limit, &literals, &distances)?;
+            }
+            _ => return None,
+        }
+        if last {
+            reader.align();
+            return Some(out);
+        }
+    }
+}
+
+/// Copies a non-compressed block.
+/// https://datatracker.ietf.org/doc/html/rfc1951#section-3.2.4
+fn stored(reader: &mut BitReader, out: &mut Vec<u8>, limit: usize) -> Option<()> {
+    reader.align();
+    let header = reader.data.get(reader.pos..reader.pos + 4)?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    let nlen = u16::from_le_bytes([header[2], header[3]]);
+    if len != !nlen || out.len() + len as usize > limit {
+        return None;
+    }
+    let start = reader.pos + 4;
+    out.extend_from_slice(reader.data.get(start..start + len as usize)?);
+    reader.pos = start + len as usize;
+    Some(())
+}
+
+/// Decodes the literals and the length/distance pairs of a compressed block.
+/// https://datatracker.ietf.org/doc/html/rfc1951#section-3.2.5
+fn codes(
+    reader: &mut BitReader,
+    out: &mut Vec<u8>,
+    limit: usize,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Option<()> {
+    loop {
+        let symbol = literals.decode(reader)? as usize;
+        if symbol < 256 {
+            if out.len() == limit {
+                return None;
+            }
+            out.push(symbol as u8);
+            continue;
+        }
+        if symbol == 256 {
+            return Some(());
+        }
+        let i = symbol - 257;
+        let length = *LENGTH_BASE.get(i)? as usize + reader.bits(LENGTH_EXTRA[i])? as usize;
+        let i = distances.decode(reader)? as usize;
+        let distance = *DISTANCE_BASE.get(i)? as usize + reader.bits(DISTANCE_EXTRA[i])? as usize;
+        if distance > out.len() || out.len() + length > limit {
+            return None;
+        }
+        // The copy may overlap the bytes being written, which repeats them.
+        let start = out.len() - distance;
+        for j in 0..length {
+            out.push(out[start + j]);
+        }
+    }
+}
+
+/// Returns the fixed Huffman codes for the literals/lengths and the distances.
+/// https://datatracker.ietf.org/doc/html/rfc1951#section-3.2.6
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [0; 288];
+    for (symbol, length) in lengths.iter_mut().enumerate() {
+        *length = match symbol {
+            0..=143 => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _ => 8,
+        };
+    }
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+/// Reads the code lengths of the Huffman codes of a block with dynamic codes.
+/// https://datatracker.ietf.org/doc/html/rfc1951#section-3.2.7
+fn dynamic_codes(reader: &mut BitReader) -> Option<(Huffman, Huffman)> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return None;
+    }
+
+    let mut code_lengths = [0; 19];
+    for i in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+        code_lengths[*i] = reader.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_lengths);
+
+    let mut lengths = vec![0; literal_count + distance_count];
+    let mut i = 0;
+    while i < lengths.len() {
+        let (value, repeat) = match code_length_code.decode(reader)? {
+            length @ 0..=15 => (length as u8, 1),
+            // "Copy the previous code length 3 - 6 times."
+            16 => (*lengths.get(i.checked_sub(1)?)?, 3 + reader.bits(2)?),
+            // "Repeat a code length of 0 for 3 - 10 times."
+            17 => (0, 3 + reader.bits(3)?),
+            // "Repeat a code length of 0 for 11 - 138 times."
+            _ => (0, 11 + reader.bits(7)?),
+        };
+        for _ in 0..repeat {
+            *lengths.get_mut(i)? = value;
+            i += 1;
+        }
+    }
+    // The end of block must have a code.
+    if lengths[256] == 0 {
+        return None;
+    }
+    Some((
+        Huffman::new(&lengths[..literal_count]),
+        Huffman::new(&lengths[literal_count..]),
+    ))
+}
+
+/// A canonical Huffman code.
+/// https://datatracker.ietf.org/doc/html/rfc1951#section-3.2.2
+struct Huffman {
+    /// The number of the codes of each length.
+    counts: [u16; MAX_BITS + 1],
+    /// The symbols ordered by their codes.
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Builds the code from the code length of each symbol, where 0 means the symbol isn't
+    /// used.
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0; MAX_BITS + 1];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        counts[0] = 0;
+        // The codes of the same length are consecutive and ordered by their symbols.
+        let mut symbols = Vec::with_capacity(lengths.len());
+        for length in 1..=MAX_BITS {
+            for (symbol, l) in lengths.iter().enumerate() {
+                if *l as usize == length {
+                    symbols.push(symbol as u16);
+                }
+            }
+        }
+        Self { counts, symbols }
+    }
+
+    /// Reads a code bit by bit, from the most significant bit, and returns its symbol.
+    fn decode(&self, reader: &mut BitReader) -> Option<u16> {
+        // `first` is the first code of the length, and `index` is the index of its symbol.
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..=MAX_BITS {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - first < count {
+                return self.symbols.get((index + code - first) as usize).copied();
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        None
+    }
+}
+
+/// Reads bits from the least significant bit of each byte.
+struct BitReader<'a> {
+    data: &'a [u8],
+    /// The index of the current byte.
+    pos: usize,
+    /// The number of the bits read in the current byte.
+    bit: u8,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            bit: 0,
+        }
+    }
+
+    /// Reads `count` bits as a number whose least significant bit comes first.
+    fn bits(&mut self, count: u8) -> Option<u32> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = *self.data.get(self.pos)?;
+            value |= (((byte >> self.bit) & 1) as u32) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.pos += 1;
+            }
+        }
+        Some(value)
+    }
+
+    /// Skips the rest of the current byte.
+    fn align(&mut self) {
+        if self.bit > 0 {
+            self.bit = 0;
+            self.pos += 1;
+        }
+    }
+}
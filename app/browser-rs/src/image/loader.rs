@@ -0,0 +1,42 @@
+//! Loading of the images of img elements.
+//! https://html.spec.whatwg.org/multipage/images.html#update-the-image-data
+
+use crate::image::decode;
+use crate::parser::dom::*;
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+#[allow(unused_imports)]
+use liumlib::*;
+
+/// Fetches and decodes the images of the img elements in `document`. `fetch` returns the body of
+/// the resource at a src, or None if it can't be fetched. An image which fails to load or to
+/// decode is left unavailable, and its element is rendered as an empty box.
+pub fn load_images<F>(document: &mut Document, fetch: F)
+where
+    F: Fn(&str) -> Option<Vec<u8>>,
+{
+    let requests: Vec<(NodeId, _)> = document
+        .descendants(document.root())
+        .filter_map(|id| {
+            let element = document.node(id).element()?;
+            if element.tag_name() != "img" {
+                return None;
+            }
+            // "If the element does not use srcset or picture, and it has a src attribute
+            // specified whose value is not the empty string, then let selected source be the
+            // value of the element's src attribute."
+            match element.get_attribute("src") {
+                Some(src) if !src.is_empty() => Some((id, src)),
+                _ => None,
+            }
+        })
+        .collect();
+
+    for (id, src) in requests {
+        match fetch(&src).and_then(|data| decode(&data)) {
+            Some(image) => document.set_image(id, Rc::new(image)),
+            None => warn!("failed to load an image: {}", src),
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! A decoder of non-interlaced PNG files with 8 or 16 bits per sample.
+//! https://www.w3.org/TR/png/
+
+use crate::image::inflate::zlib_decompress;
+use crate::image::{is_valid_size, Image};
+use crate::style::values::*;
+use alloc::vec::Vec;
+
+/// https://www.w3.org/TR/png/#5PNG-file-signature
+pub const SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+/// https://www.w3.org/TR/png/#6Colour-values
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ColorType {
+    Greyscale,
+    Truecolor,
+    IndexedColor,
+    GreyscaleWithAlpha,
+    TruecolorWithAlpha,
+}
+
+impl ColorType {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(ColorType::Greyscale),
+            2 => Some(ColorType::Truecolor),
+            3 => Some(ColorType::IndexedColor),
+            4 => Some(ColorType::GreyscaleWithAlpha),
+            6 => Some(ColorType::TruecolorWithAlpha),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of the samples in a pixel.
+    fn channels(&self) -> usize {
+        match self {
+            ColorType::Greyscale | ColorType::IndexedColor => 1,
+            ColorType::GreyscaleWithAlpha => 2,
+            ColorType::Truecolor => 3,
+            ColorType::TruecolorWithAlpha => 4,
+        }
+    }
+}
+
+/// Decodes a PNG file. Returns None if it's broken, interlaced, larger than `MAX_SIZE`, or has
+/// less than 8 bits per sample. The CRCs of the chunks aren't checked.
+pub fn decode(data: &[u8]) -> Option<Image> {
+    if !data.starts_with(&SIGNATURE) {
+        return None;
+    }
+    let mut header = None;
+    let mut palette: Vec<Color> = Vec::new();
+    let mut compressed = Vec::new();
+    let mut pos = SIGNATURE.len();
+    // https://www.w3.org/TR/png/#5Chunk-layout
+    loop {
+        let length = u32_at(data, pos)? as usize;
+        let kind = data.get(pos + 4..pos + 8)?;
+        let chunk = data.get(pos + 8..(pos + 8).checked_add(length)?)?;
+        pos += 12 + length;
+        match kind {
+            b"IHDR" => header = Some(Header::parse(chunk)?),
+            // https://www.w3.org/TR/png/#11PLTE
+            b"PLTE" => {
+                palette = chunk
+                    .chunks_exact(3)
+                    .map(|c| Color::rgb(c[0], c[1], c[2]))
+                    .collect()
+            }
+            // "tRNS [...] For colour type 3 (indexed-colour), the tRNS chunk contains a series of
+            // one-byte alpha values, corresponding to entries in the PLTE chunk." The
+            // transparent color of the other types isn't supported.
+            // https://www.w3.org/TR/png/#11tRNS
+            b"tRNS" => {
+                for (color, alpha) in palette.iter_mut().zip(chunk) {
+                    *color = Color::rgba(color.r(), color.g(), color.b(), *alpha);
+                }
+            }
+            // "There may be multiple IDAT chunks; if so, they shall appear consecutively, and the
+            // compressed datastream is then the concatenation of the contents of the data fields
+            // of all the IDAT chunks."
+            b"IDAT" => compressed.extend_from_slice(chunk),
+            b"IEND" => break,
+            // Ancillary chunks are ignored.
+            _ => {}
+        }
+    }
+
+    let header = header?;
+    // The scanlines, each of which starts with its filter type. A stream which decompresses to
+    // more is broken.
+    let raw_size = (header.width * header.bytes_per_pixel() + 1) * header.height;
+    let raw = zlib_decompress(&compressed, raw_size)?;
+    let pixels = unfilter(&header, &raw)?;
+    let sample_size = header.bit_depth as usize / 8;
+    let pixel_size = header.color_type.channels() * sample_size;
+    // The most significant byte of a 16-bit sample is enough for 8-bit colors.
+    let sample = |pixel: &[u8], i: usize| pixel[i * sample_size];
+
+    let mut colors = Vec::with_capacity(header.width * header.height);
+    for pixel in pixels.chunks_exact(pixel_size) {
+        colors.push(match header.color_type {
+            ColorType::Greyscale => {
+                let v = sample(pixel, 0);
+                Color::rgb(v, v, v)
+            }
+            ColorType::Truecolor => {
+                Color::rgb(sample(pixel, 0), sample(pixel, 1), sample(pixel, 2))
+            }
+            ColorType::IndexedColor => *palette.get(pixel[0] as usize)?,
+            ColorType::GreyscaleWithAlpha => {
+                let v = sample(pixel, 0);
+                Color::rgba(v, v, v, sample(pixel, 1))
+            }
+            ColorType::TruecolorWithAlpha => Color::rgba(
+                sample(pixel, 0),
+                sample(pixel, 1),
+                sample(pixel, 2),
+                sample(pixel, 3),
+            ),
+        });
+    }
+    Image::new(header.width, header.height, colors)
+}
+
+/// https://www.w3.org/TR/png/#11IHDR
+struct Header {
+    width: usize,
+    height: usize,
+    bit_depth: u8,
+    color_type: ColorType,
+}
+
+impl Header {
+    fn parse(chunk: &[u8]) -> Option<Self> {
+        let width = u32_at(chunk, 0)? as usize;
+        let height = u32_at(chunk, 4)? as usize;
+        let bit_depth = *chunk.get(8)?;
+        let color_type = ColorType::from_u8(*chunk.get(9)?)?;
+        let compression = *chunk.get(10)?;
+        let filter = *chunk.get(11)?;
+        let interlace = *chunk.get(12)?;
+        let supported_depth = match color_type {
+            ColorType::IndexedColor => bit_depth == 8,
+            _ => bit_depth == 8 || bit_depth == 16,
+        };
+        if !is_valid_size(width, height) || !supported_depth {
+            return None;
+        }
+        if compression != 0 || filter != 0 || interlace != 0 {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            bit_depth,
+            color_type,
+        })
+    }
+
+    /// "bpp is defined as the number of bytes per complete pixel, rounding up to one."
+    fn bytes_per_pixel(&self) -> usize {
+        self.color_type.channels() * self.bit_depth as usize / 8
+    }
+}
+
+/// Reverses the filter of each scanline in `raw` and returns the pixels without the filter type
+/// bytes.
+/// https://www.w3.org/TR/png/#9Filter-types
+fn unfilter(header: &Header, raw: &[u8]) -> Option<Vec<u8>> {
+    let bpp = header.bytes_per_pixel();
+    let stride = header.width.checked_mul(bpp)?;
+    if raw.len() < (stride + 1).checked_mul(header.height)? {
+        return None;
+    }
+
+    let mut pixels: Vec<u8> = Vec::with_capacity(stride * header.height);
+    for (y, line) in raw.chunks_exact(stride + 1).take(header.height).enumerate() {
+        let filter = line[0];
+        let start = y * stride;
+        for (x, byte) in line[1..].iter().enumerate() {
+            // a: the byte of the pixel on the left, b: above, c: above the left.
+            let a = if x >= bpp { pixels[start + x - bpp] } else { 0 };
+            let b = if y > 0 { pixels[start - stride + x] } else { 0 };
+            let c = if x >= bpp && y > 0 {
+                pixels[start - stride + x - bpp]
+            } else {
+                0
+            };
+            let predictor = match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return None,
+            };
+            pixels.push(byte.wrapping_add(predictor));
+        }
+    }
+    Some(pixels)
+}
+
+/// https://www.w3.org/TR/png/#9Filter-type-4-Paeth
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
@@ -0,0 +1,96 @@
+//! A decoder of PPM files in the binary (P6) and the plain (P3) formats.
+//! http://netpbm.sourceforge.net/doc/ppm.html
+
+use crate::image::{is_valid_size, Image};
+use crate::style::values::*;
+use alloc::vec::Vec;
+
+/// Decodes a PPM file. Only the first image is decoded if the file has more than one. Returns None
+/// if it's larger than `MAX_SIZE` or broken.
+pub fn decode(data: &[u8]) -> Option<Image> {
+    let plain = match data.get(..2)? {
+        b"P6" => false,
+        b"P3" => true,
+        _ => return None,
+    };
+    let mut reader = Reader { data, pos: 2 };
+    let width = reader.number()? as usize;
+    let height = reader.number()? as usize;
+    let max = reader.number()?;
+    if max == 0 || max > 65535 || !is_valid_size(width, height) {
+        return None;
+    }
+    let count = width.checked_mul(height)?;
+
+    // "Each sample is represented in pure binary by either 1 or 2 bytes. If the Maxval is less
+    // than 256, it is 1 byte. Otherwise, it is 2 bytes. The most significant byte is first."
+    // A single whitespace character separates the header from the raster.
+    let sample_size = if max < 256 { 1 } else { 2 };
+    if !plain {
+        reader.pos += 1;
+        let end = reader
+            .pos
+            .checked_add(count.checked_mul(3 * sample_size)?)?;
+        if end > data.len() {
+            return None;
+        }
+    }
+    let sample = |reader: &mut Reader| -> Option<u8> {
+        let value = if plain {
+            reader.number()?
+        } else if sample_size == 1 {
+            reader.byte()? as u32
+        } else {
+            ((reader.byte()? as u32) << 8) | reader.byte()? as u32
+        };
+        // Scale the sample to 0..=255.
+        Some(((value.min(max) * 255 + max / 2) / max) as u8)
+    };
+
+    let mut pixels = Vec::with_capacity(count);
+    for _ in 0..count {
+        let r = sample(&mut reader)?;
+        let g = sample(&mut reader)?;
+        let b = sample(&mut reader)?;
+        pixels.push(Color::rgb(r, g, b));
+    }
+    Image::new(width, height, pixels)
+}
+
+/// Reads the tokens of a PPM file.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Option<u8> {
+        let b = *self.data.get(self.pos)?;
+        self.pos += 1;
+        Some(b)
+    }
+
+    /// Reads a decimal number after whitespace and comments, which start with `#` and continue
+    /// to the end of the line.
+    fn number(&mut self) -> Option<u32> {
+        loop {
+            match *self.data.get(self.pos)? {
+                b'#' => {
+                    while *self.data.get(self.pos)? != b'\n' {
+                        self.pos += 1;
+                    }
+                }
+                b if b.is_ascii_whitespace() => self.pos += 1,
+                _ => break,
+            }
+        }
+        let start = self.pos;
+        while self.data.get(self.pos).is_some_and(|b| b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        core::str::from_utf8(&self.data[start..self.pos])
+            .ok()?
+            .parse()
+            .ok()
+    }
+}
@@ -0,0 +1,20 @@
+//! This is a part of JavaScript support, which runs the scripts in documents.
+//! https://tc39.es/ecma262/
+
+pub mod ast;
+pub mod builtins;
+pub mod console;
+pub mod cookie;
+pub mod dialog;
+pub mod dom;
+pub mod engine;
+pub mod event;
+pub mod heap;
+pub mod json;
+pub mod lexer;
+pub mod location;
+pub mod math;
+pub mod parser;
+pub mod runtime;
+pub mod timers;
+pub mod value;
@@ -0,0 +1,157 @@
+//! The abstract syntax tree of a script, which is built by the parser.
+//! https://tc39.es/ecma262/#sec-ecmascript-language-statements-and-declarations
+
+use crate::js::lexer::Position;
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// https://tc39.es/ecma262/#prod-Script
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    pub body: Vec<Statement>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    pub kind: StatementKind,
+    /// Where the statement starts.
+    pub pos: Position,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatementKind {
+    /// An expression followed by a semicolon, e.g. `f(1);`.
+    Expression(Expression),
+    /// e.g. `var a = 1, b;`
+    Variable {
+        kind: VariableKind,
+        declarations: Vec<VariableDeclarator>,
+    },
+    /// e.g. `function f(a, b) { return a + b; }`
+    Function(Rc<Function>),
+    Return(Option<Expression>),
+    If {
+        test: Expression,
+        consequent: Box<Statement>,
+        alternate: Option<Box<Statement>>,
+    },
+    While {
+        test: Expression,
+        body: Box<Statement>,
+    },
+    /// e.g. `for (let i = 0; i < 3; i++) {}`. `init` is a variable declaration or an expression
+    /// statement.
+    For {
+        init: Option<Box<Statement>>,
+        test: Option<Expression>,
+        update: Option<Expression>,
+        body: Box<Statement>,
+    },
+    Block(Vec<Statement>),
+    Break,
+    Continue,
+    /// A lone semicolon.
+    Empty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableKind {
+    Var,
+    Let,
+    Const,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableDeclarator {
+    pub name: String,
+    pub init: Option<Expression>,
+}
+
+/// A function declaration or expression, which is shared by the closures made of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    /// None for an anonymous function expression.
+    pub name: Option<String>,
+    pub params: Vec<String>,
+    pub body: Vec<Statement>,
+    pub pos: Position,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    /// Where the expression starts.
+    pub pos: Position,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpressionKind {
+    Number(f64),
+    String(String),
+    Boolean(bool),
+    Null,
+    Identifier(String),
+    This,
+    /// e.g. `[1, 2]`
+    Array(Vec<Expression>),
+    /// e.g. `{a: 1, "b": 2}`. The keys are in the order they're written.
+    Object(Vec<(String, Expression)>),
+    Function(Rc<Function>),
+    /// e.g. `!a`, `-a` or `typeof a`.
+    Unary {
+        operator: &'static str,
+        argument: Box<Expression>,
+    },
+    /// `++` or `--` before or after a target.
+    Update {
+        operator: &'static str,
+        prefix: bool,
+        argument: Box<Expression>,
+    },
+    /// e.g. `a + b` or `a instanceof b`.
+    Binary {
+        operator: &'static str,
+        left: Box<Expression>,
+        right: Box<Expression>,
+    },
+    /// `&&`, `||` or `??`, which evaluate the right side only when it's needed.
+    Logical {
+        operator: &'static str,
+        left: Box<Expression>,
+        right: Box<Expression>,
+    },
+    /// e.g. `a = b` or `a.b += c`. The target is an identifier, a member or an index.
+    Assignment {
+        operator: &'static str,
+        target: Box<Expression>,
+        value: Box<Expression>,
+    },
+    /// `test ? consequent : alternate`
+    Conditional {
+        test: Box<Expression>,
+        consequent: Box<Expression>,
+        alternate: Box<Expression>,
+    },
+    Call {
+        callee: Box<Expression>,
+        arguments: Vec<Expression>,
+    },
+    New {
+        callee: Box<Expression>,
+        arguments: Vec<Expression>,
+    },
+    /// e.g. `a.b`
+    Member {
+        object: Box<Expression>,
+        name: String,
+    },
+    /// e.g. `a[b]`
+    Index {
+        object: Box<Expression>,
+        index: Box<Expression>,
+    },
+    /// Expressions separated by commas, e.g. `i++, j--`.
+    Sequence(Vec<Expression>),
+}
@@ -0,0 +1,212 @@
+//! The standard built-in objects which scripts use the most, e.g. `"a,b".split(",")`,
+//! `[1, 2].map(f)` and `Math.floor(x)`. Only a subset of their methods is implemented.
+//! https://tc39.es/ecma262/#sec-fundamental-objects
+
+use crate::js::math::{floor, trunc};
+use crate::js::runtime::{argument, Exception, Runtime};
+use crate::js::value::*;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use liumlib::rand::Rng;
+
+/// A built-in method, which is called with the runtime, `this` and the arguments.
+type Method = fn(&mut Runtime, Value, Vec<Value>) -> Result<Value, Exception>;
+
+/// Defines the methods of strings and arrays and the global `Math` in `runtime`.
+pub fn install(runtime: &mut Runtime) {
+    let string = runtime.string_prototype();
+    method(string, "slice", string_slice);
+    method(string, "indexOf", string_index_of);
+    method(string, "split", string_split);
+
+    let array = runtime.array_prototype();
+    method(array, "push", array_push);
+    method(array, "map", array_map);
+    method(array, "join", array_join);
+
+    let math = ObjectRef::ordinary();
+    method(&math, "floor", |_, _, args| {
+        Ok(Value::Number(floor(argument(&args, 0).to_number())))
+    });
+    let rng = RefCell::new(Rng::new());
+    let random: NativeFunction =
+        Rc::new(move |_, _, _| Ok(Value::Number(rng.borrow_mut().next_f64())));
+    math.set(
+        "random",
+        Value::Object(ObjectRef::native_function("random", random)),
+    );
+    runtime.define_global("Math", Value::Object(math));
+}
+
+fn method(object: &ObjectRef, name: &str, f: Method) {
+    object.set(
+        name,
+        Value::Object(ObjectRef::native_function(name, Rc::new(f))),
+    );
+}
+
+/// Returns `this` of a method of strings as a string, or an error if it's undefined or null.
+fn this_string(this: &Value, name: &str) -> Result<Vec<char>, Exception> {
+    match this {
+        Value::Undefined | Value::Null => Err(Exception::type_error(&format!(
+            "String.prototype.{} called on {}",
+            name, this
+        ))),
+        _ => Ok(format!("{}", this).chars().collect()),
+    }
+}
+
+/// Returns `this` of a method of arrays, or an error if it isn't an array.
+fn this_array(this: &Value, name: &str) -> Result<ObjectRef, Exception> {
+    match this {
+        Value::Object(o) if matches!(o.borrow().kind, ObjectKind::Array(_)) => Ok(o.clone()),
+        _ => Err(Exception::type_error(&format!(
+            "Array.prototype.{} called on a non-array",
+            name
+        ))),
+    }
+}
+
+/// Returns an index argument in 0..=`len`, where a negative one counts from the end, or
+/// `default` if it's undefined.
+/// https://tc39.es/ecma262/#sec-tointegerorinfinity
+fn relative_index(value: &Value, len: usize, default: usize) -> usize {
+    if let Value::Undefined = value {
+        return default;
+    }
+    let n = value.to_number();
+    if n.is_nan() {
+        return 0;
+    }
+    let n = trunc(n);
+    if n < 0.0 {
+        let n = len as f64 + n;
+        if n < 0.0 {
+            0
+        } else {
+            n as usize
+        }
+    } else if n > len as f64 {
+        len
+    } else {
+        n as usize
+    }
+}
+
+/// https://tc39.es/ecma262/#sec-string.prototype.slice
+fn string_slice(_: &mut Runtime, this: Value, args: Vec<Value>) -> Result<Value, Exception> {
+    let s = this_string(&this, "slice")?;
+    let start = relative_index(&argument(&args, 0), s.len(), 0);
+    let end = relative_index(&argument(&args, 1), s.len(), s.len());
+    if start >= end {
+        return Ok(Value::String(String::new()));
+    }
+    Ok(Value::String(s[start..end].iter().collect()))
+}
+
+/// Returns the index of the first `search` from `position`, or -1 if it isn't found.
+/// https://tc39.es/ecma262/#sec-string.prototype.indexof
+fn string_index_of(_: &mut Runtime, this: Value, args: Vec<Value>) -> Result<Value, Exception> {
+    let s = this_string(&this, "indexOf")?;
+    let search: Vec<char> = format!("{}", argument(&args, 0)).chars().collect();
+    // A negative position is 0 rather than one from the end.
+    let position = match argument(&args, 1) {
+        Value::Undefined => 0,
+        position if position.to_number() < 0.0 => 0,
+        position => relative_index(&position, s.len(), 0),
+    };
+    let found = (position..=s.len())
+        .find(|&i| s.len() - i >= search.len() && s[i..i + search.len()] == search[..]);
+    Ok(Value::Number(match found {
+        Some(i) => i as f64,
+        None => -1.0,
+    }))
+}
+
+/// Splits a string by a separator into an array of at most `limit` strings. An empty separator
+/// splits it into characters, and no separator makes an array of the whole string.
+/// https://tc39.es/ecma262/#sec-string.prototype.split
+fn string_split(runtime: &mut Runtime, this: Value, args: Vec<Value>) -> Result<Value, Exception> {
+    let s: String = this_string(&this, "split")?.into_iter().collect();
+    let limit = match argument(&args, 1) {
+        Value::Undefined => u32::MAX,
+        limit => limit.to_uint32(),
+    } as usize;
+    let parts: Vec<Value> = match argument(&args, 0) {
+        Value::Undefined => alloc::vec![Value::String(s)],
+        separator => {
+            let separator = format!("{}", separator);
+            if separator.is_empty() {
+                s.chars().map(|c| Value::String(String::from(c))).collect()
+            } else {
+                s.split(separator.as_str()).map(Value::string).collect()
+            }
+        }
+    };
+    let parts = parts.into_iter().take(limit).collect();
+    Ok(Value::Object(runtime.array(parts)))
+}
+
+/// Appends the arguments to an array, and returns its new length.
+/// https://tc39.es/ecma262/#sec-array.prototype.push
+fn array_push(_: &mut Runtime, this: Value, args: Vec<Value>) -> Result<Value, Exception> {
+    let array = this_array(&this, "push")?;
+    let mut array = array.borrow_mut();
+    match &mut array.kind {
+        ObjectKind::Array(elements) => {
+            elements.extend(args);
+            Ok(Value::Number(elements.len() as f64))
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Returns a new array of the results of calling a function with each element, its index and
+/// the array.
+/// https://tc39.es/ecma262/#sec-array.prototype.map
+fn array_map(runtime: &mut Runtime, this: Value, args: Vec<Value>) -> Result<Value, Exception> {
+    let array = this_array(&this, "map")?;
+    let callback = argument(&args, 0);
+    if !callback.is_callable() {
+        return Err(Exception::type_error(&format!(
+            "{:?} is not a function",
+            callback
+        )));
+    }
+    let this_arg = argument(&args, 1);
+    // The elements appended by the callback aren't visited.
+    let length = array.get("length").to_uint32();
+    let mut results = Vec::new();
+    for i in 0..length {
+        let element = array.get(&format!("{}", i));
+        let arguments = alloc::vec![element, Value::Number(i as f64), this.clone()];
+        results.push(runtime.call(&callback, this_arg.clone(), arguments)?);
+    }
+    Ok(Value::Object(runtime.array(results)))
+}
+
+/// Returns the elements of an array joined by a separator, which is "," by default. Undefined
+/// and null are empty strings.
+/// https://tc39.es/ecma262/#sec-array.prototype.join
+fn array_join(_: &mut Runtime, this: Value, args: Vec<Value>) -> Result<Value, Exception> {
+    let array = this_array(&this, "join")?;
+    let separator = match argument(&args, 0) {
+        Value::Undefined => String::from(","),
+        separator => format!("{}", separator),
+    };
+    let elements = match &array.borrow().kind {
+        ObjectKind::Array(elements) => elements.clone(),
+        _ => unreachable!(),
+    };
+    let strings: Vec<String> = elements
+        .iter()
+        .map(|element| match element {
+            Value::Undefined | Value::Null => String::new(),
+            _ => format!("{}", element),
+        })
+        .collect();
+    Ok(Value::String(strings.join(&separator)))
+}
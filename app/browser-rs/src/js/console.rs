@@ -0,0 +1,132 @@
+//! The console of scripts, which prints their messages on the console of liumOS so that they
+//! can be debugged, e.g. `console.log("x =", x)`.
+//! https://console.spec.whatwg.org/
+
+use crate::js::runtime::Runtime;
+use crate::js::value::*;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+// println! is the one of std with the std feature.
+#[cfg(not(feature = "std"))]
+use liumlib::*;
+
+/// The objects and arrays nested deeper than this are shown only by their kinds, which also
+/// stops at a cycle.
+const MAX_DEPTH: usize = 2;
+
+/// The severity of a message.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Level {
+    Log,
+    Warn,
+    Error,
+}
+
+/// Prints the messages of the scripts of a page.
+#[derive(Debug, Clone)]
+pub struct Console {
+    /// The URL of the page, which tags the messages, or None if the document has no URL.
+    url: Option<String>,
+}
+
+impl Console {
+    pub fn new(url: Option<String>) -> Self {
+        Self { url }
+    }
+
+    /// Defines the global `console`, whose methods print with this console.
+    pub fn install(self: &Rc<Self>, runtime: &mut Runtime) {
+        let console = ObjectRef::ordinary();
+        for (name, level) in [
+            ("log", Level::Log),
+            ("info", Level::Log),
+            ("warn", Level::Warn),
+            ("error", Level::Error),
+        ]
+        .iter()
+        {
+            let this = self.clone();
+            let level = *level;
+            let function: NativeFunction = Rc::new(move |_, _, args| {
+                this.print(level, &format_message(&args));
+                Ok(Value::Undefined)
+            });
+            console.set(
+                name,
+                Value::Object(ObjectRef::native_function(name, function)),
+            );
+        }
+        runtime.define_global("console", Value::Object(console));
+    }
+
+    /// Returns `message` as it's printed, e.g. "[http://example.com/] warning: message".
+    pub fn line(&self, level: Level, message: &str) -> String {
+        let mut line = String::new();
+        if let Some(url) = &self.url {
+            line.push_str(&format!("[{}] ", url));
+        }
+        match level {
+            Level::Log => {}
+            Level::Warn => line.push_str("warning: "),
+            Level::Error => line.push_str("error: "),
+        }
+        line.push_str(message);
+        line
+    }
+
+    pub fn print(&self, level: Level, message: &str) {
+        println!("{}", self.line(level, message));
+    }
+}
+
+/// Returns the arguments of `console.log()` in a message, which are separated by spaces.
+/// https://console.spec.whatwg.org/#formatter
+pub fn format_message(args: &[Value]) -> String {
+    let parts: Vec<String> = args
+        .iter()
+        .map(|arg| match arg {
+            Value::String(s) => s.clone(),
+            _ => inspect(arg, 0),
+        })
+        .collect();
+    parts.join(" ")
+}
+
+/// Returns `value` as the console shows it in a message, where strings are quoted and the
+/// properties of objects are shown, e.g. `{a: 1, b: [1, "x"]}`.
+pub fn inspect(value: &Value, depth: usize) -> String {
+    let object = match value {
+        Value::Object(object) => object.borrow(),
+        _ => return format!("{:?}", value),
+    };
+    match &object.kind {
+        ObjectKind::Array(_) if depth >= MAX_DEPTH => String::from("[Array]"),
+        ObjectKind::Array(elements) => {
+            let elements: Vec<String> = elements.iter().map(|e| inspect(e, depth + 1)).collect();
+            format!("[{}]", elements.join(", "))
+        }
+        ObjectKind::Ordinary if depth >= MAX_DEPTH => String::from("[Object]"),
+        ObjectKind::Ordinary => {
+            let keys = object.keys();
+            if keys.is_empty() {
+                return String::from("{}");
+            }
+            let properties: Vec<String> = keys
+                .iter()
+                .map(|key| {
+                    let value = object.get_own(key).unwrap_or(Value::Undefined);
+                    format!("{}: {}", key, inspect(&value, depth + 1))
+                })
+                .collect();
+            format!("{{{}}}", properties.join(", "))
+        }
+        ObjectKind::Function(closure) => match &closure.function.name {
+            Some(name) => format!("[Function: {}]", name),
+            None => String::from("[Function (anonymous)]"),
+        },
+        ObjectKind::NativeFunction { name, .. } => format!("[Function: {}]", name),
+        ObjectKind::Host(_) => format!("{}", object),
+    }
+}
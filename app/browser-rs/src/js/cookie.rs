@@ -0,0 +1,38 @@
+//! The cookies of a document, which scripts read and write by `document.cookie` in the cookie
+//! jar of the browser.
+//! https://html.spec.whatwg.org/multipage/dom.html#dom-document-cookie
+
+use crate::cookie::{CookieJar, Source};
+use crate::url::ParsedUrl;
+use alloc::rc::Rc;
+use alloc::string::String;
+use core::cell::RefCell;
+
+/// The cookie jar and the URL of a document. A document without them, e.g. one given by the
+/// command line, has no cookie.
+#[derive(Default)]
+pub struct DocumentCookies {
+    jar: RefCell<Option<(ParsedUrl, Rc<CookieJar>)>>,
+}
+
+impl DocumentCookies {
+    /// Makes the cookies of the document at `url` the ones in `jar`.
+    pub fn set_jar(&self, url: ParsedUrl, jar: Rc<CookieJar>) {
+        self.jar.replace(Some((url, jar)));
+    }
+
+    /// Returns the cookies which scripts can read, e.g. "a=1; b=2", which excludes HttpOnly ones.
+    pub fn get(&self) -> String {
+        match &*self.jar.borrow() {
+            Some((url, jar)) => jar.serialize(url, Source::Script),
+            None => String::new(),
+        }
+    }
+
+    /// Sets a cookie like Set-Cookie, e.g. "a=1; path=/". Only one cookie is set at a time.
+    pub fn set(&self, cookie: &str) {
+        if let Some((url, jar)) = &*self.jar.borrow() {
+            jar.set(url, cookie, Source::Script);
+        }
+    }
+}
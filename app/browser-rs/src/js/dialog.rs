@@ -0,0 +1,78 @@
+//! The simple dialogs of scripts, `alert()` and `confirm()`, which pause the script until the
+//! user answers them. The browser shows them, e.g. as a box over the page.
+//! https://html.spec.whatwg.org/multipage/timers-and-user-prompts.html#simple-dialogs
+
+use crate::js::runtime::{argument, Runtime};
+use crate::js::value::*;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use core::cell::RefCell;
+
+/// The kind of a dialog, which tells the buttons to show.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DialogKind {
+    /// Only OK, by `alert()`.
+    Alert,
+    /// OK and Cancel, by `confirm()`.
+    Confirm,
+}
+
+/// Shows a dialog with a message and waits until the user closes it. Returns true if the user
+/// chooses OK.
+pub type DialogHandler = Rc<dyn Fn(DialogKind, &str) -> bool>;
+
+/// The dialogs of a document, which can't be shown until the browser gives a handler.
+#[derive(Default)]
+pub struct Dialogs {
+    handler: RefCell<Option<DialogHandler>>,
+}
+
+impl Dialogs {
+    pub fn set_handler(&self, handler: DialogHandler) {
+        self.handler.replace(Some(handler));
+    }
+
+    /// Defines `alert()` and `confirm()` in `runtime`, which show the dialogs.
+    pub fn install(self: &Rc<Self>, runtime: &mut Runtime) {
+        // https://html.spec.whatwg.org/multipage/timers-and-user-prompts.html#dom-alert
+        let dialogs = self.clone();
+        let alert: NativeFunction = Rc::new(move |_, _, args| {
+            dialogs.show(DialogKind::Alert, &message(&argument(&args, 0)));
+            Ok(Value::Undefined)
+        });
+        runtime.define_global(
+            "alert",
+            Value::Object(ObjectRef::native_function("alert", alert)),
+        );
+        // https://html.spec.whatwg.org/multipage/timers-and-user-prompts.html#dom-confirm
+        let dialogs = self.clone();
+        let confirm: NativeFunction = Rc::new(move |_, _, args| {
+            let ok = dialogs.show(DialogKind::Confirm, &message(&argument(&args, 0)));
+            Ok(Value::Boolean(ok))
+        });
+        runtime.define_global(
+            "confirm",
+            Value::Object(ObjectRef::native_function("confirm", confirm)),
+        );
+    }
+
+    /// Shows a dialog with the handler and returns the answer. A dialog which can't be shown is
+    /// canceled at once.
+    fn show(&self, kind: DialogKind, message: &str) -> bool {
+        // The handler isn't borrowed while it waits, so that it can be replaced meanwhile.
+        let handler = self.handler.borrow().clone();
+        match handler {
+            Some(handler) => handler(kind, message),
+            None => false,
+        }
+    }
+}
+
+/// Returns the message argument of a dialog, which is empty if it isn't given.
+fn message(value: &Value) -> String {
+    match value {
+        Value::Undefined => String::new(),
+        _ => format!("{}", value),
+    }
+}
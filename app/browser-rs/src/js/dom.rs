@@ -0,0 +1,352 @@
+//! The bindings which let scripts read and change a document, e.g. `document.getElementById`.
+//! A node is an object whose properties like `textContent` are backed by the document.
+//! https://dom.spec.whatwg.org/
+
+use crate::js::cookie::DocumentCookies;
+use crate::js::event::*;
+use crate::js::runtime::{argument, Exception, Runtime};
+use crate::js::value::*;
+use crate::parser::dom::{Document, Element, NodeId, NodeKind};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::any::Any;
+use core::cell::RefCell;
+
+/// A method of a node, which is called with the bindings, `this` and the arguments.
+type Method = fn(&DomBindings, Value, Vec<Value>) -> Result<Value, Exception>;
+
+/// The bindings of a document to a runtime.
+pub struct DomBindings {
+    document: Rc<RefCell<Document>>,
+    /// The objects of the nodes which scripts have got, so that a node is always the same object.
+    wrappers: RefCell<BTreeMap<NodeId, ObjectRef>>,
+    /// The methods of all the nodes, e.g. `appendChild`.
+    node_prototype: ObjectRef,
+    /// The methods of elements, e.g. `getAttribute`, which inherits `node_prototype`.
+    element_prototype: ObjectRef,
+    /// The methods of the document, e.g. `getElementById`, which inherits `node_prototype`.
+    document_prototype: ObjectRef,
+    /// The methods of events, e.g. `preventDefault`.
+    event_prototype: ObjectRef,
+    events: EventListeners,
+    /// The cookies of `document.cookie`.
+    cookies: Rc<DocumentCookies>,
+}
+
+impl DomBindings {
+    /// Binds `document` to `runtime`, where it's the global `document`.
+    pub fn install(runtime: &mut Runtime, document: Rc<RefCell<Document>>) -> Rc<Self> {
+        let node_prototype = ObjectRef::ordinary();
+        let bindings = Rc::new(Self {
+            document,
+            wrappers: RefCell::new(BTreeMap::new()),
+            element_prototype: ObjectRef::with_prototype(ObjectKind::Ordinary, &node_prototype),
+            document_prototype: ObjectRef::with_prototype(ObjectKind::Ordinary, &node_prototype),
+            node_prototype,
+            event_prototype: event_prototype(),
+            events: EventListeners::default(),
+            cookies: Rc::new(DocumentCookies::default()),
+        });
+
+        let node = &bindings.node_prototype;
+        bindings.method(node, "appendChild", append_child);
+        bindings.method(node, "addEventListener", add_event_listener);
+        bindings.method(node, "removeEventListener", remove_event_listener);
+        let element = &bindings.element_prototype;
+        bindings.method(element, "getAttribute", get_attribute);
+        bindings.method(element, "setAttribute", set_attribute);
+        let document = &bindings.document_prototype;
+        bindings.method(document, "getElementById", get_element_by_id);
+        bindings.method(document, "createElement", create_element);
+        bindings.method(document, "createTextNode", create_text_node);
+
+        let root = bindings.document.borrow().root();
+        runtime.define_global("document", bindings.wrap(root));
+        bindings
+    }
+
+    pub fn document(&self) -> &Rc<RefCell<Document>> {
+        &self.document
+    }
+
+    pub fn events(&self) -> &EventListeners {
+        &self.events
+    }
+
+    pub fn event_prototype(&self) -> &ObjectRef {
+        &self.event_prototype
+    }
+
+    pub fn cookies(&self) -> &DocumentCookies {
+        &self.cookies
+    }
+
+    /// Returns the values which the bindings keep alive for scripts, e.g. the objects of the nodes
+    /// and the listeners.
+    pub fn values(&self) -> Vec<Value> {
+        let mut values: Vec<Value> = self
+            .wrappers
+            .borrow()
+            .values()
+            .map(|wrapper| Value::Object(wrapper.clone()))
+            .collect();
+        for prototype in [
+            &self.node_prototype,
+            &self.element_prototype,
+            &self.document_prototype,
+            &self.event_prototype,
+        ]
+        .iter()
+        {
+            values.push(Value::Object((*prototype).clone()));
+        }
+        values.extend(self.events.values());
+        values
+    }
+
+    /// Returns the object of the node `id`.
+    pub fn wrap(&self, id: NodeId) -> Value {
+        if let Some(wrapper) = self.wrappers.borrow().get(&id) {
+            return Value::Object(wrapper.clone());
+        }
+        let prototype = match self.document.borrow().node(id).kind {
+            NodeKind::Document => &self.document_prototype,
+            NodeKind::Element(_) => &self.element_prototype,
+            _ => &self.node_prototype,
+        };
+        let node = NodeObject {
+            document: self.document.clone(),
+            cookies: self.cookies.clone(),
+            id,
+        };
+        let wrapper = ObjectRef::with_prototype(ObjectKind::Host(Rc::new(node)), prototype);
+        // The object may outlive the node in the tree, so its slot isn't reused.
+        self.document.borrow().pin(id);
+        self.wrappers.borrow_mut().insert(id, wrapper.clone());
+        Value::Object(wrapper)
+    }
+
+    /// Returns the node of `value` if it's a node of the document.
+    pub fn node_id(&self, value: &Value) -> Option<NodeId> {
+        let object = value.as_object()?.borrow();
+        match &object.kind {
+            ObjectKind::Host(host) => {
+                let node = host.as_any().downcast_ref::<NodeObject>()?;
+                if Rc::ptr_eq(&node.document, &self.document) {
+                    Some(node.id)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the node of `this` of a method, or an error if it isn't a node.
+    pub fn this_node(&self, this: &Value) -> Result<NodeId, Exception> {
+        self.node_id(this)
+            .ok_or_else(|| Exception::type_error("illegal invocation"))
+    }
+
+    /// Returns true if `ancestor` is `id` or one of its ancestors.
+    fn is_inclusive_ancestor(&self, ancestor: NodeId, id: NodeId) -> bool {
+        let document = self.document.borrow();
+        let mut node = Some(id);
+        while let Some(n) = node {
+            if n == ancestor {
+                return true;
+            }
+            node = document.node(n).parent();
+        }
+        false
+    }
+
+    /// Defines the method `name` of `object`, which is called with the bindings.
+    fn method(self: &Rc<Self>, object: &ObjectRef, name: &str, f: Method) {
+        let bindings = self.clone();
+        let function: NativeFunction = Rc::new(move |_, this, args| f(&bindings, this, args));
+        object.set(
+            name,
+            Value::Object(ObjectRef::native_function(name, function)),
+        );
+    }
+}
+
+/// https://dom.spec.whatwg.org/#dom-node-appendchild
+fn append_child(b: &DomBindings, this: Value, args: Vec<Value>) -> Result<Value, Exception> {
+    let parent = b.this_node(&this)?;
+    let child = argument(&args, 0);
+    let child_id = match b.node_id(&child) {
+        Some(id) => id,
+        None => return Err(Exception::type_error("parameter 1 is not of type 'Node'")),
+    };
+    if b.is_inclusive_ancestor(child_id, parent) {
+        return Err(Exception::new(
+            "HierarchyRequestError",
+            "the new child is an ancestor of the parent",
+        ));
+    }
+    b.document.borrow_mut().append_child(parent, child_id);
+    Ok(child)
+}
+
+/// https://dom.spec.whatwg.org/#dom-element-getattribute
+fn get_attribute(b: &DomBindings, this: Value, args: Vec<Value>) -> Result<Value, Exception> {
+    let id = b.this_node(&this)?;
+    let name = argument(&args, 0).to_string().to_ascii_lowercase();
+    let document = b.document.borrow();
+    let value = document
+        .node(id)
+        .element()
+        .and_then(|e| e.get_attribute(&name));
+    Ok(value.map_or(Value::Null, Value::String))
+}
+
+/// https://dom.spec.whatwg.org/#dom-element-setattribute
+fn set_attribute(b: &DomBindings, this: Value, args: Vec<Value>) -> Result<Value, Exception> {
+    let id = b.this_node(&this)?;
+    let name = argument(&args, 0).to_string().to_ascii_lowercase();
+    let value = argument(&args, 1).to_string();
+    b.document.borrow_mut().set_attribute(id, &name, &value);
+    Ok(Value::Undefined)
+}
+
+/// https://dom.spec.whatwg.org/#dom-nonelementparentnode-getelementbyid
+fn get_element_by_id(b: &DomBindings, _: Value, args: Vec<Value>) -> Result<Value, Exception> {
+    let id = Some(argument(&args, 0).to_string());
+    let found = {
+        let document = b.document.borrow();
+        document.descendants(document.root()).find(|n| {
+            document
+                .node(*n)
+                .element()
+                .and_then(|e| e.get_attribute("id"))
+                == id
+        })
+    };
+    Ok(found.map_or(Value::Null, |node| b.wrap(node)))
+}
+
+/// https://dom.spec.whatwg.org/#dom-document-createelement
+fn create_element(b: &DomBindings, _: Value, args: Vec<Value>) -> Result<Value, Exception> {
+    let tag = argument(&args, 0).to_string().to_ascii_lowercase();
+    let element = Element::from_tag(&tag, Vec::new());
+    let id = b.document.borrow_mut().create_element(element);
+    Ok(b.wrap(id))
+}
+
+/// https://dom.spec.whatwg.org/#dom-document-createtextnode
+fn create_text_node(b: &DomBindings, _: Value, args: Vec<Value>) -> Result<Value, Exception> {
+    let data = argument(&args, 0).to_string();
+    let id = b.document.borrow_mut().create_text(&data);
+    Ok(b.wrap(id))
+}
+
+/// The properties of a node which are backed by the document.
+struct NodeObject {
+    document: Rc<RefCell<Document>>,
+    cookies: Rc<DocumentCookies>,
+    id: NodeId,
+}
+
+impl HostObject for NodeObject {
+    fn class_name(&self) -> &str {
+        match self.document.borrow().node(self.id).kind {
+            NodeKind::Document => "HTMLDocument",
+            NodeKind::Element(_) => "HTMLElement",
+            NodeKind::Text(_) => "Text",
+            _ => "Node",
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<Value> {
+        let document = self.document.borrow();
+        let node = document.node(self.id);
+        match key {
+            // https://dom.spec.whatwg.org/#dom-node-nodetype
+            "nodeType" => Some(Value::Number(match node.kind {
+                NodeKind::Element(_) => 1.0,
+                NodeKind::Text(_) => 3.0,
+                NodeKind::Comment(_) => 8.0,
+                NodeKind::Document => 9.0,
+                NodeKind::DocumentType { .. } => 10.0,
+                NodeKind::DocumentFragment => 11.0,
+            })),
+            // https://dom.spec.whatwg.org/#dom-element-tagname
+            "tagName" => Some(Value::String(
+                node.element()?.tag_name().to_ascii_uppercase(),
+            )),
+            // https://dom.spec.whatwg.org/#dom-element-id
+            "id" => Some(Value::String(
+                node.element()?.get_attribute("id").unwrap_or_default(),
+            )),
+            // https://dom.spec.whatwg.org/#dom-node-textcontent
+            "textContent" => Some(match node.kind {
+                NodeKind::Document | NodeKind::DocumentType { .. } => Value::Null,
+                _ => Value::String(document.text_content(self.id)),
+            }),
+            // https://html.spec.whatwg.org/multipage/dynamic-markup-insertion.html#dom-element-innerhtml
+            "innerHTML" => {
+                node.element()?;
+                Some(Value::String(document.inner_html(self.id)))
+            }
+            "cookie" if node.kind == NodeKind::Document => Some(Value::String(self.cookies.get())),
+            _ => None,
+        }
+    }
+
+    fn set(&self, key: &str, value: &Value) -> bool {
+        let mut document = self.document.borrow_mut();
+        match key {
+            "cookie" if document.node(self.id).kind == NodeKind::Document => {
+                self.cookies.set(&format!("{}", value));
+                true
+            }
+            "innerHTML" if document.node(self.id).element().is_some() => {
+                let html = match value {
+                    Value::Null => String::new(),
+                    _ => format!("{}", value),
+                };
+                document.set_inner_html(self.id, &html);
+                true
+            }
+            "id" if document.node(self.id).element().is_some() => {
+                document.set_attribute(self.id, "id", &format!("{}", value));
+                true
+            }
+            // https://dom.spec.whatwg.org/#dom-node-textcontent
+            "textContent" => {
+                let text = match value {
+                    Value::Null => String::new(),
+                    _ => format!("{}", value),
+                };
+                match document.node(self.id).kind {
+                    NodeKind::Text(_) => document.node_mut(self.id).kind = NodeKind::Text(text),
+                    NodeKind::Comment(_) => {
+                        document.node_mut(self.id).kind = NodeKind::Comment(text)
+                    }
+                    NodeKind::Element(_) | NodeKind::DocumentFragment => {
+                        // The children are replaced with a text node.
+                        while let Some(child) = document.node(self.id).first_child() {
+                            document.remove_child(self.id, child);
+                        }
+                        if !text.is_empty() {
+                            let child = document.create_text(&text);
+                            document.append_child(self.id, child);
+                        }
+                    }
+                    _ => {}
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
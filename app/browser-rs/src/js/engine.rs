@@ -0,0 +1,267 @@
+//! The scripting of a document, which runs its scripts in a runtime bound to it.
+//! https://html.spec.whatwg.org/multipage/webappapis.html#enabling-and-disabling-scripting
+
+use crate::cookie::CookieJar;
+use crate::js::console::{Console, Level};
+use crate::js::dialog::{DialogHandler, Dialogs};
+use crate::js::dom::DomBindings;
+use crate::js::event::{dispatch, Event};
+use crate::js::location::Location;
+use crate::js::runtime::{Exception, Runtime};
+use crate::js::timers::{self, Clock, Timers};
+use crate::js::value::Value;
+use crate::parser::dom::{Document, NodeId, Scripting};
+use crate::timing::{milliseconds, nanos_since};
+use crate::url::ParsedUrl;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::mem;
+use liumlib::time::Instant;
+use liumlib::*;
+
+/// Returns the source of the external script at a src, or None if it can't be fetched.
+pub type ScriptFetcher = Rc<dyn Fn(&str) -> Option<String>>;
+
+/// A runtime for the scripts of a document. The document isn't owned by the engine; it's lent
+/// to the bindings only while a script runs, so that the parser or the page keeps owning it.
+pub struct Engine {
+    runtime: Runtime,
+    bindings: Rc<DomBindings>,
+    console: Rc<Console>,
+    timers: Rc<RefCell<Timers>>,
+    dialogs: Rc<Dialogs>,
+    /// The global `location`, which is None if the document has no URL.
+    location: Option<Rc<Location>>,
+    /// Fetches external scripts, which are skipped if it's None.
+    fetch: Option<ScriptFetcher>,
+    /// The nanoseconds spent in `fetch` so far.
+    fetch_time: u64,
+    /// The sources of the async and defer scripts fetched during parsing, in the document order.
+    deferred: Vec<String>,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine {
+    /// Creates an engine for a document without a URL, e.g. one given by the command line.
+    pub fn new() -> Self {
+        Self::with_console(Console::new(None), None)
+    }
+
+    /// Creates an engine for the document at `url`, which tags the messages of its console and
+    /// is the global `location`.
+    pub fn with_url(url: &str) -> Self {
+        let location = ParsedUrl::parse(url).map(|url| Rc::new(Location::new(url)));
+        Self::with_console(Console::new(Some(String::from(url))), location)
+    }
+
+    fn with_console(console: Console, location: Option<Rc<Location>>) -> Self {
+        let mut runtime = Runtime::new();
+        let document = Rc::new(RefCell::new(Document::new()));
+        let bindings = DomBindings::install(&mut runtime, document);
+        let console = Rc::new(console);
+        console.install(&mut runtime);
+        let timers = Rc::new(RefCell::new(Timers::new(milliseconds)));
+        Timers::install(&timers, &mut runtime);
+        let dialogs = Rc::new(Dialogs::default());
+        dialogs.install(&mut runtime);
+        if let Some(location) = &location {
+            location.install(&mut runtime);
+        }
+        Self {
+            runtime,
+            bindings,
+            console,
+            timers,
+            dialogs,
+            location,
+            fetch: None,
+            fetch_time: 0,
+            deferred: Vec::new(),
+        }
+    }
+
+    /// Makes the engine run external scripts, which are fetched by `fetch`.
+    pub fn set_fetcher(&mut self, fetch: ScriptFetcher) {
+        self.fetch = Some(fetch);
+    }
+
+    /// Makes `alert()` and `confirm()` show the dialogs with `handler`. Otherwise, they return
+    /// at once as if they're canceled.
+    pub fn set_dialog_handler(&mut self, handler: DialogHandler) {
+        self.dialogs.set_handler(handler);
+    }
+
+    /// Makes `document.cookie` read and write the cookies in `jar`. A document without a URL has
+    /// no cookie.
+    pub fn set_cookie_jar(&mut self, jar: Rc<CookieJar>) {
+        if let Some(location) = &self.location {
+            self.bindings.cookies().set_jar(location.url().clone(), jar);
+        }
+    }
+
+    /// Makes the timers count the time by `clock` instead of the time stamp counter.
+    #[allow(dead_code)]
+    pub fn set_clock(&mut self, clock: Clock) {
+        self.timers.borrow_mut().set_clock(clock);
+    }
+
+    /// Returns the nanoseconds spent in fetching external scripts so far.
+    pub fn fetch_time(&self) -> u64 {
+        self.fetch_time
+    }
+
+    #[allow(dead_code)]
+    pub fn runtime(&mut self) -> &mut Runtime {
+        &mut self.runtime
+    }
+
+    /// Returns the values which the host keeps alive for scripts, which are the roots of the
+    /// heap besides the global scope.
+    fn roots(&self) -> Vec<Value> {
+        let mut roots = self.bindings.values();
+        roots.extend(self.timers.borrow().values());
+        roots
+    }
+
+    /// Drops the objects of scripts in unreachable cycles, and returns how many are dropped.
+    pub fn collect_garbage(&mut self) -> usize {
+        let roots = self.roots();
+        self.runtime.collect_garbage(&roots)
+    }
+
+    /// Collects the garbage if enough objects have been made since the last collection. It's
+    /// called between tasks, when no value of scripts is held by the caller.
+    fn collect_garbage_if_needed(&mut self) {
+        if self.runtime.heap().should_collect() {
+            self.collect_garbage();
+        }
+    }
+
+    /// Runs `source` with `document`, which is the global `document` while it runs.
+    pub fn run(&mut self, document: &mut Document, source: &str) -> Result<Value, Exception> {
+        self.lend(document, |runtime, _| runtime.run(source))
+    }
+
+    /// Calls `f` with the runtime and the bindings while `document` is lent to the bindings.
+    fn lend<R, F>(&mut self, document: &mut Document, f: F) -> R
+    where
+        F: FnOnce(&mut Runtime, &DomBindings) -> R,
+    {
+        let shared = self.bindings.document();
+        shared.replace(mem::take(document));
+        let result = f(&mut self.runtime, &self.bindings);
+        *document = shared.replace(Document::new());
+        result
+    }
+
+    /// Runs the async and defer scripts fetched during parsing, which should be called after
+    /// the parser finishes.
+    /// https://html.spec.whatwg.org/multipage/parsing.html#the-end
+    pub fn run_deferred_scripts(&mut self, document: &mut Document) {
+        for source in mem::take(&mut self.deferred) {
+            self.run_and_report(document, &source);
+        }
+    }
+
+    /// Runs `source` and reports an exception on the console since there's nothing to catch it.
+    /// https://html.spec.whatwg.org/multipage/webappapis.html#report-the-exception
+    fn run_and_report(&mut self, document: &mut Document, source: &str) {
+        if let Err(e) = self.run(document, source) {
+            self.console.print(Level::Error, &format!("Uncaught {}", e));
+        }
+        self.collect_garbage_if_needed();
+    }
+}
+
+/// The document is left when its engine is dropped, and every object of its scripts is dropped
+/// with it even if it's in a cycle.
+impl Drop for Engine {
+    fn drop(&mut self) {
+        let roots = self.roots();
+        self.runtime.clear_heap(&roots);
+    }
+}
+
+impl Scripting for Engine {
+    /// Runs the script element `id` of `document`. A script with src is fetched first, and if
+    /// it's async or defer, it runs after parsing by `run_deferred_scripts()` instead. Async
+    /// scripts are treated like defer ones, so they run in the document order too.
+    /// https://html.spec.whatwg.org/multipage/scripting.html#prepare-the-script-element
+    fn run_script_element(&mut self, document: &mut Document, id: NodeId) {
+        let element = match document.node(id).element() {
+            Some(e) => e,
+            None => return,
+        };
+        let src = match element.get_attribute("src") {
+            Some(src) => src,
+            None => {
+                let source = document.text_content(id);
+                self.run_and_report(document, &source);
+                return;
+            }
+        };
+        let deferred =
+            element.get_attribute("async").is_some() || element.get_attribute("defer").is_some();
+        let fetch = match &self.fetch {
+            Some(fetch) => fetch.clone(),
+            None => return,
+        };
+        if src.is_empty() {
+            return;
+        }
+        let start = Instant::now();
+        let source = fetch(&src);
+        self.fetch_time += nanos_since(start);
+        match source {
+            Some(source) if deferred => self.deferred.push(source),
+            Some(source) => self.run_and_report(document, &source),
+            None => warn!("failed to load a script: {}", src),
+        }
+    }
+
+    fn has_due_tasks(&self) -> bool {
+        self.timers.borrow().has_due()
+    }
+
+    fn until_next_task(&self) -> Option<u64> {
+        self.timers.borrow().until_due()
+    }
+
+    fn run_due_tasks(&mut self, document: &mut Document) {
+        let console = self.console.clone();
+        let timers = self.timers.clone();
+        self.lend(document, |runtime, _| {
+            let mut report = |e: Exception| console.print(Level::Error, &format!("Uncaught {}", e));
+            timers::run_due(&timers, runtime, &mut report)
+        });
+        self.collect_garbage_if_needed();
+    }
+
+    fn take_navigation(&mut self) -> Option<ParsedUrl> {
+        self.location.as_ref()?.take_navigation()
+    }
+
+    fn dispatch_event(
+        &mut self,
+        document: &mut Document,
+        target: NodeId,
+        event_type: &str,
+    ) -> bool {
+        let console = self.console.clone();
+        let event = Rc::new(Event::new(event_type, true, true));
+        let not_canceled = self.lend(document, |runtime, bindings| {
+            let mut report = |e: Exception| console.print(Level::Error, &format!("Uncaught {}", e));
+            dispatch(runtime, bindings, target, event, &mut report)
+        });
+        self.collect_garbage_if_needed();
+        not_canceled
+    }
+}
@@ -0,0 +1,459 @@
+//! Events dispatched to the nodes of a document, e.g. a click on a link, and their listeners,
+//! which are added by `addEventListener()` or by event handler attributes like `onclick`.
+//! https://dom.spec.whatwg.org/#events
+
+use crate::js::dom::DomBindings;
+use crate::js::runtime::{argument, Exception, Runtime};
+use crate::js::value::*;
+use crate::parser::dom::NodeId;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::cell::{Cell, RefCell};
+
+/// https://dom.spec.whatwg.org/#dom-event-eventphase
+const NONE: u8 = 0;
+const CAPTURING_PHASE: u8 = 1;
+const AT_TARGET: u8 = 2;
+const BUBBLING_PHASE: u8 = 3;
+
+/// An event being dispatched, which its listeners can cancel or stop.
+/// https://dom.spec.whatwg.org/#interface-event
+pub struct Event {
+    event_type: String,
+    bubbles: bool,
+    cancelable: bool,
+    target: RefCell<Value>,
+    current_target: RefCell<Value>,
+    phase: Cell<u8>,
+    /// https://dom.spec.whatwg.org/#canceled-flag
+    canceled: Cell<bool>,
+    /// https://dom.spec.whatwg.org/#stop-propagation-flag
+    stopped: Cell<bool>,
+    /// https://dom.spec.whatwg.org/#stop-immediate-propagation-flag
+    stopped_immediately: Cell<bool>,
+}
+
+impl Event {
+    pub fn new(event_type: &str, bubbles: bool, cancelable: bool) -> Self {
+        Self {
+            event_type: String::from(event_type),
+            bubbles,
+            cancelable,
+            target: RefCell::new(Value::Null),
+            current_target: RefCell::new(Value::Null),
+            phase: Cell::new(NONE),
+            canceled: Cell::new(false),
+            stopped: Cell::new(false),
+            stopped_immediately: Cell::new(false),
+        }
+    }
+
+    /// Returns true if a listener has canceled the event by `preventDefault()`.
+    pub fn canceled(&self) -> bool {
+        self.canceled.get()
+    }
+
+    /// https://dom.spec.whatwg.org/#set-the-canceled-flag
+    fn cancel(&self) {
+        if self.cancelable {
+            self.canceled.set(true);
+        }
+    }
+}
+
+impl HostObject for Event {
+    fn class_name(&self) -> &str {
+        "Event"
+    }
+
+    fn get(&self, key: &str) -> Option<Value> {
+        Some(match key {
+            "type" => Value::string(&self.event_type),
+            "bubbles" => Value::Boolean(self.bubbles),
+            "cancelable" => Value::Boolean(self.cancelable),
+            "defaultPrevented" => Value::Boolean(self.canceled.get()),
+            "eventPhase" => Value::Number(self.phase.get() as f64),
+            "target" => self.target.borrow().clone(),
+            "currentTarget" => self.current_target.borrow().clone(),
+            _ => return None,
+        })
+    }
+
+    fn set(&self, _: &str, _: &Value) -> bool {
+        false
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn values(&self) -> Vec<Value> {
+        vec![
+            self.target.borrow().clone(),
+            self.current_target.borrow().clone(),
+        ]
+    }
+}
+
+/// A listener added by `addEventListener()`.
+/// https://dom.spec.whatwg.org/#concept-event-listener
+#[derive(Clone)]
+struct Listener {
+    event_type: String,
+    /// A function, or an object whose `handleEvent` is called.
+    callback: Value,
+    capture: bool,
+    once: bool,
+}
+
+impl Listener {
+    fn is(&self, event_type: &str, callback: &Value, capture: bool) -> bool {
+        self.event_type == event_type
+            && self.callback.strict_equals(callback)
+            && self.capture == capture
+    }
+}
+
+/// The listeners of the nodes of a document.
+#[derive(Default)]
+pub struct EventListeners {
+    listeners: RefCell<BTreeMap<NodeId, Vec<Listener>>>,
+    /// The functions compiled from the event handler attributes, with the sources which they're
+    /// compiled from so that they're compiled again when the attributes change.
+    handlers: RefCell<BTreeMap<(NodeId, String), (String, Value)>>,
+}
+
+impl EventListeners {
+    /// Returns the listeners of `node` for `event_type` in the order in which they're added.
+    fn of(&self, node: NodeId, event_type: &str) -> Vec<Listener> {
+        self.listeners
+            .borrow()
+            .get(&node)
+            .map(|listeners| {
+                listeners
+                    .iter()
+                    .filter(|l| l.event_type == event_type)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns true if `listener` is still added to `node`, which a listener may have removed.
+    fn contains(&self, node: NodeId, listener: &Listener) -> bool {
+        match self.listeners.borrow().get(&node) {
+            Some(listeners) => listeners
+                .iter()
+                .any(|l| l.is(&listener.event_type, &listener.callback, listener.capture)),
+            None => false,
+        }
+    }
+
+    /// Returns the callbacks of the listeners and the functions of the handlers.
+    pub fn values(&self) -> Vec<Value> {
+        let mut values: Vec<Value> = self
+            .listeners
+            .borrow()
+            .values()
+            .flatten()
+            .map(|l| l.callback.clone())
+            .collect();
+        values.extend(self.handlers.borrow().values().map(|(_, f)| f.clone()));
+        values
+    }
+
+    fn remove(&self, node: NodeId, event_type: &str, callback: &Value, capture: bool) {
+        if let Some(listeners) = self.listeners.borrow_mut().get_mut(&node) {
+            listeners.retain(|l| !l.is(event_type, callback, capture));
+        }
+    }
+}
+
+/// A method of events, which is called with the event of `this`.
+type EventMethod = fn(&Event);
+
+/// Creates the prototype of events, which has the methods like `preventDefault`.
+pub fn event_prototype() -> ObjectRef {
+    let prototype = ObjectRef::ordinary();
+    let methods: [(&str, EventMethod); 3] = [
+        // https://dom.spec.whatwg.org/#dom-event-preventdefault
+        ("preventDefault", |e| e.cancel()),
+        // https://dom.spec.whatwg.org/#dom-event-stoppropagation
+        ("stopPropagation", |e| e.stopped.set(true)),
+        // https://dom.spec.whatwg.org/#dom-event-stopimmediatepropagation
+        ("stopImmediatePropagation", |e| {
+            e.stopped.set(true);
+            e.stopped_immediately.set(true);
+        }),
+    ];
+    for (name, method) in methods.iter() {
+        let method = *method;
+        let function: NativeFunction = Rc::new(move |_, this, _| {
+            let host = match this.as_object() {
+                Some(object) => match &object.borrow().kind {
+                    ObjectKind::Host(host) => Some(host.clone()),
+                    _ => None,
+                },
+                None => None,
+            };
+            match host
+                .as_ref()
+                .and_then(|h| h.as_any().downcast_ref::<Event>())
+            {
+                Some(event) => method(event),
+                None => return Err(Exception::type_error("illegal invocation")),
+            }
+            Ok(Value::Undefined)
+        });
+        prototype.set(
+            name,
+            Value::Object(ObjectRef::native_function(name, function)),
+        );
+    }
+    prototype
+}
+
+/// Returns the options of `addEventListener()` or `removeEventListener()`, which are a boolean
+/// for capture or an object.
+/// https://dom.spec.whatwg.org/#event-flatten-more
+fn flatten_options(options: &Value) -> (bool, bool) {
+    match options.as_object() {
+        Some(object) => (
+            object.get("capture").to_boolean(),
+            object.get("once").to_boolean(),
+        ),
+        None => (options.to_boolean(), false),
+    }
+}
+
+/// https://dom.spec.whatwg.org/#dom-eventtarget-addeventlistener
+pub fn add_event_listener(
+    b: &DomBindings,
+    this: Value,
+    args: Vec<Value>,
+) -> Result<Value, Exception> {
+    let node = b.this_node(&this)?;
+    let event_type = format!("{}", argument(&args, 0));
+    let callback = argument(&args, 1);
+    if callback.as_object().is_none() {
+        return Ok(Value::Undefined);
+    }
+    let (capture, once) = flatten_options(&argument(&args, 2));
+    let mut listeners = b.events().listeners.borrow_mut();
+    let listeners = listeners.entry(node).or_default();
+    if !listeners
+        .iter()
+        .any(|l| l.is(&event_type, &callback, capture))
+    {
+        listeners.push(Listener {
+            event_type,
+            callback,
+            capture,
+            once,
+        });
+    }
+    Ok(Value::Undefined)
+}
+
+/// https://dom.spec.whatwg.org/#dom-eventtarget-removeeventlistener
+pub fn remove_event_listener(
+    b: &DomBindings,
+    this: Value,
+    args: Vec<Value>,
+) -> Result<Value, Exception> {
+    let node = b.this_node(&this)?;
+    let event_type = format!("{}", argument(&args, 0));
+    let (capture, _) = flatten_options(&argument(&args, 2));
+    b.events()
+        .remove(node, &event_type, &argument(&args, 1), capture);
+    Ok(Value::Undefined)
+}
+
+/// Dispatches `event` to `target` through the capture, the target and the bubble phases, and
+/// returns false if a listener cancels it. The exceptions thrown by the listeners are given to
+/// `report`, and the other listeners are still called.
+/// https://dom.spec.whatwg.org/#concept-event-dispatch
+pub fn dispatch(
+    runtime: &mut Runtime,
+    bindings: &DomBindings,
+    target: NodeId,
+    event: Rc<Event>,
+    report: &mut dyn FnMut(Exception),
+) -> bool {
+    // The path is the target and its ancestors, which is fixed before any listener runs.
+    let mut path = Vec::new();
+    let mut node = Some(target);
+    while let Some(n) = node {
+        path.push(n);
+        node = bindings.document().borrow().node(n).parent();
+    }
+    let object =
+        ObjectRef::with_prototype(ObjectKind::Host(event.clone()), bindings.event_prototype());
+    runtime.heap().register(&object);
+    let object = Value::Object(object);
+    event.target.replace(bindings.wrap(target));
+
+    let mut dispatcher = Dispatcher {
+        runtime,
+        bindings,
+        event: &event,
+        object,
+        report,
+    };
+    for &node in path[1..].iter().rev() {
+        dispatcher.invoke(node, CAPTURING_PHASE);
+    }
+    dispatcher.invoke(target, AT_TARGET);
+    if event.bubbles {
+        for &node in path[1..].iter() {
+            dispatcher.invoke(node, BUBBLING_PHASE);
+        }
+    }
+
+    event.phase.set(NONE);
+    event.current_target.replace(Value::Null);
+    !event.canceled()
+}
+
+/// The state of a dispatch, which calls the listeners of each node in the path.
+struct Dispatcher<'a> {
+    runtime: &'a mut Runtime,
+    bindings: &'a DomBindings,
+    event: &'a Event,
+    /// The object of the event given to the listeners.
+    object: Value,
+    report: &'a mut dyn FnMut(Exception),
+}
+
+impl<'a> Dispatcher<'a> {
+    /// Calls the listeners of `node` in `phase`, unless the propagation is stopped. At the
+    /// target, the capture listeners are called before the others.
+    /// https://dom.spec.whatwg.org/#concept-event-listener-invoke
+    fn invoke(&mut self, node: NodeId, phase: u8) {
+        if self.event.stopped.get() {
+            return;
+        }
+        self.event.phase.set(phase);
+        let current_target = self.bindings.wrap(node);
+        self.event.current_target.replace(current_target.clone());
+
+        let listeners = self.bindings.events().of(node, &self.event.event_type);
+        if phase != BUBBLING_PHASE {
+            self.call_listeners(node, &listeners, true, &current_target);
+        }
+        if phase != CAPTURING_PHASE {
+            // The event handler is called before the listeners added by `addEventListener()`,
+            // as if it's added first.
+            self.call_handler(node, &current_target);
+            self.call_listeners(node, &listeners, false, &current_target);
+        }
+    }
+
+    /// https://dom.spec.whatwg.org/#concept-event-listener-inner-invoke
+    fn call_listeners(
+        &mut self,
+        node: NodeId,
+        listeners: &[Listener],
+        capture: bool,
+        current_target: &Value,
+    ) {
+        for listener in listeners.iter().filter(|l| l.capture == capture) {
+            if self.event.stopped_immediately.get() {
+                return;
+            }
+            let events = self.bindings.events();
+            if !events.contains(node, listener) {
+                continue;
+            }
+            if listener.once {
+                events.remove(
+                    node,
+                    &listener.event_type,
+                    &listener.callback,
+                    listener.capture,
+                );
+            }
+            let result = if listener.callback.is_callable() {
+                self.runtime.call(
+                    &listener.callback,
+                    current_target.clone(),
+                    vec![self.object.clone()],
+                )
+            } else {
+                self.runtime
+                    .get_property(&listener.callback, "handleEvent")
+                    .and_then(|handle_event| {
+                        self.runtime.call(
+                            &handle_event,
+                            listener.callback.clone(),
+                            vec![self.object.clone()],
+                        )
+                    })
+            };
+            if let Err(e) = result {
+                (self.report)(e);
+            }
+        }
+    }
+
+    /// Calls the event handler of `node` given by its attribute, e.g. `onclick`, which cancels
+    /// the event if it returns false.
+    /// https://html.spec.whatwg.org/multipage/webappapis.html#the-event-handler-processing-algorithm
+    fn call_handler(&mut self, node: NodeId, current_target: &Value) {
+        if self.event.stopped_immediately.get() {
+            return;
+        }
+        let handler = match self.handler(node) {
+            Ok(Some(handler)) => handler,
+            Ok(None) => return,
+            Err(e) => return (self.report)(e),
+        };
+        match self
+            .runtime
+            .call(&handler, current_target.clone(), vec![self.object.clone()])
+        {
+            Ok(Value::Boolean(false)) => self.event.cancel(),
+            Ok(_) => {}
+            Err(e) => (self.report)(e),
+        }
+    }
+
+    /// Returns the function of the event handler attribute of `node`, which is compiled when
+    /// it's called first.
+    /// https://html.spec.whatwg.org/multipage/webappapis.html#getting-the-current-value-of-the-event-handler
+    fn handler(&mut self, node: NodeId) -> Result<Option<Value>, Exception> {
+        let event_type = &self.event.event_type;
+        let name = format!("on{}", event_type);
+        let source = self
+            .bindings
+            .document()
+            .borrow()
+            .node(node)
+            .element()
+            .and_then(|e| e.get_attribute(&name));
+        let source = match source {
+            Some(source) => source,
+            None => return Ok(None),
+        };
+        let key = (node, event_type.clone());
+        let events = self.bindings.events();
+        if let Some((compiled, function)) = events.handlers.borrow().get(&key) {
+            if *compiled == source {
+                return Ok(Some(function.clone()));
+            }
+        }
+        let function = self
+            .runtime
+            .run(&format!("(function {}(event) {{\n{}\n}})", name, source))?;
+        events
+            .handlers
+            .borrow_mut()
+            .insert(key, (source, function.clone()));
+        Ok(Some(function))
+    }
+}
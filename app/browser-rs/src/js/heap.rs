@@ -0,0 +1,153 @@
+//! The ownership of the objects of scripts. An object is reference counted, so it's dropped as
+//! soon as nothing refers to it, except the ones in a cycle, e.g. a function in a variable of its
+//! own scope or `a.self = a`, which would never be dropped. So the heap keeps weak references to
+//! the objects, and a mark-sweep collector clears the ones which can't be reached from the roots
+//! any more, which breaks their cycles.
+//!
+//! The roots are the global scope, the objects of the runtime and the values which the host
+//! holds, e.g. the callbacks of timers. An object which isn't made by the runtime, e.g. a
+//! prototype made by the bindings, is found from the roots and is kept track of from then on.
+//! Every object is cleared when the document is left, which drops the ones kept by the roots.
+//!
+//! A dropped object gives its memory back to the allocator, which reuses it for new objects, so
+//! scripts which make garbage cycles in a loop run in a heap of a fixed size.
+
+use crate::js::runtime::Scope;
+use crate::js::value::*;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+
+/// The objects kept track of at least before the next collection, so that collecting a small
+/// heap too often doesn't take much time.
+const MIN_THRESHOLD: usize = 1024;
+
+/// The objects which scripts have made, which are referred to weakly.
+pub struct Heap {
+    objects: RefCell<Vec<WeakObjectRef>>,
+    /// The number of the objects at which the next collection runs, which is twice as many as
+    /// the ones surviving the last one.
+    threshold: Cell<usize>,
+}
+
+impl Default for Heap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Heap {
+    pub fn new() -> Self {
+        Self {
+            objects: RefCell::new(Vec::new()),
+            threshold: Cell::new(MIN_THRESHOLD),
+        }
+    }
+
+    /// Keeps track of `object`, which is cleared if it's unreachable at a collection.
+    pub fn register(&self, object: &ObjectRef) {
+        self.objects.borrow_mut().push(object.downgrade());
+    }
+
+    /// Returns the number of the objects kept track of, which includes the ones dropped since the
+    /// last collection.
+    pub fn len(&self) -> usize {
+        self.objects.borrow().len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if enough objects have been made since the last collection.
+    pub fn should_collect(&self) -> bool {
+        self.len() >= self.threshold.get()
+    }
+
+    /// Clears the objects which can't be reached from `roots` or the `global` scope, and returns
+    /// how many are cleared. This mustn't be called while a script runs, whose values aren't in
+    /// the roots.
+    pub fn collect(&self, roots: &[Value], global: &Rc<Scope>) -> usize {
+        let reachable = mark(roots, global);
+        let garbage: Vec<ObjectRef> = self
+            .objects
+            .borrow()
+            .iter()
+            .filter_map(|o| o.upgrade())
+            .filter(|o| !reachable.contains_key(&o.address()))
+            .collect();
+        for object in &garbage {
+            object.borrow_mut().clear();
+        }
+        self.objects
+            .replace(reachable.values().map(|o| o.downgrade()).collect());
+        self.threshold.set(MIN_THRESHOLD.max(reachable.len() * 2));
+        // The garbage is dropped here, where nothing refers to it any more.
+        garbage.len()
+    }
+
+    /// Clears every object including the ones reachable from `roots` or the `global` scope, e.g.
+    /// when the document is left, so that no cycle outlives it.
+    pub fn clear(&self, roots: &[Value], global: &Rc<Scope>) {
+        let mut objects: Vec<ObjectRef> = mark(roots, global).into_values().collect();
+        objects.extend(self.objects.take().iter().filter_map(|o| o.upgrade()));
+        for object in &objects {
+            object.borrow_mut().clear();
+        }
+    }
+}
+
+/// Something which refers to the other values, which is visited by the collector.
+enum Node {
+    Object(ObjectRef),
+    Scope(Rc<Scope>),
+}
+
+/// Returns the objects reachable from `roots` and the `global` scope by their addresses. It
+/// doesn't recurse, so that a long chain of objects doesn't overflow the stack.
+fn mark(roots: &[Value], global: &Rc<Scope>) -> BTreeMap<usize, ObjectRef> {
+    let mut objects = BTreeMap::new();
+    let mut visited_scopes = BTreeSet::new();
+    let mut stack = alloc::vec![Node::Scope(global.clone())];
+    push_values(&mut stack, roots.iter());
+    while let Some(node) = stack.pop() {
+        match node {
+            Node::Object(object) => {
+                if objects.contains_key(&object.address()) {
+                    continue;
+                }
+                {
+                    let o = object.borrow();
+                    push_values(&mut stack, o.values().iter());
+                    if let Some(prototype) = &o.prototype {
+                        stack.push(Node::Object(prototype.clone()));
+                    }
+                    if let ObjectKind::Function(closure) = &o.kind {
+                        stack.push(Node::Scope(closure.scope.clone()));
+                    }
+                }
+                objects.insert(object.address(), object);
+            }
+            Node::Scope(scope) => {
+                if !visited_scopes.insert(Rc::as_ptr(&scope) as usize) {
+                    continue;
+                }
+                push_values(&mut stack, scope.values().iter());
+                if let Some(parent) = scope.parent() {
+                    stack.push(Node::Scope(parent.clone()));
+                }
+            }
+        }
+    }
+    objects
+}
+
+fn push_values<'a>(stack: &mut Vec<Node>, values: impl Iterator<Item = &'a Value>) {
+    for value in values {
+        if let Value::Object(object) = value {
+            stack.push(Node::Object(object.clone()));
+        }
+    }
+}
@@ -0,0 +1,519 @@
+//! The `JSON` object, which parses JSON text into values and serializes values into JSON text,
+//! e.g. `JSON.stringify({a: [1, "x"]})` is `{"a":[1,"x"]}`.
+//! https://tc39.es/ecma262/#sec-json-object
+
+use crate::js::runtime::{argument, Exception, Runtime};
+use crate::js::value::*;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// The arrays and objects which can be nested in JSON text or in a value being serialized, so
+/// that a deep one stops with an error before the stack overflows.
+const MAX_DEPTH: usize = 256;
+
+/// The longest indent of a level, which a longer `space` of `JSON.stringify()` is cut to.
+const MAX_GAP: usize = 10;
+
+/// Defines the global `JSON`.
+pub fn install(runtime: &mut Runtime) {
+    let json = ObjectRef::ordinary();
+    let parse: NativeFunction = Rc::new(|runtime, _, args| {
+        let value = parse(runtime, &argument(&args, 0).to_string())?;
+        let reviver = argument(&args, 1);
+        if !reviver.is_callable() {
+            return Ok(value);
+        }
+        let root = ObjectRef::ordinary();
+        root.set("", value);
+        internalize(runtime, &reviver, &root, "")
+    });
+    let stringify: NativeFunction = Rc::new(|runtime, _, args| {
+        let value = argument(&args, 0);
+        let mut serializer = Serializer::new(runtime, &argument(&args, 1), &argument(&args, 2));
+        Ok(match serializer.serialize(value)? {
+            Some(text) => Value::String(text),
+            None => Value::Undefined,
+        })
+    });
+    json.set(
+        "parse",
+        Value::Object(ObjectRef::native_function("parse", parse)),
+    );
+    json.set(
+        "stringify",
+        Value::Object(ObjectRef::native_function("stringify", stringify)),
+    );
+    runtime.define_global("JSON", Value::Object(json));
+}
+
+/// Parses `text` into a value, where objects and arrays are new ones of `runtime`, or throws a
+/// SyntaxError if it isn't JSON.
+/// https://tc39.es/ecma262/#sec-json.parse
+pub fn parse(runtime: &Runtime, text: &str) -> Result<Value, Exception> {
+    let mut parser = Parser {
+        runtime,
+        chars: text.chars().collect(),
+        pos: 0,
+        depth: 0,
+    };
+    parser.skip_whitespace();
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < parser.chars.len() {
+        return Err(parser.unexpected());
+    }
+    Ok(value)
+}
+
+/// Calls `reviver` with the properties of `holder[key]` from the deepest ones, and returns the
+/// value it returns for `key`. A property for which it returns undefined is deleted.
+/// https://tc39.es/ecma262/#sec-internalizejsonproperty
+fn internalize(
+    runtime: &mut Runtime,
+    reviver: &Value,
+    holder: &ObjectRef,
+    key: &str,
+) -> Result<Value, Exception> {
+    let value = holder.get(key);
+    if let Value::Object(object) = &value {
+        // The keys of an array are its indexes.
+        let keys = object.borrow().keys();
+        for key in keys {
+            match internalize(runtime, reviver, object, &key)? {
+                Value::Undefined => {
+                    object.borrow_mut().delete(&key);
+                }
+                element => object.set(&key, element),
+            }
+        }
+    }
+    runtime.call(
+        reviver,
+        Value::Object(holder.clone()),
+        alloc::vec![Value::string(key), value],
+    )
+}
+
+/// A parser of JSON text, which is stricter than JavaScript, e.g. strings are only in double
+/// quotes and trailing commas aren't allowed.
+/// https://www.rfc-editor.org/rfc/rfc8259
+struct Parser<'a> {
+    runtime: &'a Runtime,
+    chars: Vec<char>,
+    pos: usize,
+    /// The arrays and objects which the current value is in.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Result<char, Exception> {
+        let c = self.peek().ok_or_else(|| self.unexpected())?;
+        self.pos += 1;
+        Ok(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), Exception> {
+        if self.peek() != Some(expected) {
+            return Err(self.unexpected());
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Returns an error for the character at the current position.
+    fn unexpected(&self) -> Exception {
+        let message = match self.peek() {
+            Some(c) => format!(
+                "JSON.parse: unexpected character {:?} at position {}",
+                c, self.pos
+            ),
+            None => String::from("JSON.parse: unexpected end of data"),
+        };
+        Exception::new("SyntaxError", &message)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ') | Some('\t') | Some('\n') | Some('\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, Exception> {
+        match self.peek() {
+            Some('{') => self.nested(Self::object),
+            Some('[') => self.nested(Self::array),
+            Some('"') => Ok(Value::String(self.string()?)),
+            Some('-') | Some('0'..='9') => self.number(),
+            Some('t') => self.literal("true", Value::Boolean(true)),
+            Some('f') => self.literal("false", Value::Boolean(false)),
+            Some('n') => self.literal("null", Value::Null),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    /// Parses an array or an object by `f` one level deeper.
+    fn nested(&mut self, f: fn(&mut Self) -> Result<Value, Exception>) -> Result<Value, Exception> {
+        if self.depth >= MAX_DEPTH {
+            return Err(Exception::new(
+                "SyntaxError",
+                "JSON.parse: too deeply nested",
+            ));
+        }
+        self.depth += 1;
+        let value = f(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, Exception> {
+        for expected in word.chars() {
+            self.expect(expected)?;
+        }
+        Ok(value)
+    }
+
+    fn object(&mut self) -> Result<Value, Exception> {
+        self.expect('{')?;
+        let object = self.runtime.object();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Value::Object(object));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.unexpected());
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            self.skip_whitespace();
+            let value = self.value()?;
+            // A later property with the same key replaces the earlier one.
+            object.set(&key, value);
+            self.skip_whitespace();
+            match self.next()? {
+                ',' => continue,
+                '}' => return Ok(Value::Object(object)),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.unexpected());
+                }
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, Exception> {
+        self.expect('[')?;
+        let mut elements = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Value::Object(self.runtime.array(elements)));
+        }
+        loop {
+            self.skip_whitespace();
+            elements.push(self.value()?);
+            self.skip_whitespace();
+            match self.next()? {
+                ',' => continue,
+                ']' => return Ok(Value::Object(self.runtime.array(elements))),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.unexpected());
+                }
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, Exception> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.next()? {
+                '"' => return Ok(s),
+                '\\' => match self.next()? {
+                    '"' => s.push('"'),
+                    '\\' => s.push('\\'),
+                    '/' => s.push('/'),
+                    'b' => s.push('\u{8}'),
+                    'f' => s.push('\u{c}'),
+                    'n' => s.push('\n'),
+                    'r' => s.push('\r'),
+                    't' => s.push('\t'),
+                    'u' => s.push(self.unicode_escape()?),
+                    _ => {
+                        self.pos -= 1;
+                        return Err(self.unexpected());
+                    }
+                },
+                c if c < ' ' => {
+                    self.pos -= 1;
+                    return Err(self.unexpected());
+                }
+                c => s.push(c),
+            }
+        }
+    }
+
+    /// Parses the digits of `\uXXXX`, which is followed by another one for the low surrogate of
+    /// a character outside the BMP. A lone surrogate is U+FFFD since a string is UTF-8 here.
+    fn unicode_escape(&mut self) -> Result<char, Exception> {
+        let high = self.hex4()?;
+        if !(0xd800..0xdc00).contains(&high) {
+            return Ok(char::from_u32(high).unwrap_or('\u{fffd}'));
+        }
+        if self.chars.get(self.pos..self.pos + 2) != Some(&['\\', 'u'][..]) {
+            return Ok('\u{fffd}');
+        }
+        let start = self.pos;
+        self.pos += 2;
+        let low = self.hex4()?;
+        if !(0xdc00..0xe000).contains(&low) {
+            // The next escape is a character of its own.
+            self.pos = start;
+            return Ok('\u{fffd}');
+        }
+        let c = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+        Ok(char::from_u32(c).unwrap_or('\u{fffd}'))
+    }
+
+    fn hex4(&mut self) -> Result<u32, Exception> {
+        let mut n = 0;
+        for _ in 0..4 {
+            let digit = match self.peek().and_then(|c| c.to_digit(16)) {
+                Some(digit) => digit,
+                None => return Err(self.unexpected()),
+            };
+            n = n * 16 + digit;
+            self.pos += 1;
+        }
+        Ok(n)
+    }
+
+    /// Parses a number, which has no leading zeros, no leading "+" and no trailing ".".
+    fn number(&mut self) -> Result<Value, Exception> {
+        let start = self.pos;
+        if self.peek() == Some('-') {
+            self.pos += 1;
+        }
+        match self.peek() {
+            Some('0') => self.pos += 1,
+            Some('1'..='9') => self.digits(),
+            _ => return Err(self.unexpected()),
+        }
+        if self.peek() == Some('.') {
+            self.pos += 1;
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(self.unexpected());
+            }
+            self.digits();
+        }
+        if let Some('e') | Some('E') = self.peek() {
+            self.pos += 1;
+            if let Some('+') | Some('-') = self.peek() {
+                self.pos += 1;
+            }
+            if !matches!(self.peek(), Some('0'..='9')) {
+                return Err(self.unexpected());
+            }
+            self.digits();
+        }
+        let s: String = self.chars[start..self.pos].iter().collect();
+        Ok(Value::Number(s.parse::<f64>().unwrap_or(f64::NAN)))
+    }
+
+    fn digits(&mut self) {
+        while let Some('0'..='9') = self.peek() {
+            self.pos += 1;
+        }
+    }
+}
+
+/// The state of `JSON.stringify()`.
+/// https://tc39.es/ecma262/#sec-json.stringify
+struct Serializer<'a> {
+    runtime: &'a mut Runtime,
+    /// The function which replaces the values before they're serialized.
+    replacer: Option<Value>,
+    /// The keys of the properties of objects which are serialized, or None for all of them.
+    allowed: Option<Vec<String>>,
+    /// The indent of a level, which is empty if the text isn't indented.
+    gap: String,
+    indent: String,
+    /// The objects being serialized, where finding one again means a cycle.
+    stack: Vec<ObjectRef>,
+}
+
+impl<'a> Serializer<'a> {
+    /// Creates a serializer with the `replacer` and `space` arguments of `JSON.stringify()`.
+    fn new(runtime: &'a mut Runtime, replacer: &Value, space: &Value) -> Self {
+        let mut allowed = None;
+        if let Value::Object(object) = replacer {
+            if let ObjectKind::Array(elements) = &object.borrow().kind {
+                let mut keys: Vec<String> = Vec::new();
+                for element in elements {
+                    if let Value::String(_) | Value::Number(_) = element {
+                        let key = element.to_key();
+                        if !keys.contains(&key) {
+                            keys.push(key);
+                        }
+                    }
+                }
+                allowed = Some(keys);
+            }
+        }
+        let gap = match space {
+            Value::Number(n) => {
+                let n = if *n > MAX_GAP as f64 {
+                    MAX_GAP
+                } else {
+                    *n as usize
+                };
+                " ".repeat(n)
+            }
+            Value::String(s) => s.chars().take(MAX_GAP).collect(),
+            _ => String::new(),
+        };
+        Self {
+            runtime,
+            replacer: Some(replacer.clone()).filter(Value::is_callable),
+            allowed,
+            gap,
+            indent: String::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    fn serialize(&mut self, value: Value) -> Result<Option<String>, Exception> {
+        let wrapper = ObjectRef::ordinary();
+        wrapper.set("", value);
+        self.property(&wrapper, "")
+    }
+
+    /// Serializes `holder[key]`, or returns None if it's skipped.
+    /// https://tc39.es/ecma262/#sec-serializejsonproperty
+    fn property(&mut self, holder: &ObjectRef, key: &str) -> Result<Option<String>, Exception> {
+        let mut value = holder.get(key);
+        if let Value::Object(object) = &value {
+            let to_json = object.get("toJSON");
+            if to_json.is_callable() {
+                value =
+                    self.runtime
+                        .call(&to_json, value.clone(), alloc::vec![Value::string(key)])?;
+            }
+        }
+        if let Some(replacer) = &self.replacer {
+            let this = Value::Object(holder.clone());
+            value = self
+                .runtime
+                .call(replacer, this, alloc::vec![Value::string(key), value])?;
+        }
+        Ok(Some(match &value {
+            Value::Null => String::from("null"),
+            Value::Boolean(b) => format!("{}", b),
+            Value::String(s) => quote(s),
+            Value::Number(n) if n.is_finite() => number_to_string(*n),
+            Value::Number(_) => String::from("null"),
+            Value::Undefined => return Ok(None),
+            Value::Object(o) if o.is_callable() => return Ok(None),
+            Value::Object(o) => {
+                if self.stack.contains(o) {
+                    return Err(Exception::type_error("cyclic object value"));
+                }
+                if self.stack.len() >= MAX_DEPTH {
+                    return Err(Exception::range_error("JSON.stringify: too deeply nested"));
+                }
+                self.stack.push(o.clone());
+                let stepback = self.indent.clone();
+                self.indent.push_str(&self.gap);
+                let is_array = matches!(o.borrow().kind, ObjectKind::Array(_));
+                let result = if is_array {
+                    self.array(o)
+                } else {
+                    self.object(o)
+                };
+                self.indent = stepback;
+                self.stack.pop();
+                result?
+            }
+        }))
+    }
+
+    /// https://tc39.es/ecma262/#sec-serializejsonobject
+    fn object(&mut self, object: &ObjectRef) -> Result<String, Exception> {
+        let keys = match &self.allowed {
+            Some(keys) => keys.clone(),
+            None => object.borrow().keys(),
+        };
+        let mut members = Vec::new();
+        for key in keys {
+            if let Some(value) = self.property(object, &key)? {
+                let colon = if self.gap.is_empty() { ":" } else { ": " };
+                members.push(format!("{}{}{}", quote(&key), colon, value));
+            }
+        }
+        Ok(self.join('{', members, '}'))
+    }
+
+    /// Serializes an array, where the elements which are skipped are null.
+    /// https://tc39.es/ecma262/#sec-serializejsonarray
+    fn array(&mut self, array: &ObjectRef) -> Result<String, Exception> {
+        let length = array.get("length").to_uint32();
+        let mut elements = Vec::new();
+        for i in 0..length {
+            let element = self.property(array, &format!("{}", i))?;
+            elements.push(element.unwrap_or_else(|| String::from("null")));
+        }
+        Ok(self.join('[', elements, ']'))
+    }
+
+    /// Returns `items` in `open` and `close`, where each item is on its own line if indented.
+    fn join(&self, open: char, items: Vec<String>, close: char) -> String {
+        if items.is_empty() {
+            return format!("{}{}", open, close);
+        }
+        if self.gap.is_empty() {
+            return format!("{}{}{}", open, items.join(","), close);
+        }
+        let separator = format!(",\n{}", self.indent);
+        // `indent` is one level deeper than the brackets here.
+        let stepback = &self.indent[..self.indent.len() - self.gap.len()];
+        format!(
+            "{}\n{}{}\n{}{}",
+            open,
+            self.indent,
+            items.join(&separator),
+            stepback,
+            close
+        )
+    }
+}
+
+/// Returns `s` in double quotes, where quotes, backslashes and control characters are escaped.
+/// https://tc39.es/ecma262/#sec-quotejsonstring
+pub fn quote(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\u{8}' => quoted.push_str("\\b"),
+            '\u{c}' => quoted.push_str("\\f"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c < ' ' => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,442 @@
+//! This is a part of "12 ECMAScript Language: Lexical Grammar" for a subset of JavaScript.
+//! Regular expression literals and template literals aren't supported, so `/` is always a
+//! punctuator.
+//! https://tc39.es/ecma262/#sec-ecmascript-language-lexical-grammar
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::Iterator;
+
+/// The reserved words which can't be identifiers. `true`, `false` and `null` are literals in the
+/// spec, but they're keywords here since they can't be identifiers either.
+/// https://tc39.es/ecma262/#prod-ReservedWord
+const KEYWORDS: [&str; 39] = [
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "import",
+    "in",
+    "instanceof",
+    "let",
+    "new",
+    "null",
+    "return",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// The punctuators, longer ones first so that the longest one is taken.
+/// https://tc39.es/ecma262/#sec-punctuators
+const PUNCTUATORS: [&str; 57] = [
+    ">>>=", "...", "===", "!==", "**=", "<<=", ">>=", ">>>", "&&=", "||=", "??=", "=>", "==", "!=",
+    "<=", ">=", "&&", "||", "??", "?.", "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=",
+    "<<", ">>", "**", "{", "}", "(", ")", "[", "]", ";", ",", "<", ">", "+", "-", "*", "/", "%",
+    "&", "|", "^", "!", "~", "?", ":", "=", ".",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// https://tc39.es/ecma262/#prod-IdentifierName
+    Identifier(String),
+    /// One of the reserved words, e.g. "function".
+    Keyword(&'static str),
+    /// https://tc39.es/ecma262/#sec-literals-numeric-literals
+    Number(f64),
+    /// The value of a string literal after the escape sequences are replaced.
+    /// https://tc39.es/ecma262/#sec-literals-string-literals
+    String(String),
+    /// One of the punctuators, e.g. "+=".
+    Punctuator(&'static str),
+    /// Something which isn't a token, e.g. an unterminated string literal. The message says why.
+    Invalid(String),
+}
+
+/// Where a token starts in the source, which is shown in error messages. Both the line and the
+/// column start from 1, and the column counts characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A token with where it is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lexeme {
+    pub token: Token,
+    pub pos: Position,
+    /// True if a line terminator is between the previous token and this one, which lets a
+    /// semicolon before this token be omitted.
+    /// https://tc39.es/ecma262/#sec-rules-of-automatic-semicolon-insertion
+    pub newline_before: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lexer {
+    pos: usize,
+    input: Vec<char>,
+    line: usize,
+    column: usize,
+}
+
+impl Lexer {
+    /// Creates a lexer of `source`, e.g. the text of a script element.
+    pub fn new(source: &str) -> Self {
+        Self {
+            pos: 0,
+            input: source.chars().collect(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    /// Returns the character `n` characters after the next one without consuming anything.
+    /// Returns None past the end of the input.
+    fn peek(&self, n: usize) -> Option<char> {
+        self.input.get(self.pos + n).cloned()
+    }
+
+    /// Returns true if the character `n` characters after the next one is a decimal digit.
+    fn is_digit_at(&self, n: usize) -> bool {
+        matches!(self.peek(n), Some(c) if c.is_ascii_digit())
+    }
+
+    fn consume(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.pos += 1;
+        // CRLF is one line terminator.
+        if is_line_terminator(c) && !(c == '\r' && self.peek(0) == Some('\n')) {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    /// Returns where the next character is, which is the end of the input after the last token.
+    pub fn current_position(&self) -> Position {
+        Position {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    /// Skips whitespace and comments. Returns true if a line terminator is skipped, including
+    /// one in a multi-line comment, or an error message if a comment isn't closed.
+    /// https://tc39.es/ecma262/#sec-comments
+    fn skip_whitespace_and_comments(&mut self) -> Result<bool, String> {
+        let mut newline = false;
+        while let Some(c) = self.peek(0) {
+            if is_line_terminator(c) {
+                newline = true;
+                self.consume();
+            } else if c.is_whitespace() || c == '\u{feff}' {
+                self.consume();
+            } else if c == '/' && self.peek(1) == Some('/') {
+                while let Some(c) = self.peek(0) {
+                    if is_line_terminator(c) {
+                        break;
+                    }
+                    self.consume();
+                }
+            } else if c == '/' && self.peek(1) == Some('*') {
+                self.consume();
+                self.consume();
+                loop {
+                    match self.consume() {
+                        Some('*') if self.peek(0) == Some('/') => {
+                            self.consume();
+                            break;
+                        }
+                        Some(c) if is_line_terminator(c) => newline = true,
+                        Some(_) => {}
+                        None => return Err(String::from("unterminated comment")),
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+        Ok(newline)
+    }
+
+    /// Consumes an identifier or a keyword, whose first character is the next one.
+    /// https://tc39.es/ecma262/#sec-names-and-keywords
+    fn consume_identifier(&mut self) -> Token {
+        let mut name = String::new();
+        while let Some(c) = self.peek(0) {
+            if !is_identifier_part(c) {
+                break;
+            }
+            name.push(c);
+            self.consume();
+        }
+        match KEYWORDS.iter().copied().find(|k| *k == name) {
+            Some(keyword) => Token::Keyword(keyword),
+            None => Token::Identifier(name),
+        }
+    }
+
+    /// Consumes a numeric literal, which starts with a digit or a dot followed by a digit.
+    /// https://tc39.es/ecma262/#sec-literals-numeric-literals
+    fn consume_number(&mut self) -> Token {
+        let radix = match (self.peek(0), self.peek(1)) {
+            (Some('0'), Some('x')) | (Some('0'), Some('X')) => 16,
+            (Some('0'), Some('o')) | (Some('0'), Some('O')) => 8,
+            (Some('0'), Some('b')) | (Some('0'), Some('B')) => 2,
+            _ => 10,
+        };
+
+        let value = if radix == 10 {
+            let mut text = String::new();
+            self.consume_digits(&mut text);
+            if self.peek(0) == Some('.') {
+                text.push('.');
+                self.consume();
+                self.consume_digits(&mut text);
+            }
+            if let Some('e') | Some('E') = self.peek(0) {
+                let sign = matches!(self.peek(1), Some('+') | Some('-'));
+                if self.is_digit_at(if sign { 2 } else { 1 }) {
+                    text.push('e');
+                    self.consume();
+                    if sign {
+                        text.push(self.consume().unwrap_or('+'));
+                    }
+                    self.consume_digits(&mut text);
+                }
+            }
+            text.parse::<f64>().unwrap_or(f64::NAN)
+        } else {
+            self.consume();
+            self.consume();
+            let mut value = 0.0;
+            let mut digits = 0;
+            while let Some(digit) = self.peek(0).and_then(|c| c.to_digit(radix)) {
+                // The value is counted in f64 so that a long literal doesn't overflow.
+                value = value * radix as f64 + digit as f64;
+                digits += 1;
+                self.consume();
+            }
+            if digits == 0 {
+                return Token::Invalid(String::from("missing digits after the radix prefix"));
+            }
+            value
+        };
+
+        // A number can't be followed by an identifier, e.g. "3in", which is skipped with it.
+        match self.peek(0) {
+            Some(c) if is_identifier_part(c) => {
+                while self.peek(0).map(is_identifier_part) == Some(true) {
+                    self.consume();
+                }
+                Token::Invalid(format!("unexpected '{}' after a number", c))
+            }
+            _ => Token::Number(value),
+        }
+    }
+
+    fn consume_digits(&mut self, text: &mut String) {
+        while let Some(c) = self.peek(0) {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            text.push(c);
+            self.consume();
+        }
+    }
+
+    /// Consumes a string literal, whose quote is the next character.
+    /// https://tc39.es/ecma262/#sec-literals-string-literals
+    fn consume_string(&mut self) -> Token {
+        let quote = self.consume();
+        let mut value = String::new();
+        loop {
+            let c = match self.peek(0) {
+                Some(c) if Some(c) == quote => {
+                    self.consume();
+                    return Token::String(value);
+                }
+                // A string can't span lines without an escape.
+                Some('\n') | Some('\r') | None => {
+                    return Token::Invalid(String::from("unterminated string literal"))
+                }
+                Some(c) => c,
+            };
+            self.consume();
+            if c != '\\' {
+                value.push(c);
+                continue;
+            }
+            match self.consume_escape() {
+                Ok(Some(c)) => value.push(c),
+                Ok(None) => {}
+                Err(e) => return Token::Invalid(e),
+            }
+        }
+    }
+
+    /// Consumes an escape sequence after a backslash. Returns the character it stands for, or
+    /// None for a line continuation.
+    /// https://tc39.es/ecma262/#prod-EscapeSequence
+    fn consume_escape(&mut self) -> Result<Option<char>, String> {
+        let c = match self.consume() {
+            Some(c) => c,
+            None => return Err(String::from("unterminated string literal")),
+        };
+        let escaped = match c {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'v' => '\u{b}',
+            '0' if !self.is_digit_at(0) => '\0',
+            'x' => self.consume_hex_digits(2)?,
+            'u' if self.peek(0) == Some('{') => {
+                self.consume();
+                let mut code = 0u32;
+                loop {
+                    match self.consume() {
+                        Some('}') => break,
+                        Some(c) => match c.to_digit(16) {
+                            Some(digit) if code <= 0x10ffff => code = code * 16 + digit,
+                            _ => return Err(String::from("invalid Unicode escape sequence")),
+                        },
+                        None => return Err(String::from("invalid Unicode escape sequence")),
+                    }
+                }
+                char::from_u32(code)
+                    .ok_or_else(|| String::from("invalid Unicode escape sequence"))?
+            }
+            'u' => self.consume_hex_digits(4)?,
+            '\r' => {
+                if self.peek(0) == Some('\n') {
+                    self.consume();
+                }
+                return Ok(None);
+            }
+            c if is_line_terminator(c) => return Ok(None),
+            c if c.is_ascii_digit() => {
+                return Err(String::from("octal escape sequences aren't supported"))
+            }
+            c => c,
+        };
+        Ok(Some(escaped))
+    }
+
+    /// Consumes `n` hex digits of an escape sequence and returns the character of the code.
+    fn consume_hex_digits(&mut self, n: usize) -> Result<char, String> {
+        let mut code = 0;
+        for _ in 0..n {
+            match self.peek(0).and_then(|c| c.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit,
+                None => return Err(String::from("invalid escape sequence")),
+            }
+            self.consume();
+        }
+        // A lone surrogate can't be a char, so it's replaced.
+        Ok(char::from_u32(code).unwrap_or('\u{fffd}'))
+    }
+
+    /// Consumes the longest punctuator at the next character, or returns None if there's none.
+    fn consume_punctuator(&mut self) -> Option<&'static str> {
+        let punctuator = PUNCTUATORS.iter().find(|p| {
+            p.chars().enumerate().all(|(i, c)| self.peek(i) == Some(c))
+                // "?.5" is a conditional operator and a number, not an optional chain.
+                && !(**p == "?." && self.is_digit_at(2))
+        })?;
+        for _ in 0..punctuator.len() {
+            self.consume();
+        }
+        Some(*punctuator)
+    }
+}
+
+impl Iterator for Lexer {
+    type Item = Lexeme;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let newline_before = match self.skip_whitespace_and_comments() {
+            Ok(newline) => newline,
+            Err(e) => {
+                return Some(Lexeme {
+                    token: Token::Invalid(e),
+                    pos: self.current_position(),
+                    newline_before: false,
+                })
+            }
+        };
+        let pos = self.current_position();
+        let c = self.peek(0)?;
+        let token = if is_identifier_start(c) {
+            self.consume_identifier()
+        } else if c.is_ascii_digit() || (c == '.' && self.is_digit_at(1)) {
+            self.consume_number()
+        } else if c == '"' || c == '\'' {
+            self.consume_string()
+        } else if let Some(punctuator) = self.consume_punctuator() {
+            Token::Punctuator(punctuator)
+        } else {
+            self.consume();
+            Token::Invalid(format!("unexpected character '{}'", c))
+        };
+        Some(Lexeme {
+            token,
+            pos,
+            newline_before,
+        })
+    }
+}
+
+/// https://tc39.es/ecma262/#sec-line-terminators
+fn is_line_terminator(c: char) -> bool {
+    c == '\n' || c == '\r' || c == '\u{2028}' || c == '\u{2029}'
+}
+
+/// Returns true if `c` can start an identifier. Non-ASCII letters are allowed, but escapes
+/// aren't supported.
+/// https://tc39.es/ecma262/#prod-IdentifierStart
+fn is_identifier_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '$' || c == '_' || (!c.is_ascii() && c.is_alphabetic())
+}
+
+/// https://tc39.es/ecma262/#prod-IdentifierPart
+fn is_identifier_part(c: char) -> bool {
+    is_identifier_start(c) || c.is_ascii_digit() || (!c.is_ascii() && c.is_alphanumeric())
+}
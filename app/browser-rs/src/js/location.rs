@@ -0,0 +1,92 @@
+//! The location of a document, which scripts read its URL from and navigate by, e.g.
+//! `location.href = "next.html"`. The browser follows the navigation after the script runs.
+//! https://html.spec.whatwg.org/multipage/nav-history-apis.html#the-location-interface
+
+use crate::js::runtime::Runtime;
+use crate::js::value::*;
+use crate::url::ParsedUrl;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use core::any::Any;
+use core::cell::RefCell;
+
+/// The global `location`, whose properties reflect the URL of the document.
+pub struct Location {
+    url: ParsedUrl,
+    /// The URL which a script asked to navigate to, which is taken by the browser.
+    navigation: RefCell<Option<ParsedUrl>>,
+}
+
+impl Location {
+    pub fn new(url: ParsedUrl) -> Self {
+        Self {
+            url,
+            navigation: RefCell::new(None),
+        }
+    }
+
+    /// Defines the global `location` in `runtime`.
+    pub fn install(self: &Rc<Self>, runtime: &mut Runtime) {
+        let object = ObjectRef::new(Object::new(ObjectKind::Host(self.clone())));
+        runtime.define_global("location", Value::Object(object));
+    }
+
+    pub fn url(&self) -> &ParsedUrl {
+        &self.url
+    }
+
+    /// Returns the URL which a script asked to navigate to last, and forgets it.
+    pub fn take_navigation(&self) -> Option<ParsedUrl> {
+        self.navigation.borrow_mut().take()
+    }
+}
+
+impl HostObject for Location {
+    fn class_name(&self) -> &str {
+        "Location"
+    }
+
+    fn get(&self, key: &str) -> Option<Value> {
+        let path = self.url.request_path();
+        let (pathname, search) = match path.find('?') {
+            Some(i) => path.split_at(i),
+            None => (path, ""),
+        };
+        let value = match key {
+            // https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-href
+            "href" => self.url.to_string(),
+            "protocol" => String::from("http:"),
+            // The port is in the host unless it's the default one.
+            "host" => self.url.host.clone(),
+            "pathname" => String::from(pathname),
+            // A query or a fragment which is empty is an empty string as well as none.
+            "search" if search.len() > 1 => String::from(search),
+            "search" => String::new(),
+            "hash" => match self.url.fragment() {
+                Some(fragment) if !fragment.is_empty() => format!("#{}", fragment),
+                _ => String::new(),
+            },
+            _ => return None,
+        };
+        Some(Value::String(value))
+    }
+
+    /// Setting `href` asks to navigate to the URL relative to the document. An invalid URL is
+    /// ignored.
+    fn set(&self, key: &str, value: &Value) -> bool {
+        match key {
+            "href" => {
+                if let Some(url) = self.url.join(&format!("{}", value)) {
+                    self.navigation.replace(Some(url));
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
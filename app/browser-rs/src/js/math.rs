@@ -0,0 +1,136 @@
+//! Floating point functions which are in std but not in core, for the numbers of JavaScript.
+
+/// Every f64 whose magnitude is this or more is an integer.
+const TWO_POW_52: f64 = 4_503_599_627_370_496.0;
+const LN_2: f64 = core::f64::consts::LN_2;
+
+/// Rounds `x` toward zero.
+pub fn trunc(x: f64) -> f64 {
+    if x.is_nan() || x >= TWO_POW_52 || x <= -TWO_POW_52 {
+        return x;
+    }
+    let t = x as i64 as f64;
+    // -0.5 is truncated to -0, not 0.
+    if t == 0.0 && x.is_sign_negative() {
+        -0.0
+    } else {
+        t
+    }
+}
+
+/// Rounds `x` toward negative infinity.
+pub fn floor(x: f64) -> f64 {
+    let t = trunc(x);
+    if t > x {
+        t - 1.0
+    } else {
+        t
+    }
+}
+
+/// Returns 2 to the power of `n`.
+fn pow2(n: i64) -> f64 {
+    if n > 1023 {
+        f64::INFINITY
+    } else if n < -1022 {
+        // Subnormal results need two steps since the exponent can't be that small.
+        if n < -1074 {
+            0.0
+        } else {
+            pow2(n + 64) * pow2(-64)
+        }
+    } else {
+        f64::from_bits(((n + 1023) as u64) << 52)
+    }
+}
+
+/// Returns e to the power of `x`.
+pub fn exp(x: f64) -> f64 {
+    if x.is_nan() {
+        return x;
+    }
+    if x > 709.8 {
+        return f64::INFINITY;
+    }
+    if x < -745.2 {
+        return 0.0;
+    }
+    // e^x = 2^k * e^r where |r| <= ln(2) / 2, and e^r is the sum of its Taylor series.
+    let k = floor(x / LN_2 + 0.5);
+    let r = x - k * LN_2;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for i in 1..24 {
+        term *= r / i as f64;
+        sum += term;
+    }
+    sum * pow2(k as i64)
+}
+
+/// Returns the natural logarithm of `x`.
+pub fn ln(x: f64) -> f64 {
+    if x.is_nan() || x < 0.0 {
+        return f64::NAN;
+    }
+    if x == 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if x.is_infinite() {
+        return x;
+    }
+    // Subnormal numbers are scaled up so that they have an exponent.
+    let (x, offset) = if x < f64::MIN_POSITIVE {
+        (x * pow2(64), -64)
+    } else {
+        (x, 0)
+    };
+    // x = m * 2^e where 1 <= m < 2, and ln(m) = 2 * atanh((m - 1) / (m + 1)).
+    let bits = x.to_bits();
+    let e = ((bits >> 52) & 0x7ff) as i64 - 1023 + offset;
+    let m = f64::from_bits((bits & ((1 << 52) - 1)) | (1023 << 52));
+    let s = (m - 1.0) / (m + 1.0);
+    let s2 = s * s;
+    let mut term = s;
+    let mut sum = 0.0;
+    for i in 0..20 {
+        sum += term / (2 * i + 1) as f64;
+        term *= s2;
+    }
+    2.0 * sum + e as f64 * LN_2
+}
+
+/// Returns `x` to the power of `y`.
+/// https://tc39.es/ecma262/#sec-numeric-types-number-exponentiate
+pub fn pow(x: f64, y: f64) -> f64 {
+    if y.is_nan() {
+        return f64::NAN;
+    }
+    if y == 0.0 {
+        return 1.0;
+    }
+    if x.is_nan() {
+        return f64::NAN;
+    }
+    // An integer exponent is computed by squaring, which is exact for small results.
+    if trunc(y) == y && y < 2_147_483_648.0 && y > -2_147_483_648.0 {
+        let mut n = y as i64;
+        let negative = n < 0;
+        if negative {
+            n = -n;
+        }
+        let mut base = x;
+        let mut result = 1.0;
+        while n > 0 {
+            if n & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            n >>= 1;
+        }
+        return if negative { 1.0 / result } else { result };
+    }
+    if x < 0.0 {
+        return f64::NAN;
+    }
+    exp(y * ln(x))
+}
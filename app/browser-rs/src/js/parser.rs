@@ -0,0 +1,727 @@
+//! This is a part of "13 ECMAScript Language: Expressions" and "14 ECMAScript Language: Statements
+//! and Declarations" for a subset of JavaScript. Arrow functions, classes, destructuring and
+//! labels aren't supported.
+//! https://tc39.es/ecma262/#sec-ecmascript-language-expressions
+//! https://tc39.es/ecma262/#sec-ecmascript-language-statements-and-declarations
+
+use crate::js::ast::*;
+use crate::js::lexer::{Lexeme, Lexer, Position, Token};
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// https://tc39.es/ecma262/#prod-AssignmentOperator
+const ASSIGNMENT_OPERATORS: [&str; 16] = [
+    "=", "+=", "-=", "*=", "/=", "%=", "**=", "<<=", ">>=", ">>>=", "&=", "|=", "^=", "&&=", "||=",
+    "??=",
+];
+
+/// An error in the source with where it is, e.g. "SyntaxError: unexpected token ')' at 1:5".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub message: String,
+    pub pos: Position,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SyntaxError: {} at {}", self.message, self.pos)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Parser {
+    lexemes: Vec<Lexeme>,
+    pos: usize,
+    /// Where the source ends, which is where an unexpected end is reported.
+    end: Position,
+}
+
+impl Parser {
+    pub fn new(source: &str) -> Self {
+        let mut lexer = Lexer::new(source);
+        let lexemes = lexer.by_ref().collect();
+        Self {
+            lexemes,
+            pos: 0,
+            end: lexer.current_position(),
+        }
+    }
+
+    /// https://tc39.es/ecma262/#prod-Script
+    pub fn parse_program(&mut self) -> Result<Program, SyntaxError> {
+        let mut body = Vec::new();
+        while self.peek().is_some() {
+            body.push(self.statement()?);
+        }
+        Ok(Program { body })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.lexemes.get(self.pos).map(|l| &l.token)
+    }
+
+    /// Returns where the next token starts, or the end of the source if there's none.
+    fn next_pos(&self) -> Position {
+        match self.lexemes.get(self.pos) {
+            Some(lexeme) => lexeme.pos,
+            None => self.end,
+        }
+    }
+
+    fn advance(&mut self) -> Option<Lexeme> {
+        let lexeme = self.lexemes.get(self.pos).cloned();
+        if lexeme.is_some() {
+            self.pos += 1;
+        }
+        lexeme
+    }
+
+    fn is_punctuator(&self, punctuator: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punctuator(p)) if *p == punctuator)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Keyword(k)) if *k == keyword)
+    }
+
+    /// Consumes the next token if it's `punctuator`, and returns true if it's consumed.
+    fn eat(&mut self, punctuator: &str) -> bool {
+        if self.is_punctuator(punctuator) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, punctuator: &str) -> Result<(), SyntaxError> {
+        if self.eat(punctuator) {
+            return Ok(());
+        }
+        Err(self.unexpected())
+    }
+
+    /// Returns an error for the next token, which isn't allowed where it is.
+    fn unexpected(&self) -> SyntaxError {
+        unexpected(self.lexemes.get(self.pos), self.end)
+    }
+
+    fn identifier(&mut self) -> Result<String, SyntaxError> {
+        if let Some(Token::Identifier(name)) = self.peek() {
+            let name = name.clone();
+            self.pos += 1;
+            return Ok(name);
+        }
+        Err(self.unexpected())
+    }
+
+    /// Returns true if a semicolon can be inserted before the next token, which is when it's
+    /// `}`, the end or on another line.
+    /// https://tc39.es/ecma262/#sec-rules-of-automatic-semicolon-insertion
+    fn can_insert_semicolon(&self) -> bool {
+        match self.lexemes.get(self.pos) {
+            Some(lexeme) => lexeme.newline_before || lexeme.token == Token::Punctuator("}"),
+            None => true,
+        }
+    }
+
+    fn consume_semicolon(&mut self) -> Result<(), SyntaxError> {
+        if self.eat(";") || self.can_insert_semicolon() {
+            return Ok(());
+        }
+        Err(self.unexpected())
+    }
+
+    /// https://tc39.es/ecma262/#prod-Statement
+    fn statement(&mut self) -> Result<Statement, SyntaxError> {
+        let pos = self.next_pos();
+        let kind = match self.peek() {
+            Some(Token::Punctuator("{")) => StatementKind::Block(self.block()?),
+            Some(Token::Punctuator(";")) => {
+                self.pos += 1;
+                StatementKind::Empty
+            }
+            Some(Token::Keyword("var"))
+            | Some(Token::Keyword("let"))
+            | Some(Token::Keyword("const")) => {
+                let declaration = self.variable_declaration()?;
+                self.consume_semicolon()?;
+                declaration
+            }
+            Some(Token::Keyword("function")) => {
+                self.pos += 1;
+                StatementKind::Function(Rc::new(self.function(pos, true)?))
+            }
+            Some(Token::Keyword("return")) => {
+                self.pos += 1;
+                // A line break ends the statement, e.g. "return\na" returns undefined.
+                let argument = if self.is_punctuator(";") || self.can_insert_semicolon() {
+                    None
+                } else {
+                    Some(self.expression()?)
+                };
+                self.consume_semicolon()?;
+                StatementKind::Return(argument)
+            }
+            Some(Token::Keyword("if")) => {
+                self.pos += 1;
+                self.expect("(")?;
+                let test = self.expression()?;
+                self.expect(")")?;
+                let consequent = Box::new(self.statement()?);
+                let alternate = if self.is_keyword("else") {
+                    self.pos += 1;
+                    Some(Box::new(self.statement()?))
+                } else {
+                    None
+                };
+                StatementKind::If {
+                    test,
+                    consequent,
+                    alternate,
+                }
+            }
+            Some(Token::Keyword("while")) => {
+                self.pos += 1;
+                self.expect("(")?;
+                let test = self.expression()?;
+                self.expect(")")?;
+                StatementKind::While {
+                    test,
+                    body: Box::new(self.statement()?),
+                }
+            }
+            Some(Token::Keyword("for")) => self.for_statement()?,
+            Some(Token::Keyword("break")) => {
+                self.pos += 1;
+                self.consume_semicolon()?;
+                StatementKind::Break
+            }
+            Some(Token::Keyword("continue")) => {
+                self.pos += 1;
+                self.consume_semicolon()?;
+                StatementKind::Continue
+            }
+            _ => {
+                let expression = self.expression()?;
+                self.consume_semicolon()?;
+                StatementKind::Expression(expression)
+            }
+        };
+        Ok(Statement { kind, pos })
+    }
+
+    /// Parses statements in braces.
+    /// https://tc39.es/ecma262/#prod-Block
+    fn block(&mut self) -> Result<Vec<Statement>, SyntaxError> {
+        self.expect("{")?;
+        let mut body = Vec::new();
+        while !self.eat("}") {
+            if self.peek().is_none() {
+                return Err(self.unexpected());
+            }
+            body.push(self.statement()?);
+        }
+        Ok(body)
+    }
+
+    /// Parses a declaration which starts with `var`, `let` or `const` without the semicolon.
+    /// https://tc39.es/ecma262/#sec-declarations-and-the-variable-statement
+    fn variable_declaration(&mut self) -> Result<StatementKind, SyntaxError> {
+        let kind = match self.advance().map(|l| l.token) {
+            Some(Token::Keyword("let")) => VariableKind::Let,
+            Some(Token::Keyword("const")) => VariableKind::Const,
+            _ => VariableKind::Var,
+        };
+        let mut declarations = Vec::new();
+        loop {
+            let pos = self.next_pos();
+            let name = self.identifier()?;
+            let init = if self.eat("=") {
+                Some(self.assignment()?)
+            } else if kind == VariableKind::Const {
+                return Err(SyntaxError {
+                    message: String::from("missing initializer in const declaration"),
+                    pos,
+                });
+            } else {
+                None
+            };
+            declarations.push(VariableDeclarator { name, init });
+            if !self.eat(",") {
+                break;
+            }
+        }
+        Ok(StatementKind::Variable { kind, declarations })
+    }
+
+    /// https://tc39.es/ecma262/#sec-for-statement
+    fn for_statement(&mut self) -> Result<StatementKind, SyntaxError> {
+        self.pos += 1;
+        self.expect("(")?;
+
+        let pos = self.next_pos();
+        let init = if self.is_punctuator(";") {
+            None
+        } else if self.is_keyword("var") || self.is_keyword("let") || self.is_keyword("const") {
+            let kind = self.variable_declaration()?;
+            Some(Box::new(Statement { kind, pos }))
+        } else {
+            let kind = StatementKind::Expression(self.expression()?);
+            Some(Box::new(Statement { kind, pos }))
+        };
+        self.expect(";")?;
+
+        let test = if self.is_punctuator(";") {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.expect(";")?;
+
+        let update = if self.is_punctuator(")") {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.expect(")")?;
+
+        Ok(StatementKind::For {
+            init,
+            test,
+            update,
+            body: Box::new(self.statement()?),
+        })
+    }
+
+    /// Parses the rest of a function after the `function` keyword, which starts at `pos`.
+    /// https://tc39.es/ecma262/#sec-function-definitions
+    fn function(&mut self, pos: Position, name_required: bool) -> Result<Function, SyntaxError> {
+        let name = if name_required || matches!(self.peek(), Some(Token::Identifier(_))) {
+            Some(self.identifier()?)
+        } else {
+            None
+        };
+
+        self.expect("(")?;
+        let mut params = Vec::new();
+        while !self.eat(")") {
+            params.push(self.identifier()?);
+            if !self.is_punctuator(")") {
+                self.expect(",")?;
+            }
+        }
+
+        let body = self.block()?;
+        Ok(Function {
+            name,
+            params,
+            body,
+            pos,
+        })
+    }
+
+    /// https://tc39.es/ecma262/#prod-Expression
+    fn expression(&mut self) -> Result<Expression, SyntaxError> {
+        let pos = self.next_pos();
+        let first = self.assignment()?;
+        if !self.is_punctuator(",") {
+            return Ok(first);
+        }
+        let mut expressions = Vec::from([first]);
+        while self.eat(",") {
+            expressions.push(self.assignment()?);
+        }
+        Ok(Expression {
+            kind: ExpressionKind::Sequence(expressions),
+            pos,
+        })
+    }
+
+    /// https://tc39.es/ecma262/#prod-AssignmentExpression
+    fn assignment(&mut self) -> Result<Expression, SyntaxError> {
+        let pos = self.next_pos();
+        let target = self.conditional()?;
+        let operator = match self.peek() {
+            Some(Token::Punctuator(p)) if ASSIGNMENT_OPERATORS.contains(p) => *p,
+            _ => return Ok(target),
+        };
+        check_target(&target)?;
+        self.pos += 1;
+        let value = self.assignment()?;
+        Ok(Expression {
+            kind: ExpressionKind::Assignment {
+                operator,
+                target: Box::new(target),
+                value: Box::new(value),
+            },
+            pos,
+        })
+    }
+
+    /// https://tc39.es/ecma262/#prod-ConditionalExpression
+    fn conditional(&mut self) -> Result<Expression, SyntaxError> {
+        let pos = self.next_pos();
+        let test = self.binary(1)?;
+        if !self.eat("?") {
+            return Ok(test);
+        }
+        let consequent = self.assignment()?;
+        self.expect(":")?;
+        let alternate = self.assignment()?;
+        Ok(Expression {
+            kind: ExpressionKind::Conditional {
+                test: Box::new(test),
+                consequent: Box::new(consequent),
+                alternate: Box::new(alternate),
+            },
+            pos,
+        })
+    }
+
+    /// Returns the binary operator at the next token and its precedence.
+    fn binary_operator(&self) -> Option<(&'static str, u8)> {
+        let operator = match self.peek()? {
+            Token::Punctuator(p) => *p,
+            Token::Keyword(k) if *k == "in" || *k == "instanceof" => *k,
+            _ => return None,
+        };
+        Some((operator, precedence(operator)?))
+    }
+
+    /// Parses binary operators whose precedence is `min_precedence` or higher by precedence
+    /// climbing.
+    /// https://tc39.es/ecma262/#sec-multiplicative-operators
+    fn binary(&mut self, min_precedence: u8) -> Result<Expression, SyntaxError> {
+        let pos = self.next_pos();
+        let mut left = self.unary()?;
+        while let Some((operator, precedence)) = self.binary_operator() {
+            if precedence < min_precedence {
+                break;
+            }
+            self.pos += 1;
+            // `**` is right-associative, and the others are left-associative.
+            let right = if operator == "**" {
+                self.binary(precedence)?
+            } else {
+                self.binary(precedence + 1)?
+            };
+            let (left_box, right_box) = (Box::new(left), Box::new(right));
+            let kind = match operator {
+                "&&" | "||" | "??" => ExpressionKind::Logical {
+                    operator,
+                    left: left_box,
+                    right: right_box,
+                },
+                _ => ExpressionKind::Binary {
+                    operator,
+                    left: left_box,
+                    right: right_box,
+                },
+            };
+            left = Expression { kind, pos };
+        }
+        Ok(left)
+    }
+
+    /// https://tc39.es/ecma262/#prod-UnaryExpression
+    fn unary(&mut self) -> Result<Expression, SyntaxError> {
+        let pos = self.next_pos();
+        let operator = match self.peek() {
+            Some(Token::Punctuator(p)) if ["!", "-", "+", "~", "++", "--"].contains(p) => *p,
+            Some(Token::Keyword(k)) if ["typeof", "void", "delete"].contains(k) => *k,
+            _ => return self.postfix(),
+        };
+        self.pos += 1;
+        let argument = Box::new(self.unary()?);
+        let kind = if operator == "++" || operator == "--" {
+            check_target(&argument)?;
+            ExpressionKind::Update {
+                operator,
+                prefix: true,
+                argument,
+            }
+        } else {
+            ExpressionKind::Unary { operator, argument }
+        };
+        Ok(Expression { kind, pos })
+    }
+
+    /// https://tc39.es/ecma262/#prod-UpdateExpression
+    fn postfix(&mut self) -> Result<Expression, SyntaxError> {
+        let pos = self.next_pos();
+        let argument = self.call_member()?;
+        let operator = match self.lexemes.get(self.pos) {
+            // A line break before `++` ends the expression, e.g. "a\n++b" is "a; ++b".
+            Some(Lexeme {
+                token: Token::Punctuator(p),
+                newline_before: false,
+                ..
+            }) if *p == "++" || *p == "--" => *p,
+            _ => return Ok(argument),
+        };
+        check_target(&argument)?;
+        self.pos += 1;
+        Ok(Expression {
+            kind: ExpressionKind::Update {
+                operator,
+                prefix: false,
+                argument: Box::new(argument),
+            },
+            pos,
+        })
+    }
+
+    /// Parses a primary expression followed by members, indexes and calls, e.g. `a.b[c](d)`.
+    /// https://tc39.es/ecma262/#prod-LeftHandSideExpression
+    fn call_member(&mut self) -> Result<Expression, SyntaxError> {
+        let pos = self.next_pos();
+        let mut expression = if self.is_keyword("new") {
+            self.new_expression()?
+        } else {
+            self.primary()?
+        };
+        loop {
+            if self.is_punctuator("(") {
+                let arguments = self.arguments()?;
+                expression = Expression {
+                    kind: ExpressionKind::Call {
+                        callee: Box::new(expression),
+                        arguments,
+                    },
+                    pos,
+                };
+            } else if self.is_punctuator(".") || self.is_punctuator("[") {
+                expression = self.member(expression, pos)?;
+            } else {
+                return Ok(expression);
+            }
+        }
+    }
+
+    /// Parses `.name` or `[index]` after `object`.
+    fn member(&mut self, object: Expression, pos: Position) -> Result<Expression, SyntaxError> {
+        let kind = if self.eat(".") {
+            // Reserved words can be property names, e.g. `a.default`.
+            let name = match self.advance().map(|l| l.token) {
+                Some(Token::Identifier(name)) => name,
+                Some(Token::Keyword(keyword)) => String::from(keyword),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.unexpected());
+                }
+            };
+            ExpressionKind::Member {
+                object: Box::new(object),
+                name,
+            }
+        } else if self.eat("[") {
+            let index = self.expression()?;
+            self.expect("]")?;
+            ExpressionKind::Index {
+                object: Box::new(object),
+                index: Box::new(index),
+            }
+        } else {
+            return Err(self.unexpected());
+        };
+        Ok(Expression { kind, pos })
+    }
+
+    /// Parses `new` followed by a constructor and optional arguments, e.g. `new Foo.Bar(1)`.
+    /// https://tc39.es/ecma262/#prod-NewExpression
+    fn new_expression(&mut self) -> Result<Expression, SyntaxError> {
+        let pos = self.next_pos();
+        self.pos += 1;
+        let mut callee = if self.is_keyword("new") {
+            self.new_expression()?
+        } else {
+            self.primary()?
+        };
+        while self.is_punctuator(".") || self.is_punctuator("[") {
+            callee = self.member(callee, pos)?;
+        }
+        let arguments = if self.is_punctuator("(") {
+            self.arguments()?
+        } else {
+            Vec::new()
+        };
+        Ok(Expression {
+            kind: ExpressionKind::New {
+                callee: Box::new(callee),
+                arguments,
+            },
+            pos,
+        })
+    }
+
+    /// https://tc39.es/ecma262/#prod-Arguments
+    fn arguments(&mut self) -> Result<Vec<Expression>, SyntaxError> {
+        self.expect("(")?;
+        let mut arguments = Vec::new();
+        while !self.eat(")") {
+            arguments.push(self.assignment()?);
+            if !self.is_punctuator(")") {
+                self.expect(",")?;
+            }
+        }
+        Ok(arguments)
+    }
+
+    /// https://tc39.es/ecma262/#prod-PrimaryExpression
+    fn primary(&mut self) -> Result<Expression, SyntaxError> {
+        let pos = self.next_pos();
+        let kind = match self.peek() {
+            Some(Token::Number(n)) => ExpressionKind::Number(*n),
+            Some(Token::String(s)) => ExpressionKind::String(s.clone()),
+            Some(Token::Identifier(name)) => ExpressionKind::Identifier(name.clone()),
+            Some(Token::Keyword("true")) => ExpressionKind::Boolean(true),
+            Some(Token::Keyword("false")) => ExpressionKind::Boolean(false),
+            Some(Token::Keyword("null")) => ExpressionKind::Null,
+            Some(Token::Keyword("this")) => ExpressionKind::This,
+            Some(Token::Keyword("function")) => {
+                self.pos += 1;
+                let function = self.function(pos, false)?;
+                return Ok(Expression {
+                    kind: ExpressionKind::Function(Rc::new(function)),
+                    pos,
+                });
+            }
+            Some(Token::Punctuator("(")) => {
+                self.pos += 1;
+                let expression = self.expression()?;
+                self.expect(")")?;
+                return Ok(expression);
+            }
+            Some(Token::Punctuator("[")) => return self.array(),
+            Some(Token::Punctuator("{")) => return self.object(),
+            _ => return Err(self.unexpected()),
+        };
+        self.pos += 1;
+        Ok(Expression { kind, pos })
+    }
+
+    /// https://tc39.es/ecma262/#prod-ArrayLiteral
+    fn array(&mut self) -> Result<Expression, SyntaxError> {
+        let pos = self.next_pos();
+        self.expect("[")?;
+        let mut elements = Vec::new();
+        while !self.eat("]") {
+            elements.push(self.assignment()?);
+            if !self.is_punctuator("]") {
+                self.expect(",")?;
+            }
+        }
+        Ok(Expression {
+            kind: ExpressionKind::Array(elements),
+            pos,
+        })
+    }
+
+    /// https://tc39.es/ecma262/#prod-ObjectLiteral
+    fn object(&mut self) -> Result<Expression, SyntaxError> {
+        let pos = self.next_pos();
+        self.expect("{")?;
+        let mut properties = Vec::new();
+        while !self.eat("}") {
+            let key_pos = self.next_pos();
+            let (key, shorthand) = match self.advance().map(|l| l.token) {
+                Some(Token::Identifier(name)) => (name, true),
+                Some(Token::Keyword(keyword)) => (String::from(keyword), false),
+                Some(Token::String(s)) => (s, false),
+                Some(Token::Number(n)) => (format!("{}", n), false),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.unexpected());
+                }
+            };
+            let value = if self.eat(":") {
+                self.assignment()?
+            } else if shorthand {
+                // `{a}` is `{a: a}`.
+                Expression {
+                    kind: ExpressionKind::Identifier(key.clone()),
+                    pos: key_pos,
+                }
+            } else {
+                return Err(self.unexpected());
+            };
+            properties.push((key, value));
+            if !self.is_punctuator("}") {
+                self.expect(",")?;
+            }
+        }
+        Ok(Expression {
+            kind: ExpressionKind::Object(properties),
+            pos,
+        })
+    }
+}
+
+/// Parses `source`, e.g. the text of a script element.
+pub fn parse(source: &str) -> Result<Program, SyntaxError> {
+    Parser::new(source).parse_program()
+}
+
+/// Returns an error for `lexeme`, or for the end at `end` if it's None.
+fn unexpected(lexeme: Option<&Lexeme>, end: Position) -> SyntaxError {
+    let lexeme = match lexeme {
+        Some(lexeme) => lexeme,
+        None => {
+            return SyntaxError {
+                message: String::from("unexpected end of input"),
+                pos: end,
+            }
+        }
+    };
+    let message = match &lexeme.token {
+        Token::Identifier(name) => format!("unexpected identifier '{}'", name),
+        Token::Keyword(keyword) => format!("unexpected token '{}'", keyword),
+        Token::Number(n) => format!("unexpected number {}", n),
+        Token::String(_) => String::from("unexpected string"),
+        Token::Punctuator(p) => format!("unexpected token '{}'", p),
+        Token::Invalid(message) => message.clone(),
+    };
+    SyntaxError {
+        message,
+        pos: lexeme.pos,
+    }
+}
+
+/// Returns an error if `target` can't be assigned to.
+fn check_target(target: &Expression) -> Result<(), SyntaxError> {
+    match target.kind {
+        ExpressionKind::Identifier(_)
+        | ExpressionKind::Member { .. }
+        | ExpressionKind::Index { .. } => Ok(()),
+        _ => Err(SyntaxError {
+            message: String::from("invalid assignment target"),
+            pos: target.pos,
+        }),
+    }
+}
+
+/// Returns the precedence of a binary operator, where a higher one binds tighter, or None if
+/// `operator` isn't one.
+/// https://tc39.es/ecma262/#sec-ecmascript-language-expressions
+fn precedence(operator: &str) -> Option<u8> {
+    let precedence = match operator {
+        "??" => 1,
+        "||" => 2,
+        "&&" => 3,
+        "|" => 4,
+        "^" => 5,
+        "&" => 6,
+        "==" | "!=" | "===" | "!==" => 7,
+        "<" | ">" | "<=" | ">=" | "in" | "instanceof" => 8,
+        "<<" | ">>" | ">>>" => 9,
+        "+" | "-" => 10,
+        "*" | "/" | "%" => 11,
+        "**" => 12,
+        _ => return None,
+    };
+    Some(precedence)
+}
@@ -0,0 +1,948 @@
+//! A tree-walking interpreter which runs a script by evaluating its syntax tree.
+//! https://tc39.es/ecma262/#sec-ecmascript-language-statements-and-declarations
+
+use crate::js::ast::*;
+use crate::js::heap::Heap;
+use crate::js::lexer::Position;
+use crate::js::math::pow;
+use crate::js::parser::parse;
+use crate::js::value::*;
+use crate::js::{builtins, json};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt;
+
+/// The calls which can be nested, so that an endless recursion stops with an error before the
+/// stack overflows.
+const MAX_CALL_DEPTH: usize = 100;
+
+/// An error thrown by a script, e.g. "TypeError: f is not a function at 1:1".
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exception {
+    /// The name of the error, e.g. "TypeError".
+    pub name: &'static str,
+    pub message: String,
+    /// Where it's thrown, which is unknown until the runtime finds it for an error of a native
+    /// function.
+    pub pos: Option<Position>,
+}
+
+impl Exception {
+    pub fn new(name: &'static str, message: &str) -> Self {
+        Self {
+            name,
+            message: String::from(message),
+            pos: None,
+        }
+    }
+
+    pub fn type_error(message: &str) -> Self {
+        Self::new("TypeError", message)
+    }
+
+    pub fn reference_error(message: &str) -> Self {
+        Self::new("ReferenceError", message)
+    }
+
+    pub fn range_error(message: &str) -> Self {
+        Self::new("RangeError", message)
+    }
+
+    /// Sets where the exception is thrown unless it's known.
+    fn at(mut self, pos: Position) -> Self {
+        if self.pos.is_none() {
+            self.pos = Some(pos);
+        }
+        self
+    }
+}
+
+impl fmt::Display for Exception {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.message)?;
+        if let Some(pos) = self.pos {
+            write!(f, " at {}", pos)?;
+        }
+        Ok(())
+    }
+}
+
+/// A variable in a scope.
+#[derive(Clone)]
+struct Variable {
+    value: Value,
+    /// False for a constant.
+    mutable: bool,
+}
+
+/// The variables of a function or a block, which can use the variables of the outer ones.
+/// https://tc39.es/ecma262/#sec-environment-records
+pub struct Scope {
+    variables: RefCell<BTreeMap<String, Variable>>,
+    parent: Option<Rc<Scope>>,
+    /// `this` of a function or the global scope, where `var` declares variables. It's None for a
+    /// block.
+    this: Option<Value>,
+}
+
+impl Scope {
+    fn new(parent: Option<Rc<Scope>>, this: Option<Value>) -> Rc<Self> {
+        Rc::new(Self {
+            variables: RefCell::new(BTreeMap::new()),
+            parent,
+            this,
+        })
+    }
+
+    /// Declares a variable in this scope, which replaces the one with the same name.
+    fn declare(&self, name: &str, value: Value, mutable: bool) {
+        self.variables
+            .borrow_mut()
+            .insert(String::from(name), Variable { value, mutable });
+    }
+
+    /// Returns the value of the variable `name` in this scope or the outer ones.
+    fn lookup(&self, name: &str) -> Option<Value> {
+        if let Some(variable) = self.variables.borrow().get(name) {
+            return Some(variable.value.clone());
+        }
+        self.parent.as_ref()?.lookup(name)
+    }
+
+    /// Assigns `value` to the variable `name` in this scope or the outer ones. An undeclared
+    /// variable is declared in the global scope as scripts which aren't strict do.
+    fn assign(&self, name: &str, value: Value) -> Result<(), Exception> {
+        if let Some(variable) = self.variables.borrow_mut().get_mut(name) {
+            if !variable.mutable {
+                return Err(Exception::type_error("assignment to constant variable"));
+            }
+            variable.value = value;
+            return Ok(());
+        }
+        match &self.parent {
+            Some(parent) => parent.assign(name, value),
+            None => {
+                self.declare(name, value, true);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the values which the scope refers to, which are the ones of its variables and
+    /// `this`.
+    pub fn values(&self) -> Vec<Value> {
+        let variables = self.variables.borrow();
+        let mut values: Vec<Value> = variables.values().map(|v| v.value.clone()).collect();
+        values.extend(self.this.iter().cloned());
+        values
+    }
+
+    pub fn parent(&self) -> Option<&Rc<Scope>> {
+        self.parent.as_ref()
+    }
+
+    fn this(&self) -> Value {
+        match (&self.this, &self.parent) {
+            (Some(this), _) => this.clone(),
+            (None, Some(parent)) => parent.this(),
+            (None, None) => Value::Undefined,
+        }
+    }
+}
+
+/// How a statement finishes.
+/// https://tc39.es/ecma262/#sec-completion-record-specification-type
+enum Completion {
+    /// It finishes normally with the value of the last expression statement if there's one.
+    Normal(Option<Value>),
+    Return(Value),
+    Break,
+    Continue,
+}
+
+pub struct Runtime {
+    global: Rc<Scope>,
+    /// The calls being run.
+    depth: usize,
+    /// The methods of strings, e.g. `slice`.
+    string_prototype: ObjectRef,
+    /// The methods of arrays, e.g. `push`, which every array inherits.
+    array_prototype: ObjectRef,
+    /// The objects made by scripts, which are collected when they're in unreachable cycles.
+    heap: Heap,
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Runtime {
+    pub fn new() -> Self {
+        let mut runtime = Self {
+            global: Scope::new(None, Some(Value::Undefined)),
+            depth: 0,
+            string_prototype: ObjectRef::ordinary(),
+            array_prototype: ObjectRef::ordinary(),
+            heap: Heap::new(),
+        };
+        for (name, value) in [
+            ("undefined", Value::Undefined),
+            ("NaN", Value::Number(f64::NAN)),
+            ("Infinity", Value::Number(f64::INFINITY)),
+        ]
+        .iter()
+        {
+            runtime.global.declare(name, value.clone(), false);
+        }
+        builtins::install(&mut runtime);
+        json::install(&mut runtime);
+        runtime
+    }
+
+    pub fn string_prototype(&self) -> &ObjectRef {
+        &self.string_prototype
+    }
+
+    pub fn array_prototype(&self) -> &ObjectRef {
+        &self.array_prototype
+    }
+
+    pub fn heap(&self) -> &Heap {
+        &self.heap
+    }
+
+    /// Creates an empty object, e.g. the result of `{}`, which is kept track of by the heap.
+    pub fn object(&self) -> ObjectRef {
+        let object = ObjectRef::ordinary();
+        self.heap.register(&object);
+        object
+    }
+
+    /// Creates an array of `elements`, which has the methods of arrays.
+    pub fn array(&self, elements: Vec<Value>) -> ObjectRef {
+        let array = ObjectRef::with_prototype(ObjectKind::Array(elements), &self.array_prototype);
+        self.heap.register(&array);
+        array
+    }
+
+    /// Returns the objects which the runtime keeps alive besides the global scope.
+    fn roots(&self) -> Vec<Value> {
+        alloc::vec![
+            Value::Object(self.string_prototype.clone()),
+            Value::Object(self.array_prototype.clone()),
+        ]
+    }
+
+    /// Drops the objects which can't be reached from the global scope or `roots`, which are the
+    /// values held by the host, e.g. the callbacks of timers. It returns how many are dropped, and
+    /// must be called only when no script runs.
+    pub fn collect_garbage(&mut self, roots: &[Value]) -> usize {
+        let mut all = self.roots();
+        all.extend(roots.iter().cloned());
+        self.heap.collect(&all, &self.global)
+    }
+
+    /// Clears every object, e.g. when the document is left, so that the cycles between the
+    /// objects and `roots` are dropped. No script can run any more after this.
+    pub fn clear_heap(&mut self, roots: &[Value]) {
+        let mut all = self.roots();
+        all.extend(roots.iter().cloned());
+        self.heap.clear(&all, &self.global);
+    }
+
+    /// Declares a global variable, e.g. `document` for a page.
+    pub fn define_global(&mut self, name: &str, value: Value) {
+        self.global.declare(name, value, true);
+    }
+
+    /// Returns the value of a global variable, or None if it isn't declared.
+    #[allow(dead_code)]
+    pub fn global(&self, name: &str) -> Option<Value> {
+        self.global.lookup(name)
+    }
+
+    /// Runs `source` in the global scope, and returns the value of the last expression statement
+    /// or undefined if there's none.
+    pub fn run(&mut self, source: &str) -> Result<Value, Exception> {
+        let program = parse(source).map_err(|e| Exception {
+            name: "SyntaxError",
+            message: e.message,
+            pos: Some(e.pos),
+        })?;
+        let global = self.global.clone();
+        self.hoist(&program.body, &global);
+        match self.execute_statements(&program.body, &global)? {
+            Completion::Normal(Some(value)) | Completion::Return(value) => Ok(value),
+            _ => Ok(Value::Undefined),
+        }
+    }
+
+    /// Calls `function` with `this` and `arguments`, e.g. an event listener.
+    /// https://tc39.es/ecma262/#sec-call
+    pub fn call(
+        &mut self,
+        function: &Value,
+        this: Value,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Exception> {
+        let object = match function {
+            Value::Object(o) => o.clone(),
+            _ => {
+                return Err(Exception::type_error(&format!(
+                    "{} is not a function",
+                    function.type_of()
+                )))
+            }
+        };
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(Exception::range_error("maximum call stack size exceeded"));
+        }
+
+        // The object isn't borrowed while the function runs since it may change the object.
+        let kind = match &object.borrow().kind {
+            ObjectKind::Function(closure) => Ok(closure.clone()),
+            ObjectKind::NativeFunction { function, .. } => Err(function.clone()),
+            _ => return Err(Exception::type_error("object is not a function")),
+        };
+
+        self.depth += 1;
+        let result = match kind {
+            Ok(closure) => self.call_closure(&closure, this, arguments),
+            Err(native) => native(self, this, arguments),
+        };
+        self.depth -= 1;
+        result
+    }
+
+    fn call_closure(
+        &mut self,
+        closure: &Closure,
+        this: Value,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Exception> {
+        let scope = Scope::new(Some(closure.scope.clone()), Some(this));
+        let mut arguments = arguments.into_iter();
+        for param in &closure.function.params {
+            scope.declare(param, arguments.next().unwrap_or(Value::Undefined), true);
+        }
+        self.hoist(&closure.function.body, &scope);
+        match self.execute_statements(&closure.function.body, &scope)? {
+            Completion::Return(value) => Ok(value),
+            _ => Ok(Value::Undefined),
+        }
+    }
+
+    /// Creates a function object of `function` which is made in `scope`.
+    fn make_function(&self, function: &Rc<Function>, scope: &Rc<Scope>) -> Value {
+        let object = ObjectRef::new(Object::new(ObjectKind::Function(Closure {
+            function: function.clone(),
+            scope: scope.clone(),
+        })));
+        self.heap.register(&object);
+        Value::Object(object)
+    }
+
+    /// Declares the functions in `body` and the variables declared by `var` in it before it runs,
+    /// so that they can be used before their declarations.
+    /// https://tc39.es/ecma262/#sec-functiondeclarationinstantiation
+    fn hoist(&mut self, body: &[Statement], scope: &Rc<Scope>) {
+        for statement in body {
+            if let StatementKind::Function(function) = &statement.kind {
+                if let Some(name) = &function.name {
+                    let value = self.make_function(function, scope);
+                    scope.declare(name, value, true);
+                }
+            }
+        }
+        if scope.this.is_some() {
+            for statement in body {
+                hoist_var(statement, scope);
+            }
+        }
+    }
+
+    fn execute_statements(
+        &mut self,
+        statements: &[Statement],
+        scope: &Rc<Scope>,
+    ) -> Result<Completion, Exception> {
+        let mut last = None;
+        for statement in statements {
+            match self.execute(statement, scope)? {
+                Completion::Normal(Some(value)) => last = Some(value),
+                Completion::Normal(None) => {}
+                completion => return Ok(completion),
+            }
+        }
+        Ok(Completion::Normal(last))
+    }
+
+    /// https://tc39.es/ecma262/#sec-ecmascript-language-statements-and-declarations
+    fn execute(
+        &mut self,
+        statement: &Statement,
+        scope: &Rc<Scope>,
+    ) -> Result<Completion, Exception> {
+        match &statement.kind {
+            StatementKind::Expression(expression) => {
+                Ok(Completion::Normal(Some(self.evaluate(expression, scope)?)))
+            }
+            StatementKind::Variable { kind, declarations } => {
+                for declarator in declarations {
+                    let value = match &declarator.init {
+                        Some(init) => self.evaluate(init, scope)?,
+                        None if *kind == VariableKind::Var => continue,
+                        None => Value::Undefined,
+                    };
+                    match kind {
+                        VariableKind::Var => scope
+                            .assign(&declarator.name, value)
+                            .map_err(|e| e.at(statement.pos))?,
+                        VariableKind::Let => scope.declare(&declarator.name, value, true),
+                        VariableKind::Const => scope.declare(&declarator.name, value, false),
+                    }
+                }
+                Ok(Completion::Normal(None))
+            }
+            // A function declaration is hoisted.
+            StatementKind::Function(_) | StatementKind::Empty => Ok(Completion::Normal(None)),
+            StatementKind::Return(argument) => {
+                let value = match argument {
+                    Some(argument) => self.evaluate(argument, scope)?,
+                    None => Value::Undefined,
+                };
+                Ok(Completion::Return(value))
+            }
+            StatementKind::If {
+                test,
+                consequent,
+                alternate,
+            } => {
+                if self.evaluate(test, scope)?.to_boolean() {
+                    self.execute(consequent, scope)
+                } else if let Some(alternate) = alternate {
+                    self.execute(alternate, scope)
+                } else {
+                    Ok(Completion::Normal(None))
+                }
+            }
+            StatementKind::While { test, body } => {
+                let mut last = None;
+                while self.evaluate(test, scope)?.to_boolean() {
+                    match self.execute(body, scope)? {
+                        Completion::Break => break,
+                        Completion::Return(value) => return Ok(Completion::Return(value)),
+                        Completion::Normal(Some(value)) => last = Some(value),
+                        _ => {}
+                    }
+                }
+                Ok(Completion::Normal(last))
+            }
+            StatementKind::For {
+                init,
+                test,
+                update,
+                body,
+            } => {
+                // `let` in the initialization is scoped to the loop.
+                let scope = Scope::new(Some(scope.clone()), None);
+                if let Some(init) = init {
+                    self.execute(init, &scope)?;
+                }
+                let mut last = None;
+                loop {
+                    if let Some(test) = test {
+                        if !self.evaluate(test, &scope)?.to_boolean() {
+                            break;
+                        }
+                    }
+                    match self.execute(body, &scope)? {
+                        Completion::Break => break,
+                        Completion::Return(value) => return Ok(Completion::Return(value)),
+                        Completion::Normal(Some(value)) => last = Some(value),
+                        _ => {}
+                    }
+                    if let Some(update) = update {
+                        self.evaluate(update, &scope)?;
+                    }
+                }
+                Ok(Completion::Normal(last))
+            }
+            StatementKind::Block(body) => {
+                let scope = Scope::new(Some(scope.clone()), None);
+                self.hoist(body, &scope);
+                self.execute_statements(body, &scope)
+            }
+            StatementKind::Break => Ok(Completion::Break),
+            StatementKind::Continue => Ok(Completion::Continue),
+        }
+    }
+
+    /// https://tc39.es/ecma262/#sec-ecmascript-language-expressions
+    fn evaluate(&mut self, expression: &Expression, scope: &Rc<Scope>) -> Result<Value, Exception> {
+        let pos = expression.pos;
+        match &expression.kind {
+            ExpressionKind::Number(n) => Ok(Value::Number(*n)),
+            ExpressionKind::String(s) => Ok(Value::String(s.clone())),
+            ExpressionKind::Boolean(b) => Ok(Value::Boolean(*b)),
+            ExpressionKind::Null => Ok(Value::Null),
+            ExpressionKind::Identifier(name) => scope.lookup(name).ok_or_else(|| {
+                Exception::reference_error(&format!("{} is not defined", name)).at(pos)
+            }),
+            ExpressionKind::This => Ok(scope.this()),
+            ExpressionKind::Array(elements) => {
+                let mut values = Vec::new();
+                for element in elements {
+                    values.push(self.evaluate(element, scope)?);
+                }
+                Ok(Value::Object(self.array(values)))
+            }
+            ExpressionKind::Object(properties) => {
+                let object = self.object();
+                for (key, value) in properties {
+                    let value = self.evaluate(value, scope)?;
+                    object.set(key, value);
+                }
+                Ok(Value::Object(object))
+            }
+            ExpressionKind::Function(function) => match &function.name {
+                // A named function expression can call itself by its name.
+                Some(name) => {
+                    let closure_scope = Scope::new(Some(scope.clone()), None);
+                    let value = self.make_function(function, &closure_scope);
+                    closure_scope.declare(name, value.clone(), false);
+                    Ok(value)
+                }
+                None => Ok(self.make_function(function, scope)),
+            },
+            ExpressionKind::Unary { operator, argument } => {
+                self.evaluate_unary(operator, argument, scope)
+            }
+            ExpressionKind::Update {
+                operator,
+                prefix,
+                argument,
+            } => {
+                let old = self.evaluate(argument, scope)?.to_number();
+                let new = if *operator == "++" {
+                    old + 1.0
+                } else {
+                    old - 1.0
+                };
+                self.assign(argument, Value::Number(new), scope)?;
+                Ok(Value::Number(if *prefix { new } else { old }))
+            }
+            ExpressionKind::Binary {
+                operator,
+                left,
+                right,
+            } => {
+                let left = self.evaluate(left, scope)?;
+                let right = self.evaluate(right, scope)?;
+                binary(operator, &left, &right).map_err(|e| e.at(pos))
+            }
+            ExpressionKind::Logical {
+                operator,
+                left,
+                right,
+            } => {
+                let left = self.evaluate(left, scope)?;
+                if short_circuits(operator, &left) {
+                    return Ok(left);
+                }
+                self.evaluate(right, scope)
+            }
+            ExpressionKind::Assignment {
+                operator,
+                target,
+                value,
+            } => {
+                let value = match *operator {
+                    "=" => self.evaluate(value, scope)?,
+                    "&&=" | "||=" | "??=" => {
+                        let current = self.evaluate(target, scope)?;
+                        if short_circuits(&operator[..2], &current) {
+                            return Ok(current);
+                        }
+                        self.evaluate(value, scope)?
+                    }
+                    _ => {
+                        let current = self.evaluate(target, scope)?;
+                        let value = self.evaluate(value, scope)?;
+                        let operator = &operator[..operator.len() - 1];
+                        binary(operator, &current, &value).map_err(|e| e.at(pos))?
+                    }
+                };
+                self.assign(target, value.clone(), scope)?;
+                Ok(value)
+            }
+            ExpressionKind::Conditional {
+                test,
+                consequent,
+                alternate,
+            } => {
+                if self.evaluate(test, scope)?.to_boolean() {
+                    self.evaluate(consequent, scope)
+                } else {
+                    self.evaluate(alternate, scope)
+                }
+            }
+            ExpressionKind::Call { callee, arguments } => {
+                // A method is called with the object as `this`.
+                let (function, this) = match &callee.kind {
+                    ExpressionKind::Member { object, .. }
+                    | ExpressionKind::Index { object, .. } => {
+                        let this = self.evaluate(object, scope)?;
+                        let key = self.property_key(callee, scope)?;
+                        (self.get_property(&this, &key).map_err(|e| e.at(pos))?, this)
+                    }
+                    _ => (self.evaluate(callee, scope)?, Value::Undefined),
+                };
+                if !function.is_callable() {
+                    return Err(Exception::type_error(&format!(
+                        "{} is not a function",
+                        describe(callee)
+                    ))
+                    .at(pos));
+                }
+                let arguments = self.evaluate_arguments(arguments, scope)?;
+                self.call(&function, this, arguments).map_err(|e| e.at(pos))
+            }
+            ExpressionKind::New { callee, arguments } => {
+                let constructor = self.evaluate(callee, scope)?;
+                let arguments = self.evaluate_arguments(arguments, scope)?;
+                self.construct(&constructor, arguments)
+                    .map_err(|e| e.at(pos))
+            }
+            ExpressionKind::Member { object, .. } | ExpressionKind::Index { object, .. } => {
+                let object = self.evaluate(object, scope)?;
+                let key = self.property_key(expression, scope)?;
+                self.get_property(&object, &key).map_err(|e| e.at(pos))
+            }
+            ExpressionKind::Sequence(expressions) => {
+                let mut value = Value::Undefined;
+                for expression in expressions {
+                    value = self.evaluate(expression, scope)?;
+                }
+                Ok(value)
+            }
+        }
+    }
+
+    fn evaluate_arguments(
+        &mut self,
+        arguments: &[Expression],
+        scope: &Rc<Scope>,
+    ) -> Result<Vec<Value>, Exception> {
+        let mut values = Vec::new();
+        for argument in arguments {
+            values.push(self.evaluate(argument, scope)?);
+        }
+        Ok(values)
+    }
+
+    /// https://tc39.es/ecma262/#sec-unary-operators
+    fn evaluate_unary(
+        &mut self,
+        operator: &str,
+        argument: &Expression,
+        scope: &Rc<Scope>,
+    ) -> Result<Value, Exception> {
+        match operator {
+            // An undeclared variable is "undefined" rather than an error.
+            "typeof" => {
+                let value = match &argument.kind {
+                    ExpressionKind::Identifier(name) => {
+                        scope.lookup(name).unwrap_or(Value::Undefined)
+                    }
+                    _ => self.evaluate(argument, scope)?,
+                };
+                Ok(Value::string(value.type_of()))
+            }
+            "delete" => match &argument.kind {
+                ExpressionKind::Member { object, .. } | ExpressionKind::Index { object, .. } => {
+                    let object = self.evaluate(object, scope)?;
+                    let key = self.property_key(argument, scope)?;
+                    Ok(Value::Boolean(match object {
+                        Value::Object(o) => o.borrow_mut().delete(&key),
+                        _ => true,
+                    }))
+                }
+                _ => Ok(Value::Boolean(true)),
+            },
+            _ => {
+                let value = self.evaluate(argument, scope)?;
+                Ok(match operator {
+                    "!" => Value::Boolean(!value.to_boolean()),
+                    "-" => Value::Number(-value.to_number()),
+                    "+" => Value::Number(value.to_number()),
+                    "~" => Value::Number(!value.to_int32() as f64),
+                    _ => Value::Undefined,
+                })
+            }
+        }
+    }
+
+    /// Returns the key of a member or an index expression, e.g. "b" for `a.b` and `a["b"]`.
+    fn property_key(
+        &mut self,
+        expression: &Expression,
+        scope: &Rc<Scope>,
+    ) -> Result<String, Exception> {
+        match &expression.kind {
+            ExpressionKind::Member { name, .. } => Ok(name.clone()),
+            ExpressionKind::Index { index, .. } => Ok(self.evaluate(index, scope)?.to_key()),
+            _ => Ok(String::new()),
+        }
+    }
+
+    /// Returns the property `key` of `object`.
+    pub fn get_property(&mut self, object: &Value, key: &str) -> Result<Value, Exception> {
+        match object {
+            Value::Undefined | Value::Null => Err(Exception::type_error(&format!(
+                "cannot read properties of {} (reading '{}')",
+                object, key
+            ))),
+            Value::Object(o) => {
+                if key == "prototype" && matches!(o.borrow().kind, ObjectKind::Function(_)) {
+                    return Ok(Value::Object(prototype_of(o)));
+                }
+                Ok(o.get(key))
+            }
+            Value::String(s) => {
+                if key == "length" {
+                    return Ok(Value::Number(s.chars().count() as f64));
+                }
+                Ok(
+                    match key.parse::<usize>().ok().and_then(|i| s.chars().nth(i)) {
+                        Some(c) => Value::String(String::from(c)),
+                        None => self.string_prototype.get(key),
+                    },
+                )
+            }
+            _ => Ok(Value::Undefined),
+        }
+    }
+
+    /// Sets the property `key` of `object`. Setting a property of a primitive does nothing.
+    pub fn set_property(
+        &mut self,
+        object: &Value,
+        key: &str,
+        value: Value,
+    ) -> Result<(), Exception> {
+        match object {
+            Value::Undefined | Value::Null => Err(Exception::type_error(&format!(
+                "cannot set properties of {} (setting '{}')",
+                object, key
+            ))),
+            Value::Object(o) => {
+                o.set(key, value);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Assigns `value` to `target`, which is an identifier, a member or an index.
+    fn assign(
+        &mut self,
+        target: &Expression,
+        value: Value,
+        scope: &Rc<Scope>,
+    ) -> Result<(), Exception> {
+        let pos = target.pos;
+        match &target.kind {
+            ExpressionKind::Identifier(name) => scope.assign(name, value).map_err(|e| e.at(pos)),
+            ExpressionKind::Member { object, .. } | ExpressionKind::Index { object, .. } => {
+                let object = self.evaluate(object, scope)?;
+                let key = self.property_key(target, scope)?;
+                self.set_property(&object, &key, value)
+                    .map_err(|e| e.at(pos))
+            }
+            _ => Err(Exception::new("SyntaxError", "invalid assignment target").at(pos)),
+        }
+    }
+
+    /// Creates an object by `new`, whose prototype is the `prototype` property of the
+    /// constructor.
+    /// https://tc39.es/ecma262/#sec-construct
+    fn construct(
+        &mut self,
+        constructor: &Value,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Exception> {
+        let function = match constructor {
+            Value::Object(o) if o.is_callable() => o,
+            _ => {
+                return Err(Exception::type_error(&format!(
+                    "{} is not a constructor",
+                    constructor.type_of()
+                )))
+            }
+        };
+        let object = self.object();
+        object.borrow_mut().prototype = Some(prototype_of(function));
+        let result = self.call(constructor, Value::Object(object.clone()), arguments)?;
+        // A constructor can return another object instead.
+        match result {
+            Value::Object(_) => Ok(result),
+            _ => Ok(Value::Object(object)),
+        }
+    }
+}
+
+/// Returns the `prototype` property of a constructor, which is made when it's used first rather
+/// than for every function, since it refers back to the function.
+fn prototype_of(constructor: &ObjectRef) -> ObjectRef {
+    if let Value::Object(prototype) = constructor.get("prototype") {
+        return prototype;
+    }
+    let prototype = ObjectRef::ordinary();
+    prototype.set("constructor", Value::Object(constructor.clone()));
+    constructor.set("prototype", Value::Object(prototype.clone()));
+    prototype
+}
+
+/// Declares the variables declared by `var` in `statement` as undefined in `scope`, looking into
+/// the blocks but not the functions.
+fn hoist_var(statement: &Statement, scope: &Rc<Scope>) {
+    match &statement.kind {
+        StatementKind::Variable {
+            kind: VariableKind::Var,
+            declarations,
+        } => {
+            for declarator in declarations {
+                if scope.variables.borrow().get(&declarator.name).is_none() {
+                    scope.declare(&declarator.name, Value::Undefined, true);
+                }
+            }
+        }
+        StatementKind::If {
+            consequent,
+            alternate,
+            ..
+        } => {
+            hoist_var(consequent, scope);
+            if let Some(alternate) = alternate {
+                hoist_var(alternate, scope);
+            }
+        }
+        StatementKind::While { body, .. } => hoist_var(body, scope),
+        StatementKind::For { init, body, .. } => {
+            if let Some(init) = init {
+                hoist_var(init, scope);
+            }
+            hoist_var(body, scope);
+        }
+        StatementKind::Block(body) => {
+            for statement in body {
+                hoist_var(statement, scope);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns true if the right side of a logical operator isn't evaluated because of `left`.
+fn short_circuits(operator: &str, left: &Value) -> bool {
+    match operator {
+        "&&" => !left.to_boolean(),
+        "||" => left.to_boolean(),
+        _ => !matches!(left, Value::Undefined | Value::Null),
+    }
+}
+
+/// Returns a callee for an error message, e.g. "a.b" for `a.b()`.
+fn describe(expression: &Expression) -> String {
+    match &expression.kind {
+        ExpressionKind::Identifier(name) => name.clone(),
+        ExpressionKind::Member { object, name } => format!("{}.{}", describe(object), name),
+        ExpressionKind::Index { object, .. } => format!("{}[...]", describe(object)),
+        ExpressionKind::This => String::from("this"),
+        _ => String::from("expression"),
+    }
+}
+
+/// Applies a binary operator.
+/// https://tc39.es/ecma262/#sec-applystringornumericbinaryoperator
+fn binary(operator: &str, left: &Value, right: &Value) -> Result<Value, Exception> {
+    let number = |f: fn(f64, f64) -> f64| Value::Number(f(left.to_number(), right.to_number()));
+    let int32 = |f: fn(i32, i32) -> i32| Value::Number(f(left.to_int32(), right.to_int32()) as f64);
+    Ok(match operator {
+        "+" => {
+            // Adding a string or an object concatenates them as strings.
+            if matches!(left, Value::String(_) | Value::Object(_))
+                || matches!(right, Value::String(_) | Value::Object(_))
+            {
+                Value::String(format!("{}{}", left, right))
+            } else {
+                number(|a, b| a + b)
+            }
+        }
+        "-" => number(|a, b| a - b),
+        "*" => number(|a, b| a * b),
+        "/" => number(|a, b| a / b),
+        "%" => number(|a, b| a % b),
+        "**" => number(pow),
+        "==" => Value::Boolean(left.loose_equals(right)),
+        "!=" => Value::Boolean(!left.loose_equals(right)),
+        "===" => Value::Boolean(left.strict_equals(right)),
+        "!==" => Value::Boolean(!left.strict_equals(right)),
+        "<" => Value::Boolean(left.less_than(right) == Some(true)),
+        ">" => Value::Boolean(right.less_than(left) == Some(true)),
+        "<=" => Value::Boolean(right.less_than(left) == Some(false)),
+        ">=" => Value::Boolean(left.less_than(right) == Some(false)),
+        "&" => int32(|a, b| a & b),
+        "|" => int32(|a, b| a | b),
+        "^" => int32(|a, b| a ^ b),
+        "<<" => int32(|a, b| a.wrapping_shl(b as u32 & 31)),
+        ">>" => int32(|a, b| a.wrapping_shr(b as u32 & 31)),
+        ">>>" => Value::Number((left.to_uint32() >> (right.to_uint32() & 31)) as f64),
+        "in" => match right {
+            Value::Object(o) => Value::Boolean(o.has(&left.to_key())),
+            _ => {
+                return Err(Exception::type_error(
+                    "cannot use 'in' operator to search in a primitive",
+                ))
+            }
+        },
+        // https://tc39.es/ecma262/#sec-ordinaryhasinstance
+        "instanceof" => {
+            let constructor = match right {
+                Value::Object(o) if o.is_callable() => o,
+                _ => {
+                    return Err(Exception::type_error(
+                        "right-hand side of 'instanceof' is not callable",
+                    ))
+                }
+            };
+            let prototype = constructor.get("prototype");
+            let mut object = match left {
+                Value::Object(o) => o.borrow().prototype.clone(),
+                _ => None,
+            };
+            let mut found = false;
+            while let Some(o) = object {
+                if prototype == Value::Object(o.clone()) {
+                    found = true;
+                    break;
+                }
+                object = o.borrow().prototype.clone();
+            }
+            Value::Boolean(found)
+        }
+        _ => Value::Undefined,
+    })
+}
+
+/// Returns the argument at `index` of a native function, which is undefined if it isn't passed.
+pub fn argument(arguments: &[Value], index: usize) -> Value {
+    arguments.get(index).cloned().unwrap_or(Value::Undefined)
+}
@@ -0,0 +1,169 @@
+//! The timers of scripts, which call functions after a delay, e.g. `setTimeout(f, 100)`. The
+//! callbacks are queued as tasks, which the browser runs when they're due.
+//! https://html.spec.whatwg.org/multipage/timers-and-user-prompts.html#timers
+
+use crate::js::runtime::{argument, Exception, Runtime};
+use crate::js::value::*;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+/// Returns the current time in milliseconds.
+pub type Clock = fn() -> u64;
+
+/// A callback scheduled by `setTimeout()` or `setInterval()`.
+struct Timer {
+    id: u32,
+    /// When the callback should be called in milliseconds.
+    due: u64,
+    /// The delay between the calls of an interval, or None for a timeout.
+    interval: Option<u64>,
+    /// A function, or a string which is run as a script.
+    callback: Value,
+    arguments: Vec<Value>,
+}
+
+/// The list of active timers of a document.
+/// https://html.spec.whatwg.org/multipage/timers-and-user-prompts.html#map-of-active-timers
+pub struct Timers {
+    clock: Clock,
+    timers: Vec<Timer>,
+    /// The ID of the last timer, which is never reused.
+    last_id: u32,
+}
+
+impl Timers {
+    pub fn new(clock: Clock) -> Self {
+        Self {
+            clock,
+            timers: Vec::new(),
+            last_id: 0,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn set_clock(&mut self, clock: Clock) {
+        self.clock = clock;
+    }
+
+    /// Defines `setTimeout` and the other functions of `timers` in `runtime`.
+    pub fn install(timers: &Rc<RefCell<Self>>, runtime: &mut Runtime) {
+        for &(name, repeat) in [("setTimeout", false), ("setInterval", true)].iter() {
+            let timers = timers.clone();
+            let function: NativeFunction = Rc::new(move |_, _, args| {
+                Ok(Value::Number(
+                    timers.borrow_mut().start(&args, repeat) as f64
+                ))
+            });
+            runtime.define_global(
+                name,
+                Value::Object(ObjectRef::native_function(name, function)),
+            );
+        }
+        // https://html.spec.whatwg.org/multipage/timers-and-user-prompts.html#dom-cleartimeout
+        for name in ["clearTimeout", "clearInterval"].iter() {
+            let timers = timers.clone();
+            let function: NativeFunction = Rc::new(move |_, _, args| {
+                let id = argument(&args, 0).to_number();
+                timers.borrow_mut().timers.retain(|t| t.id as f64 != id);
+                Ok(Value::Undefined)
+            });
+            runtime.define_global(
+                name,
+                Value::Object(ObjectRef::native_function(name, function)),
+            );
+        }
+    }
+
+    /// Starts a timer with the arguments of `setTimeout()`, or `setInterval()` if `repeat`, and
+    /// returns its ID. A negative or invalid delay is 0.
+    /// https://html.spec.whatwg.org/multipage/timers-and-user-prompts.html#timer-initialisation-steps
+    fn start(&mut self, args: &[Value], repeat: bool) -> u32 {
+        let delay = argument(args, 1).to_number();
+        let delay = if delay > 0.0 { delay as u64 } else { 0 };
+        self.last_id += 1;
+        self.timers.push(Timer {
+            id: self.last_id,
+            due: (self.clock)().saturating_add(delay),
+            interval: if repeat { Some(delay) } else { None },
+            callback: argument(args, 0),
+            arguments: args.iter().skip(2).cloned().collect(),
+        });
+        self.last_id
+    }
+
+    /// Returns the callbacks of the timers and their arguments, which the timers keep alive.
+    pub fn values(&self) -> Vec<Value> {
+        let mut values = Vec::new();
+        for timer in &self.timers {
+            values.push(timer.callback.clone());
+            values.extend(timer.arguments.iter().cloned());
+        }
+        values
+    }
+
+    /// Returns true if a timer is due.
+    pub fn has_due(&self) -> bool {
+        let now = (self.clock)();
+        self.timers.iter().any(|t| t.due <= now)
+    }
+
+    /// Returns the milliseconds until the next timer is due, which is 0 if one is due, or None if
+    /// there's no timer.
+    pub fn until_due(&self) -> Option<u64> {
+        let now = (self.clock)();
+        self.timers.iter().map(|t| t.due.saturating_sub(now)).min()
+    }
+
+    /// Returns the IDs of the timers which are due at `now` in the order of their due times.
+    fn due(&self, now: u64) -> Vec<u32> {
+        let mut due: Vec<&Timer> = self.timers.iter().filter(|t| t.due <= now).collect();
+        // The sort is stable, so the timers due at the same time run in the order they're set.
+        due.sort_by_key(|t| t.due);
+        due.iter().map(|t| t.id).collect()
+    }
+
+    /// Returns the callback of the timer `id` if it's still active, and removes the timer, or
+    /// schedules it again if it's an interval.
+    fn fire(&mut self, id: u32, now: u64) -> Option<(Value, Vec<Value>)> {
+        let index = self.timers.iter().position(|t| t.id == id)?;
+        let timer = &mut self.timers[index];
+        let callback = (timer.callback.clone(), timer.arguments.clone());
+        match timer.interval {
+            // An interval runs at most once at a time, even if it's late.
+            Some(interval) => timer.due = now + interval.max(1),
+            None => {
+                self.timers.remove(index);
+            }
+        }
+        Some(callback)
+    }
+}
+
+/// Calls the callbacks of the timers which are due. The exceptions thrown by them are given to
+/// `report`. A timer set by a callback runs next time even if it's due now, so that this ends.
+/// https://html.spec.whatwg.org/multipage/timers-and-user-prompts.html#run-steps-after-a-timeout
+pub fn run_due(
+    timers: &Rc<RefCell<Timers>>,
+    runtime: &mut Runtime,
+    report: &mut dyn FnMut(Exception),
+) {
+    let now = (timers.borrow().clock)();
+    let due = timers.borrow().due(now);
+    for id in due {
+        // A timer may be cleared by the callbacks before it.
+        let (callback, arguments) = match timers.borrow_mut().fire(id, now) {
+            Some(callback) => callback,
+            None => continue,
+        };
+        let result = if callback.is_callable() {
+            runtime.call(&callback, Value::Undefined, arguments)
+        } else {
+            runtime.run(&format!("{}", callback))
+        };
+        if let Err(e) = result {
+            report(e);
+        }
+    }
+}
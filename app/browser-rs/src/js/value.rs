@@ -0,0 +1,572 @@
+//! The values of JavaScript and the conversions between them.
+//! https://tc39.es/ecma262/#sec-ecmascript-language-types
+
+use crate::js::ast::Function;
+use crate::js::math::{floor, trunc};
+use crate::js::runtime::{Exception, Runtime, Scope};
+use alloc::format;
+use alloc::rc::{Rc, Weak};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::any::Any;
+use core::cell::{Ref, RefCell, RefMut};
+use core::fmt;
+
+/// A function implemented in Rust, which is called with the runtime, `this` and the arguments.
+pub type NativeFunction = Rc<dyn Fn(&mut Runtime, Value, Vec<Value>) -> Result<Value, Exception>>;
+
+#[derive(Clone, PartialEq)]
+pub enum Value {
+    Undefined,
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    Object(ObjectRef),
+}
+
+impl Value {
+    pub fn string(s: &str) -> Self {
+        Value::String(String::from(s))
+    }
+
+    /// https://tc39.es/ecma262/#sec-typeof-operator
+    pub fn type_of(&self) -> &'static str {
+        match self {
+            Value::Undefined => "undefined",
+            Value::Null => "object",
+            Value::Boolean(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Object(o) if o.is_callable() => "function",
+            Value::Object(_) => "object",
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&ObjectRef> {
+        match self {
+            Value::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    pub fn is_callable(&self) -> bool {
+        matches!(self, Value::Object(o) if o.is_callable())
+    }
+
+    /// https://tc39.es/ecma262/#sec-toboolean
+    pub fn to_boolean(&self) -> bool {
+        match self {
+            Value::Undefined | Value::Null => false,
+            Value::Boolean(b) => *b,
+            Value::Number(n) => !(*n == 0.0 || n.is_nan()),
+            Value::String(s) => !s.is_empty(),
+            Value::Object(_) => true,
+        }
+    }
+
+    /// https://tc39.es/ecma262/#sec-tonumber
+    pub fn to_number(&self) -> f64 {
+        match self {
+            Value::Undefined => f64::NAN,
+            Value::Null => 0.0,
+            Value::Boolean(b) => {
+                if *b {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Value::Number(n) => *n,
+            Value::String(s) => string_to_number(s),
+            Value::Object(_) => string_to_number(&self.to_string()),
+        }
+    }
+
+    /// https://tc39.es/ecma262/#sec-toint32
+    pub fn to_int32(&self) -> i32 {
+        self.to_uint32() as i32
+    }
+
+    /// https://tc39.es/ecma262/#sec-touint32
+    pub fn to_uint32(&self) -> u32 {
+        let n = self.to_number();
+        if !n.is_finite() {
+            return 0;
+        }
+        // The remainder fits in i64, whose lowest 32 bits are the result.
+        (trunc(n) % 4_294_967_296.0) as i64 as u32
+    }
+
+    /// Returns the value as a property key, e.g. "1" for 1.
+    /// https://tc39.es/ecma262/#sec-topropertykey
+    pub fn to_key(&self) -> String {
+        match self {
+            Value::String(s) => s.clone(),
+            _ => self.to_string(),
+        }
+    }
+
+    /// https://tc39.es/ecma262/#sec-isstrictlyequal
+    pub fn strict_equals(&self, other: &Value) -> bool {
+        // Numbers are compared by f64, where NaN isn't equal to itself and 0 is equal to -0.
+        self == other
+    }
+
+    /// https://tc39.es/ecma262/#sec-islooselyequal
+    pub fn loose_equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Undefined, Value::Null) | (Value::Null, Value::Undefined) => true,
+            (Value::Number(_), Value::String(_))
+            | (Value::String(_), Value::Number(_))
+            | (Value::Boolean(_), _)
+            | (_, Value::Boolean(_)) => {
+                if matches!(self, Value::Undefined | Value::Null)
+                    || matches!(other, Value::Undefined | Value::Null)
+                {
+                    return false;
+                }
+                self.to_number() == other.to_number()
+            }
+            (Value::Object(_), Value::Number(_)) | (Value::Object(_), Value::String(_)) => {
+                Value::String(self.to_string()).loose_equals(other)
+            }
+            (Value::Number(_), Value::Object(_)) | (Value::String(_), Value::Object(_)) => {
+                self.loose_equals(&Value::String(other.to_string()))
+            }
+            _ => self.strict_equals(other),
+        }
+    }
+
+    /// Returns Some(true) if `self` < `other`, or None if either is NaN.
+    /// https://tc39.es/ecma262/#sec-islessthan
+    pub fn less_than(&self, other: &Value) -> Option<bool> {
+        let primitive = |v: &Value| match v {
+            Value::Object(_) => Value::String(v.to_string()),
+            _ => v.clone(),
+        };
+        match (primitive(self), primitive(other)) {
+            (Value::String(a), Value::String(b)) => Some(a < b),
+            (a, b) => {
+                let (a, b) = (a.to_number(), b.to_number());
+                if a.is_nan() || b.is_nan() {
+                    None
+                } else {
+                    Some(a < b)
+                }
+            }
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Boolean(b)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Number(n)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
+}
+
+impl From<ObjectRef> for Value {
+    fn from(o: ObjectRef) -> Self {
+        Value::Object(o)
+    }
+}
+
+/// The result of converting a value to a string.
+/// https://tc39.es/ecma262/#sec-tostring
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Undefined => write!(f, "undefined"),
+            Value::Null => write!(f, "null"),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Number(n) => write!(f, "{}", number_to_string(*n)),
+            Value::String(s) => write!(f, "{}", s),
+            Value::Object(o) => write!(f, "{}", o.borrow()),
+        }
+    }
+}
+
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{:?}", s),
+            _ => write!(f, "{}", self),
+        }
+    }
+}
+
+/// A reference to an object, which is shared by the values referring to it. Two references are
+/// equal if they refer to the same object.
+#[derive(Clone)]
+pub struct ObjectRef(Rc<RefCell<Object>>);
+
+impl ObjectRef {
+    pub fn new(object: Object) -> Self {
+        Self(Rc::new(RefCell::new(object)))
+    }
+
+    /// Creates an empty object, e.g. the result of `{}`.
+    pub fn ordinary() -> Self {
+        Self::new(Object::new(ObjectKind::Ordinary))
+    }
+
+    /// Creates an object which inherits the properties of `prototype`.
+    pub fn with_prototype(kind: ObjectKind, prototype: &ObjectRef) -> Self {
+        let mut object = Object::new(kind);
+        object.prototype = Some(prototype.clone());
+        Self::new(object)
+    }
+
+    pub fn native_function(name: &str, function: NativeFunction) -> Self {
+        Self::new(Object::new(ObjectKind::NativeFunction {
+            name: String::from(name),
+            function,
+        }))
+    }
+
+    /// Returns a reference which doesn't keep the object alive.
+    pub fn downgrade(&self) -> WeakObjectRef {
+        WeakObjectRef(Rc::downgrade(&self.0))
+    }
+
+    /// Returns the address of the object, which tells it from the others while it's alive.
+    pub fn address(&self) -> usize {
+        Rc::as_ptr(&self.0) as usize
+    }
+
+    pub fn borrow(&self) -> Ref<'_, Object> {
+        self.0.borrow()
+    }
+
+    pub fn borrow_mut(&self) -> RefMut<'_, Object> {
+        self.0.borrow_mut()
+    }
+
+    pub fn is_callable(&self) -> bool {
+        matches!(
+            self.borrow().kind,
+            ObjectKind::Function(_) | ObjectKind::NativeFunction { .. }
+        )
+    }
+
+    /// Returns the property `key` of the object or its prototypes, or undefined if there's none.
+    /// https://tc39.es/ecma262/#sec-ordinaryget
+    pub fn get(&self, key: &str) -> Value {
+        let mut object = self.clone();
+        loop {
+            if let Some(value) = object.borrow().get_own(key) {
+                return value;
+            }
+            let prototype = match &object.borrow().prototype {
+                Some(prototype) => prototype.clone(),
+                None => return Value::Undefined,
+            };
+            object = prototype;
+        }
+    }
+
+    /// Sets the own property `key` of the object.
+    pub fn set(&self, key: &str, value: Value) {
+        self.borrow_mut().set_own(key, value);
+    }
+
+    /// Returns true if the object or its prototypes have the property `key`.
+    pub fn has(&self, key: &str) -> bool {
+        if self.borrow().get_own(key).is_some() {
+            return true;
+        }
+        match &self.borrow().prototype {
+            Some(prototype) => prototype.has(key),
+            None => false,
+        }
+    }
+}
+
+impl PartialEq for ObjectRef {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// A reference to an object which doesn't keep it alive, e.g. the one of the heap.
+#[derive(Clone)]
+pub struct WeakObjectRef(Weak<RefCell<Object>>);
+
+impl WeakObjectRef {
+    /// Returns the object, or None if it's been dropped.
+    pub fn upgrade(&self) -> Option<ObjectRef> {
+        self.0.upgrade().map(ObjectRef)
+    }
+}
+
+pub struct Object {
+    pub kind: ObjectKind,
+    /// The properties in the order they're added, which is the order they're enumerated.
+    properties: Vec<(String, Value)>,
+    /// The object whose properties this object inherits.
+    pub prototype: Option<ObjectRef>,
+}
+
+impl Object {
+    pub fn new(kind: ObjectKind) -> Self {
+        Self {
+            kind,
+            properties: Vec::new(),
+            prototype: None,
+        }
+    }
+
+    /// Returns the own property `key`, including the elements and the length of an array.
+    pub fn get_own(&self, key: &str) -> Option<Value> {
+        if let ObjectKind::Host(host) = &self.kind {
+            if let Some(value) = host.get(key) {
+                return Some(value);
+            }
+        }
+        if let ObjectKind::Array(elements) = &self.kind {
+            if key == "length" {
+                return Some(Value::Number(elements.len() as f64));
+            }
+            if let Some(index) = array_index(key) {
+                return elements.get(index).cloned();
+            }
+        }
+        self.properties
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.clone())
+    }
+
+    /// Sets the own property `key`. Setting an element of an array past its end extends it, and
+    /// setting its length truncates or extends it.
+    pub fn set_own(&mut self, key: &str, value: Value) {
+        if let ObjectKind::Host(host) = &self.kind {
+            if host.set(key, &value) {
+                return;
+            }
+        }
+        if let ObjectKind::Array(elements) = &mut self.kind {
+            if key == "length" {
+                let length = value.to_uint32() as usize;
+                elements.resize(length, Value::Undefined);
+                return;
+            }
+            if let Some(index) = array_index(key) {
+                if index >= elements.len() {
+                    elements.resize(index + 1, Value::Undefined);
+                }
+                elements[index] = value;
+                return;
+            }
+        }
+        match self.properties.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value,
+            None => self.properties.push((String::from(key), value)),
+        }
+    }
+
+    /// Removes the own property `key`, and returns true if it's removed. Deleting an element of
+    /// an array leaves a hole, which is undefined here.
+    pub fn delete(&mut self, key: &str) -> bool {
+        if let ObjectKind::Array(elements) = &mut self.kind {
+            if let Some(index) = array_index(key) {
+                if let Some(element) = elements.get_mut(index) {
+                    *element = Value::Undefined;
+                }
+                return true;
+            }
+        }
+        let len = self.properties.len();
+        self.properties.retain(|(k, _)| k != key);
+        self.properties.len() < len
+    }
+
+    /// Returns the values which the object refers to, which are the ones of its properties and
+    /// elements and the ones held by the host.
+    pub fn values(&self) -> Vec<Value> {
+        let mut values: Vec<Value> = self.properties.iter().map(|(_, v)| v.clone()).collect();
+        match &self.kind {
+            ObjectKind::Array(elements) => values.extend(elements.iter().cloned()),
+            ObjectKind::Host(host) => values.extend(host.values()),
+            _ => {}
+        }
+        values
+    }
+
+    /// Removes everything the object refers to, which breaks the cycles it's in. It's an empty
+    /// ordinary object after this.
+    pub fn clear(&mut self) {
+        self.kind = ObjectKind::Ordinary;
+        self.properties.clear();
+        self.prototype = None;
+    }
+
+    /// Returns the keys of the own properties, where the indexes of an array come first.
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys = Vec::new();
+        if let ObjectKind::Array(elements) = &self.kind {
+            keys.extend((0..elements.len()).map(|i| format!("{}", i)));
+        }
+        keys.extend(self.properties.iter().map(|(k, _)| k.clone()));
+        keys
+    }
+}
+
+impl fmt::Display for Object {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ObjectKind::Ordinary => write!(f, "[object Object]"),
+            // https://tc39.es/ecma262/#sec-array.prototype.join
+            ObjectKind::Array(elements) => {
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    match element {
+                        Value::Undefined | Value::Null => {}
+                        _ => write!(f, "{}", element)?,
+                    }
+                }
+                Ok(())
+            }
+            ObjectKind::Function(closure) => write!(
+                f,
+                "function {}({}) {{ ... }}",
+                closure.function.name.as_deref().unwrap_or(""),
+                closure.function.params.join(", ")
+            ),
+            ObjectKind::NativeFunction { name, .. } => {
+                write!(f, "function {}() {{ [native code] }}", name)
+            }
+            ObjectKind::Host(host) => write!(f, "[object {}]", host.class_name()),
+        }
+    }
+}
+
+pub enum ObjectKind {
+    Ordinary,
+    Array(Vec<Value>),
+    /// A function written in JavaScript.
+    Function(Closure),
+    NativeFunction {
+        name: String,
+        function: NativeFunction,
+    },
+    /// An object whose properties are partly backed by the host, e.g. an element.
+    Host(Rc<dyn HostObject>),
+}
+
+/// The properties of an object which are backed by something outside the runtime, e.g. the
+/// attributes of an element. The other properties are ordinary ones.
+pub trait HostObject {
+    /// Returns the name shown when the object is converted to a string, e.g. "HTMLElement".
+    fn class_name(&self) -> &str;
+
+    /// Returns the property `key`, or None if it isn't backed by the host.
+    fn get(&self, key: &str) -> Option<Value>;
+
+    /// Sets the property `key`, and returns false if it isn't backed by the host.
+    fn set(&self, key: &str, value: &Value) -> bool;
+
+    /// Returns the object as `Any` so that the host can find its own type.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Returns the values which the host holds for the object, which the collector keeps alive.
+    fn values(&self) -> Vec<Value> {
+        Vec::new()
+    }
+}
+
+/// A function with the scope where it's made, whose variables it can use.
+#[derive(Clone)]
+pub struct Closure {
+    pub function: Rc<Function>,
+    pub scope: Rc<Scope>,
+}
+
+/// Returns `key` as an index of an array if it's one, e.g. Some(1) for "1" but None for "01".
+fn array_index(key: &str) -> Option<usize> {
+    if key.is_empty() || (key.len() > 1 && key.starts_with('0')) {
+        return None;
+    }
+    if !key.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    key.parse::<u32>().ok().map(|i| i as usize)
+}
+
+/// https://tc39.es/ecma262/#sec-stringtonumber
+pub fn string_to_number(s: &str) -> f64 {
+    let s = s.trim();
+    if s.is_empty() {
+        return 0.0;
+    }
+    let radix = match s.get(..2) {
+        Some("0x") | Some("0X") => 16,
+        Some("0o") | Some("0O") => 8,
+        Some("0b") | Some("0B") => 2,
+        _ => 10,
+    };
+    if radix != 10 {
+        return match u64::from_str_radix(&s[2..], radix) {
+            Ok(n) => n as f64,
+            Err(_) => f64::NAN,
+        };
+    }
+    let unsigned = s.trim_start_matches(&['+', '-'][..]);
+    if unsigned == "Infinity" && s.len() - unsigned.len() <= 1 {
+        return if s.starts_with('-') {
+            f64::NEG_INFINITY
+        } else {
+            f64::INFINITY
+        };
+    }
+    // Rust accepts "inf" and "nan", which JavaScript doesn't.
+    if !s
+        .chars()
+        .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'))
+    {
+        return f64::NAN;
+    }
+    s.parse::<f64>().unwrap_or(f64::NAN)
+}
+
+/// https://tc39.es/ecma262/#sec-numeric-types-number-tostring
+pub fn number_to_string(n: f64) -> String {
+    if n.is_nan() {
+        return String::from("NaN");
+    }
+    if n == 0.0 {
+        // -0 is "0" as well.
+        return String::from("0");
+    }
+    if n.is_infinite() {
+        return String::from(if n > 0.0 { "Infinity" } else { "-Infinity" });
+    }
+    let magnitude = if n < 0.0 { -n } else { n };
+    if !(1e-6..1e21).contains(&magnitude) {
+        // Rust writes "1e21" and "1e-7", where JavaScript writes "1e+21" and "1e-7".
+        let s = format!("{:e}", n);
+        return match s.find('e') {
+            Some(i) if !s[i + 1..].starts_with('-') => format!("{}e+{}", &s[..i], &s[i + 1..]),
+            _ => s,
+        };
+    }
+    if floor(n) == n {
+        // An integer is written without a fraction.
+        return format!("{}", n as i64);
+    }
+    format!("{}", n)
+}
@@ -0,0 +1,308 @@
+//! Keyboard and pointer input of the window, and the scrolling and the text editing bound to the
+//! keys.
+//!
+//! liumOS sends the input as events, which tell every key and where the pointer is clicked. The
+//! standard input is read instead if there are none, e.g. on Linux, where only keys arrive. The
+//! keys pressed on the keyboard of liumOS arrive there as the lowest byte of their key IDs (see
+//! src/keyid.h). In both, the ones typed in a terminal connected to the serial port arrive as
+//! escape sequences.
+//! https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h2-PC-Style-Function-Keys
+
+use crate::form::control_value;
+use crate::page::Page;
+use crate::parser::dom::ElementKind;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use liumlib::input::{self, Button, Event, InputDevice, Modifiers};
+use liumlib::poll::{poll, PollFd, POLLIN, STDIN};
+use liumlib::*;
+
+/// How far the arrow keys scroll in px.
+const LINE_STEP: f64 = 40.0;
+
+/// A key recognized in the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Tab,
+    /// Shift+Tab.
+    BackTab,
+    Enter,
+    Backspace,
+    Delete,
+    Escape,
+    /// Any other byte, e.g. a printable character.
+    Char(u8),
+}
+
+/// Decodes bytes of the input into keys.
+#[derive(Debug, Default)]
+pub struct KeyReader {
+    /// The bytes of an escape sequence read so far.
+    pending: Vec<u8>,
+}
+
+#[allow(dead_code)]
+impl KeyReader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the next byte of the input. Returns the key if `byte` completes one, or None while
+    /// in the middle of an escape sequence. Unknown escape sequences are ignored.
+    pub fn feed(&mut self, byte: u8) -> Option<Key> {
+        if self.pending.is_empty() {
+            return match byte {
+                0x1b => {
+                    self.pending.push(byte);
+                    None
+                }
+                // The key ID of Esc, whose lowest byte is 0.
+                0x00 => Some(Key::Escape),
+                // The key IDs of the cursor keys. The ones of PageUp, PageDown, Home and End are
+                // truncated into ' ', '!', '"' and '#', so they can't be told from the
+                // characters.
+                0x1c => Some(Key::Up),
+                0x1d => Some(Key::Down),
+                0x1e => Some(Key::Left),
+                0x1f => Some(Key::Right),
+                // The key ID of Tab is truncated into 'A', so only the one from the serial port
+                // is recognized.
+                b'\t' => Some(Key::Tab),
+                // liumOS turns Enter into a newline.
+                b'\n' | b'\r' => Some(Key::Enter),
+                // liumOS turns Backspace, and DEL from the serial port, into the key ID of
+                // Backspace, which is truncated into '@'. '@' can't be typed instead, which URLs
+                // hardly need.
+                b'@' | 0x08 | 0x7f => Some(Key::Backspace),
+                // The key ID of Delete.
+                0x16 => Some(Key::Delete),
+                _ => Some(Key::Char(byte)),
+            };
+        }
+
+        if self.pending.len() == 1 {
+            if byte == b'[' || byte == b'O' {
+                self.pending.push(byte);
+                return None;
+            }
+            // Not an escape sequence. There is no way to wait for the byte after Esc with a
+            // timeout, so Esc pressed twice is Esc, and otherwise the escape is dropped.
+            self.pending.clear();
+            if byte == 0x1b {
+                return Some(Key::Escape);
+            }
+            return self.feed(byte);
+        }
+
+        // Parameters are digits and `;`, and a byte in 0x40..=0x7e ends the sequence.
+        if byte.is_ascii_digit() || byte == b';' {
+            self.pending.push(byte);
+            return None;
+        }
+        let param = core::str::from_utf8(&self.pending[2..])
+            .ok()
+            .and_then(|p| p.split(';').next())
+            .and_then(|p| p.parse::<u32>().ok());
+        self.pending.clear();
+        match (byte, param) {
+            (b'A', _) => Some(Key::Up),
+            (b'B', _) => Some(Key::Down),
+            (b'C', _) => Some(Key::Right),
+            (b'D', _) => Some(Key::Left),
+            (b'H', _) => Some(Key::Home),
+            (b'F', _) => Some(Key::End),
+            (b'Z', _) => Some(Key::BackTab),
+            (b'~', Some(1)) | (b'~', Some(7)) => Some(Key::Home),
+            (b'~', Some(3)) => Some(Key::Delete),
+            (b'~', Some(4)) | (b'~', Some(8)) => Some(Key::End),
+            (b'~', Some(5)) => Some(Key::PageUp),
+            (b'~', Some(6)) => Some(Key::PageDown),
+            _ => None,
+        }
+    }
+
+    /// Reads the key pressed in a key event of liumOS. Returns the key, or None like `feed()`
+    /// while in the middle of an escape sequence or if the key isn't bound to anything.
+    pub fn feed_event(&mut self, key: input::Key, modifiers: Modifiers) -> Option<Key> {
+        if let input::Key::Char(c) = key {
+            if !c.is_ascii() {
+                return None;
+            }
+            // Ctrl turns a letter into its control character, e.g. Ctrl+L into 0x0c.
+            let byte = match c as u8 {
+                b @ b'a'..=b'z' | b @ b'A'..=b'Z' if modifiers.ctrl => b & 0x1f,
+                b => b,
+            };
+            // An escape sequence from the serial port is decoded like the standard input.
+            if byte == 0x1b || !self.pending.is_empty() {
+                return self.feed(byte);
+            }
+            return Some(Key::Char(byte));
+        }
+        // Another key ends an escape sequence.
+        self.pending.clear();
+        match key {
+            input::Key::Escape => Some(Key::Escape),
+            input::Key::Enter => Some(Key::Enter),
+            input::Key::Backspace => Some(Key::Backspace),
+            input::Key::Tab if modifiers.shift => Some(Key::BackTab),
+            input::Key::Tab => Some(Key::Tab),
+            input::Key::Delete => Some(Key::Delete),
+            input::Key::Up => Some(Key::Up),
+            input::Key::Down => Some(Key::Down),
+            input::Key::Left => Some(Key::Left),
+            input::Key::Right => Some(Key::Right),
+            input::Key::PageUp => Some(Key::PageUp),
+            input::Key::PageDown => Some(Key::PageDown),
+            input::Key::Home => Some(Key::Home),
+            input::Key::End => Some(Key::End),
+            _ => None,
+        }
+    }
+}
+
+/// What the user does in the window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Input {
+    Key(Key),
+    /// A click of the primary button at a point in px relative to the window.
+    Click {
+        x: f64,
+        y: f64,
+    },
+}
+
+/// Reads the input from the events of liumOS, or from the standard input if there are none.
+#[derive(Debug)]
+pub struct InputReader {
+    device: Option<InputDevice>,
+    keys: KeyReader,
+    /// The events read but not handled yet.
+    events: VecDeque<Event>,
+}
+
+impl Default for InputReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[allow(dead_code)]
+impl InputReader {
+    pub fn new() -> Self {
+        Self {
+            device: InputDevice::open(),
+            keys: KeyReader::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Returns true if the input is read from the events, which include the pointer.
+    pub fn has_events(&self) -> bool {
+        self.device.is_some()
+    }
+
+    /// Waits until input arrives or `timeout` milliseconds pass, or forever if it's None. Returns
+    /// true if `read()` doesn't block, or if waiting fails, in which case reading blocks instead.
+    pub fn wait(&self, timeout: Option<u64>) -> bool {
+        if !self.events.is_empty() {
+            return true;
+        }
+        let fd = match &self.device {
+            Some(device) => device.file_descriptor().number(),
+            None => STDIN,
+        };
+        let mut fds = [PollFd::new(fd, POLLIN)];
+        match poll(&mut fds, timeout) {
+            Some(_) => fds[0].is_readable(),
+            None => true,
+        }
+    }
+
+    /// Reads the next input, waiting for it if nothing has arrived. Returns None if what is read
+    /// doesn't make input by itself, e.g. a key released or the middle of an escape sequence.
+    pub fn read(&mut self) -> Option<Input> {
+        let device = match &mut self.device {
+            Some(device) => device,
+            None => return self.keys.feed(getchar()).map(Input::Key),
+        };
+        if self.events.is_empty() {
+            self.events.extend(device.read()?);
+        }
+        let event = self.events.pop_front()?;
+        self.translate(event)
+    }
+
+    /// Returns the input which `event` makes, or None if it makes none.
+    pub fn translate(&mut self, event: Event) -> Option<Input> {
+        match event {
+            Event::KeyDown { key, modifiers } => {
+                self.keys.feed_event(key, modifiers).map(Input::Key)
+            }
+            Event::PointerDown {
+                button: Button::Left,
+                x,
+                y,
+            } => Some(Input::Click {
+                x: x as f64,
+                y: y as f64,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Scrolls `page` as `key` does, if it's a key to scroll with. Returns true if the scroll
+/// position changed, i.e. the page needs repainting.
+#[allow(dead_code)]
+pub fn scroll_with_key(page: &mut Page, key: Key) -> bool {
+    // A page keeps a line of the previous page in view.
+    let page_step = (page.viewport().height - LINE_STEP).max(LINE_STEP);
+    match key {
+        Key::Up => page.scroll_by(-LINE_STEP),
+        Key::Down => page.scroll_by(LINE_STEP),
+        Key::PageUp => page.scroll_by(-page_step),
+        Key::PageDown => page.scroll_by(page_step),
+        Key::Home => page.scroll_to(0.0),
+        Key::End => page.scroll_to(page.max_scroll_y()),
+        // The document never overflows horizontally.
+        Key::Left | Key::Right => false,
+        Key::Tab | Key::BackTab | Key::Enter => false,
+        Key::Backspace | Key::Delete | Key::Escape | Key::Char(_) => false,
+    }
+}
+
+/// Edits the value of the focused text control with `key`. A printable character is typed at the
+/// end of the value, Backspace deletes the last character, Enter starts a new line in a textarea
+/// and Escape leaves the control. Returns None if no text control has the focus or `key` doesn't
+/// edit text, e.g. Tab or Enter in a text field, so that it works as usual. Otherwise returns
+/// true if the page needs repainting.
+#[allow(dead_code)]
+pub fn edit_with_key(page: &mut Page, key: Key) -> Option<bool> {
+    let control = page.focused_text_control()?;
+    let is_textarea = page.document().node(control).element_kind() == Some(ElementKind::Textarea);
+    let mut value = control_value(page.document(), control);
+    match key {
+        Key::Char(c @ 0x20..=0x7e) => value.push(c as char),
+        Key::Enter if is_textarea => value.push('\n'),
+        Key::Backspace => {
+            if value.pop().is_none() {
+                return Some(false);
+            }
+        }
+        // The caret is always at the end, so there's nothing to delete after it.
+        Key::Delete => return Some(false),
+        Key::Escape => return Some(page.focus(None)),
+        _ => return None,
+    }
+    page.set_control_value(control, value);
+    Some(true)
+}
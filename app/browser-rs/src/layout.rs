@@ -0,0 +1,41 @@
+//! Layout: the box tree and the positions and sizes of the boxes.
+//! https://www.w3.org/TR/CSS2/visuren.html
+
+pub mod block;
+pub mod box_tree;
+pub mod dump;
+pub mod geometry;
+pub mod inline;
+pub mod positioned;
+pub mod replaced;
+pub mod table;
+pub mod text_control;
+pub mod text_dump;
+
+use crate::parser::dom::*;
+use crate::style::computed::*;
+use block::*;
+use box_tree::*;
+use geometry::*;
+use positioned::*;
+
+/// Builds the box tree of `document` and lays it out in `viewport`. Returns None if nothing is
+/// rendered.
+#[allow(dead_code)]
+pub fn layout(document: &Document, styles: &StyleMap, viewport: Viewport) -> Option<LayoutBox> {
+    let mut root = build_box_tree(document, styles)?;
+
+    // "The containing block in which the root element lives is a rectangle called the initial
+    // containing block. For continuous media, it has the dimensions of the viewport and is
+    // anchored at the canvas origin."
+    // https://www.w3.org/TR/CSS2/visudet.html#containing-block-details
+    let initial_containing_block = Rect::new(0.0, 0.0, viewport.width, viewport.height);
+    layout_block(
+        &mut root,
+        initial_containing_block,
+        0.0,
+        Some(viewport.height),
+    );
+    layout_positioned(&mut root, initial_containing_block);
+    Some(root)
+}
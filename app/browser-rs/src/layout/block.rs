@@ -0,0 +1,410 @@
+//! Block formatting contexts.
+//! https://www.w3.org/TR/CSS2/visuren.html#block-formatting
+//! https://www.w3.org/TR/CSS2/visudet.html
+
+use crate::layout::box_tree::*;
+use crate::layout::geometry::*;
+use crate::layout::inline::*;
+use crate::layout::replaced::*;
+use crate::layout::table::*;
+use crate::style::values::*;
+
+/// Lays out the root box and its descendants. The top of the margin box is at `y` in
+/// `containing_block`. `percentage_height_basis` is the height of the containing block if it's
+/// definite, which percentages of height refer to.
+pub fn layout_block(
+    layout_box: &mut LayoutBox,
+    containing_block: Rect,
+    y: f64,
+    percentage_height_basis: Option<f64>,
+) {
+    // The horizontal sizes don't depend on the vertical ones, so they are computed first. Then
+    // the vertical positions can take collapsed margins, which depend on the descendants.
+    compute_edges(layout_box, containing_block.width);
+
+    // "Margins of the root element's box do not collapse." The root also establishes a block
+    // formatting context.
+    let top = y + layout_box.dimensions().margin.top;
+    place_block(
+        layout_box,
+        containing_block,
+        top,
+        percentage_height_basis,
+        true,
+    );
+}
+
+/// Computes the widths and the edges of a block-level box and its block-level descendants.
+pub fn compute_edges(layout_box: &mut LayoutBox, containing_width: f64) {
+    if *layout_box.kind() == BoxKind::Table {
+        compute_table_edges(layout_box, containing_width);
+        return;
+    }
+
+    // "The used value of 'width' is determined as for inline replaced elements. Then the rules
+    // for non-replaced block-level elements are applied to determine the margins."
+    // https://www.w3.org/TR/CSS2/visudet.html#block-replaced-width
+    let width = match replaced_size(layout_box, containing_width) {
+        Some((width, _)) => Length::Px(width),
+        None => layout_box.style().width(),
+    };
+    compute_width(layout_box, containing_width, width);
+    compute_vertical_edges(layout_box, containing_width);
+    compute_children_edges(layout_box);
+}
+
+/// Computes the widths and the edges of the block-level children of `layout_box` whose width is
+/// already computed.
+pub fn compute_children_edges(layout_box: &mut LayoutBox) {
+    let width = layout_box.dimensions().content.width;
+    for child in layout_box.children_mut() {
+        if !child.is_inline_level() {
+            compute_edges(child, width);
+        }
+    }
+}
+
+/// Places a block-level box whose top border edge is at `top` and lays out its children.
+/// `new_context` is true if the box establishes a new block formatting context, e.g. the root and
+/// a table cell, so that its margins don't collapse with its children.
+/// https://www.w3.org/TR/CSS2/visuren.html#block-formatting
+pub fn place_block(
+    layout_box: &mut LayoutBox,
+    containing_block: Rect,
+    top: f64,
+    percentage_height_basis: Option<f64>,
+    new_context: bool,
+) {
+    let replaced = replaced_size(layout_box, containing_block.width);
+    let height = match layout_box.style().height() {
+        _ if replaced.is_some() => replaced.map(|(_, height)| height),
+        Length::Auto => None,
+        Length::Percentage(p) => percentage_height_basis.map(|h| h * p / 100.0),
+        l => Some(l.to_px(0.0)),
+    };
+
+    let d = layout_box.dimensions_mut();
+    d.content.x = containing_block.x + d.margin.left + d.border.left + d.padding.left;
+    d.content.y = top + d.border.top + d.padding.top;
+    let content = d.content;
+
+    let content_height = layout_children(layout_box, content, height, new_context);
+
+    // "If 'height' is 'auto', the height depends on whether the element has any block-level
+    // children [...] the height is the distance between the top content edge and [...] the
+    // bottom margin edge of the bottom (possibly anonymous) child box".
+    // https://www.w3.org/TR/CSS2/visudet.html#normal-block
+    layout_box.dimensions_mut().content.height = height.unwrap_or(content_height);
+
+    let border_box = layout_box.dimensions().border_box();
+    let first_line = first_line(layout_box);
+    if let Some(marker) = layout_box.marker_mut() {
+        place_marker(marker, border_box, first_line);
+    }
+}
+
+/// Returns the first line box in `layout_box` or its descendants.
+fn first_line(layout_box: &LayoutBox) -> Option<Rect> {
+    match layout_box.lines().first() {
+        Some(line) => Some(line.rect()),
+        None => layout_box.children().iter().find_map(first_line),
+    }
+}
+
+/// Lays out the children from the top of `content` and returns the height of them. Block-level
+/// children are stacked vertically with their adjoining margins collapsed, and inline-level
+/// children are placed in line boxes.
+fn layout_children(
+    layout_box: &mut LayoutBox,
+    content: Rect,
+    height: Option<f64>,
+    new_context: bool,
+) -> f64 {
+    if *layout_box.kind() == BoxKind::Table {
+        return layout_table_rows(layout_box, content);
+    }
+
+    // The box tree guarantees that the children are either all inline-level or all block-level.
+    if layout_box.children().iter().any(|c| c.is_inline_level()) {
+        let lines = layout_inline(layout_box.children(), content);
+        let height = lines.iter().map(|l| l.rect().height).sum();
+        place_replaced(layout_box.children_mut(), &lines);
+        layout_box.set_lines(lines);
+        return height;
+    }
+
+    let collapses_bottom = !new_context && bottom_collapses_with_last_child(layout_box);
+    // While this is true, the top margins of the children are a part of the top margin of
+    // `layout_box`, which has already been applied.
+    let mut absorbing = !new_context && top_collapses_with_first_child(layout_box);
+    // The bottom border edge of the previous child and the margins after it.
+    let mut cursor = content.y;
+    let mut pending = CollapsedMargin::default();
+
+    for child in layout_box.children_mut() {
+        if !absorbing {
+            pending.adjoin(top_margin(child));
+        }
+        // "If the top and bottom margins of a box are adjoining, then it is possible for margins
+        // to collapse through it."
+        if collapses_through(child) {
+            place_block(child, content, cursor + pending.value(), height, false);
+            if !absorbing {
+                pending.adjoin(bottom_margin(child));
+            }
+            continue;
+        }
+        absorbing = false;
+        place_block(child, content, cursor + pending.value(), height, false);
+        cursor = child.dimensions().border_box().bottom();
+        pending = bottom_margin(child);
+    }
+
+    if collapses_bottom {
+        // The margins after the last child are a part of the bottom margin of `layout_box`.
+        cursor - content.y
+    } else {
+        cursor + pending.value() - content.y
+    }
+}
+
+/// Margins which are adjoining and collapsed into one.
+/// https://www.w3.org/TR/CSS2/box.html#collapsing-margins
+#[derive(Debug, Copy, Clone, Default)]
+struct CollapsedMargin {
+    positive: f64,
+    negative: f64,
+}
+
+impl CollapsedMargin {
+    fn new(margin: f64) -> Self {
+        let mut m = Self::default();
+        m.adjoin_px(margin);
+        m
+    }
+
+    fn adjoin_px(&mut self, margin: f64) {
+        if margin > self.positive {
+            self.positive = margin;
+        }
+        if margin < self.negative {
+            self.negative = margin;
+        }
+    }
+
+    fn adjoin(&mut self, other: CollapsedMargin) {
+        self.adjoin_px(other.positive);
+        self.adjoin_px(other.negative);
+    }
+
+    /// "When two or more margins collapse, the resulting margin width is the maximum of the
+    /// collapsing margins' widths. In the case of negative margins, the maximum of the absolute
+    /// values of the negative adjoining margins is deducted from the maximum of the positive
+    /// adjoining margins."
+    fn value(&self) -> f64 {
+        self.positive + self.negative
+    }
+}
+
+/// Returns the top margin of `layout_box` collapsed with the margins of its descendants which are
+/// adjoining to it.
+fn top_margin(layout_box: &LayoutBox) -> CollapsedMargin {
+    let mut margin = CollapsedMargin::new(layout_box.dimensions().margin.top);
+    if top_collapses_with_first_child(layout_box) {
+        for child in layout_box.children() {
+            margin.adjoin(top_margin(child));
+            if !collapses_through(child) {
+                break;
+            }
+            margin.adjoin(bottom_margin(child));
+        }
+    }
+    margin
+}
+
+/// Returns the bottom margin of `layout_box` collapsed with the margins of its descendants which
+/// are adjoining to it.
+fn bottom_margin(layout_box: &LayoutBox) -> CollapsedMargin {
+    let mut margin = CollapsedMargin::new(layout_box.dimensions().margin.bottom);
+    if bottom_collapses_with_last_child(layout_box) {
+        for child in layout_box.children().iter().rev() {
+            margin.adjoin(bottom_margin(child));
+            if !collapses_through(child) {
+                break;
+            }
+            margin.adjoin(top_margin(child));
+        }
+    }
+    margin
+}
+
+/// "top margin of a box and top margin of its first in-flow child" are adjoining if there is no
+/// border or padding between them.
+fn top_collapses_with_first_child(layout_box: &LayoutBox) -> bool {
+    let d = layout_box.dimensions();
+    has_block_children(layout_box) && d.border.top == 0.0 && d.padding.top == 0.0
+}
+
+/// "bottom margin of a last in-flow child and bottom margin of its parent if the parent has
+/// 'auto' computed height" are adjoining if there is no border or padding between them.
+fn bottom_collapses_with_last_child(layout_box: &LayoutBox) -> bool {
+    let d = layout_box.dimensions();
+    has_block_children(layout_box)
+        && layout_box.style().height().is_auto()
+        && d.border.bottom == 0.0
+        && d.padding.bottom == 0.0
+}
+
+/// Returns true if the top and the bottom margins of `layout_box` are adjoining, which is the
+/// case for a box with no height, no border, no padding and no content. A replaced box always has
+/// content.
+/// https://www.w3.org/TR/CSS2/box.html#collapsing-margins
+fn collapses_through(layout_box: &LayoutBox) -> bool {
+    if is_table_part(layout_box) || layout_box.is_replaced() {
+        return false;
+    }
+    let d = layout_box.dimensions();
+    let zero_height = match layout_box.style().height() {
+        Length::Auto | Length::Percentage(_) => true,
+        l => l.to_px(0.0) == 0.0,
+    };
+    zero_height
+        && d.border.top == 0.0
+        && d.border.bottom == 0.0
+        && d.padding.top == 0.0
+        && d.padding.bottom == 0.0
+        && layout_box
+            .children()
+            .iter()
+            .all(|c| !c.is_inline_level() && collapses_through(c))
+}
+
+/// Returns true if the children of `layout_box` are laid out as a part of a table. Tables and
+/// cells establish new block formatting contexts, so their margins don't collapse with their
+/// children.
+fn is_table_part(layout_box: &LayoutBox) -> bool {
+    matches!(
+        layout_box.kind(),
+        BoxKind::Table | BoxKind::TableRowGroup | BoxKind::TableRow | BoxKind::TableCell
+    )
+}
+
+fn has_block_children(layout_box: &LayoutBox) -> bool {
+    !is_table_part(layout_box)
+        && layout_box
+            .children()
+            .first()
+            .is_some_and(|c| !c.is_inline_level())
+}
+
+/// Computes the used width, the horizontal margins, borders and paddings of a block-level box in
+/// normal flow.
+/// https://www.w3.org/TR/CSS2/visudet.html#blockwidth
+/// `width` is the specified width, or the width decided by table layout for a table.
+pub fn compute_width(layout_box: &mut LayoutBox, containing_width: f64, width: Length) {
+    let style = layout_box.style();
+    let margin = style.margin();
+    let padding = style.padding().to_px(containing_width);
+    let border = style.border_width();
+
+    let mut margin_left = margin.left;
+    let mut margin_right = margin.right;
+
+    let total = margin_left.to_px(containing_width)
+        + border.left
+        + padding.left
+        + width.to_px(containing_width)
+        + padding.right
+        + border.right
+        + margin_right.to_px(containing_width);
+
+    // "If 'width' is not 'auto' and 'border-left-width' + 'padding-left' + 'width' +
+    // 'padding-right' + 'border-right-width' (plus any of 'margin-left' or 'margin-right' that
+    // are not 'auto') is larger than the width of the containing block, then any 'auto' values
+    // for 'margin-left' or 'margin-right' are, for the following rules, treated as zero."
+    if !width.is_auto() && total > containing_width {
+        if margin_left.is_auto() {
+            margin_left = Length::Px(0.0);
+        }
+        if margin_right.is_auto() {
+            margin_right = Length::Px(0.0);
+        }
+    }
+
+    let underflow = containing_width - total;
+    let (width, margin_left, margin_right) = match (
+        width.is_auto(),
+        margin_left.is_auto(),
+        margin_right.is_auto(),
+    ) {
+        // "If all of the above have a computed value other than 'auto', the values are said to
+        // be 'over-constrained' and one of the used values will have to be different from its
+        // computed value. If the 'direction' property of the containing block has the value
+        // 'ltr', the specified value of 'margin-right' is ignored and the value is calculated so
+        // as to make the equality true."
+        (false, false, false) => (
+            width.to_px(containing_width),
+            margin_left.to_px(containing_width),
+            margin_right.to_px(containing_width) + underflow,
+        ),
+        // "If there is exactly one value specified as 'auto', its used value follows from the
+        // equality."
+        (false, true, false) => (
+            width.to_px(containing_width),
+            underflow,
+            margin_right.to_px(containing_width),
+        ),
+        (false, false, true) => (
+            width.to_px(containing_width),
+            margin_left.to_px(containing_width),
+            underflow,
+        ),
+        // "If both 'margin-left' and 'margin-right' are 'auto', their used values are equal.
+        // This horizontally centers the element with respect to the edges of the containing
+        // block."
+        (false, true, true) => (
+            width.to_px(containing_width),
+            underflow / 2.0,
+            underflow / 2.0,
+        ),
+        // "If 'width' is set to 'auto', any other 'auto' values become '0' and 'width' follows
+        // from the resulting equality."
+        (true, _, _) => {
+            let margin_left = margin_left.to_px(containing_width);
+            let margin_right = margin_right.to_px(containing_width);
+            if underflow >= 0.0 {
+                (underflow, margin_left, margin_right)
+            } else {
+                // The width can't be negative. The box overflows the containing block.
+                (0.0, margin_left, margin_right + underflow)
+            }
+        }
+    };
+
+    let d = layout_box.dimensions_mut();
+    d.content.width = width;
+    d.margin.left = margin_left;
+    d.margin.right = margin_right;
+    d.padding.left = padding.left;
+    d.padding.right = padding.right;
+    d.border.left = border.left;
+    d.border.right = border.right;
+}
+
+/// Computes the vertical margins, borders and paddings. Percentages refer to the width of the
+/// containing block and `auto` margins are 0.
+/// https://www.w3.org/TR/CSS2/visudet.html#normal-block
+pub fn compute_vertical_edges(layout_box: &mut LayoutBox, containing_width: f64) {
+    let style = layout_box.style();
+    let margin = style.margin().to_px(containing_width);
+    let padding = style.padding().to_px(containing_width);
+    let border = style.border_width();
+
+    let d = layout_box.dimensions_mut();
+    d.margin.top = margin.top;
+    d.margin.bottom = margin.bottom;
+    d.padding.top = padding.top;
+    d.padding.bottom = padding.bottom;
+    d.border.top = border.top;
+    d.border.bottom = border.bottom;
+}
@@ -0,0 +1,457 @@
+//! This is a part of "2. Box Layout Modes" in CSS Display Module Level 3.
+//! https://www.w3.org/TR/css-display-3/#the-display-properties
+
+use crate::font::Font;
+use crate::layout::geometry::*;
+use crate::layout::inline::*;
+use crate::layout::text_control::natural_size;
+use crate::parser::dom::*;
+use crate::style::computed::*;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// https://www.w3.org/TR/css-display-3/#box-tree
+#[derive(Debug, Clone, PartialEq)]
+pub enum BoxKind {
+    /// A block box generated by an element.
+    /// https://www.w3.org/TR/css-display-3/#block-box
+    Block,
+    /// An inline box generated by an element.
+    /// https://www.w3.org/TR/css-display-3/#inline-box
+    Inline,
+    /// A block box which wraps a sequence of inline-level boxes in a block container that also
+    /// has block-level children.
+    /// https://www.w3.org/TR/CSS2/visuren.html#anonymous-block-level
+    AnonymousBlock,
+    /// https://www.w3.org/TR/CSS2/tables.html#table-display
+    Table,
+    TableRowGroup,
+    TableRow,
+    TableCell,
+    /// The box of an img element, whose contents are its image, or a text control, whose
+    /// contents are its value. The natural size of an image is that of the image, which is 0x0
+    /// if the image isn't available.
+    /// https://www.w3.org/TR/CSS2/conform.html#replaced-element
+    Replaced {
+        natural_width: f64,
+        natural_height: f64,
+    },
+    /// The contents of a text node with white space collapsed.
+    /// https://www.w3.org/TR/css-display-3/#text-run
+    Text(String),
+}
+
+/// A box in the box tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutBox {
+    kind: BoxKind,
+    /// The style of the element, or the inherited style for an anonymous box and a text run.
+    style: ComputedStyle,
+    /// The node which generated the box. None for an anonymous box.
+    node: Option<NodeId>,
+    children: Vec<LayoutBox>,
+    /// The position and the size, which are set by layout.
+    dimensions: Dimensions,
+    /// The line boxes of a block container with inline-level children, which are set by layout.
+    lines: Vec<LineBox>,
+    /// The marker of a list item, which is placed by layout.
+    /// https://www.w3.org/TR/css-lists-3/#marker-pseudo
+    marker: Option<Fragment>,
+    /// The absolutely positioned boxes generated by the child elements, which are not a part of
+    /// normal flow. They are laid out after the normal flow in their containing blocks.
+    /// https://www.w3.org/TR/CSS2/visuren.html#absolute-positioning
+    out_of_flow: Vec<LayoutBox>,
+}
+
+#[allow(dead_code)]
+impl LayoutBox {
+    fn new(kind: BoxKind, style: ComputedStyle, node: Option<NodeId>) -> Self {
+        Self {
+            kind,
+            style,
+            node,
+            children: Vec::new(),
+            dimensions: Dimensions::default(),
+            lines: Vec::new(),
+            marker: None,
+            out_of_flow: Vec::new(),
+        }
+    }
+
+    pub fn kind(&self) -> &BoxKind {
+        &self.kind
+    }
+
+    pub fn style(&self) -> &ComputedStyle {
+        &self.style
+    }
+
+    pub fn node(&self) -> Option<NodeId> {
+        self.node
+    }
+
+    pub fn children(&self) -> &Vec<LayoutBox> {
+        &self.children
+    }
+
+    pub fn children_mut(&mut self) -> &mut Vec<LayoutBox> {
+        &mut self.children
+    }
+
+    pub fn dimensions(&self) -> &Dimensions {
+        &self.dimensions
+    }
+
+    pub fn dimensions_mut(&mut self) -> &mut Dimensions {
+        &mut self.dimensions
+    }
+
+    pub fn lines(&self) -> &Vec<LineBox> {
+        &self.lines
+    }
+
+    pub fn set_lines(&mut self, lines: Vec<LineBox>) {
+        self.lines = lines;
+    }
+
+    pub fn marker(&self) -> Option<&Fragment> {
+        self.marker.as_ref()
+    }
+
+    pub fn marker_mut(&mut self) -> Option<&mut Fragment> {
+        self.marker.as_mut()
+    }
+
+    pub fn out_of_flow(&self) -> &Vec<LayoutBox> {
+        &self.out_of_flow
+    }
+
+    pub fn out_of_flow_mut(&mut self) -> &mut Vec<LayoutBox> {
+        &mut self.out_of_flow
+    }
+
+    /// Returns true if the box is absolutely positioned. A text run has the style of its parent,
+    /// so it's always in flow.
+    pub fn is_out_of_flow(&self) -> bool {
+        !matches!(self.kind, BoxKind::Text(_)) && self.style.position() == Position::Absolute
+    }
+
+    /// Moves the box and its descendants by `dx` and `dy`.
+    pub fn translate(&mut self, dx: f64, dy: f64) {
+        self.dimensions.content = self.dimensions.content.translated(dx, dy);
+        for line in &mut self.lines {
+            line.translate(dx, dy);
+        }
+        if let Some(marker) = &mut self.marker {
+            marker.translate(dx, dy);
+        }
+        for child in self.children.iter_mut().chain(self.out_of_flow.iter_mut()) {
+            child.translate(dx, dy);
+        }
+    }
+
+    /// Returns true for an inline box, a text run and an inline replaced box, which are laid out
+    /// in lines.
+    /// https://www.w3.org/TR/css-display-3/#inline-level
+    pub fn is_inline_level(&self) -> bool {
+        match self.kind {
+            BoxKind::Inline | BoxKind::Text(_) => true,
+            BoxKind::Replaced { .. } => self.style.display() == Display::Inline,
+            _ => false,
+        }
+    }
+
+    /// Returns true for the box of a replaced element.
+    pub fn is_replaced(&self) -> bool {
+        matches!(self.kind, BoxKind::Replaced { .. })
+    }
+
+    /// Returns true if the box is a text run which has only a collapsible space.
+    fn is_collapsible_space(&self) -> bool {
+        match &self.kind {
+            BoxKind::Text(text) => text == " ",
+            _ => false,
+        }
+    }
+}
+
+/// Builds the box tree of `document`. Returns None if the document has no element or the root
+/// element has `display: none`.
+/// https://www.w3.org/TR/css-display-3/#intro
+#[allow(dead_code)]
+pub fn build_box_tree(document: &Document, styles: &StyleMap) -> Option<LayoutBox> {
+    let root = document
+        .children(document.root())
+        .find(|id| document.node(*id).element().is_some())?;
+    let mut root_box = build_box(document, styles, root, &ComputedStyle::initial())?;
+    // "The root element's display type is always blockified."
+    // https://www.w3.org/TR/css-display-3/#root
+    root_box.kind = BoxKind::Block;
+    fix_block_container(&mut root_box);
+    Some(root_box)
+}
+
+/// Builds the box of the node `id` and its descendants. `parent_style` is the style of the parent
+/// element, which a text run takes. Returns None if the node generates no box.
+fn build_box(
+    document: &Document,
+    styles: &StyleMap,
+    id: NodeId,
+    parent_style: &ComputedStyle,
+) -> Option<LayoutBox> {
+    let node = document.node(id);
+    let style = match node.kind {
+        NodeKind::Text(ref data) => {
+            let text = collapse_white_space(data);
+            if text.is_empty() {
+                return None;
+            }
+            return Some(LayoutBox::new(
+                BoxKind::Text(text),
+                parent_style.clone(),
+                Some(id),
+            ));
+        }
+        NodeKind::Element(_) => styles.get(id)?,
+        _ => return None,
+    };
+
+    let kind = match style.display() {
+        // "The element and its descendants generate no boxes or text runs."
+        Display::None => return None,
+        Display::Block | Display::ListItem => BoxKind::Block,
+        Display::Table => BoxKind::Table,
+        Display::TableRowGroup => BoxKind::TableRowGroup,
+        Display::TableRow => BoxKind::TableRow,
+        Display::TableCell => BoxKind::TableCell,
+        Display::Inline => BoxKind::Inline,
+    };
+    // An img element is a replaced element whatever its display is, except for none. Its
+    // children, if any, are not rendered.
+    // https://html.spec.whatwg.org/multipage/rendering.html#images-3
+    if node.element().is_some_and(|e| e.tag_name() == "img") {
+        let (natural_width, natural_height) = document
+            .image(id)
+            .map_or((0.0, 0.0), |i| (i.width() as f64, i.height() as f64));
+        return Some(LayoutBox::new(
+            BoxKind::Replaced {
+                natural_width,
+                natural_height,
+            },
+            style.clone(),
+            Some(id),
+        ));
+    }
+    // So is a text control, which shows its value instead of its children.
+    if let Some((natural_width, natural_height)) = node
+        .element()
+        .and_then(|e| natural_size(e, &Font::from_style(style)))
+    {
+        return Some(LayoutBox::new(
+            BoxKind::Replaced {
+                natural_width,
+                natural_height,
+            },
+            style.clone(),
+            Some(id),
+        ));
+    }
+
+    let mut layout_box = LayoutBox::new(kind, style.clone(), Some(id));
+    let mut ordinal = list_start(document, id);
+    for child in document.children(id) {
+        if let Some(mut child_box) = build_box(document, styles, child, style) {
+            if child_box.style.display() == Display::ListItem {
+                ordinal = list_item_value(document, child).unwrap_or(ordinal);
+                child_box.marker =
+                    marker_text(child_box.style.list_style_type(), ordinal).map(|text| {
+                        let mut marker_style = child_box.style.clone();
+                        marker_style.clear_text_decoration();
+                        Fragment::new(text, marker_style, Some(child))
+                    });
+                ordinal += 1;
+            }
+            if child_box.is_out_of_flow() {
+                layout_box.out_of_flow.push(child_box);
+            } else {
+                layout_box.children.push(child_box);
+            }
+        }
+    }
+
+    if layout_box.kind == BoxKind::Inline
+        && layout_box.children.iter().any(|c| !c.is_inline_level())
+    {
+        // An inline box which contains a block-level box should be split around it. To keep the
+        // box tree simple, the inline box is treated as a block box instead.
+        // https://www.w3.org/TR/CSS2/visuren.html#anonymous-block-level
+        layout_box.kind = BoxKind::Block;
+    }
+    match layout_box.kind {
+        BoxKind::Block | BoxKind::TableCell => fix_block_container(&mut layout_box),
+        BoxKind::Table | BoxKind::TableRowGroup | BoxKind::TableRow => {
+            fix_table_children(&mut layout_box)
+        }
+        _ => {}
+    }
+
+    Some(layout_box)
+}
+
+/// Wraps children which can't be in a table, a row group or a row in anonymous rows or cells,
+/// e.g. a cell directly in a table gets an anonymous row. Sequences which have only collapsible
+/// white space are removed.
+/// https://www.w3.org/TR/CSS2/tables.html#anonymous-boxes
+fn fix_table_children(parent: &mut LayoutBox) {
+    let is_proper = |parent: &BoxKind, child: &BoxKind| match parent {
+        BoxKind::Table => matches!(child, BoxKind::TableRowGroup | BoxKind::TableRow),
+        BoxKind::TableRowGroup => *child == BoxKind::TableRow,
+        _ => *child == BoxKind::TableCell,
+    };
+    let wrapper = match parent.kind {
+        BoxKind::TableRow => BoxKind::TableCell,
+        _ => BoxKind::TableRow,
+    };
+
+    let children = core::mem::take(&mut parent.children);
+    let mut misparented: Vec<LayoutBox> = Vec::new();
+    for child in children {
+        if !is_proper(&parent.kind, &child.kind) {
+            misparented.push(child);
+            continue;
+        }
+        wrap_misparented(parent, wrapper.clone(), &mut misparented);
+        parent.children.push(child);
+    }
+    wrap_misparented(parent, wrapper, &mut misparented);
+}
+
+/// Moves `boxes` into a new anonymous box of `kind` at the end of the children of `parent`.
+fn wrap_misparented(parent: &mut LayoutBox, kind: BoxKind, boxes: &mut Vec<LayoutBox>) {
+    if boxes.iter().all(|c| c.is_collapsible_space()) {
+        boxes.clear();
+        return;
+    }
+    let mut anonymous = LayoutBox::new(kind, ComputedStyle::inherit_from(&parent.style), None);
+    anonymous.children = core::mem::take(boxes);
+    match anonymous.kind {
+        BoxKind::TableRow => fix_table_children(&mut anonymous),
+        _ => fix_block_container(&mut anonymous),
+    }
+    parent.children.push(anonymous);
+}
+
+/// Returns the ordinal value of the first list item in the element `id`, which is the `start`
+/// attribute of `ol`.
+/// https://html.spec.whatwg.org/multipage/grouping-content.html#concept-ol-start
+fn list_start(document: &Document, id: NodeId) -> i64 {
+    document
+        .node(id)
+        .element()
+        .filter(|e| e.tag_name() == "ol")
+        .and_then(|e| e.get_attribute("start"))
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(1)
+}
+
+/// Returns the `value` attribute of the `li` element `id`, which sets its ordinal value.
+/// https://html.spec.whatwg.org/multipage/grouping-content.html#attr-li-value
+fn list_item_value(document: &Document, id: NodeId) -> Option<i64> {
+    document
+        .node(id)
+        .element()
+        .filter(|e| e.tag_name() == "li")
+        .and_then(|e| e.get_attribute("value"))
+        .and_then(|v| v.trim().parse().ok())
+}
+
+/// Returns the text of the marker of a list item, followed by a space which separates it from
+/// the contents. Returns None for `list-style-type: none`.
+/// https://www.w3.org/TR/css-counter-styles-3/#predefined-counters
+fn marker_text(list_style_type: ListStyleType, ordinal: i64) -> Option<String> {
+    let text = match list_style_type {
+        ListStyleType::None => return None,
+        ListStyleType::Disc => String::from("\u{2022} "),
+        ListStyleType::Circle => String::from("\u{25e6} "),
+        ListStyleType::Square => String::from("\u{25aa} "),
+        ListStyleType::Decimal => format!("{}. ", ordinal),
+        ListStyleType::LowerAlpha => format!("{}. ", alphabetic(ordinal, b'a')),
+        ListStyleType::UpperAlpha => format!("{}. ", alphabetic(ordinal, b'A')),
+    };
+    Some(text)
+}
+
+/// Returns `ordinal` in the alphabetic system: a, b, ..., z, aa, ab, .... It falls back to
+/// decimal for numbers less than 1.
+/// https://www.w3.org/TR/css-counter-styles-3/#alphabetic-system
+fn alphabetic(ordinal: i64, first: u8) -> String {
+    if ordinal < 1 {
+        return format!("{}", ordinal);
+    }
+    let mut n = ordinal;
+    let mut letters = Vec::new();
+    while n > 0 {
+        n -= 1;
+        letters.push((first + (n % 26) as u8) as char);
+        n /= 26;
+    }
+    letters.iter().rev().collect()
+}
+
+/// Makes the children of a block box either all block-level or all inline-level. If there are
+/// both, each sequence of inline-level children is wrapped in an anonymous block box. A sequence
+/// which has only collapsible white space is removed because it would render nothing.
+/// https://www.w3.org/TR/CSS2/visuren.html#anonymous-block-level
+fn fix_block_container(block: &mut LayoutBox) {
+    if block.children.iter().all(|c| c.is_inline_level()) {
+        return;
+    }
+
+    let children = core::mem::take(&mut block.children);
+    let mut inlines: Vec<LayoutBox> = Vec::new();
+    for child in children {
+        if child.is_inline_level() {
+            inlines.push(child);
+            continue;
+        }
+        wrap_inlines(block, &mut inlines);
+        block.children.push(child);
+    }
+    wrap_inlines(block, &mut inlines);
+}
+
+/// Moves `inlines` into a new anonymous block box at the end of the children of `block`.
+fn wrap_inlines(block: &mut LayoutBox, inlines: &mut Vec<LayoutBox>) {
+    if inlines.iter().all(|c| c.is_collapsible_space()) {
+        inlines.clear();
+        return;
+    }
+    let mut anonymous = LayoutBox::new(
+        BoxKind::AnonymousBlock,
+        ComputedStyle::inherit_from(&block.style),
+        None,
+    );
+    anonymous.children = core::mem::take(inlines);
+    block.children.push(anonymous);
+}
+
+/// Collapses each sequence of white space into a single space.
+/// https://www.w3.org/TR/css-text-3/#white-space-phase-1
+fn collapse_white_space(text: &str) -> String {
+    let mut result = String::new();
+    let mut last_was_space = false;
+    for c in text.chars() {
+        if c == ' ' || c == '\t' || c == '\n' || c == '\r' || c == '\x0c' {
+            if !last_was_space {
+                result.push(' ');
+            }
+            last_was_space = true;
+        } else {
+            result.push(c);
+            last_was_space = false;
+        }
+    }
+    result
+}
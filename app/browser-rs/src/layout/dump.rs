@@ -0,0 +1,181 @@
+//! A text format of laid out boxes for debugging and tests. Each box is printed in a line such as
+//! `Block <p> at (8,16) size 584x16 margin=(16 0 16 0)`, indented by its depth. The position and
+//! the size are of the border box, followed by the used values which differ from the initial
+//! ones. Line boxes and text fragments in them are printed under their block container.
+
+use crate::layout::box_tree::*;
+use crate::layout::geometry::*;
+use crate::layout::inline::*;
+use crate::parser::dom::*;
+use crate::style::computed::*;
+use crate::style::values::*;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Returns the dump of `root` and its descendants, one box per line.
+#[allow(dead_code)]
+pub fn dump_layout(document: &Document, root: &LayoutBox) -> String {
+    let mut out = String::new();
+    dump_box(document, root, 0, &mut out);
+    out
+}
+
+fn dump_box(document: &Document, layout_box: &LayoutBox, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    let tag = match layout_box.node().and_then(|n| document.node(n).element()) {
+        Some(e) => format!(" <{}>", e.tag_name()),
+        None => String::new(),
+    };
+    let name = match layout_box.kind() {
+        BoxKind::Block => "Block",
+        BoxKind::AnonymousBlock => "AnonymousBlock",
+        BoxKind::Table => "Table",
+        BoxKind::TableRowGroup => "TableRowGroup",
+        BoxKind::TableRow => "TableRow",
+        BoxKind::TableCell => "TableCell",
+        BoxKind::Replaced { .. } => "Replaced",
+        // Inline-level boxes are laid out as fragments in the line boxes of their container.
+        // Absolutely positioned boxes in them are printed at their depth.
+        BoxKind::Inline | BoxKind::Text(_) => {
+            for child in layout_box.children().iter().chain(layout_box.out_of_flow()) {
+                dump_box(document, child, depth, out);
+            }
+            return;
+        }
+    };
+    let d = layout_box.dimensions();
+    out.push_str(&format!(
+        "{}{}{} {}{}\n",
+        indent,
+        name,
+        tag,
+        rect(d.border_box()),
+        box_highlights(layout_box.style(), d)
+    ));
+
+    if let Some(marker) = layout_box.marker() {
+        out.push_str(&format!(
+            "{}  Marker {:?} {}\n",
+            indent,
+            marker.text(),
+            rect(marker.rect())
+        ));
+    }
+    for line in layout_box.lines() {
+        dump_line(line, depth + 1, out);
+    }
+    for child in layout_box.children().iter().chain(layout_box.out_of_flow()) {
+        dump_box(document, child, depth + 1, out);
+    }
+}
+
+fn dump_line(line: &LineBox, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    out.push_str(&format!("{}Line {}\n", indent, rect(line.rect())));
+    // Replaced boxes in the line are printed as boxes.
+    for fragment in line.fragments().iter().filter(|f| !f.text().is_empty()) {
+        out.push_str(&format!(
+            "{}  Text {:?} {}{}\n",
+            indent,
+            fragment.text(),
+            rect(fragment.rect()),
+            text_highlights(fragment.style())
+        ));
+    }
+}
+
+fn rect(r: Rect) -> String {
+    format!(
+        "at ({},{}) size {}x{}",
+        px(r.x),
+        px(r.y),
+        px(r.width),
+        px(r.height)
+    )
+}
+
+/// Returns `v` rounded to 2 decimal places without trailing zeros, so that errors of floating
+/// point arithmetic don't appear in the dump.
+fn px(v: f64) -> String {
+    let hundredths = if v < 0.0 {
+        (v * 100.0 - 0.5) as i64
+    } else {
+        (v * 100.0 + 0.5) as i64
+    };
+    let sign = if hundredths < 0 { "-" } else { "" };
+    let (int, frac) = (hundredths.abs() / 100, hundredths.abs() % 100);
+    if frac == 0 {
+        format!("{}{}", sign, int)
+    } else if frac % 10 == 0 {
+        format!("{}{}.{}", sign, int, frac / 10)
+    } else {
+        format!("{}{}.{:02}", sign, int, frac)
+    }
+}
+
+/// Returns the edges as `(top right bottom left)`, or None if all of them are 0.
+fn edges(e: Edges<f64>) -> Option<String> {
+    if e.top == 0.0 && e.right == 0.0 && e.bottom == 0.0 && e.left == 0.0 {
+        return None;
+    }
+    Some(format!(
+        "({} {} {} {})",
+        px(e.top),
+        px(e.right),
+        px(e.bottom),
+        px(e.left)
+    ))
+}
+
+/// Returns a color as `#rrggbb`, or `#rrggbbaa` if it's not opaque.
+fn hex(c: Color) -> String {
+    if c.a() == 255 {
+        format!("#{:02x}{:02x}{:02x}", c.r(), c.g(), c.b())
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", c.r(), c.g(), c.b(), c.a())
+    }
+}
+
+fn box_highlights(style: &ComputedStyle, d: &Dimensions) -> String {
+    let mut items = Vec::new();
+    if let Some(e) = edges(d.margin) {
+        items.push(format!("margin={}", e));
+    }
+    if let Some(e) = edges(d.border) {
+        items.push(format!("border={}", e));
+    }
+    if let Some(e) = edges(d.padding) {
+        items.push(format!("padding={}", e));
+    }
+    match style.position() {
+        Position::Static => {}
+        Position::Relative => items.push(String::from("relative")),
+        Position::Absolute => items.push(String::from("absolute")),
+    }
+    if style.background_color() != Color::TRANSPARENT {
+        items.push(format!("background={}", hex(style.background_color())));
+    }
+    join(items)
+}
+
+fn text_highlights(style: &ComputedStyle) -> String {
+    let mut items = Vec::new();
+    if style.font_size() != DEFAULT_FONT_SIZE {
+        items.push(format!("font-size={}", px(style.font_size())));
+    }
+    if style.font_weight() != FONT_WEIGHT_NORMAL {
+        items.push(format!("font-weight={}", style.font_weight()));
+    }
+    if style.color() != Color::BLACK {
+        items.push(format!("color={}", hex(style.color())));
+    }
+    if style.text_decoration() == TextDecoration::Underline {
+        items.push(String::from("underline"));
+    }
+    join(items)
+}
+
+fn join(items: Vec<String>) -> String {
+    items.iter().map(|i| format!(" {}", i)).collect()
+}
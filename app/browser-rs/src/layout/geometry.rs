@@ -0,0 +1,117 @@
+//! Rectangles and the box model.
+//! https://www.w3.org/TR/CSS2/box.html#box-dimensions
+
+use crate::style::computed::Edges;
+
+/// A rectangle in px. The origin is the top-left corner of the document.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[allow(dead_code)]
+impl Rect {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+
+    pub fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    /// Returns true if the rectangles overlap. Rectangles which only touch don't overlap.
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.x < other.right()
+            && other.x < self.right()
+            && self.y < other.bottom()
+            && other.y < self.bottom()
+    }
+
+    /// Returns true if the point is in the rectangle. The right and the bottom edges are outside.
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        self.x <= x && x < self.right() && self.y <= y && y < self.bottom()
+    }
+
+    /// Returns the overlapping part of the rectangles, or None if they don't overlap.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        if !self.intersects(other) {
+            return None;
+        }
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        Some(Self::new(
+            x,
+            y,
+            self.right().min(other.right()) - x,
+            self.bottom().min(other.bottom()) - y,
+        ))
+    }
+
+    /// Returns the smallest rectangle which contains both rectangles.
+    pub fn union(&self, other: &Rect) -> Rect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Self::new(
+            x,
+            y,
+            self.right().max(other.right()) - x,
+            self.bottom().max(other.bottom()) - y,
+        )
+    }
+
+    /// Returns the rectangle moved by `dx` and `dy`.
+    pub fn translated(&self, dx: f64, dy: f64) -> Self {
+        Self::new(self.x + dx, self.y + dy, self.width, self.height)
+    }
+
+    /// Returns the rectangle grown by `edges` on each side.
+    pub fn expanded_by(&self, edges: Edges<f64>) -> Self {
+        Self {
+            x: self.x - edges.left,
+            y: self.y - edges.top,
+            width: self.width + edges.left + edges.right,
+            height: self.height + edges.top + edges.bottom,
+        }
+    }
+}
+
+/// The used sizes of the areas of a box.
+/// https://www.w3.org/TR/CSS2/box.html#box-dimensions
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Dimensions {
+    /// The content area in absolute coordinates.
+    pub content: Rect,
+    pub padding: Edges<f64>,
+    pub border: Edges<f64>,
+    pub margin: Edges<f64>,
+}
+
+#[allow(dead_code)]
+impl Dimensions {
+    /// https://www.w3.org/TR/CSS2/box.html#padding-edge
+    pub fn padding_box(&self) -> Rect {
+        self.content.expanded_by(self.padding)
+    }
+
+    /// https://www.w3.org/TR/CSS2/box.html#border-edge
+    pub fn border_box(&self) -> Rect {
+        self.padding_box().expanded_by(self.border)
+    }
+
+    /// https://www.w3.org/TR/CSS2/box.html#margin-edge
+    pub fn margin_box(&self) -> Rect {
+        self.border_box().expanded_by(self.margin)
+    }
+}
@@ -0,0 +1,280 @@
+//! Inline formatting contexts.
+//! https://www.w3.org/TR/CSS2/visuren.html#inline-formatting
+
+use crate::font::Font;
+use crate::layout::box_tree::*;
+use crate::layout::geometry::*;
+use crate::layout::replaced::*;
+use crate::parser::dom::*;
+use crate::style::computed::*;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A part of a text run placed in a line box.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fragment {
+    text: String,
+    /// The style of the text run.
+    style: ComputedStyle,
+    /// The text node of the text run.
+    node: Option<NodeId>,
+    rect: Rect,
+}
+
+#[allow(dead_code)]
+impl Fragment {
+    /// Creates a fragment which is not placed yet.
+    pub fn new(text: String, style: ComputedStyle, node: Option<NodeId>) -> Self {
+        Self {
+            text,
+            style,
+            node,
+            rect: Rect::default(),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn style(&self) -> &ComputedStyle {
+        &self.style
+    }
+
+    pub fn node(&self) -> Option<NodeId> {
+        self.node
+    }
+
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    pub fn translate(&mut self, dx: f64, dy: f64) {
+        self.rect = self.rect.translated(dx, dy);
+    }
+}
+
+/// "The rectangular area that contains the boxes that form a line is called a line box."
+/// https://www.w3.org/TR/CSS2/visuren.html#line-box
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineBox {
+    rect: Rect,
+    fragments: Vec<Fragment>,
+}
+
+#[allow(dead_code)]
+impl LineBox {
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    pub fn fragments(&self) -> &Vec<Fragment> {
+        &self.fragments
+    }
+
+    /// Moves the line box and its fragments by `dx` and `dy`.
+    pub fn translate(&mut self, dx: f64, dy: f64) {
+        self.rect = self.rect.translated(dx, dy);
+        for fragment in &mut self.fragments {
+            fragment.translate(dx, dy);
+        }
+    }
+}
+
+/// Returns the width of `text` rendered in `style`.
+fn text_width(text: &str, style: &ComputedStyle) -> f64 {
+    Font::from_style(style).text_width(text)
+}
+
+/// Returns the height of a line of text rendered in `style`.
+fn line_height(style: &ComputedStyle) -> f64 {
+    Font::from_style(style).glyph_height()
+}
+
+/// Places the marker of a list item outside of the item, in the left of its border box.
+/// `first_line` is the first line box in the item, with whose bottom the marker is aligned.
+/// https://www.w3.org/TR/css-lists-3/#list-style-position-property
+pub fn place_marker(marker: &mut Fragment, border_box: Rect, first_line: Option<Rect>) {
+    let width = text_width(&marker.text, &marker.style);
+    let height = line_height(&marker.style);
+    let y = match first_line {
+        Some(line) => line.bottom() - height,
+        None => border_box.y,
+    };
+    marker.rect = Rect::new(border_box.x - width, y, width, height);
+}
+
+/// Lays out inline-level `children` of a block container in lines from the top of `content`.
+/// Lines are broken at spaces so that they fit in the width of `content`. A word longer than a
+/// line overflows it. A replaced box is placed as a fragment without text, and its margins,
+/// borders and paddings are ignored.
+/// https://www.w3.org/TR/CSS2/visuren.html#inline-formatting
+pub fn layout_inline(children: &[LayoutBox], content: Rect) -> Vec<LineBox> {
+    let mut runs = Vec::new();
+    collect_text_runs(children, &mut runs);
+
+    // Percentages can't be resolved while measuring the widths of the contents.
+    let percentage_basis = if content.width.is_finite() {
+        content.width
+    } else {
+        0.0
+    };
+    let mut breaker = LineBreaker::new(content);
+    for (index, run) in runs.iter().enumerate() {
+        let text = match run.kind() {
+            BoxKind::Text(text) => text,
+            _ => {
+                if let Some((width, height)) = replaced_size(run, percentage_basis) {
+                    breaker.push(index, run, "", width, height);
+                }
+                continue;
+            }
+        };
+        for (i, word) in text.split(' ').enumerate() {
+            if i > 0 {
+                breaker.pending_space = Some(text_width(" ", run.style()));
+            }
+            if !word.is_empty() {
+                breaker.push_word(index, run, word);
+            }
+        }
+    }
+    breaker.finish_line();
+    breaker.lines
+}
+
+/// Sets the content boxes of the replaced boxes in `children` and their descendants to the rects
+/// of their fragments in `lines`.
+pub fn place_replaced(children: &mut [LayoutBox], lines: &[LineBox]) {
+    for child in children {
+        if child.is_replaced() {
+            let fragment = lines
+                .iter()
+                .flat_map(|l| l.fragments.iter())
+                .find(|f| f.node == child.node() && f.text.is_empty());
+            if let Some(fragment) = fragment {
+                child.dimensions_mut().content = fragment.rect;
+            }
+        } else if child.is_inline_level() {
+            place_replaced(child.children_mut(), lines);
+        }
+    }
+}
+
+/// Collects text runs and replaced boxes in `boxes` and their descendants in tree order. Inline
+/// boxes themselves don't take space in lines.
+fn collect_text_runs<'a>(boxes: &'a [LayoutBox], runs: &mut Vec<&'a LayoutBox>) {
+    for b in boxes {
+        match b.kind() {
+            BoxKind::Text(_) | BoxKind::Replaced { .. } => runs.push(b),
+            _ => collect_text_runs(b.children(), runs),
+        }
+    }
+}
+
+/// Places words in lines one by one.
+struct LineBreaker {
+    content: Rect,
+    lines: Vec<LineBox>,
+    /// The top of the current line.
+    y: f64,
+    /// The fragments in the current line with the index of their text runs.
+    fragments: Vec<(usize, Fragment)>,
+    /// The width of the fragments in the current line, including the spaces between them.
+    line_width: f64,
+    /// The width of the space before the next word, which is measured in the style of the text
+    /// run containing it. A space at the start or the end of a line is removed.
+    /// https://www.w3.org/TR/css-text-3/#white-space-phase-2
+    pending_space: Option<f64>,
+}
+
+impl LineBreaker {
+    fn new(content: Rect) -> Self {
+        Self {
+            content,
+            lines: Vec::new(),
+            y: content.y,
+            fragments: Vec::new(),
+            line_width: 0.0,
+            pending_space: None,
+        }
+    }
+
+    /// Places `word` of the text run `run` at the end of the current line, or at the start of a
+    /// new line if it doesn't fit.
+    fn push_word(&mut self, index: usize, run: &LayoutBox, word: &str) {
+        let style = run.style();
+        self.push(
+            index,
+            run,
+            word,
+            text_width(word, style),
+            line_height(style),
+        );
+    }
+
+    /// Places a fragment of `run` which is `width` x `height`. `text` is empty for a replaced
+    /// box.
+    fn push(&mut self, index: usize, run: &LayoutBox, text: &str, width: f64, height: f64) {
+        let style = run.style();
+        let mut space = match self.pending_space.take() {
+            Some(space) if !self.fragments.is_empty() => space,
+            _ => 0.0,
+        };
+
+        if !self.fragments.is_empty() && self.line_width + space + width > self.content.width {
+            self.finish_line();
+            space = 0.0;
+        }
+
+        let x = self.content.x + self.line_width;
+        self.line_width += space + width;
+        if let Some((last, fragment)) = self.fragments.last_mut() {
+            // Words from the same text run in a line form one fragment.
+            if *last == index && !text.is_empty() {
+                if space > 0.0 {
+                    fragment.text.push(' ');
+                }
+                fragment.text.push_str(text);
+                fragment.rect.width += space + width;
+                return;
+            }
+        }
+        self.fragments.push((
+            index,
+            Fragment {
+                text: String::from(text),
+                style: style.clone(),
+                node: run.node(),
+                rect: Rect::new(x + space, self.y, width, height),
+            },
+        ));
+    }
+
+    /// Closes the current line. The line is as tall as its tallest fragment, and the bottoms of
+    /// the fragments are aligned, which approximates baseline alignment for the bitmap font.
+    /// https://www.w3.org/TR/CSS2/visudet.html#line-height
+    fn finish_line(&mut self) {
+        if self.fragments.is_empty() {
+            return;
+        }
+        let height = self
+            .fragments
+            .iter()
+            .map(|(_, f)| f.rect.height)
+            .fold(0.0, f64::max);
+        let fragments: Vec<Fragment> = core::mem::take(&mut self.fragments)
+            .into_iter()
+            .map(|(_, mut f)| {
+                f.rect.y = self.y + height - f.rect.height;
+                f
+            })
+            .collect();
+        self.lines.push(LineBox {
+            rect: Rect::new(self.content.x, self.y, self.content.width, height),
+            fragments,
+        });
+        self.y += height;
+        self.line_width = 0.0;
+    }
+}
@@ -0,0 +1,299 @@
+//! Relative and absolute positioning, which are applied after the normal flow is laid out.
+//! https://www.w3.org/TR/CSS2/visuren.html#positioning-scheme
+
+use crate::layout::block::*;
+use crate::layout::box_tree::*;
+use crate::layout::geometry::*;
+use crate::layout::replaced::*;
+use crate::layout::table::*;
+use crate::style::computed::*;
+use crate::style::values::*;
+
+/// Offsets the relatively positioned boxes and lays out the absolutely positioned boxes in `root`,
+/// which is laid out in normal flow in `initial_containing_block`.
+pub fn layout_positioned(root: &mut LayoutBox, initial_containing_block: Rect) {
+    if root.style().position() == Position::Relative {
+        offset_relative(
+            root,
+            initial_containing_block,
+            Some(initial_containing_block.height),
+        );
+    }
+    let content = root.dimensions().content;
+    position_descendants(root, initial_containing_block, (content.x, content.y));
+}
+
+/// Positions the descendants of `layout_box`. `containing_block` is the padding box of the
+/// nearest positioned ancestor, or the initial containing block if there is none.
+/// `static_position` is where the absolutely positioned children of an inline box would start.
+fn position_descendants(
+    layout_box: &mut LayoutBox,
+    containing_block: Rect,
+    static_position: (f64, f64),
+) {
+    // "If the element has 'position: relative' or 'position: absolute', the containing block is
+    // formed by the [...] padding edge of the ancestor." Inline boxes have no geometry of their
+    // own, so positioned ones don't form containing blocks here.
+    // https://www.w3.org/TR/CSS2/visudet.html#containing-block-details
+    let inline = layout_box.is_inline_level();
+    let containing_block = if layout_box.style().position() != Position::Static && !inline {
+        layout_box.dimensions().padding_box()
+    } else {
+        containing_block
+    };
+    let content = layout_box.dimensions().content;
+    // The static position is approximated by the top left corner of the content box of the
+    // parent instead of where the box would be in normal flow.
+    let static_position = if inline {
+        static_position
+    } else {
+        (content.x, content.y)
+    };
+    // "If the height of the containing block is not specified explicitly [...], the percentage
+    // value is treated as 'auto'."
+    let height_basis = if layout_box.style().height().is_auto() {
+        None
+    } else {
+        Some(content.height)
+    };
+
+    for child in layout_box.children_mut() {
+        if child.style().position() == Position::Relative && !child.is_inline_level() {
+            offset_relative(child, content, height_basis);
+        }
+        position_descendants(child, containing_block, static_position);
+    }
+    for child in layout_box.out_of_flow_mut() {
+        layout_absolute(child, containing_block, static_position);
+        position_descendants(child, containing_block, static_position);
+    }
+}
+
+/// Moves a relatively positioned box from its position in normal flow.
+/// https://www.w3.org/TR/CSS2/visuren.html#relative-positioning
+fn offset_relative(layout_box: &mut LayoutBox, containing_block: Rect, height_basis: Option<f64>) {
+    let inset = layout_box.style().inset();
+    // "If both 'left' and 'right' are 'auto' (their initial values), the used values are '0'. If
+    // one of them is 'auto', it becomes the negative of the other. If neither is 'auto', 'right'
+    // is ignored". The same goes for 'top' and 'bottom'.
+    let offset = |start: Option<f64>, end: Option<f64>| match (start, end) {
+        (Some(start), _) => start,
+        (None, Some(end)) => -end,
+        (None, None) => 0.0,
+    };
+    let dx = offset(
+        resolve(inset.left, Some(containing_block.width)),
+        resolve(inset.right, Some(containing_block.width)),
+    );
+    let dy = offset(
+        resolve(inset.top, height_basis),
+        resolve(inset.bottom, height_basis),
+    );
+    layout_box.translate(dx, dy);
+}
+
+/// Lays out an absolutely positioned box in `containing_block`. `static_position` is used for
+/// the sides whose insets are `auto`.
+/// https://www.w3.org/TR/CSS2/visuren.html#absolute-positioning
+fn layout_absolute(
+    layout_box: &mut LayoutBox,
+    containing_block: Rect,
+    static_position: (f64, f64),
+) {
+    let left = compute_absolute_width(layout_box, containing_block, static_position.0);
+
+    // Lay out the contents to know the height, and then move the box to its position. The edges
+    // in a table are already computed with its columns.
+    if *layout_box.kind() != BoxKind::Table {
+        compute_vertical_edges(layout_box, containing_block.width);
+        compute_children_edges(layout_box);
+    }
+    let block = Rect::new(
+        containing_block.x + left,
+        containing_block.y,
+        containing_block.width,
+        containing_block.height,
+    );
+    let top = containing_block.y + layout_box.dimensions().margin.top;
+    place_block(layout_box, block, top, Some(containing_block.height), true);
+
+    let y = compute_absolute_height(layout_box, containing_block, static_position.1);
+    let dy = y - layout_box.dimensions().border_box().y;
+    layout_box.translate(0.0, dy);
+}
+
+/// Computes the horizontal sizes of an absolutely positioned box and returns the distance between
+/// the left edge of the containing block and the left margin edge of the box.
+/// https://www.w3.org/TR/CSS2/visudet.html#abs-non-replaced-width
+fn compute_absolute_width(
+    layout_box: &mut LayoutBox,
+    containing_block: Rect,
+    static_x: f64,
+) -> f64 {
+    let cw = containing_block.width;
+    let inset = layout_box.style().inset();
+    let left = resolve(inset.left, Some(cw));
+    let right = resolve(inset.right, Some(cw));
+    let table_width = if *layout_box.kind() == BoxKind::Table {
+        // The width of a table is decided by its columns.
+        compute_table_edges(layout_box, cw - left.unwrap_or(0.0) - right.unwrap_or(0.0));
+        Some(layout_box.dimensions().content.width)
+    } else {
+        None
+    };
+
+    let replaced_width = replaced_size(layout_box, cw).map(|(width, _)| width);
+
+    let style = layout_box.style();
+    let margin = style.margin();
+    let padding = style.padding().to_px(cw);
+    let border = style.border_width();
+    let edges = border.left + padding.left + padding.right + border.right;
+
+    let margin_left = resolve(margin.left, Some(cw));
+    let margin_right = resolve(margin.right, Some(cw));
+    let static_left = static_x - containing_block.x;
+
+    let width = table_width
+        .or(replaced_width)
+        .or_else(|| resolve(style.width(), Some(cw)));
+
+    // "calculate the preferred width by formatting the content without breaking lines other than
+    // where explicit line breaks occur, and also calculate the preferred minimum width [...].
+    // Then the shrink-to-fit width is: min(max(preferred minimum width, available width),
+    // preferred width)."
+    let shrink_to_fit = |layout_box: &LayoutBox, available: f64| {
+        let (min, max) = content_widths(layout_box);
+        let edges = horizontal_edges(layout_box);
+        (min - edges).max(available).min(max - edges)
+    };
+
+    let (left, width, margin_left, margin_right) = match (left, width, right) {
+        // "If none of the three is 'auto': If both 'margin-left' and 'margin-right' are 'auto',
+        // solve the equation under the extra constraint that the two margins get equal values,
+        // unless this would make them negative, in which case [...] set 'margin-left' to zero
+        // and solve for 'margin-right'. If one of 'margin-left' or 'margin-right' is 'auto',
+        // solve the equation for that value. If the values are over-constrained, ignore the
+        // value for [...] 'right'".
+        (Some(left), Some(width), Some(right)) => {
+            let rest = cw - left - right - width - edges;
+            match (margin_left, margin_right) {
+                (None, None) if rest >= 0.0 => (left, width, rest / 2.0, rest / 2.0),
+                (None, None) => (left, width, 0.0, rest),
+                (None, Some(mr)) => (left, width, rest - mr, mr),
+                (Some(ml), _) => (left, width, ml, rest - ml),
+            }
+        }
+        // "Otherwise, set 'auto' values for 'margin-left' and 'margin-right' to 0, and pick the
+        // one of the following six rules that applies."
+        (left, width, right) => {
+            let ml = margin_left.unwrap_or(0.0);
+            let mr = margin_right.unwrap_or(0.0);
+            let space = cw - ml - mr - edges;
+            let (left, width) = match (left, width, right) {
+                // "If all three of 'left', 'width', and 'right' are 'auto': [...] set 'left' to
+                // the static position and apply rule number three below."
+                (None, None, None) | (Some(_), None, None) => {
+                    let left = left.unwrap_or(static_left);
+                    (left, shrink_to_fit(layout_box, space - left))
+                }
+                // "'left' and 'width' are 'auto' and 'right' is not 'auto', then the width is
+                // shrink-to-fit. Then solve for 'left'"
+                (None, None, Some(right)) => {
+                    let width = shrink_to_fit(layout_box, space - right);
+                    (space - right - width, width)
+                }
+                // "'left' and 'right' are 'auto' and 'width' is not 'auto', then [...] set
+                // 'left' to the static position."
+                (None, Some(width), None) => (static_left, width),
+                // "'left' is 'auto', 'width' and 'right' are not 'auto', then solve for 'left'"
+                (None, Some(width), Some(right)) => (space - right - width, width),
+                // "'width' is 'auto', 'left' and 'right' are not 'auto', then solve for 'width'"
+                (Some(left), None, Some(right)) => (left, (space - left - right).max(0.0)),
+                (Some(left), Some(width), _) => (left, width),
+            };
+            (left, width, ml, mr)
+        }
+    };
+
+    let d = layout_box.dimensions_mut();
+    d.content.width = width;
+    d.margin.left = margin_left;
+    d.margin.right = margin_right;
+    d.padding.left = padding.left;
+    d.padding.right = padding.right;
+    d.border.left = border.left;
+    d.border.right = border.right;
+    left
+}
+
+/// Computes the vertical position of an absolutely positioned box whose contents are laid out,
+/// and returns the top of its border box. The height is stretched if both `top` and `bottom` are
+/// specified and `height` is `auto`.
+/// https://www.w3.org/TR/CSS2/visudet.html#abs-non-replaced-height
+fn compute_absolute_height(
+    layout_box: &mut LayoutBox,
+    containing_block: Rect,
+    static_y: f64,
+) -> f64 {
+    let ch = containing_block.height;
+    let style = layout_box.style();
+    let inset = style.inset();
+    let margin = style.margin();
+    // The height of a replaced box is decided by its image instead of the insets.
+    let auto_height = style.height().is_auto() && !layout_box.is_replaced();
+    let top = resolve(inset.top, Some(ch));
+    let bottom = resolve(inset.bottom, Some(ch));
+    let margin_top = resolve(margin.top, Some(containing_block.width));
+    let margin_bottom = resolve(margin.bottom, Some(containing_block.width));
+
+    let d = layout_box.dimensions_mut();
+    let edges = d.border.top + d.padding.top + d.padding.bottom + d.border.bottom;
+    match (top, bottom) {
+        (Some(top), Some(bottom)) => {
+            if auto_height {
+                // "'height' is 'auto', 'top' and 'bottom' are not 'auto', then 'auto' values for
+                // 'margin-top' and 'margin-bottom' are set to 0 and solve for 'height'"
+                let mt = margin_top.unwrap_or(0.0);
+                let mb = margin_bottom.unwrap_or(0.0);
+                d.content.height = (ch - top - bottom - mt - mb - edges).max(0.0);
+                d.margin.top = mt;
+                d.margin.bottom = mb;
+            } else {
+                // "If none of the three are 'auto': If both 'margin-top' and 'margin-bottom' are
+                // 'auto', solve the equation under the extra constraint that the two margins get
+                // equal values. If one of 'margin-top' or 'margin-bottom' is 'auto', solve the
+                // equation for that value. If the values are over-constrained, ignore the value
+                // for 'bottom' and solve for that value."
+                let rest = ch - top - bottom - d.content.height - edges;
+                let (mt, mb) = match (margin_top, margin_bottom) {
+                    (None, None) => (rest / 2.0, rest / 2.0),
+                    (None, Some(mb)) => (rest - mb, mb),
+                    (Some(mt), _) => (mt, rest - mt),
+                };
+                d.margin.top = mt;
+                d.margin.bottom = mb;
+            }
+            containing_block.y + top + d.margin.top
+        }
+        (Some(top), None) => containing_block.y + top + d.margin.top,
+        // "'top' and 'height' are 'auto' and 'bottom' is not 'auto', then the height is based on
+        // the content [...], set 'auto' values for 'margin-top' and 'margin-bottom' to 0, and
+        // solve for 'top'"
+        (None, Some(bottom)) => {
+            containing_block.bottom() - bottom - d.margin.bottom - d.border_box().height
+        }
+        // "If all three of 'top', 'height', and 'bottom' are auto, set 'top' to the static
+        // position".
+        (None, None) => static_y + d.margin.top,
+    }
+}
+
+/// Returns a length in px, or None if it's `auto` or a percentage without `percentage_basis`.
+fn resolve(length: Length, percentage_basis: Option<f64>) -> Option<f64> {
+    match (length, percentage_basis) {
+        (Length::Auto, _) | (Length::Percentage(_), None) => None,
+        (l, Some(basis)) => Some(l.to_px(basis)),
+        (l, None) => Some(l.to_px(0.0)),
+    }
+}
@@ -0,0 +1,51 @@
+//! The sizes of replaced elements.
+//! https://www.w3.org/TR/CSS2/visudet.html#inline-replaced-width
+
+use crate::layout::box_tree::*;
+use crate::style::values::*;
+
+/// Returns the used width and height of the content box of a replaced box, or None if
+/// `layout_box` isn't replaced. Percentages of width refer to `containing_width`, and those of
+/// height are treated as `auto`. The same rules apply to inline and block-level boxes.
+/// https://www.w3.org/TR/CSS2/visudet.html#inline-replaced-height
+pub fn replaced_size(layout_box: &LayoutBox, containing_width: f64) -> Option<(f64, f64)> {
+    let (natural_width, natural_height) = match layout_box.kind() {
+        BoxKind::Replaced {
+            natural_width,
+            natural_height,
+        } => (*natural_width, *natural_height),
+        _ => return None,
+    };
+    let style = layout_box.style();
+    let width = match style.width() {
+        Length::Auto => None,
+        l => Some(l.to_px(containing_width)),
+    };
+    let height = match style.height() {
+        Length::Auto | Length::Percentage(_) => None,
+        l => Some(l.to_px(0.0)),
+    };
+    let ratio = if natural_width > 0.0 && natural_height > 0.0 {
+        Some(natural_width / natural_height)
+    } else {
+        None
+    };
+
+    Some(match (width, height, ratio) {
+        (Some(width), Some(height), _) => (width, height),
+        // "if 'width' has a computed value of 'auto', 'height' has some other computed value, and
+        // the element does have an intrinsic ratio then the used value of 'width' is: (used
+        // height) * (intrinsic ratio)"
+        (None, Some(height), Some(ratio)) => (height * ratio, height),
+        // "if 'height' has a computed value of 'auto', and the element has an intrinsic ratio then
+        // the used value of 'height' is: (used width) / (intrinsic ratio)"
+        (Some(width), None, Some(ratio)) => (width, width / ratio),
+        // "Otherwise, if 'width' has a computed value of 'auto', and the element has an intrinsic
+        // width, then that intrinsic width is the used value of 'width'." The same goes for
+        // 'height'.
+        (width, height, _) => (
+            width.unwrap_or(natural_width),
+            height.unwrap_or(natural_height),
+        ),
+    })
+}
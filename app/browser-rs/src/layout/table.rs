@@ -0,0 +1,269 @@
+//! Tables. Columns get widths from the contents of their cells, similar to the automatic table
+//! layout, and cells in a row are aligned at the top and stretched to the tallest one.
+//! https://www.w3.org/TR/CSS2/tables.html#auto-table-layout
+
+use crate::layout::block::*;
+use crate::layout::box_tree::*;
+use crate::layout::geometry::*;
+use crate::layout::inline::*;
+use crate::layout::replaced::*;
+use crate::style::values::*;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The space between cells and between cells and the border of a table, which is
+/// `border-spacing: 2px` in the user agent style sheet of HTML.
+/// https://html.spec.whatwg.org/multipage/rendering.html#tables-2
+const BORDER_SPACING: f64 = 2.0;
+
+/// Computes the width and the edges of a table, the widths of its columns, and the widths of the
+/// boxes in the table.
+pub fn compute_table_edges(table: &mut LayoutBox, containing_width: f64) {
+    let (min, max) = column_widths(table);
+    let spacing = spacing(min.len());
+    let min_total = min.iter().sum::<f64>() + spacing;
+    let max_total = max.iter().sum::<f64>() + spacing;
+
+    let style = table.style();
+    let edges = horizontal_edges(table);
+    let margin = style.margin();
+    let available = containing_width
+        - edges
+        - margin.left.to_px(containing_width)
+        - margin.right.to_px(containing_width);
+
+    // "The used width of a table depends on the columns and captions widths".
+    let (width, target) = match style.width() {
+        Length::Auto => {
+            let width = if max_total <= available {
+                max_total
+            } else if min_total >= available {
+                min_total
+            } else {
+                available
+            };
+            (width, width)
+        }
+        w => {
+            let specified = w.to_px(containing_width);
+            (specified.max(min_total), specified)
+        }
+    };
+
+    let columns = distribute(&min, &max, target - spacing);
+    compute_width(table, containing_width, Length::Px(width));
+    compute_vertical_edges(table, containing_width);
+
+    let table_width = table.dimensions().content.width;
+    for_each_row(table, &mut |row| {
+        row.dimensions_mut().content.width = table_width;
+        for (cell, width) in row.children_mut().iter_mut().zip(columns.iter()) {
+            compute_edges(cell, *width);
+        }
+    });
+    for group in table.children_mut() {
+        if *group.kind() == BoxKind::TableRowGroup {
+            group.dimensions_mut().content.width = table_width;
+        }
+    }
+}
+
+/// Places the rows of `table` from the top of `content` and returns the height of them.
+pub fn layout_table_rows(table: &mut LayoutBox, content: Rect) -> f64 {
+    let y = content.y + BORDER_SPACING;
+    let bottom = place_rows(table, content, y);
+    if bottom == y {
+        // No rows.
+        return 0.0;
+    }
+    bottom - content.y
+}
+
+/// Places the rows in `parent`, which is a table or a row group, from `y`, and returns the
+/// bottom of the last one including the spacing after it.
+fn place_rows(parent: &mut LayoutBox, content: Rect, mut y: f64) -> f64 {
+    for child in parent.children_mut() {
+        if *child.kind() == BoxKind::TableRowGroup {
+            let top = y;
+            y = place_rows(child, content, y);
+            let height = if y == top {
+                0.0
+            } else {
+                y - top - BORDER_SPACING
+            };
+            child.dimensions_mut().content = Rect::new(content.x, top, content.width, height);
+        } else {
+            y += place_row(child, content, y) + BORDER_SPACING;
+        }
+    }
+    y
+}
+
+/// Places the cells of `row` from the left of `content` and returns the height of the row.
+fn place_row(row: &mut LayoutBox, content: Rect, y: f64) -> f64 {
+    let mut x = content.x + BORDER_SPACING;
+    let mut height: f64 = 0.0;
+    for cell in row.children_mut() {
+        let width = cell.dimensions().margin_box().width;
+        let top = y + cell.dimensions().margin.top;
+        place_block(cell, Rect::new(x, y, width, 0.0), top, None, true);
+        height = height.max(cell.dimensions().margin_box().height);
+        x += width + BORDER_SPACING;
+    }
+
+    // "The height of a 'table-row' element's box is calculated once the user agent has all the
+    // cells in the row available: it is the maximum of the row's computed 'height', the computed
+    // 'height' of each cell in the row, and the minimum height (MIN) required by the cells."
+    // https://www.w3.org/TR/CSS2/tables.html#height-layout
+    for cell in row.children_mut() {
+        let extra = height - cell.dimensions().margin_box().height;
+        cell.dimensions_mut().content.height += extra;
+    }
+    row.dimensions_mut().content = Rect::new(content.x, y, content.width, height);
+    height
+}
+
+/// Calls `f` with each row in `table` including ones in row groups.
+fn for_each_row<F: FnMut(&mut LayoutBox)>(table: &mut LayoutBox, f: &mut F) {
+    for child in table.children_mut() {
+        match child.kind() {
+            BoxKind::TableRowGroup => for_each_row(child, f),
+            _ => f(child),
+        }
+    }
+}
+
+/// Returns the rows in `table` including ones in row groups.
+fn rows(table: &LayoutBox) -> Vec<&LayoutBox> {
+    let mut rows = Vec::new();
+    for child in table.children() {
+        match child.kind() {
+            BoxKind::TableRowGroup => rows.extend(child.children().iter()),
+            _ => rows.push(child),
+        }
+    }
+    rows
+}
+
+/// Returns the total width of spacing around `columns` columns.
+fn spacing(columns: usize) -> f64 {
+    if columns == 0 {
+        0.0
+    } else {
+        BORDER_SPACING * (columns + 1) as f64
+    }
+}
+
+/// Returns the minimum and the maximum widths of each column, which are the largest ones of the
+/// cells in the column.
+fn column_widths(table: &LayoutBox) -> (Vec<f64>, Vec<f64>) {
+    let mut min: Vec<f64> = Vec::new();
+    let mut max: Vec<f64> = Vec::new();
+    for row in rows(table) {
+        for (i, cell) in row.children().iter().enumerate() {
+            let (cell_min, cell_max) = cell_widths(cell);
+            if i == min.len() {
+                min.push(0.0);
+                max.push(0.0);
+            }
+            min[i] = min[i].max(cell_min);
+            max[i] = max[i].max(cell_max);
+        }
+    }
+    (min, max)
+}
+
+/// Returns the minimum and the maximum widths of the margin box of a cell. A cell with a
+/// specified width in px is as wide as it as long as the contents fit.
+fn cell_widths(cell: &LayoutBox) -> (f64, f64) {
+    let (min, max) = content_widths(cell);
+    match cell.style().width() {
+        Length::Px(w) => {
+            let w = (w + horizontal_edges(cell)).max(min);
+            (w, w)
+        }
+        _ => (min, max),
+    }
+}
+
+/// Returns the min-content and the max-content widths of the margin box of `layout_box`, which
+/// are the widths when lines are broken at every opportunity and at none.
+/// https://www.w3.org/TR/css-sizing-3/#intrinsic-sizes
+pub fn content_widths(layout_box: &LayoutBox) -> (f64, f64) {
+    let edges = horizontal_edges(layout_box);
+    if let Some((width, _)) = replaced_size(layout_box, 0.0) {
+        return (width + edges, width + edges);
+    }
+    if *layout_box.kind() == BoxKind::Table {
+        let (min, max) = column_widths(layout_box);
+        let spacing = spacing(min.len());
+        return (
+            min.iter().sum::<f64>() + spacing + edges,
+            max.iter().sum::<f64>() + spacing + edges,
+        );
+    }
+
+    let children = layout_box.children();
+    if children.iter().any(|c| c.is_inline_level()) {
+        let min = longest_line(&layout_inline(children, Rect::new(0.0, 0.0, 0.0, 0.0)));
+        let max = longest_line(&layout_inline(
+            children,
+            Rect::new(0.0, 0.0, f64::INFINITY, 0.0),
+        ));
+        return (min + edges, max + edges);
+    }
+
+    let (min, max) = children
+        .iter()
+        .map(content_widths)
+        .fold((0.0, 0.0), |(a, b), (c, d)| {
+            (f64::max(a, c), f64::max(b, d))
+        });
+    (min + edges, max + edges)
+}
+
+/// Returns the width of the longest line in `lines`.
+fn longest_line(lines: &[LineBox]) -> f64 {
+    lines
+        .iter()
+        .filter_map(|l| l.fragments().last().map(|f| f.rect().x + f.rect().width))
+        .fold(0.0, f64::max)
+}
+
+/// Returns the sum of the horizontal margins, borders and paddings of `layout_box`, where
+/// percentages and `auto` are 0 because they depend on the width being computed.
+pub fn horizontal_edges(layout_box: &LayoutBox) -> f64 {
+    let style = layout_box.style();
+    let margin = style.margin().to_px(0.0);
+    let padding = style.padding().to_px(0.0);
+    let border = style.border_width();
+    margin.left + margin.right + padding.left + padding.right + border.left + border.right
+}
+
+/// Returns the widths of columns whose total is `total`. If `total` is between the sums of `min`
+/// and `max`, each column gets its minimum width and a part of the rest in proportion to the
+/// difference between its maximum and minimum widths. If `total` is larger, the rest is
+/// distributed in proportion to the maximum widths.
+fn distribute(min: &[f64], max: &[f64], total: f64) -> Vec<f64> {
+    let min_sum: f64 = min.iter().sum();
+    let max_sum: f64 = max.iter().sum();
+    if min.is_empty() {
+        return vec![];
+    }
+    if total <= min_sum {
+        return min.to_vec();
+    }
+    if total <= max_sum {
+        let ratio = (total - min_sum) / (max_sum - min_sum);
+        return min
+            .iter()
+            .zip(max.iter())
+            .map(|(a, b)| a + (b - a) * ratio)
+            .collect();
+    }
+    if max_sum == 0.0 {
+        let each = total / max.len() as f64;
+        return max.iter().map(|_| each).collect();
+    }
+    max.iter().map(|w| w * total / max_sum).collect()
+}
@@ -0,0 +1,111 @@
+//! Text fields and textareas, which are rendered as replaced elements whose contents are their
+//! values. They're drawn like the widgets of the platform, with a border and a padding of their
+//! own, as `appearance: auto` does.
+//! https://html.spec.whatwg.org/multipage/rendering.html#form-controls
+
+use crate::font::Font;
+use crate::form::{control_value, is_password, is_text_control};
+use crate::layout::box_tree::LayoutBox;
+use crate::layout::geometry::Rect;
+use crate::parser::dom::{Document, Element, ElementKind, NodeId};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// The width of the border drawn around the text in px.
+pub const BORDER: f64 = 1.0;
+/// The space between the border and the text in px.
+const PADDING: f64 = 2.0;
+/// The number of characters which a text field shows without the size attribute.
+/// https://html.spec.whatwg.org/multipage/input.html#attr-input-size
+const DEFAULT_SIZE: usize = 20;
+/// https://html.spec.whatwg.org/multipage/form-elements.html#attr-textarea-cols
+const DEFAULT_COLS: usize = 20;
+/// https://html.spec.whatwg.org/multipage/form-elements.html#attr-textarea-rows
+const DEFAULT_ROWS: usize = 2;
+
+/// Returns the value of the attribute `name` if it's a positive integer, or `default` otherwise.
+fn positive_attribute(element: &Element, name: &str, default: usize) -> usize {
+    element
+        .get_attribute(name)
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(default)
+}
+
+/// Returns the numbers of the columns and the rows of characters which a text control shows.
+fn columns_and_rows(element: &Element) -> (usize, usize) {
+    match element.kind() {
+        ElementKind::Textarea => (
+            positive_attribute(element, "cols", DEFAULT_COLS),
+            positive_attribute(element, "rows", DEFAULT_ROWS),
+        ),
+        _ => (positive_attribute(element, "size", DEFAULT_SIZE), 1),
+    }
+}
+
+/// Returns the natural width and height of a text control, including its border and padding,
+/// or None if `element` isn't a text control.
+pub fn natural_size(element: &Element, font: &Font) -> Option<(f64, f64)> {
+    if !is_text_control(element) {
+        return None;
+    }
+    let (columns, rows) = columns_and_rows(element);
+    let chrome = (BORDER + PADDING) * 2.0;
+    Some((
+        font.glyph_width() * columns as f64 + chrome,
+        font.glyph_height() * rows as f64 + chrome,
+    ))
+}
+
+/// Returns the area inside the border and the padding of a text control drawn in `rect`.
+pub fn text_area(rect: Rect) -> Rect {
+    let inset = BORDER + PADDING;
+    Rect::new(
+        rect.x + inset,
+        rect.y + inset,
+        (rect.width - inset * 2.0).max(0.0),
+        (rect.height - inset * 2.0).max(0.0),
+    )
+}
+
+/// Returns the value of the text control `id` as it's shown, where a password is hidden behind
+/// asterisks.
+pub fn shown_value(document: &Document, id: NodeId) -> String {
+    let value = control_value(document, id);
+    if document.node(id).element().is_some_and(is_password) {
+        value.chars().map(|_| '*').collect()
+    } else {
+        value
+    }
+}
+
+/// Returns the lines of the value of the text control `id` which fit in `area`. When the value
+/// overflows, its end is shown since it's where the user types.
+pub fn visible_lines(document: &Document, id: NodeId, font: &Font, area: Rect) -> Vec<String> {
+    let value = shown_value(document, id);
+    let columns = ((area.width / font.glyph_width()) as usize).max(1);
+    let rows = ((area.height / font.glyph_height()) as usize).max(1);
+    let lines: Vec<&str> = value.split('\n').collect();
+    lines[lines.len().saturating_sub(rows)..]
+        .iter()
+        .map(|line| {
+            let count = line.chars().count();
+            line.chars().skip(count.saturating_sub(columns)).collect()
+        })
+        .collect()
+}
+
+/// Returns the caret at the end of the value of the text control whose box is `layout_box`, or
+/// None if the box isn't a text control.
+pub fn caret_rect(document: &Document, layout_box: &LayoutBox, font: &Font) -> Option<Rect> {
+    let id = layout_box.node()?;
+    if !is_text_control(document.node(id).element()?) {
+        return None;
+    }
+    let area = text_area(layout_box.dimensions().content);
+    let lines = visible_lines(document, id, font, area);
+    let last = lines.last().map_or("", |l| l.as_str());
+    let x = (area.x + font.text_width(last)).min(area.right());
+    let y = area.y + font.glyph_height() * (lines.len().max(1) - 1) as f64;
+    Some(Rect::new(x, y, 1.0, font.glyph_height()))
+}
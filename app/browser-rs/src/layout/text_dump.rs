@@ -0,0 +1,241 @@
+//! A plain text rendering of laid out boxes like `w3m -dump`, for consoles without a window and
+//! for comparing pages in tests. The text is placed in character cells as wide as a glyph of the
+//! font, so the lines are broken and indented as they are in the window. Blocks separated by
+//! margins are separated by an empty line, headings are underlined, and each link is followed by
+//! a number which refers to its URL in the list at the end.
+
+use crate::font::Font;
+use crate::form::is_text_control;
+use crate::layout::box_tree::*;
+use crate::layout::text_control::shown_value;
+use crate::parser::dom::*;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A text placed in the dump, e.g. a fragment in a line box or a list marker.
+struct Piece {
+    text: String,
+    x: f64,
+    /// The top of the line where the text is. Pieces at the same top are put in the same line.
+    y: f64,
+    right: f64,
+    bottom: f64,
+    /// The pieces in the same line box are separated by a space only, while the others are
+    /// placed at their columns, e.g. the cells of a table.
+    line: usize,
+    /// The link which the text is in.
+    link: Option<NodeId>,
+    /// The level of the heading which the text is in.
+    heading: Option<usize>,
+}
+
+/// Returns the text rendering of `root` and its descendants. `resolve` returns the URL shown in
+/// the references for the href of a link, e.g. the absolute one.
+#[allow(dead_code)]
+pub fn dump_text<F: Fn(&str) -> String>(
+    document: &Document,
+    root: &LayoutBox,
+    resolve: F,
+) -> String {
+    let mut pieces = Vec::new();
+    collect_pieces(document, root, &mut pieces);
+
+    // Links are numbered in tree order, and the number follows the last text of the link.
+    let mut links: Vec<NodeId> = Vec::new();
+    let mut last_pieces = BTreeMap::new();
+    for (i, piece) in pieces.iter().enumerate() {
+        if let Some(link) = piece.link {
+            if !links.contains(&link) {
+                links.push(link);
+            }
+            last_pieces.insert(link, i);
+        }
+    }
+    for (number, link) in links.iter().enumerate() {
+        pieces[last_pieces[link]]
+            .text
+            .push_str(&format!("[{}]", number + 1));
+    }
+
+    pieces.sort_by(|a, b| {
+        a.y.partial_cmp(&b.y)
+            .unwrap_or(core::cmp::Ordering::Equal)
+            .then(a.x.partial_cmp(&b.x).unwrap_or(core::cmp::Ordering::Equal))
+    });
+    let cell = Font::new(1, false).glyph_width();
+    let left = pieces.iter().map(|p| p.x).fold(f64::INFINITY, f64::min);
+    let column = |x: f64| ((x - left) / cell + 0.5) as usize;
+
+    let mut out = String::new();
+    let mut last_bottom: Option<f64> = None;
+    let mut rest = &pieces[..];
+    while let Some(first) = rest.first() {
+        let count = rest.iter().take_while(|p| p.y - first.y < 0.5).count();
+        let (row, next) = rest.split_at(count);
+        rest = next;
+
+        // A gap as wide as half a line, e.g. a margin, separates paragraphs.
+        if let Some(bottom) = last_bottom {
+            if first.y - bottom >= cell {
+                out.push('\n');
+            }
+        }
+        let mut line = String::new();
+        let mut previous: Option<&Piece> = None;
+        for piece in row {
+            match previous {
+                Some(p) if p.line == piece.line => {
+                    if piece.x > p.right + 0.5 {
+                        line.push(' ');
+                    }
+                }
+                _ => {
+                    let width = line.chars().count();
+                    let spaces = if line.is_empty() {
+                        column(piece.x)
+                    } else {
+                        column(piece.x).max(width + 1) - width
+                    };
+                    line.push_str(&" ".repeat(spaces));
+                }
+            }
+            line.push_str(&piece.text);
+            previous = Some(piece);
+        }
+        let line = String::from(line.trim_end());
+        out.push_str(&line);
+        out.push('\n');
+
+        if let Some(level) = row.iter().filter_map(|p| p.heading).min() {
+            let indent = line.len() - line.trim_start().len();
+            let mark = if level == 1 { "=" } else { "-" };
+            out.push_str(&" ".repeat(indent));
+            out.push_str(&mark.repeat(line.chars().count() - indent));
+            out.push('\n');
+        }
+        last_bottom = Some(row.iter().map(|p| p.bottom).fold(first.bottom, f64::max));
+    }
+
+    if !links.is_empty() {
+        out.push_str("\nReferences:\n\n");
+        for (number, link) in links.iter().enumerate() {
+            let href = document
+                .node(*link)
+                .element()
+                .and_then(|e| e.get_attribute("href"))
+                .unwrap_or_default();
+            out.push_str(&format!("[{}] {}\n", number + 1, resolve(&href)));
+        }
+    }
+    out
+}
+
+fn collect_pieces(document: &Document, layout_box: &LayoutBox, pieces: &mut Vec<Piece>) {
+    // A text run in a list item has the style of the item and so a marker, but it's never placed.
+    // Only the markers of the boxes which aren't inline-level are shown, as they're painted.
+    let marker = match layout_box.marker() {
+        Some(marker) if !layout_box.is_inline_level() => Some(marker),
+        _ => None,
+    };
+    if let Some(marker) = marker {
+        let rect = marker.rect();
+        pieces.push(Piece {
+            text: ascii_marker(marker.text()),
+            x: rect.x,
+            y: rect.y,
+            right: rect.right(),
+            bottom: rect.bottom(),
+            line: pieces.len(),
+            link: None,
+            heading: None,
+        });
+    }
+    for line in layout_box.lines() {
+        let id = pieces.len();
+        for fragment in line.fragments() {
+            let rect = fragment.rect();
+            let text = if fragment.text().is_empty() {
+                replaced_text(document, fragment.node())
+            } else {
+                String::from(fragment.text())
+            };
+            pieces.push(Piece {
+                text,
+                x: rect.x,
+                y: line.rect().y,
+                right: rect.right(),
+                bottom: line.rect().bottom(),
+                line: id,
+                link: find_ancestor(document, fragment.node(), |e| {
+                    e.tag_name() == "a" && e.get_attribute("href").is_some()
+                }),
+                heading: find_ancestor(document, fragment.node(), |e| heading_level(e).is_some())
+                    .and_then(|h| heading_level(document.node(h).element()?)),
+            });
+        }
+    }
+    for child in layout_box.children().iter().chain(layout_box.out_of_flow()) {
+        collect_pieces(document, child, pieces);
+    }
+}
+
+/// Returns the text shown for a replaced element, which is the alternative text of an image or
+/// the value of a text control.
+fn replaced_text(document: &Document, node: Option<NodeId>) -> String {
+    if let Some(id) = node.filter(|&n| document.node(n).element().is_some_and(is_text_control)) {
+        return format!("[{}]", shown_value(document, id).replace('\n', " "));
+    }
+    let alt = node
+        .and_then(|n| document.node(n).element())
+        .and_then(|e| e.get_attribute("alt"))
+        .filter(|alt| !alt.is_empty());
+    match alt {
+        Some(alt) => format!("[{}]", alt),
+        None => String::from("[image]"),
+    }
+}
+
+/// Replaces the symbols of markers with ASCII characters, which any console can show.
+fn ascii_marker(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{2022}' => '*',
+            '\u{25e6}' => 'o',
+            '\u{25aa}' => '+',
+            c => c,
+        })
+        .collect::<String>()
+        .trim_end()
+        .into()
+}
+
+/// Returns the level of a heading element, e.g. 1 for h1.
+fn heading_level(element: &Element) -> Option<usize> {
+    match element.kind() {
+        ElementKind::H1 => Some(1),
+        ElementKind::H2 => Some(2),
+        ElementKind::H3 => Some(3),
+        ElementKind::H4 => Some(4),
+        ElementKind::H5 => Some(5),
+        ElementKind::H6 => Some(6),
+        _ => None,
+    }
+}
+
+/// Returns the nearest inclusive ancestor of `node` which is an element matching `matches`.
+fn find_ancestor<F: Fn(&Element) -> bool>(
+    document: &Document,
+    node: Option<NodeId>,
+    matches: F,
+) -> Option<NodeId> {
+    let mut current = node;
+    while let Some(n) = current {
+        if document.node(n).element().is_some_and(&matches) {
+            return Some(n);
+        }
+        current = document.node(n).parent();
+    }
+    None
+}
@@ -1,8 +1,28 @@
-#![no_std]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 extern crate alloc;
 
+pub mod args;
+pub mod bookmarks;
+pub mod browser;
+pub mod config;
+pub mod cookie;
+pub mod download;
+pub mod error_page;
+pub mod font;
+pub mod form;
 pub mod http;
+pub mod image;
+pub mod js;
+pub mod keyboard;
+pub mod layout;
 pub mod net;
+pub mod page;
+pub mod paint;
 pub mod parser;
+pub mod refresh;
+pub mod style;
+pub mod tab;
+pub mod timing;
+pub mod ui;
 pub mod url;
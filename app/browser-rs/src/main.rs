@@ -1,53 +1,333 @@
-#![no_std]
-#![no_main]
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(not(feature = "std"), no_main)]
 
+mod args;
+mod bookmarks;
+mod browser;
+mod config;
+mod cookie;
+mod download;
+mod error_page;
+mod font;
+mod form;
 mod http;
+mod image;
+mod js;
+mod keyboard;
+mod layout;
 mod net;
+mod page;
+mod paint;
 mod parser;
+mod refresh;
+mod style;
+mod tab;
+mod timing;
+mod ui;
 mod url;
 
 extern crate alloc;
 
+use alloc::rc::Rc;
+use alloc::string::String;
 use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use liumlib::fs::File;
+use liumlib::log::{Filter, Level, Output};
 use liumlib::*;
 
-use crate::net::udp_response;
-use crate::parser::render;
+use crate::args::{Args, DumpMode, Source};
+use crate::browser::Browser;
+use crate::config::Config;
+use crate::cookie::CookieJar;
+use crate::download::download;
+use crate::http::HttpResponse;
+use crate::layout::text_dump::dump_text;
+use crate::net::RequestOptions;
+use crate::page::Page;
+use crate::paint::raster::rasterize;
+use crate::parser::dom::Document;
+use crate::parser::{parse, parse_with_timing};
+use crate::style::computed::Viewport;
+use crate::timing::Timing;
+use crate::ui::dialog::ask_in_console;
 use crate::url::ParsedUrl;
 
-fn help_message() {
-    println!("Usage: browser-rs.bin [ OPTIONS ]");
-    println!("       -u, --url      URL. Default: http://127.0.0.1:8888/index.html");
+/// The byte which ends the standard input read by `--stdin`, Ctrl+D, since the console of liumOS
+/// never reaches the end.
+const END_OF_STDIN: u8 = 0x04;
+/// The largest HTML read by `--stdin`.
+const STDIN_LIMIT: usize = 1024 * 1024;
+
+fn help_message() -> ! {
+    println!("Usage: browser-rs.bin [ OPTIONS ] [ URL ... ]");
+    println!("       -u, --url      URL. Default: the homepage in the config file.");
+    println!("                      Several URLs are opened in tabs, or dumped one after another.");
+    println!("       --config       File to read the settings from. Default: browser.conf");
+    println!("       --width        Width of the viewport in px. Default: 600");
+    println!("       --height       Height of the viewport in px. Default: 400");
+    println!("       --color-scheme light or dark, which pages may be styled for.");
+    println!("       --dump-dom     Print the DOM tree of the page instead of rendering it.");
+    println!("       --dump-dom-json");
+    println!("                      Print the DOM tree of the page as JSON.");
+    println!("       --dump-layout  Print the laid out boxes of the page instead of rendering it.");
+    println!("       --dump         Print the page as plain text instead of showing it.");
+    println!(
+        "       --html         HTML to show instead of fetching the URL, which is the base URL."
+    );
+    println!("       --stdin        Read the HTML to show from the standard input like --html,");
+    println!("                      until its end or Ctrl+D, up to 1 MiB.");
+    println!("       --screenshot   File to write the rendered page to instead of showing it.");
+    println!("                      It's a BMP file if the name ends with .bmp, or PPM otherwise.");
+    println!(
+        "       -o, --output   File to save the body of the response to instead of parsing it."
+    );
+    println!("       --frame-time   Print the time taken to paint each frame.");
+    println!("       --timing       Print the time taken by each stage of loading a page,");
+    println!("                      and show their total in the status bar.");
+    println!("       --bookmarks    File to keep bookmarks in. Default: bookmarks.txt");
+    println!("       --no-cache     Ask caches to revalidate every response with the server.");
+    println!("       --no-refresh   Don't follow the refreshes which pages ask for.");
+    println!("       --user-agent   Value of User-Agent sent with requests.");
+    println!("       --proxy        Host and port of the proxy to send requests to.");
+    println!("       --log          Levels of the messages to log, e.g. warn or info,net=debug.");
+    println!("                      error, warn, info, debug, trace or off. Default: info");
+    println!("       --log-file     File to append the log to instead of printing it.");
+    println!("Environment variables:");
+    println!("       http_proxy     Proxy like --proxy, which overrides the config file.");
+    println!("       no_proxy       Hosts to send requests to directly, separated by commas.");
+    println!("       BROWSER_DEBUG  Log at the debug level without --log unless it's empty or 0.");
+    println!("Keys in the window:");
+    println!("       Up, Down, PageUp, PageDown, Home, End  Scroll the page.");
+    println!("       Tab, Shift+Tab                         Move the focus to a link or control.");
+    println!("       Enter                                  Follow the link or submit the form.");
+    println!("       g, Ctrl+L                              Type a URL to go to.");
+    println!("       b                                      Bookmark the page.");
+    println!("       B                                      Choose a bookmark to go to.");
+    println!("       t                                      Type a URL to open in a new tab.");
+    println!("       w                                      Close the tab.");
+    println!("       [, ], 1-9                              Go to the previous, next or n-th tab.");
+    println!("       s                                      Save the focused link or the page.");
+    println!("       r                                      Reload the page.");
+    println!("       R                                      Reload the page bypassing caches.");
+    println!("       q                                      Quit.");
+    println!("       Click                                  Focus and follow the link or control.");
+    println!("Keys in a focused text field:");
+    println!("       Characters, Backspace                  Edit the text.");
+    println!("       Enter                                  Submit the form, or a new line.");
+    println!("       Esc                                    Leave the text field.");
     exit(0);
 }
 
-entry_point!(main);
-fn main() {
-    let mut url = "http://127.0.0.1:8888/index.html";
+/// Fetches the page at `url` with the settings of `browser` and prints it as `mode`.
+fn dump_page(url: &ParsedUrl, mode: DumpMode, browser: &Browser) {
+    // The links are relative to the URL which the request is redirected to.
+    let (parsed_url, raw_response) = match browser.fetch_document(url) {
+        Ok(fetched) => fetched,
+        Err(e) => {
+            error!("failed to load {}: {}", url, e);
+            return;
+        }
+    };
+    let parsed_url = &parsed_url;
+    if mode == DumpMode::Layout {
+        println!("----- receiving a response -----");
+        println!("{}", String::from_utf8_lossy(&raw_response));
+    }
+    let response = HttpResponse::from_bytes(&raw_response);
+    // The DOM tree is printed as it's parsed, without the resources.
+    let document = match mode {
+        DumpMode::Dom | DumpMode::DomJson => parse(response.body().to_string()),
+        DumpMode::Text | DumpMode::Layout => browser.load_document(parsed_url, &response),
+    };
+    dump_document(parsed_url, document, mode, browser.viewport());
+}
 
-    let help_flag = "--help".to_string();
-    let url_flag = "--url".to_string();
+/// Prints `document` from `parsed_url` as `mode`.
+fn dump_document(parsed_url: &ParsedUrl, document: Document, mode: DumpMode, viewport: Viewport) {
+    match mode {
+        DumpMode::Dom => print!("{}", document),
+        DumpMode::DomJson => println!("{}", document.to_json(document.root())),
+        DumpMode::Text => {
+            let page = Page::new(document, viewport);
+            if let Some(root) = page.layout() {
+                print!(
+                    "{}",
+                    dump_text(page.document(), root, |href| match parsed_url.join(href) {
+                        Some(url) => url.to_string(),
+                        None => href.to_string(),
+                    })
+                );
+            }
+        }
+        DumpMode::Layout => {
+            let page = Page::new(document, viewport);
+            if let Some(root) = page.layout() {
+                print!("{}", layout::dump::dump_layout(page.document(), root));
+            }
+        }
+    }
+}
 
-    let args = env::args();
-    for i in 1..args.len() {
-        if help_flag == args[i] {
-            help_message();
+/// Prints the pages at `urls` as `mode`, or `document` given instead of fetching the first of
+/// them if it's given. The pages are fetched one by one, each with a request of its own since
+/// there's no connection to keep alive over UDP.
+fn dump_pages(urls: &[ParsedUrl], document: Option<Document>, mode: DumpMode, browser: &Browser) {
+    if let Some(document) = document {
+        dump_document(&urls[0], document, mode, browser.viewport());
+        return;
+    }
+    for url in urls {
+        if urls.len() > 1 {
+            println!("----- {} -----", url);
         }
+        dump_page(url, mode, browser);
+    }
+    browser.save_cookies();
+}
+
+/// Saves the body of the response for `urls`, which must be only one, to `path` without parsing
+/// it, so it may be anything, e.g. an image.
+fn save_response(urls: &[ParsedUrl], path: &str, browser: &Browser) {
+    if urls.len() > 1 {
+        println!("only one URL can be saved to {}", path);
+        exit(1);
+    }
+    match download(&urls[0], path, browser.options(), |size| {
+        println!("received {} bytes", size)
+    }) {
+        Ok(size) => println!("saved {} bytes to {}", size, path),
+        Err(e) => {
+            error!("{}", e);
+            exit(1);
+        }
+    }
+}
+
+/// Writes the page of the active tab of `browser` to `file`, which is a BMP file if the name
+/// ends with .bmp, or PPM otherwise.
+fn save_screenshot(browser: &Browser, file: &str) {
+    let page = &browser.active_tab().page;
+    let bitmap = rasterize(page.display_list(), page.visible_rect());
+    let data = if file.ends_with(".bmp") {
+        bitmap.to_bmp()
+    } else {
+        bitmap.to_ppm()
+    };
+    if write_file(file, &data).is_none() {
+        error!("failed to write {}", file);
+        exit(1);
+    }
+}
+
+/// Sets up the log by `--log` and `--log-file`, which is done before reading the other settings
+/// so that the problems found meanwhile are logged. `debug` logs the details of loading pages
+/// unless `--log` is given.
+fn setup_log(args: &Args, debug: bool) {
+    let filter = match &args.log {
+        Some(filter) => filter.clone(),
+        None if debug => Filter::new(Some(Level::Debug)),
+        None => Filter::default(),
+    };
+    match &args.log_file {
+        Some(path) => match File::append(path) {
+            Some(file) => log::init(filter, Output::File(file)),
+            None => {
+                log::init(filter, Output::Console);
+                error!("failed to open {}", path);
+            }
+        },
+        None => log::init(filter, Output::Console),
+    }
+}
 
-        if url_flag == args[i] {
-            if i + 1 >= args.len() {
-                help_message();
+/// Returns the HTML given instead of fetching the URL, reading the standard input if it's asked
+/// for.
+fn read_source(source: &Option<Source>) -> Option<String> {
+    match source.as_ref()? {
+        Source::Html(html) => Some(html.clone()),
+        Source::Stdin => match read_until(0, END_OF_STDIN, STDIN_LIMIT) {
+            Some(data) => Some(String::from_utf8_lossy(&data).into_owned()),
+            None => {
+                error!("failed to read the standard input");
+                exit(1);
             }
-            url = args[i + 1];
+        },
+    }
+}
+
+entry_point!(main);
+fn main() {
+    let args = match Args::parse(env::args()) {
+        Ok(args) if !args.help => args,
+        Ok(_) => help_message(),
+        Err(e) => {
+            println!("{}", e);
+            help_message()
         }
+    };
+    // The flags override the config, so it's read first.
+    let config_file = args.config_file.as_deref().unwrap_or(config::DEFAULT_FILE);
+    let (mut config, errors) = Config::load(config_file);
+    let env_errors = config.apply_env(env::var);
+    setup_log(&args, config.debug);
+    for e in errors {
+        warn!("{}: {}", config_file, e);
     }
+    for e in env_errors {
+        warn!("environment: {}", e);
+    }
+    args.apply(&mut config);
+    let source = read_source(&args.source);
 
-    let parsed_url = ParsedUrl::new(url.to_string());
+    let urls: Vec<ParsedUrl> = if args.urls.is_empty() {
+        vec![ParsedUrl::new(config.homepage.clone())]
+    } else {
+        args.urls
+            .iter()
+            .map(|url| ParsedUrl::new(url.clone()))
+            .collect()
+    };
+    // The cookies of the last run are sent again, and they're saved when the pages are left.
+    let cookies = Rc::new(CookieJar::new());
+    cookies.load(&config.cookies_file);
+    let options = RequestOptions {
+        no_cache: args.no_cache,
+        user_agent: config.user_agent.clone(),
+        proxy: config.proxy.clone(),
+        no_proxy: config.no_proxy.clone(),
+        cookies: Some(cookies),
+    };
+    let mut browser = Browser::new(config, options, args.timing);
+    // Without a window, the dialogs of scripts are asked on the console.
+    browser.set_dialog_handler(Rc::new(ask_in_console));
 
-    let response = udp_response(&parsed_url);
+    if let Some(path) = &args.output {
+        save_response(&urls, path, &browser);
+        return;
+    }
 
-    println!("----- receiving a response -----");
-    println!("{}", response);
+    // The given HTML is parsed without touching the network, even for the resources in it, so
+    // that the parser and the layout can be debugged alone.
+    let mut load_timing = Timing::new();
+    let inline_document = source.map(|html| parse_with_timing(html, &mut load_timing));
 
-    render(response);
+    if let Some(mode) = args.dump_mode {
+        dump_pages(&urls, inline_document, mode, &browser);
+        return;
+    }
+
+    // The first page is shown in the window, and the others are opened in tabs after it.
+    if let Some(file) = &args.screenshot {
+        browser.show_or_follow(urls[0].clone(), inline_document, load_timing);
+        save_screenshot(&browser, file);
+        browser.save_cookies();
+        return;
+    }
+    if let Err(e) = browser.run_window(&urls, inline_document, load_timing, args.frame_time) {
+        error!("{}", e);
+        exit(1);
+    }
+    browser.save_cookies();
 }
@@ -1,36 +1,36 @@
+use alloc::format;
+use alloc::rc::Rc;
 use alloc::string::String;
+use alloc::vec;
 use alloc::vec::Vec;
+use core::time::Duration;
 
-use crate::http::{HttpRequest, Method};
+use crate::cookie::{domain_matches, CookieJar};
+use crate::http::{HttpRequest, HttpResponse, Method};
 use crate::url::ParsedUrl;
+use liumlib::net::{SocketAddrV4, AF_INET, SOCK_DGRAM};
+use liumlib::poll::{poll, PollFd, POLLIN};
+use liumlib::rand::Rng;
+use liumlib::time::sleep;
 use liumlib::*;
 
-pub const AF_INET: u32 = 2;
-
-/// For TCP.
-pub const _SOCK_STREAM: u32 = 1;
-/// For UDP.
-pub const SOCK_DGRAM: u32 = 2;
-
-fn ip_to_int(ip: &str) -> u32 {
+/// Returns the IPv4 address `ip`, e.g. "127.0.0.1", in the network byte order, or None if it's
+/// not an address, e.g. a domain name, which can't be resolved without DNS.
+fn ip_to_int(ip: &str) -> Option<u32> {
     let ip_blocks: Vec<&str> = ip.split('.').collect();
     if ip_blocks.len() != 4 {
-        return 0;
+        return None;
     }
 
-    (ip_blocks[3].parse::<u32>().unwrap() << 24)
-        | (ip_blocks[2].parse::<u32>().unwrap() << 16)
-        | (ip_blocks[1].parse::<u32>().unwrap())
-        | (ip_blocks[0].parse::<u32>().unwrap())
+    let mut address = 0;
+    for (i, block) in ip_blocks.iter().enumerate() {
+        address |= (block.parse::<u8>().ok()? as u32) << (8 * i);
+    }
+    Some(address)
 }
 
-fn inet_addr(host: &str) -> u32 {
-    let v: Vec<&str> = host.splitn(2, ':').collect();
-    let ip = if v.len() == 2 || v.len() == 1 {
-        v[0]
-    } else {
-        panic!("invalid host name: {}", host);
-    };
+fn inet_addr(host: &str) -> Option<u32> {
+    let ip = host.split(':').next()?;
     ip_to_int(ip)
 }
 
@@ -42,37 +42,165 @@ fn htons(port: u16) -> u16 {
     }
 }
 
-pub fn udp_response(parsed_url: &ParsedUrl) -> String {
-    let http_request = HttpRequest::new(Method::Get, parsed_url);
+/// How requests are sent, which is set by the config file and the flags.
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    /// Asks caches to revalidate responses, e.g. to reload a page.
+    pub no_cache: bool,
+    /// The value of User-Agent, which isn't sent if it's None.
+    pub user_agent: Option<String>,
+    /// The proxy which requests are sent to instead of the hosts of their URLs.
+    pub proxy: Option<ParsedUrl>,
+    /// The hosts which requests are sent to directly even if there's a proxy, e.g. "example.com"
+    /// for it and its subdomains, or "*" for all.
+    pub no_proxy: Vec<String>,
+    /// The cookies sent with requests and stored from responses, which aren't used if it's None.
+    pub cookies: Option<Rc<CookieJar>>,
+}
 
-    let socket_fd = match socket(AF_INET, SOCK_DGRAM, 0) {
-        Some(fd) => fd,
-        None => panic!("can't create a socket file descriptor"),
-    };
-    let mut address = SockAddr::new(
-        AF_INET as u16,
-        htons(parsed_url.port),
-        inet_addr(&parsed_url.host),
-    );
-    let mut request = http_request.string();
-
-    println!("----- sending a request -----");
-    println!("{}", request);
-
-    if sendto(&socket_fd, &mut request, 0, &address) < 0 {
-        panic!("failed to send a request: {:?}", request);
+impl RequestOptions {
+    /// Returns the proxy which a request for `url` is sent to, which is None if it's sent to the
+    /// host directly.
+    pub fn proxy_for(&self, url: &ParsedUrl) -> Option<&ParsedUrl> {
+        let proxy = self.proxy.as_ref()?;
+        // The host of a URL includes its port.
+        let host = url.host.split(':').next().unwrap_or_default();
+        let host = host.to_ascii_lowercase();
+        let bypassed = self.no_proxy.iter().any(|entry| {
+            let domain = entry.trim_start_matches('.').to_ascii_lowercase();
+            domain == "*" || domain_matches(&host, &domain)
+        });
+        if bypassed {
+            None
+        } else {
+            Some(proxy)
+        }
     }
+}
 
-    let mut buf = [0; 1000];
-    let length = recvfrom(&socket_fd, &mut buf, 0, &mut address);
-    if length < 0 {
-        panic!("failed to receive a response");
+/// Builds a request of `method` for `parsed_url` as `options` says.
+pub fn new_request(
+    method: Method,
+    parsed_url: &ParsedUrl,
+    options: &RequestOptions,
+) -> HttpRequest {
+    let mut http_request = HttpRequest::new(method, parsed_url);
+    if let Some(user_agent) = &options.user_agent {
+        http_request.add_header(String::from("User-Agent"), user_agent.clone());
     }
+    if options.no_cache {
+        http_request.bypass_cache();
+    }
+    if options.proxy_for(parsed_url).is_some() {
+        http_request.set_absolute_target(parsed_url);
+    }
+    if let Some(cookie) = options
+        .cookies
+        .as_ref()
+        .and_then(|c| c.header_for(parsed_url))
+    {
+        http_request.add_header(String::from("Cookie"), cookie);
+    }
+    http_request
+}
 
-    close(&socket_fd);
+/// Stores the cookies which `response` for `url` sets if `options` has a cookie jar.
+pub fn store_cookies(url: &ParsedUrl, response: &HttpResponse, options: &RequestOptions) {
+    if let Some(cookies) = &options.cookies {
+        cookies.store_response(url, response);
+    }
+}
 
-    match String::from_utf8(buf.to_vec()) {
-        Ok(s) => s,
-        Err(e) => panic!("failed to convert u8 array to string: {}", e),
+/// Sends an HTTP GET request to `parsed_url` and returns the raw response, or an error message
+/// if it fails.
+pub fn get(parsed_url: &ParsedUrl, options: &RequestOptions) -> Result<String, String> {
+    let response = get_bytes(parsed_url, options)?;
+    match String::from_utf8(response) {
+        Ok(s) => Ok(s),
+        Err(e) => Err(format!("failed to convert u8 array to string: {}", e)),
     }
 }
+
+/// Sends an HTTP GET request to `parsed_url` and returns the raw response as bytes, which may
+/// contain a binary body, or an error message if it fails.
+pub fn get_bytes(parsed_url: &ParsedUrl, options: &RequestOptions) -> Result<Vec<u8>, String> {
+    let http_request = new_request(Method::Get, parsed_url, options);
+    send(&http_request, parsed_url, options)
+}
+
+/// Sends `http_request`, e.g. a POST request, to the host of `parsed_url` or the proxy in
+/// `options` over UDP, and returns the raw response as bytes, or an error message if it fails.
+pub fn send(
+    http_request: &HttpRequest,
+    parsed_url: &ParsedUrl,
+    options: &RequestOptions,
+) -> Result<Vec<u8>, String> {
+    let server = options.proxy_for(parsed_url).unwrap_or(parsed_url);
+    let ip = match inet_addr(&server.host) {
+        Some(ip) => ip,
+        None => return Err(format!("can't resolve the host {}", server.host)),
+    };
+    let request = http_request.string();
+
+    debug!("sending a request to {}:\n{}", server.host, request);
+
+    udp_send(request, ip, server.port)
+}
+
+/// The maximum size of the payload of a UDP datagram, which limits the size of a response.
+const MAX_UDP_PAYLOAD: usize = 65507;
+
+/// How many times a request over UDP is sent before it fails, since datagrams may be lost.
+const UDP_ATTEMPTS: u32 = 3;
+
+/// How long to wait for the response to a datagram before sending the request again.
+const UDP_RESPONSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The delays before sending a request again, which are random up to the base doubled for each
+/// failed attempt so that the clients which lost their datagrams together don't retry together.
+const UDP_BACKOFF_BASE: Duration = Duration::from_millis(100);
+const UDP_BACKOFF_MAX: Duration = Duration::from_secs(1);
+
+/// Sends `request` to `ip`, which is in the network byte order, and `port` in a datagram and
+/// returns the datagram of the response. The request is sent again after a random delay if no
+/// response arrives in time, which works only on Linux, since liumOS doesn't track sockets and
+/// the socket is always ready there.
+fn udp_send(mut request: String, ip: u32, port: u16) -> Result<Vec<u8>, String> {
+    let socket_fd = match socket(AF_INET, SOCK_DGRAM, 0) {
+        Some(fd) => fd,
+        None => return Err(String::from("can't create a socket file descriptor")),
+    };
+    let mut address = SockAddr::new(AF_INET as u16, htons(port), ip);
+    let mut rng = Rng::new();
+
+    for attempt in 0..UDP_ATTEMPTS {
+        if attempt > 0 {
+            sleep(rng.backoff(UDP_BACKOFF_BASE, UDP_BACKOFF_MAX, attempt));
+        }
+        if sendto(&socket_fd, &mut request, 0, &address) < 0 {
+            close(&socket_fd);
+            return Err(format!("failed to send a request: {:?}", request));
+        }
+        let mut fds = [PollFd::new(socket_fd.number(), POLLIN)];
+        let timeout = UDP_RESPONSE_TIMEOUT.as_millis() as u64;
+        if poll(&mut fds, Some(timeout)) == Some(0) {
+            continue;
+        }
+
+        // The buffer is as large as any datagram, so that the response isn't truncated.
+        let mut buf = vec![0; MAX_UDP_PAYLOAD];
+        let length = recvfrom(&socket_fd, &mut buf, 0, &mut address);
+        close(&socket_fd);
+        if length < 0 {
+            return Err(String::from("failed to receive a response"));
+        }
+        buf.truncate(length as usize);
+        return Ok(buf);
+    }
+    close(&socket_fd);
+    Err(format!(
+        "{} didn't respond to {} requests",
+        SocketAddrV4::new(ip.to_ne_bytes(), port),
+        UDP_ATTEMPTS
+    ))
+}
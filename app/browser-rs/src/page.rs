@@ -0,0 +1,493 @@
+//! A loaded document with its styles and layout for a viewport.
+
+use crate::font::Font;
+use crate::form::{is_text_control, submit, Submission};
+use crate::layout::box_tree::LayoutBox;
+use crate::layout::geometry::Rect;
+use crate::layout::layout;
+use crate::layout::text_control::caret_rect;
+use crate::paint::damage::{diff_display_lists, Damage};
+use crate::paint::display_list::{build_display_list, DisplayItem, DisplayList};
+use crate::paint::focus_ring::{focus_rects, paint_focus_ring};
+use crate::parser::dom::{Document, Element, ElementKind, NodeId};
+use crate::style::computed::{compute_styles, StyleMap, Viewport};
+use crate::timing::{Stage, Timing};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A document rendered in a viewport. The styles, the layout and the display list are kept up to
+/// date with the document and the size of the viewport, and the regions which need repainting
+/// are tracked as damage.
+#[derive(Debug)]
+pub struct Page {
+    document: Document,
+    viewport: Viewport,
+    styles: StyleMap,
+    layout: Option<LayoutBox>,
+    /// How far the document is scrolled down in px. The top of the viewport is at this position
+    /// of the document.
+    scroll_y: f64,
+    display_list: DisplayList,
+    /// The regions which changed since the damage was taken last time.
+    damage: Damage,
+    /// How far the document is scrolled since the damage was taken last time.
+    scrolled: f64,
+    /// The link or the form control which has the focus and is drawn with a focus ring.
+    focused: Option<NodeId>,
+}
+
+#[allow(dead_code)]
+impl Page {
+    /// Computes the styles of `document` and lays it out in `viewport`.
+    pub fn new(document: Document, viewport: Viewport) -> Self {
+        Self::with_timing(document, viewport, &mut Timing::new())
+    }
+
+    /// Creates a page like `new()`, and adds the time taken by the styles, the layout and the
+    /// display list to `timing`.
+    pub fn with_timing(document: Document, viewport: Viewport, timing: &mut Timing) -> Self {
+        let styles = timing.measure(Stage::Style, || compute_styles(&document, viewport));
+        let layout = timing.measure(Stage::Layout, || layout(&document, &styles, viewport));
+        let mut page = Self {
+            document,
+            viewport,
+            styles,
+            layout,
+            scroll_y: 0.0,
+            display_list: DisplayList::new(),
+            damage: Damage::new(),
+            scrolled: 0.0,
+            focused: None,
+        };
+        page.display_list = timing.measure(Stage::Paint, || page.build_display_list());
+        // Nothing is painted yet.
+        page.damage.add(page.canvas());
+        page
+    }
+
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    pub fn styles(&self) -> &StyleMap {
+        &self.styles
+    }
+
+    /// Returns the root of the laid out box tree, or None if nothing is rendered.
+    pub fn layout(&self) -> Option<&LayoutBox> {
+        self.layout.as_ref()
+    }
+
+    /// Changes the size of the viewport, e.g. when the window is resized. Styles are computed
+    /// again because media queries may match differently, and then the document is laid out
+    /// again. Returns true if the viewport changed.
+    pub fn resize(&mut self, viewport: Viewport) -> bool {
+        if self.viewport == viewport {
+            return false;
+        }
+        self.viewport = viewport;
+        self.rerender();
+        // The document may be shorter now.
+        self.scroll_to(self.scroll_y);
+        // The whole viewport is painted again because its buffer is resized.
+        self.damage.add(self.canvas());
+        true
+    }
+
+    /// Changes the document with `update`, e.g. sets an attribute, and renders it again. The
+    /// regions which look different are damaged.
+    pub fn update_document<F: FnOnce(&mut Document)>(&mut self, update: F) {
+        update(&mut self.document);
+        let old = self.rerender();
+        let damage = diff_display_lists(&old, &self.display_list);
+        self.damage.merge(&damage);
+        if self
+            .focused
+            .is_some_and(|f| !self.focusable_elements().contains(&f))
+        {
+            self.focus(None);
+        }
+        self.scroll_to(self.scroll_y);
+    }
+
+    /// Computes the styles, the layout and the display list again, and returns the old display
+    /// list.
+    fn rerender(&mut self) -> DisplayList {
+        self.styles = compute_styles(&self.document, self.viewport);
+        self.layout = layout(&self.document, &self.styles, self.viewport);
+        let list = self.build_display_list();
+        core::mem::replace(&mut self.display_list, list)
+    }
+
+    /// Returns the elements which can get the focus in tree order, i.e. the a elements with href
+    /// and the form controls which are rendered and not disabled.
+    /// https://html.spec.whatwg.org/multipage/interaction.html#sequential-focus-navigation-order
+    pub fn focusable_elements(&self) -> Vec<NodeId> {
+        let root = match &self.layout {
+            Some(root) => root,
+            None => return Vec::new(),
+        };
+        self.document
+            .descendants(self.document.root())
+            .filter(|&id| {
+                self.document.node(id).element().is_some_and(is_focusable)
+                    && !focus_rects(&self.document, root, id).is_empty()
+            })
+            .collect()
+    }
+
+    /// Returns the focusable element at the point (`x`, `y`) of the viewport, e.g. a link
+    /// clicked, or None if there's none. The last one in tree order is on top.
+    pub fn focusable_at(&self, x: f64, y: f64) -> Option<NodeId> {
+        let root = self.layout.as_ref()?;
+        let y = y + self.scroll_y;
+        self.focusable_elements().into_iter().rev().find(|&id| {
+            focus_rects(&self.document, root, id)
+                .iter()
+                .any(|r| r.contains(x, y))
+        })
+    }
+
+    pub fn focused(&self) -> Option<NodeId> {
+        self.focused
+    }
+
+    /// Moves the focus to `element`, or removes it if None, and scrolls the element into view.
+    /// The old and the new focus rings are damaged. Returns true if the focus changed.
+    pub fn focus(&mut self, element: Option<NodeId>) -> bool {
+        if self.focused == element {
+            return false;
+        }
+        // The focus stays on the node while the document changes, so its slot must not be reused.
+        if let Some(element) = element {
+            self.document.pin(element);
+        }
+        self.focused = element;
+        let list = self.build_display_list();
+        let old = core::mem::replace(&mut self.display_list, list);
+        let damage = diff_display_lists(&old, &self.display_list);
+        self.damage.merge(&damage);
+        self.scroll_into_view();
+        true
+    }
+
+    /// Moves the focus to the next focusable element, or the previous one if `backward`. The
+    /// focus wraps around after the last element. Returns true if the focus changed.
+    pub fn focus_next(&mut self, backward: bool) -> bool {
+        let elements = self.focusable_elements();
+        if elements.is_empty() {
+            return false;
+        }
+        let current = self
+            .focused
+            .and_then(|f| elements.iter().position(|&e| e == f));
+        let next = match (current, backward) {
+            (None, false) => 0,
+            (None, true) => elements.len() - 1,
+            (Some(i), false) => (i + 1) % elements.len(),
+            (Some(i), true) => (i + elements.len() - 1) % elements.len(),
+        };
+        self.focus(Some(elements[next]))
+    }
+
+    /// Fires a click at `element`, e.g. when it's activated by Enter, and renders the changes
+    /// made by the listeners. Returns false if a listener cancels the click, when its default
+    /// action, e.g. following a link, shouldn't happen.
+    /// https://html.spec.whatwg.org/multipage/webappapis.html#fire-a-click-event
+    pub fn click(&mut self, element: NodeId) -> bool {
+        if self.document.scripting().is_none() {
+            return true;
+        }
+        let mut proceed = true;
+        self.update_document(|document| proceed = document.dispatch_event(element, "click"));
+        proceed
+    }
+
+    /// Runs the tasks of the scripts which are due, e.g. the callbacks of timers, and renders the
+    /// changes made by them. Returns true if any task ran.
+    pub fn run_due_tasks(&mut self) -> bool {
+        let scripting = match self.document.scripting() {
+            Some(scripting) => scripting,
+            None => return false,
+        };
+        if !scripting.borrow().has_due_tasks() {
+            return false;
+        }
+        self.update_document(|document| scripting.borrow_mut().run_due_tasks(document));
+        true
+    }
+
+    /// Returns the milliseconds until the next task of the scripts is due, or None if there's
+    /// none.
+    pub fn until_next_task(&self) -> Option<u64> {
+        self.document.scripting()?.borrow().until_next_task()
+    }
+
+    /// Returns the href of the focused link, which is followed when it's activated.
+    pub fn focused_href(&self) -> Option<String> {
+        let focused = self.focused?;
+        self.document.node(focused).element()?.get_attribute("href")
+    }
+
+    /// Returns the focused element if it's a text control, into which keys are typed.
+    pub fn focused_text_control(&self) -> Option<NodeId> {
+        let focused = self.focused?;
+        if is_text_control(self.document.node(focused).element()?) {
+            Some(focused)
+        } else {
+            None
+        }
+    }
+
+    /// Sets the value of the text control `id` as the user edits it, and renders it again.
+    pub fn set_control_value(&mut self, id: NodeId, value: String) {
+        self.update_document(|document| {
+            if let Some(element) = document.node_mut(id).element_mut() {
+                element.set_value(value);
+            }
+        });
+    }
+
+    /// Returns what the focused form control submits when it's activated, or None if it's not a
+    /// control which submits its form.
+    pub fn focused_submission(&self) -> Option<Submission> {
+        submit(&self.document, self.focused?)
+    }
+
+    /// Scrolls the least so that the top of the focused element is in view, and as much of it as
+    /// the viewport can show.
+    fn scroll_into_view(&mut self) {
+        let rects = match (&self.layout, self.focused) {
+            (Some(root), Some(focused)) => focus_rects(&self.document, root, focused),
+            _ => return,
+        };
+        let area = match rects.split_first() {
+            Some((first, rest)) => rest.iter().fold(*first, |a, r| a.union(r)),
+            None => return,
+        };
+        let visible = self.visible_rect();
+        if area.y < visible.y {
+            self.scroll_to(area.y);
+        } else if area.bottom() > visible.bottom() {
+            self.scroll_to((area.bottom() - visible.height).min(area.y));
+        }
+    }
+
+    pub fn scroll_y(&self) -> f64 {
+        self.scroll_y
+    }
+
+    /// Returns the height of the document including the contents overflowing the boxes, which is
+    /// the range that can be scrolled.
+    /// https://www.w3.org/TR/css-overflow-3/#scrollable
+    pub fn document_height(&self) -> f64 {
+        match &self.layout {
+            Some(root) => bottom_of(root),
+            None => 0.0,
+        }
+    }
+
+    /// Returns the largest scroll position, where the bottom of the document is at the bottom of
+    /// the viewport.
+    pub fn max_scroll_y(&self) -> f64 {
+        let max = self.document_height() - self.viewport.height;
+        if max > 0.0 {
+            max
+        } else {
+            0.0
+        }
+    }
+
+    /// Scrolls to `y`, which is clamped so that the viewport doesn't go beyond the document.
+    /// Returns true if the scroll position changed.
+    pub fn scroll_to(&mut self, y: f64) -> bool {
+        let max = self.max_scroll_y();
+        let y = if y < 0.0 {
+            0.0
+        } else if y > max {
+            max
+        } else {
+            y
+        };
+        let dy = y - self.scroll_y;
+        if dy == 0.0 {
+            return false;
+        }
+        // The pixels which stay in the viewport are moved, so only the part scrolled into view
+        // is damaged.
+        let exposed = if dy > 0.0 {
+            Rect::new(
+                0.0,
+                self.scroll_y + self.viewport.height,
+                self.viewport.width,
+                dy,
+            )
+        } else {
+            Rect::new(0.0, y, self.viewport.width, -dy)
+        };
+        self.damage.add(exposed);
+        self.scrolled += dy;
+        self.scroll_y = y;
+        true
+    }
+
+    /// Scrolls so that the element indicated by `fragment` is at the top of the viewport, i.e.
+    /// the element whose id is `fragment`, or otherwise the a element named `fragment`. An empty
+    /// fragment and "top" indicate the top of the document. Returns true if the scroll position
+    /// changed.
+    /// https://html.spec.whatwg.org/multipage/browsing-the-web.html#find-a-potential-indicated-element
+    pub fn scroll_to_fragment(&mut self, fragment: &str) -> bool {
+        let document = &self.document;
+        let has_attribute = |id: NodeId, name: &str, tag: Option<&str>| {
+            document.node(id).element().is_some_and(|e| {
+                tag.is_none_or(|t| e.tag_name() == t)
+                    && e.get_attribute(name).as_deref() == Some(fragment)
+            })
+        };
+        let indicated = document
+            .descendants(document.root())
+            .find(|&n| has_attribute(n, "id", None))
+            .or_else(|| {
+                document
+                    .descendants(document.root())
+                    .find(|&n| has_attribute(n, "name", Some("a")))
+            });
+        match indicated {
+            Some(id) => match self.element_top(id) {
+                Some(top) => self.scroll_to(top),
+                None => false,
+            },
+            None if fragment.is_empty() || fragment.eq_ignore_ascii_case("top") => {
+                self.scroll_to(0.0)
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the top of the boxes of the element `id`. An element without boxes, e.g. an
+    /// empty anchor, is at the top of its nearest ancestor which has boxes.
+    fn element_top(&self, id: NodeId) -> Option<f64> {
+        let root = self.layout.as_ref()?;
+        let mut current = Some(id);
+        while let Some(node) = current {
+            let top = focus_rects(&self.document, root, node)
+                .iter()
+                .map(|r| r.y)
+                .fold(None, |top: Option<f64>, y| {
+                    Some(top.map_or(y, |t| t.min(y)))
+                });
+            if top.is_some() {
+                return top;
+            }
+            current = self.document.node(node).parent();
+        }
+        None
+    }
+
+    /// Scrolls down by `dy`, or up if it's negative. Returns true if the scroll position changed.
+    pub fn scroll_by(&mut self, dy: f64) -> bool {
+        self.scroll_to(self.scroll_y + dy)
+    }
+
+    /// Returns the part of the document shown in the viewport, to which painting is clipped.
+    pub fn visible_rect(&self) -> Rect {
+        Rect::new(
+            0.0,
+            self.scroll_y,
+            self.viewport.width,
+            self.viewport.height,
+        )
+    }
+
+    /// Returns the paint commands of the whole document.
+    pub fn display_list(&self) -> &DisplayList {
+        &self.display_list
+    }
+
+    /// Damages `rect` of the document, e.g. where something other than the page was painted over
+    /// it.
+    pub fn damage(&mut self, rect: Rect) {
+        self.damage.add(rect);
+    }
+
+    /// Returns the damage since the last call and how far the document is scrolled since then,
+    /// and resets them. A painter moves the pixels it painted by the distance and then repaints
+    /// the damaged regions.
+    pub fn take_damage(&mut self) -> (f64, Damage) {
+        let scrolled = core::mem::replace(&mut self.scrolled, 0.0);
+        (scrolled, core::mem::take(&mut self.damage))
+    }
+
+    /// Returns the canvas, which is as large as the document and at least as large as the
+    /// viewport.
+    /// https://www.w3.org/TR/CSS2/intro.html#canvas
+    fn canvas(&self) -> Rect {
+        Rect::new(
+            0.0,
+            0.0,
+            self.viewport.width,
+            self.document_height().max(self.viewport.height),
+        )
+    }
+
+    fn build_display_list(&self) -> DisplayList {
+        let root = match &self.layout {
+            Some(root) => root,
+            None => return DisplayList::new(),
+        };
+        let mut list = build_display_list(&self.document, root, self.canvas());
+        // The focus ring is drawn over the contents.
+        if let Some(focused) = self.focused {
+            paint_focus_ring(&focus_rects(&self.document, root, focused), &mut list);
+            // A focused text control shows where the text is typed.
+            let caret = find_box(root, focused).and_then(|b| {
+                caret_rect(&self.document, b, &Font::from_style(b.style()))
+                    .map(|rect| (rect, b.style().color()))
+            });
+            if let Some((rect, color)) = caret {
+                list.push(DisplayItem::SolidRect { rect, color });
+            }
+        }
+        list
+    }
+}
+
+/// Returns the box generated by `node` in `layout_box` and its descendants.
+fn find_box(layout_box: &LayoutBox, node: NodeId) -> Option<&LayoutBox> {
+    if layout_box.node() == Some(node) {
+        return Some(layout_box);
+    }
+    layout_box
+        .children()
+        .iter()
+        .chain(layout_box.out_of_flow())
+        .find_map(|child| find_box(child, node))
+}
+
+/// Returns the bottom of `layout_box` or its contents, whichever is lower.
+fn bottom_of(layout_box: &LayoutBox) -> f64 {
+    let mut bottom = layout_box.dimensions().margin_box().bottom();
+    for line in layout_box.lines() {
+        bottom = bottom.max(line.rect().bottom());
+    }
+    for child in layout_box.children().iter().chain(layout_box.out_of_flow()) {
+        bottom = bottom.max(bottom_of(child));
+    }
+    bottom
+}
+
+/// Returns true if `element` can get the focus, i.e. a link or a form control which isn't
+/// disabled.
+fn is_focusable(element: &Element) -> bool {
+    match element.kind() {
+        ElementKind::A => element.get_attribute("href").is_some(),
+        ElementKind::Button | ElementKind::Input | ElementKind::Textarea => {
+            element.get_attribute("disabled").is_none()
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,10 @@
+//! Painting: the display list built from the layout, and the backends which draw it.
+//! https://www.w3.org/TR/CSS2/zindex.html
+
+pub mod damage;
+pub mod display_list;
+pub mod double_buffer;
+pub mod focus_ring;
+pub mod frame_timer;
+pub mod framebuffer;
+pub mod raster;
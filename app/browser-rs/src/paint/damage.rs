@@ -0,0 +1,62 @@
+//! Damage tracking: the regions of the document which look different from when they were painted
+//! last time, so that only they are repainted.
+
+use crate::layout::geometry::*;
+use crate::paint::display_list::*;
+use alloc::vec::Vec;
+
+/// A set of damaged rectangles in the document. Overlapping rectangles are merged into their
+/// union so that no pixel is painted twice in a repaint.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Damage {
+    rects: Vec<Rect>,
+}
+
+#[allow(dead_code)]
+impl Damage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the damaged rectangles, which don't overlap each other.
+    pub fn rects(&self) -> &Vec<Rect> {
+        &self.rects
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// Marks `rect` as damaged. An empty rectangle is ignored.
+    pub fn add(&mut self, rect: Rect) {
+        if rect.width <= 0.0 || rect.height <= 0.0 {
+            return;
+        }
+        // The union may overlap other rectangles, so merging repeats until nothing overlaps.
+        let mut merged = rect;
+        while let Some(i) = self.rects.iter().position(|r| r.intersects(&merged)) {
+            merged = merged.union(&self.rects.swap_remove(i));
+        }
+        self.rects.push(merged);
+    }
+
+    /// Adds all the rectangles of `other`.
+    pub fn merge(&mut self, other: &Damage) {
+        for rect in &other.rects {
+            self.add(*rect);
+        }
+    }
+}
+
+/// Returns the damage caused by replacing the display list `old` with `new`: the areas of the
+/// items which are only in one of them. Items which only changed their order aren't detected.
+pub fn diff_display_lists(old: &DisplayList, new: &DisplayList) -> Damage {
+    let mut damage = Damage::new();
+    for item in old.iter().filter(|item| !new.contains(item)) {
+        damage.add(item.rect());
+    }
+    for item in new.iter().filter(|item| !old.contains(item)) {
+        damage.add(item.rect());
+    }
+    damage
+}
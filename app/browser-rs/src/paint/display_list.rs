@@ -0,0 +1,252 @@
+//! The display list, which is a sequence of paint commands in painting order built from the laid
+//! out boxes. Backends draw the commands without looking at the box tree.
+//! https://www.w3.org/TR/CSS2/zindex.html#painting-order
+
+use crate::font::Font;
+use crate::form::is_text_control;
+use crate::image::Image;
+use crate::layout::box_tree::*;
+use crate::layout::geometry::*;
+use crate::layout::inline::*;
+use crate::layout::text_control::{text_area, visible_lines, BORDER};
+use crate::parser::dom::*;
+use crate::style::computed::*;
+use crate::style::values::*;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A paint command. The coordinates are in the document, where the origin is the top-left corner
+/// of the canvas.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisplayItem {
+    /// Fills `rect` with `color`.
+    SolidRect { rect: Rect, color: Color },
+    /// The border of a box whose border box is `rect`. Sides whose width is 0 are not painted.
+    /// https://www.w3.org/TR/css-backgrounds-3/#borders
+    Border {
+        rect: Rect,
+        widths: Edges<f64>,
+        styles: Edges<BorderStyle>,
+        colors: Edges<Color>,
+    },
+    /// A run of text whose top-left corner is at the origin of `rect`.
+    Text {
+        text: String,
+        rect: Rect,
+        font: Font,
+        color: Color,
+        underline: bool,
+    },
+    /// An image scaled to fill `rect`.
+    Image { rect: Rect, image: Rc<Image> },
+}
+
+pub type DisplayList = Vec<DisplayItem>;
+
+/// The colors of a text control, which don't follow the styles like the widgets of a platform.
+const CONTROL_BACKGROUND: Color = Color::WHITE;
+const CONTROL_BORDER: Color = Color::rgb(0x76, 0x76, 0x76);
+
+#[allow(dead_code)]
+impl DisplayItem {
+    /// Returns the area which the item paints in.
+    pub fn rect(&self) -> Rect {
+        match self {
+            DisplayItem::SolidRect { rect, .. }
+            | DisplayItem::Border { rect, .. }
+            | DisplayItem::Text { rect, .. }
+            | DisplayItem::Image { rect, .. } => *rect,
+        }
+    }
+}
+
+/// Returns the display list of `root`, which is laid out on `canvas`.
+#[allow(dead_code)]
+pub fn build_display_list(document: &Document, root: &LayoutBox, canvas: Rect) -> DisplayList {
+    let mut list = Vec::new();
+    let body = body_box(document, root);
+
+    // "The background of the root element becomes the background of the canvas and covers the
+    // entire canvas". If it's transparent, the background of body is used instead and body is
+    // painted as if it were transparent.
+    // https://www.w3.org/TR/css-backgrounds-3/#special-backgrounds
+    let root_background = root.style().background_color();
+    let propagated = match body {
+        Some(body) if root_background.a() == 0 => Some(body),
+        _ => None,
+    };
+    let canvas_background = propagated.map_or(root_background, |b| b.style().background_color());
+    if canvas_background.a() != 0 {
+        list.push(DisplayItem::SolidRect {
+            rect: canvas,
+            color: canvas_background,
+        });
+    }
+
+    let skip_background = |b: &LayoutBox| {
+        core::ptr::eq(b, root) || propagated.is_some_and(|body| core::ptr::eq(b, body))
+    };
+    paint_box(document, root, &skip_background, &mut list);
+    list
+}
+
+/// Returns the box of the body element if it's a child of the root.
+fn body_box<'a>(document: &Document, root: &'a LayoutBox) -> Option<&'a LayoutBox> {
+    root.children().iter().find(|b| {
+        b.node()
+            .and_then(|n| document.node(n).element())
+            .is_some_and(|e| e.tag_name() == "body")
+    })
+}
+
+/// Appends the commands to paint `layout_box` and its descendants. This is a simplified painting
+/// order: a box paints its background and border, its marker and lines, its children in flow,
+/// then the relatively positioned children and the absolutely positioned ones. A replaced box
+/// paints its image instead of lines.
+fn paint_box<F: Fn(&LayoutBox) -> bool>(
+    document: &Document,
+    layout_box: &LayoutBox,
+    skip_background: &F,
+    list: &mut DisplayList,
+) {
+    if layout_box.is_inline_level() && !layout_box.is_replaced() {
+        // The text is painted in the line boxes of the container, but the absolutely positioned
+        // boxes in an inline box are painted here.
+        paint_children(document, layout_box, skip_background, list);
+        return;
+    }
+
+    let style = layout_box.style();
+    let d = layout_box.dimensions();
+    let background = style.background_color();
+    if background.a() != 0 && !skip_background(layout_box) {
+        list.push(DisplayItem::SolidRect {
+            rect: d.border_box(),
+            color: background,
+        });
+    }
+    let widths = d.border;
+    if widths.top > 0.0 || widths.right > 0.0 || widths.bottom > 0.0 || widths.left > 0.0 {
+        list.push(DisplayItem::Border {
+            rect: d.border_box(),
+            widths,
+            styles: style.border_style(),
+            colors: style.border_color(),
+        });
+    }
+
+    if layout_box.is_replaced() {
+        if let Some(image) = layout_box.node().and_then(|n| document.image(n)) {
+            list.push(DisplayItem::Image {
+                rect: d.content,
+                image: image.clone(),
+            });
+        }
+        paint_text_control(document, layout_box, list);
+    }
+
+    if let Some(marker) = layout_box.marker() {
+        paint_fragment(marker, list);
+    }
+    for line in layout_box.lines() {
+        for fragment in line.fragments() {
+            paint_fragment(fragment, list);
+        }
+    }
+    paint_children(document, layout_box, skip_background, list);
+}
+
+fn paint_children<F: Fn(&LayoutBox) -> bool>(
+    document: &Document,
+    layout_box: &LayoutBox,
+    skip_background: &F,
+    list: &mut DisplayList,
+) {
+    let children = layout_box.children();
+    let relative = |b: &&LayoutBox| b.style().position() == Position::Relative;
+    for child in children.iter().filter(|c| !relative(c)) {
+        paint_box(document, child, skip_background, list);
+    }
+    for child in children.iter().filter(relative) {
+        paint_box(document, child, skip_background, list);
+    }
+    for child in layout_box.out_of_flow() {
+        paint_box(document, child, skip_background, list);
+    }
+}
+
+/// Paints the border, the background and the value of a text control like a widget. Does nothing
+/// if `layout_box` isn't a text control.
+fn paint_text_control(document: &Document, layout_box: &LayoutBox, list: &mut DisplayList) {
+    let id = match layout_box.node() {
+        Some(id) => id,
+        None => return,
+    };
+    if !document.node(id).element().is_some_and(is_text_control) {
+        return;
+    }
+    let rect = layout_box.dimensions().content;
+    let widths = Edges {
+        top: BORDER,
+        right: BORDER,
+        bottom: BORDER,
+        left: BORDER,
+    };
+    list.push(DisplayItem::SolidRect {
+        rect,
+        color: CONTROL_BACKGROUND,
+    });
+    list.push(DisplayItem::Border {
+        rect,
+        widths,
+        styles: Edges {
+            top: BorderStyle::Solid,
+            right: BorderStyle::Solid,
+            bottom: BorderStyle::Solid,
+            left: BorderStyle::Solid,
+        },
+        colors: Edges {
+            top: CONTROL_BORDER,
+            right: CONTROL_BORDER,
+            bottom: CONTROL_BORDER,
+            left: CONTROL_BORDER,
+        },
+    });
+
+    let style = layout_box.style();
+    let font = Font::from_style(style);
+    let area = text_area(rect);
+    let lines = visible_lines(document, id, &font, area);
+    for (i, line) in lines.into_iter().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        list.push(DisplayItem::Text {
+            rect: Rect::new(
+                area.x,
+                area.y + font.glyph_height() * i as f64,
+                font.text_width(&line),
+                font.glyph_height(),
+            ),
+            text: line,
+            font,
+            color: style.color(),
+            underline: false,
+        });
+    }
+}
+
+fn paint_fragment(fragment: &Fragment, list: &mut DisplayList) {
+    if fragment.text().is_empty() {
+        return;
+    }
+    let style = fragment.style();
+    list.push(DisplayItem::Text {
+        text: String::from(fragment.text()),
+        rect: fragment.rect(),
+        font: Font::from_style(style),
+        color: style.color(),
+        underline: style.text_decoration() == TextDecoration::Underline,
+    });
+}
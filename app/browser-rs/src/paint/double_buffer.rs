@@ -0,0 +1,213 @@
+//! Double buffering: a frame is painted into a bitmap in memory first and then copied into the
+//! surface of the window and presented at once, so that the window never shows a frame which is
+//! painted halfway.
+
+use crate::layout::geometry::*;
+use crate::page::Page;
+use crate::paint::damage::Damage;
+use crate::paint::display_list::DisplayList;
+use crate::paint::framebuffer::{paint_region, repaint_changes};
+use crate::paint::raster::Bitmap;
+use alloc::vec::Vec;
+use liumlib::gui::BitmapImageBuffer;
+use liumlib::surface::Surface;
+
+/// A surface shown on the screen, `front`, and a bitmap as large as it, where frames are painted.
+/// The back bitmap keeps the last frame, so only the damaged regions are painted again.
+pub struct DoubleBuffer<T: Surface> {
+    front: T,
+    back: Bitmap,
+    /// The row of the buffers where the top of the page is shown, below e.g. a tab strip.
+    page_top: i64,
+    /// The parts of the document which the overlay covered in the last frame.
+    overlay: Vec<Rect>,
+}
+
+#[allow(dead_code)]
+impl<T: Surface> DoubleBuffer<T> {
+    /// Creates a black back bitmap as large as `front`.
+    pub fn new(front: T) -> Self {
+        Self::with_page_top(front, 0)
+    }
+
+    /// Creates a double buffer which shows the page from the row `page_top` down. The rows above
+    /// it are painted only by overlays.
+    pub fn with_page_top(front: T, page_top: i64) -> Self {
+        let (width, height) = front.size();
+        // The pixels of 4 bytes in BGR order are the same as the ones of surfaces in memory.
+        let back = Bitmap::with_bytes_per_pixel(width, height, 4);
+        Self {
+            front,
+            back,
+            page_top,
+            overlay: Vec::new(),
+        }
+    }
+
+    pub fn front(&self) -> &T {
+        &self.front
+    }
+
+    pub fn back(&self) -> &Bitmap {
+        &self.back
+    }
+
+    /// Brings the back bitmap up to date with `page`, and copies the pixels which may have changed
+    /// into the front surface and presents it. Returns the number of the painted regions.
+    pub fn repaint(&mut self, page: &mut Page) -> usize {
+        self.repaint_with_overlay(page, &DisplayList::new())
+    }
+
+    /// Repaints like `repaint()`, and paints `overlay` over the page, e.g. the user interface of
+    /// the browser. The items of `overlay` are placed in the window instead of the document.
+    pub fn repaint_with_overlay(&mut self, page: &mut Page, overlay: &DisplayList) -> usize {
+        // The page under the last overlay is painted again, wherever it is scrolled to.
+        for covered in self.overlay.drain(..) {
+            page.damage(covered);
+        }
+        let area = PageArea {
+            bitmap: &self.back,
+            top: self.page_top,
+        };
+        let (count, changed) = repaint_changes(&area, page);
+        let top = self.page_top as f64;
+        let mut changed = changed.map(|c| c.translated(0.0, top));
+
+        let window = self.window();
+        // Separate parts of the overlay, e.g. bars at the top and the bottom, are painted as
+        // separate regions so that the page between them isn't painted again.
+        let mut areas = Damage::new();
+        for item in overlay {
+            if let Some(rect) = item.rect().intersection(&window) {
+                areas.add(rect);
+            }
+        }
+        for area in areas.rects() {
+            paint_region(&self.back, overlay, window, *area);
+            changed = Some(changed.map_or(*area, |c| c.union(area)));
+            self.overlay
+                .push(area.translated(0.0, page.scroll_y() - top));
+        }
+
+        if let Some(changed) = changed {
+            present(&self.back, &mut self.front, changed);
+        }
+        count
+    }
+
+    /// Presents `overlay` over the last frame, e.g. a dialog shown while a script waits for it.
+    /// The back bitmap keeps the last frame, which `restore_front()` shows again.
+    pub fn paint_over_front(&mut self, overlay: &DisplayList) {
+        let window = self.window();
+        let area = overlay
+            .iter()
+            .filter_map(|item| item.rect().intersection(&window))
+            .reduce(|a, b| a.union(&b));
+        if let Some(area) = area {
+            // The overlay is painted over a copy of the frame, since the front can't be painted.
+            let scratch =
+                Bitmap::with_bytes_per_pixel(window.width as usize, window.height as usize, 4);
+            blit(&self.back, &scratch, area);
+            paint_region(&scratch, overlay, window, area);
+            present(&scratch, &mut self.front, area);
+        }
+    }
+
+    /// Shows the last frame in the front surface again, e.g. after a dialog is closed.
+    pub fn restore_front(&mut self) {
+        let window = self.window();
+        present(&self.back, &mut self.front, window);
+    }
+
+    fn window(&self) -> Rect {
+        Rect::new(
+            0.0,
+            0.0,
+            self.back.width() as f64,
+            self.back.height() as f64,
+        )
+    }
+}
+
+/// The rows of a bitmap from `top` down, where the page is painted.
+struct PageArea<'a> {
+    bitmap: &'a Bitmap,
+    top: i64,
+}
+
+impl<'a> BitmapImageBuffer for PageArea<'a> {
+    fn bytes_per_pixel(&self) -> i64 {
+        self.bitmap.bytes_per_pixel()
+    }
+    fn pixels_per_line(&self) -> i64 {
+        self.bitmap.pixels_per_line()
+    }
+    fn width(&self) -> i64 {
+        self.bitmap.width()
+    }
+    fn height(&self) -> i64 {
+        (self.bitmap.height() - self.top).max(0)
+    }
+    fn buf(&self) -> *mut u8 {
+        unsafe { self.pixel_at(0, 0) }
+    }
+    unsafe fn pixel_at(&self, x: i64, y: i64) -> *mut u8 {
+        self.bitmap.pixel_at(x, y + self.top)
+    }
+    fn flush(&self) {}
+    fn is_in_x_range(&self, px: i64) -> bool {
+        self.bitmap.is_in_x_range(px)
+    }
+    fn is_in_y_range(&self, py: i64) -> bool {
+        0 <= py && py < self.height()
+    }
+}
+
+/// Copies the pixels of `src` in `rect` to the same position of `dst`. The bytes of a pixel after
+/// the first 3 ones, i.e. BGR, are left as they are if the sizes of the pixels differ.
+pub fn blit<S: BitmapImageBuffer, D: BitmapImageBuffer>(src: &S, dst: &D, rect: Rect) {
+    let left = clamp(rect.x, src.width().min(dst.width()));
+    let right = clamp(rect.right(), src.width().min(dst.width()));
+    let top = clamp(rect.y, src.height().min(dst.height()));
+    let bottom = clamp(rect.bottom(), src.height().min(dst.height()));
+    if left >= right {
+        return;
+    }
+    let same_layout = src.bytes_per_pixel() == dst.bytes_per_pixel();
+    for y in top..bottom {
+        unsafe {
+            if same_layout {
+                let size = ((right - left) * src.bytes_per_pixel()) as usize;
+                core::ptr::copy_nonoverlapping(src.pixel_at(left, y), dst.pixel_at(left, y), size);
+            } else {
+                for x in left..right {
+                    core::ptr::copy_nonoverlapping(src.pixel_at(x, y), dst.pixel_at(x, y), 3);
+                }
+            }
+        }
+    }
+}
+
+/// Copies the pixels of `src` in `rect` to the same position of `surface`, and presents it.
+pub fn present<S: BitmapImageBuffer, T: Surface>(src: &S, surface: &mut T, rect: Rect) {
+    let (width, height) = surface.size();
+    let left = clamp(rect.x, src.width().min(width as i64));
+    let right = clamp(rect.right(), src.width().min(width as i64));
+    let top = clamp(rect.y, src.height().min(height as i64));
+    let bottom = clamp(rect.bottom(), src.height().min(height as i64));
+    let pixels = surface.pixels();
+    for y in top..bottom {
+        for x in left..right {
+            let bgr = unsafe { core::slice::from_raw_parts(src.pixel_at(x, y), 3) };
+            pixels[y as usize * width + x as usize] =
+                (bgr[2] as u32) << 16 | (bgr[1] as u32) << 8 | bgr[0] as u32;
+        }
+    }
+    surface.present();
+}
+
+/// Rounds `v` to the nearest pixel in 0..=`max`.
+fn clamp(v: f64, max: i64) -> i64 {
+    let v = if v < 0.0 { 0 } else { (v + 0.5) as i64 };
+    v.min(max)
+}
@@ -0,0 +1,90 @@
+//! The focus ring, which shows the element having the focus, e.g. a link selected with Tab.
+//! https://html.spec.whatwg.org/multipage/interaction.html#focus
+//! https://www.w3.org/TR/css-ui-4/#outline-style
+
+use crate::layout::box_tree::LayoutBox;
+use crate::layout::geometry::Rect;
+use crate::paint::display_list::{DisplayItem, DisplayList};
+use crate::parser::dom::{Document, NodeId};
+use crate::style::computed::{BorderStyle, Edges};
+use crate::style::values::Color;
+use alloc::vec::Vec;
+
+/// The width of the ring in px, drawn outside the boxes like `outline-offset: 0`.
+const RING_WIDTH: f64 = 2.0;
+const RING_COLOR: Color = Color::rgb(0x00, 0x5f, 0xcc);
+
+/// Returns the areas which the element `node` renders in: the border boxes of its block-level
+/// and replaced boxes, and the text in each line box, or an empty list if it isn't rendered.
+#[allow(dead_code)]
+pub fn focus_rects(document: &Document, root: &LayoutBox, node: NodeId) -> Vec<Rect> {
+    let mut rects = Vec::new();
+    collect_rects(document, root, node, &mut rects);
+    rects
+}
+
+fn collect_rects(document: &Document, layout_box: &LayoutBox, node: NodeId, rects: &mut Vec<Rect>) {
+    let inside = |n: Option<NodeId>| n.is_some_and(|n| is_inclusive_ancestor(document, node, n));
+    if (!layout_box.is_inline_level() || layout_box.is_replaced()) && inside(layout_box.node()) {
+        rects.push(layout_box.dimensions().border_box());
+        return;
+    }
+    for line in layout_box.lines() {
+        // The fragments of the element in a line are covered by a ring.
+        let covered = line
+            .fragments()
+            .iter()
+            .filter(|f| !f.text().is_empty() && inside(f.node()))
+            .map(|f| f.rect())
+            .fold(None, |acc: Option<Rect>, r| {
+                Some(acc.map_or(r, |a| a.union(&r)))
+            });
+        if let Some(rect) = covered {
+            rects.push(rect);
+        }
+    }
+    for child in layout_box.children().iter().chain(layout_box.out_of_flow()) {
+        collect_rects(document, child, node, rects);
+    }
+}
+
+/// Returns true if `ancestor` is `node` or one of its ancestors.
+fn is_inclusive_ancestor(document: &Document, ancestor: NodeId, node: NodeId) -> bool {
+    let mut current = Some(node);
+    while let Some(n) = current {
+        if n == ancestor {
+            return true;
+        }
+        current = document.node(n).parent();
+    }
+    false
+}
+
+/// Appends the commands to draw a ring around each of `rects`.
+#[allow(dead_code)]
+pub fn paint_focus_ring(rects: &[Rect], list: &mut DisplayList) {
+    let widths = Edges {
+        top: RING_WIDTH,
+        right: RING_WIDTH,
+        bottom: RING_WIDTH,
+        left: RING_WIDTH,
+    };
+    for rect in rects {
+        list.push(DisplayItem::Border {
+            rect: rect.expanded_by(widths),
+            widths,
+            styles: Edges {
+                top: BorderStyle::Solid,
+                right: BorderStyle::Solid,
+                bottom: BorderStyle::Solid,
+                left: BorderStyle::Solid,
+            },
+            colors: Edges {
+                top: RING_COLOR,
+                right: RING_COLOR,
+                bottom: RING_COLOR,
+                left: RING_COLOR,
+            },
+        });
+    }
+}
@@ -0,0 +1,54 @@
+//! A counter of the time taken to paint frames.
+
+use crate::timing::nanos_since;
+use liumlib::time::Instant;
+
+/// Measures frames in nanoseconds of the monotonic clock.
+#[derive(Debug, Clone, Default)]
+pub struct FrameTimer {
+    /// When the current frame started, or None between frames.
+    start: Option<Instant>,
+    frames: u64,
+    total: u64,
+    last: u64,
+}
+
+#[allow(dead_code)]
+impl FrameTimer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts measuring a frame.
+    pub fn start(&mut self) {
+        self.start = Some(Instant::now());
+    }
+
+    /// Finishes the frame started by `start()` and returns how many nanoseconds it took. Returns 0 if
+    /// no frame is started.
+    pub fn finish(&mut self) -> u64 {
+        let start = match self.start.take() {
+            Some(start) => start,
+            None => return 0,
+        };
+        self.last = nanos_since(start);
+        self.frames += 1;
+        self.total += self.last;
+        self.last
+    }
+
+    /// Returns the number of the finished frames.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Returns the nanoseconds of the last frame.
+    pub fn last(&self) -> u64 {
+        self.last
+    }
+
+    /// Returns the average nanoseconds of the finished frames.
+    pub fn average(&self) -> u64 {
+        self.total.checked_div(self.frames).unwrap_or(0)
+    }
+}
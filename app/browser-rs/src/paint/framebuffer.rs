@@ -0,0 +1,237 @@
+//! A backend which draws the display list into a bitmap buffer of liumlib, e.g. a window.
+
+use crate::image::Image;
+use crate::layout::geometry::*;
+use crate::page::Page;
+use crate::paint::display_list::*;
+use crate::style::values::*;
+use liumlib::gui::BitmapImageBuffer;
+
+/// The color of the canvas where nothing is painted.
+const CANVAS_COLOR: Color = Color::WHITE;
+
+/// Paints `list` into `buf`. The top-left pixel of `buf` shows the point (`visible.x`,
+/// `visible.y`) of the document, and items outside `visible` are skipped.
+#[allow(dead_code)]
+pub fn paint<T: BitmapImageBuffer>(buf: &T, list: &DisplayList, visible: Rect) {
+    paint_region(buf, list, visible, visible);
+}
+
+/// Paints the part `region` of the document like `paint()`. The pixels outside `region` are left
+/// as they are.
+#[allow(dead_code)]
+pub fn paint_region<T: BitmapImageBuffer>(
+    buf: &T,
+    list: &DisplayList,
+    visible: Rect,
+    region: Rect,
+) {
+    let target = Target::new(buf, visible, region);
+    target.fill_rect(region, CANVAS_COLOR);
+    for item in list {
+        if !item.rect().intersects(&region) {
+            continue;
+        }
+        match item {
+            DisplayItem::SolidRect { rect, color } => target.fill_rect(*rect, *color),
+            DisplayItem::Border {
+                rect,
+                widths,
+                colors,
+                ..
+            } => {
+                // Every style is drawn as solid.
+                let (r, w) = (rect, widths);
+                let top = Rect::new(r.x, r.y, r.width, w.top);
+                let bottom = Rect::new(r.x, r.bottom() - w.bottom, r.width, w.bottom);
+                let inner_height = r.height - w.top - w.bottom;
+                let left = Rect::new(r.x, r.y + w.top, w.left, inner_height);
+                let right = Rect::new(r.right() - w.right, r.y + w.top, w.right, inner_height);
+                target.fill_rect(top, colors.top);
+                target.fill_rect(bottom, colors.bottom);
+                target.fill_rect(left, colors.left);
+                target.fill_rect(right, colors.right);
+            }
+            DisplayItem::Text {
+                text,
+                rect,
+                font,
+                color,
+                underline,
+            } => {
+                let mut x = rect.x - target.origin.0;
+                let y = round(rect.y - target.origin.1);
+                for c in text.chars() {
+                    let left = round(x);
+                    // Glyphs on the edges of the region are drawn partially.
+                    font.for_each_pixel(c, |px, py| {
+                        target.put_pixel(left + px as i64, y + py as i64, *color);
+                    });
+                    x += font.advance(c);
+                }
+                if *underline {
+                    let line = Rect::new(
+                        rect.x,
+                        rect.y + font.underline_offset(),
+                        rect.width,
+                        font.underline_thickness(),
+                    );
+                    target.fill_rect(line, *color);
+                }
+            }
+            DisplayItem::Image { rect, image } => target.draw_image(*rect, image),
+        }
+    }
+}
+
+/// Brings `buf`, which shows `page` as it was painted last time, up to date: the pixels are moved
+/// as far as the page is scrolled since then, and only the damaged regions are painted. Returns
+/// the number of the painted regions.
+#[allow(dead_code)]
+pub fn repaint<T: BitmapImageBuffer>(buf: &T, page: &mut Page) -> usize {
+    repaint_changes(buf, page).0
+}
+
+/// Repaints `buf` like `repaint()`, and also returns the area of `buf` whose pixels may have
+/// changed, where the origin is the top-left pixel of `buf`.
+pub fn repaint_changes<T: BitmapImageBuffer>(buf: &T, page: &mut Page) -> (usize, Option<Rect>) {
+    let visible = page.visible_rect();
+    let (scrolled, damage) = page.take_damage();
+    let dy = round(scrolled);
+    scroll_pixels(buf, dy);
+    let mut changed = if dy != 0 {
+        Some(Rect::new(0.0, 0.0, visible.width, visible.height))
+    } else {
+        None
+    };
+    let mut count = 0;
+    for rect in damage.rects() {
+        if let Some(region) = rect.intersection(&visible) {
+            paint_region(buf, page.display_list(), visible, region);
+            count += 1;
+            let region = region.translated(-visible.x, -visible.y);
+            changed = Some(changed.map_or(region, |c| c.union(&region)));
+        }
+    }
+    (count, changed)
+}
+
+/// Moves the pixels of `buf` up by `dy` pixels, or down if it's negative. The rows moved in from
+/// outside keep their old pixels.
+fn scroll_pixels<T: BitmapImageBuffer>(buf: &T, dy: i64) {
+    let rows = buf.height() - dy.abs();
+    if dy == 0 || rows <= 0 {
+        return;
+    }
+    let row_size = (buf.pixels_per_line() * buf.bytes_per_pixel()) as usize;
+    let (from, to) = if dy > 0 { (dy, 0) } else { (0, -dy) };
+    unsafe {
+        core::ptr::copy(
+            buf.pixel_at(0, from),
+            buf.pixel_at(0, to),
+            row_size * rows as usize,
+        );
+    }
+}
+
+/// A buffer to paint the part `region` of the document in.
+struct Target<'a, T: BitmapImageBuffer> {
+    buf: &'a T,
+    /// The point of the document at the top-left pixel of the buffer.
+    origin: (f64, f64),
+    /// The pixels which the region covers in the buffer as (left, top, right, bottom), where the
+    /// right and the bottom are exclusive.
+    bounds: (i64, i64, i64, i64),
+}
+
+impl<'a, T: BitmapImageBuffer> Target<'a, T> {
+    fn new(buf: &'a T, visible: Rect, region: Rect) -> Self {
+        let origin = (visible.x, visible.y);
+        let clamp = |v: i64, max: i64| v.max(0).min(max);
+        let bounds = (
+            clamp(round(region.x - origin.0), buf.width()),
+            clamp(round(region.y - origin.1), buf.height()),
+            clamp(round(region.right() - origin.0), buf.width()),
+            clamp(round(region.bottom() - origin.1), buf.height()),
+        );
+        Self {
+            buf,
+            origin,
+            bounds,
+        }
+    }
+
+    /// Returns the pixels which `rect` in the document covers in the region, as `bounds`.
+    fn clip(&self, rect: Rect) -> (i64, i64, i64, i64) {
+        let (left, top, right, bottom) = self.bounds;
+        let clamp = |v: i64, min: i64, max: i64| v.max(min).min(max);
+        (
+            clamp(round(rect.x - self.origin.0), left, right),
+            clamp(round(rect.y - self.origin.1), top, bottom),
+            clamp(round(rect.right() - self.origin.0), left, right),
+            clamp(round(rect.bottom() - self.origin.1), top, bottom),
+        )
+    }
+
+    fn fill_rect(&self, rect: Rect, color: Color) {
+        let (left, top, right, bottom) = self.clip(rect);
+        for y in top..bottom {
+            for x in left..right {
+                put_pixel(self.buf, x, y, color);
+            }
+        }
+    }
+
+    /// Draws `image` scaled to `rect` in the document by the nearest neighbor sampling.
+    fn draw_image(&self, rect: Rect, image: &Image) {
+        if image.width() == 0 || image.height() == 0 {
+            return;
+        }
+        let (left, top, right, bottom) = self.clip(rect);
+        // Returns the index of the source pixel whose area covers the center of the pixel `v` in
+        // the buffer.
+        let sample = |v: i64, start: f64, length: f64, count: usize| {
+            let i = (v as f64 + 0.5 - start) / length * count as f64;
+            (i as usize).min(count - 1)
+        };
+        for y in top..bottom {
+            let sy = sample(y, rect.y - self.origin.1, rect.height, image.height());
+            for x in left..right {
+                let sx = sample(x, rect.x - self.origin.0, rect.width, image.width());
+                put_pixel(self.buf, x, y, image.pixel(sx, sy));
+            }
+        }
+    }
+
+    /// Draws `color` over the pixel at (`x`, `y`) in the buffer if it's in the region.
+    fn put_pixel(&self, x: i64, y: i64, color: Color) {
+        let (left, top, right, bottom) = self.bounds;
+        if left <= x && x < right && top <= y && y < bottom {
+            put_pixel(self.buf, x, y, color);
+        }
+    }
+}
+
+/// Draws `color` over the pixel at (`x`, `y`), which must be in `buf`, blending it by its alpha.
+fn put_pixel<T: BitmapImageBuffer>(buf: &T, x: i64, y: i64, color: Color) {
+    let blend = |dst: u8, src: u8| {
+        let a = color.a() as u32;
+        ((src as u32 * a + dst as u32 * (255 - a) + 127) / 255) as u8
+    };
+    unsafe {
+        // The pixels are in BGRA order.
+        let p = buf.pixel_at(x, y);
+        *p.add(2) = blend(*p.add(2), color.r());
+        *p.add(1) = blend(*p.add(1), color.g());
+        *p.add(0) = blend(*p.add(0), color.b());
+    }
+}
+
+/// Rounds a coordinate in px to the nearest pixel.
+fn round(v: f64) -> i64 {
+    if v < 0.0 {
+        (v - 0.5) as i64
+    } else {
+        (v + 0.5) as i64
+    }
+}
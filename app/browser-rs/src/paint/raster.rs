@@ -0,0 +1,144 @@
+//! A headless backend which paints into a bitmap in memory and encodes it as a PPM or BMP file,
+//! so that rendering can be checked pixel by pixel without a display.
+
+use crate::layout::geometry::*;
+use crate::paint::display_list::*;
+use crate::paint::framebuffer::paint;
+use crate::style::values::*;
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use liumlib::gui::BitmapImageBuffer;
+
+/// The size of a pixel in bytes by default. The pixels are in BGR order like the windows of
+/// liumOS, so that the same painter draws into both.
+const BYTES_PER_PIXEL: usize = 3;
+
+/// An image of 24-bit pixels in memory.
+#[derive(Debug)]
+pub struct Bitmap {
+    width: usize,
+    height: usize,
+    /// The size of a pixel in bytes, where the bytes after the first 3 ones are unused.
+    bytes_per_pixel: usize,
+    pixels: Vec<u8>,
+    /// The start of `pixels`, which the painter writes through.
+    ptr: *mut u8,
+}
+
+#[allow(dead_code)]
+impl Bitmap {
+    /// Creates a black bitmap.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::with_bytes_per_pixel(width, height, BYTES_PER_PIXEL)
+    }
+
+    /// Creates a black bitmap whose pixels take `bytes_per_pixel` bytes, which must be 3 or
+    /// more, e.g. to have the same layout as a window.
+    pub fn with_bytes_per_pixel(width: usize, height: usize, bytes_per_pixel: usize) -> Self {
+        assert!(bytes_per_pixel >= 3);
+        let mut pixels = vec![0; width * height * bytes_per_pixel];
+        let ptr = pixels.as_mut_ptr();
+        Self {
+            width,
+            height,
+            bytes_per_pixel,
+            pixels,
+            ptr,
+        }
+    }
+
+    /// Returns the color of the pixel at (`x`, `y`).
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        let i = (y * self.width + x) * self.bytes_per_pixel;
+        Color::rgb(self.pixels[i + 2], self.pixels[i + 1], self.pixels[i])
+    }
+
+    /// Encodes the bitmap in the binary format of PPM.
+    /// http://netpbm.sourceforge.net/doc/ppm.html
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut data = Vec::from(format!("P6\n{} {}\n255\n", self.width, self.height).as_bytes());
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let c = self.pixel(x, y);
+                data.extend_from_slice(&[c.r(), c.g(), c.b()]);
+            }
+        }
+        data
+    }
+
+    /// Encodes the bitmap as an uncompressed 24-bit BMP, whose rows are stored from the bottom and
+    /// padded to multiples of 4 bytes.
+    pub fn to_bmp(&self) -> Vec<u8> {
+        const HEADER_SIZE: usize = 14 + 40;
+        let row_size = (self.width * BYTES_PER_PIXEL + 3) & !3;
+        let image_size = row_size * self.height;
+
+        let mut data = Vec::with_capacity(HEADER_SIZE + image_size);
+        // BITMAPFILEHEADER
+        data.extend_from_slice(b"BM");
+        data.extend_from_slice(&((HEADER_SIZE + image_size) as u32).to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&(HEADER_SIZE as u32).to_le_bytes());
+        // BITMAPINFOHEADER
+        data.extend_from_slice(&40u32.to_le_bytes());
+        data.extend_from_slice(&(self.width as i32).to_le_bytes());
+        data.extend_from_slice(&(self.height as i32).to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes());
+        data.extend_from_slice(&24u16.to_le_bytes());
+        // No compression.
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&(image_size as u32).to_le_bytes());
+        // 300 ppi, the same as the windows of liumOS.
+        data.extend_from_slice(&0x2e23u32.to_le_bytes());
+        data.extend_from_slice(&0x2e23u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+
+        for y in (0..self.height).rev() {
+            for x in 0..self.width {
+                let c = self.pixel(x, y);
+                data.extend_from_slice(&[c.b(), c.g(), c.r()]);
+            }
+            data.resize(data.len() + row_size - self.width * BYTES_PER_PIXEL, 0);
+        }
+        data
+    }
+}
+
+impl BitmapImageBuffer for Bitmap {
+    fn bytes_per_pixel(&self) -> i64 {
+        self.bytes_per_pixel as i64
+    }
+    fn pixels_per_line(&self) -> i64 {
+        self.width as i64
+    }
+    fn width(&self) -> i64 {
+        self.width as i64
+    }
+    fn height(&self) -> i64 {
+        self.height as i64
+    }
+    fn buf(&self) -> *mut u8 {
+        self.ptr
+    }
+    unsafe fn pixel_at(&self, x: i64, y: i64) -> *mut u8 {
+        self.buf()
+            .add(((y * self.pixels_per_line() + x) * self.bytes_per_pixel()) as usize)
+    }
+    fn flush(&self) {}
+    fn is_in_x_range(&self, px: i64) -> bool {
+        0 <= px && px < self.width as i64
+    }
+    fn is_in_y_range(&self, py: i64) -> bool {
+        0 <= py && py < self.height as i64
+    }
+}
+
+/// Paints the part `visible` of the document into a new bitmap as large as it.
+#[allow(dead_code)]
+pub fn rasterize(list: &DisplayList, visible: Rect) -> Bitmap {
+    let bitmap = Bitmap::new(visible.width as usize, visible.height as usize);
+    paint(&bitmap, list, visible);
+    bitmap
+}
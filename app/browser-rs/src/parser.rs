@@ -1,16 +1,77 @@
+pub mod css;
 pub mod dom;
 pub mod tokenizer;
 
+use crate::js::engine::{Engine, ScriptFetcher};
+use crate::page::Page;
+use crate::style::computed::Viewport;
+use crate::timing::{nanos_since, Stage, Timing};
+use alloc::rc::Rc;
 use alloc::string::String;
+use core::cell::RefCell;
 use dom::*;
+use liumlib::time::Instant;
 use liumlib::*;
 use tokenizer::*;
 
+/// Tokenizes `html` and constructs a DOM tree.
+pub fn parse(html: String) -> Document {
+    parse_with_timing(html, &mut Timing::new())
+}
+
+/// Parses `html` like `parse()`, and adds the time taken by the tokenizer and the tree
+/// construction to `timing`. The scripts run while the tree is constructed, so they're counted in
+/// the tree construction. External scripts are skipped.
+pub fn parse_with_timing(html: String, timing: &mut Timing) -> Document {
+    parse_with_engine(html, timing, Engine::new())
+}
+
+/// Parses `html` for the document at `url` like `parse_with_timing()`, and runs the external
+/// scripts too, which are fetched by `fetch` with their src. The time taken by fetching them
+/// are added to the fetch stage.
+#[allow(dead_code)]
+pub fn parse_with_scripts(
+    html: String,
+    url: &str,
+    timing: &mut Timing,
+    fetch: ScriptFetcher,
+) -> Document {
+    let mut engine = Engine::with_url(url);
+    engine.set_fetcher(fetch);
+    parse_with_engine(html, timing, engine)
+}
+
+/// Parses `html` like `parse_with_timing()`, where the scripts run with `engine`, e.g. one which
+/// fetches external scripts and shows dialogs.
+pub fn parse_with_engine(html: String, timing: &mut Timing, engine: Engine) -> Document {
+    let mut parser = Parser::new(Tokenizer::new(html));
+    let engine = Rc::new(RefCell::new(engine));
+    parser.set_scripting(engine.clone());
+    let start = Instant::now();
+    let mut document = parser.construct_tree();
+    engine.borrow_mut().run_deferred_scripts(&mut document);
+    let time = nanos_since(start);
+    // The tokenizer and the fetches run inside the tree construction, so they're not counted
+    // twice.
+    let fetch_time = engine.borrow().fetch_time();
+    timing.add(Stage::Fetch, fetch_time);
+    timing.add(Stage::Tokenize, parser.tokenize_time());
+    timing.add(
+        Stage::TreeBuild,
+        time.saturating_sub(parser.tokenize_time() + fetch_time),
+    );
+    document
+}
+
+#[allow(dead_code)]
+pub fn render(document: Document, viewport: Viewport) -> Page {
+    render_with_timing(document, viewport, &mut Timing::new())
+}
+
+/// Renders `document` like `render()`, and adds the time taken by each stage to `timing`.
 #[allow(dead_code)]
-pub fn render(html: String) {
-    println!("===== rendering start ===== ");
-    println!("{}", html);
+pub fn render_with_timing(document: Document, viewport: Viewport, timing: &mut Timing) -> Page {
+    debug!("rendering start");
 
-    let t = Tokenizer::new(html);
-    let _root = Parser::new(t).construct_tree();
+    Page::with_timing(document, viewport, timing)
 }
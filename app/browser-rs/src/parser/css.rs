@@ -0,0 +1,7 @@
+//! This is a part of CSS support.
+//! https://www.w3.org/TR/css-syntax-3/
+
+pub mod cssom;
+pub mod media;
+pub mod selector;
+pub mod tokenizer;
@@ -0,0 +1,435 @@
+//! This is a part of "5. Parsing" in the CSS Syntax Module Level 3 and the CSS Object Model.
+//! https://www.w3.org/TR/css-syntax-3/#parsing
+//! https://www.w3.org/TR/cssom-1/
+
+use crate::parser::css::media::*;
+use crate::parser::css::selector::*;
+use crate::parser::css::tokenizer::*;
+use crate::style::computed::Viewport;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+#[allow(unused_imports)]
+use liumlib::*;
+
+/// https://www.w3.org/TR/cssom-1/#cssstylesheet
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StyleSheet {
+    /// The URL of the stylesheet if it's from a link element.
+    href: Option<String>,
+    rules: Vec<StyleRule>,
+}
+
+#[allow(dead_code)]
+impl StyleSheet {
+    pub fn new() -> Self {
+        Self {
+            href: None,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Returns an empty stylesheet for a link element. The rules are set once it's fetched.
+    pub fn from_href(href: &str) -> Self {
+        Self {
+            href: Some(String::from(href)),
+            rules: Vec::new(),
+        }
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-stylesheet-href
+    pub fn href(&self) -> Option<&str> {
+        self.href.as_deref()
+    }
+
+    /// Replaces the rules with the ones of `sheet`, e.g. a fetched stylesheet.
+    pub fn set_rules(&mut self, sheet: StyleSheet) {
+        self.rules = sheet.rules;
+    }
+
+    /// Returns the stylesheet without the rules in `@media` rules which don't match `viewport`.
+    pub fn evaluate_media(&self, viewport: Viewport) -> StyleSheet {
+        Self {
+            href: self.href.clone(),
+            rules: self
+                .rules
+                .iter()
+                .filter(|r| r.media.iter().all(|m| m.matches(viewport)))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstylesheet-cssrules
+    pub fn rules(&self) -> &Vec<StyleRule> {
+        &self.rules
+    }
+}
+
+/// A rule such as `p.a { color: red; }`.
+/// https://www.w3.org/TR/cssom-1/#cssstylerule
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyleRule {
+    selectors: SelectorList,
+    declarations: Vec<Declaration>,
+    /// The media query lists of the `@media` rules which contain this rule. The rule applies only
+    /// if all of them match.
+    /// https://www.w3.org/TR/css-conditional-3/#at-media
+    media: Vec<MediaQueryList>,
+}
+
+#[allow(dead_code)]
+impl StyleRule {
+    pub fn new(selectors: SelectorList, declarations: Vec<Declaration>) -> Self {
+        Self {
+            selectors,
+            declarations,
+            media: Vec::new(),
+        }
+    }
+
+    pub fn media(&self) -> &Vec<MediaQueryList> {
+        &self.media
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstylerule-selectortext
+    pub fn selectors(&self) -> &SelectorList {
+        &self.selectors
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-cssstylerule-style
+    pub fn declarations(&self) -> &Vec<Declaration> {
+        &self.declarations
+    }
+}
+
+/// A pair of a property name and a value such as `color: red !important`.
+/// https://www.w3.org/TR/css-syntax-3/#declaration
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
+    /// The property name in lowercase.
+    name: String,
+    /// The component values without the leading and trailing whitespace and `!important`.
+    value: Vec<CssToken>,
+    important: bool,
+}
+
+#[allow(dead_code)]
+impl Declaration {
+    pub fn new(name: &str, value: Vec<CssToken>, important: bool) -> Self {
+        Self {
+            name: name.to_ascii_lowercase(),
+            value,
+            important,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> &Vec<CssToken> {
+        &self.value
+    }
+
+    pub fn important(&self) -> bool {
+        self.important
+    }
+}
+
+/// Consumes CSS tokens and builds a stylesheet. Invalid rules and declarations are dropped
+/// without affecting the rest of the stylesheet.
+#[derive(Debug, Clone)]
+pub struct CssParser {
+    tokens: Vec<CssToken>,
+    pos: usize,
+}
+
+#[allow(dead_code)]
+impl CssParser {
+    pub fn new(t: CssTokenizer) -> Self {
+        Self {
+            tokens: t.collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<&CssToken> {
+        self.tokens.get(self.pos)
+    }
+
+    fn consume(&mut self) -> Option<CssToken> {
+        let token = self.tokens.get(self.pos).cloned();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#parse-stylesheet
+    pub fn parse_stylesheet(&mut self) -> StyleSheet {
+        let mut sheet = StyleSheet::new();
+        sheet.rules = self.consume_list_of_rules();
+        sheet
+    }
+
+    /// Returns the style rules including the ones in `@media` rules.
+    /// https://www.w3.org/TR/css-syntax-3/#consume-list-of-rules
+    fn consume_list_of_rules(&mut self) -> Vec<StyleRule> {
+        let mut rules = Vec::new();
+
+        while let Some(token) = self.peek() {
+            match token {
+                CssToken::Whitespace | CssToken::Cdo | CssToken::Cdc => {
+                    self.pos += 1;
+                }
+                CssToken::AtKeyword(_) => {
+                    rules.extend(self.consume_at_rule());
+                }
+                _ => {
+                    if let Some(rule) = self.consume_qualified_rule() {
+                        rules.push(rule);
+                    }
+                }
+            }
+        }
+
+        rules
+    }
+
+    /// Parses the contents of a style block, e.g. the value of a style attribute.
+    /// https://www.w3.org/TR/css-syntax-3/#parse-list-of-declarations
+    pub fn parse_declaration_list(&mut self) -> Vec<Declaration> {
+        let declarations = consume_declaration_list(&self.tokens[self.pos..]);
+        self.pos = self.tokens.len();
+        declarations
+    }
+
+    /// Returns the style rules in the at-rule. Only `@media` rules are supported and the other
+    /// at-rules are skipped.
+    /// https://www.w3.org/TR/css-syntax-3/#consume-at-rule
+    fn consume_at_rule(&mut self) -> Vec<StyleRule> {
+        let is_media = match self.consume() {
+            Some(CssToken::AtKeyword(name)) => name.eq_ignore_ascii_case("media"),
+            _ => false,
+        };
+
+        // Consume the prelude.
+        let prelude_start = self.pos;
+        while let Some(token) = self.consume() {
+            match token {
+                CssToken::Semicolon => return Vec::new(),
+                CssToken::OpenCurly => {
+                    let prelude_end = self.pos - 1;
+                    let block_start = self.pos;
+                    let block_end = match self.consume_block_contents(CssToken::CloseCurly) {
+                        true => self.pos - 1,
+                        false => self.pos,
+                    };
+                    if !is_media {
+                        return Vec::new();
+                    }
+
+                    // https://www.w3.org/TR/css-conditional-3/#contents-of
+                    let media = parse_media_query_list(&self.tokens[prelude_start..prelude_end]);
+                    let mut block = CssParser {
+                        tokens: self.tokens[block_start..block_end].to_vec(),
+                        pos: 0,
+                    };
+                    let mut rules = block.consume_list_of_rules();
+                    for rule in rules.iter_mut() {
+                        rule.media.insert(0, media.clone());
+                    }
+                    return rules;
+                }
+                CssToken::OpenParen => {
+                    self.consume_block_contents(CssToken::CloseParen);
+                }
+                CssToken::OpenSquare => {
+                    self.consume_block_contents(CssToken::CloseSquare);
+                }
+                CssToken::Function(_) => {
+                    self.consume_block_contents(CssToken::CloseParen);
+                }
+                _ => {}
+            }
+        }
+        Vec::new()
+    }
+
+    /// Returns None if the rule is invalid or ends before its block.
+    /// https://www.w3.org/TR/css-syntax-3/#consume-qualified-rule
+    fn consume_qualified_rule(&mut self) -> Option<StyleRule> {
+        let start = self.pos;
+        loop {
+            match self.consume() {
+                // This is a parse error. Return nothing.
+                None => return None,
+                Some(CssToken::OpenCurly) => break,
+                Some(CssToken::OpenParen) | Some(CssToken::Function(_)) => {
+                    self.consume_block_contents(CssToken::CloseParen);
+                }
+                Some(CssToken::OpenSquare) => {
+                    self.consume_block_contents(CssToken::CloseSquare);
+                }
+                Some(_) => {}
+            }
+        }
+        let prelude_end = self.pos - 1;
+
+        let block_start = self.pos;
+        let block_end = match self.consume_block_contents(CssToken::CloseCurly) {
+            true => self.pos - 1,
+            false => self.pos,
+        };
+
+        // A rule whose selector is invalid is ignored as a whole.
+        // https://www.w3.org/TR/selectors-4/#invalid
+        let selectors = parse_selector_list_tokens(&trim(&self.tokens[start..prelude_end]))?;
+        let declarations = consume_declaration_list(&self.tokens[block_start..block_end]);
+        Some(StyleRule::new(selectors, declarations))
+    }
+
+    /// Consumes tokens until `ending` at the same nesting level, or the end of the input. The
+    /// opening token is already consumed. Returns false if the input ended before `ending`.
+    /// https://www.w3.org/TR/css-syntax-3/#consume-simple-block
+    fn consume_block_contents(&mut self, ending: CssToken) -> bool {
+        while let Some(token) = self.consume() {
+            if token == ending {
+                return true;
+            }
+            let closed = match token {
+                CssToken::OpenCurly => self.consume_block_contents(CssToken::CloseCurly),
+                CssToken::OpenParen | CssToken::Function(_) => {
+                    self.consume_block_contents(CssToken::CloseParen)
+                }
+                CssToken::OpenSquare => self.consume_block_contents(CssToken::CloseSquare),
+                _ => true,
+            };
+            if !closed {
+                return false;
+            }
+        }
+        false
+    }
+}
+
+/// Parses a stylesheet.
+#[allow(dead_code)]
+pub fn parse_stylesheet(css: String) -> StyleSheet {
+    CssParser::new(CssTokenizer::new(css)).parse_stylesheet()
+}
+
+/// Parses a list of declarations such as `color: red; margin: 0`.
+#[allow(dead_code)]
+pub fn parse_declaration_list(css: String) -> Vec<Declaration> {
+    CssParser::new(CssTokenizer::new(css)).parse_declaration_list()
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#consume-list-of-declarations
+fn consume_declaration_list(tokens: &[CssToken]) -> Vec<Declaration> {
+    let mut declarations = Vec::new();
+    let mut pos = 0;
+
+    while pos < tokens.len() {
+        match tokens[pos] {
+            CssToken::Whitespace | CssToken::Semicolon => pos += 1,
+            _ => {
+                // Consume until a semicolon at the top level. At-rules and anything that doesn't
+                // start with an ident are parse errors and dropped.
+                let start = pos;
+                let end = find_declaration_end(tokens, pos);
+                if let CssToken::Ident(_) = tokens[start] {
+                    if let Some(d) = consume_declaration(&tokens[start..end]) {
+                        declarations.push(d);
+                    }
+                }
+                pos = end;
+            }
+        }
+    }
+
+    declarations
+}
+
+/// Returns the index of the semicolon ending the declaration starting at `start`, skipping
+/// nested blocks, or the end of `tokens`.
+fn find_declaration_end(tokens: &[CssToken], start: usize) -> usize {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(start) {
+        match token {
+            CssToken::OpenCurly
+            | CssToken::OpenParen
+            | CssToken::OpenSquare
+            | CssToken::Function(_) => depth += 1,
+            CssToken::CloseCurly | CssToken::CloseParen | CssToken::CloseSquare => {
+                if depth > 0 {
+                    depth -= 1;
+                }
+            }
+            CssToken::Semicolon if depth == 0 => return i,
+            _ => {}
+        }
+    }
+    tokens.len()
+}
+
+/// Returns None if the declaration is invalid.
+/// https://www.w3.org/TR/css-syntax-3/#consume-declaration
+fn consume_declaration(tokens: &[CssToken]) -> Option<Declaration> {
+    let name = match tokens.first() {
+        Some(CssToken::Ident(name)) => name,
+        _ => return None,
+    };
+
+    // While the next input token is a <whitespace-token>, consume the next input token. If the
+    // next input token is anything other than a <colon-token>, this is a parse error. Return
+    // nothing.
+    let mut pos = 1;
+    while tokens.get(pos) == Some(&CssToken::Whitespace) {
+        pos += 1;
+    }
+    if tokens.get(pos) != Some(&CssToken::Colon) {
+        return None;
+    }
+
+    let mut value = trim(&tokens[pos + 1..]);
+
+    // If the last two non-<whitespace-token>s in the declaration's value are a <delim-token> with
+    // the value "!" followed by an <ident-token> with a value that is an ASCII case-insensitive
+    // match for "important", remove them from the declaration's value and set the declaration's
+    // important flag to true.
+    let mut important = false;
+    let non_whitespace: Vec<usize> = (0..value.len())
+        .filter(|i| value[*i] != CssToken::Whitespace)
+        .collect();
+    if non_whitespace.len() >= 2 {
+        let bang = non_whitespace[non_whitespace.len() - 2];
+        let last = non_whitespace[non_whitespace.len() - 1];
+        if let (CssToken::Delim('!'), CssToken::Ident(ident)) = (&value[bang], &value[last]) {
+            if ident.eq_ignore_ascii_case("important") {
+                important = true;
+                value = trim(&value[..bang]);
+            }
+        }
+    }
+
+    if value.is_empty() {
+        return None;
+    }
+
+    Some(Declaration::new(name, value, important))
+}
+
+/// Returns the tokens without the leading and trailing whitespace tokens.
+fn trim(tokens: &[CssToken]) -> Vec<CssToken> {
+    let start = tokens
+        .iter()
+        .position(|t| *t != CssToken::Whitespace)
+        .unwrap_or(tokens.len());
+    let end = tokens
+        .iter()
+        .rposition(|t| *t != CssToken::Whitespace)
+        .map_or(start, |i| i + 1);
+    tokens[start..end].to_vec()
+}
@@ -0,0 +1,323 @@
+//! This is a part of Media Queries Level 4.
+//! https://www.w3.org/TR/mediaqueries-4/
+
+use crate::parser::css::tokenizer::*;
+use crate::style::computed::{ColorScheme, Viewport};
+use crate::style::values::*;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A comma-separated list of media queries such as `screen and (max-width: 600px), print`. It
+/// matches if any of the queries matches. An empty list matches all.
+/// https://www.w3.org/TR/mediaqueries-4/#media-query-list
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MediaQueryList {
+    queries: Vec<MediaQuery>,
+}
+
+#[allow(dead_code)]
+impl MediaQueryList {
+    pub fn queries(&self) -> &Vec<MediaQuery> {
+        &self.queries
+    }
+
+    /// Returns true if the list matches `viewport`.
+    /// https://www.w3.org/TR/mediaqueries-4/#mq-list
+    pub fn matches(&self, viewport: Viewport) -> bool {
+        self.queries.is_empty() || self.queries.iter().any(|q| q.matches(viewport))
+    }
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#media-query
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaQuery {
+    /// True if the query starts with `not`.
+    negated: bool,
+    media_type: MediaType,
+    /// The features joined with `and`.
+    features: Vec<MediaFeature>,
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#media-types
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MediaType {
+    All,
+    Screen,
+    Print,
+    /// An unknown media type, which matches nothing.
+    Unknown,
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#mq-range-context
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Range {
+    Min,
+    Max,
+    Exact,
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#media-feature
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaFeature {
+    /// https://www.w3.org/TR/mediaqueries-4/#width
+    Width(Range, Length),
+    /// https://www.w3.org/TR/mediaqueries-4/#height
+    Height(Range, Length),
+    /// A feature in the boolean context such as `(width)`, which matches if the value is not
+    /// zero.
+    /// https://www.w3.org/TR/mediaqueries-4/#mq-boolean-context
+    NonZeroWidth,
+    NonZeroHeight,
+    /// https://www.w3.org/TR/mediaqueries-5/#prefers-color-scheme
+    PrefersColorScheme(ColorScheme),
+    /// An unknown feature, which matches nothing.
+    Unknown,
+}
+
+#[allow(dead_code)]
+impl MediaQuery {
+    /// The result of a malformed media query, which matches nothing.
+    /// https://www.w3.org/TR/mediaqueries-4/#error-handling
+    fn not_all() -> Self {
+        Self {
+            negated: true,
+            media_type: MediaType::All,
+            features: Vec::new(),
+        }
+    }
+
+    pub fn negated(&self) -> bool {
+        self.negated
+    }
+
+    pub fn media_type(&self) -> MediaType {
+        self.media_type
+    }
+
+    pub fn features(&self) -> &Vec<MediaFeature> {
+        &self.features
+    }
+
+    /// Returns true if the query matches a screen shown as `viewport`.
+    pub fn matches(&self, viewport: Viewport) -> bool {
+        let type_matches = match self.media_type {
+            MediaType::All | MediaType::Screen => true,
+            MediaType::Print | MediaType::Unknown => false,
+        };
+        let result = type_matches && self.features.iter().all(|f| f.matches(viewport));
+        result != self.negated
+    }
+}
+
+impl MediaFeature {
+    fn matches(&self, viewport: Viewport) -> bool {
+        match self {
+            MediaFeature::Width(range, value) => compare(viewport.width, *range, *value),
+            MediaFeature::Height(range, value) => compare(viewport.height, *range, *value),
+            MediaFeature::NonZeroWidth => viewport.width != 0.0,
+            MediaFeature::NonZeroHeight => viewport.height != 0.0,
+            MediaFeature::PrefersColorScheme(scheme) => viewport.color_scheme == *scheme,
+            MediaFeature::Unknown => false,
+        }
+    }
+}
+
+/// Compares the size of the viewport with the value of a feature. Relative lengths in media
+/// queries refer to the initial font size.
+/// https://www.w3.org/TR/mediaqueries-4/#units
+fn compare(actual: f64, range: Range, value: Length) -> bool {
+    let value = value
+        .absolutize(DEFAULT_FONT_SIZE, DEFAULT_FONT_SIZE)
+        .to_px(0.0);
+    match range {
+        Range::Min => actual >= value,
+        Range::Max => actual <= value,
+        Range::Exact => actual == value,
+    }
+}
+
+/// A part of a media query, which is a keyword or a parenthesized feature.
+enum Item {
+    Ident(String),
+    Block(Vec<CssToken>),
+}
+
+/// Parses the prelude of an `@media` rule. A malformed query is replaced with `not all`, and the
+/// other queries in the list are still valid.
+/// https://www.w3.org/TR/mediaqueries-4/#mq-syntax
+pub fn parse_media_query_list(tokens: &[CssToken]) -> MediaQueryList {
+    let mut queries = Vec::new();
+    let mut start = 0;
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            CssToken::OpenParen | CssToken::Function(_) | CssToken::OpenSquare => depth += 1,
+            CssToken::CloseParen | CssToken::CloseSquare if depth > 0 => depth -= 1,
+            CssToken::Comma if depth == 0 => {
+                queries.push(parse_media_query(&tokens[start..i]));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = &tokens[start..];
+    // An empty prelude is an empty list, which matches all.
+    if !queries.is_empty() || last.iter().any(|t| *t != CssToken::Whitespace) {
+        queries.push(parse_media_query(last));
+    }
+    MediaQueryList { queries }
+}
+
+/// https://www.w3.org/TR/mediaqueries-4/#typedef-media-query
+fn parse_media_query(tokens: &[CssToken]) -> MediaQuery {
+    let items = match split_items(tokens) {
+        Some(items) => items,
+        None => return MediaQuery::not_all(),
+    };
+
+    let mut query = MediaQuery {
+        negated: false,
+        media_type: MediaType::All,
+        features: Vec::new(),
+    };
+    let mut pos = 0;
+
+    // [ not | only ]? <media-type> [ and <media-condition-without-or> ]?
+    if let Some(Item::Ident(keyword)) = items.get(pos) {
+        let keyword = keyword.to_ascii_lowercase();
+        if keyword == "not" || keyword == "only" {
+            query.negated = keyword == "not";
+            pos += 1;
+            match items.get(pos) {
+                Some(Item::Ident(_)) => {}
+                _ => return MediaQuery::not_all(),
+            }
+        }
+    }
+    if let Some(Item::Ident(media_type)) = items.get(pos) {
+        query.media_type = match media_type.to_ascii_lowercase().as_str() {
+            "all" => MediaType::All,
+            "screen" => MediaType::Screen,
+            "print" => MediaType::Print,
+            // The keywords are reserved and not valid as a media type.
+            "and" | "or" | "not" | "only" => return MediaQuery::not_all(),
+            _ => MediaType::Unknown,
+        };
+        pos += 1;
+        if pos == items.len() {
+            return query;
+        }
+        match items.get(pos) {
+            Some(Item::Ident(and)) if and.eq_ignore_ascii_case("and") => pos += 1,
+            _ => return MediaQuery::not_all(),
+        }
+    }
+
+    // <media-in-parens> [ and <media-in-parens> ]*
+    loop {
+        match items.get(pos) {
+            Some(Item::Block(block)) => query.features.push(parse_media_feature(block)),
+            _ => return MediaQuery::not_all(),
+        }
+        pos += 1;
+        match items.get(pos) {
+            None => return query,
+            Some(Item::Ident(and)) if and.eq_ignore_ascii_case("and") => pos += 1,
+            _ => return MediaQuery::not_all(),
+        }
+    }
+}
+
+/// Splits a media query into keywords and parenthesized blocks. Returns None if there is
+/// anything else.
+fn split_items(tokens: &[CssToken]) -> Option<Vec<Item>> {
+    let mut items = Vec::new();
+    let mut pos = 0;
+    while pos < tokens.len() {
+        match &tokens[pos] {
+            CssToken::Whitespace => pos += 1,
+            CssToken::Ident(s) => {
+                items.push(Item::Ident(s.clone()));
+                pos += 1;
+            }
+            CssToken::OpenParen => {
+                let mut depth = 0;
+                let start = pos + 1;
+                loop {
+                    match tokens.get(pos) {
+                        Some(CssToken::OpenParen) | Some(CssToken::Function(_)) => depth += 1,
+                        Some(CssToken::CloseParen) => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        Some(_) => {}
+                        // The block is not closed.
+                        None => return None,
+                    }
+                    pos += 1;
+                }
+                items.push(Item::Block(tokens[start..pos].to_vec()));
+                pos += 1;
+            }
+            _ => return None,
+        }
+    }
+    if items.is_empty() {
+        return None;
+    }
+    Some(items)
+}
+
+/// Parses the contents of `( ... )` in a media query.
+/// https://www.w3.org/TR/mediaqueries-4/#typedef-media-feature
+fn parse_media_feature(block: &[CssToken]) -> MediaFeature {
+    let tokens: Vec<&CssToken> = block
+        .iter()
+        .filter(|t| **t != CssToken::Whitespace)
+        .collect();
+    let name = match tokens.first() {
+        Some(CssToken::Ident(name)) => name.to_ascii_lowercase(),
+        _ => return MediaFeature::Unknown,
+    };
+
+    if tokens.len() == 1 {
+        return match name.as_str() {
+            "width" => MediaFeature::NonZeroWidth,
+            "height" => MediaFeature::NonZeroHeight,
+            _ => MediaFeature::Unknown,
+        };
+    }
+
+    if tokens.len() != 3 || *tokens[1] != CssToken::Colon {
+        return MediaFeature::Unknown;
+    }
+    if name == "prefers-color-scheme" {
+        return match tokens[2] {
+            CssToken::Ident(v) => match ColorScheme::parse(v) {
+                Some(scheme) => MediaFeature::PrefersColorScheme(scheme),
+                None => MediaFeature::Unknown,
+            },
+            _ => MediaFeature::Unknown,
+        };
+    }
+    let value = match parse_length(&[tokens[2].clone()]) {
+        Some(Length::Percentage(_)) | None => return MediaFeature::Unknown,
+        Some(l) => l,
+    };
+
+    let (range, name) = if let Some(n) = name.strip_prefix("min-") {
+        (Range::Min, n)
+    } else if let Some(n) = name.strip_prefix("max-") {
+        (Range::Max, n)
+    } else {
+        (Range::Exact, name.as_str())
+    };
+    match name {
+        "width" => MediaFeature::Width(range, value),
+        "height" => MediaFeature::Height(range, value),
+        _ => MediaFeature::Unknown,
+    }
+}
@@ -0,0 +1,486 @@
+//! This is a part of "Selectors Level 4".
+//! https://www.w3.org/TR/selectors-4/
+
+use crate::parser::css::tokenizer::*;
+use crate::parser::dom::*;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// https://www.w3.org/TR/selectors-4/#simple
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimpleSelector {
+    /// https://www.w3.org/TR/selectors-4/#universal-selector
+    Universal,
+    /// https://www.w3.org/TR/selectors-4/#type-selectors
+    Type(String),
+    /// https://www.w3.org/TR/selectors-4/#id-selectors
+    Id(String),
+    /// https://www.w3.org/TR/selectors-4/#class-html
+    Class(String),
+    /// https://www.w3.org/TR/selectors-4/#attribute-selectors
+    Attribute {
+        /// The attribute name in lowercase.
+        name: String,
+        /// The operator and the value. None for `[name]`.
+        matcher: Option<(AttributeMatcher, String)>,
+        /// True if the value is compared ASCII case-insensitively, i.e. `[name=value i]`.
+        case_insensitive: bool,
+    },
+    /// https://www.w3.org/TR/selectors-4/#pseudo-classes
+    PseudoClass(PseudoClass),
+}
+
+/// https://www.w3.org/TR/selectors-4/#pseudo-classes
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PseudoClass {
+    /// https://www.w3.org/TR/selectors-4/#first-child-pseudo
+    FirstChild,
+    /// https://www.w3.org/TR/selectors-4/#last-child-pseudo
+    LastChild,
+    /// https://www.w3.org/TR/selectors-4/#link
+    Link,
+    /// No link is treated as visited, so this never matches. It's supported so that a rule for
+    /// `a:link, a:visited` is not dropped as invalid.
+    /// https://www.w3.org/TR/selectors-4/#visited-pseudo
+    Visited,
+}
+
+/// https://www.w3.org/TR/selectors-4/#attribute-representation
+/// https://www.w3.org/TR/selectors-4/#attribute-substrings
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AttributeMatcher {
+    /// `[name=value]`
+    Equal,
+    /// `[name~=value]`: one of the whitespace-separated words is `value`.
+    Includes,
+    /// `[name|=value]`: `value` or `value` followed by "-".
+    DashMatch,
+    /// `[name^=value]`
+    Prefix,
+    /// `[name$=value]`
+    Suffix,
+    /// `[name*=value]`
+    Substring,
+}
+
+/// A sequence of simple selectors that are not separated by a combinator, e.g. `div.a#b`.
+/// https://www.w3.org/TR/selectors-4/#compound
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompoundSelector {
+    selectors: Vec<SimpleSelector>,
+}
+
+#[allow(dead_code)]
+impl CompoundSelector {
+    pub fn selectors(&self) -> &Vec<SimpleSelector> {
+        &self.selectors
+    }
+}
+
+/// https://www.w3.org/TR/selectors-4/#combinators
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Combinator {
+    /// https://www.w3.org/TR/selectors-4/#descendant-combinators
+    Descendant,
+    /// https://www.w3.org/TR/selectors-4/#child-combinators
+    Child,
+}
+
+/// A sequence of compound selectors separated by combinators, e.g. `div.a > p span`.
+/// https://www.w3.org/TR/selectors-4/#complex
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComplexSelector {
+    compounds: Vec<CompoundSelector>,
+    /// `combinators[i]` is the combinator between `compounds[i]` and `compounds[i + 1]`.
+    combinators: Vec<Combinator>,
+}
+
+#[allow(dead_code)]
+impl ComplexSelector {
+    pub fn compounds(&self) -> &Vec<CompoundSelector> {
+        &self.compounds
+    }
+
+    pub fn combinators(&self) -> &Vec<Combinator> {
+        &self.combinators
+    }
+
+    /// https://www.w3.org/TR/selectors-4/#specificity-rules
+    pub fn specificity(&self) -> Specificity {
+        let mut specificity = Specificity(0, 0, 0);
+        for s in self.compounds.iter().flat_map(|c| c.selectors.iter()) {
+            match s {
+                SimpleSelector::Id(_) => specificity.0 += 1,
+                SimpleSelector::Class(_)
+                | SimpleSelector::Attribute { .. }
+                | SimpleSelector::PseudoClass(_) => specificity.1 += 1,
+                SimpleSelector::Type(_) => specificity.2 += 1,
+                SimpleSelector::Universal => {}
+            }
+        }
+        specificity
+    }
+}
+
+/// The number of ID selectors, the number of class selectors, attribute selectors and
+/// pseudo-classes, and the number of type selectors and pseudo-elements. Specificities are
+/// compared lexicographically.
+/// https://www.w3.org/TR/selectors-4/#specificity
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Specificity(pub u32, pub u32, pub u32);
+
+/// A comma-separated list of complex selectors.
+/// https://www.w3.org/TR/selectors-4/#selector-list
+pub type SelectorList = Vec<ComplexSelector>;
+
+/// Parses a selector list. Returns None if `input` is not a valid selector list.
+/// https://www.w3.org/TR/selectors-4/#parse-a-selector
+pub fn parse_selector_list(input: &str) -> Option<SelectorList> {
+    let tokens: Vec<CssToken> = CssTokenizer::new(String::from(input)).collect();
+    parse_selector_list_tokens(&tokens)
+}
+
+/// Parses a selector list from CSS tokens, e.g. the prelude of a style rule.
+pub fn parse_selector_list_tokens(tokens: &[CssToken]) -> Option<SelectorList> {
+    let mut list = Vec::new();
+    for selector in tokens.split(|t| *t == CssToken::Comma) {
+        list.push(parse_complex_selector(selector)?);
+    }
+    Some(list)
+}
+
+fn parse_complex_selector(tokens: &[CssToken]) -> Option<ComplexSelector> {
+    let mut pos = 0;
+    let mut compounds = Vec::new();
+    let mut combinators = Vec::new();
+
+    loop {
+        skip_whitespace(tokens, &mut pos);
+        compounds.push(parse_compound_selector(tokens, &mut pos)?);
+
+        let had_whitespace = skip_whitespace(tokens, &mut pos);
+        if pos >= tokens.len() {
+            break;
+        }
+
+        if tokens[pos] == CssToken::Delim('>') {
+            pos += 1;
+            combinators.push(Combinator::Child);
+        } else if had_whitespace {
+            combinators.push(Combinator::Descendant);
+        } else {
+            return None;
+        }
+    }
+
+    Some(ComplexSelector {
+        compounds,
+        combinators,
+    })
+}
+
+fn parse_compound_selector(tokens: &[CssToken], pos: &mut usize) -> Option<CompoundSelector> {
+    let mut selectors = Vec::new();
+
+    // A type selector or a universal selector can only come first.
+    match tokens.get(*pos) {
+        Some(CssToken::Delim('*')) => {
+            *pos += 1;
+            selectors.push(SimpleSelector::Universal);
+        }
+        Some(CssToken::Ident(name)) => {
+            *pos += 1;
+            selectors.push(SimpleSelector::Type(name.to_ascii_lowercase()));
+        }
+        _ => {}
+    }
+
+    loop {
+        match tokens.get(*pos) {
+            Some(CssToken::Hash { value, is_id: true }) => {
+                *pos += 1;
+                selectors.push(SimpleSelector::Id(value.clone()));
+            }
+            Some(CssToken::Delim('.')) => match tokens.get(*pos + 1) {
+                Some(CssToken::Ident(name)) => {
+                    *pos += 2;
+                    selectors.push(SimpleSelector::Class(name.clone()));
+                }
+                _ => return None,
+            },
+            Some(CssToken::OpenSquare) => {
+                *pos += 1;
+                let start = *pos;
+                while tokens.get(*pos) != Some(&CssToken::CloseSquare) {
+                    if *pos >= tokens.len() {
+                        return None;
+                    }
+                    *pos += 1;
+                }
+                selectors.push(parse_attribute_selector(&tokens[start..*pos])?);
+                *pos += 1;
+            }
+            // A pseudo-element such as `::before` is not supported and makes the selector
+            // invalid.
+            Some(CssToken::Colon) => match tokens.get(*pos + 1) {
+                Some(CssToken::Ident(name)) => {
+                    *pos += 2;
+                    selectors.push(SimpleSelector::PseudoClass(parse_pseudo_class(name)?));
+                }
+                _ => return None,
+            },
+            _ => break,
+        }
+    }
+
+    if selectors.is_empty() {
+        return None;
+    }
+    Some(CompoundSelector { selectors })
+}
+
+/// Parses the tokens between `[` and `]`.
+/// https://www.w3.org/TR/selectors-4/#attribute-selectors
+fn parse_attribute_selector(tokens: &[CssToken]) -> Option<SimpleSelector> {
+    let mut pos = 0;
+    skip_whitespace(tokens, &mut pos);
+    let name = match tokens.get(pos) {
+        Some(CssToken::Ident(name)) => name.to_ascii_lowercase(),
+        _ => return None,
+    };
+    pos += 1;
+    skip_whitespace(tokens, &mut pos);
+
+    if pos >= tokens.len() {
+        return Some(SimpleSelector::Attribute {
+            name,
+            matcher: None,
+            case_insensitive: false,
+        });
+    }
+
+    let operator = match tokens.get(pos) {
+        Some(CssToken::Delim('=')) => AttributeMatcher::Equal,
+        Some(CssToken::Delim(c)) => {
+            if tokens.get(pos + 1) != Some(&CssToken::Delim('=')) {
+                return None;
+            }
+            pos += 1;
+            match c {
+                '~' => AttributeMatcher::Includes,
+                '|' => AttributeMatcher::DashMatch,
+                '^' => AttributeMatcher::Prefix,
+                '$' => AttributeMatcher::Suffix,
+                '*' => AttributeMatcher::Substring,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    pos += 1;
+    skip_whitespace(tokens, &mut pos);
+
+    let value = match tokens.get(pos) {
+        Some(CssToken::Ident(v)) | Some(CssToken::String(v)) => v.clone(),
+        _ => return None,
+    };
+    pos += 1;
+    skip_whitespace(tokens, &mut pos);
+
+    let mut case_insensitive = false;
+    match tokens.get(pos) {
+        None => {}
+        Some(CssToken::Ident(flag)) if flag.eq_ignore_ascii_case("i") => {
+            case_insensitive = true;
+            pos += 1;
+        }
+        Some(CssToken::Ident(flag)) if flag.eq_ignore_ascii_case("s") => pos += 1,
+        _ => return None,
+    }
+    skip_whitespace(tokens, &mut pos);
+    if pos < tokens.len() {
+        return None;
+    }
+
+    Some(SimpleSelector::Attribute {
+        name,
+        matcher: Some((operator, value)),
+        case_insensitive,
+    })
+}
+
+/// Returns None for an unsupported pseudo-class.
+fn parse_pseudo_class(name: &str) -> Option<PseudoClass> {
+    match name.to_ascii_lowercase().as_str() {
+        "first-child" => Some(PseudoClass::FirstChild),
+        "last-child" => Some(PseudoClass::LastChild),
+        "link" => Some(PseudoClass::Link),
+        "visited" => Some(PseudoClass::Visited),
+        _ => None,
+    }
+}
+
+/// Skips whitespace tokens and returns true if any was skipped.
+fn skip_whitespace(tokens: &[CssToken], pos: &mut usize) -> bool {
+    let start = *pos;
+    while tokens.get(*pos) == Some(&CssToken::Whitespace) {
+        *pos += 1;
+    }
+    *pos > start
+}
+
+/// Returns true if the element `id` matches any selector in `list`.
+/// https://www.w3.org/TR/selectors-4/#match-a-selector-against-an-element
+pub fn matches_selector_list(document: &Document, id: NodeId, list: &[ComplexSelector]) -> bool {
+    list.iter().any(|s| matches_complex(document, id, s))
+}
+
+/// Returns the highest specificity among the selectors in `list` that match the element `id`, or
+/// None if none of them matches.
+pub fn matching_specificity(
+    document: &Document,
+    id: NodeId,
+    list: &[ComplexSelector],
+) -> Option<Specificity> {
+    list.iter()
+        .filter(|s| matches_complex(document, id, s))
+        .map(|s| s.specificity())
+        .max()
+}
+
+/// Returns true if the element `id` matches `selector`.
+pub fn matches_complex(document: &Document, id: NodeId, selector: &ComplexSelector) -> bool {
+    matches_from(document, id, selector, selector.compounds.len() - 1)
+}
+
+/// Matches the compound selectors of `selector` up to `index` from right to left. `id` is the
+/// element which should match `selector.compounds[index]`.
+fn matches_from(document: &Document, id: NodeId, selector: &ComplexSelector, index: usize) -> bool {
+    let element = match document.node(id).element() {
+        Some(e) => e,
+        None => return false,
+    };
+    if !matches_compound(document, id, element, &selector.compounds[index]) {
+        return false;
+    }
+    if index == 0 {
+        return true;
+    }
+
+    let mut ancestor = document.node(id).parent();
+    match selector.combinators[index - 1] {
+        Combinator::Child => match ancestor {
+            Some(parent) => matches_from(document, parent, selector, index - 1),
+            None => false,
+        },
+        Combinator::Descendant => {
+            while let Some(a) = ancestor {
+                if matches_from(document, a, selector, index - 1) {
+                    return true;
+                }
+                ancestor = document.node(a).parent();
+            }
+            false
+        }
+    }
+}
+
+fn matches_compound(
+    document: &Document,
+    id: NodeId,
+    element: &Element,
+    compound: &CompoundSelector,
+) -> bool {
+    compound.selectors.iter().all(|s| match s {
+        SimpleSelector::Universal => true,
+        SimpleSelector::Type(name) => element.tag_name() == name,
+        SimpleSelector::Id(id) => element.get_attribute("id").as_deref() == Some(id.as_str()),
+        SimpleSelector::Class(class) => element.has_class(class),
+        SimpleSelector::Attribute {
+            name,
+            matcher,
+            case_insensitive,
+        } => match element.get_attribute(name) {
+            Some(actual) => match matcher {
+                Some((operator, value)) => {
+                    matches_attribute(*operator, &actual, value, *case_insensitive)
+                }
+                None => true,
+            },
+            None => false,
+        },
+        SimpleSelector::PseudoClass(pseudo_class) => {
+            matches_pseudo_class(document, id, element, *pseudo_class)
+        }
+    })
+}
+
+fn matches_pseudo_class(
+    document: &Document,
+    id: NodeId,
+    element: &Element,
+    pseudo_class: PseudoClass,
+) -> bool {
+    let is_element = |n: NodeId| document.node(n).element().is_some();
+    match pseudo_class {
+        // "The :first-child pseudo-class represents an element that is first among its inclusive
+        // siblings."
+        PseudoClass::FirstChild => {
+            let mut sibling = document.node(id).previous_sibling();
+            while let Some(s) = sibling {
+                if is_element(s) {
+                    return false;
+                }
+                sibling = document.node(s).previous_sibling();
+            }
+            true
+        }
+        PseudoClass::LastChild => {
+            let mut sibling = document.node(id).next_sibling();
+            while let Some(s) = sibling {
+                if is_element(s) {
+                    return false;
+                }
+                sibling = document.node(s).next_sibling();
+            }
+            true
+        }
+        // "All a elements that have an href attribute, and all area elements that have an href
+        // attribute, must match one of :link and :visited."
+        // https://html.spec.whatwg.org/multipage/semantics-other.html#selector-link
+        PseudoClass::Link => {
+            matches!(element.tag_name(), "a" | "area") && element.get_attribute("href").is_some()
+        }
+        PseudoClass::Visited => false,
+    }
+}
+
+/// https://www.w3.org/TR/selectors-4/#attribute-representation
+fn matches_attribute(
+    operator: AttributeMatcher,
+    actual: &str,
+    value: &str,
+    case_insensitive: bool,
+) -> bool {
+    let (actual, value) = if case_insensitive {
+        (actual.to_ascii_lowercase(), value.to_ascii_lowercase())
+    } else {
+        (String::from(actual), String::from(value))
+    };
+
+    match operator {
+        AttributeMatcher::Equal => actual == value,
+        AttributeMatcher::Includes => {
+            !value.is_empty() && actual.split_ascii_whitespace().any(|w| w == value)
+        }
+        AttributeMatcher::DashMatch => {
+            actual == value
+                || (actual.starts_with(&value) && actual[value.len()..].starts_with('-'))
+        }
+        // Selectors with an empty value for these operators represent nothing.
+        AttributeMatcher::Prefix => !value.is_empty() && actual.starts_with(&value),
+        AttributeMatcher::Suffix => !value.is_empty() && actual.ends_with(&value),
+        AttributeMatcher::Substring => !value.is_empty() && actual.contains(&value),
+    }
+}
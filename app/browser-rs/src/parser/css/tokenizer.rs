@@ -0,0 +1,519 @@
+//! This is a part of "4. Tokenization" in the CSS Syntax Module Level 3.
+//! https://www.w3.org/TR/css-syntax-3/#tokenization
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::iter::Iterator;
+#[allow(unused_imports)]
+use liumlib::*;
+
+/// https://www.w3.org/TR/css-syntax-3/#tokenization
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssToken {
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-ident-token
+    Ident(String),
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-function-token
+    Function(String),
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-at-keyword-token
+    AtKeyword(String),
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-hash-token
+    /// `is_id` is true if the type flag is "id", i.e. the value would be a valid identifier.
+    Hash {
+        value: String,
+        is_id: bool,
+    },
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-string-token
+    String(String),
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-bad-string-token
+    BadString,
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-url-token
+    Url(String),
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-bad-url-token
+    BadUrl,
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-delim-token
+    Delim(char),
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-number-token
+    Number(f64),
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-percentage-token
+    Percentage(f64),
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-dimension-token
+    Dimension(f64, String),
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-whitespace-token
+    Whitespace,
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-cdo-token
+    Cdo,
+    /// https://www.w3.org/TR/css-syntax-3/#typedef-cdc-token
+    Cdc,
+    Colon,
+    Semicolon,
+    Comma,
+    OpenSquare,
+    CloseSquare,
+    OpenParen,
+    CloseParen,
+    OpenCurly,
+    CloseCurly,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CssTokenizer {
+    pos: usize,
+    input: Vec<char>,
+}
+
+impl CssTokenizer {
+    pub fn new(css: String) -> Self {
+        Self {
+            pos: 0,
+            input: preprocess(&css),
+        }
+    }
+
+    /// Returns the code point `n` code points after the next input code point without consuming
+    /// anything. Returns None past the end of the input.
+    fn peek(&self, n: usize) -> Option<char> {
+        self.input.get(self.pos + n).cloned()
+    }
+
+    fn consume(&mut self) -> Option<char> {
+        let c = self.peek(0);
+        if c.is_some() {
+            self.pos += 1;
+        }
+        c
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-comments
+    fn consume_comments(&mut self) {
+        while self.peek(0) == Some('/') && self.peek(1) == Some('*') {
+            self.pos += 2;
+            loop {
+                match self.consume() {
+                    Some('*') if self.peek(0) == Some('/') => {
+                        self.pos += 1;
+                        break;
+                    }
+                    Some(_) => {}
+                    // Parse error. Return.
+                    None => return,
+                }
+            }
+        }
+    }
+
+    fn consume_whitespace(&mut self) {
+        while let Some(c) = self.peek(0) {
+            if !is_whitespace(c) {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Returns true if the two code points starting from `n` code points after the next input
+    /// code point are a valid escape.
+    /// https://www.w3.org/TR/css-syntax-3/#starts-with-a-valid-escape
+    fn is_valid_escape(&self, n: usize) -> bool {
+        self.peek(n) == Some('\\') && self.peek(n + 1) != Some('\n')
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#would-start-an-identifier
+    fn would_start_identifier(&self, n: usize) -> bool {
+        match self.peek(n) {
+            Some('-') => match self.peek(n + 1) {
+                Some(c) if is_name_start(c) || c == '-' => true,
+                _ => self.is_valid_escape(n + 1),
+            },
+            Some(c) if is_name_start(c) => true,
+            Some('\\') => self.is_valid_escape(n),
+            _ => false,
+        }
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#starts-with-a-number
+    fn would_start_number(&self, n: usize) -> bool {
+        let is_digit = |c: Option<char>| c.is_some_and(|c| c.is_ascii_digit());
+        match self.peek(n) {
+            Some('+') | Some('-') => {
+                is_digit(self.peek(n + 1))
+                    || (self.peek(n + 1) == Some('.') && is_digit(self.peek(n + 2)))
+            }
+            Some('.') => is_digit(self.peek(n + 1)),
+            c => is_digit(c),
+        }
+    }
+
+    /// Consumes an escaped code point. The backslash is already consumed.
+    /// https://www.w3.org/TR/css-syntax-3/#consume-escaped-code-point
+    fn consume_escaped_code_point(&mut self) -> char {
+        let c = match self.consume() {
+            Some(c) => c,
+            // Parse error. Return U+FFFD REPLACEMENT CHARACTER (�).
+            None => return '\u{fffd}',
+        };
+
+        if !c.is_ascii_hexdigit() {
+            return c;
+        }
+
+        // Consume as many hex digits as possible, but no more than 5. Note that this means 1-6
+        // hex digits have been consumed in total.
+        let mut value = c.to_digit(16).unwrap();
+        for _ in 0..5 {
+            match self.peek(0).and_then(|c| c.to_digit(16)) {
+                Some(d) => {
+                    value = value * 16 + d;
+                    self.pos += 1;
+                }
+                None => break,
+            }
+        }
+        // If the next input code point is whitespace, consume it as well.
+        if self.peek(0).is_some_and(is_whitespace) {
+            self.pos += 1;
+        }
+
+        // If this number is zero, or is for a surrogate, or is greater than the maximum allowed
+        // code point, return U+FFFD REPLACEMENT CHARACTER (�).
+        match core::char::from_u32(value) {
+            Some(c) if value != 0 => c,
+            _ => '\u{fffd}',
+        }
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-name
+    fn consume_name(&mut self) -> String {
+        let mut name = String::new();
+        loop {
+            match self.peek(0) {
+                Some(c) if is_name(c) => {
+                    name.push(c);
+                    self.pos += 1;
+                }
+                Some('\\') if self.is_valid_escape(0) => {
+                    self.pos += 1;
+                    name.push(self.consume_escaped_code_point());
+                }
+                _ => return name,
+            }
+        }
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-number
+    fn consume_number(&mut self) -> f64 {
+        let mut repr = String::new();
+
+        if let Some(c) = self.peek(0) {
+            if c == '+' || c == '-' {
+                repr.push(c);
+                self.pos += 1;
+            }
+        }
+        self.consume_digits(&mut repr);
+
+        if self.peek(0) == Some('.') && self.peek(1).is_some_and(|c| c.is_ascii_digit()) {
+            repr.push('.');
+            self.pos += 1;
+            self.consume_digits(&mut repr);
+        }
+
+        if let Some('e') | Some('E') = self.peek(0) {
+            let sign = match self.peek(1) {
+                Some('+') | Some('-') => 1,
+                _ => 0,
+            };
+            if self.peek(1 + sign).is_some_and(|c| c.is_ascii_digit()) {
+                repr.push('e');
+                if sign == 1 {
+                    repr.push(self.peek(1).unwrap());
+                }
+                self.pos += 1 + sign;
+                self.consume_digits(&mut repr);
+            }
+        }
+
+        repr.parse::<f64>().unwrap_or(0.0)
+    }
+
+    fn consume_digits(&mut self, repr: &mut String) {
+        while let Some(c) = self.peek(0) {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            repr.push(c);
+            self.pos += 1;
+        }
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-numeric-token
+    fn consume_numeric_token(&mut self) -> CssToken {
+        let value = self.consume_number();
+
+        if self.would_start_identifier(0) {
+            return CssToken::Dimension(value, self.consume_name());
+        }
+
+        if self.peek(0) == Some('%') {
+            self.pos += 1;
+            return CssToken::Percentage(value);
+        }
+
+        CssToken::Number(value)
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-ident-like-token
+    fn consume_ident_like_token(&mut self) -> CssToken {
+        let name = self.consume_name();
+
+        if name.eq_ignore_ascii_case("url") && self.peek(0) == Some('(') {
+            self.pos += 1;
+            // While the next two input code points are whitespace, consume the next input code
+            // point.
+            while self.peek(0).is_some_and(is_whitespace) && self.peek(1).is_some_and(is_whitespace)
+            {
+                self.pos += 1;
+            }
+
+            let quote = |c: Option<char>| c == Some('"') || c == Some('\'');
+            if quote(self.peek(0))
+                || (self.peek(0).is_some_and(is_whitespace) && quote(self.peek(1)))
+            {
+                return CssToken::Function(name);
+            }
+            return self.consume_url_token();
+        }
+
+        if self.peek(0) == Some('(') {
+            self.pos += 1;
+            return CssToken::Function(name);
+        }
+
+        CssToken::Ident(name)
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-string-token
+    fn consume_string_token(&mut self, ending: char) -> CssToken {
+        let mut s = String::new();
+        loop {
+            match self.consume() {
+                Some(c) if c == ending => return CssToken::String(s),
+                // Parse error. Return the string token.
+                None => return CssToken::String(s),
+                Some('\n') => {
+                    // Parse error. Reconsume the current input code point, create a
+                    // <bad-string-token>, and return it.
+                    self.pos -= 1;
+                    return CssToken::BadString;
+                }
+                Some('\\') => match self.peek(0) {
+                    None => {}
+                    Some('\n') => self.pos += 1,
+                    Some(_) => s.push(self.consume_escaped_code_point()),
+                },
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-url-token
+    fn consume_url_token(&mut self) -> CssToken {
+        let mut url = String::new();
+        self.consume_whitespace();
+
+        loop {
+            match self.consume() {
+                Some(')') => return CssToken::Url(url),
+                // Parse error. Return the <url-token>.
+                None => return CssToken::Url(url),
+                Some(c) if is_whitespace(c) => {
+                    self.consume_whitespace();
+                    match self.peek(0) {
+                        Some(')') => {
+                            self.pos += 1;
+                            return CssToken::Url(url);
+                        }
+                        None => return CssToken::Url(url),
+                        _ => {
+                            self.consume_bad_url_remnants();
+                            return CssToken::BadUrl;
+                        }
+                    }
+                }
+                Some('"') | Some('\'') | Some('(') => {
+                    // Parse error.
+                    self.consume_bad_url_remnants();
+                    return CssToken::BadUrl;
+                }
+                Some(c) if is_non_printable(c) => {
+                    // Parse error.
+                    self.consume_bad_url_remnants();
+                    return CssToken::BadUrl;
+                }
+                Some('\\') => {
+                    if self.peek(0) != Some('\n') {
+                        url.push(self.consume_escaped_code_point());
+                    } else {
+                        // Parse error.
+                        self.consume_bad_url_remnants();
+                        return CssToken::BadUrl;
+                    }
+                }
+                Some(c) => url.push(c),
+            }
+        }
+    }
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-remnants-of-bad-url
+    fn consume_bad_url_remnants(&mut self) {
+        loop {
+            match self.consume() {
+                Some(')') | None => return,
+                Some('\\') if self.peek(0) != Some('\n') => {
+                    self.consume_escaped_code_point();
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Iterator for CssTokenizer {
+    type Item = CssToken;
+
+    /// https://www.w3.org/TR/css-syntax-3/#consume-token
+    fn next(&mut self) -> Option<Self::Item> {
+        self.consume_comments();
+
+        let c = self.consume()?;
+        let token = match c {
+            c if is_whitespace(c) => {
+                self.consume_whitespace();
+                CssToken::Whitespace
+            }
+            '"' | '\'' => self.consume_string_token(c),
+            '#' => {
+                if self.peek(0).is_some_and(is_name) || self.is_valid_escape(0) {
+                    let is_id = self.would_start_identifier(0);
+                    CssToken::Hash {
+                        value: self.consume_name(),
+                        is_id,
+                    }
+                } else {
+                    CssToken::Delim(c)
+                }
+            }
+            '(' => CssToken::OpenParen,
+            ')' => CssToken::CloseParen,
+            '+' | '.' => {
+                self.pos -= 1;
+                if self.would_start_number(0) {
+                    self.consume_numeric_token()
+                } else {
+                    self.pos += 1;
+                    CssToken::Delim(c)
+                }
+            }
+            ',' => CssToken::Comma,
+            '-' => {
+                self.pos -= 1;
+                if self.would_start_number(0) {
+                    self.consume_numeric_token()
+                } else if self.peek(1) == Some('-') && self.peek(2) == Some('>') {
+                    self.pos += 3;
+                    CssToken::Cdc
+                } else if self.would_start_identifier(0) {
+                    self.consume_ident_like_token()
+                } else {
+                    self.pos += 1;
+                    CssToken::Delim(c)
+                }
+            }
+            ':' => CssToken::Colon,
+            ';' => CssToken::Semicolon,
+            '<' => {
+                if self.peek(0) == Some('!')
+                    && self.peek(1) == Some('-')
+                    && self.peek(2) == Some('-')
+                {
+                    self.pos += 3;
+                    CssToken::Cdo
+                } else {
+                    CssToken::Delim(c)
+                }
+            }
+            '@' => {
+                if self.would_start_identifier(0) {
+                    CssToken::AtKeyword(self.consume_name())
+                } else {
+                    CssToken::Delim(c)
+                }
+            }
+            '[' => CssToken::OpenSquare,
+            ']' => CssToken::CloseSquare,
+            '{' => CssToken::OpenCurly,
+            '}' => CssToken::CloseCurly,
+            '\\' => {
+                self.pos -= 1;
+                if self.is_valid_escape(0) {
+                    self.consume_ident_like_token()
+                } else {
+                    // Parse error.
+                    self.pos += 1;
+                    CssToken::Delim(c)
+                }
+            }
+            c if c.is_ascii_digit() => {
+                self.pos -= 1;
+                self.consume_numeric_token()
+            }
+            c if is_name_start(c) => {
+                self.pos -= 1;
+                self.consume_ident_like_token()
+            }
+            _ => CssToken::Delim(c),
+        };
+
+        Some(token)
+    }
+}
+
+/// Replaces CR, FF and CR LF with LF, and NULL with U+FFFD REPLACEMENT CHARACTER (�).
+/// https://www.w3.org/TR/css-syntax-3/#input-preprocessing
+fn preprocess(css: &str) -> Vec<char> {
+    let mut chars = Vec::new();
+    let mut iter = css.chars().peekable();
+    while let Some(c) = iter.next() {
+        match c {
+            '\r' => {
+                if iter.peek() == Some(&'\n') {
+                    iter.next();
+                }
+                chars.push('\n');
+            }
+            '\u{c}' => chars.push('\n'),
+            '\0' => chars.push('\u{fffd}'),
+            _ => chars.push(c),
+        }
+    }
+    chars
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#whitespace
+fn is_whitespace(c: char) -> bool {
+    c == '\n' || c == '\t' || c == ' '
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#ident-start-code-point
+fn is_name_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || !c.is_ascii() || c == '_'
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#ident-code-point
+fn is_name(c: char) -> bool {
+    is_name_start(c) || c.is_ascii_digit() || c == '-'
+}
+
+/// https://www.w3.org/TR/css-syntax-3/#non-printable-code-point
+fn is_non_printable(c: char) -> bool {
+    c <= '\u{8}' || c == '\u{b}' || ('\u{e}'..='\u{1f}').contains(&c) || c == '\u{7f}'
+}
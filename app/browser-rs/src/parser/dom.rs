@@ -1,25 +1,41 @@
 //! This is a part of "13.2.6 Tree construction" in the HTML spec.
 //! https://html.spec.whatwg.org/multipage/parsing.html#tree-construction
 
+use crate::image::Image;
+use crate::parser::css::cssom::*;
+use crate::parser::css::selector::*;
 use crate::parser::tokenizer::*;
+use crate::timing::nanos_since;
+use crate::url::ParsedUrl;
+use liumlib::time::Instant;
 #[allow(unused_imports)]
 use liumlib::*;
 
-use alloc::rc::{Rc, Weak};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::rc::Rc;
 use alloc::string::String;
+use alloc::vec;
 use alloc::vec::Vec;
 use core::cell::RefCell;
+use core::fmt;
+
+/// A handle to a node in a `Document`. This is an index into the node arena owned by the document,
+/// so it's only meaningful together with the document that created it. The index is a u32 to keep
+/// the links in `Node` small.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NodeId(u32);
 
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 /// https://dom.spec.whatwg.org/#interface-node
 pub struct Node {
     pub kind: NodeKind,
-    pub parent: Option<Weak<RefCell<Node>>>,
-    pub first_child: Option<Rc<RefCell<Node>>>,
-    pub last_child: Option<Weak<RefCell<Node>>>,
-    pub previous_sibling: Option<Weak<RefCell<Node>>>,
-    pub next_sibling: Option<Rc<RefCell<Node>>>,
+    parent: Option<NodeId>,
+    first_child: Option<NodeId>,
+    last_child: Option<NodeId>,
+    previous_sibling: Option<NodeId>,
+    next_sibling: Option<NodeId>,
 }
 
 #[allow(dead_code)]
@@ -36,48 +52,1239 @@ impl Node {
         }
     }
 
-    pub fn first_child(&self) -> Option<Rc<RefCell<Node>>> {
-        self.first_child.as_ref().map(|n| n.clone())
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+
+    pub fn first_child(&self) -> Option<NodeId> {
+        self.first_child
+    }
+
+    pub fn last_child(&self) -> Option<NodeId> {
+        self.last_child
+    }
+
+    pub fn previous_sibling(&self) -> Option<NodeId> {
+        self.previous_sibling
+    }
+
+    pub fn next_sibling(&self) -> Option<NodeId> {
+        self.next_sibling
+    }
+
+    /// Returns the element if this node is an element.
+    pub fn element(&self) -> Option<&Element> {
+        match self.kind {
+            NodeKind::Element(ref e) => Some(e),
+            _ => None,
+        }
+    }
+
+    pub fn element_mut(&mut self) -> Option<&mut Element> {
+        match self.kind {
+            NodeKind::Element(ref mut e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Returns the element kind if this node is an element.
+    pub fn element_kind(&self) -> Option<ElementKind> {
+        self.element().map(|e| e.kind())
+    }
+}
+
+/// A DOM tree. All the nodes are owned by the document in an arena and refer to each other with
+/// `NodeId`s. A node removed from the tree stays in the arena until it's freed by `free()`, after
+/// which a new node reuses its slot, so the arena doesn't grow when the same content is replaced
+/// again and again.
+/// https://dom.spec.whatwg.org/#interface-document
+#[derive(Debug, Clone)]
+pub struct Document {
+    nodes: Vec<Node>,
+    /// The slots of the freed nodes, which `create_node()` reuses.
+    free_slots: Vec<NodeId>,
+    /// The nodes which something outside the document refers to, e.g. the objects of scripts, so
+    /// they're never freed.
+    pinned: RefCell<BTreeSet<NodeId>>,
+    observers: MutationObservers,
+    /// https://dom.spec.whatwg.org/#concept-document-mode
+    quirks_mode: QuirksMode,
+    /// The stylesheets of the style elements in tree order.
+    /// https://www.w3.org/TR/cssom-1/#documentorshadowroot-document-or-shadow-root-css-style-sheets
+    style_sheets: Vec<StyleSheet>,
+    /// The decoded images of the img elements which are available.
+    /// https://html.spec.whatwg.org/multipage/images.html#img-available
+    images: BTreeMap<NodeId, Rc<Image>>,
+    scripting: ScriptingSlot,
+}
+
+/// https://dom.spec.whatwg.org/#concept-document-quirks
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum QuirksMode {
+    NoQuirks,
+    LimitedQuirks,
+    Quirks,
+}
+
+/// A change made to the tree of a `Document`.
+/// https://dom.spec.whatwg.org/#interface-mutationrecord
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mutation {
+    /// `child` was inserted into `parent`.
+    ChildInserted { parent: NodeId, child: NodeId },
+    /// `child` was removed from `parent`.
+    ChildRemoved { parent: NodeId, child: NodeId },
+    /// The attribute `name` of `element` was set.
+    AttributeChanged { element: NodeId, name: String },
+}
+
+/// A callback which is called with the document after each mutation. It can't modify the
+/// document; it's meant to record what should be recomputed later.
+pub type MutationCallback = Rc<dyn Fn(&Document, &Mutation)>;
+
+/// The scripting of a document, e.g. a JavaScript engine, which runs its scripts and the
+/// listeners of its events. The document is lent to each call, and the scripts may change it.
+/// https://html.spec.whatwg.org/multipage/webappapis.html#enabling-and-disabling-scripting
+pub trait Scripting {
+    /// Runs the script element `id` when the parser completes it.
+    /// https://html.spec.whatwg.org/multipage/scripting.html#execute-the-script-element
+    fn run_script_element(&mut self, document: &mut Document, id: NodeId);
+
+    /// Dispatches an event of `event_type` which bubbles and can be canceled, e.g. a click, to
+    /// `target`. Returns false if a listener cancels it.
+    /// https://dom.spec.whatwg.org/#concept-event-dispatch
+    fn dispatch_event(&mut self, document: &mut Document, target: NodeId, event_type: &str)
+        -> bool;
+
+    /// Returns true if a task of the scripts is due, e.g. the callback of a timer.
+    fn has_due_tasks(&self) -> bool;
+
+    /// Returns the milliseconds until the next task is due, which is 0 if one is due, or None if
+    /// no task is waiting.
+    fn until_next_task(&self) -> Option<u64>;
+
+    /// Runs the tasks which are due.
+    /// https://html.spec.whatwg.org/multipage/webappapis.html#event-loop-processing-model
+    fn run_due_tasks(&mut self, document: &mut Document);
+
+    /// Returns the URL which the scripts asked to navigate to, e.g. by setting `location.href`,
+    /// and forgets it. The last one wins if they asked more than once.
+    /// https://html.spec.whatwg.org/multipage/browsing-the-web.html#navigate
+    fn take_navigation(&mut self) -> Option<ParsedUrl>;
+}
+
+/// The scripting shared by a document and its parser.
+pub type ScriptingRef = Rc<RefCell<dyn Scripting>>;
+
+/// A handle returned by `Document::add_mutation_observer()`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ObserverId(usize);
+
+/// The registry of mutation callbacks. Unlike the DOM's MutationObserver, callbacks are called
+/// synchronously and always observe the whole document.
+/// https://dom.spec.whatwg.org/#interface-mutationobserver
+#[derive(Clone, Default)]
+struct MutationObservers {
+    /// Removed observers leave None so that `ObserverId`s stay valid.
+    callbacks: Vec<Option<MutationCallback>>,
+}
+
+/// The scripting of a document, which is None if scripting is disabled.
+/// https://html.spec.whatwg.org/multipage/webappapis.html#concept-n-noscript
+#[derive(Clone, Default)]
+struct ScriptingSlot(Option<ScriptingRef>);
+
+impl fmt::Debug for ScriptingSlot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = if self.0.is_some() {
+            "enabled"
+        } else {
+            "disabled"
+        };
+        write!(f, "Scripting({})", state)
+    }
+}
+
+impl fmt::Debug for MutationObservers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let count = self.callbacks.iter().filter(|c| c.is_some()).count();
+        write!(f, "MutationObservers({})", count)
+    }
+}
+
+#[allow(dead_code)]
+impl Document {
+    /// Creates a document which only has the Document node.
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node::new(NodeKind::Document)],
+            free_slots: Vec::new(),
+            pinned: RefCell::new(BTreeSet::new()),
+            observers: MutationObservers::default(),
+            quirks_mode: QuirksMode::NoQuirks,
+            style_sheets: Vec::new(),
+            images: BTreeMap::new(),
+            scripting: ScriptingSlot::default(),
+        }
+    }
+
+    /// Returns the scripting of the document, or None if scripting is disabled.
+    pub fn scripting(&self) -> Option<ScriptingRef> {
+        self.scripting.0.clone()
+    }
+
+    /// Enables scripting with `scripting`, which runs the scripts and the events of the document.
+    pub fn set_scripting(&mut self, scripting: ScriptingRef) {
+        self.scripting.0 = Some(scripting);
+    }
+
+    /// Dispatches an event to `target` with the scripting like `Scripting::dispatch_event()`.
+    /// Returns true without listeners if scripting is disabled or a script is already running.
+    pub fn dispatch_event(&mut self, target: NodeId, event_type: &str) -> bool {
+        let scripting = match self.scripting() {
+            Some(scripting) => scripting,
+            None => return true,
+        };
+        let mut scripting = match scripting.try_borrow_mut() {
+            Ok(scripting) => scripting,
+            Err(_) => return true,
+        };
+        scripting.dispatch_event(self, target, event_type)
+    }
+
+    /// Returns the URL which the scripts asked to navigate to like
+    /// `Scripting::take_navigation()`, or None if scripting is disabled or a script is running.
+    pub fn take_navigation(&self) -> Option<ParsedUrl> {
+        self.scripting()?.try_borrow_mut().ok()?.take_navigation()
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#dom-documentorshadowroot-stylesheets
+    pub fn style_sheets(&self) -> &Vec<StyleSheet> {
+        &self.style_sheets
+    }
+
+    pub fn style_sheets_mut(&mut self) -> &mut Vec<StyleSheet> {
+        &mut self.style_sheets
+    }
+
+    /// https://www.w3.org/TR/cssom-1/#add-a-css-style-sheet
+    pub fn add_style_sheet(&mut self, sheet: StyleSheet) {
+        self.style_sheets.push(sheet);
+    }
+
+    /// Returns the image of the img element `id` if it's available.
+    pub fn image(&self, id: NodeId) -> Option<&Rc<Image>> {
+        self.images.get(&id)
+    }
+
+    pub fn set_image(&mut self, id: NodeId, image: Rc<Image>) {
+        self.images.insert(id, image);
+    }
+
+    /// https://dom.spec.whatwg.org/#concept-document-mode
+    pub fn quirks_mode(&self) -> QuirksMode {
+        self.quirks_mode
+    }
+
+    pub fn set_quirks_mode(&mut self, mode: QuirksMode) {
+        self.quirks_mode = mode;
+    }
+
+    /// Registers `callback` to be called after every mutation of the tree through this document.
+    pub fn add_mutation_observer(&mut self, callback: MutationCallback) -> ObserverId {
+        self.observers.callbacks.push(Some(callback));
+        ObserverId(self.observers.callbacks.len() - 1)
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-mutationobserver-disconnect
+    pub fn remove_mutation_observer(&mut self, id: ObserverId) {
+        if let Some(c) = self.observers.callbacks.get_mut(id.0) {
+            *c = None;
+        }
+    }
+
+    fn notify(&self, mutation: Mutation) {
+        for callback in self.observers.callbacks.iter().flatten() {
+            callback(self, &mutation);
+        }
+    }
+
+    /// Returns the Document node, the root of the tree.
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    /// Creates a node which doesn't belong to the tree yet.
+    pub fn create_node(&mut self, kind: NodeKind) -> NodeId {
+        if let Some(id) = self.free_slots.pop() {
+            self.nodes[id.0 as usize] = Node::new(kind);
+            return id;
+        }
+        self.nodes.push(Node::new(kind));
+        NodeId((self.nodes.len() - 1) as u32)
+    }
+
+    /// Keeps `id` from being freed, because something outside the document refers to it, e.g.
+    /// the object of the node in scripts.
+    pub fn pin(&self, id: NodeId) {
+        self.pinned.borrow_mut().insert(id);
+    }
+
+    /// Frees `id`, which must not belong to the tree, and its descendants, so that new nodes reuse
+    /// their slots and their `NodeId`s must not be used anymore. Nothing is freed if any of them
+    /// is pinned. Returns whether they're freed.
+    pub fn free(&mut self, id: NodeId) -> bool {
+        assert!(
+            self.node(id).parent.is_none(),
+            "`id` should be removed from the tree"
+        );
+        let mut subtree: Vec<NodeId> = self.descendants(id).collect();
+        subtree.push(id);
+        if subtree.iter().any(|n| self.pinned.borrow().contains(n)) {
+            return false;
+        }
+        for n in subtree {
+            self.images.remove(&n);
+            // The placeholder drops the data of the node.
+            self.nodes[n.0 as usize] = Node::new(NodeKind::Text(String::new()));
+            self.free_slots.push(n);
+        }
+        true
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-document-createelement
+    pub fn create_element(&mut self, element: Element) -> NodeId {
+        self.create_node(NodeKind::Element(element))
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-document-createtextnode
+    pub fn create_text(&mut self, data: &str) -> NodeId {
+        self.create_node(NodeKind::Text(String::from(data)))
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id.0 as usize]
+    }
+
+    /// Changes made through the returned node are not reported to mutation observers.
+    pub fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        &mut self.nodes[id.0 as usize]
+    }
+
+    /// Returns the number of the slots in the arena, including the nodes removed from the tree and
+    /// the freed slots.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Appends `child` as the last child of `parent`. If `child` already belongs to a tree, it's
+    /// removed from there first.
+    /// https://dom.spec.whatwg.org/#dom-node-appendchild
+    pub fn append_child(&mut self, parent: NodeId, child: NodeId) {
+        self.insert_before(parent, child, None);
+    }
+
+    /// Inserts `child` into `parent` just before `reference`. Appends it if `reference` is None.
+    /// https://dom.spec.whatwg.org/#dom-node-insertbefore
+    pub fn insert_before(&mut self, parent: NodeId, child: NodeId, reference: Option<NodeId>) {
+        if let Some(r) = reference {
+            assert_eq!(
+                self.node(r).parent,
+                Some(parent),
+                "`reference` should be a child of `parent`"
+            );
+        }
+
+        if let Some(old_parent) = self.node(child).parent {
+            self.remove_child(old_parent, child);
+        }
+
+        let previous = match reference {
+            Some(r) => self.node(r).previous_sibling,
+            None => self.node(parent).last_child,
+        };
+
+        {
+            let c = self.node_mut(child);
+            c.parent = Some(parent);
+            c.previous_sibling = previous;
+            c.next_sibling = reference;
+        }
+
+        match previous {
+            Some(p) => self.node_mut(p).next_sibling = Some(child),
+            None => self.node_mut(parent).first_child = Some(child),
+        }
+
+        match reference {
+            Some(r) => self.node_mut(r).previous_sibling = Some(child),
+            None => self.node_mut(parent).last_child = Some(child),
+        }
+
+        self.notify(Mutation::ChildInserted { parent, child });
+    }
+
+    /// Detaches `child` from `parent` and clears all links of `child` except its own children.
+    /// https://dom.spec.whatwg.org/#dom-node-removechild
+    pub fn remove_child(&mut self, parent: NodeId, child: NodeId) {
+        assert_eq!(
+            self.node(child).parent,
+            Some(parent),
+            "`child` should be a child of `parent`"
+        );
+
+        let previous = self.node(child).previous_sibling;
+        let next = self.node(child).next_sibling;
+
+        match previous {
+            Some(p) => self.node_mut(p).next_sibling = next,
+            None => self.node_mut(parent).first_child = next,
+        }
+
+        match next {
+            Some(n) => self.node_mut(n).previous_sibling = previous,
+            None => self.node_mut(parent).last_child = previous,
+        }
+
+        let c = self.node_mut(child);
+        c.parent = None;
+        c.previous_sibling = None;
+        c.next_sibling = None;
+
+        self.notify(Mutation::ChildRemoved { parent, child });
+    }
+
+    /// Sets the attribute of the element `id`. Does nothing if `id` is not an element.
+    /// https://dom.spec.whatwg.org/#dom-element-setattribute
+    pub fn set_attribute(&mut self, id: NodeId, name: &str, value: &str) {
+        match self.node_mut(id).element_mut() {
+            Some(e) => e.set_attribute(name, value),
+            None => return,
+        }
+
+        self.notify(Mutation::AttributeChanged {
+            element: id,
+            name: String::from(name),
+        });
+    }
+
+    /// Creates a copy of `id` which doesn't belong to the tree. If `deep` is true, the children are
+    /// cloned recursively and linked to the copy in the same order.
+    /// https://dom.spec.whatwg.org/#concept-node-clone
+    pub fn clone_node(&mut self, id: NodeId, deep: bool) -> NodeId {
+        let copy = self.create_node(self.node(id).kind.clone());
+
+        if deep {
+            let children: Vec<NodeId> = self.children(id).collect();
+            for child in children {
+                let child_copy = self.clone_node(child, true);
+                self.append_child(copy, child_copy);
+            }
+        }
+
+        copy
+    }
+
+    /// Copies `id` of `other` with its descendants into this document, and returns the copy,
+    /// which doesn't belong to the tree.
+    /// https://dom.spec.whatwg.org/#dom-document-importnode
+    pub fn import_node(&mut self, other: &Document, id: NodeId) -> NodeId {
+        let copy = self.create_node(other.node(id).kind.clone());
+        for child in other.children(id) {
+            let child_copy = self.import_node(other, child);
+            self.append_child(copy, child_copy);
+        }
+        copy
+    }
+
+    /// Replaces the children of the element `id` with `html` parsed in the context of the element.
+    /// The old children are freed unless they're pinned. The scripts in `html` don't run. Does
+    /// nothing if `id` is not an element.
+    /// https://html.spec.whatwg.org/multipage/dynamic-markup-insertion.html#dom-element-innerhtml
+    pub fn set_inner_html(&mut self, id: NodeId, html: &str) {
+        let context = match self.node(id).element() {
+            Some(e) => e.clone(),
+            None => return,
+        };
+        let (fragment_document, fragment) = Parser::parse_fragment(&context, String::from(html));
+        while let Some(child) = self.node(id).first_child() {
+            self.remove_child(id, child);
+            self.free(child);
+        }
+        for child in fragment_document.children(fragment) {
+            let copy = self.import_node(&fragment_document, child);
+            self.append_child(id, copy);
+        }
+    }
+
+    /// Removes empty Text nodes in the subtree of `id` and merges adjacent Text nodes into the
+    /// first one of them.
+    /// https://dom.spec.whatwg.org/#dom-node-normalize
+    pub fn normalize(&mut self, id: NodeId) {
+        let texts: Vec<NodeId> = self
+            .descendants(id)
+            .filter(|n| matches!(self.node(*n).kind, NodeKind::Text(_)))
+            .collect();
+
+        for text in texts {
+            // The node was already merged into a previous Text node.
+            let parent = match self.node(text).parent {
+                Some(p) => p,
+                None => continue,
+            };
+
+            // Replace the data with the concatenation of the data of all contiguous exclusive Text
+            // nodes, and remove them.
+            let mut data = self.text_content(text);
+            while let Some(next) = self.node(text).next_sibling {
+                match self.node(next).kind {
+                    NodeKind::Text(ref s) => data.push_str(s),
+                    _ => break,
+                }
+                self.remove_child(parent, next);
+            }
+
+            if data.is_empty() {
+                self.remove_child(parent, text);
+            } else {
+                self.node_mut(text).kind = NodeKind::Text(data);
+            }
+        }
+    }
+
+    /// Returns true if the element `id` matches `selectors`. Returns None if `selectors` is not a
+    /// valid selector list.
+    /// https://dom.spec.whatwg.org/#dom-element-matches
+    pub fn matches(&self, id: NodeId, selectors: &str) -> Option<bool> {
+        let list = parse_selector_list(selectors)?;
+        Some(matches_selector_list(self, id, &list))
+    }
+
+    /// Returns the first element among the descendants of `scope` that matches `selectors` in
+    /// tree order. Returns None if nothing matches or `selectors` is invalid.
+    /// https://dom.spec.whatwg.org/#dom-parentnode-queryselector
+    pub fn query_selector(&self, scope: NodeId, selectors: &str) -> Option<NodeId> {
+        let list = parse_selector_list(selectors)?;
+        self.descendants(scope)
+            .find(|n| matches_selector_list(self, *n, &list))
+    }
+
+    /// Returns all the elements among the descendants of `scope` that match `selectors` in tree
+    /// order. Returns None if `selectors` is not a valid selector list.
+    /// https://dom.spec.whatwg.org/#dom-parentnode-queryselectorall
+    pub fn query_selector_all(&self, scope: NodeId, selectors: &str) -> Option<Vec<NodeId>> {
+        let list = parse_selector_list(selectors)?;
+        Some(
+            self.descendants(scope)
+                .filter(|n| matches_selector_list(self, *n, &list))
+                .collect(),
+        )
+    }
+
+    /// Returns an iterator over the direct children of `id`.
+    /// https://dom.spec.whatwg.org/#concept-tree-child
+    pub fn children(&self, id: NodeId) -> Children<'_> {
+        Children {
+            document: self,
+            next: self.node(id).first_child,
+        }
+    }
+
+    /// Returns an iterator over all descendants of `id` in tree order (depth-first, pre-order).
+    /// `id` itself is not included.
+    /// https://dom.spec.whatwg.org/#concept-tree-descendant
+    pub fn descendants(&self, id: NodeId) -> Descendants<'_> {
+        Descendants {
+            document: self,
+            root: id,
+            next: self.node(id).first_child,
+        }
+    }
+
+    /// Returns the concatenation of the data of all the Text node descendants in tree order. Unlike
+    /// the spec, a Document node returns its descendants' text and a DocumentType node returns an
+    /// empty string instead of null.
+    /// https://dom.spec.whatwg.org/#dom-node-textcontent
+    pub fn text_content(&self, id: NodeId) -> String {
+        match self.node(id).kind {
+            NodeKind::Text(ref s) | NodeKind::Comment(ref s) => return s.clone(),
+            NodeKind::DocumentType { .. } => return String::new(),
+            _ => {}
+        }
+
+        let mut s = String::new();
+        for n in self.descendants(id) {
+            if let NodeKind::Text(ref t) = self.node(n).kind {
+                s.push_str(t);
+            }
+        }
+        s
+    }
+
+    /// Returns the text of the first title element in the tree with whitespace stripped and
+    /// collapsed. Returns an empty string if there's no title element.
+    /// https://html.spec.whatwg.org/multipage/dom.html#document.title
+    pub fn title(&self) -> String {
+        let title = self
+            .descendants(self.root())
+            .find(|n| self.node(*n).element_kind() == Some(ElementKind::Title));
+
+        let text = match title {
+            Some(t) => self.text_content(t),
+            None => return String::new(),
+        };
+
+        let mut s = String::new();
+        for word in text.split_ascii_whitespace() {
+            if !s.is_empty() {
+                s.push(' ');
+            }
+            s.push_str(word);
+        }
+        s
+    }
+
+    /// Returns the HTML serialization of the children of `id`.
+    /// https://html.spec.whatwg.org/multipage/dynamic-markup-insertion.html#dom-element-innerhtml
+    pub fn inner_html(&self, id: NodeId) -> String {
+        let mut s = String::new();
+        self.serialize_children(id, &mut s);
+        s
+    }
+
+    /// Returns the HTML serialization of `id` including itself.
+    /// https://w3c.github.io/DOM-Parsing/#dom-element-outerhtml
+    pub fn outer_html(&self, id: NodeId) -> String {
+        let mut s = String::new();
+        self.serialize_node(id, false, &mut s);
+        s
+    }
+
+    /// https://html.spec.whatwg.org/multipage/parsing.html#serialising-html-fragments
+    fn serialize_children(&self, id: NodeId, out: &mut String) {
+        // If the node is one of these elements, its text children are appended literally.
+        let raw_text = self.node(id).element().is_some_and(|e| {
+            matches!(
+                e.tag_name(),
+                "style" | "script" | "xmp" | "iframe" | "noembed" | "noframes" | "plaintext"
+            )
+        });
+
+        for child in self.children(id) {
+            self.serialize_node(child, raw_text, out);
+        }
+    }
+
+    fn serialize_node(&self, id: NodeId, raw_text: bool, out: &mut String) {
+        match self.node(id).kind {
+            NodeKind::Document | NodeKind::DocumentFragment => self.serialize_children(id, out),
+            NodeKind::Element(ref e) => {
+                out.push('<');
+                out.push_str(e.tag_name());
+                for a in e.attributes() {
+                    out.push(' ');
+                    out.push_str(a.name());
+                    out.push_str("=\"");
+                    escape_string(a.value(), /*attribute_mode*/ true, out);
+                    out.push('"');
+                }
+                out.push('>');
+
+                // If current node is a void element, then continue on to the next child node.
+                if e.kind().is_void() {
+                    return;
+                }
+
+                self.serialize_children(id, out);
+
+                out.push_str("</");
+                out.push_str(e.tag_name());
+                out.push('>');
+            }
+            NodeKind::Text(ref s) => {
+                if raw_text {
+                    out.push_str(s);
+                } else {
+                    escape_string(s, /*attribute_mode*/ false, out);
+                }
+            }
+            NodeKind::Comment(ref s) => {
+                out.push_str("<!--");
+                out.push_str(s);
+                out.push_str("-->");
+            }
+            NodeKind::DocumentType { ref name, .. } => {
+                out.push_str("<!DOCTYPE ");
+                out.push_str(name);
+                out.push('>');
+            }
+        }
+    }
+
+    /// Returns the subtree of `id` as JSON. Every node is an object with a "kind" and its
+    /// children, and elements also have a "tag" and "attributes" in source order, e.g.
+    /// `{"kind":"element","tag":"p","attributes":[["id","a"]],"children":[{"kind":"text","data":"x"}]}`.
+    pub fn to_json(&self, id: NodeId) -> String {
+        let mut s = String::new();
+        self.write_json(id, &mut s);
+        s
+    }
+
+    fn write_json(&self, id: NodeId, out: &mut String) {
+        out.push_str("{\"kind\":");
+        match self.node(id).kind {
+            NodeKind::Document => out.push_str("\"document\""),
+            NodeKind::DocumentFragment => out.push_str("\"document-fragment\""),
+            NodeKind::Element(ref e) => {
+                out.push_str("\"element\",\"tag\":");
+                write_json_string(e.tag_name(), out);
+                out.push_str(",\"attributes\":[");
+                for (i, a) in e.attributes().iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    out.push('[');
+                    write_json_string(a.name(), out);
+                    out.push(',');
+                    write_json_string(a.value(), out);
+                    out.push(']');
+                }
+                out.push(']');
+            }
+            NodeKind::Text(ref s) => {
+                out.push_str("\"text\",\"data\":");
+                write_json_string(s, out);
+            }
+            NodeKind::Comment(ref s) => {
+                out.push_str("\"comment\",\"data\":");
+                write_json_string(s, out);
+            }
+            NodeKind::DocumentType {
+                ref name,
+                ref public_id,
+                ref system_id,
+            } => {
+                out.push_str("\"doctype\",\"name\":");
+                write_json_string(name, out);
+                out.push_str(",\"public_id\":");
+                write_json_string(public_id, out);
+                out.push_str(",\"system_id\":");
+                write_json_string(system_id, out);
+            }
+        }
+
+        out.push_str(",\"children\":[");
+        for (i, child) in self.children(id).enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            self.write_json(child, out);
+        }
+        out.push_str("]}");
+    }
+
+    fn fmt_node(&self, id: NodeId, depth: usize, f: &mut fmt::Formatter) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        match self.node(id).kind {
+            NodeKind::Document => writeln!(f, "{}#document", indent)?,
+            NodeKind::DocumentFragment => writeln!(f, "{}#document-fragment", indent)?,
+            NodeKind::Element(ref e) => {
+                writeln!(f, "{}<{}>", indent, e.tag_name())?;
+                let mut attributes: Vec<&Attribute> = e.attributes().iter().collect();
+                attributes.sort_by(|a, b| a.name().cmp(b.name()));
+                for a in attributes {
+                    writeln!(f, "{}  {}={:?}", indent, a.name(), a.value())?;
+                }
+            }
+            NodeKind::Text(ref s) => writeln!(f, "{}{:?}", indent, s)?,
+            NodeKind::Comment(ref s) => writeln!(f, "{}<!-- {} -->", indent, s)?,
+            NodeKind::DocumentType {
+                ref name,
+                ref public_id,
+                ref system_id,
+            } => {
+                if public_id.is_empty() && system_id.is_empty() {
+                    writeln!(f, "{}<!DOCTYPE {}>", indent, name)?
+                } else {
+                    writeln!(
+                        f,
+                        "{}<!DOCTYPE {} {:?} {:?}>",
+                        indent, name, public_id, system_id
+                    )?
+                }
+            }
+        }
+        for child in self.children(id) {
+            self.fmt_node(child, depth + 1, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Prints the tree, one node per line indented by the depth. Elements are printed as `<tag>`
+/// followed by their attributes, and text is printed as a quoted string. This follows the format
+/// of the html5lib tree construction tests.
+/// https://github.com/html5lib/html5lib-tests/tree/master/tree-construction
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_node(self.root(), 0, f)
+    }
+}
+
+/// The public identifier prefixes that set the document to quirks mode.
+/// https://html.spec.whatwg.org/multipage/parsing.html#the-initial-insertion-mode
+const QUIRKS_PUBLIC_ID_PREFIXES: [&str; 55] = [
+    "+//silmaril//dtd html pro v0r11 19970101//",
+    "-//as//dtd html 3.0 aswedit + extensions//",
+    "-//advasoft ltd//dtd html 3.0 aswedit + extensions//",
+    "-//ietf//dtd html 2.0 level 1//",
+    "-//ietf//dtd html 2.0 level 2//",
+    "-//ietf//dtd html 2.0 strict level 1//",
+    "-//ietf//dtd html 2.0 strict level 2//",
+    "-//ietf//dtd html 2.0 strict//",
+    "-//ietf//dtd html 2.0//",
+    "-//ietf//dtd html 2.1e//",
+    "-//ietf//dtd html 3.0//",
+    "-//ietf//dtd html 3.2 final//",
+    "-//ietf//dtd html 3.2//",
+    "-//ietf//dtd html 3//",
+    "-//ietf//dtd html level 0//",
+    "-//ietf//dtd html level 1//",
+    "-//ietf//dtd html level 2//",
+    "-//ietf//dtd html level 3//",
+    "-//ietf//dtd html strict level 0//",
+    "-//ietf//dtd html strict level 1//",
+    "-//ietf//dtd html strict level 2//",
+    "-//ietf//dtd html strict level 3//",
+    "-//ietf//dtd html strict//",
+    "-//ietf//dtd html//",
+    "-//metrius//dtd metrius presentational//",
+    "-//microsoft//dtd internet explorer 2.0 html strict//",
+    "-//microsoft//dtd internet explorer 2.0 html//",
+    "-//microsoft//dtd internet explorer 2.0 tables//",
+    "-//microsoft//dtd internet explorer 3.0 html strict//",
+    "-//microsoft//dtd internet explorer 3.0 html//",
+    "-//microsoft//dtd internet explorer 3.0 tables//",
+    "-//netscape comm. corp.//dtd html//",
+    "-//netscape comm. corp.//dtd strict html//",
+    "-//o'reilly and associates//dtd html 2.0//",
+    "-//o'reilly and associates//dtd html extended 1.0//",
+    "-//o'reilly and associates//dtd html extended relaxed 1.0//",
+    "-//sq//dtd html 2.0 hotmetal + extensions//",
+    "-//softquad software//dtd hotmetal pro 6.0::19990601::extensions to html 4.0//",
+    "-//softquad//dtd hotmetal pro 4.0::19971010::extensions to html 4.0//",
+    "-//spyglass//dtd html 2.0 extended//",
+    "-//sun microsystems corp.//dtd hotjava html//",
+    "-//sun microsystems corp.//dtd hotjava strict html//",
+    "-//w3c//dtd html 3 1995-03-24//",
+    "-//w3c//dtd html 3.2 draft//",
+    "-//w3c//dtd html 3.2 final//",
+    "-//w3c//dtd html 3.2//",
+    "-//w3c//dtd html 3.2s draft//",
+    "-//w3c//dtd html 4.0 frameset//",
+    "-//w3c//dtd html 4.0 transitional//",
+    "-//w3c//dtd html experimental 19960712//",
+    "-//w3c//dtd html experimental 970421//",
+    "-//w3c//dtd w3 html//",
+    "-//w3o//dtd w3 html 3.0//",
+    "-//webtechs//dtd mozilla html 2.0//",
+    "-//webtechs//dtd mozilla html//",
+];
+
+/// Returns the mode the document should be set to for a DOCTYPE token.
+/// https://html.spec.whatwg.org/multipage/parsing.html#the-initial-insertion-mode
+fn quirks_mode_for_doctype(
+    name: Option<&str>,
+    public_id: Option<&str>,
+    system_id: Option<&str>,
+    force_quirks: bool,
+) -> QuirksMode {
+    // The identifiers are compared ASCII case-insensitively.
+    let public = public_id.map(|p| p.to_ascii_lowercase());
+    let system = system_id.map(|s| s.to_ascii_lowercase());
+    let public_starts_with = |prefix: &str| public.as_ref().is_some_and(|p| p.starts_with(prefix));
+
+    if force_quirks
+        || name != Some("html")
+        || public.as_deref() == Some("-//w3o//dtd w3 html strict 3.0//en//")
+        || public.as_deref() == Some("-/w3c/dtd html 4.0 transitional/en")
+        || public.as_deref() == Some("html")
+        || system.as_deref() == Some("http://www.ibm.com/data/dtd/v11/ibmxhtml1-transitional.dtd")
+        || QUIRKS_PUBLIC_ID_PREFIXES
+            .iter()
+            .any(|prefix| public_starts_with(prefix))
+        || (system.is_none()
+            && (public_starts_with("-//w3c//dtd html 4.01 frameset//")
+                || public_starts_with("-//w3c//dtd html 4.01 transitional//")))
+    {
+        return QuirksMode::Quirks;
+    }
+
+    if public_starts_with("-//w3c//dtd xhtml 1.0 frameset//")
+        || public_starts_with("-//w3c//dtd xhtml 1.0 transitional//")
+        || (system.is_some()
+            && (public_starts_with("-//w3c//dtd html 4.01 frameset//")
+                || public_starts_with("-//w3c//dtd html 4.01 transitional//")))
+    {
+        return QuirksMode::LimitedQuirks;
+    }
+
+    QuirksMode::NoQuirks
+}
+
+/// Writes `s` as a JSON string with quotes.
+/// https://www.rfc-editor.org/rfc/rfc8259#section-7
+fn write_json_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                out.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// https://html.spec.whatwg.org/multipage/parsing.html#escapingString
+fn escape_string(s: &str, attribute_mode: bool, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '\u{a0}' => out.push_str("&nbsp;"),
+            '"' if attribute_mode => out.push_str("&quot;"),
+            '<' if !attribute_mode => out.push_str("&lt;"),
+            '>' if !attribute_mode => out.push_str("&gt;"),
+            _ => out.push(c),
+        }
     }
+}
+
+/// An iterator returned by `Document::children()`.
+#[derive(Debug, Clone)]
+pub struct Children<'a> {
+    document: &'a Document,
+    next: Option<NodeId>,
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = NodeId;
 
-    pub fn last_child(&self) -> Option<Weak<RefCell<Node>>> {
-        self.last_child.as_ref().map(|n| n.clone())
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        self.next = self.document.node(current).next_sibling;
+        Some(current)
+    }
+}
+
+/// An iterator returned by `Document::descendants()`.
+#[derive(Debug, Clone)]
+pub struct Descendants<'a> {
+    document: &'a Document,
+    /// The node whose descendants are iterated. The iteration never leaves its subtree.
+    root: NodeId,
+    next: Option<NodeId>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+
+        // Find the node following `current` in tree order: its first child, or the next sibling
+        // of the nearest inclusive ancestor that has one, without leaving the subtree of `root`.
+        let node = self.document.node(current);
+        self.next = match node.first_child {
+            Some(child) => Some(child),
+            None => {
+                let mut n = current;
+                loop {
+                    if n == self.root {
+                        break None;
+                    }
+                    let node = self.document.node(n);
+                    if let Some(sibling) = node.next_sibling {
+                        break Some(sibling);
+                    }
+                    match node.parent {
+                        Some(p) => n = p,
+                        None => break None,
+                    }
+                }
+            }
+        };
+
+        Some(current)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+    /// https://dom.spec.whatwg.org/#interface-document
+    Document,
+    /// https://dom.spec.whatwg.org/#interface-documentfragment
+    #[allow(dead_code)]
+    DocumentFragment,
+    /// https://dom.spec.whatwg.org/#interface-element
+    Element(Element),
+    /// https://dom.spec.whatwg.org/#interface-text
+    Text(String),
+    /// https://dom.spec.whatwg.org/#interface-comment
+    Comment(String),
+    /// https://dom.spec.whatwg.org/#interface-documenttype
+    DocumentType {
+        name: String,
+        public_id: String,
+        system_id: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// https://dom.spec.whatwg.org/#interface-element
+pub struct Element {
+    kind: ElementKind,
+    /// https://dom.spec.whatwg.org/#concept-element-local-name
+    tag_name: String,
+    /// https://dom.spec.whatwg.org/#concept-element-attribute
+    attributes: Vec<Attribute>,
+    /// The value of a text control edited by the user, which replaces its default value, i.e.
+    /// the value attribute or the text of a textarea. None until it's edited.
+    /// https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#concept-fe-dirty
+    value: Option<String>,
+}
+
+#[allow(dead_code)]
+impl Element {
+    pub fn new(kind: ElementKind) -> Self {
+        Self {
+            kind,
+            tag_name: String::from(kind.tag_name()),
+            attributes: Vec::new(),
+            value: None,
+        }
+    }
+
+    /// Creates an element for the `tag` name. Tag names that aren't in `ElementKind` become
+    /// `ElementKind::Unknown` and keep their name.
+    pub fn from_tag(tag: &str, attributes: Vec<Attribute>) -> Self {
+        Self {
+            kind: ElementKind::from_tag(tag),
+            tag_name: String::from(tag),
+            attributes,
+            value: None,
+        }
+    }
+
+    pub fn kind(&self) -> ElementKind {
+        self.kind
+    }
+
+    pub fn tag_name(&self) -> &str {
+        &self.tag_name
+    }
+
+    pub fn attributes(&self) -> &Vec<Attribute> {
+        &self.attributes
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-element-getattribute
+    pub fn get_attribute(&self, name: &str) -> Option<String> {
+        self.attributes
+            .iter()
+            .find(|a| a.name() == name)
+            .map(|a| String::from(a.value()))
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-element-setattribute
+    pub fn set_attribute(&mut self, name: &str, value: &str) {
+        match self.attributes.iter_mut().find(|a| a.name() == name) {
+            Some(a) => a.set_value(String::from(value)),
+            None => self
+                .attributes
+                .push(Attribute::new(String::from(name), String::from(value))),
+        }
+    }
+
+    /// Returns the value edited by the user, or None if it's not edited.
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    pub fn set_value(&mut self, value: String) {
+        self.value = Some(value);
+    }
+
+    /// Returns the class names in the class attribute in order, without duplicates.
+    /// https://dom.spec.whatwg.org/#concept-ordered-set-parser
+    pub fn classes(&self) -> Vec<&str> {
+        let value = match self.attributes.iter().find(|a| a.name() == "class") {
+            Some(a) => a.value(),
+            None => return Vec::new(),
+        };
+
+        let mut classes: Vec<&str> = Vec::new();
+        for token in value.split_ascii_whitespace() {
+            if !classes.contains(&token) {
+                classes.push(token);
+            }
+        }
+        classes
+    }
+
+    /// Returns true if the class attribute contains `name`.
+    pub fn has_class(&self, name: &str) -> bool {
+        self.classes().contains(&name)
+    }
+
+    /// Returns the declarations in the style attribute. Invalid declarations are dropped.
+    /// https://drafts.csswg.org/cssom/#dom-elementcssinlinestyle-style
+    pub fn inline_style(&self) -> Vec<Declaration> {
+        match self.attributes.iter().find(|a| a.name() == "style") {
+            Some(a) => parse_declaration_list(String::from(a.value())),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the pairs of the name and the value of data-* attributes in order. The names have
+    /// "data-" removed and are converted to camel case, e.g. "data-foo-bar" becomes "fooBar".
+    /// https://html.spec.whatwg.org/multipage/dom.html#dom-dataset
+    pub fn dataset(&self) -> Vec<(String, String)> {
+        let mut dataset = Vec::new();
+        for a in &self.attributes {
+            // For each content attribute on the element whose first five characters are the string
+            // "data-" and whose remaining characters (if any) do not include any ASCII upper
+            // alphas, in the order that those attributes are listed in the element's attribute
+            // list, add a name-value pair to list whose name is the attribute's name with the
+            // first five characters removed and whose value is the attribute's value.
+            let name = match a.name().strip_prefix("data-") {
+                Some(n) => n,
+                None => continue,
+            };
+            if name.chars().any(|c| c.is_ascii_uppercase()) {
+                continue;
+            }
+
+            // For each name in list, for each U+002D HYPHEN-MINUS character (-) in the name that
+            // is followed by an ASCII lower alpha, remove the U+002D HYPHEN-MINUS character (-)
+            // and replace the character that followed it by the same character converted to ASCII
+            // uppercase.
+            let mut key = String::new();
+            let mut chars = name.chars().peekable();
+            while let Some(c) = chars.next() {
+                match chars.peek() {
+                    Some(next) if c == '-' && next.is_ascii_lowercase() => {
+                        key.push(next.to_ascii_uppercase());
+                        chars.next();
+                    }
+                    _ => key.push(c),
+                }
+            }
+
+            dataset.push((key, String::from(a.value())));
+        }
+        dataset
+    }
+
+    /// Returns the class names as a token list which updates the class attribute when it's
+    /// modified.
+    /// https://dom.spec.whatwg.org/#dom-element-classlist
+    pub fn class_list(&mut self) -> ClassList<'_> {
+        ClassList { element: self }
+    }
+}
+
+/// The token set of the class attribute. The set isn't stored anywhere else, so it's always
+/// parsed from the attribute and written back to it after each change.
+/// https://dom.spec.whatwg.org/#interface-domtokenlist
+#[derive(Debug)]
+pub struct ClassList<'a> {
+    element: &'a mut Element,
+}
+
+#[allow(dead_code)]
+impl<'a> ClassList<'a> {
+    /// https://dom.spec.whatwg.org/#dom-domtokenlist-length
+    pub fn len(&self) -> usize {
+        self.element.classes().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-domtokenlist-item
+    pub fn item(&self, index: usize) -> Option<String> {
+        self.element.classes().get(index).map(|c| String::from(*c))
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-domtokenlist-contains
+    pub fn contains(&self, token: &str) -> bool {
+        self.element.has_class(token)
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-domtokenlist-add
+    pub fn add(&mut self, token: &str) {
+        let mut tokens = self.tokens();
+        if !tokens.iter().any(|t| t == token) {
+            tokens.push(String::from(token));
+        }
+        self.update(tokens);
+    }
+
+    /// https://dom.spec.whatwg.org/#dom-domtokenlist-remove
+    pub fn remove(&mut self, token: &str) {
+        let mut tokens = self.tokens();
+        tokens.retain(|t| t != token);
+        self.update(tokens);
+    }
+
+    /// Removes `token` if it exists, otherwise adds it. Returns true if `token` is now present.
+    /// https://dom.spec.whatwg.org/#dom-domtokenlist-toggle
+    pub fn toggle(&mut self, token: &str) -> bool {
+        if self.contains(token) {
+            self.remove(token);
+            false
+        } else {
+            self.add(token);
+            true
+        }
     }
 
-    pub fn previous_sibling(&self) -> Option<Weak<RefCell<Node>>> {
-        self.previous_sibling.as_ref().map(|n| n.clone())
+    /// https://dom.spec.whatwg.org/#dom-domtokenlist-value
+    pub fn value(&self) -> String {
+        self.element.get_attribute("class").unwrap_or_default()
     }
 
-    pub fn next_sibling(&self) -> Option<Rc<RefCell<Node>>> {
-        self.next_sibling.as_ref().map(|n| n.clone())
+    fn tokens(&self) -> Vec<String> {
+        self.element
+            .classes()
+            .iter()
+            .map(|c| String::from(*c))
+            .collect()
     }
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum NodeKind {
-    /// https://dom.spec.whatwg.org/#interface-document
-    Document,
-    /// https://dom.spec.whatwg.org/#interface-element
-    Element(Element),
-    /// https://dom.spec.whatwg.org/#interface-text
-    Text(String),
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-/// https://dom.spec.whatwg.org/#interface-element
-pub struct Element {
-    kind: ElementKind,
-    //id: String,
-    //class_name: String,
-}
 
-impl Element {
-    pub fn new(kind: ElementKind) -> Self {
-        Self {
-            kind,
-            //id: String::new(),
-            //class_name: String::new(),
+    /// https://dom.spec.whatwg.org/#concept-dtl-update
+    fn update(&mut self, tokens: Vec<String>) {
+        // If the associated element does not have an associated attribute and token set is empty,
+        // then return.
+        if tokens.is_empty() && self.element.get_attribute("class").is_none() {
+            return;
         }
+
+        self.element.set_attribute("class", &tokens.join(" "));
     }
 }
 
@@ -89,8 +1296,315 @@ pub enum ElementKind {
     Html,
     /// https://html.spec.whatwg.org/multipage/semantics.html#the-head-element
     Head,
+    /// https://html.spec.whatwg.org/multipage/semantics.html#the-title-element
+    Title,
+    /// https://html.spec.whatwg.org/multipage/semantics.html#the-meta-element
+    Meta,
+    /// https://html.spec.whatwg.org/multipage/semantics.html#the-link-element
+    Link,
+    /// https://html.spec.whatwg.org/multipage/semantics.html#the-style-element
+    Style,
+    /// https://html.spec.whatwg.org/multipage/scripting.html#the-script-element
+    Script,
     /// https://html.spec.whatwg.org/multipage/sections.html#the-body-element
     Body,
+    /// https://html.spec.whatwg.org/multipage/sections.html#the-h1,-h2,-h3,-h4,-h5,-and-h6-elements
+    H1,
+    H2,
+    H3,
+    H4,
+    H5,
+    H6,
+    /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-p-element
+    P,
+    /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-hr-element
+    Hr,
+    /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-pre-element
+    Pre,
+    /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-blockquote-element
+    Blockquote,
+    /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-ol-element
+    Ol,
+    /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-ul-element
+    Ul,
+    /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-li-element
+    Li,
+    /// https://html.spec.whatwg.org/multipage/grouping-content.html#the-div-element
+    Div,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-a-element
+    A,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-em-element
+    Em,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-strong-element
+    Strong,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-code-element
+    Code,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-i-element
+    I,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-b-element
+    B,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-u-element
+    U,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-span-element
+    Span,
+    /// https://html.spec.whatwg.org/multipage/text-level-semantics.html#the-br-element
+    Br,
+    /// https://html.spec.whatwg.org/multipage/embedded-content.html#the-img-element
+    Img,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-table-element
+    Table,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-tbody-element
+    Tbody,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-thead-element
+    Thead,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-tr-element
+    Tr,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-td-element
+    Td,
+    /// https://html.spec.whatwg.org/multipage/tables.html#the-th-element
+    Th,
+    /// https://html.spec.whatwg.org/multipage/forms.html#the-form-element
+    Form,
+    /// https://html.spec.whatwg.org/multipage/input.html#the-input-element
+    Input,
+    /// https://html.spec.whatwg.org/multipage/form-elements.html#the-button-element
+    Button,
+    /// https://html.spec.whatwg.org/multipage/form-elements.html#the-textarea-element
+    Textarea,
+    /// https://html.spec.whatwg.org/multipage/dom.html#htmlunknownelement
+    Unknown,
+}
+
+#[allow(dead_code)]
+impl ElementKind {
+    const TAG_NAMES: [(&'static str, ElementKind); 42] = [
+        ("html", ElementKind::Html),
+        ("head", ElementKind::Head),
+        ("title", ElementKind::Title),
+        ("meta", ElementKind::Meta),
+        ("link", ElementKind::Link),
+        ("style", ElementKind::Style),
+        ("script", ElementKind::Script),
+        ("body", ElementKind::Body),
+        ("h1", ElementKind::H1),
+        ("h2", ElementKind::H2),
+        ("h3", ElementKind::H3),
+        ("h4", ElementKind::H4),
+        ("h5", ElementKind::H5),
+        ("h6", ElementKind::H6),
+        ("p", ElementKind::P),
+        ("hr", ElementKind::Hr),
+        ("pre", ElementKind::Pre),
+        ("blockquote", ElementKind::Blockquote),
+        ("ol", ElementKind::Ol),
+        ("ul", ElementKind::Ul),
+        ("li", ElementKind::Li),
+        ("div", ElementKind::Div),
+        ("a", ElementKind::A),
+        ("em", ElementKind::Em),
+        ("strong", ElementKind::Strong),
+        ("code", ElementKind::Code),
+        ("i", ElementKind::I),
+        ("b", ElementKind::B),
+        ("u", ElementKind::U),
+        ("span", ElementKind::Span),
+        ("br", ElementKind::Br),
+        ("img", ElementKind::Img),
+        ("table", ElementKind::Table),
+        ("tbody", ElementKind::Tbody),
+        ("thead", ElementKind::Thead),
+        ("tr", ElementKind::Tr),
+        ("td", ElementKind::Td),
+        ("th", ElementKind::Th),
+        ("form", ElementKind::Form),
+        ("input", ElementKind::Input),
+        ("button", ElementKind::Button),
+        ("textarea", ElementKind::Textarea),
+    ];
+
+    pub fn from_tag(tag: &str) -> Self {
+        for (name, kind) in Self::TAG_NAMES.iter() {
+            if *name == tag {
+                return *kind;
+            }
+        }
+        ElementKind::Unknown
+    }
+
+    /// Returns the tag name of the element kind. `ElementKind::Unknown` has an empty name.
+    pub fn tag_name(&self) -> &'static str {
+        for (name, kind) in Self::TAG_NAMES.iter() {
+            if kind == self {
+                return name;
+            }
+        }
+        ""
+    }
+
+    pub fn is_heading(&self) -> bool {
+        matches!(
+            self,
+            ElementKind::H1
+                | ElementKind::H2
+                | ElementKind::H3
+                | ElementKind::H4
+                | ElementKind::H5
+                | ElementKind::H6
+        )
+    }
+
+    /// https://html.spec.whatwg.org/multipage/syntax.html#void-elements
+    pub fn is_void(&self) -> bool {
+        matches!(
+            self,
+            ElementKind::Meta
+                | ElementKind::Link
+                | ElementKind::Hr
+                | ElementKind::Br
+                | ElementKind::Img
+                | ElementKind::Input
+        )
+    }
+}
+
+/// Returns true if the `tag` belongs to the special category.
+/// https://html.spec.whatwg.org/multipage/parsing.html#special
+fn is_special_tag(tag: &str) -> bool {
+    const SPECIAL_TAGS: [&str; 82] = [
+        "address",
+        "applet",
+        "area",
+        "article",
+        "aside",
+        "base",
+        "basefont",
+        "bgsound",
+        "blockquote",
+        "body",
+        "br",
+        "button",
+        "caption",
+        "center",
+        "col",
+        "colgroup",
+        "dd",
+        "details",
+        "dir",
+        "div",
+        "dl",
+        "dt",
+        "embed",
+        "fieldset",
+        "figcaption",
+        "figure",
+        "footer",
+        "form",
+        "frame",
+        "frameset",
+        "h1",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+        "head",
+        "header",
+        "hgroup",
+        "hr",
+        "html",
+        "iframe",
+        "img",
+        "input",
+        "keygen",
+        "li",
+        "link",
+        "listing",
+        "main",
+        "marquee",
+        "menu",
+        "meta",
+        "nav",
+        "noembed",
+        "noframes",
+        "noscript",
+        "object",
+        "ol",
+        "p",
+        "param",
+        "plaintext",
+        "pre",
+        "script",
+        "section",
+        "select",
+        "source",
+        "style",
+        "summary",
+        "table",
+        "tbody",
+        "td",
+        "template",
+        "textarea",
+        "tfoot",
+        "th",
+        "thead",
+        "title",
+        "tr",
+        "track",
+        "ul",
+        "wbr",
+        "xmp",
+    ];
+    SPECIAL_TAGS.contains(&tag)
+}
+
+/// Returns true if the start tag `tag` closes an open p element.
+/// https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inbody
+fn closes_p_element(tag: &str) -> bool {
+    const TAGS: [&str; 35] = [
+        "address",
+        "article",
+        "aside",
+        "blockquote",
+        "center",
+        "details",
+        "dialog",
+        "dir",
+        "div",
+        "dl",
+        "fieldset",
+        "figcaption",
+        "figure",
+        "footer",
+        "header",
+        "hgroup",
+        "main",
+        "menu",
+        "nav",
+        "ol",
+        "p",
+        "section",
+        "summary",
+        "ul",
+        "pre",
+        "listing",
+        "form",
+        "table",
+        "hr",
+        "h1",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+    ];
+    TAGS.contains(&tag)
+}
+
+/// Returns true if a character token is one of U+0009 CHARACTER TABULATION, U+000A LINE FEED (LF),
+/// U+000C FORM FEED (FF), U+000D CARRIAGE RETURN (CR), or U+0020 SPACE.
+fn is_whitespace_char(c: char) -> bool {
+    let num = c as u32;
+    num == 0x09 || num == 0x0a || num == 0x0c || num == 0x0d || num == 0x20
 }
 
 #[allow(dead_code)]
@@ -102,145 +1616,121 @@ pub enum InsertionMode {
     InHead,
     AfterHead,
     InBody,
+    Text,
     AfterBody,
     AfterAfterBody,
 }
 
 #[derive(Debug, Clone)]
 pub struct Parser {
-    root: Rc<RefCell<Node>>,
+    document: Document,
     mode: InsertionMode,
+    /// https://html.spec.whatwg.org/multipage/parsing.html#original-insertion-mode
+    original_insertion_mode: InsertionMode,
     t: Tokenizer,
     /// https://html.spec.whatwg.org/multipage/parsing.html#the-stack-of-open-elements
-    stack_of_open_elements: Vec<Rc<RefCell<Node>>>,
+    stack_of_open_elements: Vec<NodeId>,
+    /// The nanoseconds spent in the tokenizer, which runs whenever the tree construction takes
+    /// the next token.
+    tokenize_time: u64,
 }
 
 impl Parser {
     pub fn new(t: Tokenizer) -> Self {
         Self {
-            root: Rc::new(RefCell::new(Node::new(NodeKind::Document))),
+            document: Document::new(),
             mode: InsertionMode::Initial,
+            original_insertion_mode: InsertionMode::Initial,
             t,
             stack_of_open_elements: Vec::new(),
+            tokenize_time: 0,
         }
     }
 
-    /// Creates an element node.
-    fn create_element(&self, kind: ElementKind) -> Node {
-        return Node::new(NodeKind::Element(Element::new(kind)));
+    /// Enables scripting of the document, where `scripting` runs each script element when its
+    /// end tag is parsed. The document keeps it after parsing.
+    pub fn set_scripting(&mut self, scripting: ScriptingRef) {
+        self.document.set_scripting(scripting);
     }
 
-    /// Creates a char node.
-    fn create_char(&self, c: char) -> Node {
-        let mut s = String::new();
-        s.push(c);
-        return Node::new(NodeKind::Text(s));
+    /// Returns the nanoseconds spent in the tokenizer so far.
+    pub fn tokenize_time(&self) -> u64 {
+        self.tokenize_time
+    }
+
+    /// Takes the next token from the tokenizer.
+    fn next_token(&mut self) -> Option<Token> {
+        let start = Instant::now();
+        let token = self.t.next();
+        self.tokenize_time += nanos_since(start);
+        token
     }
 
-    /// Creates an element based on the `tag` string.
-    fn create_element_by_tag(&self, tag: &str) -> Node {
-        if tag == "html" {
-            return self.create_element(ElementKind::Html);
-        } else if tag == "head" {
-            return self.create_element(ElementKind::Head);
-        } else if tag == "body" {
-            return self.create_element(ElementKind::Body);
+    /// Returns the current node, or the Document node if the stack of open elements is empty.
+    /// https://html.spec.whatwg.org/multipage/parsing.html#current-node
+    fn current_node(&self) -> NodeId {
+        match self.stack_of_open_elements.last() {
+            Some(n) => *n,
+            None => self.document.root(),
         }
-        panic!("not supported this tag name: {}", tag);
     }
 
     /// Creates an element node for the token and insert it to the appropriate place for inserting
     /// a node. Put the new node in the stack of open elements.
     /// https://html.spec.whatwg.org/multipage/parsing.html#insert-a-foreign-element
-    fn insert_element(&mut self, tag: &str) {
-        let current = match self.stack_of_open_elements.last() {
-            Some(n) => n,
-            None => &self.root,
-        };
-
-        let node = Rc::new(RefCell::new(self.create_element_by_tag(tag)));
-
-        if current.borrow().first_child().is_some() {
-            {
-                current
-                    .borrow()
-                    .first_child()
-                    .unwrap()
-                    .borrow_mut()
-                    .next_sibling = Some(node.clone());
-            }
-            {
-                node.borrow_mut().previous_sibling =
-                    Some(Rc::downgrade(&current.borrow().first_child().unwrap()));
-            }
-        } else {
-            current.borrow_mut().first_child = Some(node.clone());
-        }
-
-        {
-            current.borrow_mut().last_child = Some(Rc::downgrade(&node));
-        }
-        {
-            node.borrow_mut().parent = Some(Rc::downgrade(&current));
-        }
+    fn insert_element(&mut self, tag: &str, attributes: Vec<Attribute>) {
+        let current = self.current_node();
+        let node = self
+            .document
+            .create_element(Element::from_tag(tag, attributes));
+        self.document.append_child(current, node);
 
         self.stack_of_open_elements.push(node);
     }
 
     /// https://html.spec.whatwg.org/multipage/parsing.html#insert-a-character
     fn insert_char(&mut self, c: char) {
-        let current = match self.stack_of_open_elements.last() {
-            Some(n) => n,
-            None => &self.root,
-        };
+        let current = self.current_node();
 
-        {
-            match current.borrow_mut().kind {
-                NodeKind::Text(ref mut s) => {
-                    s.push(c);
-                    return;
-                }
-                _ => {}
+        // If there is a Text node immediately before the adjusted insertion location, append the
+        // data to that Text node's data.
+        if let Some(last) = self.document.node(current).last_child() {
+            if let NodeKind::Text(ref mut s) = self.document.node_mut(last).kind {
+                s.push(c);
+                return;
             }
         }
 
-        let node = Rc::new(RefCell::new(self.create_char(c)));
+        let mut s = String::new();
+        s.push(c);
+        let node = self.document.create_node(NodeKind::Text(s));
+        self.document.append_child(current, node);
+    }
 
-        if current.borrow().first_child().is_some() {
-            {
-                current
-                    .borrow()
-                    .first_child()
-                    .unwrap()
-                    .borrow_mut()
-                    .next_sibling = Some(node.clone());
-            }
-            {
-                node.borrow_mut().previous_sibling =
-                    Some(Rc::downgrade(&current.borrow().first_child().unwrap()));
-            }
-        } else {
-            current.borrow_mut().first_child = Some(node.clone());
-        }
+    /// Inserts a Comment node as the last child of `parent`, or of the current node if `parent` is
+    /// None.
+    /// https://html.spec.whatwg.org/multipage/parsing.html#insert-a-comment
+    fn insert_comment(&mut self, data: &str, parent: Option<NodeId>) {
+        let parent = match parent {
+            Some(p) => p,
+            None => self.current_node(),
+        };
 
-        {
-            current.borrow_mut().last_child = Some(Rc::downgrade(&node));
-        }
-        {
-            node.borrow_mut().parent = Some(Rc::downgrade(&current));
-        }
+        let node = self
+            .document
+            .create_node(NodeKind::Comment(String::from(data)));
+        self.document.append_child(parent, node);
+    }
 
-        self.stack_of_open_elements.push(node);
+    /// Returns the element kind of the current node.
+    fn current_node_kind(&self) -> Option<ElementKind> {
+        let current = self.stack_of_open_elements.last()?;
+        self.document.node(*current).element_kind()
     }
 
     /// Returns true if the current node's kind is same as NodeKind::Element::<element_kind>.
     fn pop_current_node(&mut self, element_kind: ElementKind) -> bool {
-        let current = match self.stack_of_open_elements.last() {
-            Some(n) => n,
-            None => return false,
-        };
-
-        if current.borrow().kind == NodeKind::Element(Element::new(element_kind)) {
+        if self.current_node_kind() == Some(element_kind) {
             self.stack_of_open_elements.pop();
             return true;
         }
@@ -253,12 +1743,12 @@ impl Parser {
         assert!(self.contain_in_stack(element_kind));
 
         loop {
-            let current = match self.stack_of_open_elements.pop() {
-                Some(n) => n,
-                None => return,
-            };
+            if self.current_node_kind() == Some(element_kind) {
+                self.stack_of_open_elements.pop();
+                return;
+            }
 
-            if current.borrow().kind == NodeKind::Element(Element::new(element_kind)) {
+            if self.stack_of_open_elements.pop().is_none() {
                 return;
             }
         }
@@ -266,59 +1756,314 @@ impl Parser {
 
     /// Returns true if the stack of open elements has NodeKind::Element::<element_kind> node.
     fn contain_in_stack(&mut self, element_kind: ElementKind) -> bool {
-        for i in 0..self.stack_of_open_elements.len() {
-            if self.stack_of_open_elements[i].borrow().kind
-                == NodeKind::Element(Element::new(element_kind))
-            {
-                return true;
+        self.stack_of_open_elements
+            .iter()
+            .any(|n| self.document.node(*n).element_kind() == Some(element_kind))
+    }
+
+    /// Pops nodes until a heading element (h1-h6) is popped.
+    fn pop_until_heading(&mut self) {
+        while let Some(kind) = self.current_node_kind() {
+            self.stack_of_open_elements.pop();
+            if kind.is_heading() {
+                return;
             }
         }
+    }
 
-        false
+    /// Returns true if the stack of open elements has any heading element.
+    fn contain_heading_in_stack(&self) -> bool {
+        self.stack_of_open_elements
+            .iter()
+            .any(|n| match self.document.node(*n).element_kind() {
+                Some(kind) => kind.is_heading(),
+                None => false,
+            })
+    }
+
+    /// https://html.spec.whatwg.org/multipage/parsing.html#close-a-p-element
+    fn close_p_element_if_open(&mut self) {
+        if self.contain_in_stack(ElementKind::P) {
+            self.pop_until(ElementKind::P);
+        }
+    }
+
+    /// Closes an open li element when a new li element starts.
+    /// https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inbody
+    /// 'A start tag whose tag name is "li"'
+    fn close_li_element_if_open(&mut self) {
+        for i in (0..self.stack_of_open_elements.len()).rev() {
+            let (kind, tag) = match self.document.node(self.stack_of_open_elements[i]).element() {
+                Some(e) => (e.kind(), String::from(e.tag_name())),
+                None => continue,
+            };
+            if kind == ElementKind::Li {
+                self.pop_until(ElementKind::Li);
+                return;
+            }
+            if is_special_tag(&tag) && tag != "address" && tag != "div" && tag != "p" {
+                return;
+            }
+        }
+    }
+
+    /// https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inbody
+    /// "Any other end tag"
+    fn close_element_by_end_tag(&mut self, tag: &str) {
+        for i in (0..self.stack_of_open_elements.len()).rev() {
+            let node_tag = match self.document.node(self.stack_of_open_elements[i]).element() {
+                Some(e) => String::from(e.tag_name()),
+                None => continue,
+            };
+            if node_tag == tag {
+                self.stack_of_open_elements.truncate(i);
+                return;
+            }
+            if is_special_tag(&node_tag) {
+                // Parse error. Ignore the token.
+                return;
+            }
+        }
+    }
+
+    /// Inserts an element for a start tag which may appear in the head element, and switches the
+    /// tokenizer and the insertion mode for elements with a text content.
+    /// https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inhead
+    fn insert_head_element(&mut self, tag: &str, attributes: Vec<Attribute>) {
+        self.insert_element(tag, attributes);
+        match tag {
+            "meta" => {
+                // Immediately pop the current node off the stack of open elements.
+                self.stack_of_open_elements.pop();
+            }
+            "link" => {
+                if let Some(link) = self.stack_of_open_elements.pop() {
+                    self.add_linked_style_sheet(link);
+                }
+            }
+            "title" => self.start_text_element(State::Rcdata),
+            "style" => self.start_text_element(State::Rawtext),
+            "script" => self.start_text_element(State::ScriptData),
+            _ => {}
+        }
+    }
+
+    /// Adds a stylesheet to be fetched to the document if `link` is a link to a stylesheet. The
+    /// stylesheet keeps its position in the document order while it's being fetched.
+    /// https://html.spec.whatwg.org/multipage/links.html#link-type-stylesheet
+    fn add_linked_style_sheet(&mut self, link: NodeId) {
+        let element = match self.document.node(link).element() {
+            Some(e) => e,
+            None => return,
+        };
+        // The rel attribute is a set of space-separated keywords, which are ASCII
+        // case-insensitive.
+        let is_stylesheet = element.get_attribute("rel").is_some_and(|rel| {
+            rel.split_ascii_whitespace()
+                .any(|k| k.eq_ignore_ascii_case("stylesheet"))
+        });
+        let href = match element.get_attribute("href") {
+            Some(h) if is_stylesheet && !h.trim().is_empty() => String::from(h.trim()),
+            _ => return,
+        };
+        self.document.add_style_sheet(StyleSheet::from_href(&href));
+    }
+
+    /// Pops the element whose contents were parsed as text and returns it. If it's a style
+    /// element, its contents are parsed as a stylesheet and added to the document.
+    /// https://html.spec.whatwg.org/multipage/semantics.html#update-a-style-block
+    fn pop_text_element(&mut self) -> Option<NodeId> {
+        let node = self.stack_of_open_elements.pop()?;
+
+        if self.document.node(node).element_kind() == Some(ElementKind::Style) {
+            let css = self.document.text_content(node);
+            self.document.add_style_sheet(parse_stylesheet(css));
+        }
+        Some(node)
+    }
+
+    /// Runs the script element `node` which has just been popped, unless it's a data block or a
+    /// module, which isn't supported, or scripting is disabled.
+    /// https://html.spec.whatwg.org/multipage/scripting.html#prepare-the-script-element
+    fn run_script(&mut self, node: NodeId) {
+        let scripting = match self.document.scripting() {
+            Some(scripting) => scripting,
+            None => return,
+        };
+        let element = match self.document.node(node).element() {
+            Some(e) if e.kind() == ElementKind::Script => e,
+            _ => return,
+        };
+        // https://html.spec.whatwg.org/multipage/scripting.html#javascript-mime-type
+        let is_classic = match element.get_attribute("type") {
+            None => true,
+            Some(t) => {
+                let t = t.trim();
+                t.is_empty()
+                    || t.eq_ignore_ascii_case("text/javascript")
+                    || t.eq_ignore_ascii_case("application/javascript")
+                    || t.eq_ignore_ascii_case("text/ecmascript")
+                    || t.eq_ignore_ascii_case("application/ecmascript")
+            }
+        };
+        if is_classic {
+            scripting
+                .borrow_mut()
+                .run_script_element(&mut self.document, node);
+        }
+    }
+
+    /// https://html.spec.whatwg.org/multipage/parsing.html#generic-raw-text-element-parsing-algorithm
+    /// https://html.spec.whatwg.org/multipage/parsing.html#generic-rcdata-element-parsing-algorithm
+    fn start_text_element(&mut self, state: State) {
+        self.t.switch_to(state);
+        self.original_insertion_mode = self.mode;
+        self.mode = InsertionMode::Text;
+    }
+
+    /// Parses `input` as the contents of the `context` element and returns a document which holds
+    /// the result as the children of a DocumentFragment node. The fragment doesn't belong to the
+    /// tree of the returned document.
+    /// https://html.spec.whatwg.org/multipage/parsing.html#parsing-html-fragments
+    pub fn parse_fragment(context: &Element, input: String) -> (Document, NodeId) {
+        // Set the state of the tokenizer according to the context element.
+        let mut t = Tokenizer::new(input);
+        match context.tag_name() {
+            "title" | "textarea" => t.switch_to(State::Rcdata),
+            "style" | "xmp" | "iframe" | "noembed" | "noframes" => t.switch_to(State::Rawtext),
+            "script" => t.switch_to(State::ScriptData),
+            _ => {}
+        }
+
+        // Let root be a new html element with no attributes. Append the element root to the
+        // Document node created above. Set up the parser's stack of open elements so that it
+        // contains just the single element root.
+        let mut parser = Parser::new(t);
+        let root = parser.document.root();
+        let html = parser
+            .document
+            .create_element(Element::new(ElementKind::Html));
+        parser.document.append_child(root, html);
+        parser.stack_of_open_elements.push(html);
+
+        // Reset the parser's insertion mode appropriately. The context element is used as the
+        // last node in the stack of open elements.
+        // https://html.spec.whatwg.org/multipage/parsing.html#reset-the-insertion-mode-appropriately
+        parser.mode = match context.kind() {
+            ElementKind::Html => InsertionMode::BeforeHead,
+            _ => InsertionMode::InBody,
+        };
+
+        let mut document = parser.construct_tree();
+
+        // Return the child nodes of root, in tree order.
+        let fragment = document.create_node(NodeKind::DocumentFragment);
+        while let Some(child) = document.node(html).first_child() {
+            document.append_child(fragment, child);
+        }
+        document.remove_child(root, html);
+
+        (document, fragment)
     }
 
-    pub fn construct_tree(&mut self) -> Rc<RefCell<Node>> {
-        let mut token = self.t.next();
+    pub fn construct_tree(&mut self) -> Document {
+        let mut token = self.next_token();
 
         while token.is_some() {
             match self.mode {
                 // https://html.spec.whatwg.org/multipage/parsing.html#the-initial-insertion-mode
-                InsertionMode::Initial => self.mode = InsertionMode::BeforeHtml,
+                InsertionMode::Initial => {
+                    match token {
+                        Some(Token::Char(c)) => {
+                            if is_whitespace_char(c) {
+                                token = self.next_token();
+                                continue;
+                            }
+                        }
+                        Some(Token::Comment(ref data)) => {
+                            let root = self.document.root();
+                            self.insert_comment(data, Some(root));
+                            token = self.next_token();
+                            continue;
+                        }
+                        Some(Token::Doctype {
+                            ref name,
+                            ref public_id,
+                            ref system_id,
+                            force_quirks,
+                        }) => {
+                            // Append a DocumentType node to the Document node, with its name set
+                            // to the name given in the DOCTYPE token, or the empty string if the
+                            // name was missing; its public ID set to the public identifier given
+                            // in the DOCTYPE token, or the empty string if the public identifier
+                            // was missing; and its system ID set to the system identifier given
+                            // in the DOCTYPE token, or the empty string if the system identifier
+                            // was missing.
+                            let doctype = self.document.create_node(NodeKind::DocumentType {
+                                name: name.clone().unwrap_or_default(),
+                                public_id: public_id.clone().unwrap_or_default(),
+                                system_id: system_id.clone().unwrap_or_default(),
+                            });
+                            let root = self.document.root();
+                            self.document.append_child(root, doctype);
+
+                            let mode = quirks_mode_for_doctype(
+                                name.as_deref(),
+                                public_id.as_deref(),
+                                system_id.as_deref(),
+                                force_quirks,
+                            );
+                            self.document.set_quirks_mode(mode);
+
+                            self.mode = InsertionMode::BeforeHtml;
+                            token = self.next_token();
+                            continue;
+                        }
+                        _ => {}
+                    }
+                    // If the document is not an iframe srcdoc document, then this is a parse
+                    // error; if the parser cannot change the mode flag is false, set the Document
+                    // to quirks mode.
+                    self.document.set_quirks_mode(QuirksMode::Quirks);
+                    self.mode = InsertionMode::BeforeHtml;
+                } // end of InsertionMode::Initial
 
                 // https://html.spec.whatwg.org/multipage/parsing.html#the-before-html-insertion-mode
                 InsertionMode::BeforeHtml => {
                     match token {
-                        Some(Token::Doctype) => {
-                            token = self.t.next();
+                        Some(Token::Doctype { .. }) => {
+                            // Parse error. Ignore the token.
+                            token = self.next_token();
+                            continue;
+                        }
+                        Some(Token::Comment(ref data)) => {
+                            let root = self.document.root();
+                            self.insert_comment(data, Some(root));
+                            token = self.next_token();
                             continue;
                         }
                         Some(Token::Char(c)) => {
                             // If a character token that is one of U+0009 CHARACTER TABULATION, U+000A
                             // LINE FEED (LF), U+000C FORM FEED (FF), U+000D CARRIAGE RETURN (CR), or
                             // U+0020 SPACE, ignore the token.
-                            let num = c as u32;
-                            if num == 0x09
-                                || num == 0x0a
-                                || num == 0x0c
-                                || num == 0x0d
-                                || num == 0x20
-                            {
-                                token = self.t.next();
+                            if is_whitespace_char(c) {
+                                token = self.next_token();
                                 continue;
                             }
                         }
                         Some(Token::StartTag {
                             ref tag,
                             self_closing: _,
+                            ref attributes,
                         }) => {
                             // A start tag whose tag name is "html"
                             // Create an element for the token in the HTML namespace, with the Document
                             // as the intended parent. Append it to the Document object. Put this
                             // element in the stack of open elements.
                             if tag == "html" {
-                                self.insert_element(tag);
+                                self.insert_element(tag, attributes.to_vec());
                                 self.mode = InsertionMode::BeforeHead;
-                                token = self.t.next();
+                                token = self.next_token();
                                 continue;
                             }
                         }
@@ -330,73 +2075,110 @@ impl Parser {
                             // Parse error. Ignore the token.
                             if tag != "head" || tag != "body" || tag != "html" || tag != "br" {
                                 // Ignore the token.
-                                token = self.t.next();
+                                token = self.next_token();
                                 continue;
                             }
                         }
                         Some(Token::Eof) | None => {
-                            return self.root.clone();
+                            return core::mem::take(&mut self.document);
                         }
                     }
-                    self.insert_element("html");
+                    self.insert_element("html", Vec::new());
                     self.mode = InsertionMode::BeforeHead;
                 } // end of InsertionMode::BeforeHtml
 
                 // https://html.spec.whatwg.org/multipage/parsing.html#the-before-head-insertion-mode
                 InsertionMode::BeforeHead => {
                     match token {
+                        Some(Token::Comment(ref data)) => {
+                            self.insert_comment(data, None);
+                            token = self.next_token();
+                            continue;
+                        }
+                        Some(Token::Doctype { .. }) => {
+                            // Parse error. Ignore the token.
+                            token = self.next_token();
+                            continue;
+                        }
                         Some(Token::Char(c)) => {
-                            let num = c as u32;
                             // If a character token that is one of U+0009 CHARACTER TABULATION, U+000A
                             // LINE FEED (LF), U+000C FORM FEED (FF), U+000D CARRIAGE RETURN (CR), or
                             // U+0020 SPACE, ignore the token.
-                            if num == 0x09
-                                || num == 0x0a
-                                || num == 0x0c
-                                || num == 0x0d
-                                || num == 0x20
-                            {
-                                token = self.t.next();
+                            if is_whitespace_char(c) {
+                                token = self.next_token();
+                                continue;
                             }
                         }
                         Some(Token::StartTag {
                             ref tag,
                             self_closing: _,
+                            ref attributes,
                         }) => {
                             if tag == "head" {
-                                self.insert_element(tag);
+                                self.insert_element(tag, attributes.to_vec());
                                 self.mode = InsertionMode::InHead;
-                                token = self.t.next();
+                                token = self.next_token();
                                 continue;
                             }
                         }
                         Some(Token::Eof) | None => {
-                            return self.root.clone();
+                            return core::mem::take(&mut self.document);
                         }
                         _ => {}
                     }
-                    self.insert_element("head");
+                    self.insert_element("head", Vec::new());
                     self.mode = InsertionMode::InHead;
                 } // end of InsertionMode::BeforeHead
 
                 // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inhead
                 InsertionMode::InHead => {
                     match token {
+                        Some(Token::Comment(ref data)) => {
+                            self.insert_comment(data, None);
+                            token = self.next_token();
+                            continue;
+                        }
+                        Some(Token::Doctype { .. }) => {
+                            // Parse error. Ignore the token.
+                            token = self.next_token();
+                            continue;
+                        }
+                        Some(Token::Char(c)) => {
+                            if is_whitespace_char(c) {
+                                token = self.next_token();
+                                continue;
+                            }
+                        }
+                        Some(Token::StartTag {
+                            ref tag,
+                            self_closing: _,
+                            ref attributes,
+                        }) => {
+                            if tag == "meta"
+                                || tag == "link"
+                                || tag == "title"
+                                || tag == "style"
+                                || tag == "script"
+                            {
+                                self.insert_head_element(tag, attributes.to_vec());
+                                token = self.next_token();
+                                continue;
+                            }
+                        }
                         Some(Token::EndTag {
                             ref tag,
                             self_closing: _,
                         }) => {
                             if tag == "head" {
                                 self.mode = InsertionMode::AfterHead;
-                                token = self.t.next();
+                                token = self.next_token();
                                 assert!(self.pop_current_node(ElementKind::Head));
                                 continue;
                             }
                         }
                         Some(Token::Eof) | None => {
-                            return self.root.clone();
+                            return core::mem::take(&mut self.document);
                         }
-                        _ => {}
                     }
                     self.mode = InsertionMode::AfterHead;
                     assert!(self.pop_current_node(ElementKind::Head));
@@ -405,44 +2187,118 @@ impl Parser {
                 // https://html.spec.whatwg.org/multipage/parsing.html#the-after-head-insertion-mode
                 InsertionMode::AfterHead => {
                     match token {
+                        Some(Token::Comment(ref data)) => {
+                            self.insert_comment(data, None);
+                            token = self.next_token();
+                            continue;
+                        }
+                        Some(Token::Doctype { .. }) => {
+                            // Parse error. Ignore the token.
+                            token = self.next_token();
+                            continue;
+                        }
+                        Some(Token::Char(c)) => {
+                            if is_whitespace_char(c) {
+                                token = self.next_token();
+                                continue;
+                            }
+                        }
                         Some(Token::StartTag {
                             ref tag,
                             self_closing: _,
+                            ref attributes,
                         }) => {
                             if tag == "body" {
-                                self.insert_element(tag);
-                                token = self.t.next();
+                                self.insert_element(tag, attributes.to_vec());
+                                token = self.next_token();
                                 self.mode = InsertionMode::InBody;
                                 continue;
                             }
                         }
                         Some(Token::Eof) | None => {
-                            return self.root.clone();
+                            return core::mem::take(&mut self.document);
                         }
                         _ => {}
                     }
-                    self.insert_element("body");
+                    self.insert_element("body", Vec::new());
                     self.mode = InsertionMode::InBody;
                 } // end of InsertionMode::AfterHead
 
                 // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inbody
                 InsertionMode::InBody => {
                     match token {
+                        Some(Token::Comment(ref data)) => {
+                            self.insert_comment(data, None);
+                            token = self.next_token();
+                            continue;
+                        }
+                        Some(Token::Doctype { .. }) => {
+                            // Parse error. Ignore the token.
+                            token = self.next_token();
+                            continue;
+                        }
                         Some(Token::StartTag {
-                            tag: _,
+                            ref tag,
                             self_closing: _,
-                        }) => {}
+                            ref attributes,
+                        }) => {
+                            token = match tag.as_str() {
+                                "html" | "body" | "head" => {
+                                    // Parse error. Ignore the token.
+                                    self.next_token()
+                                }
+                                "meta" | "link" | "title" | "style" | "script" => {
+                                    self.insert_head_element(tag, attributes.to_vec());
+                                    self.next_token()
+                                }
+                                "li" => {
+                                    self.close_li_element_if_open();
+                                    self.close_p_element_if_open();
+                                    self.insert_element(tag, attributes.to_vec());
+                                    self.next_token()
+                                }
+                                "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                                    self.close_p_element_if_open();
+                                    if let Some(kind) = self.current_node_kind() {
+                                        if kind.is_heading() {
+                                            // Parse error.
+                                            self.stack_of_open_elements.pop();
+                                        }
+                                    }
+                                    self.insert_element(tag, attributes.to_vec());
+                                    self.next_token()
+                                }
+                                "textarea" => {
+                                    self.insert_element(tag, attributes.to_vec());
+                                    self.start_text_element(State::Rcdata);
+                                    self.next_token()
+                                }
+                                _ => {
+                                    if closes_p_element(tag) {
+                                        self.close_p_element_if_open();
+                                    }
+                                    self.insert_element(tag, attributes.to_vec());
+                                    if ElementKind::from_tag(tag).is_void() {
+                                        // Immediately pop the current node off the stack of open
+                                        // elements.
+                                        self.stack_of_open_elements.pop();
+                                    }
+                                    self.next_token()
+                                }
+                            };
+                            continue;
+                        }
                         Some(Token::EndTag {
                             ref tag,
                             self_closing: _,
                         }) => {
                             if tag == "body" {
-                                self.mode = InsertionMode::AfterBody;
-                                token = self.t.next();
+                                token = self.next_token();
                                 if !self.contain_in_stack(ElementKind::Body) {
                                     // Parse error. Ignore the token.
                                     continue;
                                 }
+                                self.mode = InsertionMode::AfterBody;
                                 self.pop_until(ElementKind::Body);
                                 continue;
                             }
@@ -453,38 +2309,102 @@ impl Parser {
                                     self.mode = InsertionMode::AfterBody;
                                     assert!(self.pop_current_node(ElementKind::Html));
                                 } else {
-                                    token = self.t.next();
+                                    token = self.next_token();
+                                }
+                                continue;
+                            }
+                            if ElementKind::from_tag(tag).is_heading() {
+                                if self.contain_heading_in_stack() {
+                                    self.pop_until_heading();
                                 }
+                                token = self.next_token();
+                                continue;
+                            }
+                            if tag == "br" {
+                                // Parse error. Treat it as a start tag without attributes.
+                                self.insert_element(tag, Vec::new());
+                                self.stack_of_open_elements.pop();
+                                token = self.next_token();
                                 continue;
                             }
+                            self.close_element_by_end_tag(tag);
+                            token = self.next_token();
+                            continue;
                         }
                         Some(Token::Char(c)) => {
                             self.insert_char(c);
-                            token = self.t.next();
+                            token = self.next_token();
                             continue;
                         }
                         Some(Token::Eof) | None => {
-                            return self.root.clone();
+                            return core::mem::take(&mut self.document);
                         }
-                        _ => {}
                     }
                 } // end of InsertionMode::InBody
 
+                // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-incdata
+                InsertionMode::Text => {
+                    match token {
+                        Some(Token::Char(c)) => {
+                            self.insert_char(c);
+                            token = self.next_token();
+                            continue;
+                        }
+                        Some(Token::EndTag {
+                            tag: _,
+                            self_closing: _,
+                        }) => {
+                            let node = self.pop_text_element();
+                            self.mode = self.original_insertion_mode;
+                            // A script runs when its end tag is parsed, before the rest of the
+                            // document. A script which isn't closed before the end never runs.
+                            if let Some(node) = node {
+                                self.run_script(node);
+                            }
+                            token = self.next_token();
+                            continue;
+                        }
+                        Some(Token::Eof) | None => {
+                            // Parse error.
+                            self.pop_text_element();
+                            return core::mem::take(&mut self.document);
+                        }
+                        _ => {
+                            token = self.next_token();
+                            continue;
+                        }
+                    }
+                } // end of InsertionMode::Text
+
                 // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-afterbody
                 InsertionMode::AfterBody => {
                     match token {
+                        Some(Token::Comment(ref data)) => {
+                            // Insert a comment as the last child of the first element in the stack
+                            // of open elements (the html element).
+                            let html = self.stack_of_open_elements.first().cloned();
+                            self.insert_comment(data, html);
+                            token = self.next_token();
+                            continue;
+                        }
+                        Some(Token::Char(c)) => {
+                            if is_whitespace_char(c) {
+                                token = self.next_token();
+                                continue;
+                            }
+                        }
                         Some(Token::EndTag {
                             ref tag,
                             self_closing: _,
                         }) => {
                             if tag == "html" {
                                 self.mode = InsertionMode::AfterAfterBody;
-                                token = self.t.next();
+                                token = self.next_token();
                                 continue;
                             }
                         }
                         Some(Token::Eof) | None => {
-                            return self.root.clone();
+                            return core::mem::take(&mut self.document);
                         }
                         _ => {}
                     }
@@ -495,18 +2415,35 @@ impl Parser {
                 // https://html.spec.whatwg.org/multipage/parsing.html#the-after-after-body-insertion-mode
                 InsertionMode::AfterAfterBody => {
                     match token {
+                        Some(Token::Comment(ref data)) => {
+                            let root = self.document.root();
+                            self.insert_comment(data, Some(root));
+                            token = self.next_token();
+                            continue;
+                        }
+                        Some(Token::Doctype { .. }) => {
+                            // Parse error. Ignore the token.
+                            token = self.next_token();
+                            continue;
+                        }
+                        Some(Token::Char(c)) => {
+                            if is_whitespace_char(c) {
+                                token = self.next_token();
+                                continue;
+                            }
+                        }
                         Some(Token::EndTag {
                             ref tag,
                             self_closing: _,
                         }) => {
                             if tag == "html" {
                                 self.mode = InsertionMode::AfterAfterBody;
-                                token = self.t.next();
+                                token = self.next_token();
                                 continue;
                             }
                         }
                         Some(Token::Eof) | None => {
-                            return self.root.clone();
+                            return core::mem::take(&mut self.document);
                         }
                         _ => {}
                     }
@@ -516,6 +2453,11 @@ impl Parser {
             } // end of match self.mode {}
         } // end of while token.is_some {}
 
-        self.root.clone()
+        // The input ended in the middle of a text element such as style.
+        if self.mode == InsertionMode::Text {
+            self.pop_text_element();
+        }
+
+        core::mem::take(&mut self.document)
     }
 }
@@ -11,28 +11,119 @@ use liumlib::*;
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum State {
     Data,
+    Rcdata,
+    Rawtext,
+    ScriptData,
     TagOpen,
     EndTagOpen,
     TagName,
+    BeforeAttributeName,
+    AttributeName,
+    AfterAttributeName,
+    BeforeAttributeValue,
+    AttributeValueDoubleQuoted,
+    AttributeValueSingleQuoted,
+    AttributeValueUnquoted,
+    AfterAttributeValueQuoted,
     SelfClosingStartTag,
+    BogusComment,
+    MarkupDeclarationOpen,
+    CommentStart,
+    CommentStartDash,
+    Comment,
+    CommentEndDash,
+    CommentEnd,
+    Doctype,
+    BeforeDoctypeName,
+    DoctypeName,
+    AfterDoctypeName,
+    AfterDoctypePublicKeyword,
+    BeforeDoctypePublicIdentifier,
+    DoctypePublicIdentifierDoubleQuoted,
+    DoctypePublicIdentifierSingleQuoted,
+    AfterDoctypePublicIdentifier,
+    BetweenDoctypePublicAndSystemIdentifiers,
+    AfterDoctypeSystemKeyword,
+    BeforeDoctypeSystemIdentifier,
+    DoctypeSystemIdentifierDoubleQuoted,
+    DoctypeSystemIdentifierSingleQuoted,
+    AfterDoctypeSystemIdentifier,
+    BogusDoctype,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// https://html.spec.whatwg.org/multipage/parsing.html#tokenization
+/// "Start and end tag tokens have a tag name, a self-closing flag, and a list of attributes, each
+/// of which has a name and a value."
+pub struct Attribute {
+    name: String,
+    value: String,
+}
+
+#[allow(dead_code)]
+impl Attribute {
+    pub fn new(name: String, value: String) -> Self {
+        Self { name, value }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn set_value(&mut self, value: String) {
+        self.value = value;
+    }
 }
 
 #[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Token {
-    Doctype,
-    StartTag { tag: String, self_closing: bool },
-    EndTag { tag: String, self_closing: bool },
+    /// https://html.spec.whatwg.org/multipage/parsing.html#tokenization
+    /// "DOCTYPE tokens have a name, a public identifier, a system identifier, and a force-quirks
+    /// flag. When a DOCTYPE token is created, its name, public identifier, and system identifier
+    /// must be marked as missing"
+    Doctype {
+        name: Option<String>,
+        public_id: Option<String>,
+        system_id: Option<String>,
+        force_quirks: bool,
+    },
+    StartTag {
+        tag: String,
+        self_closing: bool,
+        attributes: Vec<Attribute>,
+    },
+    EndTag {
+        tag: String,
+        self_closing: bool,
+    },
+    Comment(String),
     Char(char),
     Eof,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DoctypeField {
+    Name,
+    PublicId,
+    SystemId,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Tokenizer {
     state: State,
     pos: usize,
     reconsume: bool,
     latest_token: Option<Token>,
+    /// True if the current attribute was a duplicate and removed from `latest_token`. Its value is
+    /// consumed but discarded.
+    dropped_attribute: bool,
+    /// https://html.spec.whatwg.org/multipage/parsing.html#appropriate-end-tag-token
+    last_start_tag: String,
     input: Vec<char>,
 }
 
@@ -43,14 +134,44 @@ impl Tokenizer {
             pos: 0,
             reconsume: false,
             latest_token: None,
+            dropped_attribute: false,
+            last_start_tag: String::new(),
             input: html.chars().collect(),
         }
     }
 
-    /// Consumes a next input character.
+    /// Switches the state. This is used by the tree construction stage to tokenize the contents of
+    /// elements such as title, style and script as text.
+    /// https://html.spec.whatwg.org/multipage/parsing.html#generic-raw-text-element-parsing-algorithm
+    pub fn switch_to(&mut self, state: State) {
+        self.state = state;
+    }
+
+    /// Returns true if the next input characters are an end tag whose name is the same as the
+    /// last start tag, i.e. an appropriate end tag.
+    /// https://html.spec.whatwg.org/multipage/parsing.html#appropriate-end-tag-token
+    fn is_appropriate_end_tag_next(&self) -> bool {
+        if self.input.get(self.pos) != Some(&'/') {
+            return false;
+        }
+        let start = self.pos + 1;
+        for (i, expected) in self.last_start_tag.chars().enumerate() {
+            match self.input.get(start + i) {
+                Some(c) if c.to_ascii_lowercase() == expected => {}
+                _ => return false,
+            }
+        }
+        match self.input.get(start + self.last_start_tag.len()) {
+            Some(c) => is_whitespace(*c) || *c == '/' || *c == '>',
+            None => false,
+        }
+    }
+
+    /// Consumes a next input character. Returns U+0000 NULL once the input is exhausted, in which
+    /// case `is_eof` returns true.
     fn consume_next_input(&mut self) -> char {
         //println!("{:?}", self);
-        let c = self.input[self.pos];
+        let c = self.input.get(self.pos).copied().unwrap_or('\0');
         self.pos += 1;
         c
     }
@@ -59,7 +180,7 @@ impl Tokenizer {
     /// `reconsume_input` is called.
     fn reconsume_input(&mut self) -> char {
         self.reconsume = false;
-        self.input[self.pos - 1]
+        self.input.get(self.pos - 1).copied().unwrap_or('\0')
     }
 
     /// Creates a StartTag or EndTag token.
@@ -70,6 +191,7 @@ impl Tokenizer {
             self.latest_token = Some(Token::StartTag {
                 tag: String::new(),
                 self_closing: false,
+                attributes: Vec::new(),
             });
         } else {
             self.latest_token = Some(Token::EndTag {
@@ -88,6 +210,7 @@ impl Tokenizer {
                 Token::StartTag {
                     ref mut tag,
                     self_closing: _,
+                    attributes: _,
                 }
                 | Token::EndTag {
                     ref mut tag,
@@ -107,6 +230,7 @@ impl Tokenizer {
                 Token::StartTag {
                     tag: _,
                     ref mut self_closing,
+                    attributes: _,
                 }
                 | Token::EndTag {
                     tag: _,
@@ -117,12 +241,189 @@ impl Tokenizer {
         }
     }
 
+    /// Starts a new attribute with an empty name and value in the `latest_token`. Attributes on
+    /// end tags are parsed but dropped.
+    fn start_new_attribute(&mut self) {
+        assert!(self.latest_token.is_some());
+
+        self.dropped_attribute = false;
+        if let Some(Token::StartTag {
+            tag: _,
+            self_closing: _,
+            ref mut attributes,
+        }) = self.latest_token
+        {
+            attributes.push(Attribute::new(String::new(), String::new()));
+        }
+    }
+
+    /// Appends a char to the name (`is_name` is true) or the value of the current attribute.
+    fn append_attribute(&mut self, c: char, is_name: bool) {
+        assert!(self.latest_token.is_some());
+
+        if self.dropped_attribute {
+            return;
+        }
+        if let Some(Token::StartTag {
+            tag: _,
+            self_closing: _,
+            ref mut attributes,
+        }) = self.latest_token
+        {
+            let len = attributes.len();
+            assert!(len > 0);
+            if is_name {
+                attributes[len - 1].name.push(c);
+            } else {
+                attributes[len - 1].value.push(c);
+            }
+        }
+    }
+
+    /// Removes the current attribute if an attribute with the same name already exists in the
+    /// `latest_token`.
+    /// https://html.spec.whatwg.org/multipage/parsing.html#attribute-name-state
+    /// "When the user agent leaves the attribute name state, ... if there is already an attribute
+    /// on the token with the exact same name, then this is a duplicate-attribute parse error and
+    /// the new attribute must be removed from the token."
+    fn drop_duplicate_attribute(&mut self) {
+        if let Some(Token::StartTag {
+            tag: _,
+            self_closing: _,
+            ref mut attributes,
+        }) = self.latest_token
+        {
+            let len = attributes.len();
+            if len < 2 {
+                return;
+            }
+            let name = attributes[len - 1].name.clone();
+            if attributes[..len - 1].iter().any(|a| a.name == name) {
+                attributes.pop();
+                self.dropped_attribute = true;
+            }
+        }
+    }
+
+    /// Creates a Comment token with empty data.
+    fn create_comment(&mut self) {
+        assert!(self.latest_token.is_none());
+
+        self.latest_token = Some(Token::Comment(String::new()));
+    }
+
+    /// Appends a char to the data of the Comment token `latest_token`.
+    fn append_comment(&mut self, c: char) {
+        assert!(self.latest_token.is_some());
+
+        if let Some(Token::Comment(ref mut data)) = self.latest_token {
+            data.push(c);
+        }
+    }
+
+    /// Creates a DOCTYPE token whose name, public identifier and system identifier are missing.
+    fn create_doctype(&mut self) {
+        assert!(self.latest_token.is_none());
+
+        self.latest_token = Some(Token::Doctype {
+            name: None,
+            public_id: None,
+            system_id: None,
+            force_quirks: false,
+        });
+    }
+
+    /// Returns the `field` of the DOCTYPE token `latest_token`.
+    fn doctype_field(&mut self, field: DoctypeField) -> &mut Option<String> {
+        match self.latest_token {
+            Some(Token::Doctype {
+                ref mut name,
+                ref mut public_id,
+                ref mut system_id,
+                force_quirks: _,
+            }) => match field {
+                DoctypeField::Name => name,
+                DoctypeField::PublicId => public_id,
+                DoctypeField::SystemId => system_id,
+            },
+            _ => panic!("`latest_token` should be Doctype"),
+        }
+    }
+
+    /// Appends a char to the name of the DOCTYPE token `latest_token`.
+    fn append_doctype_name(&mut self, c: char) {
+        self.doctype_field(DoctypeField::Name)
+            .get_or_insert_with(String::new)
+            .push(c.to_ascii_lowercase());
+    }
+
+    /// Sets the public identifier of the DOCTYPE token `latest_token` to the empty string.
+    fn start_public_identifier(&mut self) {
+        *self.doctype_field(DoctypeField::PublicId) = Some(String::new());
+    }
+
+    fn append_public_identifier(&mut self, c: char) {
+        self.doctype_field(DoctypeField::PublicId)
+            .get_or_insert_with(String::new)
+            .push(c);
+    }
+
+    /// Sets the system identifier of the DOCTYPE token `latest_token` to the empty string.
+    fn start_system_identifier(&mut self) {
+        *self.doctype_field(DoctypeField::SystemId) = Some(String::new());
+    }
+
+    fn append_system_identifier(&mut self, c: char) {
+        self.doctype_field(DoctypeField::SystemId)
+            .get_or_insert_with(String::new)
+            .push(c);
+    }
+
+    /// Sets the force-quirks flag of the DOCTYPE token `latest_token` to on.
+    fn set_force_quirks_flag(&mut self) {
+        if let Some(Token::Doctype {
+            ref mut force_quirks,
+            ..
+        }) = self.latest_token
+        {
+            *force_quirks = true;
+        }
+    }
+
+    /// Sets the force-quirks flag and returns the DOCTYPE token. This is used when the DOCTYPE is
+    /// broken, e.g. at the end of the input.
+    fn take_quirks_doctype(&mut self) -> Option<Token> {
+        self.set_force_quirks_flag();
+        self.state = State::Data;
+        self.take_latest_token()
+    }
+
+    /// Returns true and consumes the characters if `c` and the next input characters are an
+    /// ASCII case-insensitive match for `word`.
+    fn consume_if_word(&mut self, c: char, word: &str) -> bool {
+        let mut expected = word.chars();
+        if expected.next() != Some(c.to_ascii_lowercase()) {
+            return false;
+        }
+        for (i, e) in expected.clone().enumerate() {
+            match self.input.get(self.pos + i) {
+                Some(actual) if actual.to_ascii_lowercase() == e => {}
+                _ => return false,
+            }
+        }
+        self.pos += expected.count();
+        true
+    }
+
     /// Returns `latest_token` and makes it to None.
     fn take_latest_token(&mut self) -> Option<Token> {
         assert!(self.latest_token.is_some());
 
         let t = self.latest_token.as_ref().and_then(|t| Some(t.clone()));
         self.latest_token = None;
+        if let Some(Token::StartTag { ref tag, .. }) = t {
+            self.last_start_tag = tag.clone();
+        }
         assert!(self.latest_token.is_none());
 
         t
@@ -134,6 +435,12 @@ impl Tokenizer {
     }
 }
 
+/// Returns true if `c` is one of U+0009 CHARACTER TABULATION, U+000A LINE FEED (LF), U+000C FORM
+/// FEED (FF), or U+0020 SPACE.
+fn is_whitespace(c: char) -> bool {
+    c == '\t' || c == '\n' || c == '\x0c' || c == ' '
+}
+
 impl Iterator for Tokenizer {
     type Item = Token;
 
@@ -162,8 +469,38 @@ impl Iterator for Tokenizer {
 
                     return Some(Token::Char(c));
                 }
+                // https://html.spec.whatwg.org/multipage/parsing.html#rcdata-state
+                // https://html.spec.whatwg.org/multipage/parsing.html#rawtext-state
+                // https://html.spec.whatwg.org/multipage/parsing.html#script-data-state
+                // Character references and the script data escaped states are not supported. The
+                // content ends at the first appropriate end tag.
+                State::Rcdata | State::Rawtext | State::ScriptData => {
+                    if c == '<' && self.is_appropriate_end_tag_next() {
+                        self.state = State::TagOpen;
+                        continue;
+                    }
+
+                    if self.is_eof() {
+                        return Some(Token::Eof);
+                    }
+
+                    return Some(Token::Char(c));
+                }
                 // https://html.spec.whatwg.org/multipage/parsing.html#tag-open-state
                 State::TagOpen => {
+                    if c == '!' {
+                        self.state = State::MarkupDeclarationOpen;
+                        continue;
+                    }
+
+                    if c == '?' {
+                        // Parse error.
+                        self.reconsume = true;
+                        self.state = State::BogusComment;
+                        self.create_comment();
+                        continue;
+                    }
+
                     if c == '/' {
                         self.state = State::EndTagOpen;
                         continue;
@@ -196,9 +533,25 @@ impl Iterator for Tokenizer {
                         self.create_tag_open(false);
                         continue;
                     }
+
+                    if c == '>' {
+                        // Parse error. Ignore the tag.
+                        self.state = State::Data;
+                        continue;
+                    }
+
+                    // Parse error.
+                    self.reconsume = true;
+                    self.state = State::BogusComment;
+                    self.create_comment();
                 }
                 // https://html.spec.whatwg.org/multipage/parsing.html#tag-name-state
                 State::TagName => {
+                    if is_whitespace(c) {
+                        self.state = State::BeforeAttributeName;
+                        continue;
+                    }
+
                     if c == '>' {
                         self.state = State::Data;
                         return self.take_latest_token();
@@ -221,6 +574,167 @@ impl Iterator for Tokenizer {
 
                     self.append_tag_name(c);
                 }
+                // https://html.spec.whatwg.org/multipage/parsing.html#before-attribute-name-state
+                State::BeforeAttributeName => {
+                    if is_whitespace(c) {
+                        continue;
+                    }
+
+                    if c == '/' || c == '>' || self.is_eof() {
+                        self.reconsume = true;
+                        self.state = State::AfterAttributeName;
+                        continue;
+                    }
+
+                    self.reconsume = true;
+                    self.state = State::AttributeName;
+                    self.start_new_attribute();
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#attribute-name-state
+                State::AttributeName => {
+                    if is_whitespace(c) || c == '/' || c == '>' || self.is_eof() {
+                        self.reconsume = true;
+                        self.state = State::AfterAttributeName;
+                        self.drop_duplicate_attribute();
+                        continue;
+                    }
+
+                    if c == '=' {
+                        self.state = State::BeforeAttributeValue;
+                        self.drop_duplicate_attribute();
+                        continue;
+                    }
+
+                    self.append_attribute(c.to_ascii_lowercase(), /*is_name*/ true);
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#after-attribute-name-state
+                State::AfterAttributeName => {
+                    if is_whitespace(c) {
+                        continue;
+                    }
+
+                    if c == '/' {
+                        self.state = State::SelfClosingStartTag;
+                        continue;
+                    }
+
+                    if c == '=' {
+                        self.state = State::BeforeAttributeValue;
+                        continue;
+                    }
+
+                    if c == '>' {
+                        self.state = State::Data;
+                        return self.take_latest_token();
+                    }
+
+                    if self.is_eof() {
+                        // invalid parse error.
+                        return Some(Token::Eof);
+                    }
+
+                    self.reconsume = true;
+                    self.state = State::AttributeName;
+                    self.start_new_attribute();
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#before-attribute-value-state
+                State::BeforeAttributeValue => {
+                    if is_whitespace(c) {
+                        continue;
+                    }
+
+                    if c == '"' {
+                        self.state = State::AttributeValueDoubleQuoted;
+                        continue;
+                    }
+
+                    if c == '\'' {
+                        self.state = State::AttributeValueSingleQuoted;
+                        continue;
+                    }
+
+                    if c == '>' {
+                        // Parse error. Treat it as an empty value.
+                        self.state = State::Data;
+                        return self.take_latest_token();
+                    }
+
+                    self.reconsume = true;
+                    self.state = State::AttributeValueUnquoted;
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#attribute-value-(double-quoted)-state
+                State::AttributeValueDoubleQuoted => {
+                    if c == '"' {
+                        self.state = State::AfterAttributeValueQuoted;
+                        continue;
+                    }
+
+                    if self.is_eof() {
+                        // invalid parse error.
+                        return Some(Token::Eof);
+                    }
+
+                    self.append_attribute(c, /*is_name*/ false);
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#attribute-value-(single-quoted)-state
+                State::AttributeValueSingleQuoted => {
+                    if c == '\'' {
+                        self.state = State::AfterAttributeValueQuoted;
+                        continue;
+                    }
+
+                    if self.is_eof() {
+                        // invalid parse error.
+                        return Some(Token::Eof);
+                    }
+
+                    self.append_attribute(c, /*is_name*/ false);
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#attribute-value-(unquoted)-state
+                State::AttributeValueUnquoted => {
+                    if is_whitespace(c) {
+                        self.state = State::BeforeAttributeName;
+                        continue;
+                    }
+
+                    if c == '>' {
+                        self.state = State::Data;
+                        return self.take_latest_token();
+                    }
+
+                    if self.is_eof() {
+                        // invalid parse error.
+                        return Some(Token::Eof);
+                    }
+
+                    self.append_attribute(c, /*is_name*/ false);
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#after-attribute-value-(quoted)-state
+                State::AfterAttributeValueQuoted => {
+                    if is_whitespace(c) {
+                        self.state = State::BeforeAttributeName;
+                        continue;
+                    }
+
+                    if c == '/' {
+                        self.state = State::SelfClosingStartTag;
+                        continue;
+                    }
+
+                    if c == '>' {
+                        self.state = State::Data;
+                        return self.take_latest_token();
+                    }
+
+                    if self.is_eof() {
+                        // invalid parse error.
+                        return Some(Token::Eof);
+                    }
+
+                    // Parse error. Reconsume in the before attribute name state.
+                    self.reconsume = true;
+                    self.state = State::BeforeAttributeName;
+                }
                 // https://html.spec.whatwg.org/multipage/parsing.html#self-closing-start-tag-state
                 State::SelfClosingStartTag => {
                     if c == '>' {
@@ -234,6 +748,370 @@ impl Iterator for Tokenizer {
                         return Some(Token::Eof);
                     }
                 }
+                // https://html.spec.whatwg.org/multipage/parsing.html#bogus-comment-state
+                State::BogusComment => {
+                    if c == '>' {
+                        self.state = State::Data;
+                        return self.take_latest_token();
+                    }
+
+                    if self.is_eof() {
+                        return self.take_latest_token();
+                    }
+
+                    self.append_comment(c);
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#markup-declaration-open-state
+                State::MarkupDeclarationOpen => {
+                    if self.consume_if_word(c, "--") {
+                        self.state = State::CommentStart;
+                        self.create_comment();
+                        continue;
+                    }
+
+                    if self.consume_if_word(c, "doctype") {
+                        self.state = State::Doctype;
+                        continue;
+                    }
+
+                    // Parse error. CDATA sections are not supported.
+                    self.reconsume = true;
+                    self.state = State::BogusComment;
+                    self.create_comment();
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#comment-start-state
+                State::CommentStart => {
+                    if c == '-' {
+                        self.state = State::CommentStartDash;
+                        continue;
+                    }
+
+                    if c == '>' {
+                        // Parse error.
+                        self.state = State::Data;
+                        return self.take_latest_token();
+                    }
+
+                    self.reconsume = true;
+                    self.state = State::Comment;
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#comment-start-dash-state
+                State::CommentStartDash => {
+                    if c == '-' {
+                        self.state = State::CommentEnd;
+                        continue;
+                    }
+
+                    if c == '>' {
+                        // Parse error.
+                        self.state = State::Data;
+                        return self.take_latest_token();
+                    }
+
+                    if self.is_eof() {
+                        // Parse error.
+                        return self.take_latest_token();
+                    }
+
+                    self.append_comment('-');
+                    self.reconsume = true;
+                    self.state = State::Comment;
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#comment-state
+                State::Comment => {
+                    if c == '-' {
+                        self.state = State::CommentEndDash;
+                        continue;
+                    }
+
+                    if self.is_eof() {
+                        // Parse error.
+                        return self.take_latest_token();
+                    }
+
+                    self.append_comment(c);
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#comment-end-dash-state
+                State::CommentEndDash => {
+                    if c == '-' {
+                        self.state = State::CommentEnd;
+                        continue;
+                    }
+
+                    if self.is_eof() {
+                        // Parse error.
+                        return self.take_latest_token();
+                    }
+
+                    self.append_comment('-');
+                    self.reconsume = true;
+                    self.state = State::Comment;
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#comment-end-state
+                State::CommentEnd => {
+                    if c == '>' {
+                        self.state = State::Data;
+                        return self.take_latest_token();
+                    }
+
+                    if c == '-' {
+                        self.append_comment('-');
+                        continue;
+                    }
+
+                    if self.is_eof() {
+                        // Parse error.
+                        return self.take_latest_token();
+                    }
+
+                    self.append_comment('-');
+                    self.append_comment('-');
+                    self.reconsume = true;
+                    self.state = State::Comment;
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#doctype-state
+                State::Doctype => {
+                    if is_whitespace(c) {
+                        self.state = State::BeforeDoctypeName;
+                        continue;
+                    }
+
+                    if self.is_eof() {
+                        // Parse error.
+                        self.create_doctype();
+                        return self.take_quirks_doctype();
+                    }
+
+                    self.reconsume = true;
+                    self.state = State::BeforeDoctypeName;
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#before-doctype-name-state
+                State::BeforeDoctypeName => {
+                    if is_whitespace(c) {
+                        continue;
+                    }
+
+                    if c == '>' || self.is_eof() {
+                        // Parse error.
+                        self.create_doctype();
+                        return self.take_quirks_doctype();
+                    }
+
+                    self.create_doctype();
+                    self.append_doctype_name(c);
+                    self.state = State::DoctypeName;
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#doctype-name-state
+                State::DoctypeName => {
+                    if is_whitespace(c) {
+                        self.state = State::AfterDoctypeName;
+                        continue;
+                    }
+
+                    if c == '>' {
+                        self.state = State::Data;
+                        return self.take_latest_token();
+                    }
+
+                    if self.is_eof() {
+                        // Parse error.
+                        return self.take_quirks_doctype();
+                    }
+
+                    self.append_doctype_name(c);
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#after-doctype-name-state
+                State::AfterDoctypeName => {
+                    if is_whitespace(c) {
+                        continue;
+                    }
+
+                    if c == '>' {
+                        self.state = State::Data;
+                        return self.take_latest_token();
+                    }
+
+                    if self.is_eof() {
+                        // Parse error.
+                        return self.take_quirks_doctype();
+                    }
+
+                    if self.consume_if_word(c, "public") {
+                        self.state = State::AfterDoctypePublicKeyword;
+                        continue;
+                    }
+
+                    if self.consume_if_word(c, "system") {
+                        self.state = State::AfterDoctypeSystemKeyword;
+                        continue;
+                    }
+
+                    // Parse error.
+                    self.set_force_quirks_flag();
+                    self.reconsume = true;
+                    self.state = State::BogusDoctype;
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#after-doctype-public-keyword-state
+                // https://html.spec.whatwg.org/multipage/parsing.html#before-doctype-public-identifier-state
+                State::AfterDoctypePublicKeyword | State::BeforeDoctypePublicIdentifier => {
+                    if is_whitespace(c) {
+                        self.state = State::BeforeDoctypePublicIdentifier;
+                        continue;
+                    }
+
+                    if c == '"' || c == '\'' {
+                        self.start_public_identifier();
+                        self.state = if c == '"' {
+                            State::DoctypePublicIdentifierDoubleQuoted
+                        } else {
+                            State::DoctypePublicIdentifierSingleQuoted
+                        };
+                        continue;
+                    }
+
+                    if c == '>' || self.is_eof() {
+                        // Parse error.
+                        return self.take_quirks_doctype();
+                    }
+
+                    // Parse error.
+                    self.set_force_quirks_flag();
+                    self.reconsume = true;
+                    self.state = State::BogusDoctype;
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#doctype-public-identifier-(double-quoted)-state
+                // https://html.spec.whatwg.org/multipage/parsing.html#doctype-public-identifier-(single-quoted)-state
+                State::DoctypePublicIdentifierDoubleQuoted
+                | State::DoctypePublicIdentifierSingleQuoted => {
+                    let quote = if self.state == State::DoctypePublicIdentifierDoubleQuoted {
+                        '"'
+                    } else {
+                        '\''
+                    };
+
+                    if c == quote {
+                        self.state = State::AfterDoctypePublicIdentifier;
+                        continue;
+                    }
+
+                    if c == '>' || self.is_eof() {
+                        // Parse error.
+                        return self.take_quirks_doctype();
+                    }
+
+                    self.append_public_identifier(c);
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#after-doctype-public-identifier-state
+                // https://html.spec.whatwg.org/multipage/parsing.html#between-doctype-public-and-system-identifiers-state
+                State::AfterDoctypePublicIdentifier
+                | State::BetweenDoctypePublicAndSystemIdentifiers => {
+                    if is_whitespace(c) {
+                        self.state = State::BetweenDoctypePublicAndSystemIdentifiers;
+                        continue;
+                    }
+
+                    if c == '>' {
+                        self.state = State::Data;
+                        return self.take_latest_token();
+                    }
+
+                    if c == '"' || c == '\'' {
+                        self.start_system_identifier();
+                        self.state = if c == '"' {
+                            State::DoctypeSystemIdentifierDoubleQuoted
+                        } else {
+                            State::DoctypeSystemIdentifierSingleQuoted
+                        };
+                        continue;
+                    }
+
+                    if self.is_eof() {
+                        // Parse error.
+                        return self.take_quirks_doctype();
+                    }
+
+                    // Parse error.
+                    self.set_force_quirks_flag();
+                    self.reconsume = true;
+                    self.state = State::BogusDoctype;
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#after-doctype-system-keyword-state
+                // https://html.spec.whatwg.org/multipage/parsing.html#before-doctype-system-identifier-state
+                State::AfterDoctypeSystemKeyword | State::BeforeDoctypeSystemIdentifier => {
+                    if is_whitespace(c) {
+                        self.state = State::BeforeDoctypeSystemIdentifier;
+                        continue;
+                    }
+
+                    if c == '"' || c == '\'' {
+                        self.start_system_identifier();
+                        self.state = if c == '"' {
+                            State::DoctypeSystemIdentifierDoubleQuoted
+                        } else {
+                            State::DoctypeSystemIdentifierSingleQuoted
+                        };
+                        continue;
+                    }
+
+                    if c == '>' || self.is_eof() {
+                        // Parse error.
+                        return self.take_quirks_doctype();
+                    }
+
+                    // Parse error.
+                    self.set_force_quirks_flag();
+                    self.reconsume = true;
+                    self.state = State::BogusDoctype;
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#doctype-system-identifier-(double-quoted)-state
+                // https://html.spec.whatwg.org/multipage/parsing.html#doctype-system-identifier-(single-quoted)-state
+                State::DoctypeSystemIdentifierDoubleQuoted
+                | State::DoctypeSystemIdentifierSingleQuoted => {
+                    let quote = if self.state == State::DoctypeSystemIdentifierDoubleQuoted {
+                        '"'
+                    } else {
+                        '\''
+                    };
+
+                    if c == quote {
+                        self.state = State::AfterDoctypeSystemIdentifier;
+                        continue;
+                    }
+
+                    if c == '>' || self.is_eof() {
+                        // Parse error.
+                        return self.take_quirks_doctype();
+                    }
+
+                    self.append_system_identifier(c);
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#after-doctype-system-identifier-state
+                State::AfterDoctypeSystemIdentifier => {
+                    if is_whitespace(c) {
+                        continue;
+                    }
+
+                    if c == '>' {
+                        self.state = State::Data;
+                        return self.take_latest_token();
+                    }
+
+                    if self.is_eof() {
+                        // Parse error.
+                        return self.take_quirks_doctype();
+                    }
+
+                    // Parse error. This does not set the force-quirks flag.
+                    self.reconsume = true;
+                    self.state = State::BogusDoctype;
+                }
+                // https://html.spec.whatwg.org/multipage/parsing.html#bogus-doctype-state
+                State::BogusDoctype => {
+                    if c == '>' || self.is_eof() {
+                        self.state = State::Data;
+                        return self.take_latest_token();
+                    }
+                }
             }
         }
     }
@@ -0,0 +1,146 @@
+//! Declarative refreshes, which ask the browser to load a URL again or go to another one after a
+//! delay, e.g. `<meta http-equiv="refresh" content="5;url=next.html">`.
+//! https://html.spec.whatwg.org/multipage/document-lifecycle.html#shared-declarative-refresh-steps
+
+use crate::http::HttpResponse;
+use crate::parser::dom::Document;
+use crate::url::ParsedUrl;
+use alloc::string::String;
+use core::time::Duration;
+use liumlib::time::Instant;
+
+/// The refreshes which are followed in a row at most, so that pages refreshing each other don't
+/// keep the browser loading forever.
+pub const MAX_HOPS: usize = 20;
+
+/// A refresh asked by a page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Refresh {
+    /// The delay in seconds.
+    pub delay: u64,
+    /// The URL to go to, which is relative to the page, or None to load the page again.
+    pub url: Option<String>,
+}
+
+impl Refresh {
+    /// Parses the value of the content attribute of a meta element or the Refresh header, e.g.
+    /// "5; url=next.html". Returns None if it's invalid.
+    pub fn parse(content: &str) -> Option<Self> {
+        let input = content.trim_start_matches(is_whitespace);
+
+        // The delay is an integer, and a fraction after it is ignored.
+        let digits = input.len() - input.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits == 0 && !input.starts_with('.') {
+            return None;
+        }
+        // A delay too long to count is as good as never.
+        let delay = match digits {
+            0 => 0,
+            _ => input[..digits].parse::<u64>().unwrap_or(u64::MAX),
+        };
+        let input = input.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+        if input.is_empty() {
+            return Some(Self { delay, url: None });
+        }
+
+        // The URL is separated by a semicolon, a comma or whitespace.
+        if !input.starts_with(|c: char| c == ';' || c == ',' || is_whitespace(c)) {
+            return None;
+        }
+        let mut input = input.trim_start_matches(is_whitespace);
+        if input.starts_with(&[';', ','][..]) {
+            input = input[1..].trim_start_matches(is_whitespace);
+        }
+
+        // "url=" before the URL is optional.
+        if input.len() >= 3 && input[..3].eq_ignore_ascii_case("url") {
+            let rest = input[3..].trim_start_matches(is_whitespace);
+            if let Some(rest) = rest.strip_prefix('=') {
+                input = rest.trim_start_matches(is_whitespace);
+            }
+        }
+
+        // The URL may be quoted, and the quote ends it.
+        let url = match input.chars().next() {
+            Some(quote @ '"') | Some(quote @ '\'') => {
+                let rest = &input[1..];
+                match rest.find(quote) {
+                    Some(i) => &rest[..i],
+                    None => rest,
+                }
+            }
+            _ => input.trim_end_matches(is_whitespace),
+        };
+        Some(Self {
+            delay,
+            url: if url.is_empty() {
+                None
+            } else {
+                Some(String::from(url))
+            },
+        })
+    }
+
+    /// Returns the refresh asked by `response` or `document`. The Refresh header is used if the
+    /// response has it, and otherwise the first meta element with http-equiv="refresh" and a
+    /// valid content is used.
+    pub fn find(response: Option<&HttpResponse>, document: &Document) -> Option<Self> {
+        if let Some(refresh) = response
+            .and_then(|r| r.header("Refresh"))
+            .and_then(|value| Self::parse(&value))
+        {
+            return Some(refresh);
+        }
+        document
+            .descendants(document.root())
+            .filter_map(|id| document.node(id).element())
+            .filter(|e| e.tag_name() == "meta")
+            .filter(|e| match e.get_attribute("http-equiv") {
+                Some(value) => value.eq_ignore_ascii_case("refresh"),
+                None => false,
+            })
+            .find_map(|e| Self::parse(&e.get_attribute("content")?))
+    }
+}
+
+/// A refresh of a tab which waits for its time.
+#[derive(Debug, Clone)]
+pub struct ScheduledRefresh {
+    pub url: ParsedUrl,
+    /// When it's due, or None if the delay is too long to count.
+    pub due: Option<Instant>,
+    /// The number of the refreshes in a row which led to the page.
+    pub hops: usize,
+}
+
+impl ScheduledRefresh {
+    /// Schedules `refresh` of the page at `base`, which is the `hops`-th refresh in a row, or
+    /// returns None if its URL is invalid.
+    pub fn new(refresh: &Refresh, base: &ParsedUrl, hops: usize) -> Option<Self> {
+        let url = match &refresh.url {
+            Some(url) => base.join(url)?,
+            None => base.clone(),
+        };
+        Some(Self {
+            url,
+            due: Instant::now().checked_add(Duration::from_secs(refresh.delay)),
+            hops,
+        })
+    }
+
+    /// Returns true if the delay has passed.
+    pub fn is_due(&self) -> bool {
+        matches!(self.due, Some(due) if Instant::now() >= due)
+    }
+
+    /// Returns the milliseconds until it's due, which is 0 if it's due, or None if it never is.
+    pub fn until_due(&self) -> Option<u64> {
+        let due = self.due?;
+        Some(due.duration_since(Instant::now()).as_millis() as u64)
+    }
+}
+
+/// https://infra.spec.whatwg.org/#ascii-whitespace
+fn is_whitespace(c: char) -> bool {
+    c == '\t' || c == '\n' || c == '\x0c' || c == '\r' || c == ' '
+}
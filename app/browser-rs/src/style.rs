@@ -0,0 +1,9 @@
+//! Style resolution: the cascade and the computed values of elements.
+//! https://www.w3.org/TR/css-cascade-4/
+
+pub mod cascade;
+pub mod computed;
+pub mod loader;
+pub mod shorthand;
+pub mod ua;
+pub mod values;
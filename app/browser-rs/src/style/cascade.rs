@@ -0,0 +1,180 @@
+//! This is a part of "6. Cascading" in CSS Cascading and Inheritance Level 4.
+//! https://www.w3.org/TR/css-cascade-4/#cascading
+
+use crate::parser::css::cssom::*;
+use crate::parser::css::selector::*;
+use crate::parser::dom::*;
+use crate::style::shorthand::*;
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// https://www.w3.org/TR/css-cascade-4/#cascading-origins
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Origin {
+    /// https://www.w3.org/TR/css-cascade-4/#cascade-origin-ua
+    UserAgent,
+    /// https://www.w3.org/TR/css-cascade-4/#cascade-origin-author
+    Author,
+}
+
+/// The sort key of a declaration in the cascade. A declaration with a greater key wins.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Priority {
+    /// https://www.w3.org/TR/css-cascade-4/#cascade-origin
+    origin_and_importance: u8,
+    /// True for the declarations in the style attribute.
+    /// https://www.w3.org/TR/css-cascade-4/#style-attr
+    element_attached: bool,
+    /// https://www.w3.org/TR/css-cascade-4/#cascade-specificity
+    specificity: Specificity,
+    /// https://www.w3.org/TR/css-cascade-4/#cascade-order
+    order: usize,
+}
+
+/// Returns the rank of the origin and importance. Normal user-agent declarations are the weakest,
+/// then normal author declarations, important author declarations, and important user-agent
+/// declarations.
+/// https://www.w3.org/TR/css-cascade-4/#cascade-origin
+fn origin_and_importance(origin: Origin, important: bool) -> u8 {
+    match (origin, important) {
+        (Origin::UserAgent, false) => 0,
+        (Origin::Author, false) => 1,
+        (Origin::Author, true) => 2,
+        (Origin::UserAgent, true) => 3,
+    }
+}
+
+/// Returns the winning declaration of each longhand property for the element `id`. `sheets` are in the
+/// order of appearance, which breaks ties between declarations with the same origin, importance
+/// and specificity. The declarations in the style attribute of the element are author
+/// declarations which win over any rule of the same importance.
+/// https://www.w3.org/TR/css-cascade-4/#cascade-sort
+#[allow(dead_code)]
+pub fn cascade(
+    document: &Document,
+    id: NodeId,
+    sheets: &[(Origin, &StyleSheet)],
+) -> BTreeMap<String, Declaration> {
+    let mut winners: BTreeMap<String, (Priority, Declaration)> = BTreeMap::new();
+    let mut order = 0;
+
+    // "The UA may choose to honor presentational hints in a source document's markup [...] as
+    // author-level, zero-specificity declarations that are treated as preceding all other author
+    // declarations".
+    // https://www.w3.org/TR/css-cascade-4/#preshint
+    for declaration in presentational_hints(document, id) {
+        let priority = Priority {
+            origin_and_importance: origin_and_importance(Origin::Author, false),
+            element_attached: false,
+            specificity: Specificity(0, 0, 0),
+            order,
+        };
+        order += 1;
+        apply(&mut winners, priority, &declaration);
+    }
+
+    for (origin, sheet) in sheets {
+        for rule in sheet.rules() {
+            let specificity = match matching_specificity(document, id, rule.selectors()) {
+                Some(s) => s,
+                None => {
+                    order += rule.declarations().len();
+                    continue;
+                }
+            };
+
+            for declaration in rule.declarations() {
+                let priority = Priority {
+                    origin_and_importance: origin_and_importance(*origin, declaration.important()),
+                    element_attached: false,
+                    specificity,
+                    order,
+                };
+                order += 1;
+                apply(&mut winners, priority, declaration);
+            }
+        }
+    }
+
+    if let Some(element) = document.node(id).element() {
+        for declaration in element.inline_style() {
+            let priority = Priority {
+                origin_and_importance: origin_and_importance(
+                    Origin::Author,
+                    declaration.important(),
+                ),
+                element_attached: true,
+                specificity: Specificity(0, 0, 0),
+                order,
+            };
+            order += 1;
+            apply(&mut winners, priority, &declaration);
+        }
+    }
+
+    winners
+        .into_iter()
+        .map(|(name, (_, declaration))| (name, declaration))
+        .collect()
+}
+
+/// Returns the declarations which the attributes of the element `id` map to. Only the dimension
+/// attributes of img are supported.
+/// https://html.spec.whatwg.org/multipage/rendering.html#presentational-hints
+fn presentational_hints(document: &Document, id: NodeId) -> Vec<Declaration> {
+    let element = match document.node(id).element() {
+        Some(e) if e.tag_name() == "img" => e,
+        _ => return Vec::new(),
+    };
+    // "The width and height attributes on img [...] map to the dimension properties width and
+    // height on the element respectively."
+    // https://html.spec.whatwg.org/multipage/rendering.html#attributes-for-embedded-content-and-images
+    let mut css = String::new();
+    for name in &["width", "height"] {
+        if let Some(value) = element
+            .get_attribute(name)
+            .and_then(|v| parse_dimension_value(&v))
+        {
+            css.push_str(&format!("{}: {};", name, value));
+        }
+    }
+    parse_declaration_list(css)
+}
+
+/// Parses an attribute value as a length in px or a percentage, and returns it in CSS. Trailing
+/// garbage is ignored, e.g. "10px" is 10px.
+/// https://html.spec.whatwg.org/multipage/common-microsyntaxes.html#rules-for-parsing-dimension-values
+fn parse_dimension_value(value: &str) -> Option<String> {
+    let value = value.trim_start_matches(|c: char| c.is_ascii_whitespace());
+    let end = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let number = value[..end].parse::<f64>().ok()?;
+    if value[end..].starts_with('%') {
+        Some(format!("{}%", number))
+    } else {
+        Some(format!("{}px", number))
+    }
+}
+
+/// Makes `declaration` the winner of its property if it has a higher priority than the current
+/// winner. A shorthand declaration is expanded into its longhands, which have the same priority.
+fn apply(
+    winners: &mut BTreeMap<String, (Priority, Declaration)>,
+    priority: Priority,
+    declaration: &Declaration,
+) {
+    for longhand in expand_shorthand(declaration) {
+        let wins = match winners.get(longhand.name()) {
+            Some((p, _)) => priority > *p,
+            None => true,
+        };
+        if wins {
+            winners.insert(String::from(longhand.name()), (priority, longhand));
+        }
+    }
+}
@@ -0,0 +1,828 @@
+//! Computed values of elements.
+//! https://www.w3.org/TR/css-cascade-4/#computed
+
+use crate::parser::css::cssom::*;
+use crate::parser::css::tokenizer::*;
+use crate::parser::dom::*;
+use crate::style::cascade::*;
+use crate::style::ua::*;
+use crate::style::values::*;
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// https://www.w3.org/TR/css-display-3/#the-display-properties
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Display {
+    Inline,
+    Block,
+    /// A block box with a marker box.
+    /// https://www.w3.org/TR/css-display-3/#list-items
+    ListItem,
+    /// https://www.w3.org/TR/CSS2/tables.html#table-display
+    Table,
+    /// `table-row-group`, `table-header-group` and `table-footer-group`, which are not
+    /// distinguished.
+    TableRowGroup,
+    TableRow,
+    TableCell,
+    /// The element and its descendants generate no boxes.
+    /// https://www.w3.org/TR/css-display-3/#valdef-display-none
+    None,
+}
+
+/// https://www.w3.org/TR/css-text-decor-3/#text-decoration-line-property
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextDecoration {
+    None,
+    Underline,
+}
+
+/// https://www.w3.org/TR/css-backgrounds-3/#border-style
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum BorderStyle {
+    #[default]
+    None,
+    Hidden,
+    Dotted,
+    Dashed,
+    Solid,
+    Double,
+}
+
+/// https://www.w3.org/TR/css-lists-3/#text-markers
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ListStyleType {
+    None,
+    Disc,
+    Circle,
+    Square,
+    Decimal,
+    LowerAlpha,
+    UpperAlpha,
+}
+
+/// https://www.w3.org/TR/CSS2/visuren.html#choose-position
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Position {
+    Static,
+    /// The box is laid out in normal flow and then offset by `top`, `right`, `bottom` and
+    /// `left`.
+    Relative,
+    /// The box is taken out of normal flow and placed in its containing block by `top`, `right`,
+    /// `bottom` and `left`.
+    Absolute,
+}
+
+/// Values for the four sides of a box, e.g. margins.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Edges<T> {
+    pub top: T,
+    pub right: T,
+    pub bottom: T,
+    pub left: T,
+}
+
+#[allow(dead_code)]
+impl Edges<Length> {
+    /// Returns the sides in px. Percentages are resolved against `percentage_basis`, which is the
+    /// width of the containing block for margins and paddings.
+    /// https://www.w3.org/TR/CSS2/box.html#margin-properties
+    pub fn to_px(self, percentage_basis: f64) -> Edges<f64> {
+        Edges {
+            top: self.top.to_px(percentage_basis),
+            right: self.right.to_px(percentage_basis),
+            bottom: self.bottom.to_px(percentage_basis),
+            left: self.left.to_px(percentage_basis),
+        }
+    }
+
+    fn absolutize(&mut self, font_size: f64, root_font_size: f64) {
+        self.top = self.top.absolutize(font_size, root_font_size);
+        self.right = self.right.absolutize(font_size, root_font_size);
+        self.bottom = self.bottom.absolutize(font_size, root_font_size);
+        self.left = self.left.absolutize(font_size, root_font_size);
+    }
+}
+
+/// The computed values of the properties of an element.
+/// https://www.w3.org/TR/css-cascade-4/#computed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputedStyle {
+    display: Display,
+    color: Color,
+    background_color: Color,
+    /// In px.
+    font_size: f64,
+    /// A number from 1 to 1000, where 400 is normal and 700 is bold.
+    font_weight: u16,
+    text_decoration: TextDecoration,
+    /// https://www.w3.org/TR/css-lists-3/#propdef-list-style-type
+    list_style_type: ListStyleType,
+    /// Font-relative lengths are converted to px. Percentages and `auto` are resolved during
+    /// layout.
+    margin: Edges<Length>,
+    padding: Edges<Length>,
+    /// https://www.w3.org/TR/CSS2/visudet.html#the-width-property
+    width: Length,
+    /// https://www.w3.org/TR/CSS2/visudet.html#the-height-property
+    height: Length,
+    /// Percentages are not allowed.
+    border_width: Edges<Length>,
+    border_style: Edges<BorderStyle>,
+    /// None is `currentcolor`, which is the value of the color property.
+    /// https://www.w3.org/TR/css-color-4/#currentcolor-color
+    border_color: Edges<Option<Color>>,
+    position: Position,
+    /// `top`, `right`, `bottom` and `left`, which are `auto` initially.
+    /// https://www.w3.org/TR/CSS2/visuren.html#position-props
+    inset: Edges<Length>,
+}
+
+/// The width of `medium`, which is the initial value of border widths.
+/// https://www.w3.org/TR/css-backgrounds-3/#typedef-line-width
+const MEDIUM_BORDER_WIDTH: f64 = 3.0;
+
+/// https://www.w3.org/TR/css-fonts-4/#font-weight-absolute-values
+pub const FONT_WEIGHT_NORMAL: u16 = 400;
+pub const FONT_WEIGHT_BOLD: u16 = 700;
+
+/// The supported longhand properties and whether each is inherited.
+/// https://www.w3.org/TR/css-cascade-4/#inherited-property
+const PROPERTIES: &[(&str, bool)] = &[
+    ("display", false),
+    ("color", true),
+    ("background-color", false),
+    ("font-size", true),
+    ("font-weight", true),
+    ("text-decoration", false),
+    ("list-style-type", true),
+    ("width", false),
+    ("height", false),
+    ("margin-top", false),
+    ("margin-right", false),
+    ("margin-bottom", false),
+    ("margin-left", false),
+    ("padding-top", false),
+    ("padding-right", false),
+    ("padding-bottom", false),
+    ("padding-left", false),
+    ("border-top-width", false),
+    ("border-top-style", false),
+    ("border-top-color", false),
+    ("border-right-width", false),
+    ("border-right-style", false),
+    ("border-right-color", false),
+    ("border-bottom-width", false),
+    ("border-bottom-style", false),
+    ("border-bottom-color", false),
+    ("border-left-width", false),
+    ("border-left-style", false),
+    ("border-left-color", false),
+    ("position", false),
+    ("top", false),
+    ("right", false),
+    ("bottom", false),
+    ("left", false),
+];
+
+/// Returns true if `name` is an inherited property.
+pub fn is_inherited(name: &str) -> bool {
+    PROPERTIES
+        .iter()
+        .any(|(n, inherited)| *n == name && *inherited)
+}
+
+#[allow(dead_code)]
+impl ComputedStyle {
+    /// Returns the style where every property has its initial value.
+    /// https://www.w3.org/TR/css-cascade-4/#initial-values
+    pub fn initial() -> Self {
+        Self {
+            display: Display::Inline,
+            color: Color::BLACK,
+            background_color: Color::TRANSPARENT,
+            font_size: DEFAULT_FONT_SIZE,
+            font_weight: FONT_WEIGHT_NORMAL,
+            text_decoration: TextDecoration::None,
+            list_style_type: ListStyleType::Disc,
+            margin: Edges::default(),
+            width: Length::Auto,
+            height: Length::Auto,
+            padding: Edges::default(),
+            border_width: Edges {
+                top: Length::Px(MEDIUM_BORDER_WIDTH),
+                right: Length::Px(MEDIUM_BORDER_WIDTH),
+                bottom: Length::Px(MEDIUM_BORDER_WIDTH),
+                left: Length::Px(MEDIUM_BORDER_WIDTH),
+            },
+            border_style: Edges::default(),
+            border_color: Edges::default(),
+            position: Position::Static,
+            inset: Edges {
+                top: Length::Auto,
+                right: Length::Auto,
+                bottom: Length::Auto,
+                left: Length::Auto,
+            },
+        }
+    }
+
+    /// Returns the style of a child of an element with `parent` style before its own declarations
+    /// apply. Inherited properties take the parent's values. This is also the style of an
+    /// anonymous box.
+    /// https://www.w3.org/TR/css-cascade-4/#inheriting
+    /// https://www.w3.org/TR/css-display-3/#anonymous
+    pub fn inherit_from(parent: &ComputedStyle) -> Self {
+        let mut style = Self::initial();
+        for (name, inherited) in PROPERTIES {
+            if *inherited {
+                style.copy_property(name, parent);
+            }
+        }
+        // text-decoration is not inherited, but decorations are drawn across the text of the
+        // descendants. Carry it down so that the text of e.g. `<a><b>x</b></a>` is underlined.
+        // https://www.w3.org/TR/css-text-decor-3/#line-decoration
+        style.text_decoration = parent.text_decoration;
+        style
+    }
+
+    /// Sets the property `name` to the value of the property in `from`.
+    fn copy_property(&mut self, name: &str, from: &ComputedStyle) {
+        match name {
+            "display" => self.display = from.display,
+            "color" => self.color = from.color,
+            "background-color" => self.background_color = from.background_color,
+            "font-size" => self.font_size = from.font_size,
+            "font-weight" => self.font_weight = from.font_weight,
+            "text-decoration" => self.text_decoration = from.text_decoration,
+            "list-style-type" => self.list_style_type = from.list_style_type,
+            "width" => self.width = from.width,
+            "height" => self.height = from.height,
+            "margin-top" => self.margin.top = from.margin.top,
+            "margin-right" => self.margin.right = from.margin.right,
+            "margin-bottom" => self.margin.bottom = from.margin.bottom,
+            "margin-left" => self.margin.left = from.margin.left,
+            "padding-top" => self.padding.top = from.padding.top,
+            "padding-right" => self.padding.right = from.padding.right,
+            "padding-bottom" => self.padding.bottom = from.padding.bottom,
+            "padding-left" => self.padding.left = from.padding.left,
+            "border-top-width" => self.border_width.top = from.border_width.top,
+            "border-top-style" => self.border_style.top = from.border_style.top,
+            "border-top-color" => self.border_color.top = from.border_color.top,
+            "border-right-width" => self.border_width.right = from.border_width.right,
+            "border-right-style" => self.border_style.right = from.border_style.right,
+            "border-right-color" => self.border_color.right = from.border_color.right,
+            "border-bottom-width" => self.border_width.bottom = from.border_width.bottom,
+            "border-bottom-style" => self.border_style.bottom = from.border_style.bottom,
+            "border-bottom-color" => self.border_color.bottom = from.border_color.bottom,
+            "border-left-width" => self.border_width.left = from.border_width.left,
+            "border-left-style" => self.border_style.left = from.border_style.left,
+            "border-left-color" => self.border_color.left = from.border_color.left,
+            "position" => self.position = from.position,
+            "top" => self.inset.top = from.inset.top,
+            "right" => self.inset.right = from.inset.right,
+            "bottom" => self.inset.bottom = from.inset.bottom,
+            "left" => self.inset.left = from.inset.left,
+            _ => {}
+        }
+    }
+
+    /// Applies a declaration whose value is a CSS-wide keyword. Returns false if the value is not
+    /// one.
+    /// https://www.w3.org/TR/css-cascade-4/#defaulting-keywords
+    fn apply_css_wide_keyword(
+        &mut self,
+        declaration: &Declaration,
+        parent: &ComputedStyle,
+    ) -> bool {
+        let keyword = match declaration.value().as_slice() {
+            [CssToken::Ident(k)] => k.to_ascii_lowercase(),
+            _ => return false,
+        };
+        let name = declaration.name();
+        match keyword.as_str() {
+            "initial" => self.copy_property(name, &Self::initial()),
+            "inherit" => self.copy_property(name, parent),
+            // "If the cascaded value of a property is the unset keyword, then if it is an
+            // inherited property, this is treated as inherit, and if it is not, this is treated as
+            // initial."
+            "unset" => {
+                if is_inherited(name) {
+                    self.copy_property(name, parent);
+                } else {
+                    self.copy_property(name, &Self::initial());
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Sets the property of the declaration. Unknown properties and invalid values are ignored.
+    /// `root_font_size` is the font size of the root element, which `rem` refers to.
+    fn apply(&mut self, declaration: &Declaration, parent: &ComputedStyle, root_font_size: f64) {
+        if self.apply_css_wide_keyword(declaration, parent) {
+            return;
+        }
+
+        let value = declaration.value().as_slice();
+        match declaration.name() {
+            "display" => {
+                if let Some(d) = parse_display(value) {
+                    self.display = d;
+                }
+            }
+            "color" => {
+                if let Some(c) = parse_color(value) {
+                    self.color = c;
+                }
+            }
+            "background-color" => {
+                if let Some(c) = parse_color(value) {
+                    self.background_color = c;
+                }
+            }
+            "font-size" => {
+                // Font-relative lengths and percentages of font-size refer to the font size of the
+                // parent element.
+                // https://www.w3.org/TR/css-fonts-4/#font-size-prop
+                match parse_length(value) {
+                    Some(Length::Percentage(p)) if p >= 0.0 => {
+                        self.font_size = parent.font_size * p / 100.0
+                    }
+                    Some(l) if !l.is_negative() => {
+                        self.font_size = l.absolutize(parent.font_size, root_font_size).to_px(0.0)
+                    }
+                    _ => {}
+                }
+            }
+            "font-weight" => {
+                if let Some(w) = parse_font_weight(value) {
+                    self.font_weight = w;
+                }
+            }
+            "text-decoration" => {
+                if let Some(d) = parse_text_decoration(value) {
+                    self.text_decoration = d;
+                }
+            }
+            "list-style-type" => {
+                if let Some(t) = parse_list_style_type(value) {
+                    self.list_style_type = t;
+                }
+            }
+            // "Negative values for width and height are illegal."
+            "width" => set_length_or_auto(&mut self.width, value, false),
+            "height" => set_length_or_auto(&mut self.height, value, false),
+            "margin-top" => set_length_or_auto(&mut self.margin.top, value, true),
+            "margin-right" => set_length_or_auto(&mut self.margin.right, value, true),
+            "margin-bottom" => set_length_or_auto(&mut self.margin.bottom, value, true),
+            "margin-left" => set_length_or_auto(&mut self.margin.left, value, true),
+            // "Negative values for padding properties are invalid."
+            "padding-top" => set_length(&mut self.padding.top, value, false),
+            "padding-right" => set_length(&mut self.padding.right, value, false),
+            "padding-bottom" => set_length(&mut self.padding.bottom, value, false),
+            "padding-left" => set_length(&mut self.padding.left, value, false),
+            "border-top-width" => {
+                if let Some(w) = parse_line_width(value) {
+                    self.border_width.top = w;
+                }
+            }
+            "border-top-style" => {
+                if let Some(st) = parse_border_style(value) {
+                    self.border_style.top = st;
+                }
+            }
+            "border-top-color" => {
+                if let Some(c) = parse_border_color(value) {
+                    self.border_color.top = c;
+                }
+            }
+            "border-right-width" => {
+                if let Some(w) = parse_line_width(value) {
+                    self.border_width.right = w;
+                }
+            }
+            "border-right-style" => {
+                if let Some(st) = parse_border_style(value) {
+                    self.border_style.right = st;
+                }
+            }
+            "border-right-color" => {
+                if let Some(c) = parse_border_color(value) {
+                    self.border_color.right = c;
+                }
+            }
+            "border-bottom-width" => {
+                if let Some(w) = parse_line_width(value) {
+                    self.border_width.bottom = w;
+                }
+            }
+            "border-bottom-style" => {
+                if let Some(st) = parse_border_style(value) {
+                    self.border_style.bottom = st;
+                }
+            }
+            "border-bottom-color" => {
+                if let Some(c) = parse_border_color(value) {
+                    self.border_color.bottom = c;
+                }
+            }
+            "border-left-width" => {
+                if let Some(w) = parse_line_width(value) {
+                    self.border_width.left = w;
+                }
+            }
+            "border-left-style" => {
+                if let Some(st) = parse_border_style(value) {
+                    self.border_style.left = st;
+                }
+            }
+            "border-left-color" => {
+                if let Some(c) = parse_border_color(value) {
+                    self.border_color.left = c;
+                }
+            }
+            "position" => {
+                if let Some(p) = parse_position(value) {
+                    self.position = p;
+                }
+            }
+            "top" => set_length_or_auto(&mut self.inset.top, value, true),
+            "right" => set_length_or_auto(&mut self.inset.right, value, true),
+            "bottom" => set_length_or_auto(&mut self.inset.bottom, value, true),
+            "left" => set_length_or_auto(&mut self.inset.left, value, true),
+            _ => {}
+        }
+    }
+
+    /// Converts the font-relative lengths to px once the font size of the element is known.
+    fn absolutize_lengths(&mut self, root_font_size: f64) {
+        self.margin.absolutize(self.font_size, root_font_size);
+        self.width = self.width.absolutize(self.font_size, root_font_size);
+        self.height = self.height.absolutize(self.font_size, root_font_size);
+        self.padding.absolutize(self.font_size, root_font_size);
+        self.border_width.absolutize(self.font_size, root_font_size);
+        self.inset.absolutize(self.font_size, root_font_size);
+    }
+
+    /// "if 'position' has the value 'absolute' or 'fixed', the box is absolutely positioned [...]
+    /// and display is set according to the table below."
+    /// https://www.w3.org/TR/CSS2/visuren.html#dis-pos-flo
+    fn blockify_absolutely_positioned(&mut self) {
+        if self.position != Position::Absolute {
+            return;
+        }
+        self.display = match self.display {
+            Display::Inline | Display::TableRowGroup | Display::TableRow | Display::TableCell => {
+                Display::Block
+            }
+            d => d,
+        };
+    }
+
+    pub fn display(&self) -> Display {
+        self.display
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    pub fn background_color(&self) -> Color {
+        self.background_color
+    }
+
+    pub fn font_size(&self) -> f64 {
+        self.font_size
+    }
+
+    pub fn font_weight(&self) -> u16 {
+        self.font_weight
+    }
+
+    pub fn text_decoration(&self) -> TextDecoration {
+        self.text_decoration
+    }
+
+    pub fn list_style_type(&self) -> ListStyleType {
+        self.list_style_type
+    }
+
+    /// Removes text decorations, which don't propagate to e.g. list markers.
+    /// https://www.w3.org/TR/css-text-decor-3/#line-decoration
+    pub fn clear_text_decoration(&mut self) {
+        self.text_decoration = TextDecoration::None;
+    }
+
+    pub fn margin(&self) -> Edges<Length> {
+        self.margin
+    }
+
+    pub fn padding(&self) -> Edges<Length> {
+        self.padding
+    }
+
+    pub fn width(&self) -> Length {
+        self.width
+    }
+
+    pub fn height(&self) -> Length {
+        self.height
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    pub fn inset(&self) -> Edges<Length> {
+        self.inset
+    }
+
+    /// Returns the used border widths in px. A side whose style is `none` or `hidden` has no
+    /// border.
+    /// https://www.w3.org/TR/css-backgrounds-3/#border-width
+    pub fn border_width(&self) -> Edges<f64> {
+        let width = |w: Length, style: BorderStyle| match style {
+            BorderStyle::None | BorderStyle::Hidden => 0.0,
+            _ => w.to_px(0.0),
+        };
+        Edges {
+            top: width(self.border_width.top, self.border_style.top),
+            right: width(self.border_width.right, self.border_style.right),
+            bottom: width(self.border_width.bottom, self.border_style.bottom),
+            left: width(self.border_width.left, self.border_style.left),
+        }
+    }
+
+    pub fn border_style(&self) -> Edges<BorderStyle> {
+        self.border_style
+    }
+
+    /// Returns the border colors with `currentcolor` resolved.
+    pub fn border_color(&self) -> Edges<Color> {
+        Edges {
+            top: self.border_color.top.unwrap_or(self.color),
+            right: self.border_color.right.unwrap_or(self.color),
+            bottom: self.border_color.bottom.unwrap_or(self.color),
+            left: self.border_color.left.unwrap_or(self.color),
+        }
+    }
+}
+
+/// https://www.w3.org/TR/css-display-3/#the-display-properties
+fn parse_display(value: &[CssToken]) -> Option<Display> {
+    match value {
+        [CssToken::Ident(v)] => match v.to_ascii_lowercase().as_str() {
+            "block" => Some(Display::Block),
+            "inline" => Some(Display::Inline),
+            "list-item" => Some(Display::ListItem),
+            "table" => Some(Display::Table),
+            "table-row-group" | "table-header-group" | "table-footer-group" => {
+                Some(Display::TableRowGroup)
+            }
+            "table-row" => Some(Display::TableRow),
+            "table-cell" => Some(Display::TableCell),
+            "none" => Some(Display::None),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// https://www.w3.org/TR/css-lists-3/#propdef-list-style-type
+fn parse_list_style_type(value: &[CssToken]) -> Option<ListStyleType> {
+    match value {
+        [CssToken::Ident(v)] => match v.to_ascii_lowercase().as_str() {
+            "none" => Some(ListStyleType::None),
+            "disc" => Some(ListStyleType::Disc),
+            "circle" => Some(ListStyleType::Circle),
+            "square" => Some(ListStyleType::Square),
+            "decimal" => Some(ListStyleType::Decimal),
+            "lower-alpha" | "lower-latin" => Some(ListStyleType::LowerAlpha),
+            "upper-alpha" | "upper-latin" => Some(ListStyleType::UpperAlpha),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-weight-prop
+fn parse_font_weight(value: &[CssToken]) -> Option<u16> {
+    match value {
+        [CssToken::Ident(v)] => match v.to_ascii_lowercase().as_str() {
+            "normal" => Some(FONT_WEIGHT_NORMAL),
+            "bold" => Some(FONT_WEIGHT_BOLD),
+            _ => None,
+        },
+        [CssToken::Number(n)] if *n >= 1.0 && *n <= 1000.0 => Some(*n as u16),
+        _ => None,
+    }
+}
+
+/// https://www.w3.org/TR/css-text-decor-3/#text-decoration-property
+fn parse_text_decoration(value: &[CssToken]) -> Option<TextDecoration> {
+    match value {
+        [CssToken::Ident(v)] => match v.to_ascii_lowercase().as_str() {
+            "none" => Some(TextDecoration::None),
+            "underline" => Some(TextDecoration::Underline),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// https://www.w3.org/TR/css-backgrounds-3/#typedef-line-width
+pub fn parse_line_width(value: &[CssToken]) -> Option<Length> {
+    match value {
+        [CssToken::Ident(v)] => match v.to_ascii_lowercase().as_str() {
+            "thin" => Some(Length::Px(1.0)),
+            "medium" => Some(Length::Px(MEDIUM_BORDER_WIDTH)),
+            "thick" => Some(Length::Px(5.0)),
+            _ => None,
+        },
+        _ => match parse_length(value) {
+            Some(Length::Percentage(_)) => None,
+            Some(l) if !l.is_negative() => Some(l),
+            _ => None,
+        },
+    }
+}
+
+/// https://www.w3.org/TR/css-backgrounds-3/#typedef-line-style
+pub fn parse_border_style(value: &[CssToken]) -> Option<BorderStyle> {
+    match value {
+        [CssToken::Ident(v)] => match v.to_ascii_lowercase().as_str() {
+            "none" => Some(BorderStyle::None),
+            "hidden" => Some(BorderStyle::Hidden),
+            "dotted" => Some(BorderStyle::Dotted),
+            "dashed" => Some(BorderStyle::Dashed),
+            "solid" => Some(BorderStyle::Solid),
+            "double" => Some(BorderStyle::Double),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// https://www.w3.org/TR/CSS2/visuren.html#choose-position
+fn parse_position(value: &[CssToken]) -> Option<Position> {
+    match value {
+        [CssToken::Ident(v)] => match v.to_ascii_lowercase().as_str() {
+            "static" => Some(Position::Static),
+            "relative" => Some(Position::Relative),
+            "absolute" => Some(Position::Absolute),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Parses a border color. Returns Some(None) for `currentcolor`.
+/// https://www.w3.org/TR/css-backgrounds-3/#border-color
+pub fn parse_border_color(value: &[CssToken]) -> Option<Option<Color>> {
+    match value {
+        [CssToken::Ident(v)] if v.eq_ignore_ascii_case("currentcolor") => Some(None),
+        _ => parse_color(value).map(Some),
+    }
+}
+
+/// Sets `target` to the length in `value` if it's valid.
+fn set_length(target: &mut Length, value: &[CssToken], allow_negative: bool) {
+    match parse_length(value) {
+        Some(l) if allow_negative || !l.is_negative() => *target = l,
+        _ => {}
+    }
+}
+
+/// Sets `target` to the length or `auto` in `value` if it's valid.
+fn set_length_or_auto(target: &mut Length, value: &[CssToken], allow_negative: bool) {
+    match parse_length_or_auto(value) {
+        Some(l) if allow_negative || !l.is_negative() => *target = l,
+        _ => {}
+    }
+}
+
+/// The computed styles of all the elements in a document.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StyleMap {
+    styles: BTreeMap<NodeId, ComputedStyle>,
+}
+
+#[allow(dead_code)]
+impl StyleMap {
+    /// Returns the computed style of the element `id`, or None if `id` is not an element in the
+    /// tree.
+    pub fn get(&self, id: NodeId) -> Option<&ComputedStyle> {
+        self.styles.get(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.styles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.styles.is_empty()
+    }
+}
+
+/// The colors which the user prefers pages to be shown in.
+/// https://www.w3.org/TR/mediaqueries-5/#prefers-color-scheme
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColorScheme {
+    Light,
+    Dark,
+}
+
+impl ColorScheme {
+    /// Parses "light" or "dark" in any case.
+    pub fn parse(s: &str) -> Option<Self> {
+        if s.eq_ignore_ascii_case("light") {
+            Some(ColorScheme::Light)
+        } else if s.eq_ignore_ascii_case("dark") {
+            Some(ColorScheme::Dark)
+        } else {
+            None
+        }
+    }
+}
+
+/// The size of the area which the document is rendered in, in px, and the preferences of the
+/// user for it. Media queries are evaluated against it.
+/// https://www.w3.org/TR/CSS2/visuren.html#viewport
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Viewport {
+    pub width: f64,
+    pub height: f64,
+    pub color_scheme: ColorScheme,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            width: 600.0,
+            height: 400.0,
+            color_scheme: ColorScheme::Light,
+        }
+    }
+}
+
+/// Computes the style of every element in `document` from the user-agent stylesheet and the
+/// stylesheets of the document. Rules in `@media` rules apply if they match `viewport`.
+#[allow(dead_code)]
+pub fn compute_styles(document: &Document, viewport: Viewport) -> StyleMap {
+    let ua = user_agent_style_sheet();
+    let author: Vec<StyleSheet> = document
+        .style_sheets()
+        .iter()
+        .map(|s| s.evaluate_media(viewport))
+        .collect();
+    let mut sheets: Vec<(Origin, &StyleSheet)> = vec![(Origin::UserAgent, &ua)];
+    sheets.extend(author.iter().map(|s| (Origin::Author, s)));
+
+    let mut map = StyleMap::default();
+    compute_subtree(
+        document,
+        document.root(),
+        &ComputedStyle::initial(),
+        DEFAULT_FONT_SIZE,
+        &sheets,
+        &mut map,
+    );
+    map
+}
+
+/// Computes the styles of the children of `id`, whose style is `parent_style`, and their
+/// descendants. `root_font_size` is the font size of the root element, or the initial font size
+/// when computing the root element itself.
+fn compute_subtree(
+    document: &Document,
+    id: NodeId,
+    parent_style: &ComputedStyle,
+    root_font_size: f64,
+    sheets: &[(Origin, &StyleSheet)],
+    map: &mut StyleMap,
+) {
+    for child in document.children(id) {
+        if document.node(child).element().is_none() {
+            continue;
+        }
+
+        let mut style = ComputedStyle::inherit_from(parent_style);
+        for declaration in cascade(document, child, sheets).values() {
+            style.apply(declaration, parent_style, root_font_size);
+        }
+        style.absolutize_lengths(root_font_size);
+        style.blockify_absolutely_positioned();
+
+        let root_font_size = if id == document.root() {
+            style.font_size
+        } else {
+            root_font_size
+        };
+        compute_subtree(document, child, &style, root_font_size, sheets, map);
+        map.styles.insert(child, style);
+    }
+}
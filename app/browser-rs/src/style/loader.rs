@@ -0,0 +1,30 @@
+//! Loading of the stylesheets linked from a document.
+//! https://html.spec.whatwg.org/multipage/links.html#link-type-stylesheet
+
+use crate::parser::css::cssom::*;
+use crate::parser::dom::*;
+
+use alloc::string::String;
+#[allow(unused_imports)]
+use liumlib::*;
+
+/// Fetches the stylesheets of the link elements in `document` and sets their rules. `fetch`
+/// returns the body of the resource at an href, or None if it can't be fetched. A stylesheet that
+/// fails to load stays empty and the other stylesheets are still applied, so the page is rendered
+/// as if the link didn't exist. The stylesheets stay in the document order because they are
+/// already in the document.
+pub fn load_style_sheets<F>(document: &mut Document, fetch: F)
+where
+    F: Fn(&str) -> Option<String>,
+{
+    for sheet in document.style_sheets_mut() {
+        let href = match sheet.href() {
+            Some(h) => String::from(h),
+            None => continue,
+        };
+        match fetch(&href) {
+            Some(css) => sheet.set_rules(parse_stylesheet(css)),
+            None => warn!("failed to load a stylesheet: {}", href),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Shorthand properties, which set several longhand properties at once.
+//! https://www.w3.org/TR/css-cascade-4/#shorthand-property
+
+use crate::parser::css::cssom::*;
+use crate::parser::css::tokenizer::*;
+use crate::style::computed::*;
+use crate::style::values::*;
+
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A shorthand whose value is one to four values for the top, right, bottom and left sides, the
+/// prefix and the suffix of its longhand names around the side, and the validator of a value for
+/// one side.
+type BoxShorthand = (
+    &'static str,
+    &'static str,
+    &'static str,
+    fn(&[CssToken]) -> bool,
+);
+
+/// https://www.w3.org/TR/css-box-4/#margin-shorthand
+/// https://www.w3.org/TR/css-backgrounds-3/#border-width
+const BOX_SHORTHANDS: &[BoxShorthand] = &[
+    ("margin", "margin-", "", is_margin),
+    ("padding", "padding-", "", is_padding),
+    ("border-width", "border-", "-width", is_line_width),
+    ("border-style", "border-", "-style", is_border_style),
+    ("border-color", "border-", "-color", is_border_color),
+];
+
+const SIDES: [&str; 4] = ["top", "right", "bottom", "left"];
+
+/// Returns the longhand declarations which `declaration` sets. A longhand declaration is returned
+/// as is. An invalid shorthand declaration sets nothing.
+pub fn expand_shorthand(declaration: &Declaration) -> Vec<Declaration> {
+    let (prefix, suffix, is_valid) = match BOX_SHORTHANDS
+        .iter()
+        .find(|(name, _, _, _)| *name == declaration.name())
+    {
+        Some((_, prefix, suffix, is_valid)) => (prefix, suffix, is_valid),
+        None => return vec![declaration.clone()],
+    };
+    let longhand = |side: &str| format!("{}{}{}", prefix, side, suffix);
+
+    // A CSS-wide keyword in a shorthand sets all the longhands to the keyword.
+    // https://www.w3.org/TR/css-cascade-4/#shorthand-property
+    if let [CssToken::Ident(k)] = declaration.value().as_slice() {
+        if ["inherit", "initial", "unset"]
+            .iter()
+            .any(|w| k.eq_ignore_ascii_case(w))
+        {
+            return SIDES
+                .iter()
+                .map(|side| {
+                    Declaration::new(
+                        &longhand(side),
+                        declaration.value().clone(),
+                        declaration.important(),
+                    )
+                })
+                .collect();
+        }
+    }
+
+    let values = split_components(declaration.value());
+    if !values.iter().all(|v| is_valid(v)) {
+        return Vec::new();
+    }
+
+    // "If there is only one component value, it applies to all sides. If there are two values,
+    // the top and bottom are set to the first value and the right and left are set to the second.
+    // If there are three values, the top is set to the first value, the left and right are set to
+    // the second, and the bottom is set to the third. If there are four values they apply to the
+    // top, right, bottom, and left, respectively."
+    let indices = match values.len() {
+        1 => [0, 0, 0, 0],
+        2 => [0, 1, 0, 1],
+        3 => [0, 1, 2, 1],
+        4 => [0, 1, 2, 3],
+        _ => return Vec::new(),
+    };
+
+    SIDES
+        .iter()
+        .zip(indices.iter())
+        .map(|(side, i)| {
+            Declaration::new(&longhand(side), values[*i].clone(), declaration.important())
+        })
+        .collect()
+}
+
+/// https://www.w3.org/TR/css-box-4/#margin-physical
+fn is_margin(value: &[CssToken]) -> bool {
+    parse_length_or_auto(value).is_some()
+}
+
+/// https://www.w3.org/TR/css-box-4/#padding-physical
+fn is_padding(value: &[CssToken]) -> bool {
+    parse_length(value).is_some_and(|l| !l.is_negative())
+}
+
+fn is_line_width(value: &[CssToken]) -> bool {
+    parse_line_width(value).is_some()
+}
+
+fn is_border_style(value: &[CssToken]) -> bool {
+    parse_border_style(value).is_some()
+}
+
+fn is_border_color(value: &[CssToken]) -> bool {
+    parse_border_color(value).is_some()
+}
+
+/// Splits a value into the component values separated by whitespace. A function such as
+/// `rgb(1, 2, 3)` is one component value.
+/// https://www.w3.org/TR/css-syntax-3/#component-value
+fn split_components(value: &[CssToken]) -> Vec<Vec<CssToken>> {
+    let mut components: Vec<Vec<CssToken>> = Vec::new();
+    let mut current = Vec::new();
+    let mut depth = 0;
+
+    for token in value {
+        match token {
+            CssToken::Whitespace if depth == 0 => {
+                if !current.is_empty() {
+                    components.push(current);
+                    current = Vec::new();
+                }
+                continue;
+            }
+            CssToken::Function(_)
+            | CssToken::OpenParen
+            | CssToken::OpenSquare
+            | CssToken::OpenCurly => depth += 1,
+            CssToken::CloseParen | CssToken::CloseSquare | CssToken::CloseCurly if depth > 0 => {
+                depth -= 1
+            }
+            _ => {}
+        }
+        current.push(token.clone());
+    }
+    if !current.is_empty() {
+        components.push(current);
+    }
+
+    components
+}
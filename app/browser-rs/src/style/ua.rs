@@ -0,0 +1,105 @@
+//! The default stylesheet of the browser.
+//! https://www.w3.org/TR/css-cascade-4/#cascade-origin-ua
+
+use crate::parser::css::cssom::*;
+
+use alloc::string::String;
+
+/// A small subset of the rendering suggestions in the HTML standard.
+/// https://html.spec.whatwg.org/multipage/rendering.html
+const USER_AGENT_CSS: &str = r#"
+[hidden], area, base, datalist, head, link, meta, noscript, param, rp, script, style, template,
+title {
+  display: none;
+}
+
+html, body, div, p, address, article, aside, blockquote, center, dl, dd, dt, fieldset, figure,
+figcaption, footer, form, header, hgroup, hr, legend, main, nav, ol, ul, pre, section,
+h1, h2, h3, h4, h5, h6 {
+  display: block;
+}
+
+li {
+  display: list-item;
+}
+
+table {
+  display: table;
+}
+
+thead, tbody, tfoot {
+  display: table-row-group;
+}
+
+tr {
+  display: table-row;
+}
+
+td, th {
+  display: table-cell;
+  padding: 1px;
+}
+
+body {
+  margin: 8px;
+}
+
+p, blockquote, dl, figure, pre, ul, ol {
+  margin-top: 1em;
+  margin-bottom: 1em;
+}
+
+ul, ol {
+  padding-left: 40px;
+}
+
+ol {
+  list-style-type: decimal;
+}
+
+ul {
+  list-style-type: disc;
+}
+
+ul ul, ol ul {
+  list-style-type: circle;
+}
+
+ul ul ul, ul ol ul, ol ul ul, ol ol ul {
+  list-style-type: square;
+}
+
+blockquote, figure {
+  margin-left: 40px;
+  margin-right: 40px;
+}
+
+dd {
+  margin-left: 40px;
+}
+
+h1 { font-size: 2em; margin-top: 0.67em; margin-bottom: 0.67em; }
+h2 { font-size: 1.5em; margin-top: 0.83em; margin-bottom: 0.83em; }
+h3 { font-size: 1.17em; margin-top: 1em; margin-bottom: 1em; }
+h4 { font-size: 1em; margin-top: 1.33em; margin-bottom: 1.33em; }
+h5 { font-size: 0.83em; margin-top: 1.67em; margin-bottom: 1.67em; }
+h6 { font-size: 0.67em; margin-top: 2.33em; margin-bottom: 2.33em; }
+
+h1, h2, h3, h4, h5, h6, b, strong, th, dt {
+  font-weight: bold;
+}
+
+:link {
+  color: blue;
+  text-decoration: underline;
+}
+
+u, ins {
+  text-decoration: underline;
+}
+"#;
+
+/// Returns the parsed user-agent stylesheet. It is applied at the lowest priority of the cascade.
+pub fn user_agent_style_sheet() -> StyleSheet {
+    parse_stylesheet(String::from(USER_AGENT_CSS))
+}
@@ -0,0 +1,288 @@
+//! Typed CSS values and their parsers.
+//! https://www.w3.org/TR/css-values-4/
+
+use crate::parser::css::tokenizer::*;
+
+use alloc::vec::Vec;
+
+/// An sRGB color with alpha.
+/// https://www.w3.org/TR/css-color-4/#color-type
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Color {
+    r: u8,
+    g: u8,
+    b: u8,
+    a: u8,
+}
+
+#[allow(dead_code)]
+impl Color {
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    /// https://www.w3.org/TR/css-color-4/#transparent-color
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub fn r(&self) -> u8 {
+        self.r
+    }
+
+    pub fn g(&self) -> u8 {
+        self.g
+    }
+
+    pub fn b(&self) -> u8 {
+        self.b
+    }
+
+    pub fn a(&self) -> u8 {
+        self.a
+    }
+
+    /// Returns the color as 0xRRGGBB, the format of liumlib's drawing functions. Alpha is
+    /// dropped.
+    pub fn to_u32(self) -> u32 {
+        ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
+    }
+
+    /// Returns the color for a named color keyword.
+    /// https://www.w3.org/TR/css-color-4/#named-colors
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (r, g, b) = match name.to_ascii_lowercase().as_str() {
+            "black" => (0, 0, 0),
+            "silver" => (192, 192, 192),
+            "gray" | "grey" => (128, 128, 128),
+            "white" => (255, 255, 255),
+            "maroon" => (128, 0, 0),
+            "red" => (255, 0, 0),
+            "purple" => (128, 0, 128),
+            "fuchsia" => (255, 0, 255),
+            "green" => (0, 128, 0),
+            "lime" => (0, 255, 0),
+            "olive" => (128, 128, 0),
+            "yellow" => (255, 255, 0),
+            "orange" => (255, 165, 0),
+            "navy" => (0, 0, 128),
+            "blue" => (0, 0, 255),
+            "teal" => (0, 128, 128),
+            "aqua" => (0, 255, 255),
+            "transparent" => return Some(Self::TRANSPARENT),
+            _ => return None,
+        };
+        Some(Self::rgb(r, g, b))
+    }
+}
+
+/// Parses a color value. Returns None if the value is not a supported color.
+/// https://www.w3.org/TR/css-color-4/#typedef-color
+pub fn parse_color(value: &[CssToken]) -> Option<Color> {
+    match value {
+        [CssToken::Ident(name)] => Color::from_name(name),
+        [CssToken::Hash { value, .. }] => parse_hex_color(value),
+        [CssToken::Function(name), args @ .., CssToken::CloseParen]
+            if name.eq_ignore_ascii_case("rgb") || name.eq_ignore_ascii_case("rgba") =>
+        {
+            parse_rgb_function(args)
+        }
+        _ => None,
+    }
+}
+
+/// Parses the digits of `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`.
+/// https://www.w3.org/TR/css-color-4/#hex-notation
+fn parse_hex_color(digits: &str) -> Option<Color> {
+    let mut values = Vec::new();
+    for c in digits.chars() {
+        values.push(c.to_digit(16)? as u8);
+    }
+
+    match values.len() {
+        // "The three-digit RGB notation (#RGB) is converted into six-digit form (#RRGGBB) by
+        // replicating digits, not by adding zeros."
+        3 | 4 => {
+            let v: Vec<u8> = values.iter().map(|d| d * 17).collect();
+            let a = if v.len() == 4 { v[3] } else { 255 };
+            Some(Color::rgba(v[0], v[1], v[2], a))
+        }
+        6 | 8 => {
+            let v: Vec<u8> = values.chunks(2).map(|d| d[0] * 16 + d[1]).collect();
+            let a = if v.len() == 4 { v[3] } else { 255 };
+            Some(Color::rgba(v[0], v[1], v[2], a))
+        }
+        _ => None,
+    }
+}
+
+/// Parses the arguments of `rgb()` or `rgba()`, which are the same functions. Both the legacy
+/// comma-separated syntax `rgb(255, 0, 0, 0.5)` and the modern syntax `rgb(255 0 0 / 50%)` are
+/// supported.
+/// https://www.w3.org/TR/css-color-4/#rgb-functions
+fn parse_rgb_function(args: &[CssToken]) -> Option<Color> {
+    let args: Vec<&CssToken> = args
+        .iter()
+        .filter(|t| **t != CssToken::Whitespace)
+        .collect();
+
+    let (channels, alpha) = if args.iter().any(|t| **t == CssToken::Comma) {
+        // The legacy syntax separates every component with a comma.
+        let components: Vec<&CssToken> = args.iter().step_by(2).copied().collect();
+        if !args
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .all(|t| **t == CssToken::Comma)
+            || args.len().is_multiple_of(2)
+        {
+            return None;
+        }
+        match components.len() {
+            3 => (components, None),
+            4 => (components[..3].to_vec(), Some(components[3])),
+            _ => return None,
+        }
+    } else {
+        match args.len() {
+            3 => (args, None),
+            5 if *args[3] == CssToken::Delim('/') => (args[..3].to_vec(), Some(args[4])),
+            _ => return None,
+        }
+    };
+
+    // "The legacy syntax doesn't allow mixing numbers and percentages", and neither does this
+    // parser for the modern syntax, which is simpler than the spec.
+    let r = channel(channels[0])?;
+    let g = channel(channels[1])?;
+    let b = channel(channels[2])?;
+    let all_numbers = channels.iter().all(|t| matches!(t, CssToken::Number(_)));
+    let all_percentages = channels
+        .iter()
+        .all(|t| matches!(t, CssToken::Percentage(_)));
+    if !all_numbers && !all_percentages {
+        return None;
+    }
+
+    let a = match alpha {
+        None => 255,
+        Some(CssToken::Number(n)) => to_u8(*n * 255.0),
+        Some(CssToken::Percentage(p)) => to_u8(*p * 255.0 / 100.0),
+        Some(_) => return None,
+    };
+
+    Some(Color::rgba(r, g, b, a))
+}
+
+/// Returns the value of a red, green or blue component. Out of range values are clamped.
+fn channel(token: &CssToken) -> Option<u8> {
+    match token {
+        CssToken::Number(n) => Some(to_u8(*n)),
+        CssToken::Percentage(p) => Some(to_u8(*p * 255.0 / 100.0)),
+        _ => None,
+    }
+}
+
+/// Clamps `v` to [0, 255] and rounds it to the nearest integer.
+fn to_u8(v: f64) -> u8 {
+    if v <= 0.0 {
+        0
+    } else if v >= 255.0 {
+        255
+    } else {
+        (v + 0.5) as u8
+    }
+}
+
+/// A length or a percentage.
+/// https://www.w3.org/TR/css-values-4/#typedef-length-percentage
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Length {
+    Px(f64),
+    /// https://www.w3.org/TR/css-values-4/#em
+    Em(f64),
+    /// https://www.w3.org/TR/css-values-4/#rem
+    Rem(f64),
+    /// https://www.w3.org/TR/css-values-4/#percentages
+    Percentage(f64),
+    /// `auto`, which only some properties such as width and margins accept. Layout decides the
+    /// actual length.
+    /// https://www.w3.org/TR/CSS2/visudet.html#the-width-property
+    Auto,
+}
+
+impl Default for Length {
+    fn default() -> Self {
+        Length::Px(0.0)
+    }
+}
+
+#[allow(dead_code)]
+impl Length {
+    /// Returns the length with font-relative units converted to px, which is what the computed
+    /// value of a length is. Percentages are kept because they depend on layout.
+    /// https://www.w3.org/TR/css-values-4/#font-relative-lengths
+    pub fn absolutize(self, font_size: f64, root_font_size: f64) -> Self {
+        match self {
+            Length::Em(n) => Length::Px(n * font_size),
+            Length::Rem(n) => Length::Px(n * root_font_size),
+            _ => self,
+        }
+    }
+
+    /// Returns the length in px, resolving a percentage against `percentage_basis`, e.g. the width
+    /// of the containing block. The length must be absolutized first; font-relative units are
+    /// resolved against the default font size as a fallback. `auto` is 0.
+    pub fn to_px(self, percentage_basis: f64) -> f64 {
+        match self {
+            Length::Px(n) => n,
+            Length::Percentage(p) => percentage_basis * p / 100.0,
+            Length::Em(n) | Length::Rem(n) => n * DEFAULT_FONT_SIZE,
+            Length::Auto => 0.0,
+        }
+    }
+
+    /// Returns true if the value is negative.
+    pub fn is_negative(self) -> bool {
+        match self {
+            Length::Px(n) | Length::Em(n) | Length::Rem(n) | Length::Percentage(n) => n < 0.0,
+            Length::Auto => false,
+        }
+    }
+
+    pub fn is_auto(self) -> bool {
+        self == Length::Auto
+    }
+}
+
+/// The font size of the root element when nothing specifies it.
+/// https://www.w3.org/TR/css-fonts-4/#valdef-font-size-medium
+pub const DEFAULT_FONT_SIZE: f64 = 16.0;
+
+/// Parses a length or a percentage. A unitless zero is also a length.
+/// https://www.w3.org/TR/css-values-4/#lengths
+pub fn parse_length(value: &[CssToken]) -> Option<Length> {
+    match value {
+        [CssToken::Dimension(n, unit)] => match unit.to_ascii_lowercase().as_str() {
+            "px" => Some(Length::Px(*n)),
+            "em" => Some(Length::Em(*n)),
+            "rem" => Some(Length::Rem(*n)),
+            _ => None,
+        },
+        [CssToken::Percentage(p)] => Some(Length::Percentage(*p)),
+        [CssToken::Number(n)] if *n == 0.0 => Some(Length::Px(0.0)),
+        _ => None,
+    }
+}
+
+/// Parses a length, a percentage or `auto`.
+pub fn parse_length_or_auto(value: &[CssToken]) -> Option<Length> {
+    match value {
+        [CssToken::Ident(v)] if v.eq_ignore_ascii_case("auto") => Some(Length::Auto),
+        _ => parse_length(value),
+    }
+}
@@ -0,0 +1,130 @@
+//! Tabs, each of which is a browsing context showing a page of its own. Only the active tab is
+//! shown in the window, and the others keep their documents, layouts and scroll positions.
+
+use crate::page::Page;
+use crate::refresh::ScheduledRefresh;
+use crate::ui::status_bar::StatusBar;
+use crate::ui::tab_strip::TabStrip;
+use crate::url::ParsedUrl;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A page with the URL it's loaded from and how far it's loaded.
+#[derive(Debug)]
+pub struct Tab {
+    pub page: Page,
+    pub url: ParsedUrl,
+    pub status: StatusBar,
+    /// The refresh which the page asked for, e.g. with a meta element.
+    pub refresh: Option<ScheduledRefresh>,
+}
+
+impl Tab {
+    /// Creates a tab showing `page` loaded from `url`.
+    pub fn new(page: Page, url: ParsedUrl) -> Self {
+        let mut status = StatusBar::new(&url.to_string());
+        status.finish(page.document().title());
+        Self {
+            page,
+            url,
+            status,
+            refresh: None,
+        }
+    }
+}
+
+/// The open tabs in order, one of which is active. There's always at least one tab.
+#[derive(Debug)]
+pub struct Tabs {
+    tabs: Vec<Tab>,
+    active: usize,
+}
+
+#[allow(dead_code)]
+impl Tabs {
+    /// Creates the tabs with only `first`.
+    pub fn new(first: Tab) -> Self {
+        Self {
+            tabs: vec![first],
+            active: 0,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.tabs.len()
+    }
+
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    pub fn active(&self) -> &Tab {
+        &self.tabs[self.active]
+    }
+
+    pub fn active_mut(&mut self) -> &mut Tab {
+        &mut self.tabs[self.active]
+    }
+
+    /// Opens `tab` next to the active tab and makes it active.
+    pub fn open(&mut self, tab: Tab) {
+        self.tabs.insert(self.active + 1, tab);
+        self.select(self.active + 1);
+    }
+
+    /// Closes the active tab and makes the next one active, or the previous one if it was the
+    /// last. Returns false if it's the only tab, which is left open.
+    pub fn close_active(&mut self) -> bool {
+        if self.tabs.len() == 1 {
+            return false;
+        }
+        self.tabs.remove(self.active);
+        let next = self.active.min(self.tabs.len() - 1);
+        self.active = usize::MAX;
+        self.select(next);
+        true
+    }
+
+    /// Makes the tab at `index` active. Its page is damaged as a whole since the window showed
+    /// another one. Returns false if there's no such tab or it's already active.
+    pub fn select(&mut self, index: usize) -> bool {
+        if index >= self.tabs.len() || index == self.active {
+            return false;
+        }
+        self.active = index;
+        let page = &mut self.tabs[index].page;
+        page.damage(page.visible_rect());
+        true
+    }
+
+    /// Makes the next tab active, or the previous one if `backward` is true. The first and the
+    /// last tabs are next to each other. Returns false if there's only one tab.
+    pub fn select_next(&mut self, backward: bool) -> bool {
+        let len = self.tabs.len();
+        let next = if backward {
+            (self.active + len - 1) % len
+        } else {
+            (self.active + 1) % len
+        };
+        self.select(next)
+    }
+
+    /// Returns the strip showing the tabs, where each tab is labeled with the title of its page,
+    /// or the URL if it has no title.
+    pub fn strip(&self) -> TabStrip {
+        let labels = self
+            .tabs
+            .iter()
+            .map(|tab| {
+                let title = tab.page.document().title();
+                if title.is_empty() {
+                    tab.url.to_string()
+                } else {
+                    title
+                }
+            })
+            .collect();
+        TabStrip::new(labels, self.active)
+    }
+}
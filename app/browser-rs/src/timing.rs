@@ -0,0 +1,114 @@
+//! The time taken by each stage of loading a page, so that a stage which gets slower is visible.
+
+use alloc::format;
+use alloc::string::String;
+use liumlib::time::{uptime, Instant};
+
+/// Returns the time in milliseconds since an arbitrary point, e.g. for the timers of scripts.
+pub fn milliseconds() -> u64 {
+    uptime().as_millis() as u64
+}
+
+/// Returns the nanoseconds since `start`, which the stages and the frames are measured in.
+pub fn nanos_since(start: Instant) -> u64 {
+    start.elapsed().as_nanos() as u64
+}
+
+/// The stages of loading a page in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Fetching the document and its style sheets and images.
+    Fetch,
+    Tokenize,
+    /// Constructing the DOM tree from the tokens, without the time spent in the tokenizer.
+    TreeBuild,
+    /// Computing the styles of the elements.
+    Style,
+    Layout,
+    /// Building the display list. Painting it to the window is measured by frames instead.
+    Paint,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 6] = [
+        Stage::Fetch,
+        Stage::Tokenize,
+        Stage::TreeBuild,
+        Stage::Style,
+        Stage::Layout,
+        Stage::Paint,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Fetch => "fetch",
+            Stage::Tokenize => "tokenize",
+            Stage::TreeBuild => "tree",
+            Stage::Style => "style",
+            Stage::Layout => "layout",
+            Stage::Paint => "paint",
+        }
+    }
+}
+
+/// Measures the stages of a navigation in nanoseconds of the monotonic clock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timing {
+    nanos: [u64; 6],
+}
+
+#[allow(dead_code)]
+impl Timing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `nanos` to `stage`, which may run in several steps, e.g. fetching each resource.
+    pub fn add(&mut self, stage: Stage, nanos: u64) {
+        self.nanos[stage as usize] += nanos;
+    }
+
+    /// Runs `f` and adds the nanoseconds taken by it to `stage`.
+    pub fn measure<R, F: FnOnce() -> R>(&mut self, stage: Stage, f: F) -> R {
+        let start = Instant::now();
+        let result = f();
+        self.add(stage, nanos_since(start));
+        result
+    }
+
+    /// Returns the nanoseconds taken by `stage`.
+    pub fn get(&self, stage: Stage) -> u64 {
+        self.nanos[stage as usize]
+    }
+
+    /// Returns the nanoseconds taken by all the stages.
+    pub fn total(&self) -> u64 {
+        self.nanos.iter().sum()
+    }
+
+    /// Returns the times of the stages in a line, e.g. "fetch 1.2s, tokenize 30.5ms, ...,
+    /// total 2.0s".
+    pub fn summary(&self) -> String {
+        let mut text = String::new();
+        for stage in Stage::ALL.iter() {
+            text.push_str(&format!(
+                "{} {}, ",
+                stage.name(),
+                format_nanos(self.get(*stage))
+            ));
+        }
+        text.push_str(&format!("total {}", format_nanos(self.total())));
+        text
+    }
+}
+
+/// Returns `nanos` in a short form with a unit, e.g. "950ns", "30.5us", "1.2ms" or "2.0s".
+pub fn format_nanos(nanos: u64) -> String {
+    let units = [(1_000_000_000, "s"), (1_000_000, "ms"), (1_000, "us")];
+    for (size, unit) in units.iter() {
+        if nanos >= *size {
+            return format!("{:.1}{}", nanos as f64 / *size as f64, unit);
+        }
+    }
+    format!("{}ns", nanos)
+}
@@ -0,0 +1,63 @@
+//! The user interface of the browser drawn over the page, e.g. the address bar.
+
+pub mod address_bar;
+pub mod bookmark_menu;
+pub mod dialog;
+pub mod status_bar;
+pub mod tab_strip;
+
+use crate::keyboard::Key;
+use crate::paint::display_list::DisplayList;
+use address_bar::AddressBar;
+use alloc::string::String;
+use bookmark_menu::BookmarkMenu;
+
+/// Put at the end of a text which is too long to fit.
+const ELLIPSIS: &str = "...";
+
+/// Returns `text` cut with an ellipsis to fit in `capacity` characters.
+pub fn truncate(text: &str, capacity: usize) -> String {
+    if text.chars().count() <= capacity {
+        return String::from(text);
+    }
+    let kept = capacity.saturating_sub(ELLIPSIS.len());
+    let mut text: String = text.chars().take(kept).collect();
+    text.push_str(&ELLIPSIS[..capacity.min(ELLIPSIS.len())]);
+    text
+}
+
+/// What a popup asks the browser to do after a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// The popup changed, e.g. the text was edited, so it needs repainting.
+    Edit,
+    /// Enter was pressed to go to the URL.
+    Navigate(String),
+    /// Esc was pressed to close the popup.
+    Cancel,
+}
+
+/// A part of the user interface which takes the keys instead of the page while it's open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Popup {
+    AddressBar(AddressBar),
+    BookmarkMenu(BookmarkMenu),
+}
+
+impl Popup {
+    /// Passes `key` to the popup, and returns what to do next, or None if the key does nothing.
+    pub fn handle_key(&mut self, key: Key) -> Option<Action> {
+        match self {
+            Popup::AddressBar(bar) => bar.handle_key(key),
+            Popup::BookmarkMenu(menu) => menu.handle_key(key),
+        }
+    }
+
+    /// Returns the commands to paint the popup at the top of a window of `width` px.
+    pub fn display_list(&self, width: f64) -> DisplayList {
+        match self {
+            Popup::AddressBar(bar) => bar.display_list(width),
+            Popup::BookmarkMenu(menu) => menu.display_list(width),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! A one-line input at the top of the window to type a URL in.
+
+use crate::font::Font;
+use crate::keyboard::Key;
+use crate::layout::geometry::Rect;
+use crate::paint::display_list::{DisplayItem, DisplayList};
+use crate::style::values::Color;
+use crate::ui::Action;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// The space around the text in px.
+const PADDING: f64 = 2.0;
+const BACKGROUND_COLOR: Color = Color::rgb(0xee, 0xee, 0xee);
+const BORDER_COLOR: Color = Color::rgb(0x88, 0x88, 0x88);
+
+/// The text typed in the address bar and the position of the cursor in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressBar {
+    text: Vec<char>,
+    /// The index of the character before which text is inserted.
+    cursor: usize,
+}
+
+#[allow(dead_code)]
+impl AddressBar {
+    /// Opens the bar with `url`, e.g. the current one, and the cursor at its end.
+    pub fn new(url: &str) -> Self {
+        let text: Vec<char> = url.chars().collect();
+        let cursor = text.len();
+        Self { text, cursor }
+    }
+
+    /// Returns the height of the bar in px, including the line under it.
+    pub fn height() -> f64 {
+        Font::new(1, false).glyph_height() + PADDING * 2.0 + 1.0
+    }
+
+    pub fn text(&self) -> String {
+        self.text.iter().collect()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Edits the text with `key`, and returns what to do next, or None if the key does nothing.
+    pub fn handle_key(&mut self, key: Key) -> Option<Action> {
+        match key {
+            Key::Enter => return Some(Action::Navigate(self.text())),
+            Key::Escape => return Some(Action::Cancel),
+            Key::Left if self.cursor > 0 => self.cursor -= 1,
+            Key::Right if self.cursor < self.text.len() => self.cursor += 1,
+            Key::Home | Key::Up => self.cursor = 0,
+            Key::End | Key::Down => self.cursor = self.text.len(),
+            Key::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.text.remove(self.cursor);
+            }
+            Key::Delete if self.cursor < self.text.len() => {
+                self.text.remove(self.cursor);
+            }
+            Key::Char(c) if (0x20..0x7f).contains(&c) => {
+                self.text.insert(self.cursor, c as char);
+                self.cursor += 1;
+            }
+            _ => return None,
+        }
+        Some(Action::Edit)
+    }
+
+    /// Returns the commands to paint the bar across the top of a window of `width` px. The text
+    /// is scrolled horizontally to keep the cursor in view.
+    pub fn display_list(&self, width: f64) -> DisplayList {
+        let font = Font::new(1, false);
+        let height = Self::height();
+        let mut list = DisplayList::new();
+        list.push(DisplayItem::SolidRect {
+            rect: Rect::new(0.0, 0.0, width, height - 1.0),
+            color: BACKGROUND_COLOR,
+        });
+        list.push(DisplayItem::SolidRect {
+            rect: Rect::new(0.0, height - 1.0, width, 1.0),
+            color: BORDER_COLOR,
+        });
+
+        // The number of characters which fit. The cursor after the last one is drawn in the
+        // padding.
+        let capacity = ((width - PADDING * 2.0) / font.glyph_width()) as usize;
+        let capacity = capacity.max(1);
+        let first = self.cursor.saturating_sub(capacity);
+        let text: String = self.text.iter().skip(first).take(capacity).collect();
+        if !text.is_empty() {
+            list.push(DisplayItem::Text {
+                rect: Rect::new(
+                    PADDING,
+                    PADDING,
+                    font.text_width(&text),
+                    font.glyph_height(),
+                ),
+                text,
+                font,
+                color: Color::BLACK,
+                underline: false,
+            });
+        }
+        list.push(DisplayItem::SolidRect {
+            rect: Rect::new(
+                PADDING + (self.cursor - first) as f64 * font.glyph_width(),
+                PADDING,
+                1.0,
+                font.glyph_height(),
+            ),
+            color: Color::BLACK,
+        });
+        list
+    }
+}
@@ -0,0 +1,137 @@
+//! A list of the bookmarks at the top of the window to choose one to go to.
+
+use crate::bookmarks::{Bookmark, Bookmarks};
+use crate::font::Font;
+use crate::keyboard::Key;
+use crate::layout::geometry::Rect;
+use crate::paint::display_list::{DisplayItem, DisplayList};
+use crate::style::values::Color;
+use crate::ui::Action;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// The space around the text of a row in px.
+const PADDING: f64 = 2.0;
+/// The number of the rows shown at once. The list is scrolled to show the selected one.
+const MAX_ROWS: usize = 10;
+const BACKGROUND_COLOR: Color = Color::rgb(0xee, 0xee, 0xee);
+const SELECTED_COLOR: Color = Color::rgb(0xcc, 0xdd, 0xff);
+const BORDER_COLOR: Color = Color::rgb(0x88, 0x88, 0x88);
+
+/// The bookmarks to choose from and the selected one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookmarkMenu {
+    entries: Vec<Bookmark>,
+    /// The index of the selected entry.
+    selected: usize,
+}
+
+#[allow(dead_code)]
+impl BookmarkMenu {
+    /// Opens the menu with the first of `bookmarks` selected.
+    pub fn new(bookmarks: &Bookmarks) -> Self {
+        Self {
+            entries: bookmarks.entries().clone(),
+            selected: 0,
+        }
+    }
+
+    /// Returns the height of a row in px.
+    pub fn row_height() -> f64 {
+        Font::new(1, false).glyph_height() + PADDING * 2.0
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Moves the selection with `key`, and returns what to do next, or None if the key does
+    /// nothing. 1 to 9 go to the bookmark of the number at once.
+    pub fn handle_key(&mut self, key: Key) -> Option<Action> {
+        match key {
+            Key::Enter => {
+                return match self.entries.get(self.selected) {
+                    Some(bookmark) => Some(Action::Navigate(bookmark.url.clone())),
+                    None => Some(Action::Cancel),
+                };
+            }
+            Key::Escape | Key::Char(b'q') => return Some(Action::Cancel),
+            Key::Char(c @ b'1'..=b'9') => {
+                let bookmark = self.entries.get((c - b'1') as usize)?;
+                return Some(Action::Navigate(bookmark.url.clone()));
+            }
+            Key::Up | Key::BackTab if self.selected > 0 => self.selected -= 1,
+            Key::Down | Key::Tab if self.selected + 1 < self.entries.len() => self.selected += 1,
+            Key::Home if self.selected > 0 => self.selected = 0,
+            Key::End if self.selected + 1 < self.entries.len() => {
+                self.selected = self.entries.len() - 1
+            }
+            _ => return None,
+        }
+        Some(Action::Edit)
+    }
+
+    /// Returns the lines shown in the menu, which are numbered from 1.
+    pub fn lines(&self) -> Vec<String> {
+        if self.entries.is_empty() {
+            return alloc::vec![String::from("No bookmarks. Press b to bookmark a page.")];
+        }
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                if b.title.is_empty() {
+                    format!("{}. {}", i + 1, b.url)
+                } else {
+                    format!("{}. {} - {}", i + 1, b.title, b.url)
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the commands to paint the menu across the top of a window of `width` px.
+    pub fn display_list(&self, width: f64) -> DisplayList {
+        let font = Font::new(1, false);
+        let row_height = Self::row_height();
+        let lines = self.lines();
+        let first = if self.selected >= MAX_ROWS {
+            self.selected + 1 - MAX_ROWS
+        } else {
+            0
+        };
+        let rows = lines.len().min(MAX_ROWS);
+
+        let mut list = DisplayList::new();
+        list.push(DisplayItem::SolidRect {
+            rect: Rect::new(0.0, 0.0, width, row_height * rows as f64),
+            color: BACKGROUND_COLOR,
+        });
+        for (row, line) in lines.into_iter().skip(first).take(rows).enumerate() {
+            let y = row_height * row as f64;
+            if !self.entries.is_empty() && first + row == self.selected {
+                list.push(DisplayItem::SolidRect {
+                    rect: Rect::new(0.0, y, width, row_height),
+                    color: SELECTED_COLOR,
+                });
+            }
+            list.push(DisplayItem::Text {
+                rect: Rect::new(
+                    PADDING,
+                    y + PADDING,
+                    font.text_width(&line),
+                    font.glyph_height(),
+                ),
+                text: line,
+                font,
+                color: Color::BLACK,
+                underline: false,
+            });
+        }
+        list.push(DisplayItem::SolidRect {
+            rect: Rect::new(0.0, row_height * rows as f64, width, 1.0),
+            color: BORDER_COLOR,
+        });
+        list
+    }
+}
@@ -0,0 +1,147 @@
+//! A box in the middle of the window which shows the message of `alert()` or `confirm()` while
+//! the script waits for the answer. Without a window, the message is asked on the console.
+
+use crate::font::Font;
+use crate::js::dialog::DialogKind;
+use crate::keyboard::Key;
+use crate::layout::geometry::Rect;
+use crate::paint::display_list::{DisplayItem, DisplayList};
+use crate::style::values::Color;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use liumlib::*;
+
+/// The space between the border and the text in px.
+const PADDING: f64 = 8.0;
+/// The lines of a message shown at most. The last one is cut with an ellipsis if there are more.
+const MAX_LINES: usize = 10;
+const BACKGROUND_COLOR: Color = Color::rgb(0xee, 0xee, 0xee);
+const BORDER_COLOR: Color = Color::rgb(0x88, 0x88, 0x88);
+
+/// A dialog waiting for the user's answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dialog {
+    kind: DialogKind,
+    message: String,
+}
+
+#[allow(dead_code)]
+impl Dialog {
+    pub fn new(kind: DialogKind, message: &str) -> Self {
+        Self {
+            kind,
+            message: String::from(message),
+        }
+    }
+
+    /// Returns the answer if `key` closes the dialog, or None if it does nothing. Enter chooses
+    /// OK and Esc chooses Cancel, which closes an alert as OK does.
+    pub fn handle_key(&self, key: Key) -> Option<bool> {
+        match key {
+            Key::Enter => Some(true),
+            Key::Escape => Some(self.kind == DialogKind::Alert),
+            _ => None,
+        }
+    }
+
+    /// Returns the keys to answer with, which are shown under the message.
+    fn buttons(&self) -> &'static str {
+        match self.kind {
+            DialogKind::Alert => "[Enter] OK",
+            DialogKind::Confirm => "[Enter] OK  [Esc] Cancel",
+        }
+    }
+
+    /// Returns the lines of the message wrapped to fit in `capacity` characters.
+    fn lines(&self, capacity: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+        for line in self.message.split('\n') {
+            let chars: Vec<char> = line.chars().collect();
+            if chars.is_empty() {
+                lines.push(String::new());
+            }
+            for chunk in chars.chunks(capacity) {
+                lines.push(chunk.iter().collect());
+            }
+        }
+        if lines.len() > MAX_LINES {
+            lines.truncate(MAX_LINES);
+            // An ellipsis at the end of the last line tells the rest is cut.
+            let kept = capacity.saturating_sub(3);
+            let last: String = lines[MAX_LINES - 1].chars().take(kept).collect();
+            lines[MAX_LINES - 1] = format!("{}...", last);
+        }
+        lines
+    }
+
+    /// Returns the commands to paint the dialog in the middle of a window of `width` x `height`
+    /// px, which is as wide as the message up to 80% of the window.
+    pub fn display_list(&self, width: f64, height: f64) -> DisplayList {
+        let font = Font::new(1, false);
+        let line_height = font.glyph_height();
+        let capacity = ((width * 0.8 - PADDING * 2.0) / font.glyph_width()) as usize;
+        let mut lines = self.lines(capacity.max(1));
+        // The buttons are under a blank line.
+        lines.push(String::new());
+        lines.push(String::from(self.buttons()));
+
+        let text_width = lines
+            .iter()
+            .map(|line| font.text_width(line))
+            .fold(0.0, f64::max);
+        let box_width = text_width + PADDING * 2.0;
+        let box_height = line_height * lines.len() as f64 + PADDING * 2.0;
+        let x = ((width - box_width) / 2.0).max(0.0);
+        let y = ((height - box_height) / 2.0).max(0.0);
+
+        let mut list = DisplayList::new();
+        list.push(DisplayItem::SolidRect {
+            rect: Rect::new(x, y, box_width, box_height),
+            color: BORDER_COLOR,
+        });
+        list.push(DisplayItem::SolidRect {
+            rect: Rect::new(x + 1.0, y + 1.0, box_width - 2.0, box_height - 2.0),
+            color: BACKGROUND_COLOR,
+        });
+        for (i, line) in lines.into_iter().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            list.push(DisplayItem::Text {
+                rect: Rect::new(
+                    x + PADDING,
+                    y + PADDING + line_height * i as f64,
+                    font.text_width(&line),
+                    line_height,
+                ),
+                text: line,
+                font,
+                color: Color::BLACK,
+                underline: false,
+            });
+        }
+        list
+    }
+}
+
+/// Shows a dialog on the console instead of a window, e.g. while dumping a page. It prints the
+/// message and waits for a line of the standard input, where "y" chooses OK for a confirm.
+pub fn ask_in_console(kind: DialogKind, message: &str) -> bool {
+    match kind {
+        DialogKind::Alert => print!("alert: {}\n[Enter] OK ", message),
+        DialogKind::Confirm => print!("confirm: {}\nOK? [y/N] ", message),
+    }
+    let mut answer = String::new();
+    loop {
+        // 0 is read at the end of the input.
+        match getchar() {
+            0 | b'\n' | b'\r' => break,
+            c => answer.push(c as char),
+        }
+    }
+    match kind {
+        DialogKind::Alert => true,
+        DialogKind::Confirm => answer.trim().eq_ignore_ascii_case("y"),
+    }
+}
@@ -0,0 +1,146 @@
+//! A one-line bar at the bottom of the window which shows what the browser is doing.
+
+use crate::font::Font;
+use crate::layout::geometry::Rect;
+use crate::paint::display_list::{DisplayItem, DisplayList};
+use crate::style::values::Color;
+use crate::timing::format_nanos;
+use crate::ui::truncate;
+use alloc::format;
+use alloc::string::String;
+
+/// The space around the text in px.
+const PADDING: f64 = 2.0;
+const BACKGROUND_COLOR: Color = Color::rgb(0xee, 0xee, 0xee);
+const BORDER_COLOR: Color = Color::rgb(0x88, 0x88, 0x88);
+
+/// How far the page in the status bar is loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadState {
+    /// The document or its resources are being fetched. `fetched` is the number of the resources,
+    /// e.g. style sheets and images, fetched so far.
+    Loading {
+        fetched: usize,
+    },
+    Done,
+    /// The document couldn't be fetched, and an error page is shown instead.
+    Failed,
+}
+
+/// The URL and the title of the page, and how far it's loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusBar {
+    url: String,
+    title: String,
+    state: LoadState,
+    /// The nanoseconds taken to load the page, which are shown only if they're measured.
+    load_time: Option<u64>,
+}
+
+#[allow(dead_code)]
+impl StatusBar {
+    /// Shows `url` which starts loading.
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: String::from(url),
+            title: String::new(),
+            state: LoadState::Loading { fetched: 0 },
+            load_time: None,
+        }
+    }
+
+    /// Returns the height of the bar in px, including the line above it.
+    pub fn height() -> f64 {
+        Font::new(1, false).glyph_height() + PADDING * 2.0 + 1.0
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn state(&self) -> LoadState {
+        self.state
+    }
+
+    /// Starts loading `url`. The title is unknown until the document is loaded.
+    pub fn start(&mut self, url: &str) {
+        *self = Self::new(url);
+    }
+
+    /// Counts up the resources fetched for the loading page.
+    pub fn fetched(&mut self, fetched: usize) {
+        self.state = LoadState::Loading { fetched };
+    }
+
+    /// Finishes loading the document titled `title`.
+    pub fn finish(&mut self, title: String) {
+        self.title = title;
+        self.state = LoadState::Done;
+    }
+
+    /// Shows the nanoseconds taken to load the page after its state, e.g. "Done in 1.2ms".
+    pub fn set_load_time(&mut self, nanos: u64) {
+        self.load_time = Some(nanos);
+    }
+
+    /// Fails to load the URL. It's still shown so that the user can tell which one failed.
+    pub fn fail(&mut self) {
+        self.state = LoadState::Failed;
+    }
+
+    /// Returns the line shown in the bar, e.g. "Done | Title | http://127.0.0.1:8888/".
+    pub fn text(&self) -> String {
+        let state = match self.state {
+            LoadState::Loading { fetched: 0 } => String::from("Loading"),
+            LoadState::Loading { fetched } => format!("Loading ({} fetched)", fetched),
+            LoadState::Done => match self.load_time {
+                Some(nanos) => format!("Done in {}", format_nanos(nanos)),
+                None => String::from("Done"),
+            },
+            LoadState::Failed => String::from("Failed"),
+        };
+        if self.title.is_empty() {
+            format!("{} | {}", state, self.url)
+        } else {
+            format!("{} | {} | {}", state, self.title, self.url)
+        }
+    }
+
+    /// Returns the commands to paint the bar across the window of `width` px, with its top at
+    /// `y`. A text too long to fit is cut with an ellipsis.
+    pub fn display_list(&self, width: f64, y: f64) -> DisplayList {
+        let font = Font::new(1, false);
+        let height = Self::height();
+        let mut list = DisplayList::new();
+        list.push(DisplayItem::SolidRect {
+            rect: Rect::new(0.0, y, width, 1.0),
+            color: BORDER_COLOR,
+        });
+        list.push(DisplayItem::SolidRect {
+            rect: Rect::new(0.0, y + 1.0, width, height - 1.0),
+            color: BACKGROUND_COLOR,
+        });
+
+        let capacity = ((width - PADDING * 2.0) / font.glyph_width()) as usize;
+        let text = truncate(&self.text(), capacity);
+        if !text.is_empty() {
+            list.push(DisplayItem::Text {
+                rect: Rect::new(
+                    PADDING,
+                    y + 1.0 + PADDING,
+                    font.text_width(&text),
+                    font.glyph_height(),
+                ),
+                text,
+                font,
+                color: Color::BLACK,
+                underline: false,
+            });
+        }
+        list
+    }
+}
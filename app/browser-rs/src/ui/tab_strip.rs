@@ -0,0 +1,98 @@
+//! A one-line strip at the top of the window which shows the titles of the open tabs.
+
+use crate::font::Font;
+use crate::layout::geometry::Rect;
+use crate::paint::display_list::{DisplayItem, DisplayList};
+use crate::style::values::Color;
+use crate::ui::truncate;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// The space around the text in px.
+const PADDING: f64 = 2.0;
+const BACKGROUND_COLOR: Color = Color::rgb(0xdd, 0xdd, 0xdd);
+const ACTIVE_COLOR: Color = Color::WHITE;
+const BORDER_COLOR: Color = Color::rgb(0x88, 0x88, 0x88);
+
+/// The labels of the tabs in order, and which one is shown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TabStrip {
+    labels: Vec<String>,
+    active: usize,
+}
+
+#[allow(dead_code)]
+impl TabStrip {
+    pub fn new(labels: Vec<String>, active: usize) -> Self {
+        Self { labels, active }
+    }
+
+    /// Returns the height of the strip in px, including the line below it.
+    pub fn height() -> f64 {
+        Font::new(1, false).glyph_height() + PADDING * 2.0 + 1.0
+    }
+
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// Returns the commands to paint the strip at the top of the window of `width` px. The tabs
+    /// share the width equally, and a label too long to fit is cut with an ellipsis.
+    pub fn display_list(&self, width: f64) -> DisplayList {
+        let font = Font::new(1, false);
+        let height = Self::height();
+        let mut list = DisplayList::new();
+        list.push(DisplayItem::SolidRect {
+            rect: Rect::new(0.0, 0.0, width, height - 1.0),
+            color: BACKGROUND_COLOR,
+        });
+        list.push(DisplayItem::SolidRect {
+            rect: Rect::new(0.0, height - 1.0, width, 1.0),
+            color: BORDER_COLOR,
+        });
+        if self.labels.is_empty() {
+            return list;
+        }
+
+        // The tabs are placed on whole pixels so that the separators are sharp.
+        let tab_width = (width / self.labels.len() as f64) as usize as f64;
+        for (i, label) in self.labels.iter().enumerate() {
+            let x = tab_width * i as f64;
+            if i == self.active {
+                // The active tab is open to the page below it.
+                list.push(DisplayItem::SolidRect {
+                    rect: Rect::new(x, 0.0, tab_width, height),
+                    color: ACTIVE_COLOR,
+                });
+            }
+            if i > 0 {
+                list.push(DisplayItem::SolidRect {
+                    rect: Rect::new(x, 0.0, 1.0, height - 1.0),
+                    color: BORDER_COLOR,
+                });
+            }
+
+            let capacity = ((tab_width - PADDING * 2.0) / font.glyph_width()).max(0.0) as usize;
+            let text = truncate(label, capacity);
+            if !text.is_empty() {
+                list.push(DisplayItem::Text {
+                    rect: Rect::new(
+                        x + PADDING,
+                        PADDING,
+                        font.text_width(&text),
+                        font.glyph_height(),
+                    ),
+                    text,
+                    font,
+                    color: Color::BLACK,
+                    underline: false,
+                });
+            }
+        }
+        list
+    }
+}
@@ -1,8 +1,10 @@
+use alloc::format;
 use alloc::string::String;
 use alloc::string::ToString;
 use alloc::vec::Vec;
+use core::fmt;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ParsedUrl {
     scheme: String,
     pub host: String,
@@ -55,4 +57,90 @@ impl ParsedUrl {
             path: path,
         }
     }
+
+    /// Parses `u` like `new()`, or returns None if `new()` can't handle it, e.g. a URL typed by
+    /// the user with another scheme or an invalid port.
+    pub fn parse(u: &str) -> Option<Self> {
+        let rest = if let Some(rest) = u.strip_prefix("http://") {
+            rest
+        } else if Self::has_scheme(u) {
+            return None;
+        } else {
+            u
+        };
+        let host = rest.split('/').next()?;
+        let mut parts = host.splitn(2, ':');
+        if parts.next()?.is_empty() {
+            return None;
+        }
+        if let Some(port) = parts.next() {
+            port.parse::<u16>().ok()?;
+        }
+        Some(Self::new(u.to_string()))
+    }
+
+    /// Returns true if `u` starts with a scheme, which is followed by "://" before the path, the
+    /// query and the fragment, e.g. not "a.html?next=http://h/".
+    fn has_scheme(u: &str) -> bool {
+        match u.find("://") {
+            Some(i) => !u[..i].contains(['/', '?', '#']),
+            None => false,
+        }
+    }
+
+    /// Returns the fragment without '#', or None if the URL has no fragment.
+    /// https://url.spec.whatwg.org/#concept-url-fragment
+    pub fn fragment(&self) -> Option<&str> {
+        self.path.find('#').map(|i| &self.path[i + 1..])
+    }
+
+    /// Returns the path with the query, which is sent in a request. The fragment is used only in
+    /// the browser.
+    pub fn request_path(&self) -> &str {
+        match self.path.find('#') {
+            Some(i) => &self.path[..i],
+            None => &self.path,
+        }
+    }
+
+    /// Returns true if the URLs are the same except for their fragments, in which case going
+    /// from one to the other only scrolls the document.
+    /// https://html.spec.whatwg.org/multipage/browsing-the-web.html#scroll-to-fragid
+    pub fn equals_except_fragment(&self, other: &ParsedUrl) -> bool {
+        self.scheme == other.scheme
+            && self.host == other.host
+            && self.port == other.port
+            && self.request_path() == other.request_path()
+    }
+
+    /// Returns the URL of `href` relative to this URL, e.g. a URL in a link element, or None if
+    /// `parse()` can't handle it, e.g. an invalid port. Only absolute http URLs, absolute paths,
+    /// relative paths and fragments are supported.
+    /// https://url.spec.whatwg.org/#concept-basic-url-parser
+    pub fn join(&self, href: &str) -> Option<Self> {
+        if href.starts_with("http://") || Self::has_scheme(href) {
+            return Self::parse(href);
+        }
+
+        let path = if href.starts_with('/') {
+            href.to_string()
+        } else if href.starts_with('#') {
+            // Replace the fragment.
+            format!("{}{}", self.request_path(), href)
+        } else {
+            // Replace the last segment of the path.
+            let dir = match self.request_path().rfind('/') {
+                Some(i) => &self.path[..i + 1],
+                None => "/",
+            };
+            format!("{}{}", dir, href)
+        };
+        Self::parse(&format!("{}{}", self.host, path))
+    }
+}
+
+impl fmt::Display for ParsedUrl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}://{}{}", self.scheme, self.host, self.path)
+    }
 }
@@ -0,0 +1,127 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use browser_rs::keyboard::Key;
+use browser_rs::layout::geometry::Rect;
+use browser_rs::paint::display_list::*;
+use browser_rs::ui::address_bar::*;
+use browser_rs::ui::Action;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in address_bar.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// Types `keys` into `bar` and returns the last action.
+fn type_keys(bar: &mut AddressBar, keys: &[Key]) -> Option<Action> {
+    keys.iter().fold(None, |_, key| bar.handle_key(*key))
+}
+
+#[test_case]
+fn editing() {
+    let mut bar = AddressBar::new("a/b");
+    assert_eq!((bar.text(), bar.cursor()), (String::from("a/b"), 3));
+    assert_eq!(
+        type_keys(&mut bar, &[Key::Backspace, Key::Char(b'c')]),
+        Some(Action::Edit)
+    );
+    assert_eq!(bar.text(), "a/c");
+    type_keys(
+        &mut bar,
+        &[Key::Home, Key::Delete, Key::Char(b'x'), Key::Right],
+    );
+    assert_eq!((bar.text(), bar.cursor()), (String::from("x/c"), 2));
+    type_keys(
+        &mut bar,
+        &[Key::Left, Key::Left, Key::Left, Key::Char(b'y')],
+    );
+    assert_eq!((bar.text(), bar.cursor()), (String::from("yx/c"), 1));
+    type_keys(&mut bar, &[Key::End]);
+    assert_eq!(bar.cursor(), 4);
+    // Keys at the ends and unknown keys do nothing.
+    assert_eq!(bar.handle_key(Key::Right), None);
+    assert_eq!(bar.handle_key(Key::Delete), None);
+    assert_eq!(bar.handle_key(Key::Tab), None);
+    assert_eq!(bar.handle_key(Key::Char(0x07)), None);
+}
+
+#[test_case]
+fn actions() {
+    let mut bar = AddressBar::new("");
+    assert_eq!(bar.handle_key(Key::Backspace), None);
+    assert_eq!(
+        type_keys(&mut bar, &[Key::Char(b'h'), Key::Char(b'i'), Key::Enter]),
+        Some(Action::Navigate(String::from("hi")))
+    );
+    assert_eq!(bar.handle_key(Key::Escape), Some(Action::Cancel));
+}
+
+/// Returns the text and the rect of the cursor in the display list.
+fn painted(list: &DisplayList) -> (String, Rect) {
+    let text = list
+        .iter()
+        .filter_map(|item| match item {
+            DisplayItem::Text { text, .. } => Some(text.clone()),
+            _ => None,
+        })
+        .collect();
+    (text, list.last().unwrap().rect())
+}
+
+#[test_case]
+fn display_list() {
+    assert_eq!(AddressBar::height(), 21.0);
+    let mut bar = AddressBar::new("abc");
+    assert_eq!(
+        painted(&bar.display_list(100.0)),
+        (String::from("abc"), Rect::new(26.0, 2.0, 1.0, 16.0))
+    );
+    // The text is scrolled to show the cursor.
+    let mut bar_long = AddressBar::new("0123456789");
+    assert_eq!(
+        painted(&bar_long.display_list(44.0)),
+        (String::from("56789"), Rect::new(42.0, 2.0, 1.0, 16.0))
+    );
+    bar_long.handle_key(Key::Home);
+    assert_eq!(
+        painted(&bar_long.display_list(44.0)),
+        (String::from("01234"), Rect::new(2.0, 2.0, 1.0, 16.0))
+    );
+    bar.handle_key(Key::Home);
+    assert_eq!(painted(&bar.display_list(100.0)).1.x, 2.0);
+}
@@ -0,0 +1,80 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in alloc.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn reusing_memory() {
+    // 16 MiB in total, much more than the heap of liumlib.
+    for i in 0..256 {
+        let v = vec![i as u8; 0x10000];
+        assert!(v.iter().all(|b| *b == i as u8));
+    }
+}
+
+#[test_case]
+fn merging_free_blocks() {
+    let small: Vec<Box<[u8; 512]>> = (0..1200).map(|_| Box::new([0; 512])).collect();
+    // Keeps the memory above the small blocks, so that the large one fits only in the free blocks
+    // merged from them.
+    let guard = Box::new(0u8);
+    drop(small);
+    let large = vec![1u8; 500 * 1024];
+    assert!(large.iter().all(|b| *b == 1));
+    drop(guard);
+}
+
+#[test_case]
+fn alignment() {
+    #[repr(align(4096))]
+    struct Page([u8; 4096]);
+    let pages: Vec<Box<Page>> = (0..4).map(|_| Box::new(Page([0; 4096]))).collect();
+    let bytes: Vec<Box<u8>> = (0..64).map(Box::new).collect();
+    for page in &pages {
+        assert_eq!(&**page as *const Page as usize % 4096, 0);
+        assert!(page.0.iter().all(|b| *b == 0));
+    }
+    for (i, byte) in bytes.iter().enumerate() {
+        assert_eq!(**byte as usize, i);
+    }
+}
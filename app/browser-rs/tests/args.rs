@@ -0,0 +1,142 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+
+use browser_rs::args::*;
+use browser_rs::config::Config;
+use browser_rs::style::computed::ColorScheme;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in args.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn urls_and_flags() {
+    let args = Args::parse(&[
+        "browser-rs.bin",
+        "http://127.0.0.1:8888/a.html",
+        "--width",
+        "800",
+        "--dump",
+        "-u",
+        "http://127.0.0.1:8888/b.html",
+        "--screenshot",
+        "page.bmp",
+        "--no-cache",
+        "http://127.0.0.1:8888/c.html",
+    ])
+    .unwrap();
+    // The values of the flags aren't URLs.
+    assert_eq!(
+        args.urls,
+        vec![
+            String::from("http://127.0.0.1:8888/a.html"),
+            String::from("http://127.0.0.1:8888/b.html"),
+            String::from("http://127.0.0.1:8888/c.html"),
+        ]
+    );
+    assert_eq!(args.width, Some(800.0));
+    assert_eq!(args.dump_mode, Some(DumpMode::Text));
+    assert_eq!(args.screenshot.as_deref(), Some("page.bmp"));
+    assert!(args.no_cache);
+    assert!(!args.help);
+    assert!(!args.timing);
+    assert_eq!(args.source, None);
+
+    let args = Args::parse(&["browser-rs.bin", "--help"]).unwrap();
+    assert!(args.help);
+    assert!(args.urls.is_empty());
+}
+
+#[test_case]
+fn later_flags_win() {
+    let args = Args::parse(&["browser-rs.bin", "--stdin", "--html", "<p>hi</p>"]).unwrap();
+    assert_eq!(args.source, Some(Source::Html(String::from("<p>hi</p>"))));
+    let args = Args::parse(&["browser-rs.bin", "--html", "<p>hi</p>", "--stdin"]).unwrap();
+    assert_eq!(args.source, Some(Source::Stdin));
+    // The dump modes are chosen in their order instead.
+    let args = Args::parse(&["browser-rs.bin", "--dump-layout", "--dump-dom"]).unwrap();
+    assert_eq!(args.dump_mode, Some(DumpMode::Dom));
+}
+
+#[test_case]
+fn invalid_flags() {
+    assert_eq!(
+        Args::parse(&["browser-rs.bin", "--url"]).unwrap_err(),
+        "--url needs a value"
+    );
+    assert_eq!(
+        Args::parse(&["browser-rs.bin", "--height", "-1"]).unwrap_err(),
+        "invalid --height: -1"
+    );
+    assert_eq!(
+        Args::parse(&["browser-rs.bin", "--color-scheme", "blue"]).unwrap_err(),
+        "invalid --color-scheme: blue"
+    );
+    assert!(Args::parse(&["browser-rs.bin", "--log", "net=loud"])
+        .unwrap_err()
+        .starts_with("invalid --log: "));
+}
+
+#[test_case]
+fn overriding_the_config() {
+    let (mut config, _) = Config::parse("width = 800\nuser_agent = config\nrefresh = on\n");
+    let args = Args::parse(&[
+        "browser-rs.bin",
+        "--height",
+        "300",
+        "--color-scheme",
+        "dark",
+        "--user-agent",
+        "flag",
+        "--no-refresh",
+        "--bookmarks",
+        "marks.txt",
+    ])
+    .unwrap();
+    args.apply(&mut config);
+    // The settings without flags are kept.
+    assert_eq!(config.viewport.width, 800.0);
+    assert_eq!(config.viewport.height, 300.0);
+    assert_eq!(config.viewport.color_scheme, ColorScheme::Dark);
+    assert_eq!(config.user_agent.as_deref(), Some("flag"));
+    assert!(!config.refresh);
+    assert_eq!(config.bookmarks_file, "marks.txt");
+    assert!(config.proxy.is_none());
+}
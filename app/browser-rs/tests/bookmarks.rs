@@ -0,0 +1,144 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::bookmarks::*;
+use browser_rs::keyboard::Key;
+use browser_rs::layout::geometry::Rect;
+use browser_rs::paint::display_list::*;
+use browser_rs::ui::bookmark_menu::*;
+use browser_rs::ui::Action;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in bookmarks.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn file_format() {
+    let bookmarks =
+        Bookmarks::parse("http://127.0.0.1:8888/index.html\tTop page\n\n127.0.0.1:8888/a.html\n");
+    assert_eq!(
+        bookmarks.entries(),
+        &vec![
+            Bookmark {
+                url: String::from("http://127.0.0.1:8888/index.html"),
+                title: String::from("Top page"),
+            },
+            Bookmark {
+                url: String::from("127.0.0.1:8888/a.html"),
+                title: String::new(),
+            },
+        ]
+    );
+    assert_eq!(
+        bookmarks.to_text(),
+        "http://127.0.0.1:8888/index.html\tTop page\n127.0.0.1:8888/a.html\t\n"
+    );
+    assert_eq!(Bookmarks::parse(&bookmarks.to_text()), bookmarks);
+}
+
+#[test_case]
+fn add() {
+    let mut bookmarks = Bookmarks::new();
+    assert!(bookmarks.add("http://a/", "A"));
+    assert!(bookmarks.add("http://b/", "B\tb\n"));
+    assert_eq!(bookmarks.entries()[1].title, "B b ");
+    // A page bookmarked again gets the new title.
+    assert!(!bookmarks.add("http://a/", "A2"));
+    assert_eq!(bookmarks.entries().len(), 2);
+    assert_eq!(bookmarks.entries()[0].title, "A2");
+}
+
+fn menu() -> BookmarkMenu {
+    let mut bookmarks = Bookmarks::new();
+    bookmarks.add("http://a/", "A");
+    bookmarks.add("http://b/", "");
+    bookmarks.add("http://c/", "C");
+    BookmarkMenu::new(&bookmarks)
+}
+
+#[test_case]
+fn choose() {
+    let mut menu = menu();
+    assert_eq!(
+        menu.lines(),
+        vec!["1. A - http://a/", "2. http://b/", "3. C - http://c/"]
+    );
+    assert_eq!(menu.handle_key(Key::Up), None);
+    assert_eq!(menu.handle_key(Key::Down), Some(Action::Edit));
+    assert_eq!(menu.handle_key(Key::End), Some(Action::Edit));
+    assert_eq!(menu.selected(), 2);
+    assert_eq!(menu.handle_key(Key::Tab), None);
+    assert_eq!(
+        menu.handle_key(Key::Enter),
+        Some(Action::Navigate(String::from("http://c/")))
+    );
+    assert_eq!(
+        menu.handle_key(Key::Char(b'2')),
+        Some(Action::Navigate(String::from("http://b/")))
+    );
+    assert_eq!(menu.handle_key(Key::Char(b'4')), None);
+    assert_eq!(menu.handle_key(Key::Escape), Some(Action::Cancel));
+
+    let mut empty = BookmarkMenu::new(&Bookmarks::new());
+    assert_eq!(empty.lines().len(), 1);
+    assert_eq!(empty.handle_key(Key::Down), None);
+    assert_eq!(empty.handle_key(Key::Enter), Some(Action::Cancel));
+}
+
+#[test_case]
+fn display_list() {
+    let mut menu = menu();
+    menu.handle_key(Key::Down);
+    let list = menu.display_list(200.0);
+    let texts: Vec<(String, f64)> = list
+        .iter()
+        .filter_map(|item| match item {
+            DisplayItem::Text { text, rect, .. } => Some((text.clone(), rect.y)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(texts.len(), 3);
+    assert_eq!(texts[1], (String::from("2. http://b/"), 22.0));
+    // The background, the rows with the selected one highlighted and the line under them.
+    assert_eq!(list.len(), 6);
+    assert_eq!(list[2].rect(), Rect::new(0.0, 20.0, 200.0, 20.0));
+    assert_eq!(list[5].rect().y, 60.0);
+}
@@ -0,0 +1,235 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use browser_rs::layout::box_tree::*;
+use browser_rs::parser::dom::*;
+use browser_rs::parser::parse;
+use browser_rs::style::computed::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in box_tree.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// Returns the box tree of `html` in lines such as `Block p`, `Inline span` and `Text "a"`.
+fn dump(html: &str) -> Vec<String> {
+    let document = parse(String::from(html));
+    let styles = compute_styles(&document, Viewport::default());
+    let mut lines = Vec::new();
+    if let Some(root) = build_box_tree(&document, &styles) {
+        dump_box(&document, &root, 0, &mut lines);
+    }
+    lines
+}
+
+fn dump_box(document: &Document, layout_box: &LayoutBox, depth: usize, lines: &mut Vec<String>) {
+    let tag = |b: &LayoutBox| match b.node().and_then(|n| document.node(n).element()) {
+        Some(e) => String::from(e.tag_name()),
+        None => String::new(),
+    };
+    let label = match layout_box.kind() {
+        BoxKind::Block => format!("Block {}", tag(layout_box)),
+        BoxKind::Inline => format!("Inline {}", tag(layout_box)),
+        BoxKind::AnonymousBlock => String::from("AnonymousBlock"),
+        BoxKind::Text(text) => format!("Text {:?}", text),
+        kind => format!("{:?} {}", kind, tag(layout_box)),
+    };
+    lines.push(format!("{}{}", "  ".repeat(depth), label));
+    for child in layout_box.children() {
+        dump_box(document, child, depth + 1, lines);
+    }
+}
+
+fn lines(expected: &[&str]) -> Vec<String> {
+    expected.iter().map(|l| String::from(*l)).collect()
+}
+
+#[test_case]
+fn blocks_and_inlines() {
+    assert_eq!(
+        dump(
+            "<html><head><title>t</title></head><body><p>a <b>b</b></p><div>c</div></body></html>"
+        ),
+        lines(&[
+            "Block html",
+            "  Block body",
+            "    Block p",
+            "      Text \"a \"",
+            "      Inline b",
+            "        Text \"b\"",
+            "    Block div",
+            "      Text \"c\"",
+        ])
+    );
+}
+
+#[test_case]
+fn anonymous_blocks() {
+    assert_eq!(
+        dump("<body>a<p>b</p>\n  <i>c</i> d\n<div>e</div>\n</body>"),
+        lines(&[
+            "Block html",
+            "  Block body",
+            "    AnonymousBlock",
+            "      Text \"a\"",
+            "    Block p",
+            "      Text \"b\"",
+            "    AnonymousBlock",
+            "      Text \" \"",
+            "      Inline i",
+            "        Text \"c\"",
+            "      Text \" d \"",
+            "    Block div",
+            "      Text \"e\"",
+        ])
+    );
+}
+
+#[test_case]
+fn white_space() {
+    assert_eq!(
+        dump("<body><p>\n  a \t b\n</p></body>"),
+        lines(&[
+            "Block html",
+            "  Block body",
+            "    Block p",
+            "      Text \" a b \""
+        ])
+    );
+}
+
+#[test_case]
+fn display_none_is_pruned() {
+    assert_eq!(
+        dump(
+            "<head><style>.x { display: none }</style></head>\
+             <body><p class=x>a<span>b</span></p><span class=x>c</span><p>d</p></body>"
+        ),
+        lines(&[
+            "Block html",
+            "  Block body",
+            "    Block p",
+            "      Text \"d\""
+        ])
+    );
+    assert_eq!(
+        dump("<html style=\"display: none\"><p>a</p></html>"),
+        lines(&[])
+    );
+}
+
+#[test_case]
+fn root_is_block() {
+    assert_eq!(
+        dump("<html style=\"display: inline\"><body style=\"display: inline\">a</body></html>"),
+        lines(&["Block html", "  Inline body", "    Text \"a\""])
+    );
+}
+
+#[test_case]
+fn block_in_inline() {
+    assert_eq!(
+        dump("<body><span>a<div>b</div></span></body>"),
+        lines(&[
+            "Block html",
+            "  Block body",
+            "    Block span",
+            "      AnonymousBlock",
+            "        Text \"a\"",
+            "      Block div",
+            "        Text \"b\"",
+        ])
+    );
+}
+
+#[test_case]
+fn text_style() {
+    let document = parse(String::from("<p style=\"color: red\">a</p>"));
+    let styles = compute_styles(&document, Viewport::default());
+    let root = build_box_tree(&document, &styles).unwrap();
+    let p = &root.children()[0].children()[0];
+    assert_eq!(p.children()[0].kind(), &BoxKind::Text(String::from("a")));
+    assert_eq!(p.children()[0].style().color(), p.style().color());
+}
+
+#[test_case]
+fn tables() {
+    assert_eq!(
+        dump("<table> <tr> <td>a</td> </tr> <tfoot><tr><th>b</th></tr></tfoot></table>"),
+        lines(&[
+            "Block html",
+            "  Block body",
+            "    Table table",
+            "      TableRow tr",
+            "        TableCell td",
+            "          Text \"a\"",
+            "      TableRowGroup tfoot",
+            "        TableRow tr",
+            "          TableCell th",
+            "            Text \"b\"",
+        ])
+    );
+}
+
+#[test_case]
+fn anonymous_table_boxes() {
+    // Boxes which can't be in a table are wrapped in anonymous rows and cells.
+    assert_eq!(
+        dump(
+            "<div style=\"display: table\"><i>a</i> <p style=\"display: table-cell\">b</p>\
+             <p style=\"display: table-row\">c</p></div>"
+        ),
+        lines(&[
+            "Block html",
+            "  Block body",
+            "    Table div",
+            "      TableRow ",
+            "        TableCell ",
+            "          Inline i",
+            "            Text \"a\"",
+            "          Text \" \"",
+            "        TableCell p",
+            "          Text \"b\"",
+            "      TableRow p",
+            "        TableCell ",
+            "          Text \"c\"",
+        ])
+    );
+}
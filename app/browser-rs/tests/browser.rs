@@ -0,0 +1,182 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+
+use browser_rs::browser::Browser;
+use browser_rs::config::Config;
+use browser_rs::net::RequestOptions;
+use browser_rs::parser::{parse, parse_with_scripts};
+use browser_rs::timing::Timing;
+use browser_rs::ui::status_bar::LoadState;
+use browser_rs::url::ParsedUrl;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in browser.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+const HTML: &str = "<title>Long</title><style>body { margin: 0 } div { height: 300px }</style>\
+                    <body><div></div><div id=b></div><div></div><div></div></body>";
+
+fn new_browser() -> Browser {
+    let (config, _) = Config::parse("homepage = http://127.0.0.1:8888/start.html");
+    Browser::new(config, RequestOptions::default(), false)
+}
+
+#[test_case]
+fn blank_tab() {
+    let browser = new_browser();
+    assert_eq!(browser.tabs().count(), 1);
+    let tab = browser.active_tab();
+    assert_eq!(tab.url.to_string(), "http://127.0.0.1:8888/start.html");
+    assert_eq!(tab.page.viewport(), browser.viewport());
+    assert_eq!(tab.page.document_height(), 0.0);
+}
+
+#[test_case]
+fn showing_a_document() {
+    let mut browser = new_browser();
+    let url = ParsedUrl::new(String::from("http://127.0.0.1:8888/long.html#b"));
+    browser.show(url, parse(String::from(HTML)), Timing::new());
+    let tab = browser.active_tab();
+    assert_eq!(tab.url.to_string(), "http://127.0.0.1:8888/long.html#b");
+    assert_eq!(tab.status.state(), LoadState::Done);
+    assert_eq!(tab.status.title(), "Long");
+    assert_eq!(tab.page.scroll_y(), 300.0);
+}
+
+#[test_case]
+fn going_to_a_fragment() {
+    let mut browser = new_browser();
+    let url = ParsedUrl::new(String::from("http://127.0.0.1:8888/long.html"));
+    browser.show(url, parse(String::from(HTML)), Timing::new());
+
+    // Another fragment of the same page is scrolled to without loading it.
+    let url = ParsedUrl::new(String::from("http://127.0.0.1:8888/long.html#b"));
+    assert!(browser.go_to_fragment(&url));
+    assert_eq!(browser.active_tab().page.scroll_y(), 300.0);
+    assert_eq!(
+        browser.active_tab().url.to_string(),
+        "http://127.0.0.1:8888/long.html#b"
+    );
+
+    // Another page needs loading.
+    let url = ParsedUrl::new(String::from("http://127.0.0.1:8888/other.html#b"));
+    assert!(!browser.go_to_fragment(&url));
+    assert_eq!(
+        browser.active_tab().url.to_string(),
+        "http://127.0.0.1:8888/long.html#b"
+    );
+}
+
+#[test_case]
+fn scheduling_a_refresh() {
+    let html = "<meta http-equiv=refresh content='0; url=next.html'><p>a</p>";
+    let url = ParsedUrl::new(String::from("http://127.0.0.1:8888/dir/a.html"));
+    let mut browser = new_browser();
+    browser.show(url.clone(), parse(String::from(html)), Timing::new());
+    let refresh = browser.active_tab().refresh.as_ref().unwrap();
+    assert_eq!(
+        refresh.url.to_string(),
+        "http://127.0.0.1:8888/dir/next.html"
+    );
+    assert_eq!(refresh.hops, 0);
+
+    // No refresh is scheduled if they're turned off.
+    let (config, _) = Config::parse("refresh = off");
+    let mut browser = Browser::new(config, RequestOptions::default(), false);
+    browser.show(url.clone(), parse(String::from(html)), Timing::new());
+    assert!(browser.active_tab().refresh.is_none());
+
+    // Nor is a refresh to an invalid URL.
+    let html = "<meta http-equiv=refresh content='0; url=http://127.0.0.1:99999/'>";
+    let mut browser = new_browser();
+    browser.show(url, parse(String::from(html)), Timing::new());
+    assert!(browser.active_tab().refresh.is_none());
+}
+
+#[test_case]
+fn navigating_by_scripts() {
+    // A script which navigates while the page is loaded is followed at once like a refresh.
+    let url = ParsedUrl::new(String::from("http://127.0.0.1:8888/dir/a.html"));
+    let html = String::from("<script>location.href = 'next.html';</script>");
+    let document = parse_with_scripts(
+        html,
+        &url.to_string(),
+        &mut Timing::new(),
+        Rc::new(|_| None),
+    );
+    let mut browser = new_browser();
+    browser.show(url, document, Timing::new());
+    let refresh = browser.active_tab().refresh.as_ref().unwrap();
+    assert_eq!(
+        refresh.url.to_string(),
+        "http://127.0.0.1:8888/dir/next.html"
+    );
+    assert_eq!(refresh.hops, 0);
+
+    // A listener which navigates to a fragment of the page only scrolls it.
+    let url = ParsedUrl::new(String::from("http://127.0.0.1:8888/long.html"));
+    let html = format!("{}<a id=link onclick=\"location.href = '#b'\">b</a>", HTML);
+    let document = parse_with_scripts(
+        html,
+        &url.to_string(),
+        &mut Timing::new(),
+        Rc::new(|_| None),
+    );
+    browser.show(url, document, Timing::new());
+    assert!(!browser.follow_navigation(|_| {}));
+    let page = &mut browser.active_tab_mut().page;
+    let link = page
+        .document()
+        .descendants(page.document().root())
+        .find(|n| {
+            page.document()
+                .node(*n)
+                .element()
+                .and_then(|e| e.get_attribute("id"))
+                == Some(String::from("link"))
+        })
+        .unwrap();
+    page.click(link);
+    assert!(browser.follow_navigation(|_| {}));
+    let tab = browser.active_tab();
+    assert_eq!(tab.url.to_string(), "http://127.0.0.1:8888/long.html#b");
+    assert_eq!(tab.page.scroll_y(), 300.0);
+}
@@ -0,0 +1,33 @@
+//! Helpers shared by the tests of scripts, which each test binary includes with `mod common;`.
+//! A binary uses only some of them.
+#![allow(dead_code)]
+
+use alloc::format;
+use alloc::string::String;
+use browser_rs::js::engine::Engine;
+use browser_rs::js::runtime::{Exception, Runtime};
+use browser_rs::js::value::Value;
+use browser_rs::parser::dom::Document;
+
+/// Returns the value of a script, or the exception it threw, as a string.
+fn show(result: Result<Value, Exception>) -> String {
+    match result {
+        Ok(value) => format!("{:?}", value),
+        Err(e) => format!("{}", e),
+    }
+}
+
+/// Runs `source` in a new runtime and returns the result as a string.
+pub fn run(source: &str) -> String {
+    show(Runtime::new().run(source))
+}
+
+/// Runs `source` in `runtime` and returns the result as a string.
+pub fn run_in(runtime: &mut Runtime, source: &str) -> String {
+    show(runtime.run(source))
+}
+
+/// Runs `source` with `engine` on an empty document and returns the result as a string.
+pub fn run_with(engine: &mut Engine, source: &str) -> String {
+    show(engine.run(&mut Document::new(), source))
+}
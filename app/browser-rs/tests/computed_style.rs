@@ -0,0 +1,494 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use browser_rs::parser::parse;
+use browser_rs::style::computed::*;
+use browser_rs::style::loader::*;
+use browser_rs::style::values::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in computed_style.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// Parses `html` and returns the computed style of the first element matching `selector`.
+fn style_of(html: &str, selector: &str) -> ComputedStyle {
+    let document = parse(String::from(html));
+    let styles = compute_styles(&document, Viewport::default());
+    let id = document.query_selector(document.root(), selector).unwrap();
+    styles.get(id).unwrap().clone()
+}
+
+#[test_case]
+fn initial_values() {
+    let style = style_of("<span>a</span>", "span");
+    assert_eq!(style, ComputedStyle::initial());
+    assert_eq!(style.display(), Display::Inline);
+    assert_eq!(style.color(), Color::BLACK);
+    assert_eq!(style.background_color(), Color::TRANSPARENT);
+    assert_eq!(style.font_size(), DEFAULT_FONT_SIZE);
+}
+
+#[test_case]
+fn every_element_has_a_style() {
+    let document = parse(String::from(
+        "<html><body><p>a<b>b</b></p><!-- c --></body></html>",
+    ));
+    let styles = compute_styles(&document, Viewport::default());
+    // html, head, body, p and b.
+    assert_eq!(styles.len(), 5);
+    for id in document.descendants(document.root()) {
+        assert_eq!(
+            styles.get(id).is_some(),
+            document.node(id).element().is_some()
+        );
+    }
+}
+
+#[test_case]
+fn declared_values() {
+    let html = "<style>p { display: block; color: red; background-color: navy; font-size: 20px; \
+                margin-top: 8px; margin-left: 0; padding-bottom: 3px }</style><p>a</p>";
+    let style = style_of(html, "p");
+    assert_eq!(style.display(), Display::Block);
+    assert_eq!(style.color(), Color::rgb(255, 0, 0));
+    assert_eq!(style.background_color(), Color::rgb(0, 0, 128));
+    assert_eq!(style.font_size(), 20.0);
+    assert_eq!(style.margin().top, Length::Px(8.0));
+    assert_eq!(style.margin().left, Length::Px(0.0));
+    assert_eq!(style.padding().bottom, Length::Px(3.0));
+}
+
+#[test_case]
+fn invalid_values_are_ignored() {
+    let html =
+        "<style>span { display: flexible; color: nocolor; margin-top: 8 }</style><span>a</span>";
+    assert_eq!(style_of(html, "span"), ComputedStyle::initial());
+}
+
+#[test_case]
+fn inline_style_and_cascade() {
+    let html = "<style>p { color: red } .a { color: blue }</style>\
+                <p class=a style=\"background-color: yellow\">a</p>";
+    let style = style_of(html, "p");
+    assert_eq!(style.color(), Color::rgb(0, 0, 255));
+    assert_eq!(style.background_color(), Color::rgb(255, 255, 0));
+}
+
+#[test_case]
+fn inherited_from_parent() {
+    let html = "<style>div { color: green; font-size: 10px; margin-top: 5px; \
+                background-color: red }</style><div><span>a</span></div>";
+    let style = style_of(html, "span");
+    assert_eq!(style.color(), Color::rgb(0, 128, 0));
+    assert_eq!(style.font_size(), 10.0);
+    // Non-inherited properties take their initial values.
+    assert_eq!(style.margin().top, Length::Px(0.0));
+    assert_eq!(style.background_color(), Color::TRANSPARENT);
+}
+
+#[test_case]
+fn inherited_properties() {
+    assert!(is_inherited("color"));
+    assert!(is_inherited("font-size"));
+    assert!(!is_inherited("display"));
+    assert!(!is_inherited("margin-top"));
+    assert!(!is_inherited("unknown"));
+}
+
+#[test_case]
+fn inherit_keyword() {
+    let html = "<style>div { color: red; margin-top: 4px; background-color: blue } \
+                span { color: green; margin-top: inherit; background-color: INHERIT } \
+                em { color: inherit }</style><div><span>a<em>b</em></span></div>";
+    let span = style_of(html, "span");
+    assert_eq!(span.color(), Color::rgb(0, 128, 0));
+    assert_eq!(span.margin().top, Length::Px(4.0));
+    assert_eq!(span.background_color(), Color::rgb(0, 0, 255));
+    assert_eq!(style_of(html, "em").color(), Color::rgb(0, 128, 0));
+}
+
+#[test_case]
+fn initial_keyword() {
+    let html = "<style>div { color: red; font-size: 30px } \
+                span { color: initial; display: block } \
+                span { display: initial }</style><div><span>a</span></div>";
+    let span = style_of(html, "span");
+    assert_eq!(span.color(), Color::BLACK);
+    assert_eq!(span.font_size(), 30.0);
+    assert_eq!(span.display(), Display::Inline);
+}
+
+#[test_case]
+fn unset_keyword() {
+    let html = "<style>div { color: red; margin-top: 4px } \
+                span { color: blue; margin-top: 2px } \
+                span { color: unset; margin-top: unset }</style><div><span>a</span></div>";
+    let span = style_of(html, "span");
+    assert_eq!(span.color(), Color::rgb(255, 0, 0));
+    assert_eq!(span.margin().top, Length::Px(0.0));
+}
+
+#[test_case]
+fn inherit_at_root() {
+    let html = "<html style=\"color: inherit; font-size: inherit\"><p>a</p></html>";
+    let style = style_of(html, "html");
+    assert_eq!(style.color(), Color::BLACK);
+    assert_eq!(style.font_size(), DEFAULT_FONT_SIZE);
+}
+
+#[test_case]
+fn user_agent_style_sheet() {
+    let html = "<body><h1>a</h1><p>b <a href=x><b>c</b></a></p></body>";
+
+    let body = style_of(html, "body");
+    assert_eq!(body.display(), Display::Block);
+    assert_eq!(body.margin().left, Length::Px(8.0));
+
+    let h1 = style_of(html, "h1");
+    assert_eq!(h1.display(), Display::Block);
+    assert_eq!(h1.font_size(), 32.0);
+    assert_eq!(h1.font_weight(), FONT_WEIGHT_BOLD);
+
+    let p = style_of(html, "p");
+    assert_eq!(p.margin().top, Length::Px(16.0));
+    assert_eq!(p.font_weight(), FONT_WEIGHT_NORMAL);
+
+    let link = style_of(html, "a");
+    assert_eq!(link.display(), Display::Inline);
+    assert_eq!(link.color(), Color::rgb(0, 0, 255));
+    assert_eq!(link.text_decoration(), TextDecoration::Underline);
+
+    // The decoration is drawn across the text of the descendants.
+    let b = style_of(html, "b");
+    assert_eq!(b.color(), Color::rgb(0, 0, 255));
+    assert_eq!(b.text_decoration(), TextDecoration::Underline);
+
+    // An anchor without href is not a link.
+    let anchor = style_of("<p><a>d</a></p>", "a");
+    assert_eq!(anchor.text_decoration(), TextDecoration::None);
+}
+
+#[test_case]
+fn author_overrides_user_agent() {
+    let html = "<style>h1 { font-size: 20px; font-weight: normal } \
+                a { color: red; text-decoration: none }</style><h1>a</h1><a href=x>b</a>";
+    let h1 = style_of(html, "h1");
+    assert_eq!(h1.font_size(), 20.0);
+    assert_eq!(h1.font_weight(), FONT_WEIGHT_NORMAL);
+    let link = style_of(html, "a");
+    assert_eq!(link.color(), Color::rgb(255, 0, 0));
+    assert_eq!(link.text_decoration(), TextDecoration::None);
+}
+
+#[test_case]
+fn font_relative_lengths() {
+    let html = "<html style=\"font-size: 20px\"><body><style>div { font-size: 2em; \
+                margin-top: 1em; margin-left: 1rem; padding-top: 0.5em } \
+                span { font-size: 50%; margin-top: 2em; padding-left: 2rem }</style>\
+                <div><span>a</span></div></body></html>";
+
+    let div = style_of(html, "div");
+    assert_eq!(div.font_size(), 40.0);
+    assert_eq!(div.margin().top, Length::Px(40.0));
+    assert_eq!(div.margin().left, Length::Px(20.0));
+    assert_eq!(div.padding().top, Length::Px(20.0));
+
+    let span = style_of(html, "span");
+    assert_eq!(span.font_size(), 20.0);
+    assert_eq!(span.margin().top, Length::Px(40.0));
+    assert_eq!(span.padding().left, Length::Px(40.0));
+}
+
+#[test_case]
+fn rem_on_root_element() {
+    let html = "<html style=\"font-size: 2rem; margin-top: 1rem\"></html>";
+    let style = style_of(html, "html");
+    assert_eq!(style.font_size(), 32.0);
+    // rem on the root element refers to the initial font size.
+    assert_eq!(style.margin().top, Length::Px(16.0));
+}
+
+#[test_case]
+fn percentages_are_resolved_in_layout() {
+    let html = "<style>p { margin-top: 10%; padding-left: 5% }</style><p>a</p>";
+    let p = style_of(html, "p");
+    assert_eq!(p.margin().top, Length::Percentage(10.0));
+    let px = p.margin().to_px(300.0);
+    assert_eq!(px.top, 30.0);
+    assert_eq!(px.bottom, 16.0);
+    assert_eq!(p.padding().to_px(300.0).left, 15.0);
+}
+
+#[test_case]
+fn negative_lengths() {
+    let html = "<style>p { margin-top: -4px; padding-top: -4px; font-size: -1px }</style><p>a</p>";
+    let p = style_of(html, "p");
+    assert_eq!(p.margin().top, Length::Px(-4.0));
+    assert_eq!(p.padding().top, Length::Px(0.0));
+    assert_eq!(p.font_size(), DEFAULT_FONT_SIZE);
+}
+
+#[test_case]
+fn width_and_height() {
+    let html = "<style>div { width: 10em; height: 50%; margin: 0 auto } \
+                p { width: -1px; height: auto }</style><div>a</div><p>b</p>";
+    let div = style_of(html, "div");
+    assert_eq!(div.width(), Length::Px(160.0));
+    assert_eq!(div.height(), Length::Percentage(50.0));
+    assert_eq!(div.margin().left, Length::Auto);
+    assert_eq!(div.margin().top, Length::Px(0.0));
+
+    let p = style_of(html, "p");
+    assert_eq!(p.width(), Length::Auto);
+    assert_eq!(p.height(), Length::Auto);
+}
+
+#[test_case]
+fn list_style_type() {
+    let html = "<style>.a { list-style-type: lower-alpha } .n { list-style-type: none }\
+                .x { list-style-type: bogus }</style><ul><li>a<ul><li>b<ol><li>c<ul>\
+                <li>d</li></ul></li></ol></li></ul></li></ul><ol class=a><li class=x>e</li>\
+                <li class=n>f</li></ol>";
+    let li = style_of(html, "li");
+    assert_eq!(li.display(), Display::ListItem);
+    assert_eq!(li.list_style_type(), ListStyleType::Disc);
+    assert_eq!(
+        style_of(html, "ul ul > li").list_style_type(),
+        ListStyleType::Circle
+    );
+    assert_eq!(
+        style_of(html, "ol > li").list_style_type(),
+        ListStyleType::Decimal
+    );
+    assert_eq!(
+        style_of(html, "ol ul > li").list_style_type(),
+        ListStyleType::Square
+    );
+    // The property is inherited.
+    assert_eq!(
+        style_of(html, ".x").list_style_type(),
+        ListStyleType::LowerAlpha
+    );
+    assert_eq!(style_of(html, ".n").list_style_type(), ListStyleType::None);
+}
+
+#[test_case]
+fn display_none() {
+    let html = "<html><head><title>t</title><style>.x { display: none }</style></head>\
+                <body><p class=x><span>a</span></p><div hidden>b</div></body></html>";
+    assert_eq!(style_of(html, "head").display(), Display::None);
+    assert_eq!(style_of(html, "title").display(), Display::None);
+    assert_eq!(style_of(html, "style").display(), Display::None);
+    assert_eq!(style_of(html, "p").display(), Display::None);
+    assert_eq!(style_of(html, "div").display(), Display::None);
+    // display is not inherited. The span generates no box because its parent doesn't.
+    assert_eq!(style_of(html, "span").display(), Display::Inline);
+    assert_eq!(style_of(html, "body").display(), Display::Block);
+}
+
+#[test_case]
+fn display_keywords() {
+    let html = "<style>p { display: none } p { display: inline } div { display: NONE } \
+                span { display: block }</style><p>a</p><div>b</div><span>c</span>";
+    assert_eq!(style_of(html, "p").display(), Display::Inline);
+    assert_eq!(style_of(html, "div").display(), Display::None);
+    assert_eq!(style_of(html, "span").display(), Display::Block);
+}
+
+#[test_case]
+fn linked_style_sheets() {
+    let mut document = parse(String::from(
+        "<html><head><link rel=stylesheet href=a.css><style>p { color: red }</style>\
+         <link rel=stylesheet href=missing.css><link rel=stylesheet href=b.css></head>\
+         <body><p>a</p><span>b</span></body></html>",
+    ));
+    load_style_sheets(&mut document, |href| match href {
+        "a.css" => Some(String::from(
+            "p { color: blue; font-size: 10px } span { color: blue }",
+        )),
+        "b.css" => Some(String::from("p { font-size: 20px }")),
+        _ => None,
+    });
+
+    let styles = compute_styles(&document, Viewport::default());
+    let p = styles
+        .get(document.query_selector(document.root(), "p").unwrap())
+        .unwrap();
+    // The style element comes after a.css and b.css comes after the style element.
+    assert_eq!(p.color(), Color::rgb(255, 0, 0));
+    assert_eq!(p.font_size(), 20.0);
+    let span = styles
+        .get(document.query_selector(document.root(), "span").unwrap())
+        .unwrap();
+    assert_eq!(span.color(), Color::rgb(0, 0, 255));
+}
+
+#[test_case]
+fn box_shorthands() {
+    let html = "<style>p { margin: 1px 2px 3px; padding: 4px 5px } \
+                div { margin: 1em; padding: 6px; padding-left: 7px }</style>\
+                <div style=\"font-size: 10px\"><p>a</p></div>";
+
+    let p = style_of(html, "p");
+    assert_eq!(
+        p.margin(),
+        Edges {
+            top: Length::Px(1.0),
+            right: Length::Px(2.0),
+            bottom: Length::Px(3.0),
+            left: Length::Px(2.0),
+        }
+    );
+    assert_eq!(
+        p.padding(),
+        Edges {
+            top: Length::Px(4.0),
+            right: Length::Px(5.0),
+            bottom: Length::Px(4.0),
+            left: Length::Px(5.0),
+        }
+    );
+
+    let div = style_of(html, "div");
+    assert_eq!(div.margin().to_px(0.0).bottom, 10.0);
+    assert_eq!(div.padding().to_px(0.0).right, 6.0);
+    assert_eq!(div.padding().to_px(0.0).left, 7.0);
+}
+
+#[test_case]
+fn borders() {
+    let html = "<style>p { color: green; border-width: thin 2px 3px 0.5em; \
+                border-style: solid dashed none; border-color: red currentcolor } \
+                span { border-width: 4px; border-style: INHERIT }</style>\
+                <p style=\"font-size: 10px\">a<span>b</span></p>";
+
+    let p = style_of(html, "p");
+    assert_eq!(
+        p.border_width(),
+        Edges {
+            top: 1.0,
+            right: 2.0,
+            bottom: 0.0,
+            left: 5.0,
+        }
+    );
+    assert_eq!(
+        p.border_style(),
+        Edges {
+            top: BorderStyle::Solid,
+            right: BorderStyle::Dashed,
+            bottom: BorderStyle::None,
+            left: BorderStyle::Dashed,
+        }
+    );
+    let green = Color::rgb(0, 128, 0);
+    let red = Color::rgb(255, 0, 0);
+    assert_eq!(
+        p.border_color(),
+        Edges {
+            top: red,
+            right: green,
+            bottom: red,
+            left: green,
+        }
+    );
+
+    let span = style_of(html, "span");
+    assert_eq!(span.border_width().top, 4.0);
+    assert_eq!(span.border_style().right, BorderStyle::Dashed);
+    // The initial border color is currentcolor.
+    assert_eq!(span.border_color().top, green);
+}
+
+#[test_case]
+fn initial_borders() {
+    let style = style_of(
+        "<style>span { border-top-style: solid }</style><span>a</span>",
+        "span",
+    );
+    // The initial width is medium, but a side without a style has no border.
+    assert_eq!(style.border_width().top, 3.0);
+    assert_eq!(style.border_width().left, 0.0);
+    assert_eq!(style.border_color().top, Color::BLACK);
+}
+
+#[test_case]
+fn media_queries() {
+    let html = "<style>p { color: red } @media (max-width: 400px) { p { color: blue } } \
+                @media print { p { font-size: 30px } }</style><p>a</p>";
+    let document = parse(String::from(html));
+    let p = document.query_selector(document.root(), "p").unwrap();
+
+    let narrow = Viewport {
+        width: 300.0,
+        height: 300.0,
+        color_scheme: ColorScheme::Light,
+    };
+    let styles = compute_styles(&document, narrow);
+    assert_eq!(styles.get(p).unwrap().color(), Color::rgb(0, 0, 255));
+    assert_eq!(styles.get(p).unwrap().font_size(), DEFAULT_FONT_SIZE);
+
+    let wide = Viewport {
+        width: 800.0,
+        height: 300.0,
+        color_scheme: ColorScheme::Light,
+    };
+    let styles = compute_styles(&document, wide);
+    assert_eq!(styles.get(p).unwrap().color(), Color::rgb(255, 0, 0));
+}
+
+#[test_case]
+fn position() {
+    let html = "<style>p { position: relative; top: 1em; left: -10% } \
+                span { position: absolute; right: 0 } i { position: bogus }</style>\
+                <p>a</p><span>b</span><i>c</i>";
+    let p = style_of(html, "p");
+    assert_eq!(p.position(), Position::Relative);
+    assert_eq!(p.inset().top, Length::Px(16.0));
+    assert_eq!(p.inset().left, Length::Percentage(-10.0));
+    assert_eq!(p.inset().bottom, Length::Auto);
+    assert_eq!(p.display(), Display::Block);
+    // An absolutely positioned box is blockified.
+    let span = style_of(html, "span");
+    assert_eq!(span.position(), Position::Absolute);
+    assert_eq!(span.inset().right, Length::Px(0.0));
+    assert_eq!(span.display(), Display::Block);
+    assert_eq!(style_of(html, "i").position(), Position::Static);
+    assert_eq!(style_of(html, "i").display(), Display::Inline);
+}
@@ -0,0 +1,175 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+
+use browser_rs::config::*;
+use browser_rs::net::RequestOptions;
+use browser_rs::style::computed::{ColorScheme, Viewport};
+use browser_rs::url::ParsedUrl;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in config.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn settings() {
+    let text = "# The settings of the browser.\n\
+                homepage = http://10.0.2.2:8888/start.html\n\
+                \n\
+                width=800\n\
+                height = 300\n\
+                color_scheme = Dark\n\
+                user_agent = browser-rs/0.1 (liumOS)\n\
+                proxy = 10.0.2.2:8080\n\
+                bookmarks = marks.txt\n\
+                cookies = jar.txt\n\
+                refresh = off\n";
+    let (config, errors) = Config::parse(text);
+    assert!(errors.is_empty());
+    assert_eq!(config.homepage, "http://10.0.2.2:8888/start.html");
+    assert_eq!(
+        config.viewport,
+        Viewport {
+            width: 800.0,
+            height: 300.0,
+            color_scheme: ColorScheme::Dark,
+        }
+    );
+    assert_eq!(
+        config.user_agent.as_deref(),
+        Some("browser-rs/0.1 (liumOS)")
+    );
+    let proxy = config.proxy.unwrap();
+    assert_eq!((proxy.host.as_str(), proxy.port), ("10.0.2.2:8080", 8080));
+    assert_eq!(config.bookmarks_file, "marks.txt");
+    assert_eq!(config.cookies_file, "jar.txt");
+    assert!(!config.refresh);
+}
+
+#[test_case]
+fn defaults() {
+    let (config, errors) = Config::parse("");
+    assert!(errors.is_empty());
+    assert_eq!(config.homepage, DEFAULT_HOMEPAGE);
+    assert_eq!(config.viewport, Viewport::default());
+    assert_eq!(config.user_agent, None);
+    assert!(config.proxy.is_none());
+    assert_eq!(config.bookmarks_file, "bookmarks.txt");
+    assert_eq!(config.cookies_file, "cookies.txt");
+    assert!(config.refresh);
+    assert!(config.no_proxy.is_empty());
+    assert!(!config.debug);
+
+    // A missing file is the defaults.
+    let (config, errors) = Config::load("missing.conf");
+    assert!(errors.is_empty());
+    assert_eq!(config.homepage, DEFAULT_HOMEPAGE);
+}
+
+#[test_case]
+fn invalid_lines() {
+    let text = "width = -1\nheight = 200\nhomepage = https://example.com/\nfont = 12\nproxy\nrefresh = no\n";
+    let (config, errors) = Config::parse(text);
+    // The valid lines are still used.
+    assert_eq!(config.viewport.width, 600.0);
+    assert_eq!(config.viewport.height, 200.0);
+    assert_eq!(
+        errors,
+        vec![
+            String::from("line 1: invalid width: -1"),
+            String::from("line 3: invalid homepage: https://example.com/"),
+            String::from("line 4: unknown key: font"),
+            String::from("line 5: expected key = value"),
+            String::from("line 6: invalid refresh: no"),
+        ]
+    );
+}
+
+/// Returns the variables in `vars` of NAME=value.
+fn environment<'a>(vars: &'a [&'a str]) -> impl Fn(&str) -> Option<&'a str> {
+    move |name| {
+        vars.iter()
+            .filter_map(|var| var.split_once('='))
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| value)
+    }
+}
+
+#[test_case]
+fn environment_variables() {
+    let (mut config, _) = Config::parse("proxy = 10.0.2.2:8080\n");
+    let vars = [
+        "http_proxy=http://127.0.0.1:3128/",
+        "NO_PROXY=localhost, .example.com,,",
+        "BROWSER_DEBUG=1",
+    ];
+    assert!(config.apply_env(environment(&vars)).is_empty());
+    assert_eq!(config.proxy.unwrap().host, "127.0.0.1:3128");
+    assert_eq!(config.no_proxy, vec!["localhost", ".example.com"]);
+    assert!(config.debug);
+
+    // An empty proxy turns it off, and an invalid one is ignored.
+    let (mut config, _) = Config::parse("proxy = 10.0.2.2:8080\n");
+    assert!(config
+        .apply_env(environment(&["HTTP_PROXY=", "BROWSER_DEBUG=0"]))
+        .is_empty());
+    assert!(config.proxy.is_none());
+    assert!(!config.debug);
+    let errors = config.apply_env(environment(&["http_proxy=https://proxy/"]));
+    assert_eq!(errors, vec!["invalid http_proxy: https://proxy/"]);
+    assert!(config.proxy.is_none());
+}
+
+#[test_case]
+fn bypassing_the_proxy() {
+    let url = |u: &str| ParsedUrl::new(String::from(u));
+    let mut options = RequestOptions {
+        proxy: Some(url("http://10.0.2.2:8080/")),
+        no_proxy: vec![String::from("localhost"), String::from(".example.com")],
+        ..RequestOptions::default()
+    };
+    let proxied = |options: &RequestOptions, u: &str| options.proxy_for(&url(u)).is_some();
+    assert!(proxied(&options, "http://example.org/"));
+    assert!(!proxied(&options, "http://localhost:8888/index.html"));
+    assert!(!proxied(&options, "http://example.com/"));
+    assert!(!proxied(&options, "http://www.EXAMPLE.com/"));
+    assert!(proxied(&options, "http://notexample.com/"));
+    options.no_proxy = vec![String::from("*")];
+    assert!(!proxied(&options, "http://example.org/"));
+}
@@ -0,0 +1,275 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::rc::Rc;
+use alloc::string::String;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use browser_rs::cookie::*;
+use browser_rs::http::{HttpResponse, Method};
+use browser_rs::net::{new_request, RequestOptions};
+use browser_rs::url::ParsedUrl;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in cookie.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// The time of the fake clock in milliseconds. Each test starts with setting it since the tests
+/// share it.
+static NOW: AtomicU64 = AtomicU64::new(0);
+
+fn now() -> u64 {
+    NOW.load(Ordering::SeqCst)
+}
+
+/// Returns an empty jar whose clock is `NOW` at 0.
+fn jar() -> CookieJar {
+    NOW.store(0, Ordering::SeqCst);
+    CookieJar::with_clock(now)
+}
+
+fn url(s: &str) -> ParsedUrl {
+    ParsedUrl::new(String::from(s))
+}
+
+/// Returns the cookies sent to `u`, or an empty string if there's none.
+fn sent(jar: &CookieJar, u: &str) -> String {
+    jar.header_for(&url(u)).unwrap_or_default()
+}
+
+#[test_case]
+fn set_and_send() {
+    let jar = jar();
+    let page = url("http://example.com:8000/a/b.html");
+    jar.set(&page, "sid=abc123", Source::Http);
+    jar.set(&page, " theme = dark ; Path=/", Source::Http);
+    // A cookie without Path is for the directory of the page.
+    assert_eq!(sent(&jar, "example.com/a/c.html"), "sid=abc123; theme=dark");
+    assert_eq!(sent(&jar, "example.com/b.html"), "theme=dark");
+    assert_eq!(sent(&jar, "example.com/ab"), "theme=dark");
+    // The port doesn't matter, but the host does.
+    assert_eq!(sent(&jar, "example.com:8888/a"), "sid=abc123; theme=dark");
+    assert_eq!(jar.header_for(&url("other.com/a")), None);
+    assert_eq!(jar.header_for(&url("sub.example.com/a")), None);
+
+    // A cookie with the same name, domain and path is replaced in its place.
+    jar.set(&page, "sid=xyz", Source::Http);
+    assert_eq!(sent(&jar, "example.com/a/"), "sid=xyz; theme=dark");
+
+    // Invalid ones are ignored.
+    jar.set(&page, "no-value", Source::Http);
+    jar.set(&page, "=empty-name", Source::Http);
+    jar.set(&page, "secure=1; Secure", Source::Http);
+    assert_eq!(sent(&jar, "example.com/a/"), "sid=xyz; theme=dark");
+}
+
+#[test_case]
+fn paths_and_domains() {
+    let jar = jar();
+    let page = url("http://www.example.com/");
+    jar.set(&page, "a=1; Path=/docs", Source::Http);
+    jar.set(&page, "b=2; Path=/docs/api/", Source::Http);
+    jar.set(&page, "c=3; Domain=.Example.com", Source::Http);
+    // A Domain which isn't the host or its parent is ignored.
+    jar.set(&page, "d=4; Domain=other.com", Source::Http);
+    jar.set(&page, "e=5; Domain=sub.www.example.com", Source::Http);
+
+    // Longer paths come first.
+    assert_eq!(sent(&jar, "www.example.com/docs/api/x"), "b=2; a=1; c=3");
+    assert_eq!(sent(&jar, "www.example.com/docs"), "a=1; c=3");
+    assert_eq!(sent(&jar, "www.example.com/docsearch"), "c=3");
+    assert_eq!(sent(&jar, "example.com/"), "c=3");
+    assert_eq!(sent(&jar, "api.example.com/docs"), "c=3");
+    assert_eq!(sent(&jar, "notexample.com/"), "");
+
+    // An IP address has no subdomain.
+    let jar = self::jar();
+    jar.set(&url("127.0.0.1/"), "a=1; Domain=0.0.1", Source::Http);
+    jar.set(&url("127.0.0.1/"), "b=2; Domain=127.0.0.1", Source::Http);
+    assert_eq!(sent(&jar, "127.0.0.1/"), "b=2");
+}
+
+#[test_case]
+fn expiry() {
+    let jar = jar();
+    let page = url("example.com/");
+    jar.set(&page, "a=1; Max-Age=10", Source::Http);
+    jar.set(
+        &page,
+        "b=2; Expires=Wed, 21 Oct 2099 07:28:00 GMT",
+        Source::Http,
+    );
+    jar.set(
+        &page,
+        "c=3; Expires=Thu, 01 Jan 1970 00:00:00 GMT",
+        Source::Http,
+    );
+    // Max-Age takes precedence over Expires.
+    jar.set(
+        &page,
+        "d=4; Max-Age=0; Expires=Wed, 21 Oct 2099 07:28:00 GMT",
+        Source::Http,
+    );
+    assert_eq!(sent(&jar, "example.com/"), "a=1; b=2");
+
+    NOW.store(10_000, Ordering::SeqCst);
+    assert_eq!(sent(&jar, "example.com/"), "b=2");
+
+    // An expired cookie removes the one it replaces.
+    jar.set(&page, "b=; Max-Age=-1", Source::Http);
+    assert_eq!(sent(&jar, "example.com/"), "");
+}
+
+#[test_case]
+fn time_from_responses() {
+    let jar = jar();
+    let page = url("example.com/");
+    // The time is 2015-10-21T07:28:00Z from now on.
+    let response = HttpResponse::new(String::from(
+        "HTTP/1.1 200 OK\r\n\
+         Date: Wed, 21 Oct 2015 07:28:00 GMT\r\n\
+         Set-Cookie: a=1; Expires=Wed, 21 Oct 2015 07:28:30 GMT\r\n\
+         Set-Cookie: b=2; Expires=Wed, 21 Oct 2015 07:27:00 GMT\r\n\
+         set-cookie: c=3\r\n\
+         \r\n",
+    ));
+    jar.store_response(&page, &response);
+    assert_eq!(sent(&jar, "example.com/"), "a=1; c=3");
+    NOW.store(30_000, Ordering::SeqCst);
+    assert_eq!(sent(&jar, "example.com/"), "c=3");
+}
+
+#[test_case]
+fn time_from_wall_clock() {
+    // Linux has a clock of the date, so a jar knows it's later than 2010 without responses.
+    let jar = CookieJar::new();
+    let page = url("example.com/");
+    jar.set(
+        &page,
+        "a=1; Expires=Fri, 01 Jan 2010 00:00:00 GMT",
+        Source::Http,
+    );
+    jar.set(
+        &page,
+        "b=2; Expires=Wed, 21 Oct 2099 07:28:00 GMT",
+        Source::Http,
+    );
+    assert_eq!(sent(&jar, "example.com/"), "b=2");
+}
+
+#[test_case]
+fn http_only() {
+    let jar = jar();
+    let page = url("example.com/");
+    jar.set(&page, "sid=1; HttpOnly", Source::Http);
+    jar.set(&page, "theme=dark", Source::Script);
+    // Scripts can neither read nor replace an HttpOnly cookie, nor set one.
+    jar.set(&page, "sid=2", Source::Script);
+    jar.set(&page, "x=1; HttpOnly", Source::Script);
+    assert_eq!(jar.serialize(&page, Source::Script), "theme=dark");
+    assert_eq!(sent(&jar, "example.com/"), "sid=1; theme=dark");
+}
+
+#[test_case]
+fn saving() {
+    let jar = jar();
+    let page = url("http://www.example.com/a/b");
+    jar.set(&page, "sid=1", Source::Http);
+    jar.set(&page, "a=1; Max-Age=100", Source::Http);
+    jar.set(
+        &page,
+        "b=2; Max-Age=100; Domain=example.com; Path=/; HttpOnly",
+        Source::Http,
+    );
+    jar.set(&page, "c=3; Max-Age=10", Source::Http);
+    NOW.store(10_000, Ordering::SeqCst);
+    // The session cookie and the expired one aren't saved.
+    let text = jar.to_text();
+    assert_eq!(
+        text,
+        "# Netscape HTTP Cookie File\n\
+         www.example.com\tFALSE\t/a\tFALSE\t946684900\ta\t1\n\
+         #HttpOnly_.example.com\tTRUE\t/\tFALSE\t946684900\tb\t2\n"
+    );
+
+    let loaded = self::jar();
+    loaded.set(&page, "a=new; Max-Age=100", Source::Http);
+    loaded.load_text(&text);
+    loaded.load_text("broken line\nexample.com\tFALSE\t/\tFALSE\t1\told\t1\n");
+    assert_eq!(sent(&loaded, "www.example.com/a/"), "a=new; b=2");
+    assert_eq!(sent(&loaded, "sub.example.com/"), "b=2");
+    assert_eq!(loaded.serialize(&url("example.com/"), Source::Script), "");
+
+    // A file which doesn't exist has no cookies.
+    let missing = self::jar();
+    missing.load("missing-cookies.txt");
+    assert_eq!(sent(&missing, "www.example.com/a/"), "");
+}
+
+#[test_case]
+fn dates() {
+    let date = |s: &str| parse_date(s);
+    assert_eq!(date("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+    assert_eq!(date("Wed, 21 Oct 2015 07:28:00 GMT"), Some(1_445_412_480));
+    // The formats of the old days, and two-digit years.
+    assert_eq!(
+        date("Wednesday, 21-Oct-15 07:28:00 GMT"),
+        Some(1_445_412_480)
+    );
+    assert_eq!(date("Wed Oct 21 7:28:00 2015"), Some(1_445_412_480));
+    assert_eq!(date("29 Feb 2000 12:00:00"), Some(951_825_600));
+    assert_eq!(date("01 Jan 1969 00:00:00"), Some(-31_536_000));
+    assert_eq!(date("Wed, 21 Oct 2015"), None);
+    assert_eq!(date("Wed, 32 Oct 2015 07:28:00 GMT"), None);
+    assert_eq!(date("Wed, 21 Foo 2015 07:28:00 GMT"), None);
+    assert_eq!(date("Wed, 21 Oct 2015 24:00:00 GMT"), None);
+}
+
+#[test_case]
+fn requests() {
+    let jar = Rc::new(jar());
+    let page = url("example.com/");
+    jar.set(&page, "a=1", Source::Http);
+    let options = RequestOptions {
+        cookies: Some(jar),
+        ..RequestOptions::default()
+    };
+    let request = new_request(Method::Get, &page, &options).string();
+    assert!(request.contains("\nCookie: a=1\n"), "{}", request);
+    let request = new_request(Method::Get, &url("other.com/"), &options).string();
+    assert!(!request.contains("Cookie"), "{}", request);
+}
@@ -0,0 +1,191 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::parser::css::tokenizer::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in css_tokenizer.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[macro_export]
+macro_rules! run_test {
+    ($css:literal, $( $token:expr ),*) => {
+        let t = CssTokenizer::new(String::from($css));
+
+        let expected = vec![$($token),*];
+
+        assert_eq!(t.collect::<Vec<CssToken>>(), expected);
+    };
+}
+
+fn ident(s: &str) -> CssToken {
+    CssToken::Ident(String::from(s))
+}
+
+#[test_case]
+fn empty() {
+    let mut t = CssTokenizer::new(String::from(""));
+    assert_eq!(t.next(), None);
+}
+
+#[test_case]
+fn rule() {
+    run_test!(
+        "p > a { color: red; }",
+        ident("p"),
+        CssToken::Whitespace,
+        CssToken::Delim('>'),
+        CssToken::Whitespace,
+        ident("a"),
+        CssToken::Whitespace,
+        CssToken::OpenCurly,
+        CssToken::Whitespace,
+        ident("color"),
+        CssToken::Colon,
+        CssToken::Whitespace,
+        ident("red"),
+        CssToken::Semicolon,
+        CssToken::Whitespace,
+        CssToken::CloseCurly
+    );
+}
+
+#[test_case]
+fn hash_and_class() {
+    run_test!(
+        "#main.a #1",
+        CssToken::Hash {
+            value: String::from("main"),
+            is_id: true
+        },
+        CssToken::Delim('.'),
+        ident("a"),
+        CssToken::Whitespace,
+        CssToken::Hash {
+            value: String::from("1"),
+            is_id: false
+        }
+    );
+}
+
+#[test_case]
+fn numbers() {
+    run_test!(
+        "10px 1.5em -3 +.5 50% 1e2 2E-1",
+        CssToken::Dimension(10.0, String::from("px")),
+        CssToken::Whitespace,
+        CssToken::Dimension(1.5, String::from("em")),
+        CssToken::Whitespace,
+        CssToken::Number(-3.0),
+        CssToken::Whitespace,
+        CssToken::Number(0.5),
+        CssToken::Whitespace,
+        CssToken::Percentage(50.0),
+        CssToken::Whitespace,
+        CssToken::Number(100.0),
+        CssToken::Whitespace,
+        CssToken::Number(0.2)
+    );
+}
+
+#[test_case]
+fn strings() {
+    run_test!(
+        "\"a\\\"b\" 'c' \"d\ne\"",
+        CssToken::String(String::from("a\"b")),
+        CssToken::Whitespace,
+        CssToken::String(String::from("c")),
+        CssToken::Whitespace,
+        CssToken::BadString,
+        CssToken::Whitespace,
+        ident("e"),
+        CssToken::String(String::from(""))
+    );
+}
+
+#[test_case]
+fn functions_and_urls() {
+    run_test!(
+        "rgb(1,2) url( a.png ) url(\"b.png\") url(a b)",
+        CssToken::Function(String::from("rgb")),
+        CssToken::Number(1.0),
+        CssToken::Comma,
+        CssToken::Number(2.0),
+        CssToken::CloseParen,
+        CssToken::Whitespace,
+        CssToken::Url(String::from("a.png")),
+        CssToken::Whitespace,
+        CssToken::Function(String::from("url")),
+        CssToken::String(String::from("b.png")),
+        CssToken::CloseParen,
+        CssToken::Whitespace,
+        CssToken::BadUrl
+    );
+}
+
+#[test_case]
+fn at_keyword_and_comments() {
+    run_test!(
+        "/* x */@media/**/(max-width:10px)<!---->",
+        CssToken::AtKeyword(String::from("media")),
+        CssToken::OpenParen,
+        ident("max-width"),
+        CssToken::Colon,
+        CssToken::Dimension(10.0, String::from("px")),
+        CssToken::CloseParen,
+        CssToken::Cdo,
+        CssToken::Cdc
+    );
+}
+
+#[test_case]
+fn escapes() {
+    run_test!(
+        "\\41 b -\\-x --y -",
+        ident("Ab"),
+        CssToken::Whitespace,
+        ident("--x"),
+        CssToken::Whitespace,
+        ident("--y"),
+        CssToken::Whitespace,
+        CssToken::Delim('-')
+    );
+}
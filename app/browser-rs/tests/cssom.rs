@@ -0,0 +1,221 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::parser::css::cssom::*;
+use browser_rs::parser::css::selector::*;
+use browser_rs::parser::css::tokenizer::*;
+use browser_rs::style::computed::{ColorScheme, Viewport};
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in cssom.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+fn ident(s: &str) -> CssToken {
+    CssToken::Ident(String::from(s))
+}
+
+fn declaration(name: &str, value: Vec<CssToken>, important: bool) -> Declaration {
+    Declaration::new(name, value, important)
+}
+
+#[test_case]
+fn empty() {
+    assert_eq!(parse_stylesheet(String::from("")), StyleSheet::new());
+    assert_eq!(
+        parse_stylesheet(String::from(" <!-- --> /* c */ ")),
+        StyleSheet::new()
+    );
+}
+
+#[test_case]
+fn style_rule() {
+    let sheet = parse_stylesheet(String::from(
+        "p.a, div > span { color: red; MARGIN : 0 auto }",
+    ));
+    assert_eq!(sheet.rules().len(), 1);
+
+    let rule = &sheet.rules()[0];
+    assert_eq!(
+        rule.selectors(),
+        &parse_selector_list("p.a, div > span").unwrap()
+    );
+    assert_eq!(
+        rule.declarations(),
+        &vec![
+            declaration("color", vec![ident("red")], false),
+            declaration(
+                "margin",
+                vec![CssToken::Number(0.0), CssToken::Whitespace, ident("auto")],
+                false
+            ),
+        ]
+    );
+}
+
+#[test_case]
+fn important() {
+    let declarations = parse_declaration_list(String::from(
+        "color: red !important; width: 10px ! IMPORTANT; height: !important",
+    ));
+    assert_eq!(
+        declarations,
+        vec![
+            declaration("color", vec![ident("red")], true),
+            declaration(
+                "width",
+                vec![CssToken::Dimension(10.0, String::from("px"))],
+                true
+            ),
+        ]
+    );
+}
+
+#[test_case]
+fn invalid_declarations_are_skipped() {
+    let declarations = parse_declaration_list(String::from(
+        "color red; 10px: x; @foo; background: rgb(1; 2) ; width: 1px",
+    ));
+    assert_eq!(
+        declarations,
+        vec![
+            declaration(
+                "background",
+                vec![
+                    CssToken::Function(String::from("rgb")),
+                    CssToken::Number(1.0),
+                    CssToken::Semicolon,
+                    CssToken::Whitespace,
+                    CssToken::Number(2.0),
+                    CssToken::CloseParen,
+                ],
+                false
+            ),
+            declaration(
+                "width",
+                vec![CssToken::Dimension(1.0, String::from("px"))],
+                false
+            ),
+        ]
+    );
+}
+
+#[test_case]
+fn invalid_rules_are_skipped() {
+    let sheet = parse_stylesheet(String::from(
+        "@import url(a.css); p! { color: red } @font-face { a { b: c } } h1 { color: blue } em {",
+    ));
+    let selectors: Vec<SelectorList> = sheet
+        .rules()
+        .iter()
+        .map(|r| r.selectors().clone())
+        .collect();
+    assert_eq!(
+        selectors,
+        vec![
+            parse_selector_list("h1").unwrap(),
+            parse_selector_list("em").unwrap()
+        ]
+    );
+    assert_eq!(
+        sheet.rules()[0].declarations(),
+        &vec![declaration("color", vec![ident("blue")], false)]
+    );
+    assert!(sheet.rules()[1].declarations().is_empty());
+}
+
+#[test_case]
+fn nested_blocks_in_prelude() {
+    let sheet = parse_stylesheet(String::from("a[href] { x: y } a[x={}] { c: d } b { e: f }"));
+    let selectors: Vec<SelectorList> = sheet
+        .rules()
+        .iter()
+        .map(|r| r.selectors().clone())
+        .collect();
+    assert_eq!(
+        selectors,
+        vec![
+            parse_selector_list("a[href]").unwrap(),
+            parse_selector_list("b").unwrap()
+        ]
+    );
+}
+
+#[test_case]
+fn media_rules() {
+    let sheet = parse_stylesheet(String::from(
+        "a { x: y } @media screen { b { x: y } @media (min-width: 10px) { c { x: y } } } \
+         @media print { d { x: y } } e { x: y }",
+    ));
+    let media: Vec<(SelectorList, usize)> = sheet
+        .rules()
+        .iter()
+        .map(|r| (r.selectors().clone(), r.media().len()))
+        .collect();
+    assert_eq!(
+        media,
+        vec![
+            (parse_selector_list("a").unwrap(), 0),
+            (parse_selector_list("b").unwrap(), 1),
+            (parse_selector_list("c").unwrap(), 2),
+            (parse_selector_list("d").unwrap(), 1),
+            (parse_selector_list("e").unwrap(), 0),
+        ]
+    );
+
+    let evaluated: Vec<SelectorList> = sheet
+        .evaluate_media(Viewport {
+            width: 5.0,
+            height: 5.0,
+            color_scheme: ColorScheme::Light,
+        })
+        .rules()
+        .iter()
+        .map(|r| r.selectors().clone())
+        .collect();
+    assert_eq!(
+        evaluated,
+        vec![
+            parse_selector_list("a").unwrap(),
+            parse_selector_list("b").unwrap(),
+            parse_selector_list("e").unwrap(),
+        ]
+    );
+}
@@ -0,0 +1,141 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+
+use browser_rs::layout::geometry::*;
+use browser_rs::page::Page;
+use browser_rs::paint::damage::*;
+use browser_rs::paint::display_list::*;
+use browser_rs::parser::parse;
+use browser_rs::style::computed::*;
+use browser_rs::style::values::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in damage.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn merge_overlapping_rects() {
+    let mut damage = Damage::new();
+    damage.add(Rect::new(0.0, 0.0, 10.0, 10.0));
+    damage.add(Rect::new(20.0, 0.0, 10.0, 10.0));
+    damage.add(Rect::new(0.0, 0.0, 0.0, 10.0));
+    assert_eq!(damage.rects().len(), 2);
+    // A rect bridging the two merges all of them.
+    damage.add(Rect::new(5.0, 5.0, 20.0, 1.0));
+    assert_eq!(damage.rects(), &vec![Rect::new(0.0, 0.0, 30.0, 10.0)]);
+    // Touching rects aren't merged.
+    damage.add(Rect::new(0.0, 10.0, 5.0, 5.0));
+    assert_eq!(damage.rects().len(), 2);
+}
+
+#[test_case]
+fn diff() {
+    let red = DisplayItem::SolidRect {
+        rect: Rect::new(0.0, 0.0, 10.0, 10.0),
+        color: Color::rgb(255, 0, 0),
+    };
+    let blue = DisplayItem::SolidRect {
+        rect: Rect::new(0.0, 20.0, 10.0, 10.0),
+        color: Color::rgb(0, 0, 255),
+    };
+    let moved = DisplayItem::SolidRect {
+        rect: Rect::new(5.0, 20.0, 10.0, 10.0),
+        color: Color::rgb(0, 0, 255),
+    };
+    assert!(diff_display_lists(
+        &vec![red.clone(), blue.clone()],
+        &vec![red.clone(), blue.clone()]
+    )
+    .is_empty());
+    assert_eq!(
+        diff_display_lists(&vec![red.clone(), blue], &vec![red, moved]).rects(),
+        &vec![Rect::new(0.0, 20.0, 15.0, 10.0)]
+    );
+}
+
+const VIEWPORT: Viewport = Viewport {
+    width: 100.0,
+    height: 50.0,
+    color_scheme: ColorScheme::Light,
+};
+
+#[test_case]
+fn page_damage() {
+    let html = "<style>body { margin: 0 } div { height: 30px }</style>\
+                <body><div></div><div id=b></div><div></div></body>";
+    let mut page = Page::new(parse(String::from(html)), VIEWPORT);
+    // Everything is damaged at first.
+    assert_eq!(
+        page.take_damage(),
+        (0.0, {
+            let mut d = Damage::new();
+            d.add(Rect::new(0.0, 0.0, 100.0, 90.0));
+            d
+        })
+    );
+    assert!(page.take_damage().1.is_empty());
+
+    // Scrolling damages the part scrolled into view.
+    page.scroll_by(15.0);
+    page.scroll_by(-5.0);
+    let (scrolled, damage) = page.take_damage();
+    assert_eq!(scrolled, 10.0);
+    assert_eq!(
+        damage.rects(),
+        &vec![
+            Rect::new(0.0, 50.0, 100.0, 15.0),
+            Rect::new(0.0, 10.0, 100.0, 5.0)
+        ]
+    );
+
+    // Changing the style of an element damages only where it looks different.
+    page.update_document(|document| {
+        let b = document.query_selector(document.root(), "#b").unwrap();
+        document.set_attribute(b, "style", "background-color: red");
+    });
+    assert_eq!(
+        page.take_damage(),
+        (0.0, {
+            let mut d = Damage::new();
+            d.add(Rect::new(0.0, 30.0, 100.0, 30.0));
+            d
+        })
+    );
+}
@@ -0,0 +1,103 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use browser_rs::js::dialog::DialogKind;
+use browser_rs::keyboard::Key;
+use browser_rs::layout::geometry::Rect;
+use browser_rs::paint::display_list::*;
+use browser_rs::ui::dialog::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in dialog.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// Returns the texts in the display list.
+fn texts(list: &DisplayList) -> Vec<String> {
+    list.iter()
+        .filter_map(|item| match item {
+            DisplayItem::Text { text, .. } => Some(text.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test_case]
+fn handle_key() {
+    let alert = Dialog::new(DialogKind::Alert, "a");
+    assert_eq!(alert.handle_key(Key::Enter), Some(true));
+    // Esc closes an alert as well as Enter.
+    assert_eq!(alert.handle_key(Key::Escape), Some(true));
+    assert_eq!(alert.handle_key(Key::Char(b'y')), None);
+
+    let confirm = Dialog::new(DialogKind::Confirm, "a");
+    assert_eq!(confirm.handle_key(Key::Enter), Some(true));
+    assert_eq!(confirm.handle_key(Key::Escape), Some(false));
+    assert_eq!(confirm.handle_key(Key::Tab), None);
+}
+
+#[test_case]
+fn display_list() {
+    let list = Dialog::new(DialogKind::Confirm, "Sure?").display_list(400.0, 300.0);
+    // The box fits the buttons, which are wider than the message.
+    assert_eq!(list[0].rect(), Rect::new(96.0, 118.0, 208.0, 64.0));
+    assert_eq!(list[1].rect(), Rect::new(97.0, 119.0, 206.0, 62.0));
+    assert_eq!(list[2].rect(), Rect::new(104.0, 126.0, 40.0, 16.0));
+    assert_eq!(texts(&list), ["Sure?", "[Enter] OK  [Esc] Cancel"]);
+
+    // A long message is wrapped in 80% of the window, and an empty line is kept.
+    let list =
+        Dialog::new(DialogKind::Alert, "0123456789abcdefghijk\n\nxyz").display_list(200.0, 300.0);
+    assert_eq!(
+        texts(&list),
+        ["0123456789abcdefgh", "ijk", "xyz", "[Enter] OK"]
+    );
+    assert_eq!(list[0].rect().width, 160.0);
+}
+
+#[test_case]
+fn too_many_lines() {
+    let message: Vec<String> = (0..12).map(|i| alloc::format!("line {}", i)).collect();
+    let list = Dialog::new(DialogKind::Alert, &message.join("\n")).display_list(200.0, 300.0);
+    let texts = texts(&list);
+    // The rest of the message is cut with an ellipsis at the end of the last line.
+    assert_eq!(texts.len(), 11);
+    assert_eq!(texts[8], "line 8");
+    assert_eq!(texts[9], "line 9...");
+}
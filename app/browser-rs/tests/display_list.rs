@@ -0,0 +1,161 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::font::Font;
+use browser_rs::layout::geometry::*;
+use browser_rs::page::Page;
+use browser_rs::paint::display_list::*;
+use browser_rs::parser::parse;
+use browser_rs::style::computed::*;
+use browser_rs::style::values::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in display_list.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+fn display_list(html: &str) -> DisplayList {
+    Page::new(parse(String::from(html)), Viewport::default())
+        .display_list()
+        .clone()
+}
+
+fn color(name: &str) -> Color {
+    Color::from_name(name).unwrap()
+}
+
+fn text(text: &str, rect: Rect, color: Color, underline: bool) -> DisplayItem {
+    DisplayItem::Text {
+        text: String::from(text),
+        rect,
+        font: Font::new(1, false),
+        color,
+        underline,
+    }
+}
+
+#[test_case]
+fn backgrounds_borders_and_text() {
+    let html = "<style>body, p { margin: 0 } p { background-color: yellow; border-width: 2px; \
+                border-style: solid; border-color: red }</style><body><p>a <a href=x>b</a></p>\
+                </body>";
+    let border = Edges {
+        top: 2.0,
+        right: 2.0,
+        bottom: 2.0,
+        left: 2.0,
+    };
+    assert_eq!(
+        display_list(html),
+        vec![
+            DisplayItem::SolidRect {
+                rect: Rect::new(0.0, 0.0, 600.0, 20.0),
+                color: color("yellow"),
+            },
+            DisplayItem::Border {
+                rect: Rect::new(0.0, 0.0, 600.0, 20.0),
+                widths: border,
+                styles: Edges {
+                    top: BorderStyle::Solid,
+                    right: BorderStyle::Solid,
+                    bottom: BorderStyle::Solid,
+                    left: BorderStyle::Solid,
+                },
+                colors: Edges {
+                    top: color("red"),
+                    right: color("red"),
+                    bottom: color("red"),
+                    left: color("red"),
+                },
+            },
+            text("a", Rect::new(2.0, 2.0, 8.0, 16.0), Color::BLACK, false),
+            text("b", Rect::new(18.0, 2.0, 8.0, 16.0), color("blue"), true),
+        ]
+    );
+}
+
+#[test_case]
+fn canvas_background() {
+    // The background of body covers the canvas if the root has none.
+    let html = "<body style=\"background-color: red; margin: 0\"><div style=\"height: 1000px\">\
+                </div></body>";
+    assert_eq!(
+        display_list(html),
+        vec![DisplayItem::SolidRect {
+            rect: Rect::new(0.0, 0.0, 600.0, 1000.0),
+            color: color("red"),
+        }]
+    );
+    // The background of the root takes precedence, and body paints its own.
+    let html = "<html style=\"background-color: lime\"><body style=\"background-color: red; \
+                margin: 0; height: 10px\"></body></html>";
+    assert_eq!(
+        display_list(html),
+        vec![
+            DisplayItem::SolidRect {
+                rect: Rect::new(0.0, 0.0, 600.0, 400.0),
+                color: color("lime"),
+            },
+            DisplayItem::SolidRect {
+                rect: Rect::new(0.0, 0.0, 600.0, 10.0),
+                color: color("red"),
+            },
+        ]
+    );
+    assert_eq!(display_list("<p style=\"display: none\">a</p>"), vec![]);
+}
+
+#[test_case]
+fn positioned_boxes_are_painted_later() {
+    let html = "<style>body, p { margin: 0 }</style><body><p style=\"position: absolute\">a</p>\
+                <p style=\"position: relative\">b</p><p>c</p></body>";
+    let texts: Vec<String> = display_list(html)
+        .iter()
+        .filter_map(|item| match item {
+            DisplayItem::Text { text, .. } => Some(text.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(texts, vec!["c", "b", "a"]);
+    assert_eq!(
+        display_list(html)[0].rect(),
+        Rect::new(0.0, 16.0, 8.0, 16.0)
+    );
+}
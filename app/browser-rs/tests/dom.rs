@@ -6,26 +6,18 @@
 
 extern crate alloc;
 
+use alloc::format;
 use alloc::rc::Rc;
 use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
 use core::cell::RefCell;
 
 use browser_rs::parser::dom::*;
+use browser_rs::parser::parse;
 use browser_rs::parser::tokenizer::*;
 use liumlib::*;
 
-fn print_node(node: Option<Rc<RefCell<Node>>>, depth: usize) {
-    match node {
-        Some(n) => {
-            print!("{}", "  ".repeat(depth));
-            println!("{:?}", n.borrow().kind);
-            print_node(n.borrow().first_child(), depth + 1);
-            print_node(n.borrow().next_sibling(), depth);
-        }
-        None => return,
-    }
-}
-
 #[cfg(test)]
 pub trait Testable {
     fn run(&self) -> ();
@@ -58,138 +50,175 @@ fn main() {
     test_main();
 }
 
-/// Checks the node kinds recursively.
-fn node_equals(expected: Option<Rc<RefCell<Node>>>, actual: Option<Rc<RefCell<Node>>>) -> bool {
-    if expected.is_some() != actual.is_some() {
-        // A node is Some and another is None.
-        println!("expected {:?} but actual {:?}", expected, actual);
-        return false;
+/// Returns a label of the `index`-th child `id` used in a path to a node.
+fn node_label(document: &Document, id: NodeId, index: usize) -> String {
+    match document.node(id).kind {
+        NodeKind::Document => String::from("#document"),
+        NodeKind::DocumentFragment => String::from("#document-fragment"),
+        NodeKind::Element(ref e) => format!("{}[{}]", e.tag_name(), index),
+        NodeKind::Text(_) => format!("#text[{}]", index),
+        NodeKind::Comment(_) => format!("#comment[{}]", index),
+        NodeKind::DocumentType { .. } => format!("#doctype[{}]", index),
+    }
+}
+
+/// Compares the subtrees of `expected` and `actual` including attributes and text, and returns
+/// the description of the first divergent node with its path from the root.
+fn first_difference(
+    expected_document: &Document,
+    expected: NodeId,
+    actual_document: &Document,
+    actual: NodeId,
+    path: &str,
+) -> Option<String> {
+    let expected_node = expected_document.node(expected);
+    let actual_node = actual_document.node(actual);
+    if expected_node.kind != actual_node.kind {
+        return Some(format!(
+            "{}: expected {:?} but actual {:?}",
+            path, expected_node.kind, actual_node.kind
+        ));
     }
 
-    if expected.is_none() {
-        // Both nodes are None, return true.
-        return true;
+    let expected_children: Vec<NodeId> = expected_document.children(expected).collect();
+    let actual_children: Vec<NodeId> = actual_document.children(actual).collect();
+    for i in 0..core::cmp::max(expected_children.len(), actual_children.len()) {
+        match (expected_children.get(i), actual_children.get(i)) {
+            (Some(e), Some(a)) => {
+                let child_path = format!("{}/{}", path, node_label(expected_document, *e, i));
+                if let Some(d) =
+                    first_difference(expected_document, *e, actual_document, *a, &child_path)
+                {
+                    return Some(d);
+                }
+            }
+            (Some(e), None) => {
+                return Some(format!(
+                    "{}: missing child {:?}",
+                    path,
+                    expected_document.node(*e).kind
+                ))
+            }
+            (None, Some(a)) => {
+                return Some(format!(
+                    "{}: unexpected child {:?}",
+                    path,
+                    actual_document.node(*a).kind
+                ))
+            }
+            (None, None) => unreachable!(),
+        }
     }
 
-    // Both nodes are Some, check kind.
-    let expected_node = expected.unwrap();
-    let expected_borrowed = expected_node.borrow();
-    let actual_node = actual.unwrap();
-    let actual_borrowed = actual_node.borrow();
-    if expected_borrowed.kind != actual_borrowed.kind {
-        println!(
-            "expected {:?} but actual {:?}",
-            expected_borrowed, actual_borrowed
-        );
-        return false;
+    None
+}
+
+/// Prints the trees side by side. Lines that differ are marked with "!".
+fn print_side_by_side(expected: &Document, actual: &Document) {
+    let expected = format!("{}", expected);
+    let actual = format!("{}", actual);
+    let expected_lines: Vec<&str> = expected.lines().collect();
+    let actual_lines: Vec<&str> = actual.lines().collect();
+    let width = expected_lines.iter().map(|l| l.len()).max().unwrap_or(0);
+
+    println!("\n{:width$}   {}", "expected", "actual", width = width);
+    for i in 0..core::cmp::max(expected_lines.len(), actual_lines.len()) {
+        let e = expected_lines.get(i).cloned().unwrap_or("");
+        let a = actual_lines.get(i).cloned().unwrap_or("");
+        let mark = if e == a { "|" } else { "!" };
+        println!("{:width$} {} {}", e, mark, a, width = width);
     }
+}
 
-    node_equals(
-        expected_borrowed.first_child().clone(),
-        actual_borrowed.first_child().clone(),
-    ) && node_equals(
-        expected_borrowed.next_sibling().clone(),
-        actual_borrowed.next_sibling().clone(),
-    )
+/// Checks that the trees are the same. On mismatch, prints both trees and panics with the path to
+/// the first divergent node.
+fn assert_tree_eq(expected: &Document, actual: &Document) {
+    if let Some(d) = first_difference(
+        expected,
+        expected.root(),
+        actual,
+        actual.root(),
+        "#document",
+    ) {
+        print_side_by_side(expected, actual);
+        panic!("trees differ at {}", d);
+    }
 }
 
 #[macro_export]
 macro_rules! run_test {
-    ($html:literal, $expected_root:expr) => {
+    ($html:literal, $expected_document:expr) => {
         use browser_rs::parser::dom::*;
 
         let t = Tokenizer::new(String::from($html));
 
         let mut p = Parser::new(t);
-        let root_raw = p.construct_tree();
-        let root = Some(root_raw.clone());
-        println!("\n----- nodes -----");
-        print_node(Some(root_raw.clone()), 0);
+        let document = p.construct_tree();
 
-        // Check nodes recursively.
-        assert!(node_equals($expected_root, root.clone()));
+        let expected: Document = $expected_document;
+        assert_tree_eq(&expected, &document);
     };
 }
 
-fn create_base_dom_tree() -> Rc<RefCell<Node>> {
+fn create_element(document: &mut Document, kind: ElementKind) -> NodeId {
+    document.create_element(Element::new(kind))
+}
+
+fn create_base_dom_tree() -> Document {
     // root (Document)
     // └── html
     //     └── head
     //     └── body
 
-    let root = Rc::new(RefCell::new(Node::new(NodeKind::Document)));
-    let html = Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
-        ElementKind::Html,
-    )))));
-    let head = Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
-        ElementKind::Head,
-    )))));
-    let body = Rc::new(RefCell::new(Node::new(NodeKind::Element(Element::new(
-        ElementKind::Body,
-    )))));
-
-    // root <--> html
-    {
-        html.borrow_mut().parent = Some(Rc::downgrade(&root));
-    }
-    {
-        root.borrow_mut().first_child = Some(html.clone());
-    }
-    {
-        root.borrow_mut().last_child = Some(Rc::downgrade(&html));
-    }
+    let mut document = Document::new();
+    let root = document.root();
+    let html = create_element(&mut document, ElementKind::Html);
+    let head = create_element(&mut document, ElementKind::Head);
+    let body = create_element(&mut document, ElementKind::Body);
 
-    // html <--> head
-    {
-        head.borrow_mut().parent = Some(Rc::downgrade(&html));
-    }
-    {
-        html.borrow_mut().first_child = Some(head.clone());
-    }
-    {
-        html.borrow_mut().last_child = Some(Rc::downgrade(&head));
-    }
+    document.append_child(root, html);
+    document.append_child(html, head);
+    document.append_child(html, body);
 
-    // html <--> body
-    {
-        body.borrow_mut().parent = Some(Rc::downgrade(&html));
-    }
-    {
-        html.borrow_mut()
-            .first_child()
-            .unwrap()
-            .borrow_mut()
-            .next_sibling = Some(body.clone());
-    }
-    {
-        body.borrow_mut().previous_sibling = Some(Rc::downgrade(&html));
+    document
+}
+
+fn text_of(document: &Document, node: Option<NodeId>) -> String {
+    match document.node(node.unwrap()).kind {
+        NodeKind::Text(ref s) => s.clone(),
+        _ => panic!("expected a text node"),
     }
+}
 
-    root.clone()
+/// Returns the first element with `kind` in tree order.
+fn find_element(document: &Document, kind: ElementKind) -> NodeId {
+    document
+        .descendants(document.root())
+        .find(|n| document.node(*n).element_kind() == Some(kind))
+        .unwrap()
 }
 
 #[test_case]
 fn no_input() {
-    let root = Rc::new(RefCell::new(Node::new(NodeKind::Document)));
-    run_test!("", Some(root));
+    run_test!("", Document::new());
 }
 
 #[test_case]
 fn html() {
-    let root = create_base_dom_tree();
-    run_test!("<html></html>", Some(root));
+    run_test!("<html></html>", create_base_dom_tree());
 }
 
 #[test_case]
 fn head() {
-    let root = create_base_dom_tree();
-    run_test!("<html><head></head></html>", Some(root));
+    run_test!("<html><head></head></html>", create_base_dom_tree());
 }
 
 #[test_case]
 fn body() {
-    let root = create_base_dom_tree();
-    run_test!("<html><head></head><body></body></html>", Some(root));
+    run_test!(
+        "<html><head></head><body></body></html>",
+        create_base_dom_tree()
+    );
 }
 
 #[test_case]
@@ -199,25 +228,662 @@ fn text() {
     //     └── head
     //     └── body
     //         └── text
-    let root = create_base_dom_tree();
-    let body = root
-        .borrow_mut()
-        .first_child()
-        .unwrap()
-        .borrow_mut()
-        .first_child()
-        .unwrap()
-        .borrow_mut()
-        .next_sibling()
-        .unwrap();
-    let text = Rc::new(RefCell::new(Node::new(NodeKind::Text(String::from("foo")))));
-    // body <--> text
-    {
-        body.borrow_mut().first_child = Some(text.clone());
+    let mut document = create_base_dom_tree();
+    let body = find_element(&document, ElementKind::Body);
+    let text = document.create_text("foo");
+    document.append_child(body, text);
+
+    run_test!("<html><head></head><body>foo</body></html>", document);
+}
+
+#[test_case]
+fn append_child() {
+    let mut document = Document::new();
+    let parent = document.root();
+    let a = document.create_text("a");
+    let b = document.create_text("b");
+    let c = document.create_text("c");
+    document.append_child(parent, a);
+    document.append_child(parent, b);
+    document.append_child(parent, c);
+
+    let d = &document;
+    assert_eq!(text_of(d, d.node(parent).first_child()), "a");
+    assert_eq!(text_of(d, d.node(parent).last_child()), "c");
+    assert_eq!(text_of(d, d.node(a).next_sibling()), "b");
+    assert_eq!(text_of(d, d.node(b).next_sibling()), "c");
+    assert!(d.node(c).next_sibling().is_none());
+    assert!(d.node(a).previous_sibling().is_none());
+    assert_eq!(text_of(d, d.node(c).previous_sibling()), "b");
+    assert_eq!(d.node(b).parent(), Some(parent));
+}
+
+#[test_case]
+fn insert_before() {
+    let mut document = Document::new();
+    let parent = document.root();
+    let a = document.create_text("a");
+    let b = document.create_text("b");
+    let c = document.create_text("c");
+    document.append_child(parent, c);
+    document.insert_before(parent, a, Some(c));
+    document.insert_before(parent, b, Some(c));
+
+    let d = &document;
+    assert_eq!(text_of(d, d.node(parent).first_child()), "a");
+    assert_eq!(text_of(d, d.node(a).next_sibling()), "b");
+    assert_eq!(text_of(d, d.node(b).next_sibling()), "c");
+    assert_eq!(text_of(d, d.node(c).previous_sibling()), "b");
+    assert_eq!(text_of(d, d.node(parent).last_child()), "c");
+}
+
+#[test_case]
+fn remove_child() {
+    let mut document = Document::new();
+    let parent = document.root();
+    let a = document.create_text("a");
+    let b = document.create_text("b");
+    let c = document.create_text("c");
+    document.append_child(parent, a);
+    document.append_child(parent, b);
+    document.append_child(parent, c);
+
+    document.remove_child(parent, b);
+    assert!(document.node(b).parent().is_none());
+    assert!(document.node(b).next_sibling().is_none());
+    assert_eq!(text_of(&document, document.node(a).next_sibling()), "c");
+    assert_eq!(text_of(&document, document.node(c).previous_sibling()), "a");
+
+    document.remove_child(parent, a);
+    document.remove_child(parent, c);
+    assert!(document.node(parent).first_child().is_none());
+    assert!(document.node(parent).last_child().is_none());
+}
+
+#[test_case]
+fn append_child_moves_node() {
+    let mut document = Document::new();
+    let first = create_element(&mut document, ElementKind::Div);
+    let second = create_element(&mut document, ElementKind::Div);
+    let a = document.create_text("a");
+    document.append_child(first, a);
+    document.append_child(second, a);
+
+    assert!(document.node(first).first_child().is_none());
+    assert_eq!(document.node(a).parent(), Some(second));
+}
+
+#[test_case]
+fn children() {
+    let document = create_base_dom_tree();
+    let root = document.root();
+    let html = document.node(root).first_child().unwrap();
+
+    assert_eq!(document.children(root).count(), 1);
+    let kinds: Vec<NodeKind> = document
+        .children(html)
+        .map(|n| document.node(n).kind.clone())
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            NodeKind::Element(Element::new(ElementKind::Head)),
+            NodeKind::Element(Element::new(ElementKind::Body)),
+        ]
+    );
+}
+
+#[test_case]
+fn descendants() {
+    let t = Tokenizer::new(String::from("<html><head></head><body>foo</body></html>"));
+    let document = Parser::new(t).construct_tree();
+
+    let kinds: Vec<NodeKind> = document
+        .descendants(document.root())
+        .map(|n| document.node(n).kind.clone())
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            NodeKind::Element(Element::new(ElementKind::Html)),
+            NodeKind::Element(Element::new(ElementKind::Head)),
+            NodeKind::Element(Element::new(ElementKind::Body)),
+            NodeKind::Text(String::from("foo")),
+        ]
+    );
+
+    // The iteration stays in the subtree of the given node.
+    let head = find_element(&document, ElementKind::Head);
+    assert_eq!(document.descendants(head).count(), 0);
+}
+
+#[test_case]
+fn nested_elements() {
+    let document = parse(String::from(
+        "<html><head><title>t</title></head><body><div id=main><p>a<b>b</b></p><ul><li>1<li>2</ul></div></body></html>",
+    ));
+    assert_eq!(
+        format!("{}", document),
+        r#"#document
+  <html>
+    <head>
+      <title>
+        "t"
+    <body>
+      <div>
+        id="main"
+        <p>
+          "a"
+          <b>
+            "b"
+        <ul>
+          <li>
+            "1"
+          <li>
+            "2"
+"#
+    );
+}
+
+#[test_case]
+fn implicit_p_end() {
+    let document = parse(String::from("<p>a<p>b<div>c</div>"));
+    assert_eq!(
+        format!("{}", document),
+        r#"#document
+  <html>
+    <head>
+    <body>
+      <p>
+        "a"
+      <p>
+        "b"
+      <div>
+        "c"
+"#
+    );
+}
+
+#[test_case]
+fn void_elements() {
+    let document = parse(String::from("<body>a<br>b<img src=x.png>c</body>"));
+    assert_eq!(
+        format!("{}", document),
+        r#"#document
+  <html>
+    <head>
+    <body>
+      "a"
+      <br>
+      "b"
+      <img>
+        src="x.png"
+      "c"
+"#
+    );
+}
+
+#[test_case]
+fn style_contents_are_text() {
+    let document = parse(String::from(
+        "<html><head><style>p > a { color: red; }</style></head></html>",
+    ));
+    assert_eq!(
+        format!("{}", document),
+        r#"#document
+  <html>
+    <head>
+      <style>
+        "p > a { color: red; }"
+    <body>
+"#
+    );
+}
+
+#[test_case]
+fn outer_html() {
+    let html = "<html><head><title>t</title></head><body><p id=\"a\" class=\"x y\">foo<br>bar</p><img src=\"a.png\"></body></html>";
+    let document = parse(String::from(html));
+    assert_eq!(document.inner_html(document.root()), html);
+
+    let html_node = find_element(&document, ElementKind::Html);
+    assert_eq!(document.outer_html(html_node), html);
+}
+
+#[test_case]
+fn inner_html() {
+    let document = parse(String::from("<body><div><p>a</p><p>b</p></div></body>"));
+    let div = find_element(&document, ElementKind::Div);
+    assert_eq!(document.inner_html(div), "<p>a</p><p>b</p>");
+}
+
+#[test_case]
+fn escape_html() {
+    let mut document = Document::new();
+    let mut p = Element::new(ElementKind::P);
+    p.set_attribute("title", "\"a\" & <b>");
+    let p = document.create_element(p);
+    let text = document.create_text("1 < 2 & 3 > 2\u{a0}");
+    let root = document.root();
+    document.append_child(root, p);
+    document.append_child(p, text);
+
+    assert_eq!(
+        document.inner_html(document.root()),
+        "<p title=\"&quot;a&quot; &amp; <b>\">1 &lt; 2 &amp; 3 &gt; 2&nbsp;</p>"
+    );
+}
+
+#[test_case]
+fn raw_text_is_not_escaped() {
+    let document = parse(String::from(
+        "<html><head><style>a > b { content: \"&\"; }</style></head></html>",
+    ));
+    assert_eq!(
+        document.inner_html(document.root()),
+        "<html><head><style>a > b { content: \"&\"; }</style></head><body></body></html>"
+    );
+}
+
+#[test_case]
+fn text_content() {
+    let document = parse(String::from(
+        "<body><p>Hello, <b>brave</b> <i>new</i> world</p><p>!</p></body>",
+    ));
+    assert_eq!(
+        document.text_content(document.root()),
+        "Hello, brave new world!"
+    );
+
+    let p = find_element(&document, ElementKind::P);
+    assert_eq!(document.text_content(p), "Hello, brave new world");
+}
+
+#[test_case]
+fn title() {
+    let document = parse(String::from(
+        "<html><head><title>\n  My   page\n</title></head></html>",
+    ));
+    assert_eq!(document.title(), "My page");
+
+    let document = parse(String::from("<html><body>no title</body></html>"));
+    assert_eq!(document.title(), "");
+}
+
+#[test_case]
+fn comment_and_doctype() {
+    let html = "<!DOCTYPE html><!--a--><html><head><!--b--></head><body><p>x<!--c--></p></body></html><!--d-->";
+    let document = parse(String::from(html));
+    assert_eq!(
+        format!("{}", document),
+        r#"#document
+  <!DOCTYPE html>
+  <!-- a -->
+  <html>
+    <head>
+      <!-- b -->
+    <body>
+      <p>
+        "x"
+        <!-- c -->
+  <!-- d -->
+"#
+    );
+    assert_eq!(document.inner_html(document.root()), html);
+}
+
+#[test_case]
+fn clone_node() {
+    let mut document = parse(String::from(
+        "<body><div id=a><p>x<b>y</b></p>z</div></body>",
+    ));
+    let div = find_element(&document, ElementKind::Div);
+
+    let shallow = document.clone_node(div, false);
+    assert!(document.node(shallow).parent().is_none());
+    assert!(document.node(shallow).first_child().is_none());
+    assert_eq!(document.outer_html(shallow), "<div id=\"a\"></div>");
+
+    let deep = document.clone_node(div, true);
+    assert!(document.node(deep).parent().is_none());
+    assert_eq!(
+        document.outer_html(deep),
+        "<div id=\"a\"><p>x<b>y</b></p>z</div>"
+    );
+
+    // The copy has its own nodes, so changing it doesn't affect the original.
+    let p = document.node(deep).first_child().unwrap();
+    assert_eq!(document.node(p).parent(), Some(deep));
+    document.remove_child(deep, p);
+    assert_eq!(document.outer_html(deep), "<div id=\"a\">z</div>");
+    assert_eq!(
+        document.outer_html(div),
+        "<div id=\"a\"><p>x<b>y</b></p>z</div>"
+    );
+}
+
+#[test_case]
+fn parse_fragment() {
+    let div = Element::new(ElementKind::Div);
+    let (document, fragment) =
+        Parser::parse_fragment(&div, String::from("a<p>b<!--c--></p><li>d</body>e"));
+    assert_eq!(
+        document.inner_html(fragment),
+        "a<p>b<!--c--></p><li>de</li>"
+    );
+    assert!(document.node(fragment).parent().is_none());
+    assert!(document.node(document.root()).first_child().is_none());
+    assert_eq!(
+        format!("{}", document),
+        r#"#document
+"#
+    );
+}
+
+#[test_case]
+fn parse_fragment_in_text_element() {
+    let title = Element::new(ElementKind::Title);
+    let (document, fragment) = Parser::parse_fragment(&title, String::from("<b>a</b> &amp;"));
+    assert_eq!(document.children(fragment).count(), 1);
+    assert_eq!(document.text_content(fragment), "<b>a</b> &amp;");
+}
+
+#[test_case]
+fn set_inner_html() {
+    let mut document = parse(String::from("<body><p>a<b>b</b></p></body>"));
+    let p = find_element(&document, ElementKind::P);
+    let b = find_element(&document, ElementKind::B);
+    // A pinned node, which a script may refer to, stays after it's replaced.
+    document.pin(b);
+    document.set_inner_html(p, "x<i title=t>y</i><!--z--><script>w</script>");
+    assert_eq!(
+        document.inner_html(p),
+        "x<i title=\"t\">y</i><!--z--><script>w</script>"
+    );
+    assert!(document.node(b).parent().is_none());
+    assert_eq!(document.text_content(b), "b");
+    let i = find_element(&document, ElementKind::I);
+    assert_eq!(document.node(i).parent(), Some(p));
+
+    // The slots of the replaced children are reused.
+    let count = document.node_count();
+    for _ in 0..10 {
+        document.set_inner_html(p, "x<i title=t>y</i><!--z--><script>w</script>");
     }
-    {
-        text.borrow_mut().parent = Some(Rc::downgrade(&body));
+    assert_eq!(document.node_count(), count);
+    assert_eq!(document.text_content(b), "b");
+
+    // The text of a text element isn't parsed.
+    let mut document = parse(String::from("<title>a</title>"));
+    let title = find_element(&document, ElementKind::Title);
+    document.set_inner_html(title, "<b>b</b>");
+    assert_eq!(document.text_content(title), "<b>b</b>");
+}
+
+#[test_case]
+fn import_node() {
+    let from = parse(String::from("<div id=a>x<b>y</b></div>"));
+    let div = find_element(&from, ElementKind::Div);
+    let mut document = parse(String::from("<p>z</p>"));
+    let copy = document.import_node(&from, div);
+    assert!(document.node(copy).parent().is_none());
+    let p = find_element(&document, ElementKind::P);
+    document.append_child(p, copy);
+    assert_eq!(
+        document.outer_html(p),
+        "<p>z<div id=\"a\">x<b>y</b></div></p>"
+    );
+    assert_eq!(from.outer_html(div), "<div id=\"a\">x<b>y</b></div>");
+}
+
+#[test_case]
+fn normalize() {
+    let mut document = parse(String::from("<body><p>a<b>b</b></p></body>"));
+    let p = find_element(&document, ElementKind::P);
+    let b = find_element(&document, ElementKind::B);
+    let empty = document.create_text("");
+    let c = document.create_text("c");
+    let d = document.create_text("d");
+    let e = document.create_text("e");
+    document.insert_before(p, empty, Some(b));
+    document.insert_before(p, c, Some(b));
+    document.append_child(p, d);
+    document.append_child(p, e);
+    assert_eq!(document.children(p).count(), 6);
+
+    document.normalize(document.root());
+    let texts: Vec<String> = document
+        .children(p)
+        .filter(|n| document.node(*n).element().is_none())
+        .map(|n| text_of(&document, Some(n)))
+        .collect();
+    assert_eq!(texts, vec!["ac", "de"]);
+    assert_eq!(document.inner_html(p), "ac<b>b</b>de");
+}
+
+#[test_case]
+fn text_run_is_single_node() {
+    let document = parse(String::from("<body><p>foo bar</p>baz<br>qux</body>"));
+    let p = find_element(&document, ElementKind::P);
+    assert_eq!(document.children(p).count(), 1);
+    let body = find_element(&document, ElementKind::Body);
+    assert_eq!(document.children(body).count(), 4);
+}
+
+#[test_case]
+fn class_list() {
+    let mut element = Element::new(ElementKind::Div);
+    assert!(element.classes().is_empty());
+
+    // Removing from an element without a class attribute doesn't create one.
+    element.class_list().remove("a");
+    assert_eq!(element.get_attribute("class"), None);
+
+    element.set_attribute("class", "  a b\ta c ");
+    assert_eq!(element.classes(), vec!["a", "b", "c"]);
+    assert!(element.has_class("b"));
+    assert!(!element.has_class("d"));
+
+    let mut list = element.class_list();
+    assert_eq!(list.len(), 3);
+    assert_eq!(list.item(1), Some(String::from("b")));
+    list.add("d");
+    list.add("a");
+    assert_eq!(list.value(), "a b c d");
+    list.remove("b");
+    assert_eq!(list.value(), "a c d");
+    assert!(!list.toggle("c"));
+    assert!(list.toggle("e"));
+    assert!(list.contains("e"));
+    assert_eq!(element.get_attribute("class"), Some(String::from("a d e")));
+}
+
+#[test_case]
+fn dataset() {
+    let document = parse(String::from(
+        "<body><div data-id=1 title=t data-foo-bar=x data-a--b=y data-Upper=z data-=e></div></body>",
+    ));
+    let div = find_element(&document, ElementKind::Div);
+    let mut element = document.node(div).element().unwrap().clone();
+    // The tokenizer lowercases attribute names, so this one can only be set directly.
+    element.set_attribute("data-Upper", "u");
+
+    let expected: Vec<(String, String)> = vec![
+        (String::from("id"), String::from("1")),
+        (String::from("fooBar"), String::from("x")),
+        (String::from("a-B"), String::from("y")),
+        (String::from("upper"), String::from("z")),
+        (String::from(""), String::from("e")),
+    ];
+    assert_eq!(element.dataset(), expected);
+}
+
+#[test_case]
+fn tree_difference() {
+    let mut expected = parse(String::from("<body><p id=a>x</p><p>y</p></body>"));
+    let actual = parse(String::from("<body><p id=b>x</p><p>y</p></body>"));
+    let root = expected.root();
+    assert_eq!(
+        first_difference(&expected, root, &actual, actual.root(), "#document"),
+        Some(String::from(
+            "#document/html[0]/body[1]/p[0]: expected Element(Element { kind: P, tag_name: \"p\", attributes: [Attribute { name: \"id\", value: \"a\" }], value: None }) but actual Element(Element { kind: P, tag_name: \"p\", attributes: [Attribute { name: \"id\", value: \"b\" }], value: None })"
+        ))
+    );
+
+    let p = find_element(&expected, ElementKind::P);
+    expected
+        .node_mut(p)
+        .element_mut()
+        .unwrap()
+        .set_attribute("id", "b");
+    assert_eq!(
+        first_difference(&expected, root, &actual, actual.root(), "#document"),
+        None
+    );
+
+    let body = find_element(&expected, ElementKind::Body);
+    let text = expected.create_text("z");
+    expected.append_child(body, text);
+    assert_eq!(
+        first_difference(&expected, root, &actual, actual.root(), "#document"),
+        Some(String::from(
+            "#document/html[0]/body[1]: missing child Text(\"z\")"
+        ))
+    );
+}
+
+#[test_case]
+fn to_json() {
+    let document = parse(String::from(
+        "<!DOCTYPE html><body><p id=a class=\"b\">x\n\"y\"\\<!--c--></p></body>",
+    ));
+    assert_eq!(
+        document.to_json(document.root()),
+        concat!(
+            r#"{"kind":"document","children":["#,
+            r#"{"kind":"doctype","name":"html","public_id":"","system_id":"","children":[]},"#,
+            r#"{"kind":"element","tag":"html","attributes":[],"children":["#,
+            r#"{"kind":"element","tag":"head","attributes":[],"children":[]},"#,
+            r#"{"kind":"element","tag":"body","attributes":[],"children":["#,
+            r#"{"kind":"element","tag":"p","attributes":[["id","a"],["class","b"]],"children":["#,
+            r#"{"kind":"text","data":"x\n\"y\"\\","children":[]},"#,
+            r#"{"kind":"comment","data":"c","children":[]}"#,
+            r#"]}]}]}]}"#
+        )
+    );
+}
+
+#[test_case]
+fn mutation_observer() {
+    let mut document = parse(String::from("<body><p>a</p></body>"));
+    let body = find_element(&document, ElementKind::Body);
+    let p = find_element(&document, ElementKind::P);
+
+    let records = Rc::new(RefCell::new(Vec::new()));
+    let r = records.clone();
+    let observer = document.add_mutation_observer(Rc::new(move |d: &Document, m: &Mutation| {
+        // The callback sees the document after the change.
+        if let Mutation::ChildInserted { parent, child } = m {
+            assert_eq!(d.node(*child).parent(), Some(*parent));
+        }
+        r.borrow_mut().push(m.clone());
+    }));
+
+    let div = create_element(&mut document, ElementKind::Div);
+    document.append_child(body, div);
+    // Moving a node is reported as a removal and an insertion.
+    document.append_child(div, p);
+    document.set_attribute(div, "id", "x");
+    assert_eq!(
+        *records.borrow(),
+        vec![
+            Mutation::ChildInserted {
+                parent: body,
+                child: div
+            },
+            Mutation::ChildRemoved {
+                parent: body,
+                child: p
+            },
+            Mutation::ChildInserted {
+                parent: div,
+                child: p
+            },
+            Mutation::AttributeChanged {
+                element: div,
+                name: String::from("id")
+            },
+        ]
+    );
+
+    document.remove_mutation_observer(observer);
+    document.remove_child(div, p);
+    assert_eq!(records.borrow().len(), 4);
+}
+
+#[test_case]
+fn quirks_mode() {
+    let cases = [
+        ("<!DOCTYPE html>", QuirksMode::NoQuirks),
+        ("<!doctype HTML><p>", QuirksMode::NoQuirks),
+        ("<p>no doctype", QuirksMode::Quirks),
+        ("<!DOCTYPE>", QuirksMode::Quirks),
+        ("<!DOCTYPE svg>", QuirksMode::Quirks),
+        (
+            "<!DOCTYPE html PUBLIC \"-//W3C//DTD HTML 3.2 Final//EN\">",
+            QuirksMode::Quirks,
+        ),
+        (
+            "<!DOCTYPE html PUBLIC \"-//W3C//DTD HTML 4.01 Transitional//EN\">",
+            QuirksMode::Quirks,
+        ),
+        (
+            "<!DOCTYPE html PUBLIC \"-//W3C//DTD HTML 4.01 Transitional//EN\" \"http://www.w3.org/TR/html4/loose.dtd\">",
+            QuirksMode::LimitedQuirks,
+        ),
+        (
+            "<!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Strict//EN\" \"http://www.w3.org/TR/xhtml1/DTD/xhtml1-strict.dtd\">",
+            QuirksMode::NoQuirks,
+        ),
+        (
+            "<!DOCTYPE html PUBLIC \"-//W3C//DTD XHTML 1.0 Transitional//EN\" \"x\">",
+            QuirksMode::LimitedQuirks,
+        ),
+    ];
+    for (html, mode) in cases.iter() {
+        let document = parse(String::from(*html));
+        assert_eq!(document.quirks_mode(), *mode);
     }
+}
 
-    run_test!("<html><head></head><body>foo</body></html>", Some(root));
+#[test_case]
+fn style_sheets() {
+    let document = parse(String::from(
+        "<html><head><style>p { color: red }</style><title>t</title></head><body><style>a { x: y } b { z: w }</style><style></style></body></html>",
+    ));
+    let sheets = document.style_sheets();
+    assert_eq!(sheets.len(), 3);
+    assert_eq!(sheets[0].rules().len(), 1);
+    assert_eq!(sheets[0].rules()[0].declarations()[0].name(), "color");
+    assert_eq!(sheets[1].rules().len(), 2);
+    assert!(sheets[2].rules().is_empty());
+
+    // An unclosed style element is parsed at the end of the input.
+    let document = parse(String::from("<style>p { color: red }"));
+    assert_eq!(document.style_sheets().len(), 1);
+}
+
+#[test_case]
+fn linked_style_sheets() {
+    let document = parse(String::from(
+        "<html><head><link rel=stylesheet href=a.css><style>p { color: red }</style>\
+         <link rel=\"alternate STYLESHEET\" href=\" /b.css \"><link rel=icon href=c.png>\
+         <link rel=stylesheet></head><body><link rel=Stylesheet href=d.css></body></html>",
+    ));
+    let hrefs: Vec<Option<&str>> = document.style_sheets().iter().map(|s| s.href()).collect();
+    assert_eq!(
+        hrefs,
+        vec![Some("a.css"), None, Some("/b.css"), Some("d.css")]
+    );
+    assert!(document.style_sheets()[0].rules().is_empty());
 }
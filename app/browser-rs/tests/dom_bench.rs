@@ -0,0 +1,96 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::String;
+use browser_rs::parser::parse;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in dom_bench.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// The sections of the document, each of which has 13 nodes.
+const SECTIONS: usize = 100;
+
+/// Returns a document of `SECTIONS` sections with headings, paragraphs and lists.
+fn large_document() -> String {
+    let mut html = String::from("<html><head><title>Bench</title></head><body>");
+    for i in 0..SECTIONS {
+        html.push_str(&format!(
+            "<div class=s><h2>Title {}</h2><p>Some <b>bold</b> text {}</p>\
+             <ul><li>a</li><li>b</li></ul></div>",
+            i, i
+        ));
+    }
+    html.push_str("</body></html>");
+    html
+}
+
+/// Returns the fewest cycles which `f` takes in `runs` runs.
+fn min_cycles<F: FnMut()>(runs: usize, mut f: F) -> u64 {
+    (0..runs)
+        .map(|_| {
+            let start = rdtsc();
+            f();
+            rdtsc() - start
+        })
+        .min()
+        .unwrap()
+}
+
+/// Prints the cycles taken by building the DOM of a large document and by walking it, e.g. to
+/// compare ways of storing the DOM. The numbers mean something only in a release build, which
+/// `make test` is.
+#[test_case]
+fn build_and_traverse() {
+    let html = large_document();
+    let build = min_cycles(5, || {
+        parse(html.clone());
+    });
+    let document = parse(html);
+    let mut count = 0;
+    let traverse = min_cycles(20, || {
+        count = document.descendants(document.root()).count();
+    });
+    // html, head, title and its text, body, and the sections.
+    assert_eq!(count, 5 + 13 * SECTIONS);
+    print!(
+        "build: {} kcycles, traverse: {} kcycles ",
+        build / 1000,
+        traverse / 1000
+    );
+}
@@ -0,0 +1,55 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use browser_rs::download::file_name;
+use browser_rs::url::ParsedUrl;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in download.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn file_names() {
+    let name = |path: &str| file_name(&ParsedUrl::new(String::from(path)));
+    assert_eq!(name("127.0.0.1:8888/images/a.ppm"), "a.ppm");
+    assert_eq!(name("127.0.0.1:8888/search.html?q=a/b#top"), "search.html");
+    // A directory is saved as its index.
+    assert_eq!(name("127.0.0.1:8888/dir/"), "index.html");
+    assert_eq!(name("127.0.0.1:8888"), "index.html");
+}
@@ -0,0 +1,55 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in env.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn arguments() {
+    // The first argument is the program.
+    assert!(env::args()[0].contains("env"));
+}
+
+#[test_case]
+fn variables_on_linux() {
+    let path = env::var("PATH").unwrap();
+    assert!(path.contains('/'));
+    assert!(env::vars().any(|(name, value)| name == "PATH" && value == path));
+    assert_eq!(env::var("LIUMLIB_UNDEFINED_VARIABLE"), None);
+}
@@ -0,0 +1,90 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use browser_rs::error_page::*;
+use browser_rs::http::HttpResponse;
+use browser_rs::parser::parse;
+use browser_rs::url::ParsedUrl;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in error_page.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+fn url() -> ParsedUrl {
+    ParsedUrl::new(String::from("127.0.0.1:8888/a.html?q=<b>"))
+}
+
+#[test_case]
+fn network_error() {
+    let html = network_error_page(&url(), "failed to receive a response");
+    let document = parse(html.clone());
+    assert_eq!(document.title(), "This page can't be reached");
+    assert!(document
+        .text_content(document.root())
+        .contains("failed to receive a response"));
+    // The URL is escaped.
+    assert!(html.contains("http://127.0.0.1:8888/a.html?q=&lt;b&gt;"));
+}
+
+#[test_case]
+fn placeholders_in_values() {
+    // A value is inserted as it is even if it looks like a placeholder.
+    let html = error_page(&url(), "{detail}", "{title}");
+    assert!(html.contains("<title>{detail}</title>"));
+    assert!(html.contains("<p>{title}</p>"));
+}
+
+#[test_case]
+fn http_error() {
+    let response = |raw: &str| HttpResponse::new(String::from(raw));
+    let html = http_error_page(&url(), &response("HTTP/1.1 404 Not Found\n\n"));
+    assert_eq!(
+        html.map(|html| parse(html).title()),
+        Some(String::from("404 Not Found"))
+    );
+    // A body from the server is shown instead.
+    assert_eq!(
+        http_error_page(&url(), &response("HTTP/1.1 404 Not Found\n\n<p>No</p>")),
+        None
+    );
+    assert_eq!(
+        http_error_page(&url(), &response("HTTP/1.1 204 No Content\n\n")),
+        None
+    );
+}
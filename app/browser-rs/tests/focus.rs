@@ -0,0 +1,180 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::layout::geometry::*;
+use browser_rs::page::Page;
+use browser_rs::paint::display_list::*;
+use browser_rs::paint::focus_ring::*;
+use browser_rs::parser::parse;
+use browser_rs::style::computed::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in focus.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+const VIEWPORT: Viewport = Viewport {
+    width: 200.0,
+    height: 100.0,
+    color_scheme: ColorScheme::Light,
+};
+
+const HTML: &str = "<style>body { margin: 0 } p { margin: 0; height: 20px } \
+                    .tall { height: 200px }</style>\
+                    <p><a href='a.html'>aa</a> <a>no href</a> <a href='b.html' \
+                    style='display: none'>hidden</a></p><p class='tall'></p>\
+                    <p><a href='/c.html'><span>c</span>c</a></p>";
+
+fn page() -> Page {
+    Page::new(parse(String::from(HTML)), VIEWPORT)
+}
+
+/// Returns the hrefs of `links`.
+fn hrefs(page: &Page) -> Vec<String> {
+    page.focusable_elements()
+        .iter()
+        .map(|&id| {
+            page.document()
+                .node(id)
+                .element()
+                .unwrap()
+                .get_attribute("href")
+                .unwrap()
+        })
+        .collect()
+}
+
+/// Returns the rects of the focus ring in the display list.
+fn rings(page: &Page) -> Vec<Rect> {
+    page.display_list()
+        .iter()
+        .filter_map(|item| match item {
+            DisplayItem::Border { rect, widths, .. } if widths.top == 2.0 => Some(*rect),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test_case]
+fn focusable_links() {
+    // Links without href and the ones which aren't rendered can't get the focus.
+    assert_eq!(hrefs(&page()), vec!["a.html", "/c.html"]);
+}
+
+#[test_case]
+fn focusable_at() {
+    let mut page = page();
+    let links = page.focusable_elements();
+    // "aa" is at the top-left corner.
+    assert_eq!(page.focusable_at(2.0, 5.0), Some(links[0]));
+    assert_eq!(page.focusable_at(2.0, 30.0), None);
+    assert_eq!(page.focusable_at(190.0, 5.0), None);
+    // The point is in the viewport, which is scrolled over the document.
+    assert!(page.scroll_to(140.0));
+    assert_eq!(page.focusable_at(2.0, 5.0), None);
+    assert_eq!(page.focusable_at(2.0, 85.0), Some(links[1]));
+}
+
+#[test_case]
+fn tab_order() {
+    let mut page = page();
+    let links = page.focusable_elements();
+    assert_eq!(page.focused(), None);
+    assert_eq!(page.focused_href(), None);
+    assert!(page.focus_next(false));
+    assert_eq!(page.focused(), Some(links[0]));
+    assert_eq!(page.focused_href(), Some(String::from("a.html")));
+    assert!(page.focus_next(false));
+    assert_eq!(page.focused(), Some(links[1]));
+    // The focus wraps around.
+    assert!(page.focus_next(false));
+    assert_eq!(page.focused(), Some(links[0]));
+    assert!(page.focus_next(true));
+    assert_eq!(page.focused(), Some(links[1]));
+    assert!(!page.focus(Some(links[1])));
+    assert!(page.focus(None));
+    assert!(page.focus_next(true));
+    assert_eq!(page.focused(), Some(links[1]));
+}
+
+#[test_case]
+fn focus_ring() {
+    let mut page = page();
+    assert!(rings(&page).is_empty());
+    page.take_damage();
+    page.focus_next(false);
+    // The text "aa" in the first line.
+    let ring = Rect::new(-2.0, -2.0, 20.0, 20.0);
+    assert_eq!(rings(&page), vec![ring]);
+    let (_, damage) = page.take_damage();
+    assert_eq!(damage.rects(), &vec![ring]);
+
+    // The text in the span and the one after it are covered by a ring.
+    let root = page.layout().unwrap();
+    let link = page.focusable_elements()[1];
+    assert_eq!(
+        focus_rects(page.document(), root, link),
+        vec![Rect::new(0.0, 220.0, 16.0, 16.0)]
+    );
+}
+
+#[test_case]
+fn scroll_into_view() {
+    let mut page = page();
+    page.focus_next(false);
+    page.focus_next(false);
+    // The bottom of the link is at the bottom of the viewport.
+    assert_eq!(page.scroll_y(), 136.0);
+    page.focus_next(false);
+    assert_eq!(page.scroll_y(), 0.0);
+}
+
+#[test_case]
+fn removed_link() {
+    let mut page = page();
+    page.focus_next(false);
+    page.update_document(|document| {
+        let p = document.query_selector(document.root(), "p").unwrap();
+        let a = document.query_selector(p, "a").unwrap();
+        document.remove_child(p, a);
+    });
+    assert_eq!(page.focused(), None);
+    assert!(rings(&page).is_empty());
+}
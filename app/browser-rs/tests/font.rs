@@ -0,0 +1,129 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::font::Font;
+use browser_rs::parser::parse;
+use browser_rs::style::computed::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in font.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// Returns the font of the first element matching `selector` in `html`.
+fn font_of(html: &str, selector: &str) -> Font {
+    let document = parse(String::from(html));
+    let styles = compute_styles(&document, Viewport::default());
+    let id = document.query_selector(document.root(), selector).unwrap();
+    Font::from_style(styles.get(id).unwrap())
+}
+
+/// Returns the glyph of `c` as lines of `.` and `*`.
+fn draw(font: &Font, c: char) -> Vec<String> {
+    let size = (font.glyph_width() as usize, font.glyph_height() as usize);
+    let mut lines = vec![vec!['.'; size.0]; size.1];
+    font.for_each_pixel(c, |x, y| lines[y][x] = '*');
+    lines.iter().map(|l| l.iter().collect()).collect()
+}
+
+#[test_case]
+fn font_sizes() {
+    let html =
+        "<style>.s { font-size: 10px } .m { font-size: 20px } .l { font-size: 24px }</style>\
+                <p class=s>a</p><p class=m>a</p><p class=l>a</p><h1>a</h1>";
+    assert_eq!(font_of(html, "p"), Font::new(1, false));
+    assert_eq!(font_of(html, ".s"), Font::new(1, false));
+    assert_eq!(font_of(html, ".m"), Font::new(1, false));
+    assert_eq!(font_of(html, ".l"), Font::new(2, false));
+    assert_eq!(font_of(html, "h1"), Font::new(2, true));
+}
+
+#[test_case]
+fn metrics() {
+    let font = Font::new(1, false);
+    assert_eq!((font.glyph_width(), font.glyph_height()), (8.0, 16.0));
+    assert_eq!(font.advance('a'), 8.0);
+    assert_eq!(font.text_width("hello"), 40.0);
+
+    let font = Font::new(2, true);
+    assert_eq!((font.glyph_width(), font.glyph_height()), (16.0, 32.0));
+    assert_eq!(font.text_width("hello"), 80.0);
+    assert_eq!(font.underline_offset(), 28.0);
+    assert_eq!(font.underline_thickness(), 2.0);
+}
+
+#[test_case]
+fn glyphs() {
+    let a = draw(&Font::new(1, false), 'A');
+    assert_eq!(a[0], "........");
+    assert_eq!(a[1], "...**...");
+    assert_eq!(a[9], ".******.");
+    assert_eq!(a[13], "***..***");
+
+    // Bold glyphs are drawn twice.
+    let bold = draw(&Font::new(1, true), 'A');
+    assert_eq!(bold[1], "...***..");
+
+    // Each pixel becomes a square.
+    let large = draw(&Font::new(2, false), 'A');
+    assert_eq!(large[2], "......****......");
+    assert_eq!(large[3], "......****......");
+
+    // Characters out of the font are drawn as '?'.
+    assert_eq!(
+        draw(&Font::new(1, false), '\u{3042}'),
+        draw(&Font::new(1, false), '?')
+    );
+}
+
+#[test_case]
+fn bullets() {
+    let font = Font::new(1, false);
+    let disc = draw(&font, '\u{2022}');
+    assert_eq!(disc[6], "...**...");
+    assert_eq!(disc[7], "..****..");
+    let circle = draw(&font, '\u{25e6}');
+    assert_eq!(circle[6], ".**..**.");
+    let square = draw(&font, '\u{25aa}');
+    assert_eq!(
+        &square[5..11],
+        ["........", "..****..", "..****..", "..****..", "..****..", "........"]
+    );
+}
@@ -0,0 +1,270 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use browser_rs::form::*;
+use browser_rs::page::Page;
+use browser_rs::parser::dom::*;
+use browser_rs::parser::parse;
+use browser_rs::style::computed::{ColorScheme, Viewport};
+use browser_rs::url::ParsedUrl;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in form.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// Returns the first element matching `selectors`.
+fn find(document: &Document, selectors: &str) -> NodeId {
+    document.query_selector(document.root(), selectors).unwrap()
+}
+
+fn entries(pairs: &[(&str, &str)]) -> Vec<Entry> {
+    pairs
+        .iter()
+        .map(|(n, v)| (String::from(*n), String::from(*v)))
+        .collect()
+}
+
+#[test_case]
+fn urlencoded() {
+    assert_eq!(
+        urlencode(&entries(&[("q", "a b&c=d"), ("lang", "日"), ("*-._", "~")])),
+        "q=a+b%26c%3Dd&lang=%E6%97%A5&*-._=%7E"
+    );
+    // Newlines are sent as CRLF.
+    assert_eq!(
+        urlencode(&entries(&[("t", "a\nb\r\nc")])),
+        "t=a%0D%0Ab%0D%0Ac"
+    );
+    assert_eq!(urlencode(&[]), "");
+}
+
+#[test_case]
+fn controls_in_entry_list() {
+    let document = parse(String::from(
+        "<form><input name=q value='a b'><input name=empty><input value=noname>\
+         <input type=checkbox name=c1 checked><input type=checkbox name=c2>\
+         <input type=radio name=r value=x><input type=radio name=r value=y checked>\
+         <input name=d value=1 disabled><input type=hidden name=h value=2>\
+         <textarea name=t>text</textarea>\
+         <button name=b1 value=1>One</button><input type=submit name=b2 value=2></form>\
+         <input name=outside value=3><input form=f name=owned value=4>",
+    ));
+    let form = find(&document, "form");
+    let b1 = find(&document, "button");
+    assert_eq!(
+        entry_list(&document, form, Some(b1)),
+        entries(&[
+            ("q", "a b"),
+            ("empty", ""),
+            ("c1", "on"),
+            ("r", "y"),
+            ("h", "2"),
+            ("t", "text"),
+            ("b1", "1"),
+        ])
+    );
+}
+
+#[test_case]
+fn form_attribute() {
+    let document = parse(String::from(
+        "<form id=f><input name=a value=1></form><form><input form=f name=b value=2></form>\
+         <input form=missing name=c>",
+    ));
+    let form = find(&document, "#f");
+    assert_eq!(
+        entry_list(&document, form, None),
+        entries(&[("a", "1"), ("b", "2")])
+    );
+    let missing = find(&document, "[form=missing]");
+    assert_eq!(form_owner(&document, missing), None);
+}
+
+#[test_case]
+fn get_url() {
+    let base = ParsedUrl::new(String::from("http://127.0.0.1:8888/dir/index.html?old"));
+    let mut submission = Submission {
+        method: FormMethod::Get,
+        enctype: FormEnctype::Urlencoded,
+        action: String::from("search.html?x=1#top"),
+        entries: entries(&[("q", "a b")]),
+    };
+    assert_eq!(
+        submission.url(&base).unwrap().to_string(),
+        "http://127.0.0.1:8888/dir/search.html?q=a+b#top"
+    );
+    // A form without action is submitted to the document.
+    submission.action = String::new();
+    assert_eq!(
+        submission.url(&base).unwrap().to_string(),
+        "http://127.0.0.1:8888/dir/index.html?q=a+b"
+    );
+    // An invalid action has no URL.
+    submission.action = String::from("http://127.0.0.1:99999/");
+    assert!(submission.url(&base).is_none());
+}
+
+#[test_case]
+fn post_body() {
+    let base = ParsedUrl::new(String::from("http://127.0.0.1:8888/login.html"));
+    let document = parse(String::from(
+        "<form method=post action='/login?next=1'><input name=user value='a b'>\
+         <input type=password name=password value='p&w'><button>Log in</button></form>",
+    ));
+    let submission = submit(&document, find(&document, "button")).unwrap();
+    assert_eq!(submission.method, FormMethod::Post);
+    // The query of the action is kept, unlike GET.
+    assert_eq!(
+        submission.action_url(&base).unwrap().to_string(),
+        "http://127.0.0.1:8888/login?next=1"
+    );
+    assert_eq!(
+        submission.body(),
+        FormBody {
+            content_type: String::from(URLENCODED),
+            data: String::from("user=a+b&password=p%26w"),
+        }
+    );
+}
+
+#[test_case]
+fn multipart_body() {
+    let document = parse(String::from(
+        "<form method=post enctype=multipart/form-data><textarea name='a\"b'></textarea>\
+         <input name=q value=x><button formenctype=text/plain>Plain</button><button>Go</button>\
+         </form>",
+    ));
+    let buttons = document
+        .query_selector_all(document.root(), "button")
+        .unwrap();
+    let submission = submit(&document, buttons[1]).unwrap();
+    assert_eq!(submission.enctype, FormEnctype::Multipart);
+    let entries = entries(&[("a\"b", "1\n2"), ("q", "x")]);
+    assert_eq!(
+        multipart_encode(&entries, "B"),
+        "--B\r\nContent-Disposition: form-data; name=\"a%22b\"\r\n\r\n1\r\n2\r\n\
+         --B\r\nContent-Disposition: form-data; name=\"q\"\r\n\r\nx\r\n--B--\r\n"
+    );
+
+    // The boundary is random, and the body is encoded with it.
+    let body = submission.body();
+    let boundary = body
+        .content_type
+        .strip_prefix("multipart/form-data; boundary=----liumosFormBoundary")
+        .unwrap();
+    assert_eq!(boundary.len(), 16);
+    assert!(boundary.bytes().all(|b| b.is_ascii_alphanumeric()));
+    assert_ne!(submission.body().content_type, body.content_type);
+    assert!(body.data.ends_with(&format!("{}--\r\n", boundary)));
+
+    // An unsupported type is sent as URL-encoded.
+    let submission = submit(&document, buttons[0]).unwrap();
+    assert_eq!(submission.enctype, FormEnctype::Urlencoded);
+}
+
+#[test_case]
+fn submit_button() {
+    let document = parse(String::from(
+        "<form action=a.html><input name=q value=1>\
+         <button type=button>No</button><button name=go formaction=b.html>Go</button></form>",
+    ));
+    let buttons = document
+        .query_selector_all(document.root(), "button")
+        .unwrap();
+    assert_eq!(submit(&document, buttons[0]), None);
+    assert_eq!(
+        submit(&document, buttons[1]),
+        Some(Submission {
+            method: FormMethod::Get,
+            enctype: FormEnctype::Urlencoded,
+            action: String::from("b.html"),
+            entries: entries(&[("q", "1"), ("go", "")]),
+        })
+    );
+}
+
+#[test_case]
+fn implicit_submission() {
+    // Enter in a text field submits the form by its first submit button.
+    let document = parse(String::from(
+        "<form method=POST><input name=q><input type=submit name=s value=Go></form>",
+    ));
+    let submission = submit(&document, find(&document, "[name=q]")).unwrap();
+    assert_eq!(submission.method, FormMethod::Post);
+    assert_eq!(submission.entries, entries(&[("q", ""), ("s", "Go")]));
+
+    // Without a submit button, it's submitted only if there's one text field.
+    let document = parse(String::from(
+        "<form><input name=q><input type=checkbox></form>",
+    ));
+    assert!(submit(&document, find(&document, "input")).is_some());
+    let document = parse(String::from("<form><input name=a><input name=b></form>"));
+    assert_eq!(submit(&document, find(&document, "input")), None);
+    let document = parse(String::from(
+        "<form><input name=q><input type=submit disabled></form>",
+    ));
+    assert_eq!(submit(&document, find(&document, "input")), None);
+}
+
+#[test_case]
+fn focused_button() {
+    let viewport = Viewport {
+        width: 200.0,
+        height: 100.0,
+        color_scheme: ColorScheme::Light,
+    };
+    let mut page = Page::new(
+        parse(String::from(
+            "<form><a href=a.html>a</a><button>Go</button><button disabled>No</button></form>",
+        )),
+        viewport,
+    );
+    assert_eq!(page.focusable_elements().len(), 2);
+    page.focus_next(true);
+    assert_eq!(page.focused_href(), None);
+    assert_eq!(
+        page.focused_submission().map(|s| s.entries),
+        Some(Vec::new())
+    );
+    page.focus_next(true);
+    assert_eq!(page.focused_submission(), None);
+    assert_eq!(page.focused_href(), Some(String::from("a.html")));
+}
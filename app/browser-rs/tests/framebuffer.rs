@@ -0,0 +1,376 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::image::loader::load_images;
+use browser_rs::layout::geometry::Rect;
+use browser_rs::page::Page;
+use browser_rs::paint::display_list::*;
+use browser_rs::paint::double_buffer::*;
+use browser_rs::paint::frame_timer::FrameTimer;
+use browser_rs::paint::framebuffer::repaint;
+use browser_rs::paint::raster::*;
+use browser_rs::parser::parse;
+use browser_rs::style::computed::*;
+use browser_rs::style::values::Color;
+use liumlib::gui::BitmapImageBuffer;
+use liumlib::surface::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in framebuffer.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// Returns the pixel at (x, y) as 0xRRGGBB.
+fn rgb(bitmap: &Bitmap, x: usize, y: usize) -> u32 {
+    bitmap.pixel(x, y).to_u32()
+}
+
+/// Returns whether `surface` shows the same pixels as `bitmap`.
+fn shows(surface: &MemorySurface, bitmap: &Bitmap) -> bool {
+    let (width, height) = surface.size();
+    (0..height).all(|y| (0..width).all(|x| surface.presented_pixel(x, y) == rgb(bitmap, x, y)))
+}
+
+/// Returns the rows of pixels in a rectangle as lines of `.` for white and `#` for the others.
+fn draw(bitmap: &Bitmap, x: usize, y: usize, width: usize, height: usize) -> Vec<String> {
+    (y..y + height)
+        .map(|y| {
+            (x..x + width)
+                .map(|x| {
+                    if rgb(bitmap, x, y) == 0xffffff {
+                        '.'
+                    } else {
+                        '#'
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+const VIEWPORT: Viewport = Viewport {
+    width: 40.0,
+    height: 30.0,
+    color_scheme: ColorScheme::Light,
+};
+
+/// Renders `html` in a 40x30 bitmap scrolled down by `scroll_y`.
+fn render(html: &str, scroll_y: f64) -> Bitmap {
+    let mut page = Page::new(parse(String::from(html)), VIEWPORT);
+    page.scroll_to(scroll_y);
+    rasterize(page.display_list(), page.visible_rect())
+}
+
+#[test_case]
+fn backgrounds() {
+    let html = "<style>body { margin: 0 } div { margin: 2px; height: 4px; \
+                background-color: #ff0000 } p { margin: 0; height: 2px; \
+                background-color: #0000ff80 }</style><body><div><p></p></div></body>";
+    let buf = render(html, 0.0);
+    assert_eq!(rgb(&buf, 0, 0), 0xffffff);
+    assert_eq!(rgb(&buf, 37, 5), 0xff0000);
+    assert_eq!(rgb(&buf, 38, 5), 0xffffff);
+    assert_eq!(rgb(&buf, 2, 6), 0xffffff);
+    // A translucent color is blended with the pixels under it.
+    assert_eq!(rgb(&buf, 2, 3), 0x7f0080);
+    assert_eq!(rgb(&buf, 2, 4), 0xff0000);
+}
+
+#[test_case]
+fn borders() {
+    let html = "<body style=\"margin: 0\"><div style=\"width: 2px; height: 1px; \
+                border-style: solid; border-width: 1px 2px; border-color: #00ff00\"></div>";
+    let buf = render(html, 0.0);
+    assert_eq!(
+        draw(&buf, 0, 0, 7, 4),
+        vec!["######.", "##..##.", "######.", "......."]
+    );
+    assert_eq!(rgb(&buf, 0, 1), 0x00ff00);
+}
+
+#[test_case]
+fn text() {
+    let buf = render("<body style=\"margin: 1px\">-</body>", 0.0);
+    assert_eq!(rgb(&buf, 3, 9), 0x000000);
+    assert_eq!(
+        draw(&buf, 0, 8, 10, 3),
+        vec!["..........", ".#######..", ".........."]
+    );
+
+    // Text is drawn in its color, and links are underlined.
+    let buf = render(
+        "<body style=\"margin: 0\"><a href=x>-</a><span style=\"color: red\">-</span>",
+        0.0,
+    );
+    assert_eq!(rgb(&buf, 0, 8), 0x0000ff);
+    assert_eq!(rgb(&buf, 8, 8), 0xff0000);
+    assert_eq!(
+        draw(&buf, 0, 13, 17, 3),
+        vec![
+            ".................",
+            "########.........",
+            "................."
+        ]
+    );
+    assert_eq!(rgb(&buf, 7, 14), 0x0000ff);
+}
+
+#[test_case]
+fn scrolled() {
+    // The document is painted from the scroll position, and the rest is clipped.
+    let html = "<style>body { margin: 0 } div { height: 20px; background-color: #ff0000 } \
+                p { margin: 0; height: 20px }</style><body><p></p><div></div><p></p></body>";
+    let buf = render(html, 10.0);
+    assert_eq!(rgb(&buf, 0, 9), 0xffffff);
+    assert_eq!(rgb(&buf, 0, 10), 0xff0000);
+    assert_eq!(rgb(&buf, 39, 29), 0xff0000);
+    // Glyphs on the edges are clipped.
+    let buf = render(
+        "<body style=\"margin: 0\"><p style=\"margin: 20px 0 0 36px\">-</p></body>",
+        0.0,
+    );
+    assert_eq!(draw(&buf, 34, 28, 6, 2), vec!["..####", "......"]);
+}
+
+#[test_case]
+fn images() {
+    // A 2x2 image of red, lime, blue and black is scaled to 4x4 pixels.
+    let mut document = parse(String::from(
+        "<body style=\"margin: 0\"><img src=a.ppm width=4 height=4>",
+    ));
+    load_images(&mut document, |_| {
+        let mut data = Vec::from(&b"P6 2 2 255\n"[..]);
+        data.extend_from_slice(&[255, 0, 0, 0, 255, 0, 0, 0, 255, 0, 0, 0]);
+        Some(data)
+    });
+    let page = Page::new(document, VIEWPORT);
+    let buf = rasterize(page.display_list(), page.visible_rect());
+    assert_eq!(rgb(&buf, 0, 0), 0xff0000);
+    assert_eq!(rgb(&buf, 1, 1), 0xff0000);
+    assert_eq!(rgb(&buf, 2, 1), 0x00ff00);
+    assert_eq!(rgb(&buf, 3, 0), 0x00ff00);
+    assert_eq!(rgb(&buf, 0, 3), 0x0000ff);
+    assert_eq!(rgb(&buf, 3, 3), 0x000000);
+    assert_eq!(draw(&buf, 0, 0, 5, 5)[4], ".....");
+    assert_eq!(draw(&buf, 4, 0, 1, 4), vec![".", ".", ".", "."]);
+}
+
+#[test_case]
+fn repaint_damaged_regions() {
+    let html = "<style>body { margin: 0 } div { height: 20px; background-color: #ff0000 } \
+                p { margin: 0; height: 20px }</style><body><p></p><div></div><p>-</p></body>";
+    let mut page = Page::new(parse(String::from(html)), VIEWPORT);
+    let buf = Bitmap::new(40, 30);
+    assert_eq!(repaint(&buf, &mut page), 1);
+    assert_eq!(buf.to_ppm(), render(html, 0.0).to_ppm());
+    // Nothing is painted without damage.
+    assert_eq!(repaint(&buf, &mut page), 0);
+
+    // The pixels are moved, and the part scrolled into view is painted.
+    page.scroll_by(12.0);
+    page.scroll_by(3.0);
+    // The strips scrolled into view touch but don't overlap.
+    assert_eq!(repaint(&buf, &mut page), 2);
+    assert_eq!(buf.to_ppm(), render(html, 15.0).to_ppm());
+    page.scroll_by(-7.0);
+    repaint(&buf, &mut page);
+    assert_eq!(buf.to_ppm(), render(html, 8.0).to_ppm());
+
+    page.update_document(|document| {
+        let div = document.query_selector(document.root(), "div").unwrap();
+        document.set_attribute(div, "style", "background-color: #0000ff");
+    });
+    assert_eq!(repaint(&buf, &mut page), 1);
+    assert_eq!(rgb(&buf, 0, 12), 0x0000ff);
+}
+
+#[test_case]
+fn double_buffer() {
+    let html = "<style>body { margin: 0 } div { height: 20px; background-color: #ff0000 } \
+                p { margin: 0; height: 20px }</style><body><p></p><div></div><p>-</p></body>";
+    let mut page = Page::new(parse(String::from(html)), VIEWPORT);
+    let mut buffer = DoubleBuffer::new(MemorySurface::new(40, 30));
+    assert_eq!(buffer.back().bytes_per_pixel(), 4);
+    assert_eq!(buffer.repaint(&mut page), 1);
+    assert!(shows(buffer.front(), &render(html, 0.0)));
+    assert_eq!(buffer.front().presentations(), 1);
+
+    // The front surface isn't presented without damage.
+    assert_eq!(buffer.repaint(&mut page), 0);
+    assert_eq!(buffer.front().presentations(), 1);
+
+    // Scrolling changes the whole front surface.
+    page.scroll_by(15.0);
+    buffer.repaint(&mut page);
+    assert!(shows(buffer.front(), &render(html, 15.0)));
+}
+
+#[test_case]
+fn overlay() {
+    let html = "<style>body { margin: 0 } p { margin: 0; height: 10px } \
+                div { height: 10px; background-color: #ff0000 }</style>\
+                <body><p></p><div></div><p></p><p></p><p></p><p></p></body>";
+    let mut page = Page::new(parse(String::from(html)), VIEWPORT);
+    let mut buffer = DoubleBuffer::new(MemorySurface::new(40, 30));
+    let overlay = vec![DisplayItem::SolidRect {
+        rect: Rect::new(0.0, 0.0, 40.0, 5.0),
+        color: Color::rgb(0, 0xff, 0),
+    }];
+    buffer.repaint_with_overlay(&mut page, &overlay);
+    assert_eq!(buffer.front().presented_pixel(0, 4), 0x00ff00);
+    assert_eq!(buffer.front().presented_pixel(0, 5), 0xffffff);
+
+    // The page under the overlay is repainted after scrolling and closing it.
+    page.scroll_by(3.0);
+    buffer.repaint_with_overlay(&mut page, &overlay);
+    assert_eq!(buffer.front().presented_pixel(0, 4), 0x00ff00);
+    assert_eq!(buffer.front().presented_pixel(0, 7), 0xff0000);
+    page.scroll_by(-3.0);
+    buffer.repaint(&mut page);
+    assert!(shows(buffer.front(), &render(html, 0.0)));
+}
+
+#[test_case]
+fn separate_overlays() {
+    let html = "<body style='background-color: #0000ff'></body>";
+    let mut page = Page::new(parse(String::from(html)), VIEWPORT);
+    let mut buffer = DoubleBuffer::new(MemorySurface::new(40, 30));
+    let bar = |y| DisplayItem::SolidRect {
+        rect: Rect::new(0.0, y, 40.0, 5.0),
+        color: Color::rgb(0, 0xff, 0),
+    };
+    let overlay = vec![bar(0.0), bar(25.0)];
+    buffer.repaint_with_overlay(&mut page, &overlay);
+    // Only the page under the two bars is painted again, not between them.
+    assert_eq!(buffer.repaint_with_overlay(&mut page, &overlay), 2);
+    assert_eq!(buffer.front().presented_pixel(0, 26), 0x00ff00);
+    assert_eq!(buffer.front().presented_pixel(0, 15), 0x0000ff);
+}
+
+#[test_case]
+fn blit_between_layouts() {
+    let src = render("<body style='background-color: #123456'></body>", 0.0);
+    let dst = Bitmap::with_bytes_per_pixel(40, 30, 4);
+    blit(&src, &dst, Rect::new(2.0, 3.0, 4.0, 5.0));
+    assert_eq!(rgb(&dst, 1, 3), 0x000000);
+    assert_eq!(rgb(&dst, 2, 3), 0x123456);
+    assert_eq!(rgb(&dst, 5, 7), 0x123456);
+    assert_eq!(rgb(&dst, 6, 7), 0x000000);
+    assert_eq!(rgb(&dst, 5, 8), 0x000000);
+    // The parts outside the buffers are skipped.
+    blit(&src, &dst, Rect::new(-10.0, 25.0, 100.0, 100.0));
+    assert_eq!(rgb(&dst, 39, 29), 0x123456);
+}
+
+#[test_case]
+fn frame_timer() {
+    let mut timer = FrameTimer::new();
+    assert_eq!(timer.finish(), 0);
+    timer.start();
+    render("<p>a</p>", 0.0);
+    let cycles = timer.finish();
+    assert!(cycles > 0);
+    assert_eq!(
+        (timer.frames(), timer.last(), timer.average()),
+        (1, cycles, cycles)
+    );
+    assert_eq!(timer.finish(), 0);
+    assert_eq!(timer.frames(), 1);
+}
+
+#[test_case]
+fn page_below_overlay() {
+    let html = "<style>body { margin: 0 } div { height: 10px; background-color: #ff0000 } \
+                p { margin: 0; height: 10px }</style><body><div></div><p></p><div></div><p></p><div></div></body>";
+    let mut page = Page::new(parse(String::from(html)), VIEWPORT);
+    // The page is shown from the 5th row, below a strip.
+    let mut buffer = DoubleBuffer::with_page_top(MemorySurface::new(40, 35), 5);
+    let strip = vec![DisplayItem::SolidRect {
+        rect: Rect::new(0.0, 0.0, 40.0, 5.0),
+        color: Color::rgb(0, 0xff, 0),
+    }];
+    buffer.repaint_with_overlay(&mut page, &strip);
+    assert_eq!(buffer.front().presented_pixel(0, 4), 0x00ff00);
+    assert_eq!(buffer.front().presented_pixel(0, 5), 0xff0000);
+    assert_eq!(buffer.front().presented_pixel(0, 15), 0xffffff);
+    assert_eq!(buffer.front().presented_pixel(0, 25), 0xff0000);
+
+    // Scrolling moves only the page.
+    page.scroll_by(10.0);
+    buffer.repaint_with_overlay(&mut page, &strip);
+    assert_eq!(buffer.front().presented_pixel(0, 4), 0x00ff00);
+    assert_eq!(buffer.front().presented_pixel(0, 5), 0xffffff);
+    assert_eq!(buffer.front().presented_pixel(0, 15), 0xff0000);
+    assert_eq!(buffer.front().presented_pixel(0, 25), 0xffffff);
+}
+
+#[test_case]
+fn present_into_surface() {
+    let src = render("<body style='background-color: #123456'></body>", 0.0);
+    let mut surface = MemorySurface::new(40, 30);
+    present(&src, &mut surface, Rect::new(2.0, 3.0, 4.0, 5.0));
+    assert_eq!(surface.presentations(), 1);
+    assert_eq!(surface.presented_pixel(1, 3), 0x000000);
+    assert_eq!(surface.presented_pixel(2, 3), 0x123456);
+    assert_eq!(surface.presented_pixel(5, 7), 0x123456);
+    assert_eq!(surface.presented_pixel(6, 7), 0x000000);
+    // The parts outside the surface are skipped.
+    present(&src, &mut surface, Rect::new(-10.0, 25.0, 100.0, 100.0));
+    assert_eq!(surface.presented_pixel(39, 29), 0x123456);
+}
+
+#[test_case]
+fn overlay_over_front() {
+    let html = "<body style='background-color: #0000ff'></body>";
+    let mut page = Page::new(parse(String::from(html)), VIEWPORT);
+    let mut buffer = DoubleBuffer::new(MemorySurface::new(40, 30));
+    buffer.repaint(&mut page);
+    let dialog = vec![DisplayItem::SolidRect {
+        rect: Rect::new(10.0, 10.0, 20.0, 10.0),
+        color: Color::rgb(0, 0xff, 0),
+    }];
+    buffer.paint_over_front(&dialog);
+    assert_eq!(buffer.front().presented_pixel(10, 10), 0x00ff00);
+    assert_eq!(buffer.front().presented_pixel(9, 10), 0x0000ff);
+    // The back bitmap keeps the frame under the dialog.
+    assert_eq!(rgb(buffer.back(), 10, 10), 0x0000ff);
+    buffer.restore_front();
+    assert!(shows(buffer.front(), &render(html, 0.0)));
+}
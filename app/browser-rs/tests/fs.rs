@@ -0,0 +1,102 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use liumlib::fs::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in fs.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// A file outside the tree, which each test writes from scratch.
+const PATH: &str = "/tmp/liumlib-fs-test.txt";
+
+#[test_case]
+fn reading_and_writing() {
+    let file = File::create(PATH).unwrap();
+    file.write_all(b"hello, world").unwrap();
+    assert_eq!(file.seek(SeekFrom::Start(7)), Some(7));
+    file.write_all(b"liumOS").unwrap();
+    assert_eq!(file.seek(SeekFrom::Current(0)), Some(13));
+    file.set_len(5).unwrap();
+    drop(file);
+
+    let file = File::open(PATH).unwrap();
+    let mut buf = [0u8; 3];
+    assert_eq!(file.read(&mut buf), Some(3));
+    assert_eq!(&buf, b"hel");
+    assert_eq!(file.read_to_end(), Some(b"lo".to_vec()));
+    assert_eq!(file.read(&mut buf), Some(0));
+    assert_eq!(file.seek(SeekFrom::End(-2)), Some(3));
+    assert_eq!(file.read_to_end(), Some(b"lo".to_vec()));
+    // A file opened to read can't be written.
+    assert_eq!(file.write(b"x"), None);
+
+    File::append(PATH).unwrap().write_all(b"!").unwrap();
+    assert_eq!(read_file(PATH), Some(b"hello!".to_vec()));
+    write_file(PATH, b"new").unwrap();
+    assert_eq!(read_file(PATH), Some(b"new".to_vec()));
+}
+
+#[test_case]
+fn reading_until_a_byte() {
+    write_file(PATH, b"<p>a</p>\x04ignored").unwrap();
+    let read = |end: u8, limit: usize| {
+        let file = File::open(PATH).unwrap();
+        read_until(file.file_descriptor().number(), end, limit)
+    };
+    assert_eq!(read(0x04, 100), Some(b"<p>a</p>".to_vec()));
+    assert_eq!(read(0x04, 3), Some(b"<p>".to_vec()));
+    assert_eq!(read(0, 100), Some(b"<p>a</p>\x04ignored".to_vec()));
+}
+
+#[test_case]
+fn missing_files() {
+    assert!(File::open("/tmp/liumlib-missing/file.txt").is_none());
+    assert!(File::create("/tmp/liumlib-missing/file.txt").is_none());
+    assert_eq!(read_file("/tmp/liumlib-missing/file.txt"), None);
+    assert_eq!(read_dir("/tmp/liumlib-missing"), None);
+}
+
+#[test_case]
+fn directories() {
+    write_file(PATH, b"").unwrap();
+    let entries = read_dir("/tmp").unwrap();
+    assert!(entries.iter().any(|e| e.name == "liumlib-fs-test.txt"));
+    assert!(!entries.iter().any(|e| e.name == "." || e.name == ".."));
+    // A file isn't a directory.
+    assert_eq!(read_dir(PATH), None);
+}
@@ -0,0 +1,191 @@
+//! The tests of the parsers which run with std on the host, where the allocator frees memory and
+//! the test harness of Rust runs the cases in parallel, so they sweep many generated inputs.
+//! Run them by `make test_host`, or by `cargo test --features std --test host` with LLVM_CC and
+//! LLVM_AR set.
+
+use browser_rs::http::HttpResponse;
+use browser_rs::layout::text_dump::dump_text;
+use browser_rs::page::Page;
+use browser_rs::parser::css::tokenizer::*;
+use browser_rs::parser::parse;
+use browser_rs::parser::tokenizer::*;
+use browser_rs::style::computed::{ColorScheme, Viewport};
+use browser_rs::style::values::*;
+use browser_rs::url::ParsedUrl;
+
+const HOSTS: [&str; 4] = ["127.0.0.1", "10.0.0.1", "192.168.0.254", "localhost"];
+const PATHS: [&str; 6] = [
+    "/",
+    "/index.html",
+    "/dir/a.html",
+    "/a/b/c/d.css",
+    "/search?q=1&r=2",
+    "/page.html?x=y#top",
+];
+
+#[test]
+fn urls() {
+    for host in HOSTS.iter() {
+        for port in [1, 80, 8080, 8888, 65535].iter() {
+            for path in PATHS.iter() {
+                let text = format!("http://{}:{}{}", host, port, path);
+                let url = ParsedUrl::parse(&text).unwrap_or_else(|| panic!("{}", text));
+                assert_eq!(url.host, format!("{}:{}", host, port), "{}", text);
+                assert_eq!(url.port, *port, "{}", text);
+                assert_eq!(url.path, *path, "{}", text);
+                assert_eq!(url.to_string(), text);
+            }
+        }
+    }
+}
+
+#[test]
+fn joined_urls() {
+    let base = ParsedUrl::new(String::from("http://127.0.0.1:8888/a/b/c.html"));
+    for path in PATHS.iter() {
+        // An absolute path replaces the whole path, and a relative one the file name.
+        assert_eq!(base.join(path).unwrap().path, *path);
+        let relative = &path[1..];
+        assert_eq!(
+            base.join(relative).unwrap().path,
+            format!("/a/b/{}", relative)
+        );
+        for host in HOSTS.iter() {
+            let absolute = format!("http://{}:80{}", host, path);
+            assert_eq!(base.join(&absolute).unwrap().to_string(), absolute);
+        }
+    }
+}
+
+#[test]
+fn status_lines() {
+    for code in 100..600 {
+        let raw = format!(
+            "HTTP/1.1 {} Reason {}\r\nContent-Length: 4\r\nX-Code: {}\r\n\r\nbody",
+            code, code, code
+        );
+        let response = HttpResponse::new(raw);
+        assert_eq!(response.version(), "HTTP/1.1");
+        assert_eq!(response.status_code(), code);
+        assert_eq!(response.reason(), format!("Reason {}", code));
+        assert_eq!(response.header("x-code"), Some(code.to_string()));
+        assert_eq!(response.body(), "body");
+        let redirects = [301, 302, 303, 307, 308].contains(&code);
+        assert_eq!(response.redirect_location(), None);
+        let located = HttpResponse::new(format!("HTTP/1.1 {} R\nLocation: /next\n\n", code));
+        assert_eq!(located.redirect_location().is_some(), redirects, "{}", code);
+    }
+}
+
+fn tokens(html: &str) -> Vec<Token> {
+    Tokenizer::new(String::from(html)).collect()
+}
+
+#[test]
+fn characters() {
+    for c in (' '..='~').filter(|c| !matches!(c, '<' | '&')) {
+        assert_eq!(tokens(&c.to_string()), vec![Token::Char(c)], "{:?}", c);
+    }
+    for c in ['é', 'あ', '🦀'].iter() {
+        assert_eq!(tokens(&c.to_string()), vec![Token::Char(*c)]);
+    }
+}
+
+#[test]
+fn tags() {
+    let names = [
+        "a", "b", "p", "div", "span", "body", "head", "html", "script", "style", "h1", "h6",
+    ];
+    let quotes = ["\"", "'", ""];
+    for name in names.iter() {
+        for quote in quotes.iter() {
+            let html = format!(
+                "<{} id={}x{} class={}y{}></{}>",
+                name.to_ascii_uppercase(),
+                quote,
+                quote,
+                quote,
+                quote,
+                name
+            );
+            let start = Token::StartTag {
+                tag: name.to_string(),
+                self_closing: false,
+                attributes: vec![
+                    Attribute::new(String::from("id"), String::from("x")),
+                    Attribute::new(String::from("class"), String::from("y")),
+                ],
+            };
+            let end = Token::EndTag {
+                tag: name.to_string(),
+                self_closing: false,
+            };
+            assert_eq!(tokens(&html), vec![start, end], "{}", html);
+        }
+    }
+}
+
+fn color(css: &str) -> Option<Color> {
+    parse_color(&CssTokenizer::new(String::from(css)).collect::<Vec<CssToken>>())
+}
+
+#[test]
+fn short_hex_colors() {
+    for r in 0..16u8 {
+        for g in 0..16u8 {
+            for b in 0..16u8 {
+                let css = format!("#{:x}{:x}{:X}", r, g, b);
+                let expected = Color::rgb(r * 17, g * 17, b * 17);
+                assert_eq!(color(&css), Some(expected), "{}", css);
+            }
+        }
+    }
+}
+
+#[test]
+fn rgb_colors() {
+    for v in 0..=255u8 {
+        let css = format!("rgb({}, {}, {})", v, 255 - v, v / 2);
+        assert_eq!(color(&css), Some(Color::rgb(v, 255 - v, v / 2)), "{}", css);
+        let css = format!("#{:02x}{:02x}{:02x}", v, 255 - v, v / 2);
+        assert_eq!(color(&css), Some(Color::rgb(v, 255 - v, v / 2)), "{}", css);
+    }
+}
+
+#[test]
+fn lengths() {
+    for px in 0..1000 {
+        let css = format!("{}px", px);
+        let tokens: Vec<CssToken> = CssTokenizer::new(css.clone()).collect();
+        let length = parse_length(&tokens).unwrap_or_else(|| panic!("{}", css));
+        assert_eq!(length.to_px(0.0), px as f64, "{}", css);
+    }
+}
+
+#[test]
+fn wrapped_words() {
+    let viewport = Viewport {
+        width: 200.0,
+        height: 100.0,
+        color_scheme: ColorScheme::Light,
+    };
+    let words = [
+        "a",
+        "liumos",
+        "browser",
+        "wraps",
+        "lines",
+        "x",
+        "longerwords",
+    ];
+    for count in 1..100 {
+        let text: Vec<&str> = words.iter().cycle().take(count).copied().collect();
+        let html = format!("<p>{}</p>", text.join(" "));
+        let page = Page::new(parse(html), viewport);
+        let dumped = dump_text(page.document(), page.layout().unwrap(), |href| {
+            href.to_string()
+        });
+        // The lines are broken only between the words, which all stay in order.
+        assert_eq!(dumped.split_whitespace().collect::<Vec<&str>>(), text);
+    }
+}
@@ -0,0 +1,95 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use browser_rs::http::HttpResponse;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in http.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn status_line_headers_and_body() {
+    let response = HttpResponse::new(String::from(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 5\r\n\r\nhello",
+    ));
+    assert_eq!(response.version(), "HTTP/1.1");
+    assert_eq!(response.status_code(), 200);
+    assert_eq!(response.reason(), "OK");
+    assert_eq!(
+        response.header("Content-Type"),
+        Some(String::from("text/html"))
+    );
+    assert_eq!(response.header("Content-Length"), Some(String::from("5")));
+    assert_eq!(response.body(), "hello");
+}
+
+#[test_case]
+fn lf_line_endings() {
+    let response = HttpResponse::new(String::from(
+        "HTTP/1.1 404 Not Found\nServer: test\n\n<p>a</p>\n\n<p>b</p>",
+    ));
+    assert_eq!(response.status_code(), 404);
+    assert_eq!(response.reason(), "Not Found");
+    assert_eq!(response.header("Server"), Some(String::from("test")));
+    // Only the first empty line ends the headers.
+    assert_eq!(response.body(), "<p>a</p>\n\n<p>b</p>");
+}
+
+#[test_case]
+fn header_names_are_case_insensitive() {
+    let response = HttpResponse::new(String::from(
+        "HTTP/1.1 200 OK\r\ncontent-type:text/plain\r\n\r\n",
+    ));
+    assert_eq!(
+        response.header("Content-Type"),
+        Some(String::from("text/plain"))
+    );
+    assert_eq!(response.header("Content-Length"), None);
+    assert_eq!(response.body(), "");
+}
+
+#[test_case]
+fn status_line_only() {
+    let response = HttpResponse::new(String::from("HTTP/1.0 204 No Content"));
+    assert_eq!(response.version(), "HTTP/1.0");
+    assert_eq!(response.status_code(), 204);
+    assert_eq!(response.reason(), "No Content");
+    assert_eq!(response.header("Content-Type"), None);
+    assert_eq!(response.body(), "");
+}
@@ -0,0 +1,297 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::image::inflate::*;
+use browser_rs::image::*;
+use browser_rs::layout::geometry::*;
+use browser_rs::paint::display_list::*;
+use browser_rs::paint::raster::*;
+use browser_rs::style::values::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in image.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+const RED: Color = Color::rgb(255, 0, 0);
+const BLUE: Color = Color::rgb(0, 0, 255);
+
+/// Returns the pixels of `image` in rows.
+fn pixels(image: &Image) -> Vec<Vec<Color>> {
+    (0..image.height())
+        .map(|y| (0..image.width()).map(|x| image.pixel(x, y)).collect())
+        .collect()
+}
+
+#[test_case]
+fn binary_ppm() {
+    let mut data = Vec::from(&b"P6\n# a comment\n2 1\n255\n"[..]);
+    data.extend_from_slice(&[255, 0, 0, 0, 0, 255]);
+    let image = decode(&data).unwrap();
+    assert_eq!(pixels(&image), vec![vec![RED, BLUE]]);
+
+    // Samples are 2 bytes if the maxval is more than 255.
+    let mut data = Vec::from(&b"P6 1 1 65535\n"[..]);
+    data.extend_from_slice(&[0xff, 0xff, 0x80, 0x00, 0x00, 0x00]);
+    let image = decode(&data).unwrap();
+    assert_eq!(image.pixel(0, 0), Color::rgb(255, 128, 0));
+
+    // The raster is shorter than the size.
+    assert_eq!(decode(&data[..data.len() - 1]), None);
+}
+
+#[test_case]
+fn plain_ppm() {
+    let image = decode(b"P3\n1 2\n15\n15 0 0\n# blue\n0 0 15\n").unwrap();
+    assert_eq!(pixels(&image), vec![vec![RED], vec![BLUE]]);
+    assert_eq!(decode(b"P3\n1 2\n15\n15 0 0\n"), None);
+    // A size larger than the limit is rejected before the samples are read.
+    assert_eq!(decode(b"P3\n100000 100000\n15\n15 0 0\n"), None);
+    assert_eq!(decode(b"P3\n0 1\n15\n"), None);
+}
+
+#[test_case]
+fn bmp() {
+    // A bitmap encoded by the headless backend: red at the top-left and blue at the
+    // bottom-right on white.
+    let list = vec![
+        DisplayItem::SolidRect {
+            rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            color: RED,
+        },
+        DisplayItem::SolidRect {
+            rect: Rect::new(2.0, 1.0, 1.0, 1.0),
+            color: BLUE,
+        },
+    ];
+    let data = rasterize(&list, Rect::new(0.0, 0.0, 3.0, 2.0)).to_bmp();
+    let image = decode(&data).unwrap();
+    let w = Color::WHITE;
+    assert_eq!(pixels(&image), vec![vec![RED, w, w], vec![w, w, BLUE]]);
+
+    // A top-down bitmap with 32 bits per pixel.
+    let mut data = data[..54].to_vec();
+    data[18..22].copy_from_slice(&2i32.to_le_bytes());
+    data[22..26].copy_from_slice(&(-2i32).to_le_bytes());
+    data[28] = 32;
+    data.extend_from_slice(&[0, 0, 255, 0, 255, 0, 0, 0]);
+    data.extend_from_slice(&[255, 255, 255, 0, 0, 0, 0, 0]);
+    let image = decode(&data).unwrap();
+    assert_eq!(pixels(&image), vec![vec![RED, BLUE], vec![w, Color::BLACK]]);
+
+    // The pixels start inside the headers, or the size is larger than the limit.
+    let mut broken = data.clone();
+    broken[10..14].copy_from_slice(&20u32.to_le_bytes());
+    assert_eq!(decode(&broken), None);
+    let mut broken = data.clone();
+    broken[18..22].copy_from_slice(&i32::MAX.to_le_bytes());
+    assert_eq!(decode(&broken), None);
+    let mut broken = data.clone();
+    broken[22..26].copy_from_slice(&i32::MIN.to_le_bytes());
+    assert_eq!(decode(&broken), None);
+
+    // Compressed bitmaps aren't supported.
+    data[30] = 1;
+    assert_eq!(decode(&data), None);
+    // The pixels are shorter than the size.
+    data[30] = 0;
+    data.truncate(data.len() - 1);
+    assert_eq!(decode(&data), None);
+}
+
+#[test_case]
+fn unsupported_formats() {
+    assert_eq!(decode(b"GIF89a"), None);
+    assert_eq!(decode(b""), None);
+    assert_eq!(decode(b"BM"), None);
+}
+
+#[test_case]
+fn zlib() {
+    // Compressed by zlib with a stored block, fixed Huffman codes and dynamic ones.
+    let stored = [
+        120, 1, 1, 11, 0, 244, 255, 104, 101, 108, 108, 111, 32, 104, 101, 108, 108, 111, 25, 145,
+        4, 73,
+    ];
+    assert_eq!(zlib_decompress(&stored, 1024).unwrap(), b"hello hello");
+    let fixed = [
+        120, 218, 203, 72, 205, 201, 201, 87, 200, 64, 144, 0, 58, 46, 6, 125,
+    ];
+    assert_eq!(zlib_decompress(&fixed, 1024).unwrap(), b"hello hello hello");
+    let dynamic = [
+        120, 218, 165, 202, 71, 1, 0, 32, 12, 0, 177, 235, 132, 14, 255, 122, 17, 65, 222, 225, 8,
+        90, 180, 19, 11, 135, 171, 88, 51, 65, 130, 92, 202, 240, 97, 147, 159, 246, 0, 77, 5, 1,
+        137,
+    ];
+    let expected: Vec<u8> = (0..100)
+        .map(|i| if i % 3 == 0 { 0 } else { (i * 7 % 13) as u8 })
+        .collect();
+    assert_eq!(zlib_decompress(&dynamic, 1024).unwrap(), expected);
+
+    // The checksum doesn't match.
+    let mut broken = fixed;
+    broken[15] ^= 1;
+    assert_eq!(zlib_decompress(&broken, 1024), None);
+    assert_eq!(zlib_decompress(&fixed[..10], 1024), None);
+
+    // The output is longer than the limit, in a stored block, a literal or a copy.
+    assert_eq!(zlib_decompress(&stored, 10), None);
+    assert_eq!(zlib_decompress(&fixed, 3), None);
+    assert_eq!(zlib_decompress(&fixed, 16), None);
+    assert_eq!(zlib_decompress(&fixed, 17).unwrap(), b"hello hello hello");
+}
+
+/// Returns `data` in a zlib stream with a stored block.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let len = data.len() as u16;
+    let mut stream = vec![0x78, 0x01, 0x01];
+    stream.extend_from_slice(&len.to_le_bytes());
+    stream.extend_from_slice(&(!len).to_le_bytes());
+    stream.extend_from_slice(data);
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    stream.extend_from_slice(&((b << 16) | a).to_be_bytes());
+    stream
+}
+
+/// Returns a PNG file of `chunks`, whose CRCs are dummies.
+fn png(chunks: &[(&[u8], &[u8])]) -> Vec<u8> {
+    let mut data = vec![137, 80, 78, 71, 13, 10, 26, 10];
+    for (kind, chunk) in chunks {
+        data.extend_from_slice(&(chunk.len() as u32).to_be_bytes());
+        data.extend_from_slice(kind);
+        data.extend_from_slice(chunk);
+        data.extend_from_slice(&[0; 4]);
+    }
+    data
+}
+
+/// Returns an IHDR chunk.
+fn ihdr(width: u32, height: u32, bit_depth: u8, color_type: u8) -> Vec<u8> {
+    let mut chunk = Vec::new();
+    chunk.extend_from_slice(&width.to_be_bytes());
+    chunk.extend_from_slice(&height.to_be_bytes());
+    chunk.extend_from_slice(&[bit_depth, color_type, 0, 0, 0]);
+    chunk
+}
+
+#[test_case]
+fn png_filters() {
+    // Each scanline starts with its filter type: Sub, Up, Average and Paeth.
+    let raw = [
+        1, 10, 20, 30, 30, 30, 30, //
+        2, 5, 5, 5, 5, 5, 5, //
+        3, 13, 18, 23, 18, 18, 18, //
+        4, 5, 5, 5, 5, 5, 5,
+    ];
+    let stream = zlib_stored(&raw);
+    // The compressed data can be split into IDAT chunks.
+    let data = png(&[
+        (b"IHDR", &ihdr(2, 4, 8, 2)),
+        (b"IDAT", &stream[..5]),
+        (b"tEXt", b"Comment\0ignored"),
+        (b"IDAT", &stream[5..]),
+        (b"IEND", b""),
+    ]);
+    let image = decode(&data).unwrap();
+    let rgb = Color::rgb;
+    assert_eq!(
+        pixels(&image),
+        vec![
+            vec![rgb(10, 20, 30), rgb(40, 50, 60)],
+            vec![rgb(15, 25, 35), rgb(45, 55, 65)],
+            vec![rgb(20, 30, 40), rgb(50, 60, 70)],
+            vec![rgb(25, 35, 45), rgb(55, 65, 75)],
+        ]
+    );
+
+    // The data decompresses to more scanlines than the header says, or the size is larger than
+    // the limit.
+    for header in [ihdr(2, 3, 8, 2), ihdr(100000, 4, 8, 2)].iter() {
+        let data = png(&[(b"IHDR", header), (b"IDAT", &stream), (b"IEND", b"")]);
+        assert_eq!(decode(&data), None);
+    }
+}
+
+#[test_case]
+fn png_color_types() {
+    let decode_png = |header: Vec<u8>, chunks: &[(&[u8], &[u8])], raw: &[u8]| {
+        let stream = zlib_stored(raw);
+        let mut all: Vec<(&[u8], &[u8])> = vec![(b"IHDR", &header)];
+        all.extend_from_slice(chunks);
+        all.push((b"IDAT", &stream));
+        all.push((b"IEND", b""));
+        decode(&png(&all)).map(|image| pixels(&image))
+    };
+
+    let rgba = decode_png(ihdr(2, 1, 8, 6), &[], &[0, 255, 0, 0, 128, 0, 0, 255, 0]);
+    assert_eq!(
+        rgba,
+        Some(vec![vec![
+            Color::rgba(255, 0, 0, 128),
+            Color::rgba(0, 0, 255, 0)
+        ]])
+    );
+
+    // The palette entries without alpha in tRNS are opaque.
+    let indexed = decode_png(
+        ihdr(3, 1, 8, 3),
+        &[(b"PLTE", &[255, 0, 0, 0, 0, 255]), (b"tRNS", &[64])],
+        &[0, 1, 0, 1],
+    );
+    assert_eq!(
+        indexed,
+        Some(vec![vec![BLUE, Color::rgba(255, 0, 0, 64), BLUE]])
+    );
+
+    // Only the most significant bytes of 16-bit samples are used.
+    let grey = decode_png(ihdr(1, 1, 16, 4), &[], &[0, 0x12, 0x34, 0xff, 0xff]);
+    assert_eq!(grey, Some(vec![vec![Color::rgb(0x12, 0x12, 0x12)]]));
+
+    // An index out of the palette, an unknown filter type and sub-byte samples are errors.
+    assert_eq!(
+        decode_png(ihdr(1, 1, 8, 3), &[(b"PLTE", &[0, 0, 0])], &[0, 1]),
+        None
+    );
+    assert_eq!(decode_png(ihdr(1, 1, 8, 0), &[], &[5, 0]), None);
+    assert_eq!(decode_png(ihdr(8, 1, 1, 0), &[], &[0, 0]), None);
+}
@@ -0,0 +1,152 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use liumlib::input::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in input.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+fn decode(raws: &[RawInputEvent]) -> Vec<Event> {
+    let mut decoder = EventDecoder::new();
+    raws.iter().flat_map(|raw| decoder.decode(raw)).collect()
+}
+
+fn down(key: Key, shift: bool, ctrl: bool) -> Event {
+    Event::KeyDown {
+        key,
+        modifiers: Modifiers { shift, ctrl },
+    }
+}
+
+#[test_case]
+fn keys() {
+    // 'A' typed with Shift, released, and the key IDs of Enter, Shift+Tab and Ctrl+L.
+    assert_eq!(
+        decode(&[
+            RawInputEvent::key(0x4010),
+            RawInputEvent::key(0x0100 | b'A' as u16),
+            RawInputEvent::key(0x8100 | b'A' as u16),
+            RawInputEvent::key(b'\n' as u16),
+            RawInputEvent::key(0x4141),
+            RawInputEvent::key(0x0200 | b'l' as u16),
+        ]),
+        vec![
+            down(Key::Shift, false, false),
+            down(Key::Char('A'), true, false),
+            Event::KeyUp {
+                key: Key::Char('A'),
+                modifiers: Modifiers {
+                    shift: true,
+                    ctrl: false
+                }
+            },
+            down(Key::Enter, false, false),
+            down(Key::Tab, true, false),
+            down(Key::Char('l'), false, true),
+        ]
+    );
+    // The extended keys.
+    assert_eq!(
+        decode(&[
+            RawInputEvent::key(0x4000),
+            RawInputEvent::key(0x401c),
+            RawInputEvent::key(0x4020),
+            RawInputEvent::key(0x4023),
+            RawInputEvent::key(0x4005),
+            RawInputEvent::key(0x4040),
+            RawInputEvent::key(0x407f),
+        ]),
+        vec![
+            down(Key::Escape, false, false),
+            down(Key::Up, false, false),
+            down(Key::PageUp, false, false),
+            down(Key::End, false, false),
+            down(Key::Function(5), false, false),
+            down(Key::Backspace, false, false),
+            down(Key::Other(0x7f), false, false),
+        ]
+    );
+}
+
+#[test_case]
+fn pointer() {
+    assert_eq!(
+        decode(&[
+            RawInputEvent::pointer(10, 20, 0),
+            RawInputEvent::pointer(10, 20, 0),
+            RawInputEvent::pointer(12, 25, BUTTON_LEFT),
+            RawInputEvent::pointer(12, 25, BUTTON_LEFT | BUTTON_RIGHT),
+            RawInputEvent::pointer(12, 25, 0),
+        ]),
+        vec![
+            Event::PointerMove { x: 10, y: 20 },
+            // The button is pressed after moving.
+            Event::PointerMove { x: 12, y: 25 },
+            Event::PointerDown {
+                button: Button::Left,
+                x: 12,
+                y: 25
+            },
+            Event::PointerDown {
+                button: Button::Right,
+                x: 12,
+                y: 25
+            },
+            Event::PointerUp {
+                button: Button::Left,
+                x: 12,
+                y: 25
+            },
+            Event::PointerUp {
+                button: Button::Right,
+                x: 12,
+                y: 25
+            },
+        ]
+    );
+    // Unknown events are ignored.
+    assert_eq!(decode(&[RawInputEvent::default()]), vec![]);
+}
+
+#[test_case]
+fn no_device_on_linux() {
+    assert!(InputDevice::open().is_none());
+}
@@ -0,0 +1,111 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+mod common;
+
+use common::run;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in js_builtins.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn string_slice() {
+    assert_eq!(run("'hello'.slice(1)"), "\"ello\"");
+    assert_eq!(run("'hello'.slice(1, 3)"), "\"el\"");
+    assert_eq!(run("'hello'.slice(-3, -1)"), "\"ll\"");
+    assert_eq!(run("'hello'.slice(3, 1)"), "\"\"");
+    assert_eq!(run("'hello'.slice(0, 100)"), "\"hello\"");
+    assert_eq!(run("'héllo'.slice(1, 2)"), "\"é\"");
+}
+
+#[test_case]
+fn string_index_of() {
+    assert_eq!(run("'abcabc'.indexOf('c')"), "2");
+    assert_eq!(run("'abcabc'.indexOf('c', 3)"), "5");
+    assert_eq!(run("'abcabc'.indexOf('d')"), "-1");
+    assert_eq!(run("'abc'.indexOf('')"), "0");
+    assert_eq!(run("'abc'.indexOf('', 10)"), "3");
+    assert_eq!(run("'abc'.indexOf('a', -5)"), "0");
+}
+
+#[test_case]
+fn string_split() {
+    assert_eq!(run("'a,b,,c'.split(',').join('|')"), "\"a|b||c\"");
+    assert_eq!(run("'abc'.split('').join('|')"), "\"a|b|c\"");
+    assert_eq!(run("'abc'.split().length"), "1");
+    assert_eq!(run("'a b c'.split(' ', 2).join('|')"), "\"a|b\"");
+    assert_eq!(run("''.split(',').length"), "1");
+    assert_eq!(
+        run("var split = 'a'.split; split(',')"),
+        "TypeError: String.prototype.split called on undefined at 1:24"
+    );
+}
+
+#[test_case]
+fn array_methods() {
+    assert_eq!(run("var a = [1]; a.push(2, 3)"), "3");
+    assert_eq!(run("var a = [1]; a.push(2, 3); a.join()"), "\"1,2,3\"");
+    assert_eq!(run("[1, null, undefined, 'x'].join('-')"), "\"1---x\"");
+    assert_eq!(run("[].join()"), "\"\"");
+    assert_eq!(
+        run("[1, 2, 3].map(function (x, i) { return x * 10 + i; }).join()"),
+        "\"10,21,32\""
+    );
+    assert_eq!(
+        run("[1].map(function () { return this.n; }, {n: 5}).join()"),
+        "\"5\""
+    );
+    assert_eq!(run("[1].map(2)"), "TypeError: 2 is not a function at 1:1");
+    // The arrays parsed from JSON have the methods as well.
+    assert_eq!(run("JSON.parse('[1, 2]').join(' ')"), "\"1 2\"");
+}
+
+#[test_case]
+fn math() {
+    assert_eq!(run("Math.floor(1.5)"), "1");
+    assert_eq!(run("Math.floor(-1.5)"), "-2");
+    assert_eq!(run("Math.floor('3.9')"), "3");
+    assert_eq!(
+        run(
+            "var ok = true; for (var i = 0; i < 100; i++) { var r = Math.random(); \
+             if (!(r >= 0 && r < 1)) ok = false; } ok"
+        ),
+        "true"
+    );
+    assert_eq!(run("Math.random() === Math.random()"), "false");
+}
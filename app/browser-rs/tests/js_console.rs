@@ -0,0 +1,114 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+
+use browser_rs::js::console::*;
+use browser_rs::js::runtime::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in js_console.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+fn format(source: &str) -> String {
+    let mut runtime = Runtime::new();
+    let value = runtime.run(source).expect("the script should run");
+    format_message(&[value])
+}
+
+#[test_case]
+fn messages() {
+    assert_eq!(format("'hello'"), "hello");
+    assert_eq!(format("1 / 2"), "0.5");
+    assert_eq!(format("undefined"), "undefined");
+    assert_eq!(format("null"), "null");
+    assert_eq!(
+        format("[1, 'a', [true, [null, [2]]]]"),
+        "[1, \"a\", [true, [Array]]]"
+    );
+    assert_eq!(format("({a: 1, b: {c: 'x'}})"), "{a: 1, b: {c: \"x\"}}");
+    assert_eq!(format("({})"), "{}");
+    assert_eq!(format("(function f() {})"), "[Function: f]");
+    assert_eq!(format("(function () {})"), "[Function (anonymous)]");
+    // A cycle is cut at the maximum depth.
+    assert_eq!(
+        format("var o = {}; o.self = o; o"),
+        "{self: {self: [Object]}}"
+    );
+}
+
+#[test_case]
+fn arguments_are_separated_by_spaces() {
+    let mut runtime = Runtime::new();
+    let args = [
+        runtime.run("'x ='").unwrap(),
+        runtime.run("1").unwrap(),
+        runtime.run("'y'").unwrap(),
+    ];
+    assert_eq!(format_message(&args), "x = 1 y");
+    assert_eq!(format_message(&[]), "");
+}
+
+#[test_case]
+fn lines() {
+    let console = Console::new(Some(String::from("http://example.com/")));
+    assert_eq!(
+        console.line(Level::Log, "hello"),
+        "[http://example.com/] hello"
+    );
+    assert_eq!(
+        console.line(Level::Warn, "hello"),
+        "[http://example.com/] warning: hello"
+    );
+    assert_eq!(
+        console.line(Level::Error, "hello"),
+        "[http://example.com/] error: hello"
+    );
+    assert_eq!(Console::new(None).line(Level::Log, "hello"), "hello");
+}
+
+#[test_case]
+fn console_object() {
+    let mut runtime = Runtime::new();
+    Rc::new(Console::new(None)).install(&mut runtime);
+    let types = runtime
+        .run("typeof console.log + ' ' + typeof console.warn + ' ' + typeof console.error")
+        .expect("the script should run");
+    assert_eq!(format!("{}", types), "function function function");
+}
@@ -0,0 +1,100 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+mod common;
+
+use alloc::rc::Rc;
+use alloc::string::String;
+
+use browser_rs::cookie::{CookieJar, Source};
+use browser_rs::js::engine::Engine;
+use browser_rs::url::ParsedUrl;
+use common::run_with;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in js_cookie.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn read_and_write() {
+    let jar = Rc::new(CookieJar::new());
+    let page = ParsedUrl::new(String::from("http://example.com/app/index.html"));
+    jar.set(&page, "sid=1; Path=/; HttpOnly", Source::Http);
+    jar.set(&page, "lang=en; Path=/", Source::Http);
+
+    let mut engine = Engine::with_url("http://example.com/app/index.html");
+    engine.set_cookie_jar(jar.clone());
+    // HttpOnly cookies are hidden from scripts.
+    assert_eq!(run_with(&mut engine, "document.cookie"), "\"lang=en\"");
+    // Each assignment sets one cookie, which is sent with the requests too.
+    run_with(
+        &mut engine,
+        "document.cookie = 'visits=' + 1; document.cookie = 'lang=ja; path=/'",
+    );
+    assert_eq!(
+        run_with(&mut engine, "document.cookie"),
+        "\"visits=1; lang=ja\""
+    );
+    assert_eq!(jar.header_for(&page).unwrap(), "visits=1; sid=1; lang=ja");
+    // A cookie is deleted by making it expire.
+    run_with(
+        &mut engine,
+        "document.cookie = 'visits=; expires=Thu, 01 Jan 1970 00:00:00 GMT'",
+    );
+    assert_eq!(run_with(&mut engine, "document.cookie"), "\"lang=ja\"");
+    // The other elements have no cookie.
+    assert_eq!(
+        run_with(&mut engine, "document.createElement('p').cookie"),
+        "undefined"
+    );
+}
+
+#[test_case]
+fn without_jar() {
+    let mut engine = Engine::with_url("http://example.com/");
+    assert_eq!(
+        run_with(&mut engine, "document.cookie = 'a=1'; document.cookie"),
+        "\"\""
+    );
+    // A document without a URL has no cookie even with a jar.
+    let mut engine = Engine::new();
+    engine.set_cookie_jar(Rc::new(CookieJar::new()));
+    assert_eq!(
+        run_with(&mut engine, "document.cookie = 'a=1'; document.cookie"),
+        "\"\""
+    );
+}
@@ -0,0 +1,100 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+mod common;
+
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use browser_rs::js::dialog::DialogKind;
+use browser_rs::js::engine::Engine;
+use common::run_with;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in js_dialog.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// The dialogs which have been shown, with their messages.
+type Shown = Rc<RefCell<Vec<(DialogKind, String)>>>;
+
+/// Returns an engine whose dialogs are answered with `answer` and recorded in the returned list.
+fn engine_answering(answer: bool) -> (Engine, Shown) {
+    let shown = Rc::new(RefCell::new(Vec::new()));
+    let mut engine = Engine::new();
+    let record = shown.clone();
+    engine.set_dialog_handler(Rc::new(move |kind, message| {
+        record.borrow_mut().push((kind, String::from(message)));
+        answer
+    }));
+    (engine, shown)
+}
+
+#[test_case]
+fn alert() {
+    let (mut engine, shown) = engine_answering(false);
+    assert_eq!(run_with(&mut engine, "alert('hi ' + 1)"), "undefined");
+    assert_eq!(run_with(&mut engine, "alert()"), "undefined");
+    assert_eq!(
+        *shown.borrow(),
+        [
+            (DialogKind::Alert, String::from("hi 1")),
+            (DialogKind::Alert, String::new())
+        ]
+    );
+}
+
+#[test_case]
+fn confirm() {
+    let (mut engine, shown) = engine_answering(true);
+    // The script waits for the answer before it goes on.
+    assert_eq!(
+        run_with(&mut engine, "var ok = confirm('Sure?'); ok ? 'yes' : 'no'"),
+        "\"yes\""
+    );
+    assert_eq!(
+        *shown.borrow(),
+        [(DialogKind::Confirm, String::from("Sure?"))]
+    );
+
+    let (mut engine, _) = engine_answering(false);
+    assert_eq!(run_with(&mut engine, "confirm(null)"), "false");
+
+    // A dialog which can't be shown is canceled.
+    assert_eq!(run_with(&mut Engine::new(), "confirm('Sure?')"), "false");
+}
@@ -0,0 +1,212 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+mod common;
+
+use alloc::rc::Rc;
+use alloc::string::String;
+use core::cell::RefCell;
+
+use browser_rs::js::dom::*;
+use browser_rs::js::runtime::*;
+use browser_rs::parser::dom::Document;
+use browser_rs::parser::parse;
+use common::run_in;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in js_dom.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// Parses `html` and returns a runtime bound to it.
+fn bind(html: &str) -> (Runtime, Rc<DomBindings>) {
+    let document = Rc::new(RefCell::new(parse(String::from(html))));
+    let mut runtime = Runtime::new();
+    let bindings = DomBindings::install(&mut runtime, document);
+    (runtime, bindings)
+}
+
+fn body_html(document: &Rc<RefCell<Document>>) -> String {
+    let document = document.borrow();
+    let body = document
+        .descendants(document.root())
+        .find(|n| document.node(*n).element().map(|e| e.tag_name()) == Some("body"))
+        .expect("there should be a body");
+    document.inner_html(body)
+}
+
+#[test_case]
+fn get_element_by_id() {
+    let (mut runtime, _) = bind("<p id=a class=x>Hello <b>world</b></p>");
+    assert_eq!(
+        run_in(&mut runtime, "document.getElementById('a').tagName"),
+        "\"P\""
+    );
+    assert_eq!(
+        run_in(&mut runtime, "document.getElementById('a').textContent"),
+        "\"Hello world\""
+    );
+    assert_eq!(
+        run_in(&mut runtime, "document.getElementById('missing')"),
+        "null"
+    );
+    assert_eq!(
+        run_in(
+            &mut runtime,
+            "document.getElementById('a') === document.getElementById('a')"
+        ),
+        "true"
+    );
+    assert_eq!(run_in(&mut runtime, "document.nodeType"), "9");
+    assert_eq!(
+        run_in(&mut runtime, "document.getElementById('a') + ''"),
+        "\"[object HTMLElement]\""
+    );
+}
+
+#[test_case]
+fn attributes() {
+    let (mut runtime, bindings) = bind("<p id=a class=x>Hello</p>");
+    assert_eq!(
+        run_in(
+            &mut runtime,
+            "var p = document.getElementById('a'); p.getAttribute('class')"
+        ),
+        "\"x\""
+    );
+    assert_eq!(run_in(&mut runtime, "p.getAttribute('title')"), "null");
+    assert_eq!(
+        run_in(
+            &mut runtime,
+            "p.setAttribute('title', 1); p.getAttribute('TITLE')"
+        ),
+        "\"1\""
+    );
+    assert_eq!(run_in(&mut runtime, "p.id = 'b'; p.id"), "\"b\"");
+    assert_eq!(
+        body_html(bindings.document()),
+        "<p id=\"b\" class=\"x\" title=\"1\">Hello</p>"
+    );
+}
+
+#[test_case]
+fn text_content() {
+    let (mut runtime, bindings) = bind("<p id=a>Hello <b>world</b></p>");
+    assert_eq!(
+        run_in(
+            &mut runtime,
+            "var p = document.getElementById('a'); p.textContent = 'Bye'; p.textContent"
+        ),
+        "\"Bye\""
+    );
+    assert_eq!(body_html(bindings.document()), "<p id=\"a\">Bye</p>");
+    run_in(&mut runtime, "p.textContent = ''");
+    assert_eq!(body_html(bindings.document()), "<p id=\"a\"></p>");
+}
+
+#[test_case]
+fn inner_html() {
+    let (mut runtime, bindings) = bind("<div id=a>Hello <b>world</b></div>");
+    assert_eq!(
+        run_in(
+            &mut runtime,
+            "var a = document.getElementById('a'); a.innerHTML"
+        ),
+        "\"Hello <b>world</b>\""
+    );
+    assert_eq!(
+        run_in(
+            &mut runtime,
+            "a.innerHTML = '<p id=b>new</p><script>a.id = \"c\"</script>';\n\
+             document.getElementById('b').textContent"
+        ),
+        "\"new\""
+    );
+    // The script inserted by innerHTML doesn't run.
+    assert_eq!(run_in(&mut runtime, "a.id"), "\"a\"");
+    run_in(&mut runtime, "a.innerHTML = null");
+    assert_eq!(body_html(bindings.document()), "<div id=\"a\"></div>");
+    assert_eq!(run_in(&mut runtime, "document.innerHTML"), "undefined");
+
+    // A replaced node which a script has got stays the same node.
+    run_in(
+        &mut runtime,
+        "a.innerHTML = '<i id=i>old</i>'; var i = document.getElementById('i');\n\
+         a.innerHTML = '<b>new</b><b>new</b>'; a.appendChild(i)",
+    );
+    assert_eq!(
+        body_html(bindings.document()),
+        "<div id=\"a\"><b>new</b><b>new</b><i id=\"i\">old</i></div>"
+    );
+}
+
+#[test_case]
+fn creating_elements() {
+    let (mut runtime, bindings) = bind("<div id=list></div>");
+    run_in(
+        &mut runtime,
+        "var list = document.getElementById('list');\n\
+         for (var i = 1; i <= 2; i++) {\n\
+           var item = document.createElement('LI');\n\
+           item.appendChild(document.createTextNode('item ' + i));\n\
+           list.appendChild(item);\n\
+         }",
+    );
+    assert_eq!(
+        body_html(bindings.document()),
+        "<div id=\"list\"><li>item 1</li><li>item 2</li></div>"
+    );
+}
+
+#[test_case]
+fn errors() {
+    let (mut runtime, _) = bind("<div id=a><p id=b></p></div>");
+    assert_eq!(
+        run_in(
+            &mut runtime,
+            "var a = document.getElementById('a');\na.appendChild(1)"
+        ),
+        "TypeError: parameter 1 is not of type 'Node' at 2:1"
+    );
+    assert_eq!(
+        run_in(&mut runtime, "document.getElementById('b').appendChild(a)"),
+        "HierarchyRequestError: the new child is an ancestor of the parent at 1:1"
+    );
+    assert_eq!(
+        run_in(&mut runtime, "var get = a.getAttribute; get('id')"),
+        "TypeError: illegal invocation at 1:27"
+    );
+}
@@ -0,0 +1,198 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+
+use browser_rs::js::engine::ScriptFetcher;
+use browser_rs::parser::dom::Document;
+use browser_rs::parser::{parse, parse_with_scripts};
+use browser_rs::timing::{Stage, Timing};
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in js_engine.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+fn body_html(document: &Document) -> String {
+    let body = document
+        .descendants(document.root())
+        .find(|n| document.node(*n).element().map(|e| e.tag_name()) == Some("body"))
+        .expect("there should be a body");
+    document.inner_html(body)
+}
+
+#[test_case]
+fn inline_script() {
+    let document = parse(String::from(
+        "<p id=a>Hello</p>\
+         <script>document.getElementById('a').textContent = 'Bye';</script>",
+    ));
+    assert_eq!(
+        body_html(&document),
+        "<p id=\"a\">Bye</p>\
+         <script>document.getElementById('a').textContent = 'Bye';</script>"
+    );
+}
+
+#[test_case]
+fn scripts_run_when_parsed() {
+    // A script sees only the nodes before it, and the globals of the scripts before it.
+    let document = parse(String::from(
+        "<p id=a></p>\
+         <script>var found = document.getElementById('b') === null;</script>\
+         <p id=b></p>\
+         <script>document.getElementById('a').textContent = found;</script>",
+    ));
+    let a = document
+        .descendants(document.root())
+        .find(|n| {
+            document
+                .node(*n)
+                .element()
+                .and_then(|e| e.get_attribute("id"))
+                == Some("a".into())
+        })
+        .expect("there should be #a");
+    assert_eq!(document.text_content(a), "true");
+}
+
+#[test_case]
+fn scripts_which_do_not_run() {
+    let document = parse(String::from(
+        "<p id=a>Hello</p>\
+         <script type=text/plain>document.getElementById('a').textContent = 'plain';</script>\
+         <script type=module>document.getElementById('a').textContent = 'module';</script>\
+         <script src=a.js>document.getElementById('a').textContent = 'src';</script>\
+         <script>document.getElementById('a').textContent = 'unclosed';",
+    ));
+    assert!(body_html(&document).starts_with("<p id=\"a\">Hello</p>"));
+}
+
+#[test_case]
+fn errors_do_not_stop_later_scripts() {
+    let document = parse(String::from(
+        "<p id=a>Hello</p>\
+         <script>undefinedFunction();</script>\
+         <script>document.getElementById('a').textContent = 'Bye';</script>",
+    ));
+    assert!(body_html(&document).starts_with("<p id=\"a\">Bye</p>"));
+}
+
+/// Returns a fetcher of the scripts "a.js", which appends "a" to the log, and "b.js", which
+/// appends "b".
+fn fetcher() -> ScriptFetcher {
+    Rc::new(|src| match src {
+        "a.js" => Some(String::from("log.textContent += 'a';")),
+        "b.js" => Some(String::from("log.textContent += 'b';")),
+        _ => None,
+    })
+}
+
+fn parse_log(html: &str) -> String {
+    let mut timing = Timing::new();
+    let html = format!(
+        "<p id=log></p><script>var log = document.getElementById('log');</script>{}",
+        html
+    );
+    let document = parse_with_scripts(html, "http://localhost/", &mut timing, fetcher());
+    let log = document
+        .descendants(document.root())
+        .find(|n| {
+            document
+                .node(*n)
+                .element()
+                .and_then(|e| e.get_attribute("id"))
+                == Some("log".into())
+        })
+        .expect("there should be #log");
+    document.text_content(log)
+}
+
+#[test_case]
+fn external_scripts() {
+    assert_eq!(
+        parse_log(
+            "<script src=b.js></script>\
+             <script>log.textContent += '-';</script>\
+             <script src=a.js></script>"
+        ),
+        "b-a"
+    );
+    // A script which fails to load is skipped.
+    assert_eq!(
+        parse_log("<script src=missing.js></script><script src=a.js></script>"),
+        "a"
+    );
+    // External scripts aren't fetched without a fetcher.
+    let document = parse(String::from(
+        "<p id=log></p>\
+         <script>var log = document.getElementById('log');</script>\
+         <script src=a.js></script>",
+    ));
+    assert!(body_html(&document).starts_with("<p id=\"log\"></p>"));
+}
+
+#[test_case]
+fn deferred_scripts() {
+    // Async and defer scripts run in the document order after parsing.
+    assert_eq!(
+        parse_log(
+            "<script defer src=a.js></script>\
+             <script async src=b.js></script>\
+             <script>log.textContent += '-';</script>"
+        ),
+        "-ab"
+    );
+    // Defer is ignored by inline scripts.
+    assert_eq!(
+        parse_log("<script defer src=b.js></script><script defer>log.textContent += 'a';</script>"),
+        "ab"
+    );
+}
+
+#[test_case]
+fn fetching_scripts_is_timed() {
+    let mut timing = Timing::new();
+    parse_with_scripts(
+        String::from("<script>var log = {};</script><script src=a.js></script>"),
+        "http://localhost/",
+        &mut timing,
+        fetcher(),
+    );
+    assert!(timing.get(Stage::Fetch) > 0);
+}
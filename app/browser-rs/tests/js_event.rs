@@ -0,0 +1,232 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::String;
+
+use browser_rs::page::Page;
+use browser_rs::parser::dom::{Document, NodeId};
+use browser_rs::parser::parse;
+use browser_rs::style::computed::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in js_event.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+const VIEWPORT: Viewport = Viewport {
+    width: 200.0,
+    height: 100.0,
+    color_scheme: ColorScheme::Light,
+};
+
+/// Returns the element whose id is `id`.
+fn by_id(document: &Document, id: &str) -> NodeId {
+    document
+        .descendants(document.root())
+        .find(|n| {
+            document
+                .node(*n)
+                .element()
+                .and_then(|e| e.get_attribute("id"))
+                .as_deref()
+                == Some(id)
+        })
+        .expect("the element should exist")
+}
+
+/// Parses `html` with a log, clicks the element `target`, and returns whether the click isn't
+/// canceled and the log.
+fn click(html: &str, target: &str) -> (bool, String) {
+    let mut document = parse(format!(
+        "<p id=log></p><script>var log = document.getElementById('log');\n\
+         function append(s) {{ log.textContent += s; }}</script>{}",
+        html
+    ));
+    let target = by_id(&document, target);
+    let proceed = document.dispatch_event(target, "click");
+    let log = by_id(&document, "log");
+    (proceed, document.text_content(log))
+}
+
+#[test_case]
+fn phases() {
+    let script = "<script>\n\
+                  function listen(id, capture) {\n\
+                    document.getElementById(id).addEventListener('click', function (e) {\n\
+                      append(id + e.eventPhase + ' ');\n\
+                    }, capture);\n\
+                  }\n\
+                  listen('outer', false); listen('outer', true);\n\
+                  listen('inner', false); listen('inner', true);\n\
+                  document.addEventListener('click', function (e) {\n\
+                    append('doc' + e.eventPhase);\n\
+                  });\n\
+                  </script>";
+    assert_eq!(
+        click(
+            &format!("<div id=outer><b id=inner>x</b></div>{}", script),
+            "inner"
+        ),
+        (true, String::from("outer1 inner2 inner2 outer3 doc3"))
+    );
+}
+
+#[test_case]
+fn event_properties() {
+    assert_eq!(
+        click(
+            "<div id=outer><b id=inner>x</b></div><script>\n\
+             document.getElementById('outer').addEventListener('click', function (e) {\n\
+               append(e.type + e.target.id + e.currentTarget.id + this.id + e.bubbles);\n\
+             });</script>",
+            "inner"
+        ),
+        (true, String::from("clickinnerouteroutertrue"))
+    );
+}
+
+#[test_case]
+fn handler_attributes() {
+    assert_eq!(
+        click(
+            "<div id=outer onclick=\"append('outer')\">\
+             <b id=inner onclick=\"append(this.id + event.type + ' ')\">x</b></div>",
+            "inner"
+        ),
+        (true, String::from("innerclick outer"))
+    );
+    // A handler which returns false cancels the event.
+    assert_eq!(
+        click(
+            "<a id=a href=x onclick='append(1); return false'>x</a>",
+            "a"
+        ),
+        (false, String::from("1"))
+    );
+}
+
+#[test_case]
+fn canceling_and_stopping() {
+    assert_eq!(
+        click(
+            "<div id=outer onclick=\"append('outer')\"><b id=inner>x</b></div><script>\n\
+             var inner = document.getElementById('inner');\n\
+             inner.addEventListener('click', function (e) {\n\
+               e.preventDefault(); e.stopPropagation(); append('a');\n\
+             });\n\
+             inner.addEventListener('click', function (e) {\n\
+               append(e.defaultPrevented ? 'b' : '');\n\
+             });\n\
+             </script>",
+            "inner"
+        ),
+        (false, String::from("ab"))
+    );
+    assert_eq!(
+        click(
+            "<b id=inner>x</b><script>\n\
+             var inner = document.getElementById('inner');\n\
+             inner.addEventListener('click', function (e) {\n\
+               e.stopImmediatePropagation(); append('a');\n\
+             });\n\
+             inner.addEventListener('click', function (e) { append('b'); });\n\
+             </script>",
+            "inner"
+        ),
+        (true, String::from("a"))
+    );
+}
+
+#[test_case]
+fn adding_and_removing() {
+    assert_eq!(
+        click(
+            "<b id=b>x</b><script>\n\
+             var b = document.getElementById('b');\n\
+             function f() { append('f'); }\n\
+             b.addEventListener('click', f); b.addEventListener('click', f);\n\
+             b.addEventListener('click', function g() { append('g'); }, {once: true});\n\
+             b.addEventListener('click', { handleEvent: function () { append('h'); } });\n\
+             </script>",
+            "b"
+        ),
+        (true, String::from("fgh"))
+    );
+    assert_eq!(
+        click(
+            "<b id=b>x</b><script>\n\
+             var b = document.getElementById('b');\n\
+             function f() { append('f'); }\n\
+             b.addEventListener('click', f); b.removeEventListener('click', f);\n\
+             b.addEventListener('click', f, true); b.removeEventListener('click', f);\n\
+             </script>",
+            "b"
+        ),
+        (true, String::from("f"))
+    );
+}
+
+#[test_case]
+fn errors_do_not_stop_other_listeners() {
+    assert_eq!(
+        click(
+            "<b id=b onclick='undefinedFunction()'>x</b><script>\n\
+             var b = document.getElementById('b');\n\
+             b.addEventListener('click', function () { append('a'); });\n\
+             </script>",
+            "b"
+        ),
+        (true, String::from("a"))
+    );
+}
+
+#[test_case]
+fn clicking_a_page() {
+    let mut page = Page::new(
+        parse(String::from(
+            "<a id=a href=x onclick=\"this.textContent = 'clicked'; return false\">link</a>",
+        )),
+        VIEWPORT,
+    );
+    let a = by_id(page.document(), "a");
+    assert!(!page.click(a));
+    assert_eq!(page.document().text_content(a), "clicked");
+    // A document without scripting isn't clicked.
+    let mut document = Document::new();
+    let root = document.root();
+    assert!(document.dispatch_event(root, "click"));
+}
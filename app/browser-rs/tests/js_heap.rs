@@ -0,0 +1,143 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use browser_rs::js::engine::Engine;
+use browser_rs::js::runtime::Runtime;
+use browser_rs::js::value::*;
+use browser_rs::parser::dom::Document;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in js_heap.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// Runs `source` with `engine`, and returns a weak reference to the object it evaluates to.
+fn weak_result(engine: &mut Engine, source: &str) -> WeakObjectRef {
+    match engine.run(&mut Document::new(), source) {
+        Ok(Value::Object(object)) => object.downgrade(),
+        result => panic!("not an object: {:?}", result.map(|_| ())),
+    }
+}
+
+#[test_case]
+fn cycles() {
+    let mut engine = Engine::new();
+    // An object referring to itself, and a function in a variable of its own scope.
+    let object = weak_result(
+        &mut engine,
+        "(function () { var o = {}; o.self = o; return o; })()",
+    );
+    let function = weak_result(
+        &mut engine,
+        "(function () { var g = function () { return g; }; return g; })()",
+    );
+    // They're alive in their cycles until the garbage is collected.
+    assert!(object.upgrade().is_some());
+    assert!(function.upgrade().is_some());
+    assert!(engine.collect_garbage() >= 2);
+    assert!(object.upgrade().is_none());
+    assert!(function.upgrade().is_none());
+}
+
+#[test_case]
+fn roots() {
+    let mut engine = Engine::new();
+    let global = weak_result(&mut engine, "var kept = {}; kept.self = kept; kept");
+    let timer = weak_result(
+        &mut engine,
+        "(function () { var f = function () { f; }; setTimeout(f, 1000); return f; })()",
+    );
+    let listener = weak_result(
+        &mut engine,
+        "(function () { var f = function () { f; }; document.addEventListener('x', f); \
+         return f; })()",
+    );
+    let node = weak_result(
+        &mut engine,
+        "(function () { var e = document.createElement('p'); e.self = e; return e; })()",
+    );
+    engine.collect_garbage();
+    assert!(global.upgrade().is_some());
+    assert!(timer.upgrade().is_some());
+    assert!(listener.upgrade().is_some());
+    assert!(node.upgrade().is_some());
+    assert_eq!(
+        engine
+            .run(&mut Document::new(), "kept.self === kept")
+            .unwrap(),
+        Value::Boolean(true)
+    );
+
+    // Everything is freed when the document is left.
+    drop(engine);
+    assert!(global.upgrade().is_none());
+    assert!(timer.upgrade().is_none());
+    assert!(listener.upgrade().is_none());
+    assert!(node.upgrade().is_none());
+}
+
+#[test_case]
+fn threshold() {
+    let mut runtime = Runtime::new();
+    assert!(!runtime.heap().should_collect());
+    runtime
+        .run("for (var i = 0; i < 2000; i++) { var o = { i: i }; o.self = o; }")
+        .unwrap();
+    assert!(runtime.heap().should_collect());
+    // The last object is still in the variable.
+    assert_eq!(runtime.collect_garbage(&[]), 1999);
+    assert_eq!(runtime.run("o.self.i").unwrap(), Value::Number(1999.0));
+    assert!(!runtime.heap().should_collect());
+}
+
+#[test_case]
+fn reusing_memory() {
+    let mut runtime = Runtime::new();
+    // Each round makes garbage cycles of much more than the heap of liumlib in total, which fit
+    // only if the memory of the collected ones is reused.
+    for _ in 0..20 {
+        runtime
+            .run(
+                "for (var i = 0; i < 2000; i++) { \
+                 var o = { i: i, s: 'a string which takes some room in the heap' }; \
+                 o.self = o; }",
+            )
+            .unwrap();
+        // The last object of the round before is collected too.
+        assert!(runtime.collect_garbage(&[]) >= 1999);
+    }
+    assert_eq!(runtime.run("o.self.i").unwrap(), Value::Number(1999.0));
+}
@@ -0,0 +1,165 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+mod common;
+
+use common::run;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in js_json.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn parse() {
+    assert_eq!(run("JSON.parse('1.5e2')"), "150");
+    assert_eq!(run("JSON.parse(' -0.25 ')"), "-0.25");
+    assert_eq!(run("JSON.parse('\"a\\\\nb\\\\u00e9\"')"), "\"a\\nbé\"");
+    assert_eq!(run("JSON.parse('\"\\\\ud83d\\\\ude00\"')"), "\"😀\"");
+    assert_eq!(run("JSON.parse('null')"), "null");
+    assert_eq!(
+        run(
+            "var o = JSON.parse('{\"a\": [1, true, {\"b\": null}], \"c\": \"x\"}'); o.a[2].b + o.c"
+        ),
+        "\"nullx\""
+    );
+    assert_eq!(run("JSON.parse('[]').length"), "0");
+    // A later property replaces the earlier one.
+    assert_eq!(run("JSON.parse('{\"a\": 1, \"a\": 2}').a"), "2");
+}
+
+#[test_case]
+fn parse_errors() {
+    assert_eq!(
+        run("JSON.parse('[1, 2,]')"),
+        "SyntaxError: JSON.parse: unexpected character ']' at position 6 at 1:1"
+    );
+    assert_eq!(
+        run("JSON.parse(\"{'a': 1}\")"),
+        "SyntaxError: JSON.parse: unexpected character '\\'' at position 1 at 1:1"
+    );
+    assert_eq!(
+        run("JSON.parse('01')"),
+        "SyntaxError: JSON.parse: unexpected character '1' at position 1 at 1:1"
+    );
+    assert_eq!(
+        run("JSON.parse('{\"a\": 1')"),
+        "SyntaxError: JSON.parse: unexpected end of data at 1:1"
+    );
+    assert_eq!(
+        run("JSON.parse('')"),
+        "SyntaxError: JSON.parse: unexpected end of data at 1:1"
+    );
+    assert_eq!(
+        run("var s = ''; for (var i = 0; i < 300; i++) s += '['; JSON.parse(s)"),
+        "SyntaxError: JSON.parse: too deeply nested at 1:53"
+    );
+}
+
+#[test_case]
+fn reviver() {
+    assert_eq!(
+        run(
+            "JSON.stringify(JSON.parse('{\"a\": 1, \"b\": [2, 3]}', function (k, v) {
+                 return typeof v === 'number' ? v * 10 : v;
+             }))"
+        ),
+        "\"{\\\"a\\\":10,\\\"b\\\":[20,30]}\""
+    );
+    // A property for which the reviver returns undefined is deleted.
+    assert_eq!(
+        run(
+            "JSON.stringify(JSON.parse('{\"a\": 1, \"b\": 2}', function (k, v) {
+                 if (k !== 'a') return v;
+             }))"
+        ),
+        "\"{\\\"b\\\":2}\""
+    );
+}
+
+#[test_case]
+fn stringify() {
+    assert_eq!(run("JSON.stringify(1 / 2)"), "\"0.5\"");
+    assert_eq!(run("JSON.stringify(1 / 0)"), "\"null\"");
+    assert_eq!(
+        run("JSON.stringify('a\"\\n\\u0001')"),
+        "\"\\\"a\\\\\\\"\\\\n\\\\u0001\\\"\""
+    );
+    assert_eq!(run("JSON.stringify(undefined)"), "undefined");
+    assert_eq!(run("JSON.stringify(function () {})"), "undefined");
+    assert_eq!(
+        run("JSON.stringify({a: [1, 'x', null, undefined], b: undefined, c: function () {}})"),
+        "\"{\\\"a\\\":[1,\\\"x\\\",null,null]}\""
+    );
+    assert_eq!(run("JSON.stringify({})"), "\"{}\"");
+    assert_eq!(
+        run("JSON.stringify({toJSON: function (key) { return 'key:' + key; }})"),
+        "\"\\\"key:\\\"\""
+    );
+    assert_eq!(
+        run("var o = {}; o.self = o; JSON.stringify(o)"),
+        "TypeError: cyclic object value at 1:25"
+    );
+    // The same object can be serialized twice if it isn't in itself.
+    assert_eq!(run("var o = {}; JSON.stringify([o, o])"), "\"[{},{}]\"");
+}
+
+#[test_case]
+fn replacer_and_space() {
+    assert_eq!(
+        run("JSON.stringify({a: 1, b: 'x'}, function (k, v) {
+                 return typeof v === 'number' ? v + 1 : v;
+             })"),
+        "\"{\\\"a\\\":2,\\\"b\\\":\\\"x\\\"}\""
+    );
+    assert_eq!(
+        run("JSON.stringify({a: 1, b: 2, c: 3}, ['c', 'a'])"),
+        "\"{\\\"c\\\":3,\\\"a\\\":1}\""
+    );
+    assert_eq!(
+        run("JSON.stringify({a: [1, {}], b: []}, null, 2)"),
+        "\"{\\n  \\\"a\\\": [\\n    1,\\n    {}\\n  ],\\n  \\\"b\\\": []\\n}\""
+    );
+    assert_eq!(run("JSON.stringify([1], null, '--')"), "\"[\\n--1\\n]\"");
+}
+
+#[test_case]
+fn round_trip() {
+    assert_eq!(
+        run("JSON.stringify(JSON.parse(' { \"a\" : [ 1 , \"\\\\u0041\" ] , \"b\" : { } } '))"),
+        "\"{\\\"a\\\":[1,\\\"A\\\"],\\\"b\\\":{}}\""
+    );
+}
@@ -0,0 +1,209 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::js::lexer::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in js_lexer.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+fn tokens(source: &str) -> Vec<Token> {
+    Lexer::new(source).map(|lexeme| lexeme.token).collect()
+}
+
+fn identifier(name: &str) -> Token {
+    Token::Identifier(String::from(name))
+}
+
+fn string(value: &str) -> Token {
+    Token::String(String::from(value))
+}
+
+#[test_case]
+fn empty() {
+    assert_eq!(tokens(""), vec![]);
+    assert_eq!(tokens(" \t\n // nothing\n /* at all */ "), vec![]);
+}
+
+#[test_case]
+fn statement() {
+    assert_eq!(
+        tokens("var x = foo(1, 'a');"),
+        vec![
+            Token::Keyword("var"),
+            identifier("x"),
+            Token::Punctuator("="),
+            identifier("foo"),
+            Token::Punctuator("("),
+            Token::Number(1.0),
+            Token::Punctuator(","),
+            string("a"),
+            Token::Punctuator(")"),
+            Token::Punctuator(";"),
+        ]
+    );
+}
+
+#[test_case]
+fn identifiers() {
+    assert_eq!(
+        tokens("$el _private functionName café"),
+        vec![
+            identifier("$el"),
+            identifier("_private"),
+            identifier("functionName"),
+            identifier("café"),
+        ]
+    );
+}
+
+#[test_case]
+fn numbers() {
+    assert_eq!(
+        tokens("0 42 3.25 .5 1e3 2.5E-2 0xff 0o17 0b101"),
+        vec![
+            Token::Number(0.0),
+            Token::Number(42.0),
+            Token::Number(3.25),
+            Token::Number(0.5),
+            Token::Number(1000.0),
+            Token::Number(0.025),
+            Token::Number(255.0),
+            Token::Number(15.0),
+            Token::Number(5.0),
+        ]
+    );
+    match tokens("3in").as_slice() {
+        [Token::Invalid(_)] => {}
+        other => panic!("expected an invalid token but got {:?}", other),
+    }
+}
+
+#[test_case]
+fn strings() {
+    assert_eq!(
+        tokens(
+            r#""double" 'single' "it's" 'a\'b' "\n\t\\" "\x41B\u{1F600}" "a\
+b""#
+        ),
+        vec![
+            string("double"),
+            string("single"),
+            string("it's"),
+            string("a'b"),
+            string("\n\t\\"),
+            string("AB\u{1F600}"),
+            string("ab"),
+        ]
+    );
+    match tokens("'unterminated").as_slice() {
+        [Token::Invalid(_)] => {}
+        other => panic!("expected an invalid token but got {:?}", other),
+    }
+}
+
+#[test_case]
+fn punctuators() {
+    assert_eq!(
+        tokens("a>>>=b===c=>d?.e?.5:f..."),
+        vec![
+            identifier("a"),
+            Token::Punctuator(">>>="),
+            identifier("b"),
+            Token::Punctuator("==="),
+            identifier("c"),
+            Token::Punctuator("=>"),
+            identifier("d"),
+            Token::Punctuator("?."),
+            identifier("e"),
+            Token::Punctuator("?"),
+            Token::Number(0.5),
+            Token::Punctuator(":"),
+            identifier("f"),
+            Token::Punctuator("..."),
+        ]
+    );
+}
+
+#[test_case]
+fn comments() {
+    let lexemes: Vec<Lexeme> = Lexer::new("a // one\nb /* two */ c /* three\n */ d").collect();
+    let tokens: Vec<Token> = lexemes.iter().map(|l| l.token.clone()).collect();
+    assert_eq!(
+        tokens,
+        vec![
+            identifier("a"),
+            identifier("b"),
+            identifier("c"),
+            identifier("d")
+        ]
+    );
+    let newlines: Vec<bool> = lexemes.iter().map(|l| l.newline_before).collect();
+    assert_eq!(newlines, vec![false, true, false, true]);
+
+    match self::tokens("a /* unclosed").as_slice() {
+        [_, Token::Invalid(_)] => {}
+        other => panic!("expected an invalid token but got {:?}", other),
+    }
+}
+
+#[test_case]
+fn positions() {
+    let positions: Vec<String> = Lexer::new("let a;\r\n  a = 1;")
+        .map(|l| alloc::format!("{}", l.pos))
+        .collect();
+    assert_eq!(
+        positions,
+        vec!["1:1", "1:5", "1:6", "2:3", "2:5", "2:7", "2:8"]
+    );
+}
+
+#[test_case]
+fn unexpected_character() {
+    assert_eq!(
+        tokens("a # b"),
+        vec![
+            identifier("a"),
+            Token::Invalid(String::from("unexpected character '#'")),
+            identifier("b"),
+        ]
+    );
+}
@@ -0,0 +1,109 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+mod common;
+
+use alloc::string::ToString;
+
+use browser_rs::js::engine::Engine;
+use browser_rs::parser::dom::Scripting;
+use common::run_with;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in js_location.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn properties() {
+    let mut engine = Engine::with_url("http://example.com:8080/dir/a.html?q=1#top");
+    assert_eq!(
+        run_with(&mut engine, "location.href"),
+        "\"http://example.com:8080/dir/a.html?q=1#top\""
+    );
+    assert_eq!(run_with(&mut engine, "location.protocol"), "\"http:\"");
+    assert_eq!(
+        run_with(&mut engine, "location.host"),
+        "\"example.com:8080\""
+    );
+    assert_eq!(
+        run_with(&mut engine, "location.pathname"),
+        "\"/dir/a.html\""
+    );
+    assert_eq!(run_with(&mut engine, "location.search"), "\"?q=1\"");
+    assert_eq!(run_with(&mut engine, "location.hash"), "\"#top\"");
+    assert_eq!(
+        run_with(&mut engine, "location + ''"),
+        "\"[object Location]\""
+    );
+
+    let mut engine = Engine::with_url("http://example.com/");
+    assert_eq!(run_with(&mut engine, "location.host"), "\"example.com\"");
+    assert_eq!(run_with(&mut engine, "location.search"), "\"\"");
+    assert_eq!(run_with(&mut engine, "location.hash"), "\"\"");
+
+    // A document without a URL has no location.
+    assert_eq!(
+        run_with(&mut Engine::new(), "location"),
+        "ReferenceError: location is not defined at 1:1"
+    );
+}
+
+#[test_case]
+fn navigation() {
+    let mut engine = Engine::with_url("http://example.com/dir/a.html");
+    assert!(engine.take_navigation().is_none());
+    run_with(&mut engine, "location.href = 'b.html'");
+    assert_eq!(
+        engine.take_navigation().unwrap().to_string(),
+        "http://example.com/dir/b.html"
+    );
+    assert!(engine.take_navigation().is_none());
+
+    // The last one wins, and the location doesn't change until the browser navigates.
+    run_with(
+        &mut engine,
+        "location.href = '/c.html'; location.href = '#d'",
+    );
+    assert_eq!(
+        run_with(&mut engine, "location.href"),
+        "\"http://example.com/dir/a.html\""
+    );
+    assert_eq!(
+        engine.take_navigation().unwrap().to_string(),
+        "http://example.com/dir/a.html#d"
+    );
+}
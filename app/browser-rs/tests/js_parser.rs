@@ -0,0 +1,355 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use browser_rs::js::ast::*;
+use browser_rs::js::parser::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in js_parser.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// Returns `expression` in parentheses, e.g. "(+ a (* b c))", to check how it's grouped.
+fn sexp(expression: &Expression) -> String {
+    let list = |items: Vec<String>| format!("({})", items.join(" "));
+    match &expression.kind {
+        ExpressionKind::Number(n) => format!("{}", n),
+        ExpressionKind::String(s) => format!("{:?}", s),
+        ExpressionKind::Boolean(b) => format!("{}", b),
+        ExpressionKind::Null => String::from("null"),
+        ExpressionKind::Identifier(name) => name.clone(),
+        ExpressionKind::This => String::from("this"),
+        ExpressionKind::Array(elements) => {
+            let mut items = Vec::from([String::from("array")]);
+            items.extend(elements.iter().map(sexp));
+            list(items)
+        }
+        ExpressionKind::Object(properties) => {
+            let mut items = Vec::from([String::from("object")]);
+            items.extend(properties.iter().map(|(k, v)| format!("{}:{}", k, sexp(v))));
+            list(items)
+        }
+        ExpressionKind::Function(f) => format!(
+            "(function {} ({}) {})",
+            f.name.clone().unwrap_or_default(),
+            f.params.join(" "),
+            f.body.len()
+        ),
+        ExpressionKind::Unary { operator, argument } => {
+            list(Vec::from([String::from(*operator), sexp(argument)]))
+        }
+        ExpressionKind::Update {
+            operator,
+            prefix,
+            argument,
+        } => {
+            let operator = if *prefix {
+                format!("{}_", operator)
+            } else {
+                format!("_{}", operator)
+            };
+            list(Vec::from([operator, sexp(argument)]))
+        }
+        ExpressionKind::Binary {
+            operator,
+            left,
+            right,
+        }
+        | ExpressionKind::Logical {
+            operator,
+            left,
+            right,
+        } => list(Vec::from([
+            String::from(*operator),
+            sexp(left),
+            sexp(right),
+        ])),
+        ExpressionKind::Assignment {
+            operator,
+            target,
+            value,
+        } => list(Vec::from([
+            String::from(*operator),
+            sexp(target),
+            sexp(value),
+        ])),
+        ExpressionKind::Conditional {
+            test,
+            consequent,
+            alternate,
+        } => list(Vec::from([
+            String::from("?"),
+            sexp(test),
+            sexp(consequent),
+            sexp(alternate),
+        ])),
+        ExpressionKind::Call { callee, arguments } => {
+            let mut items = Vec::from([String::from("call"), sexp(callee)]);
+            items.extend(arguments.iter().map(sexp));
+            list(items)
+        }
+        ExpressionKind::New { callee, arguments } => {
+            let mut items = Vec::from([String::from("new"), sexp(callee)]);
+            items.extend(arguments.iter().map(sexp));
+            list(items)
+        }
+        ExpressionKind::Member { object, name } => format!("{}.{}", sexp(object), name),
+        ExpressionKind::Index { object, index } => format!("{}[{}]", sexp(object), sexp(index)),
+        ExpressionKind::Sequence(expressions) => {
+            let mut items = Vec::from([String::from(",")]);
+            items.extend(expressions.iter().map(sexp));
+            list(items)
+        }
+    }
+}
+
+/// Parses `source` which is an expression statement and returns it by `sexp`.
+fn expression(source: &str) -> String {
+    let program = parse(source).expect("should be parsed");
+    assert_eq!(program.body.len(), 1);
+    match &program.body[0].kind {
+        StatementKind::Expression(expression) => sexp(expression),
+        other => panic!("expected an expression but got {:?}", other),
+    }
+}
+
+fn error(source: &str) -> String {
+    match parse(source) {
+        Ok(program) => panic!("expected an error but got {:?}", program),
+        Err(e) => format!("{}", e),
+    }
+}
+
+#[test_case]
+fn literals() {
+    assert_eq!(expression("1"), "1");
+    assert_eq!(expression("'a'"), "\"a\"");
+    assert_eq!(expression("true"), "true");
+    assert_eq!(expression("null"), "null");
+    assert_eq!(expression("this"), "this");
+    assert_eq!(expression("[1, 'a', ]"), "(array 1 \"a\")");
+    assert_eq!(
+        expression("({a: 1, 'b c': 2, 3: x, d, if: 4})"),
+        "(object a:1 b c:2 3:x d:d if:4)"
+    );
+}
+
+#[test_case]
+fn precedence() {
+    assert_eq!(expression("1 + 2 * 3"), "(+ 1 (* 2 3))");
+    assert_eq!(expression("(1 + 2) * 3"), "(* (+ 1 2) 3)");
+    assert_eq!(expression("1 - 2 - 3"), "(- (- 1 2) 3)");
+    assert_eq!(expression("2 ** 3 ** 2"), "(** 2 (** 3 2))");
+    assert_eq!(
+        expression("a || b && c == d < e + f"),
+        "(|| a (&& b (== c (< d (+ e f)))))"
+    );
+    assert_eq!(expression("a | b ^ c & d"), "(| a (^ b (& c d)))");
+    assert_eq!(expression("a << 1 + 2"), "(<< a (+ 1 2))");
+    assert_eq!(expression("a in b instanceof c"), "(instanceof (in a b) c)");
+    assert_eq!(expression("!a + -b"), "(+ (! a) (- b))");
+    assert_eq!(
+        expression("typeof a === 'string'"),
+        "(=== (typeof a) \"string\")"
+    );
+    assert_eq!(expression("a ? b : c ? d : e"), "(? a b (? c d e))");
+    assert_eq!(expression("a = b = c + 1"), "(= a (= b (+ c 1)))");
+    assert_eq!(expression("a += b ?? c"), "(+= a (?? b c))");
+    assert_eq!(expression("i++, --j"), "(, (_++ i) (--_ j))");
+}
+
+#[test_case]
+fn calls_and_members() {
+    assert_eq!(expression("f()"), "(call f)");
+    assert_eq!(expression("a.b.c(1, 2)"), "(call a.b.c 1 2)");
+    assert_eq!(expression("a[i + 1].b"), "a[(+ i 1)].b");
+    assert_eq!(expression("f(x)(y)"), "(call (call f x) y)");
+    assert_eq!(expression("a.default"), "a.default");
+    assert_eq!(expression("new Foo"), "(new Foo)");
+    assert_eq!(expression("new a.Foo(1).bar()"), "(call (new a.Foo 1).bar)");
+    assert_eq!(expression("-a.b()"), "(- (call a.b))");
+    assert_eq!(
+        expression("f(function (x) { return x; })"),
+        "(call f (function  (x) 1))"
+    );
+}
+
+#[test_case]
+fn declarations() {
+    let program = parse("var a = 1, b; let c = a; const d = 2;").expect("should be parsed");
+    assert_eq!(program.body.len(), 3);
+    match &program.body[0].kind {
+        StatementKind::Variable { kind, declarations } => {
+            assert_eq!(*kind, VariableKind::Var);
+            assert_eq!(declarations.len(), 2);
+            assert_eq!(declarations[0].name, "a");
+            assert_eq!(
+                declarations[0].init.as_ref().map(sexp),
+                Some(String::from("1"))
+            );
+            assert_eq!(declarations[1].name, "b");
+            assert_eq!(declarations[1].init, None);
+        }
+        other => panic!("expected a declaration but got {:?}", other),
+    }
+    match &program.body[1].kind {
+        StatementKind::Variable { kind, .. } => assert_eq!(*kind, VariableKind::Let),
+        other => panic!("expected a declaration but got {:?}", other),
+    }
+    match &program.body[2].kind {
+        StatementKind::Variable { kind, .. } => assert_eq!(*kind, VariableKind::Const),
+        other => panic!("expected a declaration but got {:?}", other),
+    }
+}
+
+#[test_case]
+fn functions() {
+    let program = parse("function add(a, b) {\n  return a + b;\n}").expect("should be parsed");
+    match &program.body[0].kind {
+        StatementKind::Function(f) => {
+            assert_eq!(f.name.as_deref(), Some("add"));
+            assert_eq!(f.params, ["a", "b"]);
+            assert_eq!(f.body.len(), 1);
+            match &f.body[0].kind {
+                StatementKind::Return(Some(e)) => assert_eq!(sexp(e), "(+ a b)"),
+                other => panic!("expected a return but got {:?}", other),
+            }
+            assert_eq!(format!("{}", f.body[0].pos), "2:3");
+        }
+        other => panic!("expected a function but got {:?}", other),
+    }
+}
+
+#[test_case]
+fn control_flow() {
+    let program = parse(
+        "if (a) b(); else { c(); }\n\
+         while (i < 3) i++;\n\
+         for (let i = 0; i < 3; i++) { if (i) break; else continue; }\n\
+         for (;;) {}",
+    )
+    .expect("should be parsed");
+    assert_eq!(program.body.len(), 4);
+    match &program.body[0].kind {
+        StatementKind::If {
+            test,
+            consequent,
+            alternate,
+        } => {
+            assert_eq!(sexp(test), "a");
+            assert!(matches!(consequent.kind, StatementKind::Expression(_)));
+            assert!(matches!(
+                alternate.as_ref().map(|s| &s.kind),
+                Some(StatementKind::Block(_))
+            ));
+        }
+        other => panic!("expected an if but got {:?}", other),
+    }
+    match &program.body[1].kind {
+        StatementKind::While { test, .. } => assert_eq!(sexp(test), "(< i 3)"),
+        other => panic!("expected a while but got {:?}", other),
+    }
+    match &program.body[2].kind {
+        StatementKind::For {
+            init, test, update, ..
+        } => {
+            assert!(matches!(
+                init.as_ref().map(|s| &s.kind),
+                Some(StatementKind::Variable { .. })
+            ));
+            assert_eq!(test.as_ref().map(sexp), Some(String::from("(< i 3)")));
+            assert_eq!(update.as_ref().map(sexp), Some(String::from("(_++ i)")));
+        }
+        other => panic!("expected a for but got {:?}", other),
+    }
+    match &program.body[3].kind {
+        StatementKind::For {
+            init: None,
+            test: None,
+            update: None,
+            ..
+        } => {}
+        other => panic!("expected a for but got {:?}", other),
+    }
+}
+
+#[test_case]
+fn automatic_semicolons() {
+    let program = parse("a = 1\nb = 2\n{ c() }").expect("should be parsed");
+    assert_eq!(program.body.len(), 3);
+
+    let program = parse("a\n++b").expect("should be parsed");
+    assert_eq!(program.body.len(), 2);
+
+    let program = parse("function f() { return\n1 }").expect("should be parsed");
+    match &program.body[0].kind {
+        StatementKind::Function(f) => {
+            assert!(matches!(f.body[0].kind, StatementKind::Return(None)))
+        }
+        other => panic!("expected a function but got {:?}", other),
+    }
+
+    assert_eq!(
+        error("a b"),
+        "SyntaxError: unexpected identifier 'b' at 1:3"
+    );
+}
+
+#[test_case]
+fn errors() {
+    assert_eq!(error("f(1,"), "SyntaxError: unexpected end of input at 1:5");
+    assert_eq!(error("a +\n)"), "SyntaxError: unexpected token ')' at 2:1");
+    assert_eq!(
+        error("1 = 2"),
+        "SyntaxError: invalid assignment target at 1:1"
+    );
+    assert_eq!(
+        error("const a;"),
+        "SyntaxError: missing initializer in const declaration at 1:7"
+    );
+    assert_eq!(
+        error("var s = 'abc"),
+        "SyntaxError: unterminated string literal at 1:9"
+    );
+    assert_eq!(
+        error("if (a {}"),
+        "SyntaxError: unexpected token '{' at 1:7"
+    );
+}
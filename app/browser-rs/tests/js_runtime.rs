@@ -0,0 +1,221 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+mod common;
+
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+
+use browser_rs::js::runtime::*;
+use browser_rs::js::value::*;
+use common::run;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in js_runtime.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn arithmetic() {
+    assert_eq!(run("1 + 2 * 3"), "7");
+    assert_eq!(run("(1 + 2) * 3"), "9");
+    assert_eq!(run("7 / 2"), "3.5");
+    assert_eq!(run("7 % 3"), "1");
+    assert_eq!(run("2 ** 10"), "1024");
+    assert_eq!(run("1 / 0"), "Infinity");
+    assert_eq!(run("0 / 0"), "NaN");
+    assert_eq!(run("0.1 + 0.2"), "0.30000000000000004");
+    assert_eq!(run("1e21"), "1e+21");
+    assert_eq!(run("-7 >> 1"), "-4");
+    assert_eq!(run("-1 >>> 28"), "15");
+    assert_eq!(run("5 & 3 | 8"), "9");
+}
+
+#[test_case]
+fn strings_and_conversions() {
+    assert_eq!(run("'a' + 'b'"), "\"ab\"");
+    assert_eq!(run("'n = ' + 1"), "\"n = 1\"");
+    assert_eq!(run("'3' * '4'"), "12");
+    assert_eq!(run("'abc'.length"), "3");
+    assert_eq!(run("'abc'[1]"), "\"b\"");
+    assert_eq!(run("[1, 2] + ''"), "\"1,2\"");
+    assert_eq!(run("({}) + ''"), "\"[object Object]\"");
+    assert_eq!(run("+'0x10'"), "16");
+    assert_eq!(run("+'abc'"), "NaN");
+    assert_eq!(
+        run("typeof 1 + typeof 'a' + typeof null + typeof undefined"),
+        "\"numberstringobjectundefined\""
+    );
+    assert_eq!(run("typeof function () {}"), "\"function\"");
+    assert_eq!(run("typeof notDeclared"), "\"undefined\"");
+}
+
+#[test_case]
+fn comparisons() {
+    assert_eq!(run("1 < 2"), "true");
+    assert_eq!(run("'b' > 'a'"), "true");
+    assert_eq!(run("'10' < '9'"), "true");
+    assert_eq!(run("'10' < 9"), "false");
+    assert_eq!(run("1 == '1'"), "true");
+    assert_eq!(run("1 === '1'"), "false");
+    assert_eq!(run("null == undefined"), "true");
+    assert_eq!(run("null === undefined"), "false");
+    assert_eq!(run("NaN == NaN"), "false");
+    assert_eq!(run("var o = {}; o === o"), "true");
+    assert_eq!(run("({}) === ({})"), "false");
+    assert_eq!(run("0 || null || 'x'"), "\"x\"");
+    assert_eq!(run("1 && 0"), "0");
+    assert_eq!(run("null ?? 0"), "0");
+    assert_eq!(run("!''"), "true");
+}
+
+#[test_case]
+fn variables() {
+    assert_eq!(run("var a = 1; a += 2; a"), "3");
+    assert_eq!(run("let a = 1; { let a = 2; } a"), "1");
+    assert_eq!(run("var a = 1; { var a = 2; } a"), "2");
+    assert_eq!(run("a = 5; a"), "5");
+    assert_eq!(run("b"), "ReferenceError: b is not defined at 1:1");
+    assert_eq!(run("x; var x = 1"), "undefined");
+    assert_eq!(
+        run("const c = 1;\nc = 2"),
+        "TypeError: assignment to constant variable at 2:1"
+    );
+    assert_eq!(run("var i = 0; i++ + ++i"), "2");
+}
+
+#[test_case]
+fn control_flow() {
+    assert_eq!(
+        run("var s = 0; for (var i = 1; i <= 10; i++) s += i; s"),
+        "55"
+    );
+    assert_eq!(
+        run("var s = '';\n\
+             for (let i = 0; i < 5; i++) { if (i == 1) continue; if (i == 3) break; s += i; }\n\
+             s"),
+        "\"02\""
+    );
+    assert_eq!(run("var n = 0; while (n < 3) n++; n"), "3");
+    assert_eq!(run("if (0) 'a'; else 'b'"), "\"b\"");
+    assert_eq!(run("true ? 1 : 2"), "1");
+}
+
+#[test_case]
+fn functions_and_closures() {
+    assert_eq!(run("function add(a, b) { return a + b; } add(1, 2)"), "3");
+    assert_eq!(run("twice(4); function twice(x) { return x * 2; }"), "8");
+    assert_eq!(run("function f(a, b) { return b; } f(1)"), "undefined");
+    assert_eq!(
+        run(
+            "function counter() { var n = 0; return function () { return ++n; }; }\n\
+             var c = counter(); c(); c(); c()"
+        ),
+        "3"
+    );
+    assert_eq!(
+        run("var fact = function f(n) { return n <= 1 ? 1 : n * f(n - 1); }; fact(5)"),
+        "120"
+    );
+    assert_eq!(
+        run("function loop() { return loop(); } loop()"),
+        "RangeError: maximum call stack size exceeded at 1:26"
+    );
+    assert_eq!(
+        run("var x = 1;\nx()"),
+        "TypeError: x is not a function at 2:1"
+    );
+}
+
+#[test_case]
+fn objects_and_arrays() {
+    assert_eq!(run("var o = {a: 1, b: {c: 2}}; o.b.c + o['a']"), "3");
+    assert_eq!(run("var o = {}; o.x = 1; o['y'] = 2; o.x + o.y"), "3");
+    assert_eq!(run("var o = {a: 1}; delete o.a; o.a"), "undefined");
+    assert_eq!(run("'a' in {a: 1}"), "true");
+    assert_eq!(run("var a = [1, 2, 3]; a[1] + a.length"), "5");
+    assert_eq!(run("var a = []; a[2] = 'x'; a.length"), "3");
+    assert_eq!(run("var a = [1, 2, 3]; a.length = 1; a + ''"), "\"1\"");
+    assert_eq!(
+        run("var o = {n: 2, twice: function () { return this.n * 2; }}; o.twice()"),
+        "4"
+    );
+    assert_eq!(
+        run("function Point(x) { this.x = x; }\n\
+             Point.prototype.get = function () { return this.x; };\n\
+             var p = new Point(7); p.get() + (p instanceof Point)"),
+        "8"
+    );
+    assert_eq!(
+        run("var o;\no.a"),
+        "TypeError: cannot read properties of undefined (reading 'a') at 2:1"
+    );
+}
+
+#[test_case]
+fn native_functions() {
+    let mut runtime = Runtime::new();
+    let add = ObjectRef::native_function(
+        "add",
+        Rc::new(|_, _, args| Ok(Value::Number(args.iter().map(|a| a.to_number()).sum()))),
+    );
+    runtime.define_global("add", Value::Object(add));
+    let fail = ObjectRef::native_function(
+        "fail",
+        Rc::new(|_, _, _| Err(Exception::type_error("failed"))),
+    );
+    runtime.define_global("fail", Value::Object(fail));
+
+    assert_eq!(runtime.run("add(1, 2, 3)"), Ok(Value::Number(6.0)));
+    assert!(runtime
+        .run("var f = function (x) { return x + 1; }")
+        .is_ok());
+    let f = runtime.global("f").expect("f should be declared");
+    assert_eq!(
+        runtime.call(&f, Value::Undefined, alloc::vec![Value::Number(1.0)]),
+        Ok(Value::Number(2.0))
+    );
+    assert_eq!(
+        runtime.run("1;\n  fail()").map_err(|e| format!("{}", e)),
+        Err(String::from("TypeError: failed at 2:3"))
+    );
+}
+
+#[test_case]
+fn syntax_errors() {
+    assert_eq!(run("1 +"), "SyntaxError: unexpected end of input at 1:4");
+}
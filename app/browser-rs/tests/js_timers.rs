@@ -0,0 +1,189 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::String;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use browser_rs::js::engine::Engine;
+use browser_rs::page::Page;
+use browser_rs::parser::dom::{Document, Scripting};
+use browser_rs::parser::parse;
+use browser_rs::style::computed::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in js_timers.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+const VIEWPORT: Viewport = Viewport {
+    width: 200.0,
+    height: 100.0,
+    color_scheme: ColorScheme::Light,
+};
+
+/// The time of the fake clock in milliseconds. Each test starts with setting it since the tests
+/// share it.
+static NOW: AtomicU64 = AtomicU64::new(0);
+
+fn now() -> u64 {
+    NOW.load(Ordering::SeqCst)
+}
+
+/// Returns an engine whose clock is `NOW` at 0, and a document with a log.
+fn engine() -> (Engine, Document) {
+    NOW.store(0, Ordering::SeqCst);
+    let mut engine = Engine::new();
+    engine.set_clock(now);
+    let mut document = parse(String::from("<p id=log></p>"));
+    engine
+        .run(
+            &mut document,
+            "var log = document.getElementById('log');\n\
+             function append(s) { log.textContent += s; }",
+        )
+        .expect("the script should run");
+    (engine, document)
+}
+
+/// Moves the clock to `time` and runs the timers which are due, and returns the log.
+fn run_at(engine: &mut Engine, document: &mut Document, time: u64) -> String {
+    NOW.store(time, Ordering::SeqCst);
+    engine.run_due_tasks(document);
+    let log = document
+        .descendants(document.root())
+        .find(|n| document.node(*n).element().map(|e| e.tag_name()) == Some("p"))
+        .expect("there should be the log");
+    document.text_content(log)
+}
+
+#[test_case]
+fn timeouts() {
+    let (mut engine, mut document) = engine();
+    let ids = engine
+        .run(
+            &mut document,
+            "[setTimeout(append, 20, 'b'), setTimeout(append, 10, 'a'), \
+             setTimeout(function (x, y) { append(x + y); }, 20, 'c', 'd')]",
+        )
+        .expect("the script should run");
+    assert_eq!(format!("{}", ids), "1,2,3");
+    assert!(!engine.has_due_tasks());
+    assert_eq!(engine.until_next_task(), Some(10));
+    assert_eq!(run_at(&mut engine, &mut document, 9), "");
+    assert_eq!(engine.until_next_task(), Some(1));
+    assert_eq!(run_at(&mut engine, &mut document, 10), "a");
+    assert!(!engine.has_due_tasks());
+    assert_eq!(engine.until_next_task(), Some(10));
+    NOW.store(25, Ordering::SeqCst);
+    // A late timer is due now.
+    assert_eq!(engine.until_next_task(), Some(0));
+    assert_eq!(run_at(&mut engine, &mut document, 30), "abcd");
+    assert_eq!(engine.until_next_task(), None);
+    assert_eq!(run_at(&mut engine, &mut document, 40), "abcd");
+}
+
+#[test_case]
+fn clearing_timers() {
+    let (mut engine, mut document) = engine();
+    engine
+        .run(
+            &mut document,
+            "var a = setTimeout(append, 10, 'a');\n\
+             setTimeout(function () { append('b'); clearTimeout(c); }, 10);\n\
+             var c = setTimeout(append, 10, 'c');\n\
+             clearTimeout(a);",
+        )
+        .expect("the script should run");
+    assert_eq!(run_at(&mut engine, &mut document, 10), "b");
+    assert!(!engine.has_due_tasks());
+}
+
+#[test_case]
+fn intervals() {
+    let (mut engine, mut document) = engine();
+    engine
+        .run(
+            &mut document,
+            "var count = 0;\n\
+             var id = setInterval(function () {\n\
+               append(++count);\n\
+               if (count == 3) clearInterval(id);\n\
+             }, 10);",
+        )
+        .expect("the script should run");
+    assert_eq!(run_at(&mut engine, &mut document, 10), "1");
+    // A late interval runs once.
+    assert_eq!(run_at(&mut engine, &mut document, 35), "12");
+    assert_eq!(run_at(&mut engine, &mut document, 44), "12");
+    assert_eq!(run_at(&mut engine, &mut document, 45), "123");
+    assert_eq!(run_at(&mut engine, &mut document, 100), "123");
+}
+
+#[test_case]
+fn timers_set_by_timers() {
+    let (mut engine, mut document) = engine();
+    engine
+        .run(
+            &mut document,
+            "setTimeout(function () { append('a'); setTimeout(\"append('b')\"); }, -5);",
+        )
+        .expect("the script should run");
+    // A timer set by a callback waits for the next run even without a delay.
+    assert_eq!(run_at(&mut engine, &mut document, 0), "a");
+    assert_eq!(run_at(&mut engine, &mut document, 0), "ab");
+}
+
+#[test_case]
+fn running_tasks_of_a_page() {
+    let mut page = Page::new(
+        parse(String::from(
+            "<p id=p>before</p>\
+             <script>setTimeout(function () {\n\
+               document.getElementById('p').textContent = 'after';\n\
+             });</script>",
+        )),
+        VIEWPORT,
+    );
+    assert!(page.run_due_tasks());
+    assert!(!page.run_due_tasks());
+    let document = page.document();
+    let p = document
+        .descendants(document.root())
+        .find(|n| document.node(*n).element().map(|e| e.tag_name()) == Some("p"))
+        .expect("there should be the p");
+    assert_eq!(document.text_content(p), "after");
+}
@@ -0,0 +1,208 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::keyboard::*;
+use browser_rs::page::Page;
+use browser_rs::parser::parse;
+use browser_rs::style::computed::*;
+use liumlib::input::{Button, Event, Key as InputKey, Modifiers};
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in keyboard.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// Returns the keys decoded from `input`.
+fn keys(input: &[u8]) -> Vec<Key> {
+    let mut reader = KeyReader::new();
+    input.iter().filter_map(|b| reader.feed(*b)).collect()
+}
+
+#[test_case]
+fn key_ids() {
+    assert_eq!(
+        keys(&[0x1c, 0x1d, 0x1e, 0x1f, b'q', b'\t', b'\n', b'\r']),
+        vec![
+            Key::Up,
+            Key::Down,
+            Key::Left,
+            Key::Right,
+            Key::Char(b'q'),
+            Key::Tab,
+            Key::Enter,
+            Key::Enter
+        ]
+    );
+}
+
+#[test_case]
+fn escape_sequences() {
+    assert_eq!(
+        keys(b"\x1b[A\x1b[B\x1b[C\x1b[D\x1bOA"),
+        vec![Key::Up, Key::Down, Key::Right, Key::Left, Key::Up]
+    );
+    assert_eq!(
+        keys(b"\x1b[5~\x1b[6~\x1b[H\x1b[F\x1b[1~\x1b[4~"),
+        vec![
+            Key::PageUp,
+            Key::PageDown,
+            Key::Home,
+            Key::End,
+            Key::Home,
+            Key::End
+        ]
+    );
+    // With modifiers.
+    assert_eq!(
+        keys(b"\x1b[1;5A\x1b[5;2~\x1b[Z"),
+        vec![Key::Up, Key::PageUp, Key::BackTab]
+    );
+    // Unknown sequences are ignored, and an escape without a sequence is dropped unless it's
+    // pressed twice.
+    assert_eq!(
+        keys(b"\x1b[2~a\x1bb\x1b\x1b"),
+        vec![Key::Char(b'a'), Key::Char(b'b'), Key::Escape]
+    );
+    assert_eq!(
+        keys(b"\x1b[3~\x00@\x7f\x16"),
+        vec![
+            Key::Delete,
+            Key::Escape,
+            Key::Backspace,
+            Key::Backspace,
+            Key::Delete
+        ]
+    );
+}
+
+fn down(key: InputKey, shift: bool, ctrl: bool) -> Event {
+    Event::KeyDown {
+        key,
+        modifiers: Modifiers { shift, ctrl },
+    }
+}
+
+/// Returns the input made by `events`.
+fn inputs(events: &[Event]) -> Vec<Input> {
+    let mut reader = InputReader::new();
+    events.iter().filter_map(|e| reader.translate(*e)).collect()
+}
+
+#[test_case]
+fn input_events() {
+    assert_eq!(
+        inputs(&[
+            down(InputKey::Char('q'), false, false),
+            // '@' is a character unlike on the standard input.
+            down(InputKey::Char('@'), true, false),
+            down(InputKey::Tab, true, false),
+            down(InputKey::Char('l'), false, true),
+            down(InputKey::PageDown, false, false),
+            down(InputKey::Shift, false, false),
+            Event::KeyUp {
+                key: InputKey::Char('q'),
+                modifiers: Modifiers::default(),
+            },
+        ]),
+        vec![
+            Input::Key(Key::Char(b'q')),
+            Input::Key(Key::Char(b'@')),
+            Input::Key(Key::BackTab),
+            Input::Key(Key::Char(0x0c)),
+            Input::Key(Key::PageDown),
+        ]
+    );
+    // The escape sequences from the serial port are decoded.
+    let sequence: Vec<Event> = "\x1b[5~a"
+        .chars()
+        .map(|c| down(InputKey::Char(c), false, false))
+        .collect();
+    assert_eq!(
+        inputs(&sequence),
+        vec![Input::Key(Key::PageUp), Input::Key(Key::Char(b'a'))]
+    );
+    // Only the primary button clicks.
+    assert_eq!(
+        inputs(&[
+            Event::PointerMove { x: 3, y: 4 },
+            Event::PointerDown {
+                button: Button::Right,
+                x: 3,
+                y: 4
+            },
+            Event::PointerDown {
+                button: Button::Left,
+                x: 3,
+                y: 4
+            },
+            Event::PointerUp {
+                button: Button::Left,
+                x: 3,
+                y: 4
+            },
+        ]),
+        vec![Input::Click { x: 3.0, y: 4.0 }]
+    );
+}
+
+#[test_case]
+fn scroll() {
+    let html = "<style>body { margin: 0 } div { height: 1000px }</style><div></div>";
+    let viewport = Viewport {
+        width: 100.0,
+        height: 200.0,
+        color_scheme: ColorScheme::Light,
+    };
+    let mut page = Page::new(parse(String::from(html)), viewport);
+    assert!(!scroll_with_key(&mut page, Key::Up));
+    assert!(scroll_with_key(&mut page, Key::Down));
+    assert_eq!(page.scroll_y(), 40.0);
+    assert!(scroll_with_key(&mut page, Key::PageDown));
+    assert_eq!(page.scroll_y(), 200.0);
+    assert!(scroll_with_key(&mut page, Key::End));
+    assert_eq!(page.scroll_y(), 800.0);
+    assert!(!scroll_with_key(&mut page, Key::PageDown));
+    assert!(scroll_with_key(&mut page, Key::PageUp));
+    assert_eq!(page.scroll_y(), 640.0);
+    assert!(!scroll_with_key(&mut page, Key::Left));
+    assert!(!scroll_with_key(&mut page, Key::Char(b' ')));
+    assert!(scroll_with_key(&mut page, Key::Home));
+    assert_eq!(page.scroll_y(), 0.0);
+}
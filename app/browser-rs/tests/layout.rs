@@ -0,0 +1,697 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::image::loader::load_images;
+use browser_rs::layout::box_tree::*;
+use browser_rs::layout::dump::dump_layout;
+use browser_rs::layout::geometry::*;
+use browser_rs::layout::inline::*;
+use browser_rs::layout::layout;
+use browser_rs::page::Page;
+use browser_rs::parser::dom::*;
+use browser_rs::parser::parse;
+use browser_rs::style::computed::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in layout.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+const VIEWPORT: Viewport = Viewport {
+    width: 600.0,
+    height: 400.0,
+    color_scheme: ColorScheme::Light,
+};
+
+/// Lays out `html` and returns the dimensions of the box of the element matching `selector`.
+fn dimensions_of(html: &str, selector: &str) -> Dimensions {
+    dimensions_in(&parse(String::from(html)), selector)
+}
+
+/// Lays out `document` and returns the dimensions of the box of the element matching `selector`.
+fn dimensions_in(document: &Document, selector: &str) -> Dimensions {
+    let styles = compute_styles(document, VIEWPORT);
+    let root = layout(document, &styles, VIEWPORT).unwrap();
+    let id = document.query_selector(document.root(), selector).unwrap();
+    *find_box(&root, id).unwrap().dimensions()
+}
+
+/// Parses `html` and loads a 10x20 image for the img elements whose src is "a.ppm".
+fn parse_with_images(html: &str) -> Document {
+    let mut document = parse(String::from(html));
+    load_images(&mut document, |src| {
+        if src != "a.ppm" {
+            return None;
+        }
+        let mut data = Vec::from(&b"P6 10 20 255\n"[..]);
+        data.resize(data.len() + 10 * 20 * 3, 0);
+        Some(data)
+    });
+    document
+}
+
+/// Lays out `html` and returns the line boxes of the element matching `selector`.
+fn lines_of(html: &str, selector: &str) -> Vec<LineBox> {
+    let document = parse(String::from(html));
+    let styles = compute_styles(&document, VIEWPORT);
+    let root = layout(&document, &styles, VIEWPORT).unwrap();
+    let id = document.query_selector(document.root(), selector).unwrap();
+    find_box(&root, id).unwrap().lines().clone()
+}
+
+/// Returns the text and the rect of each fragment in `line`.
+fn fragments(line: &LineBox) -> Vec<(&str, Rect)> {
+    line.fragments()
+        .iter()
+        .map(|f| (f.text(), f.rect()))
+        .collect()
+}
+
+fn find_box(layout_box: &LayoutBox, id: NodeId) -> Option<&LayoutBox> {
+    if layout_box.node() == Some(id) {
+        return Some(layout_box);
+    }
+    layout_box
+        .children()
+        .iter()
+        .chain(layout_box.out_of_flow())
+        .find_map(|c| find_box(c, id))
+}
+
+#[test_case]
+fn root_fills_viewport_width() {
+    let html = "<html><body></body></html>";
+    let d = dimensions_of(html, "html");
+    // The margins of an empty body collapse through it.
+    assert_eq!(d.content, Rect::new(0.0, 0.0, 600.0, 8.0));
+
+    // The user agent style sheet gives body an 8px margin.
+    let d = dimensions_of(html, "body");
+    assert_eq!(d.content, Rect::new(8.0, 8.0, 584.0, 0.0));
+    assert_eq!(d.margin_box(), Rect::new(0.0, 0.0, 600.0, 16.0));
+}
+
+#[test_case]
+fn blocks_stack_vertically() {
+    let html = "<style>body { margin: 0 } div { height: 10px; margin: 5px 0; padding: 1px; \
+                border-width: 2px; border-style: solid }</style><body><div id=a></div><div id=b></div></body>";
+    let a = dimensions_of(html, "#a");
+    assert_eq!(a.content, Rect::new(3.0, 8.0, 594.0, 10.0));
+    assert_eq!(a.border_box(), Rect::new(0.0, 5.0, 600.0, 16.0));
+
+    // The margins between the siblings collapse into one.
+    let b = dimensions_of(html, "#b");
+    assert_eq!(b.border_box().y, a.border_box().bottom() + 5.0);
+    assert_eq!(b.content.y, 29.0);
+
+    // The top margin of #a and the bottom margin of #b collapse with the margins of body.
+    let body = dimensions_of(html, "body");
+    assert_eq!(body.content, Rect::new(0.0, 5.0, 600.0, 37.0));
+}
+
+#[test_case]
+fn specified_width() {
+    let html = "<style>body { margin: 0 } div { width: 100px; margin-left: 10px; \
+                padding: 0 5px }</style><body><div></div></body>";
+    let d = dimensions_of(html, "div");
+    assert_eq!(d.content.x, 15.0);
+    assert_eq!(d.content.width, 100.0);
+    // The width is over-constrained, so margin-right takes the rest.
+    assert_eq!(d.margin.right, 480.0);
+}
+
+#[test_case]
+fn auto_margins() {
+    let html = "<style>body { margin: 0 } #a { width: 200px; margin: 0 auto } \
+                #b { width: 200px; margin-left: auto } \
+                #c { width: 700px; margin: 0 auto }</style>\
+                <body><div id=a></div><div id=b></div><div id=c></div></body>";
+    let a = dimensions_of(html, "#a");
+    assert_eq!((a.margin.left, a.margin.right), (200.0, 200.0));
+    assert_eq!(a.content.x, 200.0);
+
+    let b = dimensions_of(html, "#b");
+    assert_eq!((b.margin.left, b.margin.right), (400.0, 0.0));
+
+    // Auto margins are 0 if the box is wider than its containing block.
+    let c = dimensions_of(html, "#c");
+    assert_eq!((c.margin.left, c.margin.right), (0.0, -100.0));
+}
+
+#[test_case]
+fn auto_width_with_margins() {
+    let html = "<style>body { margin: 0 } div { margin: 0 auto; padding: 0 10% }</style>\
+                <body><div></div></body>";
+    let d = dimensions_of(html, "div");
+    assert_eq!((d.margin.left, d.margin.right), (0.0, 0.0));
+    assert_eq!(d.padding.left, 60.0);
+    assert_eq!(d.content.width, 480.0);
+}
+
+#[test_case]
+fn percentage_sizes() {
+    let html = "<style>html, body { height: 100% } body { margin: 0 } \
+                #a { width: 50%; height: 25% } #b { height: 50% } #c { height: 50% }</style>\
+                <body><div id=a></div><div id=b><div id=c></div></div></body>";
+    let a = dimensions_of(html, "#a");
+    assert_eq!((a.content.width, a.content.height), (300.0, 100.0));
+    assert_eq!(dimensions_of(html, "#b").content.height, 200.0);
+    assert_eq!(dimensions_of(html, "#c").content.height, 100.0);
+
+    // A percentage height is treated as auto if the containing block has no definite height.
+    let html = "<style>#a { height: 50% } #b { height: 20px }</style>\
+                <body><div id=a><div id=b></div></div></body>";
+    assert_eq!(dimensions_of(html, "#a").content.height, 20.0);
+}
+
+#[test_case]
+fn text_in_one_line() {
+    let html = "<style>body { margin: 0 }</style><body><p>  hello   <b>big</b>\n world </p></body>";
+    let lines = lines_of(html, "p");
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].rect(), Rect::new(0.0, 16.0, 600.0, 16.0));
+    assert_eq!(
+        fragments(&lines[0]),
+        [
+            ("hello", Rect::new(0.0, 16.0, 40.0, 16.0)),
+            ("big", Rect::new(48.0, 16.0, 24.0, 16.0)),
+            ("world", Rect::new(80.0, 16.0, 40.0, 16.0)),
+        ]
+    );
+    assert_eq!(dimensions_of(html, "p").content.height, 16.0);
+}
+
+#[test_case]
+fn lines_are_broken_at_spaces() {
+    let html = "<style>body { margin: 0 } div { width: 60px }</style>\
+                <body><div>aaa bbb ccc <span>dd</span>dddddddddd e</div></body>";
+    let lines = lines_of(html, "div");
+    let texts: Vec<Vec<&str>> = lines
+        .iter()
+        .map(|l| l.fragments().iter().map(|f| f.text()).collect())
+        .collect();
+    assert_eq!(
+        texts,
+        [
+            vec!["aaa bbb"],
+            vec!["ccc", "dd"],
+            // A word longer than the line overflows.
+            vec!["dddddddddd"],
+            vec!["e"],
+        ]
+    );
+    assert_eq!(lines[1].fragments()[1].rect().x, 32.0);
+    assert_eq!(lines[3].rect(), Rect::new(0.0, 48.0, 60.0, 16.0));
+    assert_eq!(dimensions_of(html, "div").content.height, 64.0);
+}
+
+#[test_case]
+fn fragments_are_aligned_at_the_bottom() {
+    let html = "<style>body { margin: 0 } span { font-size: 32px }</style>\
+                <body><div>a <span>b</span></div></body>";
+    let lines = lines_of(html, "div");
+    assert_eq!(lines[0].rect().height, 32.0);
+    assert_eq!(
+        fragments(&lines[0]),
+        [
+            ("a", Rect::new(0.0, 16.0, 8.0, 16.0)),
+            ("b", Rect::new(16.0, 0.0, 16.0, 32.0)),
+        ]
+    );
+}
+
+#[test_case]
+fn resize() {
+    let html = "<style>body { margin: 0 } @media (max-width: 300px) { p { margin: 0 } }</style>\
+                <body><p>aaaa bbbb cccc</p></body>";
+    let mut page = Page::new(parse(String::from(html)), VIEWPORT);
+    let height = |page: &Page| page.layout().unwrap().dimensions().content.height;
+    assert_eq!(height(&page), 48.0);
+
+    // The document is laid out again in the new width, and the media query matches now.
+    let narrow = Viewport {
+        width: 80.0,
+        height: 400.0,
+        color_scheme: ColorScheme::Light,
+    };
+    assert!(page.resize(narrow));
+    assert_eq!(page.viewport(), narrow);
+    assert_eq!(page.layout().unwrap().dimensions().content.width, 80.0);
+    assert_eq!(height(&page), 32.0);
+
+    assert!(!page.resize(narrow));
+    assert!(page.resize(VIEWPORT));
+    assert_eq!(height(&page), 48.0);
+}
+
+#[test_case]
+fn sibling_margins_collapse() {
+    let html = "<style>body { margin: 0; padding-top: 1px } \
+                div { height: 10px } #a { margin-bottom: 20px } #b { margin-top: 10px } \
+                #c { margin-top: -5px } #d { margin: -10px 0 30px } #e { margin-top: 15px }\
+                </style><body><div id=a></div><div id=b></div><div id=c></div>\
+                <div id=d></div><div id=e></div></body>";
+    let y = |selector: &str| dimensions_of(html, selector).border_box().y;
+    // The larger one of positive margins wins.
+    assert_eq!(y("#a"), 1.0);
+    assert_eq!(y("#b"), 31.0);
+    // A negative margin is added to the positive one.
+    assert_eq!(y("#c"), 36.0);
+    // The most negative one wins if both are negative.
+    assert_eq!(y("#d"), 36.0);
+    assert_eq!(y("#e"), 76.0);
+}
+
+#[test_case]
+fn parent_and_child_margins_collapse() {
+    let html = "<style>body { margin: 0 } #outer { margin: 10px 0 } \
+                #inner { margin: 20px 0 5px; height: 10px } #next { margin-top: 8px }\
+                </style><body><div id=outer><div id=inner></div></div><div id=next></div></body>";
+    let outer = dimensions_of(html, "#outer");
+    let inner = dimensions_of(html, "#inner");
+    // The top margin of the parent is the larger one of the two.
+    assert_eq!(outer.border_box().y, 20.0);
+    assert_eq!(inner.border_box().y, 20.0);
+    assert_eq!(outer.content.height, 10.0);
+    assert_eq!(dimensions_of(html, "#next").border_box().y, 40.0);
+
+    // Padding separates the margins.
+    let html = "<style>body { margin: 0 } #outer { margin-top: 10px; padding-top: 1px } \
+                #inner { margin-top: 20px }</style><body><div id=outer><div id=inner></div>\
+                </div></body>";
+    assert_eq!(dimensions_of(html, "#outer").border_box().y, 10.0);
+    assert_eq!(dimensions_of(html, "#inner").border_box().y, 31.0);
+}
+
+#[test_case]
+fn margins_collapse_through_empty_boxes() {
+    let html =
+        "<style>body { margin: 0; padding-top: 1px } #a { height: 10px; margin-bottom: 10px } \
+                #empty { margin: 30px 0 20px } #b { margin-top: 5px }</style>\
+                <body><div id=a></div><div id=empty></div><div id=b>x</div></body>";
+    assert_eq!(dimensions_of(html, "#b").border_box().y, 41.0);
+}
+
+/// Lays out `html` and returns the dump of the boxes.
+fn dump(html: &str) -> String {
+    let page = Page::new(parse(String::from(html)), VIEWPORT);
+    dump_layout(page.document(), page.layout().unwrap())
+}
+
+#[test_case]
+fn dump_boxes() {
+    let html = "<html><head><title>t</title></head><body><h1>Title</h1>\
+                <p style=\"padding: 4px; border-width: 1px 2px; border-style: solid; \
+                background-color: #ff000080\">a <a href=x>link</a> <b>b</b></p>\
+                <div>x<p>y</p></div></body></html>";
+    // The bottom margin of the last p collapses through div and body.
+    assert_eq!(
+        dump(html),
+        "Block <html> at (0,0) size 600x180.88
+  Block <body> at (8,21.44) size 584x143.44 margin=(8 8 8 8)
+    Block <h1> at (8,21.44) size 584x32 margin=(21.44 0 21.44 0)
+      Line at (8,21.44) size 584x32
+        Text \"Title\" at (8,21.44) size 80x32 font-size=32 font-weight=700
+    Block <p> at (8,74.88) size 584x26 margin=(16 0 16 0) border=(1 2 1 2) padding=(4 4 4 4) background=#ff000080
+      Line at (14,79.88) size 572x16
+        Text \"a\" at (14,79.88) size 8x16
+        Text \"link\" at (30,79.88) size 32x16 color=#0000ff underline
+        Text \"b\" at (70,79.88) size 8x16 font-weight=700
+    Block <div> at (8,116.88) size 584x48
+      AnonymousBlock at (8,116.88) size 584x16
+        Line at (8,116.88) size 584x16
+          Text \"x\" at (8,116.88) size 8x16
+      Block <p> at (8,148.88) size 584x16 margin=(16 0 16 0)
+        Line at (8,148.88) size 584x16
+          Text \"y\" at (8,148.88) size 8x16
+"
+    );
+}
+
+#[test_case]
+fn list_markers() {
+    let html = "<style>body { margin: 0 } ul, ol { margin: 0 }</style><body><ul><li>a</li>\
+                <li><p style=\"margin: 0\">b</p></li></ul><ol start=9><li>c</li>\
+                <li value=2><u>d</u></li><li style=\"list-style-type: upper-alpha\">e</li>\
+                <li style=\"list-style-type: none\">f</li></ol></body>";
+    assert_eq!(
+        dump(html),
+        "Block <html> at (0,0) size 600x96
+  Block <body> at (0,0) size 600x96
+    Block <ul> at (0,0) size 600x32 padding=(0 0 0 40)
+      Block <li> at (40,0) size 560x16
+        Marker \"\u{2022} \" at (24,0) size 16x16
+        Line at (40,0) size 560x16
+          Text \"a\" at (40,0) size 8x16
+      Block <li> at (40,16) size 560x16
+        Marker \"\u{2022} \" at (24,16) size 16x16
+        Block <p> at (40,16) size 560x16
+          Line at (40,16) size 560x16
+            Text \"b\" at (40,16) size 8x16
+    Block <ol> at (0,32) size 600x64 padding=(0 0 0 40)
+      Block <li> at (40,32) size 560x16
+        Marker \"9. \" at (16,32) size 24x16
+        Line at (40,32) size 560x16
+          Text \"c\" at (40,32) size 8x16
+      Block <li> at (40,48) size 560x16
+        Marker \"2. \" at (16,48) size 24x16
+        Line at (40,48) size 560x16
+          Text \"d\" at (40,48) size 8x16 underline
+      Block <li> at (40,64) size 560x16
+        Marker \"C. \" at (16,64) size 24x16
+        Line at (40,64) size 560x16
+          Text \"e\" at (40,64) size 8x16
+      Block <li> at (40,80) size 560x16
+        Line at (40,80) size 560x16
+          Text \"f\" at (40,80) size 8x16
+"
+    );
+}
+
+#[test_case]
+fn tables() {
+    let html = "<style>body { margin: 0 }</style><body><table><tr><th>Name</th><th>Value</th>\
+                </tr><tbody><tr><td>a</td><td>long text here</td></tr><tr><td>bb</td>\
+                <td style=\"height: 30px\">c</td></tr></tbody></table></body>";
+    assert_eq!(
+        dump(html),
+        "Block <html> at (0,0) size 600x76
+  Block <body> at (0,0) size 600x76
+    Table <table> at (0,0) size 154x76 margin=(0 446 0 0)
+      TableRow <tr> at (0,2) size 154x18
+        TableCell <th> at (2,2) size 34x18 padding=(1 1 1 1)
+          Line at (3,3) size 32x16
+            Text \"Name\" at (3,3) size 32x16 font-weight=700
+        TableCell <th> at (38,2) size 114x18 padding=(1 1 1 1)
+          Line at (39,3) size 112x16
+            Text \"Value\" at (39,3) size 40x16 font-weight=700
+      TableRowGroup <tbody> at (0,22) size 154x52
+        TableRow <tr> at (0,22) size 154x18
+          TableCell <td> at (2,22) size 34x18 padding=(1 1 1 1)
+            Line at (3,23) size 32x16
+              Text \"a\" at (3,23) size 8x16
+          TableCell <td> at (38,22) size 114x18 padding=(1 1 1 1)
+            Line at (39,23) size 112x16
+              Text \"long text here\" at (39,23) size 112x16
+        TableRow <tr> at (0,42) size 154x32
+          TableCell <td> at (2,42) size 34x32 padding=(1 1 1 1)
+            Line at (3,43) size 32x16
+              Text \"bb\" at (3,43) size 16x16
+          TableCell <td> at (38,42) size 114x32 padding=(1 1 1 1)
+            Line at (39,43) size 112x16
+              Text \"c\" at (39,43) size 8x16
+"
+    );
+}
+
+#[test_case]
+fn table_column_widths() {
+    let cells = |html: &str| -> Vec<f64> {
+        let page = Page::new(parse(String::from(html)), VIEWPORT);
+        page.document()
+            .query_selector_all(page.document().root(), "td")
+            .unwrap()
+            .iter()
+            .map(|id| {
+                find_box(page.layout().unwrap(), *id)
+                    .unwrap()
+                    .dimensions()
+                    .border_box()
+                    .width
+            })
+            .collect()
+    };
+
+    // The contents fit, so each column is as wide as its contents.
+    assert_eq!(
+        cells("<table><tr><td>aaa aaa</td><td>bbbb</td></tr></table>"),
+        [58.0, 34.0]
+    );
+    // A wider table distributes the rest in proportion to the widths of the contents.
+    assert_eq!(
+        cells("<table style=\"width: 190px\"><tr><td>aaa aaa</td><td>bbbb</td></tr></table>"),
+        [116.0, 68.0]
+    );
+    // A narrower table shrinks the columns which can be wrapped, down to their longest words.
+    assert_eq!(
+        cells("<table style=\"width: 70px\"><tr><td>aaa aaa</td><td>bbbb</td></tr></table>"),
+        [30.0, 34.0]
+    );
+    assert_eq!(
+        cells("<table style=\"width: 10px\"><tr><td>aaa aaa</td><td>bbbb</td></tr></table>"),
+        [26.0, 34.0]
+    );
+    // A cell with a width in px is as wide as it.
+    assert_eq!(
+        cells("<table><tr><td style=\"width: 50px\">a</td><td>b</td></tr></table>"),
+        [52.0, 10.0]
+    );
+}
+
+#[test_case]
+fn scrolling() {
+    // The document is laid out beyond the bottom of the viewport.
+    let html = "<style>body { margin: 0 } div { height: 300px }</style>\
+                <body><div></div><div></div><p style=\"height: 10px; margin: 0\"></p></body>";
+    let mut page = Page::new(parse(String::from(html)), VIEWPORT);
+    assert_eq!(page.document_height(), 610.0);
+    assert_eq!(page.max_scroll_y(), 210.0);
+    assert_eq!(page.scroll_y(), 0.0);
+
+    assert!(page.scroll_by(100.0));
+    assert_eq!(page.visible_rect(), Rect::new(0.0, 100.0, 600.0, 400.0));
+    // The scroll position is clamped.
+    assert!(page.scroll_by(500.0));
+    assert_eq!(page.scroll_y(), 210.0);
+    assert!(!page.scroll_by(1.0));
+    assert!(page.scroll_to(-5.0));
+    assert_eq!(page.scroll_y(), 0.0);
+
+    // A taller viewport has less to scroll.
+    page.scroll_to(210.0);
+    page.resize(Viewport {
+        width: 600.0,
+        height: 500.0,
+        color_scheme: ColorScheme::Light,
+    });
+    assert_eq!(page.scroll_y(), 110.0);
+}
+
+#[test_case]
+fn scrolling_to_fragment() {
+    let html = "<style>body { margin: 0 } div { height: 300px }</style>\
+                <body><div></div><div id=b></div><a name=c>c</a><div></div><div></div></body>";
+    let mut page = Page::new(parse(String::from(html)), VIEWPORT);
+    assert!(page.scroll_to_fragment("b"));
+    assert_eq!(page.scroll_y(), 300.0);
+    // A named anchor is indicated when no element has the id.
+    assert!(page.scroll_to_fragment("c"));
+    assert_eq!(page.scroll_y(), 600.0);
+    assert!(page.scroll_to_fragment("TOP"));
+    assert_eq!(page.scroll_y(), 0.0);
+    // An unknown fragment doesn't scroll.
+    assert!(!page.scroll_to_fragment("d"));
+    assert_eq!(page.scroll_y(), 0.0);
+}
+
+#[test_case]
+fn overflowing_contents_are_scrollable() {
+    // Contents overflowing a box with a fixed height are a part of the document.
+    let html = "<style>body { margin: 0 } #a { height: 10px } #b { height: 1000px }</style>\
+                <body><div id=a><div id=b></div></div></body>";
+    let page = Page::new(parse(String::from(html)), VIEWPORT);
+    assert_eq!(dimensions_of(html, "body").content.height, 10.0);
+    assert_eq!(page.document_height(), 1000.0);
+
+    // A short document can't be scrolled.
+    let page = Page::new(parse(String::from("<p>a</p>")), VIEWPORT);
+    assert_eq!(page.max_scroll_y(), 0.0);
+}
+
+#[test_case]
+fn relative_positioning() {
+    let html = "<style>body, p { margin: 0 }</style><body><p>a</p>\
+                <p id=r style=\"position: relative; left: 10px; bottom: 5px\">b</p><p id=c>c</p>\
+                </body>";
+    let r = dimensions_of(html, "#r");
+    assert_eq!(r.content, Rect::new(10.0, 11.0, 600.0, 16.0));
+    assert_eq!(
+        fragments(&lines_of(html, "#r")[0]),
+        vec![("b", Rect::new(10.0, 11.0, 8.0, 16.0))]
+    );
+    // The following boxes are placed as if it were not offset.
+    assert_eq!(dimensions_of(html, "#c").content.y, 32.0);
+
+    // Percentages of left refer to the width of the containing block, and percentages of top are
+    // ignored if its height is auto.
+    let html = "<body style=\"margin: 0\"><p id=r style=\"margin: 0; position: relative; \
+                left: 10%; top: 50%\">a</p></body>";
+    assert_eq!(dimensions_of(html, "#r").content.x, 60.0);
+    assert_eq!(dimensions_of(html, "#r").content.y, 0.0);
+}
+
+#[test_case]
+fn absolute_positioning() {
+    let html = "<style>body { margin: 0 } \
+                #c { position: relative; margin: 20px; padding: 10px; height: 100px } \
+                #a { position: absolute; top: 5px; right: 5px; width: 50px; height: 20px } \
+                #b { position: absolute; bottom: 0; left: 0 } \
+                #d { position: absolute; top: 10px; bottom: 10px; left: 0; right: 50% }</style>\
+                <body><div id=c><div id=a></div><span id=b>xy z</span>text</div>\
+                <div id=d></div></body>";
+    // The containing block is the padding box of the nearest positioned ancestor.
+    assert_eq!(
+        dimensions_of(html, "#a").content,
+        Rect::new(525.0, 25.0, 50.0, 20.0)
+    );
+    // The width shrinks to fit the contents, and an inline element is blockified.
+    assert_eq!(
+        dimensions_of(html, "#b").content,
+        Rect::new(20.0, 124.0, 32.0, 16.0)
+    );
+    // Without a positioned ancestor, the containing block is the initial containing block.
+    assert_eq!(
+        dimensions_of(html, "#d").content,
+        Rect::new(0.0, 10.0, 300.0, 380.0)
+    );
+    // Absolutely positioned boxes are out of flow.
+    assert_eq!(
+        fragments(&lines_of(html, "#c")[0]),
+        vec![("text", Rect::new(30.0, 30.0, 32.0, 16.0))]
+    );
+}
+
+#[test_case]
+fn laying_out_many_times() {
+    // Each layout drops its document, styles and boxes, so laying out the same page many times
+    // takes much more memory than the heap in total but runs in it.
+    let html = "<style>p { margin: 10px } #a { position: absolute; top: 5px }</style>\
+                <body><p>Some text which is broken into lines</p><div id=a>a</div></body>";
+    let first = dimensions_of(html, "#a");
+    for _ in 0..500 {
+        assert_eq!(dimensions_of(html, "#a"), first);
+    }
+}
+
+#[test_case]
+fn absolute_positioning_with_auto_insets() {
+    // The box is placed at the top left of the content box of the parent, and auto margins
+    // center it between the insets.
+    let html = "<style>body { margin: 0 } div { padding: 7px }</style><body><div><span>a\
+                <b id=s style=\"position: absolute\">b</b></span></div>\
+                <p id=m style=\"position: absolute; left: 0; right: 0; width: 100px; \
+                margin: 0 auto\">m</p></body>";
+    assert_eq!(
+        dimensions_of(html, "#s").content,
+        Rect::new(7.0, 7.0, 8.0, 16.0)
+    );
+    let m = dimensions_of(html, "#m");
+    assert_eq!(m.content.x, 250.0);
+    assert_eq!(m.margin.right, 250.0);
+    assert_eq!(
+        dump("<body style=\"margin: 0\"><i style=\"position: absolute; left: 1px\">i</i></body>"),
+        "Block <html> at (0,0) size 600x0
+  Block <body> at (0,0) size 600x0
+    Block <i> at (1,0) size 8x16 absolute
+      Line at (1,0) size 8x16
+        Text \"i\" at (1,0) size 8x16
+"
+    );
+}
+
+#[test_case]
+fn images_in_lines() {
+    let html = "<body style=\"margin: 0\"><p style=\"margin: 0\">a<img src=a.ppm>b</p>";
+    let document = parse_with_images(html);
+    assert_eq!(
+        dimensions_in(&document, "img").content,
+        Rect::new(8.0, 0.0, 10.0, 20.0)
+    );
+    // The line is as tall as the image, and the text is aligned with its bottom.
+    let styles = compute_styles(&document, VIEWPORT);
+    let root = layout(&document, &styles, VIEWPORT).unwrap();
+    let p = document.query_selector(document.root(), "p").unwrap();
+    let lines = find_box(&root, p).unwrap().lines().clone();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].rect(), Rect::new(0.0, 0.0, 600.0, 20.0));
+    assert_eq!(
+        fragments(&lines[0]),
+        vec![
+            ("a", Rect::new(0.0, 4.0, 8.0, 16.0)),
+            ("", Rect::new(8.0, 0.0, 10.0, 20.0)),
+            ("b", Rect::new(18.0, 4.0, 8.0, 16.0)),
+        ]
+    );
+}
+
+#[test_case]
+fn image_sizes() {
+    let size = |html: &str| dimensions_in(&parse_with_images(html), "img").content;
+    // The other dimension follows the ratio of the image.
+    let r = size("<img src=a.ppm width=30>");
+    assert_eq!((r.width, r.height), (30.0, 60.0));
+    let r = size("<img src=a.ppm style=\"height: 10px\">");
+    assert_eq!((r.width, r.height), (5.0, 10.0));
+    // CSS wins over the attributes.
+    let r = size("<style>img { height: 10px }</style><img src=a.ppm width=30 height=40>");
+    assert_eq!((r.width, r.height), (30.0, 10.0));
+    let r = size("<img src=a.ppm width=\"50%\">");
+    assert_eq!((r.width, r.height), (292.0, 584.0));
+    // An image which isn't available takes no space unless its size is specified.
+    let r = size("<img src=missing.ppm>");
+    assert_eq!((r.width, r.height), (0.0, 0.0));
+    let r = size("<img src=missing.ppm width=5 height=6>");
+    assert_eq!((r.width, r.height), (5.0, 6.0));
+}
+
+#[test_case]
+fn block_level_images() {
+    let html = "<style>body { margin: 0 } img { display: block; margin: 2px auto }</style>\
+                <body><img src=a.ppm><p style=\"margin: 0\">a</p>";
+    let document = parse_with_images(html);
+    let img = dimensions_in(&document, "img");
+    assert_eq!(img.content, Rect::new(295.0, 2.0, 10.0, 20.0));
+    assert_eq!(dimensions_in(&document, "p").content.y, 24.0);
+
+    // An absolutely positioned image keeps its size.
+    let html = "<img src=a.ppm style=\"position: absolute; right: 0; bottom: 0\">";
+    let img = dimensions_in(&parse_with_images(html), "img");
+    assert_eq!(img.content, Rect::new(590.0, 380.0, 10.0, 20.0));
+}
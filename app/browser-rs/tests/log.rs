@@ -0,0 +1,126 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use liumlib::fs::File;
+use liumlib::log::{Filter, Level, Output};
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in log.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn levels() {
+    assert_eq!(Level::parse("warn"), Some(Level::Warn));
+    assert_eq!(Level::parse("DEBUG"), Some(Level::Debug));
+    assert_eq!(Level::parse("verbose"), None);
+    assert!(Level::Error < Level::Trace);
+    assert_eq!(Level::Info.to_string(), "INFO");
+}
+
+#[test_case]
+fn filtering_by_level() {
+    let filter = Filter::new(Some(Level::Warn));
+    assert!(filter.enabled(Level::Error, "browser_rs::net"));
+    assert!(filter.enabled(Level::Warn, "browser_rs::net"));
+    assert!(!filter.enabled(Level::Info, "browser_rs::net"));
+
+    let off = Filter::new(None);
+    assert!(!off.enabled(Level::Error, "browser_rs::net"));
+    assert!(Filter::default().enabled(Level::Info, "browser_rs"));
+    assert!(!Filter::default().enabled(Level::Debug, "browser_rs"));
+}
+
+#[test_case]
+fn filtering_by_module() {
+    let filter = Filter::parse("warn, net=debug, browser_rs::net::http=off, parser").unwrap();
+    // The crate can be left out.
+    assert!(filter.enabled(Level::Debug, "browser_rs::net"));
+    assert!(filter.enabled(Level::Debug, "browser_rs::net::udp"));
+    assert!(!filter.enabled(Level::Trace, "browser_rs::net"));
+    // The nearest module decides.
+    assert!(!filter.enabled(Level::Error, "browser_rs::net::http"));
+    // A module without a level logs everything.
+    assert!(filter.enabled(Level::Trace, "browser_rs::parser::css"));
+    // Only the whole names of modules match.
+    assert!(!filter.enabled(Level::Info, "browser_rs::network"));
+    assert!(!filter.enabled(Level::Info, "browser_rs::browser"));
+}
+
+#[test_case]
+fn invalid_filters() {
+    assert_eq!(Filter::parse(""), Ok(Filter::default()));
+    assert_eq!(
+        Filter::parse("net=loud"),
+        Err(String::from("unknown level: loud"))
+    );
+    assert_eq!(
+        Filter::parse("=debug"),
+        Err(String::from("no module in =debug"))
+    );
+}
+
+#[test_case]
+fn formatting() {
+    assert_eq!(
+        log::format_line(Level::Warn, "browser_rs::net", format_args!("no response")),
+        "[WARN browser_rs::net] no response\n"
+    );
+}
+
+/// A file outside the tree, which is written from scratch.
+const PATH: &str = "/tmp/liumlib-log-test.txt";
+
+#[test_case]
+fn logging_to_a_file() {
+    drop(File::create(PATH).unwrap());
+    log::init(
+        Filter::parse("warn").unwrap(),
+        Output::File(File::append(PATH).unwrap()),
+    );
+    info!("hidden");
+    warn!("shown {}", 1);
+    error!("shown {}", 2);
+    // The other tests log to the console.
+    log::init(Filter::default(), Output::Console);
+
+    let logged = File::open(PATH).unwrap().read_to_end().unwrap();
+    assert_eq!(
+        String::from_utf8(logged).unwrap(),
+        "[WARN log] shown 1\n[ERROR log] shown 2\n"
+    );
+}
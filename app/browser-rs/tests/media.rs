@@ -0,0 +1,129 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use browser_rs::parser::css::media::*;
+use browser_rs::parser::css::tokenizer::*;
+use browser_rs::style::computed::{ColorScheme, Viewport};
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in media.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// Returns true if `query` matches a viewport of the size.
+fn matches(query: &str, width: f64, height: f64) -> bool {
+    matches_in(
+        query,
+        Viewport {
+            width,
+            height,
+            color_scheme: ColorScheme::Light,
+        },
+    )
+}
+
+fn matches_in(query: &str, viewport: Viewport) -> bool {
+    let tokens: Vec<CssToken> = CssTokenizer::new(String::from(query)).collect();
+    parse_media_query_list(&tokens).matches(viewport)
+}
+
+#[test_case]
+fn media_types() {
+    assert!(matches("", 100.0, 100.0));
+    assert!(matches("all", 100.0, 100.0));
+    assert!(matches("SCREEN", 100.0, 100.0));
+    assert!(matches("only screen", 100.0, 100.0));
+    assert!(!matches("print", 100.0, 100.0));
+    assert!(!matches("tv", 100.0, 100.0));
+    assert!(matches("not print", 100.0, 100.0));
+    assert!(!matches("not screen", 100.0, 100.0));
+    assert!(matches("print, screen", 100.0, 100.0));
+}
+
+#[test_case]
+fn width_and_height() {
+    assert!(matches("(max-width: 600px)", 600.0, 100.0));
+    assert!(!matches("(max-width: 600px)", 601.0, 100.0));
+    assert!(matches("(min-width: 600px)", 600.0, 100.0));
+    assert!(!matches("(min-width: 600px)", 599.0, 100.0));
+    assert!(matches("(width: 600px)", 600.0, 100.0));
+    assert!(matches("(min-height: 10em)", 0.0, 160.0));
+    assert!(!matches("(max-height: 5rem)", 0.0, 81.0));
+    assert!(matches("(width)", 1.0, 0.0));
+    assert!(!matches("(height)", 1.0, 0.0));
+}
+
+#[test_case]
+fn combinations() {
+    let query = "screen and (min-width: 100px) and (max-width: 200px)";
+    assert!(matches(query, 150.0, 0.0));
+    assert!(!matches(query, 250.0, 0.0));
+    assert!(matches(
+        "(min-width: 100px) and (max-height: 10px)",
+        150.0,
+        10.0
+    ));
+    assert!(matches("not screen and (max-width: 100px)", 150.0, 0.0));
+    assert!(!matches("not all and (max-width: 100px)", 50.0, 0.0));
+}
+
+#[test_case]
+fn malformed_queries() {
+    // A malformed query doesn't match, but the others in the list still do.
+    assert!(!matches("(max-width: 10%)", 0.0, 0.0));
+    assert!(!matches("(orientation: portrait)", 100.0, 100.0));
+    assert!(!matches("screen (max-width: 10px)", 0.0, 0.0));
+    assert!(!matches("and", 0.0, 0.0));
+    assert!(!matches("screen and", 0.0, 0.0));
+    assert!(!matches("(max-width: 10px", 0.0, 0.0));
+    assert!(matches("screen and, print, all", 0.0, 0.0));
+}
+
+#[test_case]
+fn prefers_color_scheme() {
+    let dark = Viewport {
+        color_scheme: ColorScheme::Dark,
+        ..Viewport::default()
+    };
+    assert!(matches_in("(prefers-color-scheme: dark)", dark));
+    assert!(!matches_in("(prefers-color-scheme: light)", dark));
+    assert!(matches("(prefers-color-scheme: LIGHT)", 0.0, 0.0));
+    assert!(!matches("(prefers-color-scheme: blue)", 0.0, 0.0));
+    assert!(!matches("(prefers-color-scheme: 1px)", 0.0, 0.0));
+}
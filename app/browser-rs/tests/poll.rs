@@ -0,0 +1,101 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use liumlib::net::*;
+use liumlib::poll::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in poll.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// Returns a listener on a port which the system chooses, and a connection to it.
+fn connection() -> (TcpListener, TcpStream) {
+    let listener = TcpListener::bind(SocketAddrV4::new([127, 0, 0, 1], 0)).unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    (listener, client)
+}
+
+fn fd(stream: &TcpStream, events: i16) -> PollFd {
+    PollFd::new(stream.file_descriptor().number(), events)
+}
+
+#[test_case]
+fn readiness() {
+    let (listener, client) = connection();
+    let (server, _) = listener.accept().unwrap();
+    let mut fds = [fd(&client, POLLIN | POLLOUT), fd(&server, POLLIN)];
+    // Only writing to the client doesn't block.
+    assert_eq!(poll(&mut fds, Some(0)), Some(1));
+    assert!(fds[0].is_writable());
+    assert!(!fds[0].is_readable());
+    assert!(!fds[1].is_readable());
+
+    client.write_all(b"ping").unwrap();
+    let mut fds = [fd(&client, POLLIN), fd(&server, POLLIN)];
+    assert_eq!(poll(&mut fds, None), Some(1));
+    assert!(!fds[0].is_readable());
+    assert!(fds[1].is_readable());
+
+    // The end of the stream is readable too.
+    drop(server);
+    let mut fds = [fd(&client, POLLIN)];
+    assert_eq!(poll(&mut fds, Some(1000)), Some(1));
+    assert!(fds[0].is_readable());
+}
+
+#[test_case]
+fn timeouts() {
+    let (listener, client) = connection();
+    let mut fds = [fd(&client, POLLIN)];
+    assert_eq!(poll(&mut fds, Some(20)), Some(0));
+    assert_eq!(fds[0].revents(), 0);
+    drop(listener);
+}
+
+#[test_case]
+fn ignored_and_closed_fds() {
+    let (_listener, client) = connection();
+    let mut fds = [
+        PollFd::new(-1, POLLIN),
+        fd(&client, POLLOUT),
+        PollFd::new(1000, POLLIN),
+    ];
+    assert_eq!(poll(&mut fds, Some(0)), Some(2));
+    assert_eq!(fds[0].revents(), 0);
+    assert!(fds[1].is_writable());
+    assert_eq!(fds[2].revents(), POLLNVAL);
+}
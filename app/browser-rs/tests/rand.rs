@@ -0,0 +1,86 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use core::time::Duration;
+use liumlib::rand::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in rand.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn seeded_generators() {
+    // The same seed makes the same numbers, even 0.
+    for seed in [0, 42] {
+        let mut a = Rng::with_seed(seed);
+        let mut b = Rng::with_seed(seed);
+        assert_eq!(a.next_u64(), b.next_u64());
+        assert_ne!(a.next_u64(), a.next_u64());
+    }
+    let mut rng = Rng::with_seed(1);
+    for _ in 0..100 {
+        let f = rng.next_f64();
+        assert!((0.0..1.0).contains(&f));
+        assert!(rng.below(6) < 6);
+    }
+    assert_eq!(rng.below(1), 0);
+    let mut buf = [0u8; 13];
+    rng.fill(&mut buf);
+    assert!(buf.iter().any(|&b| b != 0));
+}
+
+#[test_case]
+fn seeded_by_the_system() {
+    let mut buf = [0u8; 32];
+    assert_eq!(fill_bytes(&mut buf), Some(()));
+    assert!(buf.iter().any(|&b| b != 0));
+    assert_ne!(Rng::new().next_u64(), Rng::new().next_u64());
+}
+
+#[test_case]
+fn jittered_backoff() {
+    let mut rng = Rng::with_seed(7);
+    let base = Duration::from_millis(100);
+    let max = Duration::from_secs(1);
+    for _ in 0..100 {
+        assert!(rng.backoff(base, max, 1) <= base);
+        assert!(rng.backoff(base, max, 3) <= base * 4);
+        assert!(rng.backoff(base, max, 40) <= max);
+    }
+    // The delays are spread rather than the same.
+    assert_ne!(rng.backoff(base, max, 3), rng.backoff(base, max, 3));
+}
@@ -0,0 +1,103 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::layout::geometry::*;
+use browser_rs::paint::display_list::*;
+use browser_rs::paint::raster::*;
+use browser_rs::style::values::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in raster.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// Returns a 3x2 bitmap whose top-left pixel is red and the bottom-right one is blue on white.
+fn bitmap() -> Bitmap {
+    let list = vec![
+        DisplayItem::SolidRect {
+            rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            color: Color::rgb(255, 0, 0),
+        },
+        DisplayItem::SolidRect {
+            rect: Rect::new(12.0, 11.0, 1.0, 1.0),
+            color: Color::rgb(0, 0, 255),
+        },
+    ];
+    rasterize(&list, Rect::new(10.0, 10.0, 3.0, 2.0))
+}
+
+#[test_case]
+fn rasterize_visible_part() {
+    let bitmap = bitmap();
+    assert_eq!(bitmap.pixel(0, 0), Color::WHITE);
+    assert_eq!(bitmap.pixel(2, 1), Color::rgb(0, 0, 255));
+    assert_eq!(bitmap.pixel(1, 1), Color::WHITE);
+}
+
+#[test_case]
+fn ppm() {
+    let mut expected = Vec::from(&b"P6\n3 2\n255\n"[..]);
+    for pixel in 0..6 {
+        if pixel == 5 {
+            expected.extend_from_slice(&[0, 0, 255]);
+        } else {
+            expected.extend_from_slice(&[255, 255, 255]);
+        }
+    }
+    assert_eq!(bitmap().to_ppm(), expected);
+}
+
+#[test_case]
+fn bmp() {
+    let data = bitmap().to_bmp();
+    let u32_at = |i: usize| u32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+    // Each row of 9 bytes is padded to 12 bytes.
+    assert_eq!(data.len(), 54 + 24);
+    assert_eq!(&data[0..2], b"BM");
+    assert_eq!(u32_at(2), 78);
+    assert_eq!(u32_at(10), 54);
+    assert_eq!((u32_at(18), u32_at(22)), (3, 2));
+    assert_eq!(u32_at(34), 24);
+    // The bottom row comes first, and the pixels are in BGR order.
+    assert_eq!(
+        &data[54..66],
+        &[255, 255, 255, 255, 255, 255, 255, 0, 0, 0, 0, 0]
+    );
+    assert_eq!(&data[66..69], &[255, 255, 255]);
+}
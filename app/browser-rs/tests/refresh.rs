@@ -0,0 +1,165 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use browser_rs::http::HttpResponse;
+use browser_rs::parser::parse;
+use browser_rs::refresh::*;
+use browser_rs::url::ParsedUrl;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in refresh.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+fn refresh(delay: u64, url: Option<&str>) -> Option<Refresh> {
+    Some(Refresh {
+        delay,
+        url: url.map(String::from),
+    })
+}
+
+#[test_case]
+fn parsing() {
+    assert_eq!(Refresh::parse("5"), refresh(5, None));
+    assert_eq!(
+        Refresh::parse(" 0; url=next.html"),
+        refresh(0, Some("next.html"))
+    );
+    assert_eq!(Refresh::parse("3,URL = /a b "), refresh(3, Some("/a b")));
+    assert_eq!(Refresh::parse("1 next.html"), refresh(1, Some("next.html")));
+    assert_eq!(
+        Refresh::parse("2.5; url='quoted.html' ignored"),
+        refresh(2, Some("quoted.html"))
+    );
+    assert_eq!(Refresh::parse(".5;url=a"), refresh(0, Some("a")));
+    assert_eq!(Refresh::parse("0;"), refresh(0, None));
+    assert_eq!(Refresh::parse(""), None);
+    assert_eq!(Refresh::parse("soon"), None);
+    assert_eq!(Refresh::parse("5x"), None);
+}
+
+#[test_case]
+fn finding() {
+    let document = parse(String::from(
+        "<head><meta http-equiv=other content=1>\
+         <meta http-equiv=Refresh content=invalid>\
+         <meta http-equiv=refresh content='4;url=meta.html'></head>",
+    ));
+    assert_eq!(
+        Refresh::find(None, &document),
+        refresh(4, Some("meta.html"))
+    );
+
+    // The header is preferred to the meta elements.
+    let response = HttpResponse::new(String::from(
+        "HTTP/1.1 200 OK\nRefresh: 1; url=header.html\n\n<p>a</p>",
+    ));
+    assert_eq!(
+        Refresh::find(Some(&response), &document),
+        refresh(1, Some("header.html"))
+    );
+    assert_eq!(Refresh::find(None, &parse(String::from("<p>a</p>"))), None);
+}
+
+#[test_case]
+fn scheduling() {
+    let base = ParsedUrl::new(String::from("http://127.0.0.1:8888/dir/page.html"));
+    let refresh = Refresh::parse("0;url=next.html").unwrap();
+    let scheduled = ScheduledRefresh::new(&refresh, &base, 1).unwrap();
+    assert_eq!(
+        scheduled.url.to_string(),
+        "http://127.0.0.1:8888/dir/next.html"
+    );
+    assert_eq!(scheduled.hops, 1);
+    assert!(scheduled.is_due());
+    assert_eq!(scheduled.until_due(), Some(0));
+
+    // The page is loaded again if no URL is given.
+    let scheduled = ScheduledRefresh::new(&Refresh::parse("60").unwrap(), &base, 0).unwrap();
+    assert_eq!(scheduled.url.to_string(), base.to_string());
+    assert!(!scheduled.is_due());
+    assert!(matches!(scheduled.until_due(), Some(ms) if ms > 59_000 && ms <= 60_000));
+
+    // A delay too long to count never comes.
+    let refresh = Refresh::parse("99999999999999999999").unwrap();
+    let scheduled = ScheduledRefresh::new(&refresh, &base, 0).unwrap();
+    assert!(!scheduled.is_due());
+    assert_eq!(scheduled.until_due(), None);
+
+    // A refresh to an invalid URL isn't scheduled.
+    let refresh = Refresh::parse("0;url=http://127.0.0.1:99999/").unwrap();
+    assert!(ScheduledRefresh::new(&refresh, &base, 0).is_none());
+}
+
+#[test_case]
+fn redirects() {
+    let response = HttpResponse::new(String::from(
+        "HTTP/1.1 302 Found\nlocation: /moved.html\n\n",
+    ));
+    assert_eq!(response.redirect_location().as_deref(), Some("/moved.html"));
+    // Location isn't followed without a redirect status.
+    let response = HttpResponse::new(String::from(
+        "HTTP/1.1 201 Created\nLocation: /new.html\n\n",
+    ));
+    assert_eq!(response.redirect_location(), None);
+}
+
+#[test_case]
+fn redirect_urls() {
+    let url = ParsedUrl::new(String::from("http://127.0.0.1:8888/dir/a.html"));
+    let redirect = |location: &str| {
+        HttpResponse::new(format!("HTTP/1.1 302 Found\nLocation: {}\n\n", location))
+            .redirect_url(&url)
+            .map(|next| next.map(|next| next.to_string()))
+    };
+    assert_eq!(
+        redirect("b.html"),
+        Ok(Some(String::from("http://127.0.0.1:8888/dir/b.html")))
+    );
+    // A malformed location fails the request instead of the browser.
+    assert_eq!(
+        redirect("http://127.0.0.1:99999/"),
+        Err(String::from("invalid redirect to http://127.0.0.1:99999/"))
+    );
+    let response = HttpResponse::new(String::from("HTTP/1.1 200 OK\n\n"));
+    assert_eq!(
+        response.redirect_url(&url).map(|next| next.is_none()),
+        Ok(true)
+    );
+}
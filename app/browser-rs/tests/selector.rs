@@ -0,0 +1,263 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::parser::css::selector::*;
+use browser_rs::parser::dom::*;
+use browser_rs::parser::parse;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in selector.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+const HTML: &str = "<html><body><div id=main class='a b' lang=en-US data-x=\"Foo Bar\"><p class=x>1<span>2</span></p></div><p>3</p></body></html>";
+
+/// Returns the first element with `tag` in tree order.
+fn find(document: &Document, tag: &str) -> NodeId {
+    document
+        .descendants(document.root())
+        .find(|n| match document.node(*n).element() {
+            Some(e) => e.tag_name() == tag,
+            None => false,
+        })
+        .unwrap()
+}
+
+#[macro_export]
+macro_rules! run_test {
+    ($tag:literal, $selector:literal, $expected:expr) => {
+        let document = parse(String::from(HTML));
+        let element = find(&document, $tag);
+        assert_eq!(document.matches(element, $selector), $expected);
+    };
+}
+
+#[test_case]
+fn parse_compound() {
+    let list = parse_selector_list("DIV#main.a").unwrap();
+    assert_eq!(list.len(), 1);
+    assert_eq!(
+        list[0].compounds()[0].selectors(),
+        &vec![
+            SimpleSelector::Type(String::from("div")),
+            SimpleSelector::Id(String::from("main")),
+            SimpleSelector::Class(String::from("a")),
+        ]
+    );
+}
+
+#[test_case]
+fn parse_combinators() {
+    let list = parse_selector_list("div > p span, *").unwrap();
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[0].compounds().len(), 3);
+    assert_eq!(
+        list[0].combinators(),
+        &vec![Combinator::Child, Combinator::Descendant]
+    );
+    assert_eq!(
+        list[1].compounds()[0].selectors(),
+        &vec![SimpleSelector::Universal]
+    );
+}
+
+#[test_case]
+fn invalid() {
+    assert_eq!(parse_selector_list(""), None);
+    assert_eq!(parse_selector_list("div,"), None);
+    assert_eq!(parse_selector_list("div >"), None);
+    assert_eq!(parse_selector_list("#"), None);
+    assert_eq!(parse_selector_list("div!"), None);
+    run_test!("div", "p..x", None);
+}
+
+#[test_case]
+fn type_selector() {
+    run_test!("div", "div", Some(true));
+    run_test!("div", "p", Some(false));
+    run_test!("div", "*", Some(true));
+}
+
+#[test_case]
+fn id_and_class() {
+    run_test!("div", "#main", Some(true));
+    run_test!("div", ".a.b", Some(true));
+    run_test!("div", "div#main.b", Some(true));
+    run_test!("div", ".c", Some(false));
+    run_test!("div", "#other", Some(false));
+}
+
+#[test_case]
+fn descendant_combinator() {
+    run_test!("span", "div span", Some(true));
+    run_test!("span", "body .a span", Some(true));
+    run_test!("span", "html p.x span", Some(true));
+    run_test!("span", "span span", Some(false));
+}
+
+#[test_case]
+fn child_combinator() {
+    run_test!("span", "p > span", Some(true));
+    run_test!("span", "div > span", Some(false));
+    run_test!("span", "div > p > span", Some(true));
+    run_test!("span", "body > * span", Some(true));
+}
+
+#[test_case]
+fn selector_list() {
+    run_test!("p", "span, p.x", Some(true));
+    run_test!("p", "span, ul", Some(false));
+}
+
+#[test_case]
+fn non_element() {
+    let document = parse(String::from(HTML));
+    assert_eq!(document.matches(document.root(), "*"), Some(false));
+}
+
+#[test_case]
+fn parse_attribute() {
+    let list = parse_selector_list("[ HREF ][a|=\"b\" i]").unwrap();
+    assert_eq!(
+        list[0].compounds()[0].selectors(),
+        &vec![
+            SimpleSelector::Attribute {
+                name: String::from("href"),
+                matcher: None,
+                case_insensitive: false,
+            },
+            SimpleSelector::Attribute {
+                name: String::from("a"),
+                matcher: Some((AttributeMatcher::DashMatch, String::from("b"))),
+                case_insensitive: true,
+            },
+        ]
+    );
+
+    assert_eq!(parse_selector_list("[]"), None);
+    assert_eq!(parse_selector_list("[a"), None);
+    assert_eq!(parse_selector_list("[a=]"), None);
+    assert_eq!(parse_selector_list("[a!=b]"), None);
+    assert_eq!(parse_selector_list("[a=b c]"), None);
+}
+
+#[test_case]
+fn attribute() {
+    run_test!("div", "[id]", Some(true));
+    run_test!("div", "div[lang]", Some(true));
+    run_test!("div", "[href]", Some(false));
+    run_test!("div", "[id=main]", Some(true));
+    run_test!("div", "[id=\"main\"]", Some(true));
+    run_test!("div", "[id=Main]", Some(false));
+    run_test!("div", "[id=Main i]", Some(true));
+    run_test!("div", "[class~=b]", Some(true));
+    run_test!("div", "[class~=\"a b\"]", Some(false));
+    run_test!("div", "[lang|=en]", Some(true));
+    run_test!("div", "[lang|=e]", Some(false));
+    run_test!("div", "[data-x^=Foo]", Some(true));
+    run_test!("div", "[data-x$=Bar]", Some(true));
+    run_test!("div", "[data-x*=\"o B\"]", Some(true));
+    run_test!("div", "[data-x^=\"\"]", Some(false));
+}
+
+#[test_case]
+fn query_selector() {
+    let document = parse(String::from(HTML));
+    let root = document.root();
+    let p = find(&document, "p");
+
+    assert_eq!(document.query_selector(root, "p"), Some(p));
+    assert_eq!(document.query_selector(root, "ul"), None);
+    assert_eq!(document.query_selector(root, "p >"), None);
+
+    let all = document.query_selector_all(root, "p, span").unwrap();
+    let tags: Vec<String> = all
+        .iter()
+        .map(|n| String::from(document.node(*n).element().unwrap().tag_name()))
+        .collect();
+    assert_eq!(tags, vec!["p", "span", "p"]);
+
+    // Only descendants of the scope are returned.
+    assert_eq!(document.query_selector_all(p, "p").unwrap().len(), 0);
+    assert_eq!(document.query_selector_all(root, "p >"), None);
+}
+
+#[test_case]
+fn pseudo_classes() {
+    // Text nodes are not counted as siblings.
+    run_test!("span", "span:first-child", Some(true));
+    run_test!("span", ":last-child", Some(true));
+    run_test!("div", "div:FIRST-CHILD", Some(true));
+    run_test!("div", "div:last-child", Some(false));
+    run_test!("body", ":first-child", Some(false));
+    run_test!("body", ":last-child", Some(true));
+    run_test!("span", "div > :first-child > span", Some(true));
+    run_test!("div", ":link", Some(false));
+    run_test!("div", ":hover", None);
+    run_test!("div", "div::before", None);
+    run_test!("div", "div:", None);
+
+    let document = parse(String::from(
+        "<a href=x>1</a><a>2</a><area href=y><p href=z>3</p>",
+    ));
+    let links: Vec<NodeId> = document
+        .query_selector_all(document.root(), ":link")
+        .unwrap();
+    let tags: Vec<&str> = links
+        .iter()
+        .map(|id| document.node(*id).element().unwrap().tag_name())
+        .collect();
+    assert_eq!(tags, vec!["a", "area"]);
+    assert_eq!(document.query_selector(document.root(), "a:visited"), None);
+}
+
+#[test_case]
+fn pseudo_class_specificity() {
+    let list = parse_selector_list("a:link, li:first-child:last-child").unwrap();
+    assert_eq!(list[0].specificity(), Specificity(0, 1, 1));
+    assert_eq!(list[1].specificity(), Specificity(0, 2, 1));
+    assert_eq!(
+        list[0].compounds()[0].selectors(),
+        &vec![
+            SimpleSelector::Type(String::from("a")),
+            SimpleSelector::PseudoClass(PseudoClass::Link),
+        ]
+    );
+}
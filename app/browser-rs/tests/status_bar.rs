@@ -0,0 +1,108 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use browser_rs::layout::geometry::Rect;
+use browser_rs::paint::display_list::*;
+use browser_rs::ui::status_bar::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in status_bar.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn loading() {
+    let mut bar = StatusBar::new("http://127.0.0.1:8888/index.html");
+    assert_eq!(bar.state(), LoadState::Loading { fetched: 0 });
+    assert_eq!(bar.text(), "Loading | http://127.0.0.1:8888/index.html");
+    bar.fetched(2);
+    assert_eq!(
+        bar.text(),
+        "Loading (2 fetched) | http://127.0.0.1:8888/index.html"
+    );
+    bar.finish(String::from("Top page"));
+    assert_eq!(
+        bar.text(),
+        "Done | Top page | http://127.0.0.1:8888/index.html"
+    );
+
+    // The title of the last page is cleared when the next one starts loading.
+    bar.start("http://127.0.0.1:8888/a.html");
+    assert_eq!(bar.title(), "");
+    bar.fail();
+    assert_eq!(bar.text(), "Failed | http://127.0.0.1:8888/a.html");
+}
+
+/// Returns the text in the display list and its rect.
+fn painted(list: &DisplayList) -> Option<(String, Rect)> {
+    list.iter().find_map(|item| match item {
+        DisplayItem::Text { text, rect, .. } => Some((text.clone(), *rect)),
+        _ => None,
+    })
+}
+
+#[test_case]
+fn display_list() {
+    assert_eq!(StatusBar::height(), 21.0);
+    let mut bar = StatusBar::new("a/b");
+    bar.finish(String::new());
+    let list = bar.display_list(100.0, 50.0);
+    assert_eq!(list[0].rect(), Rect::new(0.0, 50.0, 100.0, 1.0));
+    assert_eq!(list[1].rect(), Rect::new(0.0, 51.0, 100.0, 20.0));
+    assert_eq!(
+        painted(&list),
+        Some((String::from("Done | a/b"), Rect::new(2.0, 53.0, 80.0, 16.0)))
+    );
+    // A long text is cut with an ellipsis.
+    assert_eq!(
+        painted(&bar.display_list(68.0, 0.0)).map(|(text, _)| text),
+        Some(String::from("Done ..."))
+    );
+}
+
+#[test_case]
+fn load_time() {
+    let mut bar = StatusBar::new("a/b");
+    bar.set_load_time(1_234_567);
+    bar.finish(String::from("Title"));
+    assert_eq!(bar.text(), "Done in 1.2ms | Title | a/b");
+    // The time of the last page aren't shown for the next one.
+    bar.start("a/c");
+    bar.finish(String::new());
+    assert_eq!(bar.text(), "Done | a/c");
+}
@@ -0,0 +1,320 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::parser::css::cssom::*;
+use browser_rs::parser::css::selector::*;
+use browser_rs::parser::css::tokenizer::*;
+use browser_rs::parser::dom::*;
+use browser_rs::parser::parse;
+use browser_rs::style::cascade::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in style.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+const HTML: &str =
+    "<html><body><div id=main class=a><p class=x>text<span style=\"color: green\">s</span></p></div></body></html>";
+
+/// Returns the first element with `tag` in tree order.
+fn find(document: &Document, tag: &str) -> NodeId {
+    document.query_selector(document.root(), tag).unwrap()
+}
+
+/// Returns the winning value of each property as a string such as "red".
+fn values(cascaded: &BTreeMap<String, Declaration>) -> Vec<(String, String)> {
+    cascaded
+        .iter()
+        .map(|(name, d)| {
+            let value: Vec<String> = d
+                .value()
+                .iter()
+                .map(|t| match t {
+                    CssToken::Ident(s) => s.clone(),
+                    CssToken::Whitespace => String::from(" "),
+                    t => format!("{:?}", t),
+                })
+                .collect();
+            (name.clone(), value.concat())
+        })
+        .collect()
+}
+
+fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+    expected
+        .iter()
+        .map(|(n, v)| (String::from(*n), String::from(*v)))
+        .collect()
+}
+
+#[macro_export]
+macro_rules! run_test {
+    ($tag:literal, [ $( ($origin:expr, $css:literal) ),* ], $expected:expr) => {
+        let document = parse(String::from(HTML));
+        let element = find(&document, $tag);
+
+        let sheets = [ $( ($origin, parse_stylesheet(String::from($css))) ),* ];
+        let sheets: Vec<(Origin, &StyleSheet)> = sheets.iter().map(|(o, s)| (*o, s)).collect();
+
+        assert_eq!(values(&cascade(&document, element, &sheets)), pairs(&$expected));
+    };
+}
+
+#[test_case]
+fn no_match() {
+    run_test!("p", [(Origin::Author, "div { color: red }")], []);
+}
+
+#[test_case]
+fn source_order() {
+    run_test!(
+        "p",
+        [(
+            Origin::Author,
+            "p { color: red; margin: 0 auto } p { color: blue }"
+        )],
+        [
+            ("color", "blue"),
+            ("margin-bottom", "Number(0.0)"),
+            ("margin-left", "auto"),
+            ("margin-right", "auto"),
+            ("margin-top", "Number(0.0)")
+        ]
+    );
+    run_test!(
+        "p",
+        [
+            (Origin::Author, "p { color: red }"),
+            (Origin::Author, "p { color: blue }")
+        ],
+        [("color", "blue")]
+    );
+}
+
+#[test_case]
+fn specificity() {
+    run_test!(
+        "p",
+        [(
+            Origin::Author,
+            "#main p { color: red } div p.x { color: blue } p { color: green }"
+        )],
+        [("color", "red")]
+    );
+    run_test!(
+        "p",
+        [(Origin::Author, ".x { color: red } body p { color: blue }")],
+        [("color", "red")]
+    );
+    // The most specific matching selector in a list counts.
+    run_test!(
+        "p",
+        [(
+            Origin::Author,
+            "p, #main > .x { color: red } div .x { color: blue }"
+        )],
+        [("color", "red")]
+    );
+}
+
+#[test_case]
+fn origin_and_importance() {
+    run_test!(
+        "p",
+        [
+            (Origin::UserAgent, "#main p { color: red }"),
+            (Origin::Author, "p { color: blue }")
+        ],
+        [("color", "blue")]
+    );
+    run_test!(
+        "p",
+        [(
+            Origin::Author,
+            "p { color: red !important } #main .x { color: blue }"
+        )],
+        [("color", "red")]
+    );
+    run_test!(
+        "p",
+        [
+            (Origin::UserAgent, "p { color: red !important }"),
+            (Origin::Author, "p { color: blue !important }")
+        ],
+        [("color", "red")]
+    );
+}
+
+#[test_case]
+fn specificity_values() {
+    let sheet = parse_stylesheet(String::from(
+        "* {} p {} div > p.a[x] {} #a #b .c {} ul li * {}",
+    ));
+    let specificities: Vec<_> = sheet
+        .rules()
+        .iter()
+        .map(|r| r.selectors()[0].specificity())
+        .collect();
+    assert_eq!(
+        specificities,
+        vec![
+            Specificity(0, 0, 0),
+            Specificity(0, 0, 1),
+            Specificity(0, 2, 2),
+            Specificity(2, 1, 0),
+            Specificity(0, 0, 2),
+        ]
+    );
+}
+
+#[test_case]
+fn inline_style() {
+    let document = parse(String::from(HTML));
+    let span = find(&document, "span");
+    assert_eq!(
+        document.node(span).element().unwrap().inline_style(),
+        vec![Declaration::new(
+            "color",
+            vec![CssToken::Ident(String::from("green"))],
+            false
+        )]
+    );
+
+    run_test!(
+        "span",
+        [(
+            Origin::Author,
+            "#main .x span { color: red; margin-top: 0 }"
+        )],
+        [("color", "green"), ("margin-top", "Number(0.0)")]
+    );
+    run_test!(
+        "span",
+        [(Origin::Author, "span { color: red !important }")],
+        [("color", "red")]
+    );
+    run_test!(
+        "span",
+        [(Origin::UserAgent, "span { color: red }")],
+        [("color", "green")]
+    );
+}
+
+#[test_case]
+fn important_inline_style() {
+    let mut document = parse(String::from(HTML));
+    let p = find(&document, "p");
+    document.set_attribute(
+        p,
+        "style",
+        "color: green !important; bogus; margin-top: 1px",
+    );
+
+    let sheet = parse_stylesheet(String::from("p { color: red !important; margin-top: 0 }"));
+    let cascaded = cascade(&document, p, &[(Origin::Author, &sheet)]);
+    assert_eq!(
+        values(&cascaded),
+        pairs(&[("color", "green"), ("margin-top", "Dimension(1.0, \"px\")")])
+    );
+}
+
+#[test_case]
+fn shorthands() {
+    // A shorthand and its longhands are cascaded by priority and the order of appearance.
+    run_test!(
+        "p",
+        [(
+            Origin::Author,
+            "p { padding-top: 1px; padding: 2px 3px; padding-left: 4px } \
+             div p { padding-right: 5px } p { padding-right: 6px }"
+        )],
+        [
+            ("padding-bottom", "Dimension(2.0, \"px\")"),
+            ("padding-left", "Dimension(4.0, \"px\")"),
+            ("padding-right", "Dimension(5.0, \"px\")"),
+            ("padding-top", "Dimension(2.0, \"px\")")
+        ]
+    );
+    // An invalid shorthand sets nothing.
+    run_test!(
+        "p",
+        [(
+            Origin::Author,
+            "p { margin: 1px 2px 3px 4px 5px; padding: 1px -2px; border-style: solid wavy }"
+        )],
+        []
+    );
+}
+
+#[test_case]
+fn presentational_hints() {
+    let document = parse(String::from("<img width=\"10\" height=\" 50%x\">"));
+    let img = find(&document, "img");
+    let sheet = parse_stylesheet(String::from("img { height: 3px }"));
+    assert_eq!(
+        values(&cascade(&document, img, &[])),
+        pairs(&[
+            ("height", "Percentage(50.0)"),
+            ("width", "Dimension(10.0, \"px\")")
+        ])
+    );
+    // Any author rule wins over the hints, but user-agent rules don't.
+    assert_eq!(
+        values(&cascade(&document, img, &[(Origin::Author, &sheet)])),
+        pairs(&[
+            ("height", "Dimension(3.0, \"px\")"),
+            ("width", "Dimension(10.0, \"px\")")
+        ])
+    );
+    assert_eq!(
+        values(&cascade(&document, img, &[(Origin::UserAgent, &sheet)])),
+        pairs(&[
+            ("height", "Percentage(50.0)"),
+            ("width", "Dimension(10.0, \"px\")")
+        ])
+    );
+
+    // A value which isn't a number is ignored.
+    let document = parse(String::from("<img width=abc>"));
+    let img = find(&document, "img");
+    assert_eq!(values(&cascade(&document, img, &[])), pairs(&[]));
+}
@@ -0,0 +1,57 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use liumlib::surface::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in surface.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn memory_surface() {
+    let mut surface = MemorySurface::new(4, 3);
+    assert_eq!(surface.size(), (4, 3));
+    assert_eq!(surface.pixels().len(), 12);
+    surface.pixels()[4 + 1] = 0x123456;
+    // Nothing is shown until it's presented.
+    assert_eq!(surface.presented_pixel(1, 1), 0);
+    assert_eq!(surface.presentations(), 0);
+    surface.present();
+    assert_eq!(surface.presented_pixel(1, 1), 0x123456);
+    assert_eq!(surface.presented()[5], 0x123456);
+    assert_eq!(surface.presentations(), 1);
+}
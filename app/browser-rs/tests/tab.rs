@@ -0,0 +1,129 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::page::Page;
+use browser_rs::parser::parse;
+use browser_rs::style::computed::{ColorScheme, Viewport};
+use browser_rs::tab::*;
+use browser_rs::url::ParsedUrl;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in tab.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+const VIEWPORT: Viewport = Viewport {
+    width: 600.0,
+    height: 400.0,
+    color_scheme: ColorScheme::Light,
+};
+
+/// Returns a tab showing a page titled `title` at http://127.0.0.1:8888/`path`.
+fn tab(title: &str, path: &str) -> Tab {
+    let html = format!("<title>{}</title><a href=x>a</a>", title);
+    let url = ParsedUrl::new(format!("127.0.0.1:8888/{}", path));
+    Tab::new(Page::new(parse(html), VIEWPORT), url)
+}
+
+/// Returns the labels of the tabs and the index of the active one.
+fn labels(tabs: &Tabs) -> (Vec<String>, usize) {
+    let strip = tabs.strip();
+    (strip.labels().to_vec(), strip.active())
+}
+
+#[test_case]
+fn open_and_close() {
+    let mut tabs = Tabs::new(tab("A", "a.html"));
+    assert_eq!(tabs.count(), 1);
+    assert_eq!(tabs.active().status.title(), "A");
+    // A new tab is opened next to the active one.
+    tabs.open(tab("B", "b.html"));
+    tabs.select(0);
+    tabs.open(tab("", "c.html"));
+    assert_eq!(
+        labels(&tabs),
+        (
+            vec![
+                String::from("A"),
+                String::from("http://127.0.0.1:8888/c.html"),
+                String::from("B"),
+            ],
+            1
+        )
+    );
+
+    // The next tab is active after closing one, or the previous one at the end.
+    assert!(tabs.close_active());
+    assert_eq!(tabs.active().url.path, "/b.html");
+    assert!(tabs.close_active());
+    assert_eq!(tabs.active().url.path, "/a.html");
+    // The last tab isn't closed.
+    assert!(!tabs.close_active());
+    assert_eq!(tabs.count(), 1);
+}
+
+#[test_case]
+fn select() {
+    let mut tabs = Tabs::new(tab("A", "a.html"));
+    tabs.open(tab("B", "b.html"));
+    tabs.open(tab("C", "c.html"));
+    assert_eq!(tabs.active_index(), 2);
+    assert!(tabs.select_next(false));
+    assert_eq!(tabs.active_index(), 0);
+    assert!(tabs.select_next(true));
+    assert_eq!(tabs.active_index(), 2);
+    assert!(!tabs.select(2));
+    assert!(!tabs.select(3));
+
+    // Each tab keeps its own state, and the selected page is painted again as a whole.
+    tabs.active_mut().page.take_damage();
+    tabs.active_mut().page.focus_next(false);
+    assert!(tabs.select(1));
+    assert_eq!(tabs.active().page.focused(), None);
+    assert!(tabs.select(2));
+    assert!(tabs.active().page.focused().is_some());
+    let (_, damage) = tabs.active_mut().page.take_damage();
+    assert_eq!(damage.rects(), &vec![tabs.active().page.visible_rect()]);
+
+    // Only one tab can't be switched.
+    let mut tabs = Tabs::new(tab("A", "a.html"));
+    assert!(!tabs.select_next(false));
+}
@@ -0,0 +1,88 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::layout::geometry::Rect;
+use browser_rs::paint::display_list::*;
+use browser_rs::style::values::Color;
+use browser_rs::ui::tab_strip::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in tab_strip.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// Returns the texts in the display list and their rects.
+fn painted(list: &DisplayList) -> Vec<(String, Rect)> {
+    list.iter()
+        .filter_map(|item| match item {
+            DisplayItem::Text { text, rect, .. } => Some((text.clone(), *rect)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test_case]
+fn display_list() {
+    assert_eq!(TabStrip::height(), 21.0);
+    let strip = TabStrip::new(vec![String::from("A"), String::from("Tab")], 1);
+    let list = strip.display_list(100.0);
+    assert_eq!(list[0].rect(), Rect::new(0.0, 0.0, 100.0, 20.0));
+    assert_eq!(list[1].rect(), Rect::new(0.0, 20.0, 100.0, 1.0));
+    assert_eq!(
+        painted(&list),
+        vec![
+            (String::from("A"), Rect::new(2.0, 2.0, 8.0, 16.0)),
+            (String::from("Tab"), Rect::new(52.0, 2.0, 24.0, 16.0)),
+        ]
+    );
+    // The active tab covers the line below the strip.
+    assert!(list.iter().any(|item| matches!(
+        item,
+        DisplayItem::SolidRect { rect, color }
+            if *rect == Rect::new(50.0, 0.0, 50.0, 21.0) && *color == Color::WHITE
+    )));
+
+    // A long label is cut with an ellipsis.
+    let strip = TabStrip::new(vec![String::from("A long title"), String::from("B")], 0);
+    assert_eq!(
+        painted(&strip.display_list(140.0))[0].0,
+        String::from("A lon...")
+    );
+}
@@ -0,0 +1,152 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use liumlib::net::*;
+use liumlib::poll::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in tcp.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+fn localhost(port: u16) -> SocketAddrV4 {
+    SocketAddrV4::new([127, 0, 0, 1], port)
+}
+
+/// Returns a listener on a port which the system chooses.
+fn listen() -> TcpListener {
+    TcpListener::bind(localhost(0)).unwrap()
+}
+
+#[test_case]
+fn connect_and_accept() {
+    let listener = listen();
+    let address = listener.local_addr().unwrap();
+    assert_eq!(address.ip(), [127, 0, 0, 1]);
+    assert_ne!(address.port(), 0);
+
+    // The connection waits in the backlog until it's accepted.
+    let client = TcpStream::connect(address).unwrap();
+    let (server, peer) = listener.accept().unwrap();
+    assert_eq!(peer.ip(), [127, 0, 0, 1]);
+
+    client.write_all(b"GET / HTTP/1.1\n\n").unwrap();
+    client.shutdown(Shutdown::Write).unwrap();
+    let mut request = Vec::new();
+    assert_eq!(server.read_to_end(&mut request), Ok(16));
+    assert_eq!(request, b"GET / HTTP/1.1\n\n");
+
+    server.write_all(b"HTTP/1.1 200 OK\n\nhello").unwrap();
+    drop(server);
+    let mut response = Vec::new();
+    client.read_to_end(&mut response).unwrap();
+    assert_eq!(response, b"HTTP/1.1 200 OK\n\nhello");
+}
+
+#[test_case]
+fn receiving_all() {
+    let listener = listen();
+    let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+
+    // Nothing arrives while the server is silent.
+    let mut response = Vec::new();
+    let timeout = Some(Duration::from_millis(10));
+    let fd = client.file_descriptor();
+    assert_eq!(
+        recv_all(fd, &mut response, timeout),
+        Err(SocketError::TimedOut)
+    );
+
+    // The buffer grows beyond its first capacity without truncating the response.
+    let body = vec![b'a'; 10000];
+    server.write_all(&body).unwrap();
+    drop(server);
+    let timeout = Some(Duration::from_secs(1));
+    assert_eq!(recv_all(fd, &mut response, timeout), Ok(10000));
+    assert_eq!(response, body);
+}
+
+#[test_case]
+fn errors() {
+    let listener = listen();
+    let address = listener.local_addr().unwrap();
+    assert_eq!(
+        TcpListener::bind(address).unwrap_err(),
+        SocketError::AddrInUse
+    );
+    drop(listener);
+    let error = TcpStream::connect(address).unwrap_err();
+    assert_eq!(error, SocketError::ConnectionRefused);
+    assert_eq!(format!("{}", error), "connection refused");
+    assert_eq!(SocketError::from_errno(999), SocketError::Other(999));
+}
+
+#[test_case]
+fn nonblocking_streams() {
+    let listener = listen();
+    let address = listener.local_addr().unwrap();
+    let client = TcpStream::connect_nonblocking(address).unwrap();
+    let mut fds = [PollFd::new(client.file_descriptor().number(), POLLOUT)];
+    assert_eq!(poll(&mut fds, Some(1000)), Some(1));
+    assert_eq!(client.take_error(), Ok(None));
+
+    let mut buf = [0u8; 16];
+    assert_eq!(client.read(&mut buf), Err(SocketError::WouldBlock));
+    listener.set_nonblocking(true).unwrap();
+    let (server, _) = listener.accept().unwrap();
+    assert_eq!(listener.accept().unwrap_err(), SocketError::WouldBlock);
+    server.write_all(b"hi").unwrap();
+    client.set_nonblocking(false).unwrap();
+    assert_eq!(client.read(&mut buf), Ok(2));
+    drop(listener);
+
+    // A refused connection is known once the socket is ready.
+    let client = TcpStream::connect_nonblocking(address);
+    if let Ok(client) = client {
+        let mut fds = [PollFd::new(client.file_descriptor().number(), POLLOUT)];
+        assert_eq!(poll(&mut fds, Some(1000)), Some(1));
+        assert_eq!(
+            client.take_error(),
+            Ok(Some(SocketError::ConnectionRefused))
+        );
+    }
+}
@@ -0,0 +1,217 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use browser_rs::font::Font;
+use browser_rs::form::*;
+use browser_rs::keyboard::*;
+use browser_rs::layout::geometry::*;
+use browser_rs::layout::text_control::*;
+use browser_rs::page::Page;
+use browser_rs::paint::display_list::*;
+use browser_rs::paint::focus_ring::*;
+use browser_rs::parser::dom::*;
+use browser_rs::parser::parse;
+use browser_rs::style::computed::{ColorScheme, Viewport};
+use browser_rs::style::values::Color;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in text_control.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+const VIEWPORT: Viewport = Viewport {
+    width: 400.0,
+    height: 100.0,
+    color_scheme: ColorScheme::Light,
+};
+
+fn page(html: &str) -> Page {
+    let html = String::from("<style>body { margin: 0 }</style>") + html;
+    Page::new(parse(html), VIEWPORT)
+}
+
+/// Returns the first element matching `selectors`.
+fn find(page: &Page, selectors: &str) -> NodeId {
+    let document = page.document();
+    document.query_selector(document.root(), selectors).unwrap()
+}
+
+/// Returns the rect of the box of `id`, which is the content box of a replaced box.
+fn control_rect(page: &Page, id: NodeId) -> Rect {
+    focus_rects(page.document(), page.layout().unwrap(), id)[0]
+}
+
+/// Returns the texts in the display list.
+fn texts(page: &Page) -> Vec<(String, Rect)> {
+    page.display_list()
+        .iter()
+        .filter_map(|item| match item {
+            DisplayItem::Text { text, rect, .. } => Some((text.clone(), *rect)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test_case]
+fn natural_sizes() {
+    let page = page("<input><textarea cols=10 rows=3></textarea><input size=5 type=password>");
+    // 20 characters of 8px and a line of 16px, with a border of 1px and a padding of 2px.
+    let input = control_rect(&page, find(&page, "input"));
+    assert_eq!((input.width, input.height), (166.0, 22.0));
+    let textarea = control_rect(&page, find(&page, "textarea"));
+    assert_eq!((textarea.width, textarea.height), (86.0, 54.0));
+    let password = control_rect(&page, find(&page, "[type=password]"));
+    assert_eq!((password.width, password.height), (46.0, 22.0));
+    // The other inputs aren't text controls.
+    let page = self::page("<input type=checkbox><input type=hidden>");
+    assert!(focus_rects(
+        page.document(),
+        page.layout().unwrap(),
+        find(&page, "input")
+    )
+    .is_empty());
+}
+
+#[test_case]
+fn painted_value() {
+    let page = page("<input value=abc><input type=password value=pw>");
+    let rect = control_rect(&page, find(&page, "input"));
+    let password = control_rect(&page, find(&page, "[type=password]"));
+    assert_eq!(
+        texts(&page),
+        vec![
+            (
+                String::from("abc"),
+                Rect::new(rect.x + 3.0, rect.y + 3.0, 24.0, 16.0)
+            ),
+            (
+                String::from("**"),
+                Rect::new(password.x + 3.0, password.y + 3.0, 16.0, 16.0)
+            ),
+        ]
+    );
+}
+
+#[test_case]
+fn overflowing_value() {
+    // The end of the value is shown, where the text is typed.
+    let page = page("<input size=3 value=abcdef><textarea rows=2>a\nb\nc</textarea>");
+    let document = page.document();
+    let input = find(&page, "input");
+    let area = text_area(control_rect(&page, input));
+    assert_eq!(
+        visible_lines(document, input, &Font::new(1, false), area),
+        vec![String::from("def")]
+    );
+    let textarea = find(&page, "textarea");
+    let area = text_area(control_rect(&page, textarea));
+    assert_eq!(
+        visible_lines(document, textarea, &Font::new(1, false), area),
+        vec![String::from("b"), String::from("c")]
+    );
+}
+
+#[test_case]
+fn typing() {
+    let mut page = page("<form><input name=q value=ab><button>Go</button></form>");
+    let input = find(&page, "input");
+    assert_eq!(edit_with_key(&mut page, Key::Char(b'x')), None);
+    assert!(page.focus_next(false));
+    assert_eq!(page.focused_text_control(), Some(input));
+
+    assert_eq!(edit_with_key(&mut page, Key::Char(b'c')), Some(true));
+    assert_eq!(edit_with_key(&mut page, Key::Char(b' ')), Some(true));
+    assert_eq!(edit_with_key(&mut page, Key::Backspace), Some(true));
+    assert_eq!(control_value(page.document(), input), "abc");
+    // The default value is kept in the attribute.
+    let element = page.document().node(input).element().unwrap();
+    assert_eq!(element.get_attribute("value"), Some(String::from("ab")));
+    // The edited value is submitted.
+    assert_eq!(
+        page.focused_submission().map(|s| s.entries),
+        Some(vec![(String::from("q"), String::from("abc"))])
+    );
+    assert!(texts(&page).iter().any(|(text, _)| text == "abc"));
+
+    // Keys which don't edit text work as usual.
+    assert_eq!(edit_with_key(&mut page, Key::Enter), None);
+    assert_eq!(edit_with_key(&mut page, Key::Tab), None);
+    assert_eq!(edit_with_key(&mut page, Key::Char(0x0c)), None);
+    assert_eq!(edit_with_key(&mut page, Key::Escape), Some(true));
+    assert_eq!(page.focused(), None);
+}
+
+#[test_case]
+fn textarea_lines() {
+    let mut page = page("<form><textarea name=t></textarea></form>");
+    let textarea = find(&page, "textarea");
+    page.focus_next(false);
+    for key in [Key::Char(b'a'), Key::Enter, Key::Char(b'b')].iter() {
+        assert_eq!(edit_with_key(&mut page, *key), Some(true));
+    }
+    assert_eq!(control_value(page.document(), textarea), "a\nb");
+    assert_eq!(
+        entry_list(page.document(), find(&page, "form"), None),
+        vec![(String::from("t"), String::from("a\nb"))]
+    );
+    assert_eq!(edit_with_key(&mut page, Key::Backspace), Some(true));
+    assert_eq!(edit_with_key(&mut page, Key::Backspace), Some(true));
+    assert_eq!(control_value(page.document(), textarea), "a");
+    assert_eq!(edit_with_key(&mut page, Key::Backspace), Some(true));
+    assert_eq!(edit_with_key(&mut page, Key::Backspace), Some(false));
+}
+
+#[test_case]
+fn caret() {
+    let mut page = page("<input value=ab>");
+    let rect = control_rect(&page, find(&page, "input"));
+    let caret = Rect::new(rect.x + 3.0 + 16.0, rect.y + 3.0, 1.0, 16.0);
+    let has_caret = |page: &Page| {
+        page.display_list().iter().any(|item| {
+            item == &DisplayItem::SolidRect {
+                rect: caret,
+                color: Color::BLACK,
+            }
+        })
+    };
+    assert!(!has_caret(&page));
+    page.focus_next(false);
+    assert!(has_caret(&page));
+}
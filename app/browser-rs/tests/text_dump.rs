@@ -0,0 +1,97 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use browser_rs::layout::text_dump::dump_text;
+use browser_rs::page::Page;
+use browser_rs::parser::parse;
+use browser_rs::style::computed::{ColorScheme, Viewport};
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in text_dump.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+const VIEWPORT: Viewport = Viewport {
+    width: 200.0,
+    height: 100.0,
+    color_scheme: ColorScheme::Light,
+};
+
+/// Lays out `html` and returns its text rendering, where the URLs of links are prefixed with
+/// "http://a/".
+fn dump(html: &str) -> String {
+    let page = Page::new(parse(String::from(html)), VIEWPORT);
+    dump_text(page.document(), page.layout().unwrap(), |href| {
+        alloc::format!("http://a/{}", href)
+    })
+}
+
+#[test_case]
+fn paragraphs_and_headings() {
+    let out = dump(
+        "<h1>Title</h1><p>The first paragraph is broken into lines.</p>\
+         <h2>Section</h2><p>Second</p>",
+    );
+    // The lines are broken as they are in the viewport.
+    assert_eq!(
+        out,
+        "Title\n=====\n\nThe first paragraph is\nbroken into lines.\n\n\
+         Section\n-------\n\nSecond\n"
+    );
+}
+
+#[test_case]
+fn lists_and_links() {
+    let out = dump(
+        "<ul><li>One</li><li>Two <a href='two.html'>link</a></li></ul>\
+         <ol><li><a href='x'>A</a> and <a href='y'>B</a></li><li>C</li></ol>\
+         <img alt='logo'><img>",
+    );
+    assert_eq!(
+        out,
+        "   * One\n   * Two link[1]\n\n  1. A[2] and B[3]\n  2. C\n\n[logo][image]\n\n\
+         References:\n\n[1] http://a/two.html\n[2] http://a/x\n[3] http://a/y\n"
+    );
+}
+
+#[test_case]
+fn tables() {
+    // The cells are placed at their columns.
+    let out = dump("<table><tr><td>a</td><td>bb</td></tr><tr><td>ccc</td><td>d</td></tr></table>");
+    assert_eq!(out, "a   bb\nccc d\n");
+}
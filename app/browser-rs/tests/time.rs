@@ -0,0 +1,83 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use core::time::Duration;
+
+use liumlib::time::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in time.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn instants() {
+    let start = Instant::now();
+    let later = Instant::now();
+    assert!(later >= start);
+    assert_eq!(
+        start.duration_since(later + Duration::from_secs(1)),
+        Duration::ZERO
+    );
+    assert_eq!(
+        (start + Duration::from_millis(5)) - start,
+        Duration::from_millis(5)
+    );
+    assert_eq!(start.checked_add(Duration::MAX), None);
+    assert!(start.elapsed() < Duration::from_secs(60));
+}
+
+#[test_case]
+fn sleeping() {
+    let start = Instant::now();
+    sleep(Duration::from_millis(20));
+    assert!(start.elapsed() >= Duration::from_millis(20));
+
+    let deadline = Instant::now() + Duration::from_millis(10);
+    sleep_until(deadline);
+    assert!(Instant::now() >= deadline);
+    // A deadline which has passed returns at once.
+    let start = Instant::now();
+    sleep_until(deadline);
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test_case]
+fn wall_clock_on_linux() {
+    // Linux knows the date, which is later than 2020-01-01T00:00:00Z.
+    let now = wall_clock().unwrap();
+    assert!(now.as_secs() > 1_577_836_800);
+}
@@ -0,0 +1,90 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use browser_rs::page::Page;
+use browser_rs::parser::parse_with_timing;
+use browser_rs::style::computed::Viewport;
+use browser_rs::timing::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in timing.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+#[test_case]
+fn stages() {
+    let mut timing = Timing::new();
+    assert_eq!(timing.total(), 0);
+    timing.add(Stage::Fetch, 100);
+    timing.add(Stage::Fetch, 50);
+    assert_eq!(timing.measure(Stage::Layout, || 42), 42);
+    assert_eq!(timing.get(Stage::Fetch), 150);
+    assert!(timing.get(Stage::Layout) > 0);
+    assert_eq!(timing.get(Stage::Style), 0);
+    assert_eq!(timing.total(), 150 + timing.get(Stage::Layout));
+}
+
+#[test_case]
+fn summary() {
+    let mut timing = Timing::new();
+    timing.add(Stage::Fetch, 1_500_000);
+    timing.add(Stage::Tokenize, 30_500);
+    timing.add(Stage::Paint, 950);
+    assert_eq!(
+        timing.summary(),
+        "fetch 1.5ms, tokenize 30.5us, tree 0ns, style 0ns, layout 0ns, paint 950ns, total 1.5ms"
+    );
+    assert_eq!(format_nanos(2_000_000_000), "2.0s");
+}
+
+#[test_case]
+fn rendering() {
+    let mut timing = Timing::new();
+    let html = String::from("<html><body><p>a</p><p>b</p></body></html>");
+    let document = parse_with_timing(html, &mut timing);
+    assert!(timing.get(Stage::Tokenize) > 0);
+    assert!(timing.get(Stage::TreeBuild) > 0);
+    assert_eq!(timing.get(Stage::Style), 0);
+    let page = Page::with_timing(document, Viewport::default(), &mut timing);
+    assert!(page.layout().is_some());
+    for stage in [Stage::Style, Stage::Layout, Stage::Paint].iter() {
+        assert!(timing.get(*stage) > 0);
+    }
+    // Nothing is fetched.
+    assert_eq!(timing.get(Stage::Fetch), 0);
+}
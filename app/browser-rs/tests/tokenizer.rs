@@ -7,6 +7,7 @@
 extern crate alloc;
 
 use alloc::string::String;
+use alloc::vec;
 use alloc::vec::Vec;
 
 use browser_rs::parser::tokenizer::*;
@@ -53,10 +54,7 @@ macro_rules! run_test {
     ($html:literal, $( $token:expr ),*) => {
         let mut t = Tokenizer::new(String::from($html));
 
-        let mut expected = Vec::new();
-        $(
-            expected.push($token);
-        )*
+        let expected = vec![$($token),*];
 
         for e in expected {
             let token = t.next().expect("tokenizer should have a next Token");
@@ -82,6 +80,7 @@ fn body() {
         Token::StartTag {
             tag: String::from("body"),
             self_closing: false,
+            attributes: Vec::new(),
         },
         Token::EndTag {
             tag: String::from("body"),
@@ -97,6 +96,7 @@ fn BODY() {
         Token::StartTag {
             tag: String::from("body"),
             self_closing: false,
+            attributes: Vec::new(),
         },
         Token::EndTag {
             tag: String::from("body"),
@@ -112,6 +112,7 @@ fn br() {
         Token::StartTag {
             tag: String::from("br"),
             self_closing: true,
+            attributes: Vec::new(),
         }
     );
 }
@@ -123,10 +124,12 @@ fn simple_page() {
         Token::StartTag {
             tag: String::from("html"),
             self_closing: false,
+            attributes: Vec::new(),
         },
         Token::StartTag {
             tag: String::from("body"),
             self_closing: false,
+            attributes: Vec::new(),
         },
         Token::Char('a'),
         Token::Char('b'),
@@ -141,3 +144,102 @@ fn simple_page() {
         }
     );
 }
+
+#[test_case]
+fn attributes() {
+    run_test!(
+        "<p class=\"A\" id='b' data-x=1 hidden>",
+        Token::StartTag {
+            tag: String::from("p"),
+            self_closing: false,
+            attributes: vec![
+                Attribute::new(String::from("class"), String::from("A")),
+                Attribute::new(String::from("id"), String::from("b")),
+                Attribute::new(String::from("data-x"), String::from("1")),
+                Attribute::new(String::from("hidden"), String::new()),
+            ],
+        }
+    );
+}
+
+#[test_case]
+fn duplicate_attribute() {
+    run_test!(
+        "<img SRC=a.png src=b.png/>",
+        Token::StartTag {
+            tag: String::from("img"),
+            self_closing: false,
+            attributes: vec![Attribute::new(String::from("src"), String::from("a.png"))],
+        }
+    );
+}
+
+#[test_case]
+fn self_closing_with_attribute() {
+    run_test!(
+        "<br class='x'/>",
+        Token::StartTag {
+            tag: String::from("br"),
+            self_closing: true,
+            attributes: vec![Attribute::new(String::from("class"), String::from("x"))],
+        }
+    );
+}
+
+#[test_case]
+fn unterminated_tag() {
+    run_test!("<a href=", Token::Eof);
+}
+
+#[test_case]
+fn comment() {
+    run_test!(
+        "<!-- foo -- bar --><!---->",
+        Token::Comment(String::from(" foo -- bar ")),
+        Token::Comment(String::new())
+    );
+}
+
+#[test_case]
+fn bogus_comment() {
+    run_test!("<?xml?>", Token::Comment(String::from("?xml?")));
+}
+
+#[test_case]
+fn doctype() {
+    run_test!(
+        "<!DOCTYPE html>",
+        Token::Doctype {
+            name: Some(String::from("html")),
+            public_id: None,
+            system_id: None,
+            force_quirks: false,
+        }
+    );
+}
+
+#[test_case]
+fn doctype_with_identifiers() {
+    run_test!(
+        "<!doctype HTML PUBLIC \"-//W3C//DTD HTML 4.01//EN\" 'http://www.w3.org/TR/html4/strict.dtd'>",
+        Token::Doctype {
+            name: Some(String::from("html")),
+            public_id: Some(String::from("-//W3C//DTD HTML 4.01//EN")),
+            system_id: Some(String::from("http://www.w3.org/TR/html4/strict.dtd")),
+            force_quirks: false,
+        }
+    );
+}
+
+#[test_case]
+fn broken_doctype() {
+    run_test!(
+        "<!DOCTYPE>",
+        Token::Doctype {
+            name: None,
+            public_id: None,
+            system_id: None,
+            force_quirks: true,
+        }
+    );
+}
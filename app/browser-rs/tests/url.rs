@@ -0,0 +1,151 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+
+use browser_rs::url::ParsedUrl;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in url.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+fn join(base: &str, href: &str) -> (String, u16, String) {
+    let url = ParsedUrl::new(String::from(base)).join(href).unwrap();
+    (url.host, url.port, url.path)
+}
+
+fn expected(host: &str, port: u16, path: &str) -> (String, u16, String) {
+    (String::from(host), port, String::from(path))
+}
+
+#[test_case]
+fn join_relative_path() {
+    assert_eq!(
+        join("http://127.0.0.1:8888/dir/index.html", "style.css"),
+        expected("127.0.0.1:8888", 8888, "/dir/style.css")
+    );
+    assert_eq!(
+        join("http://127.0.0.1:8888/index.html", "css/a.css"),
+        expected("127.0.0.1:8888", 8888, "/css/a.css")
+    );
+}
+
+#[test_case]
+fn join_absolute_path() {
+    assert_eq!(
+        join("http://127.0.0.1:8888/dir/index.html", "/a.css"),
+        expected("127.0.0.1:8888", 8888, "/a.css")
+    );
+}
+
+#[test_case]
+fn join_absolute_url() {
+    assert_eq!(
+        join(
+            "http://127.0.0.1:8888/dir/index.html",
+            "http://10.0.0.1:80/a.css"
+        ),
+        expected("10.0.0.1:80", 80, "/a.css")
+    );
+}
+
+#[test_case]
+fn join_invalid_url() {
+    let base = ParsedUrl::new(String::from("http://127.0.0.1:8888/dir/index.html"));
+    assert!(base.join("http://127.0.0.1:99999/a.css").is_none());
+    assert!(base.join("http://:80/a.css").is_none());
+    assert!(base.join("https://example.com/a.css").is_none());
+    // A URL in the query isn't a scheme.
+    assert_eq!(
+        join(
+            "http://127.0.0.1:8888/dir/index.html",
+            "a.html?next=http://h/"
+        ),
+        expected("127.0.0.1:8888", 8888, "/dir/a.html?next=http://h/")
+    );
+}
+
+#[test_case]
+fn parse_typed_url() {
+    let parse = |u: &str| ParsedUrl::parse(u).map(|url| (url.host, url.port, url.path));
+    assert_eq!(
+        parse("http://127.0.0.1:8888/a.html"),
+        Some(expected("127.0.0.1:8888", 8888, "/a.html"))
+    );
+    assert_eq!(
+        parse("10.0.0.1"),
+        Some(expected("10.0.0.1", 8888, "/index.html"))
+    );
+    assert_eq!(parse("https://example.com/"), None);
+    assert_eq!(
+        parse("10.0.0.1/a?u=https://h/"),
+        Some(expected("10.0.0.1", 8888, "/a?u=https://h/"))
+    );
+    assert_eq!(parse("10.0.0.1:http/"), None);
+    assert_eq!(parse("/a.html"), None);
+    assert_eq!(parse(""), None);
+}
+
+#[test_case]
+fn display() {
+    let url = ParsedUrl::new(String::from("127.0.0.1:8888/dir/a.html"));
+    assert_eq!(
+        alloc::format!("{}", url),
+        "http://127.0.0.1:8888/dir/a.html"
+    );
+}
+
+#[test_case]
+fn fragment() {
+    let url = ParsedUrl::new(String::from("127.0.0.1:8888/a.html?q=1#top"));
+    assert_eq!(url.fragment(), Some("top"));
+    assert_eq!(url.request_path(), "/a.html?q=1");
+    assert_eq!(
+        join("http://127.0.0.1:8888/dir/a.html#x", "#y"),
+        expected("127.0.0.1:8888", 8888, "/dir/a.html#y")
+    );
+    assert_eq!(
+        join("http://127.0.0.1:8888/dir/a.html#x/y", "b.html"),
+        expected("127.0.0.1:8888", 8888, "/dir/b.html")
+    );
+
+    // Only the fragment differs.
+    let other = url.join("#bottom").unwrap();
+    assert!(url.equals_except_fragment(&other));
+    assert!(!url.equals_except_fragment(&url.join("b.html#top").unwrap()));
+    assert_eq!(url.join("b.html").unwrap().fragment(), None);
+}
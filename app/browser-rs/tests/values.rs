@@ -0,0 +1,131 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use browser_rs::parser::css::tokenizer::*;
+use browser_rs::style::values::*;
+use liumlib::*;
+
+#[cfg(test)]
+pub trait Testable {
+    fn run(&self) -> ();
+}
+
+#[cfg(test)]
+impl<T> Testable for T
+where
+    T: Fn(),
+{
+    fn run(&self) {
+        print!("{} ...\t", core::any::type_name::<T>());
+        self();
+        println!("[ok]");
+    }
+}
+
+#[cfg(test)]
+pub fn test_runner(tests: &[&dyn Testable]) {
+    println!("Running {} tests in values.rs", tests.len());
+    for test in tests {
+        test.run();
+    }
+}
+
+#[cfg(test)]
+entry_point!(main);
+#[cfg(test)]
+fn main() {
+    test_main();
+}
+
+/// Tokenizes `css` as the value of a declaration.
+fn tokens(css: &str) -> Vec<CssToken> {
+    CssTokenizer::new(String::from(css)).collect()
+}
+
+fn color(css: &str) -> Option<Color> {
+    parse_color(&tokens(css))
+}
+
+#[test_case]
+fn named_colors() {
+    assert_eq!(color("red"), Some(Color::rgb(255, 0, 0)));
+    assert_eq!(color("Navy"), Some(Color::rgb(0, 0, 128)));
+    assert_eq!(color("orange"), Some(Color::rgb(255, 165, 0)));
+    assert_eq!(color("transparent"), Some(Color::TRANSPARENT));
+    assert_eq!(color("reddish"), None);
+}
+
+#[test_case]
+fn hex_colors() {
+    assert_eq!(color("#f00"), Some(Color::rgb(255, 0, 0)));
+    assert_eq!(color("#0f08"), Some(Color::rgba(0, 255, 0, 136)));
+    assert_eq!(color("#1A2b3c"), Some(Color::rgb(0x1a, 0x2b, 0x3c)));
+    assert_eq!(color("#00000080"), Some(Color::rgba(0, 0, 0, 128)));
+    assert_eq!(color("#12345"), None);
+    assert_eq!(color("#ggg"), None);
+}
+
+#[test_case]
+fn rgb_functions() {
+    assert_eq!(color("rgb(1, 2, 3)"), Some(Color::rgb(1, 2, 3)));
+    assert_eq!(color("RGB(1,2,3)"), Some(Color::rgb(1, 2, 3)));
+    assert_eq!(color("rgba(1, 2, 3, 0.5)"), Some(Color::rgba(1, 2, 3, 128)));
+    assert_eq!(color("rgb(1, 2, 3, 0)"), Some(Color::rgba(1, 2, 3, 0)));
+    assert_eq!(color("rgb(100%, 50%, 0%)"), Some(Color::rgb(255, 128, 0)));
+    assert_eq!(color("rgb(300, -1, 2.4)"), Some(Color::rgb(255, 0, 2)));
+    assert_eq!(color("rgb(1 2 3)"), Some(Color::rgb(1, 2, 3)));
+    assert_eq!(color("rgb(1 2 3 / 25%)"), Some(Color::rgba(1, 2, 3, 64)));
+}
+
+#[test_case]
+fn invalid_rgb_functions() {
+    assert_eq!(color("rgb(1, 2)"), None);
+    assert_eq!(color("rgb(1, 2, 3, 4, 5)"), None);
+    assert_eq!(color("rgb(1, 2 3)"), None);
+    assert_eq!(color("rgb(1, 2%, 3)"), None);
+    assert_eq!(color("rgb(1 2 3 0.5)"), None);
+    assert_eq!(color("rgb(1, 2, 3"), None);
+    assert_eq!(color("hsl(1, 2%, 3%)"), None);
+}
+
+#[test_case]
+fn to_u32() {
+    assert_eq!(Color::rgb(0x12, 0x34, 0x56).to_u32(), 0x123456);
+}
+
+fn length(css: &str) -> Option<Length> {
+    parse_length(&tokens(css))
+}
+
+#[test_case]
+fn lengths() {
+    assert_eq!(length("10px"), Some(Length::Px(10.0)));
+    assert_eq!(length("-1.5PX"), Some(Length::Px(-1.5)));
+    assert_eq!(length("2em"), Some(Length::Em(2.0)));
+    assert_eq!(length("0.5rem"), Some(Length::Rem(0.5)));
+    assert_eq!(length("50%"), Some(Length::Percentage(50.0)));
+    assert_eq!(length("0"), Some(Length::Px(0.0)));
+    assert_eq!(length("10"), None);
+    assert_eq!(length("10pt"), None);
+    assert_eq!(length("auto"), None);
+}
+
+#[test_case]
+fn length_resolution() {
+    assert_eq!(Length::Em(2.0).absolutize(10.0, 20.0), Length::Px(20.0));
+    assert_eq!(Length::Rem(2.0).absolutize(10.0, 20.0), Length::Px(40.0));
+    assert_eq!(
+        Length::Percentage(10.0).absolutize(10.0, 20.0),
+        Length::Percentage(10.0)
+    );
+    assert_eq!(Length::Px(3.0).to_px(200.0), 3.0);
+    assert_eq!(Length::Percentage(10.0).to_px(200.0), 20.0);
+}
@@ -102,6 +102,109 @@ void Route(char *response, char *path) {
     BuildResponse(response, 200, body);
     return;
   }
+  if (strcmp(path, "/login.html") == 0) {
+    char *body =
+        "<html>\n"
+        "  <body>\n"
+        "    <h1>Login</h1>\n"
+        "    <form method=\"post\" action=\"/login\">\n"
+        "      <p>User: <input name=\"user\" value=\"guest\"></p>\n"
+        "      <p>Password: <input type=\"password\" name=\"password\"></p>\n"
+        "      <p><button>Log in</button></p>\n"
+        "    </form>\n"
+        "  </body>\n"
+        "</html>\n";
+    BuildResponse(response, 200, body);
+    return;
+  }
+  char *body =
+      "<html>\n"
+      "  <body>\n"
+      "    <h1>Error!</h1>\n"
+      "    <p>Page is not found.</p>\n"
+      " </body>\n"
+      "</html>\n";
+  BuildResponse(response, 404, body);
+}
+
+// Returns the body of `request`, which follows the empty line after the headers. Returns an
+// empty string if there's no body.
+char *RequestBody(char *request) {
+  for (char *p = request; *p; p++) {
+    if (strncmp(p, "\n\n", 2) == 0)
+      return p + 2;
+    if (strncmp(p, "\r\n\r\n", 4) == 0)
+      return p + 4;
+  }
+  return "";
+}
+
+int HexDigit(char c) {
+  if ('0' <= c && c <= '9')
+    return c - '0';
+  if ('a' <= c && c <= 'f')
+    return c - 'a' + 10;
+  if ('A' <= c && c <= 'F')
+    return c - 'A' + 10;
+  return -1;
+}
+
+// Copies the value of `name` in an application/x-www-form-urlencoded `body` to `value`, which
+// has `size` bytes, decoding '+' and percent-encoded bytes. Characters special in HTML are
+// replaced with '?' so that the value can be put in a page as it is. `value` is empty if `name`
+// isn't in `body`.
+void FormValue(char *body, char *name, char *value, int size) {
+  int name_len = strlen(name);
+  value[0] = '\0';
+  char *p = body;
+  while (*p) {
+    if (strncmp(p, name, name_len) == 0 && p[name_len] == '=') {
+      p += name_len + 1;
+      int i = 0;
+      while (*p && *p != '&' && i < size - 1) {
+        char c = *p++;
+        if (c == '+') {
+          c = ' ';
+        } else if (c == '%' && HexDigit(p[0]) >= 0 && HexDigit(p[1]) >= 0) {
+          c = HexDigit(p[0]) * 16 + HexDigit(p[1]);
+          p += 2;
+        }
+        if (c == '<' || c == '>' || c == '&' || c == '"')
+          c = '?';
+        value[i++] = c;
+      }
+      value[i] = '\0';
+      return;
+    }
+    // Skip to the next name-value pair.
+    while (*p && *p != '&')
+      p++;
+    if (*p == '&')
+      p++;
+  }
+}
+
+void RoutePost(char *response, char *path, char *request_body) {
+  if (strcmp(path, "/login") == 0) {
+    char user[64];
+    char password[64];
+    FormValue(request_body, "user", user, sizeof(user));
+    FormValue(request_body, "password", password, sizeof(password));
+
+    char body[256];
+    strcpy(body, "<html>\n  <body>\n    <h1>");
+    if (strcmp(password, "password") == 0) {
+      strcat(body, "Welcome, ");
+      strcat(body, user);
+      strcat(body, "!");
+    } else {
+      strcat(body, "Wrong password for ");
+      strcat(body, user);
+    }
+    strcat(body, "</h1>\n    <p><a href=\"/login.html\">Back</a></p>\n  </body>\n</html>\n");
+    BuildResponse(response, 200, body);
+    return;
+  }
   char *body =
       "<html>\n"
       "  <body>\n"
@@ -149,7 +252,8 @@ void StartServer() {
   while (1) {
     Println("Log: Waiting for a request...\n");
 
-    char request[SIZE_REQUEST];
+    // One more byte for the null terminator.
+    char request[SIZE_REQUEST + 1];
     int size = -1;
 
     // In TCP, a request is received by accept() and read().
@@ -171,9 +275,13 @@ void StartServer() {
       exit(EXIT_FAILURE);
     }
 
+    request[size] = '\0';
+
     Println("----- request -----");
     Println(request);
 
+    // Find the body before strtok() breaks the request into pieces.
+    char *body = RequestBody(request);
     char *method = strtok(request, " ");
     char *path = strtok(NULL, " ");
 
@@ -181,8 +289,10 @@ void StartServer() {
 
     if (strcmp(method, "GET") == 0) {
       Route(response, path);
+    } else if (strcmp(method, "POST") == 0) {
+      RoutePost(response, path, body);
     } else {
-      BuildResponse(response, 500, "Only GET method is supported.");
+      BuildResponse(response, 500, "Only GET and POST methods are supported.");
     }
 
     size = -1;
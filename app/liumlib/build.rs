@@ -1,6 +1,7 @@
 #![feature(array_map)]
 
 use std::env;
+use std::fs;
 use std::path::Path;
 use std::process::Command;
 
@@ -11,15 +12,54 @@ fn get_object_name(s: &str) -> String {
     v.join(".")
 }
 
+/// Converts the bitmap font of liumOS (../../src/font.txt) into a Rust array, like
+/// tools/fontmaker does for the kernel. Each glyph is a header line `0xNN` followed by 16 lines
+/// of 8 characters, where `*` is a pixel to be drawn.
+fn generate_font(out_dir: &str) {
+    let src = "../../src/font.txt";
+    let txt = fs::read_to_string(src).expect("failed to read font.txt");
+    let mut font = vec![[0u8; 16]; 256];
+    let mut code = None;
+    let mut row = 0;
+    for line in txt.lines() {
+        let line = line.trim();
+        if let Some(hex) = line.strip_prefix("0x") {
+            code = Some(usize::from_str_radix(hex, 16).expect("invalid glyph code"));
+            row = 0;
+            continue;
+        }
+        if let (Some(c), true) = (code, line.len() == 8 && row < 16) {
+            font[c][row] = line
+                .bytes()
+                .fold(0, |bits, b| (bits << 1) | (b == b'*') as u8);
+            row += 1;
+        }
+    }
+
+    let glyphs: Vec<String> = font.iter().map(|g| format!("    {:?},", g)).collect();
+    let gen = format!(
+        "pub static FONT: [[u8; GLYPH_HEIGHT]; 256] = [\n{}\n];\n",
+        glyphs.join("\n")
+    );
+    fs::write(format!("{}/font.gen.rs", out_dir), gen).expect("failed to write font.gen.rs");
+    println!("cargo:rerun-if-changed={}", src);
+}
+
 fn main() {
-    let srcs = ["entry.S", "syscall.S"];
-    let objs = srcs.map(|e| get_object_name(e)).to_vec();
+    // The entry of the process is the one of std when it's built with std.
+    let srcs: &[&str] = if env::var("CARGO_FEATURE_STD").is_ok() {
+        &["syscall.S"]
+    } else {
+        &["entry.S", "syscall.S"]
+    };
+    let objs: Vec<String> = srcs.iter().map(|e| get_object_name(e)).collect();
     let out_dir = env::var("OUT_DIR").unwrap();
+    generate_font(&out_dir);
     let llvm_cc_path = env::var("LLVM_CC").unwrap();
     let llvm_ar_path = env::var("LLVM_AR").unwrap();
 
     // Build srcs
-    for f in &srcs {
+    for f in srcs {
         let src = format!("src/{}", f);
         let dst = format!("{}/{}", out_dir, get_object_name(f));
         println!("{:?} => {:?}", src, dst);
@@ -39,6 +39,7 @@ char* strcpy(char* dest, const char* src) {
     dest++;
     src++;
   }
+  *dest = '\0';
   return start;
 }
 
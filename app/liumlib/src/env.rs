@@ -0,0 +1,76 @@
+// The command line arguments and the environment variables of the process, which the kernel puts
+// on the stack when it starts the process. The shell of liumOS sets the variables by the words of
+// NAME=value before a command, e.g. `BROWSER_DEBUG=1 browser-rs`, like sh.
+
+use crate::*;
+#[cfg(feature = "std")]
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ptr::addr_of;
+
+static mut ARGS: Vec<&str> = Vec::new();
+/// The variables as NAME=value.
+static mut VARS: Vec<&str> = Vec::new();
+
+pub fn args() -> &'static Vec<&'static str> {
+    #[cfg(feature = "std")]
+    setup_from_std();
+    unsafe { &*addr_of!(ARGS) }
+}
+
+/// Returns the value of the environment variable `name`, or None if it isn't set.
+pub fn var(name: &str) -> Option<&'static str> {
+    vars().find(|(n, _)| *n == name).map(|(_, value)| value)
+}
+
+/// Returns the names and the values of the environment variables.
+pub fn vars() -> impl Iterator<Item = (&'static str, &'static str)> {
+    #[cfg(feature = "std")]
+    setup_from_std();
+    unsafe { &*addr_of!(VARS) }
+        .iter()
+        .filter_map(|var| var.split_once('='))
+}
+
+/// Reads the arguments, and the variables after the null pointer which ends `argv`.
+/// https://uclibc.org/docs/psABI-x86_64.pdf Figure 3.9: Initial Process Stack
+///
+/// # Safety
+///
+/// This function should be called only from setup_liumlib
+pub unsafe fn setup(argc: usize, argv: *const *const u8) {
+    for i in 0..argc {
+        let p = argv.add(i);
+        let s = core::slice::from_raw_parts(*p, strlen(*p));
+        let s = core::str::from_utf8(s);
+        match s {
+            Ok(s) => ARGS.push(s),
+            Err(e) => panic!("Failed to convert argv to utf8 str: {}", e),
+        }
+    }
+    let mut envp = argv.add(argc + 1);
+    while !(*envp).is_null() {
+        let s = core::slice::from_raw_parts(*envp, strlen(*envp));
+        // The variables which aren't UTF-8 are left for other programs.
+        if let Ok(s) = core::str::from_utf8(s) {
+            VARS.push(s);
+        }
+        envp = envp.add(1);
+    }
+}
+
+/// Reads the arguments and the variables from std the first time they're used, since the process
+/// doesn't start from entry_point! when it's built with std.
+#[cfg(feature = "std")]
+fn setup_from_std() {
+    static READ: std::sync::Once = std::sync::Once::new();
+    READ.call_once(|| {
+        let leak = |s: String| &*alloc::boxed::Box::leak(s.into_boxed_str());
+        unsafe {
+            ARGS = std::env::args().map(leak).collect();
+            VARS = std::env::vars()
+                .map(|(name, value)| leak(alloc::format!("{}={}", name, value)))
+                .collect();
+        }
+    });
+}
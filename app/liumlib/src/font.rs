@@ -0,0 +1,19 @@
+// The bitmap font of liumOS, generated from src/font.txt by build.rs.
+
+/// Width of a glyph in pixels.
+pub const GLYPH_WIDTH: usize = 8;
+/// Height of a glyph in pixels.
+pub const GLYPH_HEIGHT: usize = 16;
+
+include!(concat!(env!("OUT_DIR"), "/font.gen.rs"));
+
+/// Returns the glyph of `c`. Each byte is a row from the top, and the most significant bit is the
+/// leftmost pixel.
+pub fn glyph(c: u8) -> &'static [u8; GLYPH_HEIGHT] {
+    &FONT[c as usize]
+}
+
+/// Returns true if the pixel at (`x`, `y`) of the glyph of `c` should be drawn.
+pub fn is_pixel_set(c: u8, x: usize, y: usize) -> bool {
+    x < GLYPH_WIDTH && y < GLYPH_HEIGHT && (glyph(c)[y] >> (GLYPH_WIDTH - 1 - x)) & 1 == 1
+}
@@ -0,0 +1,160 @@
+// Files and directories over the file syscalls of Linux, like File and read_dir of std. liumOS
+// doesn't have a file system for applications yet, so its kernel keeps the files which
+// applications create in memory until it shuts down, and read_dir works only on Linux.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use crate::*;
+
+const SEEK_SET: u32 = 0;
+const SEEK_CUR: u32 = 1;
+const SEEK_END: u32 = 2;
+
+/// Where `File::seek()` moves the offset from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeekFrom {
+    Start(u64),
+    End(i64),
+    Current(i64),
+}
+
+/// An open file, which is closed when it's dropped.
+#[derive(Debug)]
+pub struct File {
+    fd: FileDescriptor,
+}
+
+impl File {
+    /// Opens `path` to read it. Returns None if it can't be opened, e.g. it doesn't exist.
+    pub fn open(path: &str) -> Option<Self> {
+        Self::open_with(path, O_RDONLY, 0)
+    }
+
+    /// Opens `path` to write it, creating it if it doesn't exist, or emptying it if it does.
+    pub fn create(path: &str) -> Option<Self> {
+        Self::open_with(path, O_WRONLY | O_CREAT | O_TRUNC, 0o664)
+    }
+
+    /// Opens `path` to write at its end, creating it if it doesn't exist.
+    pub fn append(path: &str) -> Option<Self> {
+        Self::open_with(path, O_WRONLY | O_CREAT | O_APPEND, 0o664)
+    }
+
+    /// Opens `path` with `flags`, e.g. O_RDWR | O_CREAT, and `mode` of a created file.
+    pub fn open_with(path: &str, flags: u32, mode: u32) -> Option<Self> {
+        Some(Self {
+            fd: open(path, flags, mode)?,
+        })
+    }
+
+    pub fn file_descriptor(&self) -> &FileDescriptor {
+        &self.fd
+    }
+
+    /// Reads into `buf` from the offset and returns how many bytes are read, which is 0 at the
+    /// end of the file.
+    pub fn read(&self, buf: &mut [u8]) -> Option<usize> {
+        let len = unsafe { sys_read(self.fd.fd, buf.as_mut_ptr(), buf.len()) };
+        if len < 0 {
+            None
+        } else {
+            Some(len as usize)
+        }
+    }
+
+    /// Reads from the offset until the end of the file.
+    pub fn read_to_end(&self) -> Option<Vec<u8>> {
+        read_to_end(self.fd.fd)
+    }
+
+    /// Writes some of `buf` at the offset and returns how many bytes are written.
+    pub fn write(&self, buf: &[u8]) -> Option<usize> {
+        let len = unsafe { sys_write(self.fd.fd, buf.as_ptr(), buf.len()) };
+        if len < 0 {
+            None
+        } else {
+            Some(len as usize)
+        }
+    }
+
+    /// Writes the whole `buf` at the offset.
+    pub fn write_all(&self, mut buf: &[u8]) -> Option<()> {
+        while !buf.is_empty() {
+            match self.write(buf)? {
+                0 => return None,
+                len => buf = &buf[len..],
+            }
+        }
+        Some(())
+    }
+
+    /// Moves the offset, where the next read or write starts, and returns it.
+    pub fn seek(&self, pos: SeekFrom) -> Option<u64> {
+        let (offset, whence) = match pos {
+            SeekFrom::Start(offset) => (offset as i64, SEEK_SET),
+            SeekFrom::End(offset) => (offset, SEEK_END),
+            SeekFrom::Current(offset) => (offset, SEEK_CUR),
+        };
+        let offset = unsafe { sys_lseek(self.fd.fd as u32, offset, whence) };
+        if offset < 0 {
+            None
+        } else {
+            Some(offset as u64)
+        }
+    }
+
+    /// Truncates or extends the file to `size` bytes.
+    pub fn set_len(&self, size: usize) -> Option<()> {
+        if ftruncate(&self.fd, size) < 0 {
+            None
+        } else {
+            Some(())
+        }
+    }
+}
+
+/// A file or a directory in a directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    pub name: String,
+    pub inode: u64,
+}
+
+/// Returns the entries of the directory `path` except `.` and `..` in the order the file system
+/// has them, or None if it can't be read.
+pub fn read_dir(path: &str) -> Option<Vec<DirEntry>> {
+    let fd = open(path, O_RDONLY, 0)?;
+    let mut entries = Vec::new();
+    let mut buf = [0u8; 512];
+    loop {
+        let len = getdents64(&fd, &mut buf);
+        if len < 0 {
+            return None;
+        }
+        if len == 0 {
+            return Some(entries);
+        }
+        let mut cur = 0;
+        while cur < len as usize {
+            let entry = unsafe { &*(buf.as_ptr().add(cur) as *const DirectoryEntry) };
+            let size = entry.size();
+            if size < size_of::<DirectoryEntry>() || cur + size > len as usize {
+                return None;
+            }
+            let name = &buf[cur + size_of::<DirectoryEntry>()..cur + size];
+            let name = match name.iter().position(|&b| b == 0) {
+                Some(end) => &name[..end],
+                None => name,
+            };
+            if name != b"." && name != b".." {
+                entries.push(DirEntry {
+                    name: String::from_utf8_lossy(name).into_owned(),
+                    inode: entry.inode(),
+                });
+            }
+            cur += size;
+        }
+    }
+}
@@ -0,0 +1,267 @@
+// Keyboard and pointer events of the window. liumOS sends the keys to the process running in the
+// foreground, and the pointer to the process whose window is under it, as events read from the
+// file at INPUT_PATH. Unlike the standard input, they tell when keys are released and which keys
+// are pressed with Shift or Ctrl. Linux has no such file, so applications read the standard input
+// instead there.
+
+use crate::*;
+use alloc::vec::Vec;
+
+/// The file which the events are read from.
+pub const INPUT_PATH: &str = "/dev/liumos/input";
+
+/// The types of `RawInputEvent`.
+pub const EVENT_TYPE_KEY: u16 = 1;
+pub const EVENT_TYPE_POINTER: u16 = 2;
+
+/// The bits of the pressed buttons in `RawInputEvent`.
+pub const BUTTON_LEFT: u8 = 0b001;
+pub const BUTTON_RIGHT: u8 = 0b010;
+pub const BUTTON_MIDDLE: u8 = 0b100;
+
+// The bits of a key ID of liumOS (see src/keyid.h).
+const KEY_ID_BREAK: u16 = 0x8000;
+const KEY_ID_EXTENDED: u16 = 0x4000;
+const KEY_ID_SHIFT: u16 = 0x0100;
+const KEY_ID_CTRL: u16 = 0x0200;
+const KEY_ID_CODE: u16 = 0x00ff;
+
+/// An event as the kernel writes it, which `EventDecoder` turns into `Event`s.
+/// c.f. struct InputEvent in src/syscall.cc
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RawInputEvent {
+    pub kind: u16,
+    /// The key ID of a key event, including whether it's released and the modifiers.
+    pub key_id: u16,
+    /// The position of the pointer relative to the window.
+    pub x: i16,
+    pub y: i16,
+    /// The bits of the pressed buttons, e.g. BUTTON_LEFT.
+    pub buttons: u8,
+    padding: [u8; 7],
+}
+
+impl RawInputEvent {
+    /// Returns the event of the key `key_id`.
+    pub fn key(key_id: u16) -> Self {
+        Self {
+            kind: EVENT_TYPE_KEY,
+            key_id,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the event of the pointer at (`x`, `y`) with `buttons` pressed.
+    pub fn pointer(x: i16, y: i16, buttons: u8) -> Self {
+        Self {
+            kind: EVENT_TYPE_POINTER,
+            x,
+            y,
+            buttons,
+            ..Self::default()
+        }
+    }
+}
+
+/// A key of the keyboard. The letters and the symbols are told by the characters they type, which
+/// depend on Shift. The escape sequences of a terminal on the serial port arrive as their
+/// characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Escape,
+    Enter,
+    Backspace,
+    Tab,
+    Delete,
+    Insert,
+    Up,
+    Down,
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Shift,
+    Ctrl,
+    Alt,
+    /// F1 to F12.
+    Function(u8),
+    /// A key without a name here, by the code in its key ID.
+    Other(u16),
+}
+
+impl Key {
+    /// Returns the key of the key ID `key_id` without its attributes.
+    fn from_key_id(key_id: u16) -> Self {
+        let code = key_id & KEY_ID_CODE;
+        if key_id & KEY_ID_EXTENDED == 0 {
+            return match code as u8 {
+                b'\n' | b'\r' => Key::Enter,
+                0x08 | 0x7f => Key::Backspace,
+                b'\t' => Key::Tab,
+                c => Key::Char(c as char),
+            };
+        }
+        match code {
+            0x00 => Key::Escape,
+            0x01..=0x0c => Key::Function(code as u8),
+            0x10 | 0x11 => Key::Shift,
+            0x12 | 0x13 => Key::Ctrl,
+            0x14 | 0x15 => Key::Alt,
+            0x16 => Key::Delete,
+            0x17 => Key::Insert,
+            0x1c => Key::Up,
+            0x1d => Key::Down,
+            0x1e => Key::Left,
+            0x1f => Key::Right,
+            0x20 => Key::PageUp,
+            0x21 => Key::PageDown,
+            0x22 => Key::Home,
+            0x23 => Key::End,
+            0x40 => Key::Backspace,
+            0x41 => Key::Tab,
+            0x42 => Key::Enter,
+            _ => Key::Other(code),
+        }
+    }
+}
+
+/// The modifier keys held while a key is pressed or released.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    Left,
+    Right,
+    Middle,
+}
+
+impl Button {
+    const ALL: [(Button, u8); 3] = [
+        (Button::Left, BUTTON_LEFT),
+        (Button::Right, BUTTON_RIGHT),
+        (Button::Middle, BUTTON_MIDDLE),
+    ];
+}
+
+/// An input event. The positions are in px relative to the top-left corner of the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    KeyDown { key: Key, modifiers: Modifiers },
+    KeyUp { key: Key, modifiers: Modifiers },
+    PointerMove { x: i32, y: i32 },
+    PointerDown { button: Button, x: i32, y: i32 },
+    PointerUp { button: Button, x: i32, y: i32 },
+}
+
+/// Turns raw events into `Event`s. The kernel sends the state of the pointer, so the decoder
+/// remembers the last one to tell what changed.
+#[derive(Debug, Default)]
+pub struct EventDecoder {
+    position: Option<(i32, i32)>,
+    buttons: u8,
+}
+
+impl EventDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the events which `raw` makes, which are none if nothing changed, and a move before
+    /// the buttons pressed or released at the new position.
+    pub fn decode(&mut self, raw: &RawInputEvent) -> Vec<Event> {
+        let mut events = Vec::new();
+        match raw.kind {
+            EVENT_TYPE_KEY => {
+                let key = Key::from_key_id(raw.key_id);
+                let modifiers = Modifiers {
+                    shift: raw.key_id & KEY_ID_SHIFT != 0,
+                    ctrl: raw.key_id & KEY_ID_CTRL != 0,
+                };
+                events.push(if raw.key_id & KEY_ID_BREAK == 0 {
+                    Event::KeyDown { key, modifiers }
+                } else {
+                    Event::KeyUp { key, modifiers }
+                });
+            }
+            EVENT_TYPE_POINTER => {
+                let (x, y) = (raw.x as i32, raw.y as i32);
+                if self.position != Some((x, y)) {
+                    self.position = Some((x, y));
+                    events.push(Event::PointerMove { x, y });
+                }
+                for (button, bit) in Button::ALL.iter() {
+                    match (self.buttons & bit != 0, raw.buttons & bit != 0) {
+                        (false, true) => events.push(Event::PointerDown {
+                            button: *button,
+                            x,
+                            y,
+                        }),
+                        (true, false) => events.push(Event::PointerUp {
+                            button: *button,
+                            x,
+                            y,
+                        }),
+                        _ => {}
+                    }
+                }
+                self.buttons = raw.buttons;
+            }
+            _ => {}
+        }
+        events
+    }
+}
+
+/// The events of the window, read from INPUT_PATH.
+#[derive(Debug)]
+pub struct InputDevice {
+    fd: FileDescriptor,
+    decoder: EventDecoder,
+}
+
+impl InputDevice {
+    /// Opens the events of the window, or returns None if the system doesn't send them, e.g.
+    /// Linux.
+    pub fn open() -> Option<Self> {
+        let fd = open(INPUT_PATH, O_RDONLY, 0)?;
+        Some(Self {
+            fd,
+            decoder: EventDecoder::new(),
+        })
+    }
+
+    /// Returns the file descriptor to wait for events with `poll()`.
+    pub fn file_descriptor(&self) -> &FileDescriptor {
+        &self.fd
+    }
+
+    /// Reads the events which have arrived, waiting for one if there is none. Returns None if
+    /// reading fails.
+    pub fn read(&mut self) -> Option<Vec<Event>> {
+        let mut buf = [RawInputEvent::default(); 16];
+        let size = size_of::<RawInputEvent>();
+        let len = unsafe {
+            sys_read(
+                self.fd.number(),
+                buf.as_mut_ptr() as *mut u8,
+                size * buf.len(),
+            )
+        };
+        if len < 0 {
+            return None;
+        }
+        let mut events = Vec::new();
+        for raw in &buf[..len as usize / size] {
+            events.extend(self.decoder.decode(raw));
+        }
+        Some(events)
+    }
+}
@@ -1,21 +1,44 @@
-#![no_std]
-#![feature(alloc_error_handler)]
+// With the std feature, liumlib is built with std for the host, e.g. to test the libraries of
+// applications with the test harness of Rust. Then std provides the allocator, the panic handler
+// and the entry of the process, and the syscalls are the ones of Linux.
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(not(feature = "std"), feature(alloc_error_handler))]
 #![feature(rustc_private)]
 
 extern crate alloc;
 
+pub mod env;
+pub mod font;
+pub mod fs;
 pub mod gui;
-
+pub mod input;
+pub mod log;
+pub mod net;
+pub mod poll;
+pub mod rand;
+pub mod surface;
+pub mod time;
+
+#[cfg(not(feature = "std"))]
 use alloc::alloc::{GlobalAlloc, Layout};
 use alloc::string::String;
 use alloc::vec::Vec;
 use core::fmt;
 use core::mem::size_of;
+#[cfg(not(feature = "std"))]
 use core::panic::PanicInfo;
 use core::ptr::null_mut;
 
+pub const O_RDONLY: u32 = 0;
+pub const O_WRONLY: u32 = 1;
 pub const O_RDWR: u32 = 2;
 pub const O_CREAT: u32 = 1 << 6;
+pub const O_TRUNC: u32 = 0o1000;
+pub const O_APPEND: u32 = 0o2000;
+pub const O_NONBLOCK: u32 = 0o4000;
+
+pub const F_GETFL: u32 = 3;
+pub const F_SETFL: u32 = 4;
 
 pub const PROT_READ: u32 = 0x01;
 pub const PROT_WRITE: u32 = 0x02;
@@ -95,23 +118,38 @@ impl SockAddr {
     }
 }
 
+/// The address of an IPv4 socket with the padding, which Linux needs for connect, bind and
+/// accept.
+/// https://elixir.bootlin.com/linux/v4.15/source/include/uapi/linux/in.h#L232
+#[repr(C)]
+#[derive(Debug, Default)]
+struct SockAddrIn {
+    sin_family: u16,
+    sin_port: u16,
+    in_addr: u32,
+    padding: [u8; 8],
+}
+
 #[link(name = "liumos", kind = "static")]
 extern "C" {
-    fn sys_read(fp: i32, str: *mut u8, len: usize);
-    fn sys_write(fp: i32, str: *const u8, len: usize);
+    fn sys_read(fp: i32, str: *mut u8, len: usize) -> i64;
+    fn sys_write(fp: i32, str: *const u8, len: usize) -> i64;
     fn sys_open(filename: *const u8, flags: u32, mode: u32) -> i32;
+    fn sys_lseek(fd: u32, offset: i64, whence: u32) -> i64;
     pub fn sys_close(fp: i32) -> i32;
+    fn sys_poll(fds: *mut poll::PollFd, nfds: usize, timeout: i32) -> i32;
     fn sys_mmap(addr: *mut u8, size: usize, prot: u32, flags: u32, fd: i32, offset: u32)
         -> *mut u8;
     fn sys_munmap(addr: *mut u8, size: usize) -> i32;
     fn sys_msync(addr: *mut u8, size: usize, flags: i32) -> i32;
+    fn sys_nanosleep(req: *const time::Timespec, rem: *mut time::Timespec) -> i32;
     fn sys_socket(domain: u32, socket_type: u32, protocol: u32) -> i32;
     fn sys_sendto(
         sockfd: u32,
         buf: *mut u8,
         len: usize,
         flags: u32,
-        dest_addr: &SockAddr,
+        dest_addr: *const SockAddr,
         addrlen: usize,
     ) -> i64;
     fn sys_recvfrom(
@@ -119,11 +157,27 @@ extern "C" {
         buf: *mut u8,
         len: usize,
         flags: u32,
-        src_addr: &mut SockAddr,
+        src_addr: *mut SockAddr,
         addrlen: usize,
     ) -> i64;
+    fn sys_connect(sockfd: u32, addr: *const SockAddrIn, addrlen: usize) -> i32;
+    fn sys_accept(sockfd: u32, addr: *mut SockAddrIn, addrlen: *mut u32) -> i32;
+    fn sys_shutdown(sockfd: u32, how: u32) -> i32;
+    fn sys_bind(sockfd: u32, addr: *const SockAddrIn, addrlen: usize) -> i32;
+    fn sys_listen(sockfd: u32, backlog: u32) -> i32;
+    fn sys_getsockname(sockfd: u32, addr: *mut SockAddrIn, addrlen: *mut u32) -> i32;
+    fn sys_getsockopt(
+        sockfd: u32,
+        level: u32,
+        optname: u32,
+        optval: *mut i32,
+        optlen: *mut u32,
+    ) -> i32;
     fn sys_exit(code: i32) -> !;
+    fn sys_fcntl(fd: u32, cmd: u32, arg: u64) -> i32;
     fn sys_ftruncate(fd: u32, size: usize) -> i32;
+    fn sys_clock_gettime(clock_id: u32, tp: *mut time::Timespec) -> i32;
+    fn sys_getrandom(buf: *mut u8, len: usize, flags: u32) -> i64;
     pub fn sys_getdents64(fd: u32, buf: *mut u8, buf_size: usize) -> i32;
 }
 
@@ -224,10 +278,75 @@ pub fn recvfrom(
     unsafe { sys_recvfrom(sockfd.fd as u32, buf.as_mut_ptr(), len, flags, src_addr, 0) }
 }
 
+/// Gets or sets the flags of `fd` by `cmd`, e.g. F_SETFL with O_NONBLOCK. Returns the flags for
+/// F_GETFL, or a negative errno if it fails.
+pub fn fcntl(fd: &FileDescriptor, cmd: u32, arg: u64) -> i32 {
+    unsafe { sys_fcntl(fd.fd as u32, cmd, arg) }
+}
+
 pub fn ftruncate(fd: &FileDescriptor, size: usize) -> i32 {
     unsafe { sys_ftruncate(fd.fd as u32, size) }
 }
 
+/// Writes `data` to the file `filename`, creating it if it doesn't exist or replacing its
+/// content if it does. Returns None if the file can't be written.
+pub fn write_file(filename: &str, data: &[u8]) -> Option<()> {
+    fs::File::create(filename)?.write_all(data)
+}
+
+/// Returns the content of the file `filename`, or None if it can't be read, e.g. it doesn't
+/// exist.
+pub fn read_file(filename: &str) -> Option<Vec<u8>> {
+    fs::File::open(filename)?.read_to_end()
+}
+
+/// Reads the file descriptor `fd`, e.g. 0 for the standard input, until the end. Returns None if
+/// it can't be read.
+pub fn read_to_end(fd: i32) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 512];
+    loop {
+        let len = unsafe { sys_read(fd, buf.as_mut_ptr(), buf.len()) };
+        if len < 0 {
+            return None;
+        }
+        if len == 0 {
+            return Some(data);
+        }
+        data.extend_from_slice(&buf[..len as usize]);
+    }
+}
+
+/// Reads the file descriptor `fd` until the end, the byte `end`, which isn't included, or `limit`
+/// bytes. The console of liumOS never reaches the end, so `end` like 0x04 (Ctrl+D) ends it.
+/// Returns None if it can't be read.
+pub fn read_until(fd: i32, end: u8, limit: usize) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 512];
+    while data.len() < limit {
+        let len = unsafe { sys_read(fd, buf.as_mut_ptr(), buf.len().min(limit - data.len())) };
+        if len < 0 {
+            return None;
+        }
+        let read = &buf[..len as usize];
+        match read.iter().position(|b| *b == end) {
+            Some(i) => {
+                data.extend_from_slice(&read[..i]);
+                break;
+            }
+            None if len == 0 => break,
+            None => data.extend_from_slice(read),
+        }
+    }
+    Some(data)
+}
+
+/// Returns the time stamp counter of the CPU, which counts cycles at a rate which isn't known, so
+/// durations are measured by `time::Instant` instead.
+pub fn rdtsc() -> u64 {
+    unsafe { core::arch::x86_64::_rdtsc() }
+}
+
 pub fn exit(code: i32) -> ! {
     unsafe {
         sys_exit(code);
@@ -247,6 +366,7 @@ impl fmt::Write for StdIoWriter {
     }
 }
 
+#[cfg(not(feature = "std"))]
 #[panic_handler]
 fn panic(info: &PanicInfo) -> ! {
     println!("PANIC!!!");
@@ -262,11 +382,14 @@ mod tests {
     }
 }
 
+// std has its own, which print the same.
+#[cfg(not(feature = "std"))]
 #[macro_export]
 macro_rules! print {
         ($($arg:tt)*) => (_print(format_args!($($arg)*)));
 }
 
+#[cfg(not(feature = "std"))]
 #[macro_export]
 macro_rules! println {
         () => ($crate::print!("\n"));
@@ -279,48 +402,167 @@ pub fn _print(args: fmt::Arguments) {
     fmt::write(&mut writer, args).unwrap();
 }
 
+#[cfg(not(feature = "std"))]
 trait MutableAllocator {
     fn alloc(&mut self, layout: Layout) -> *mut u8;
     fn dealloc(&mut self, _ptr: *mut u8, _layout: Layout);
 }
 
+#[cfg(not(feature = "std"))]
 const ALLOCATOR_BUF_SIZE: usize = 0x100000;
+
+// Every block is a multiple of this size and starts at a multiple of it, so that a free block can
+// hold a FreeBlock and the rest of a block which is split is a block too.
+#[cfg(not(feature = "std"))]
+const ALLOCATOR_BLOCK_SIZE: usize = size_of::<FreeBlock>();
+
+// A block which was deallocated, in the list of the free blocks sorted by their addresses.
+#[cfg(not(feature = "std"))]
+#[repr(align(16))]
+struct FreeBlock {
+    size: usize,
+    next: *mut FreeBlock,
+}
+
+// Allocates blocks below the water mark from the free blocks, and the others by raising the mark.
+// A deallocated block is merged with the free blocks next to it, and given back to the area above
+// the mark if it's the last one, so that memory is reused by later allocations.
+#[cfg(not(feature = "std"))]
 pub struct WaterMarkAllocator {
     buf: [u8; ALLOCATOR_BUF_SIZE],
     used_bytes: usize,
+    free_blocks: *mut FreeBlock,
 }
 
+#[cfg(not(feature = "std"))]
 pub struct GlobalAllocatorWrapper {
     allocator: WaterMarkAllocator,
 }
 
+#[cfg(not(feature = "std"))]
 #[global_allocator]
 static mut ALLOCATOR: GlobalAllocatorWrapper = GlobalAllocatorWrapper {
     allocator: WaterMarkAllocator {
         buf: [0; ALLOCATOR_BUF_SIZE],
         used_bytes: 0,
+        free_blocks: null_mut(),
     },
 };
 
+#[cfg(not(feature = "std"))]
 #[alloc_error_handler]
 fn alloc_error_handler(layout: alloc::alloc::Layout) -> ! {
     panic!("allocation error: {:?}", layout)
 }
 
+#[cfg(not(feature = "std"))]
+impl WaterMarkAllocator {
+    fn block_size(layout: &Layout) -> usize {
+        Self::align_up(layout.size().max(1), ALLOCATOR_BLOCK_SIZE)
+    }
+    fn align_up(addr: usize, align: usize) -> usize {
+        (addr + align - 1) / align * align
+    }
+    // The address of the first block.
+    fn start(&self) -> usize {
+        Self::align_up(self.buf.as_ptr() as usize, ALLOCATOR_BLOCK_SIZE)
+    }
+    fn end(&self) -> usize {
+        self.buf.as_ptr() as usize + ALLOCATOR_BUF_SIZE
+    }
+    fn water_mark(&self) -> usize {
+        self.start() + self.used_bytes
+    }
+    // Puts the block of `size` bytes at `addr` into the free blocks, merged with the ones next to
+    // it, or gives it back to the area above the water mark if it ends at the mark. The block must
+    // not overlap the free ones.
+    unsafe fn free(&mut self, addr: usize, size: usize) {
+        if size == 0 {
+            return;
+        }
+        let mut before_prev: *mut FreeBlock = null_mut();
+        let mut prev: *mut FreeBlock = null_mut();
+        let mut next = self.free_blocks;
+        while !next.is_null() && (next as usize) < addr {
+            before_prev = prev;
+            prev = next;
+            next = (*next).next;
+        }
+        let block = addr as *mut FreeBlock;
+        block.write(FreeBlock { size, next });
+        if !next.is_null() && addr + size == next as usize {
+            (*block).size += (*next).size;
+            (*block).next = (*next).next;
+        }
+        // The block which has the freed one, and the free block before it.
+        let (merged, before) = if prev.is_null() {
+            self.free_blocks = block;
+            (block, null_mut())
+        } else if prev as usize + (*prev).size == addr {
+            (*prev).size += (*block).size;
+            (*prev).next = (*block).next;
+            (prev, before_prev)
+        } else {
+            (*prev).next = block;
+            (block, prev)
+        };
+        if merged as usize + (*merged).size == self.water_mark() {
+            self.used_bytes = merged as usize - self.start();
+            if before.is_null() {
+                self.free_blocks = null_mut();
+            } else {
+                (*before).next = null_mut();
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
 impl MutableAllocator for WaterMarkAllocator {
     fn alloc(&mut self, layout: Layout) -> *mut u8 {
-        if self.used_bytes > ALLOCATOR_BUF_SIZE {
-            return null_mut();
+        let size = Self::block_size(&layout);
+        let align = layout.align().max(ALLOCATOR_BLOCK_SIZE);
+        unsafe {
+            // The first free block which has room for the aligned block.
+            let mut prev: *mut FreeBlock = null_mut();
+            let mut block = self.free_blocks;
+            while !block.is_null() {
+                let begin = block as usize;
+                let end = begin + (*block).size;
+                let addr = Self::align_up(begin, align);
+                if addr + size <= end {
+                    let next = (*block).next;
+                    if prev.is_null() {
+                        self.free_blocks = next;
+                    } else {
+                        (*prev).next = next;
+                    }
+                    self.free(begin, addr - begin);
+                    self.free(addr + size, end - addr - size);
+                    return addr as *mut u8;
+                }
+                prev = block;
+                block = (*block).next;
+            }
         }
-        self.used_bytes = (self.used_bytes + layout.align() - 1) / layout.align() * layout.align();
-        self.used_bytes += layout.size();
-        if self.used_bytes > ALLOCATOR_BUF_SIZE {
+        let mark = self.water_mark();
+        let addr = Self::align_up(mark, align);
+        if addr > self.end() || self.end() - addr < size {
             return null_mut();
         }
-        unsafe { self.buf.as_mut_ptr().add(self.used_bytes - layout.size()) }
+        self.used_bytes = addr + size - self.start();
+        unsafe {
+            self.free(mark, addr - mark);
+        }
+        addr as *mut u8
+    }
+    fn dealloc(&mut self, ptr: *mut u8, layout: Layout) {
+        unsafe {
+            self.free(ptr as usize, Self::block_size(&layout));
+        }
     }
-    fn dealloc(&mut self, _ptr: *mut u8, _layout: Layout) {}
 }
+#[cfg(not(feature = "std"))]
 unsafe impl GlobalAlloc for GlobalAllocatorWrapper {
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
         ALLOCATOR.allocator.alloc(layout)
@@ -342,28 +584,11 @@ unsafe fn strlen(s: *const u8) -> usize {
     count
 }
 
-static mut ARGS: Vec<&str> = Vec::new();
-
-pub mod env {
-    use alloc::vec::Vec;
-    pub fn args() -> &'static Vec<&'static str> {
-        unsafe { &super::ARGS }
-    }
-}
-
 /// # Safety
 ///
 /// This function should be called only from entry_point function
 pub unsafe fn setup_liumlib(argc: usize, argv: *const *const u8) {
-    for i in 0..argc {
-        let p = argv.add(i);
-        let s = core::slice::from_raw_parts(*p, strlen(*p));
-        let s = core::str::from_utf8(s);
-        match s {
-            Ok(s) => ARGS.push(&s),
-            Err(e) => panic!("Failed to convert argv to utf8 str: {}", e),
-        }
-    }
+    env::setup(argc, argv);
 }
 
 #[macro_export]
@@ -0,0 +1,246 @@
+// Logging with levels, like the log crate. Messages are written with the macros error!, warn!,
+// info!, debug! and trace!, which tell the module they are written in, and a `Filter` decides the
+// levels written for each module, e.g. `warn,net=debug` from a command line flag. They are written
+// to the console, or appended to a file, which liumOS keeps in memory until it shuts down.
+
+use crate::fs::File;
+use crate::*;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ptr::addr_of;
+
+/// How important a message is, from the most to the least.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// Something failed.
+    Error = 1,
+    /// Something went wrong but it's recovered from.
+    Warn,
+    /// What a user wants to know happened, e.g. a redirect.
+    Info,
+    /// Details to find why something went wrong, e.g. the requests sent.
+    Debug,
+    /// More details, e.g. each step of a loop.
+    Trace,
+}
+
+impl Level {
+    /// Parses a level by its name in any case, e.g. `warn`.
+    pub fn parse(name: &str) -> Option<Self> {
+        [
+            Level::Error,
+            Level::Warn,
+            Level::Info,
+            Level::Debug,
+            Level::Trace,
+        ]
+        .iter()
+        .copied()
+        .find(|level| level.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The most detailed level written for each module. The levels are None where nothing is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    default: Option<Level>,
+    /// The modules given, whose levels override the default one for them and their submodules.
+    modules: Vec<(String, Option<Level>)>,
+}
+
+impl Filter {
+    /// Creates a filter which writes the messages up to `default` from all the modules.
+    pub fn new(default: Option<Level>) -> Self {
+        Self {
+            default,
+            modules: Vec::new(),
+        }
+    }
+
+    /// Parses a filter separated by commas, each of which is a level for all the modules, a
+    /// module to write everything from, or a module and a level like `net=debug`. `off` is a level
+    /// which writes nothing. The modules may be given without the crate, e.g. `net` is
+    /// `browser_rs::net`. The messages up to Info are written if no level is given for all.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut filter = Self::new(Some(Level::Info));
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let (module, level) = match directive.split_once('=') {
+                Some((module, level)) => (Some(module.trim()), Some(level.trim())),
+                None => match parse_level(directive) {
+                    Ok(level) => {
+                        filter.default = level;
+                        continue;
+                    }
+                    Err(_) => (Some(directive), None),
+                },
+            };
+            let level = match level {
+                Some(level) => parse_level(level)?,
+                None => Some(Level::Trace),
+            };
+            match module {
+                Some(module) if !module.is_empty() => filter.set_module(module, level),
+                _ => return Err(format!("no module in {}", directive)),
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Writes the messages up to `level` from `module` and its submodules.
+    pub fn set_module(&mut self, module: &str, level: Option<Level>) {
+        self.modules.retain(|(m, _)| m != module);
+        self.modules.push((module.to_string(), level));
+    }
+
+    /// Returns whether the message of `level` from the module `target` is written. The module
+    /// given nearest to `target` decides it.
+    pub fn enabled(&self, level: Level, target: &str) -> bool {
+        let local = target.split_once("::").map(|(_, path)| path);
+        let max = self
+            .modules
+            .iter()
+            .filter(|(module, _)| {
+                contains(module, target) || local.is_some_and(|path| contains(module, path))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map_or(self.default, |(_, level)| *level);
+        max.is_some_and(|max| level <= max)
+    }
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self::new(Some(Level::Info))
+    }
+}
+
+/// Parses a level or `off`.
+fn parse_level(name: &str) -> Result<Option<Level>, String> {
+    if name.eq_ignore_ascii_case("off") {
+        return Ok(None);
+    }
+    Level::parse(name)
+        .map(Some)
+        .ok_or_else(|| format!("unknown level: {}", name))
+}
+
+/// Returns whether `path` is `module` or one of its submodules.
+fn contains(module: &str, path: &str) -> bool {
+    match path.strip_prefix(module) {
+        Some(rest) => rest.is_empty() || rest.starts_with("::"),
+        None => false,
+    }
+}
+
+/// Where the messages are written.
+#[derive(Debug)]
+pub enum Output {
+    Console,
+    /// A file opened to append to, e.g. by `File::append()`.
+    File(File),
+}
+
+#[derive(Debug)]
+struct Logger {
+    filter: Filter,
+    output: Output,
+}
+
+/// The messages up to Info are written to the console until it's set.
+static mut LOGGER: Option<Logger> = None;
+
+fn logger() -> Option<&'static Logger> {
+    unsafe { &*addr_of!(LOGGER) }.as_ref()
+}
+
+/// Writes the messages which `filter` passes to `output` from now on.
+pub fn init(filter: Filter, output: Output) {
+    unsafe {
+        LOGGER = Some(Logger { filter, output });
+    }
+}
+
+/// Returns whether the message of `level` from the module `target` is written, which the macros
+/// check before formatting it.
+pub fn enabled(level: Level, target: &str) -> bool {
+    match logger() {
+        Some(logger) => logger.filter.enabled(level, target),
+        None => level <= Level::Info,
+    }
+}
+
+/// Returns the line written for the message, e.g. `[WARN browser_rs::net] no response`.
+pub fn format_line(level: Level, target: &str, args: fmt::Arguments) -> String {
+    format!("[{} {}] {}\n", level, target, args)
+}
+
+/// Writes the message of `level` from the module `target` if it's enabled. The macros are
+/// usually used instead, which tell the module.
+pub fn log(level: Level, target: &str, args: fmt::Arguments) {
+    if !enabled(level, target) {
+        return;
+    }
+    let line = format_line(level, target, args);
+    match logger().map(|logger| &logger.output) {
+        // A message which can't be written is lost rather than written somewhere else.
+        Some(Output::File(file)) => {
+            let _ = file.write_all(line.as_bytes());
+        }
+        Some(Output::Console) | None => print!("{}", line),
+    }
+}
+
+/// Writes a message of `$level` from the module it's written in, e.g.
+/// `log!(Level::Warn, "no response from {}", addr)`.
+#[macro_export]
+macro_rules! log {
+    ($level:expr, $($arg:tt)+) => {{
+        let level = $level;
+        if $crate::log::enabled(level, module_path!()) {
+            $crate::log::log(level, module_path!(), format_args!($($arg)+));
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => ($crate::log!($crate::log::Level::Error, $($arg)+));
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => ($crate::log!($crate::log::Level::Warn, $($arg)+));
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => ($crate::log!($crate::log::Level::Info, $($arg)+));
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => ($crate::log!($crate::log::Level::Debug, $($arg)+));
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => ($crate::log!($crate::log::Level::Trace, $($arg)+));
+}
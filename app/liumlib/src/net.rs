@@ -0,0 +1,405 @@
+// TCP sockets over the socket syscalls of Linux, like TcpStream and TcpListener of std.
+// The kernel of liumOS doesn't have TCP sockets yet, so these work only when the application
+// runs on Linux, like the --tcp mode of httpclient and httpserver. On liumOS, creating a socket
+// fails with Unsupported, as do connect, listen, accept and shutdown.
+// A socket in the non-blocking mode returns WouldBlock instead of waiting, and poll tells when
+// it's ready.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::mem::size_of;
+use core::ptr::{null, null_mut};
+use core::time::Duration;
+
+use crate::poll::*;
+use crate::*;
+
+pub const AF_INET: u32 = 2;
+pub const SOCK_STREAM: u32 = 1;
+pub const SOCK_DGRAM: u32 = 2;
+
+/// Returns EPIPE instead of raising SIGPIPE when the peer has closed the connection.
+const MSG_NOSIGNAL: u32 = 0x4000;
+
+/// The number of connections waiting to be accepted.
+const BACKLOG: u32 = 128;
+
+/// Why a socket syscall failed, which is decoded from the negative errno it returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocketError {
+    PermissionDenied,
+    AddrInUse,
+    AddrNotAvailable,
+    ConnectionRefused,
+    ConnectionReset,
+    ConnectionAborted,
+    NotConnected,
+    TimedOut,
+    HostUnreachable,
+    NetworkUnreachable,
+    BrokenPipe,
+    WouldBlock,
+    /// A non-blocking connection is being established.
+    InProgress,
+    Interrupted,
+    InvalidInput,
+    /// The syscall, the address family or the operation isn't supported.
+    Unsupported,
+    /// Any other errno.
+    Other(i64),
+}
+
+impl SocketError {
+    /// Returns the error of `errno`, e.g. 111 for ECONNREFUSED.
+    /// https://elixir.bootlin.com/linux/v5.4.66/source/include/uapi/asm-generic/errno.h
+    pub fn from_errno(errno: i64) -> Self {
+        match errno {
+            1 | 13 => SocketError::PermissionDenied,
+            4 => SocketError::Interrupted,
+            11 => SocketError::WouldBlock,
+            9 | 14 | 22 | 88 => SocketError::InvalidInput,
+            32 => SocketError::BrokenPipe,
+            38 | 93 | 94 | 95 | 97 => SocketError::Unsupported,
+            98 => SocketError::AddrInUse,
+            99 => SocketError::AddrNotAvailable,
+            101 => SocketError::NetworkUnreachable,
+            103 => SocketError::ConnectionAborted,
+            104 => SocketError::ConnectionReset,
+            107 => SocketError::NotConnected,
+            110 => SocketError::TimedOut,
+            111 => SocketError::ConnectionRefused,
+            113 => SocketError::HostUnreachable,
+            114 | 115 => SocketError::InProgress,
+            _ => SocketError::Other(errno),
+        }
+    }
+}
+
+impl fmt::Display for SocketError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            SocketError::PermissionDenied => "permission denied",
+            SocketError::AddrInUse => "address in use",
+            SocketError::AddrNotAvailable => "address not available",
+            SocketError::ConnectionRefused => "connection refused",
+            SocketError::ConnectionReset => "connection reset",
+            SocketError::ConnectionAborted => "connection aborted",
+            SocketError::NotConnected => "not connected",
+            SocketError::TimedOut => "timed out",
+            SocketError::HostUnreachable => "host unreachable",
+            SocketError::NetworkUnreachable => "network unreachable",
+            SocketError::BrokenPipe => "broken pipe",
+            SocketError::WouldBlock => "operation would block",
+            SocketError::InProgress => "operation in progress",
+            SocketError::Interrupted => "interrupted",
+            SocketError::InvalidInput => "invalid input",
+            SocketError::Unsupported => "unsupported",
+            SocketError::Other(errno) => return write!(f, "errno {}", errno),
+        };
+        f.write_str(message)
+    }
+}
+
+/// Returns the value which a syscall returned, or the error if it's a negative errno.
+fn check(ret: i64) -> Result<usize, SocketError> {
+    if ret < 0 {
+        Err(SocketError::from_errno(-ret))
+    } else {
+        Ok(ret as usize)
+    }
+}
+
+/// The capacity which an empty buffer gets first when bytes are received into it.
+const MIN_RECV_CAPACITY: usize = 4096;
+
+/// Receives the bytes which have arrived on the socket `fd` into the end of `buf`, waiting for
+/// some if none has and the socket blocks. The buffer becomes twice as large whenever it's full,
+/// so that nothing is truncated. Returns how many bytes are received, which is 0 at the end of
+/// the stream.
+pub fn recv_into(fd: &FileDescriptor, buf: &mut Vec<u8>) -> Result<usize, SocketError> {
+    if buf.len() == buf.capacity() {
+        buf.reserve(buf.capacity().max(MIN_RECV_CAPACITY));
+    }
+    let start = buf.len();
+    let len = check(unsafe {
+        sys_recvfrom(
+            fd.fd as u32,
+            buf.as_mut_ptr().add(start),
+            buf.capacity() - start,
+            0,
+            null_mut(),
+            0,
+        )
+    })?;
+    // The kernel has written `len` bytes into the spare capacity.
+    unsafe { buf.set_len(start + len) };
+    Ok(len)
+}
+
+/// Receives bytes from the socket `fd` like `recv_into()` until the peer shuts down its side of
+/// the connection, and returns how many are received. It fails with TimedOut if nothing arrives
+/// for `timeout`, or waits forever if it's None. A non-blocking socket is waited for by `poll()`.
+pub fn recv_all(
+    fd: &FileDescriptor,
+    buf: &mut Vec<u8>,
+    timeout: Option<Duration>,
+) -> Result<usize, SocketError> {
+    let start = buf.len();
+    let timeout = timeout.map(|t| t.as_millis().min(u64::MAX as u128) as u64);
+    loop {
+        // A blocking socket is waited for here too, since `recv_into()` can't time out.
+        if timeout.is_some() {
+            let mut fds = [PollFd::new(fd.fd, POLLIN)];
+            if poll(&mut fds, timeout) == Some(0) {
+                return Err(SocketError::TimedOut);
+            }
+        }
+        match recv_into(fd, buf) {
+            Ok(0) => return Ok(buf.len() - start),
+            Ok(_) | Err(SocketError::Interrupted) => {}
+            Err(SocketError::WouldBlock) => {
+                if timeout.is_none() {
+                    poll(&mut [PollFd::new(fd.fd, POLLIN)], None);
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// An IPv4 address and a port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SocketAddrV4 {
+    ip: [u8; 4],
+    port: u16,
+}
+
+impl SocketAddrV4 {
+    pub fn new(ip: [u8; 4], port: u16) -> Self {
+        Self { ip, port }
+    }
+
+    pub fn ip(&self) -> [u8; 4] {
+        self.ip
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    fn to_raw(self) -> SockAddrIn {
+        SockAddrIn {
+            sin_family: AF_INET as u16,
+            sin_port: self.port.to_be(),
+            in_addr: u32::from_ne_bytes(self.ip),
+            padding: [0; 8],
+        }
+    }
+
+    fn from_raw(raw: &SockAddrIn) -> Self {
+        Self::new(raw.in_addr.to_ne_bytes(), u16::from_be(raw.sin_port))
+    }
+}
+
+impl fmt::Display for SocketAddrV4 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [a, b, c, d] = self.ip;
+        write!(f, "{}.{}.{}.{}:{}", a, b, c, d, self.port)
+    }
+}
+
+const SOL_SOCKET: u32 = 1;
+const SO_ERROR: u32 = 4;
+
+/// Sets or clears O_NONBLOCK of `fd`.
+fn set_nonblocking(fd: &FileDescriptor, nonblocking: bool) -> Result<(), SocketError> {
+    let flags = check(fcntl(fd, F_GETFL, 0) as i64)? as u32;
+    let flags = if nonblocking {
+        flags | O_NONBLOCK
+    } else {
+        flags & !O_NONBLOCK
+    };
+    check(fcntl(fd, F_SETFL, flags as u64) as i64)?;
+    Ok(())
+}
+
+/// Which directions of a connection are shut down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shutdown {
+    Read,
+    Write,
+    Both,
+}
+
+fn tcp_socket() -> Result<FileDescriptor, SocketError> {
+    let fd = unsafe { sys_socket(AF_INET, SOCK_STREAM, 0) };
+    check(fd as i64)?;
+    Ok(FileDescriptor { fd })
+}
+
+/// A TCP connection, which is closed when it's dropped.
+#[derive(Debug)]
+pub struct TcpStream {
+    fd: FileDescriptor,
+}
+
+impl TcpStream {
+    /// Connects to `addr`, waiting until the connection is established.
+    pub fn connect(addr: SocketAddrV4) -> Result<Self, SocketError> {
+        let fd = tcp_socket()?;
+        let raw = addr.to_raw();
+        check(unsafe { sys_connect(fd.fd as u32, &raw, size_of::<SockAddrIn>()) } as i64)?;
+        Ok(Self { fd })
+    }
+
+    /// Starts connecting to `addr` without waiting, and returns the stream in the non-blocking
+    /// mode. It's connected when poll says it's writable, and `take_error()` tells whether it
+    /// failed then.
+    pub fn connect_nonblocking(addr: SocketAddrV4) -> Result<Self, SocketError> {
+        let fd = tcp_socket()?;
+        set_nonblocking(&fd, true)?;
+        let raw = addr.to_raw();
+        match check(unsafe { sys_connect(fd.fd as u32, &raw, size_of::<SockAddrIn>()) } as i64) {
+            Ok(_) | Err(SocketError::InProgress) => Ok(Self { fd }),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn file_descriptor(&self) -> &FileDescriptor {
+        &self.fd
+    }
+
+    /// Makes reading and writing return WouldBlock instead of waiting if `nonblocking`.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> Result<(), SocketError> {
+        set_nonblocking(&self.fd, nonblocking)
+    }
+
+    /// Returns the error which happened on the socket, e.g. a refused non-blocking connection,
+    /// and clears it.
+    pub fn take_error(&self) -> Result<Option<SocketError>, SocketError> {
+        let mut error = 0;
+        let mut len = size_of::<i32>() as u32;
+        check(unsafe {
+            sys_getsockopt(
+                self.fd.fd as u32,
+                SOL_SOCKET,
+                SO_ERROR,
+                &mut error,
+                &mut len,
+            )
+        } as i64)?;
+        if error == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(SocketError::from_errno(error as i64)))
+        }
+    }
+
+    /// Reads the bytes which have arrived into `buf`, waiting for some if none has, and returns
+    /// how many are read. It returns 0 at the end of the stream.
+    pub fn read(&self, buf: &mut [u8]) -> Result<usize, SocketError> {
+        check(unsafe {
+            sys_recvfrom(
+                self.fd.fd as u32,
+                buf.as_mut_ptr(),
+                buf.len(),
+                0,
+                null_mut(),
+                0,
+            )
+        })
+    }
+
+    /// Reads until the peer shuts down its side of the connection, appending the bytes to
+    /// `buf`, and returns how many are read.
+    pub fn read_to_end(&self, buf: &mut Vec<u8>) -> Result<usize, SocketError> {
+        recv_all(&self.fd, buf, None)
+    }
+
+    /// Writes some of `buf` and returns how many bytes are written.
+    pub fn write(&self, buf: &[u8]) -> Result<usize, SocketError> {
+        check(unsafe {
+            sys_sendto(
+                self.fd.fd as u32,
+                buf.as_ptr() as *mut u8,
+                buf.len(),
+                MSG_NOSIGNAL,
+                null(),
+                0,
+            )
+        })
+    }
+
+    /// Writes the whole `buf`.
+    pub fn write_all(&self, mut buf: &[u8]) -> Result<(), SocketError> {
+        while !buf.is_empty() {
+            match self.write(buf) {
+                Ok(0) => return Err(SocketError::BrokenPipe),
+                Ok(len) => buf = &buf[len..],
+                Err(SocketError::Interrupted) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Shuts down reading, writing or both, e.g. to tell the peer that nothing more is sent.
+    pub fn shutdown(&self, how: Shutdown) -> Result<(), SocketError> {
+        let how = match how {
+            Shutdown::Read => 0,
+            Shutdown::Write => 1,
+            Shutdown::Both => 2,
+        };
+        check(unsafe { sys_shutdown(self.fd.fd as u32, how) } as i64)?;
+        Ok(())
+    }
+}
+
+/// A TCP socket listening for connections, which is closed when it's dropped.
+#[derive(Debug)]
+pub struct TcpListener {
+    fd: FileDescriptor,
+}
+
+impl TcpListener {
+    /// Binds a socket to `addr` and listens on it. The port is chosen by the system if it's 0,
+    /// which `local_addr()` returns.
+    pub fn bind(addr: SocketAddrV4) -> Result<Self, SocketError> {
+        let fd = tcp_socket()?;
+        let raw = addr.to_raw();
+        check(unsafe { sys_bind(fd.fd as u32, &raw, size_of::<SockAddrIn>()) } as i64)?;
+        check(unsafe { sys_listen(fd.fd as u32, BACKLOG) } as i64)?;
+        Ok(Self { fd })
+    }
+
+    pub fn file_descriptor(&self) -> &FileDescriptor {
+        &self.fd
+    }
+
+    /// Makes `accept()` return WouldBlock instead of waiting if `nonblocking`.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> Result<(), SocketError> {
+        set_nonblocking(&self.fd, nonblocking)
+    }
+
+    /// Returns the address which the socket is bound to.
+    pub fn local_addr(&self) -> Result<SocketAddrV4, SocketError> {
+        let mut raw = SockAddrIn::default();
+        let mut len = size_of::<SockAddrIn>() as u32;
+        check(unsafe { sys_getsockname(self.fd.fd as u32, &mut raw, &mut len) } as i64)?;
+        Ok(SocketAddrV4::from_raw(&raw))
+    }
+
+    /// Waits for a connection and returns it with the address of the peer.
+    pub fn accept(&self) -> Result<(TcpStream, SocketAddrV4), SocketError> {
+        let mut raw = SockAddrIn::default();
+        let mut len = size_of::<SockAddrIn>() as u32;
+        let fd = unsafe { sys_accept(self.fd.fd as u32, &mut raw, &mut len) };
+        check(fd as i64)?;
+        Ok((
+            TcpStream {
+                fd: FileDescriptor { fd },
+            },
+            SocketAddrV4::from_raw(&raw),
+        ))
+    }
+}
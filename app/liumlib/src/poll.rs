@@ -0,0 +1,71 @@
+// Waiting for file descriptors to be ready, e.g. the standard input to have a key or a socket to
+// have a response, with a timeout. The kernel of liumOS knows whether the standard input has a
+// key, but it doesn't track sockets, so they are always ready there.
+
+use crate::*;
+
+/// There is data to read.
+pub const POLLIN: i16 = 0x1;
+/// Writing doesn't block.
+pub const POLLOUT: i16 = 0x4;
+pub const POLLERR: i16 = 0x8;
+/// The peer has closed the connection.
+pub const POLLHUP: i16 = 0x10;
+/// The file descriptor isn't open.
+pub const POLLNVAL: i16 = 0x20;
+
+/// The file descriptor of the standard input, which keys are read from.
+pub const STDIN: i32 = 0;
+
+/// A file descriptor to wait for, and the events which have happened on it.
+/// https://elixir.bootlin.com/linux/v5.4.66/source/include/uapi/asm-generic/poll.h#L36
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PollFd {
+    fd: i32,
+    events: i16,
+    revents: i16,
+}
+
+impl PollFd {
+    /// Waits for `events`, e.g. POLLIN, on the file descriptor `fd`, which is ignored if it's
+    /// negative.
+    pub fn new(fd: i32, events: i16) -> Self {
+        Self {
+            fd,
+            events,
+            revents: 0,
+        }
+    }
+
+    /// Returns the events which have happened, which may include POLLERR, POLLHUP and POLLNVAL
+    /// even if they aren't waited for.
+    pub fn revents(&self) -> i16 {
+        self.revents
+    }
+
+    /// Returns true if reading doesn't block, which includes the end of the file and errors.
+    pub fn is_readable(&self) -> bool {
+        self.revents & (POLLIN | POLLERR | POLLHUP | POLLNVAL) != 0
+    }
+
+    /// Returns true if writing doesn't block, which includes errors.
+    pub fn is_writable(&self) -> bool {
+        self.revents & (POLLOUT | POLLERR | POLLHUP | POLLNVAL) != 0
+    }
+}
+
+/// Waits until any of `fds` is ready or `timeout` milliseconds pass, or forever if it's None.
+/// Returns the number of the ready ones, which is 0 at the timeout, or None if it fails.
+pub fn poll(fds: &mut [PollFd], timeout: Option<u64>) -> Option<usize> {
+    let timeout = match timeout {
+        Some(ms) => ms.min(i32::MAX as u64) as i32,
+        None => -1,
+    };
+    let ready = unsafe { sys_poll(fds.as_mut_ptr(), fds.len(), timeout) };
+    if ready < 0 {
+        None
+    } else {
+        Some(ready as usize)
+    }
+}
@@ -0,0 +1,103 @@
+// Random numbers, e.g. for the boundaries of multipart bodies and the jitter of retries. The
+// generator is seeded by the getrandom syscall, whose bytes liumOS mixes from its clock, so the
+// numbers are unpredictable enough for IDs but not for cryptography.
+
+use crate::*;
+use core::time::Duration;
+
+/// Returned by getrandom when a signal interrupts it.
+const EINTR: i64 = -4;
+
+/// Fills `buf` with random bytes of the system, or returns None if it can't make them.
+pub fn fill_bytes(buf: &mut [u8]) -> Option<()> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let rest = &mut buf[filled..];
+        let len = unsafe { sys_getrandom(rest.as_mut_ptr(), rest.len(), 0) };
+        match len {
+            EINTR => {}
+            len if len <= 0 => return None,
+            len => filled += len as usize,
+        }
+    }
+    Some(())
+}
+
+/// A xorshift64* generator.
+/// https://en.wikipedia.org/wiki/Xorshift#xorshift*
+#[derive(Debug, Clone)]
+pub struct Rng {
+    /// It mustn't be 0, which the generator never leaves.
+    state: u64,
+}
+
+impl Rng {
+    /// Creates a generator seeded by the system, or by the time stamp counter if the system
+    /// can't make random bytes.
+    pub fn new() -> Self {
+        let mut seed = [0u8; 8];
+        match fill_bytes(&mut seed) {
+            Some(()) => Self::with_seed(u64::from_ne_bytes(seed)),
+            None => Self::with_seed(rdtsc()),
+        }
+    }
+
+    /// Creates a generator which makes the same numbers for the same seed, e.g. in tests.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            state: if seed == 0 { 0x9e3779b97f4a7c15 } else { seed },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        // The upper bits are better than the lower ones.
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Returns a number in [0, 1), which has all the precision of f64.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a number in 0..`bound`, which must be positive.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "empty range");
+        // The high bits of the product are even enough for the small bounds used here.
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+
+    /// Fills `buf` with random bytes.
+    pub fn fill(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(8) {
+            let bytes = self.next_u64().to_ne_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    /// Returns how long to wait before retrying after `attempt` failures, e.g. 1 after the first
+    /// one. It's random up to `base` doubled for each failure but the first, and `max` at most,
+    /// so that the clients which failed together don't retry together.
+    /// https://aws.amazon.com/blogs/architecture/exponential-backoff-and-jitter/
+    pub fn backoff(&mut self, base: Duration, max: Duration, attempt: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        let ceiling = base.checked_mul(factor).unwrap_or(max).min(max);
+        ceiling.mul_f64(self.next_f64())
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,240 @@
+// Surfaces, which applications paint pixels into and show on the screen. A window of liumOS is a
+// BMP file mapped into the memory of the process, and the kernel copies its pixels to the screen
+// when the file is synced (see sys_msync in src/syscall.cc). A `Window` hides the file behind the
+// pixels and `present()`, so that painters don't depend on where and how they are stored.
+
+use crate::gui::BitmapImageBuffer;
+use crate::*;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The file of the window of a process.
+const WINDOW_PATH: &str = "window.bmp";
+
+#[repr(packed)]
+pub struct BMPFileHeader {
+    pub signature: [u8; 2],
+    pub file_size: u32,
+    pub reserved: u32,
+    pub offset_to_data: u32, // offset in file to pixels
+}
+
+#[repr(packed)]
+pub struct BMPInfoV3Header {
+    pub info_size: u32, // = 0x38 for Size of data follows this header
+    pub xsize: i32,
+    pub ysize: i32,
+    pub planes: u16,            // = 1
+    pub bpp: u16,               // = 32
+    pub compression_type: u32,  // = 3: bit field, {B,G,R,A}
+    pub image_data_size: u32,   // Size of data which follows this header
+    pub pixel_per_meter_x: u32, // = 0x2E23 = 300 ppi
+    pub pixel_per_meter_y: u32,
+    pub padding: u64,
+    pub r_mask: u32,
+    pub g_mask: u32,
+    pub b_mask: u32,
+}
+
+/// Pixels shown on the screen. The pixels are 0x00RRGGBB, row by row from the top-left corner,
+/// and what's painted into them is shown when the surface is presented.
+pub trait Surface {
+    /// Returns the width and the height in px.
+    fn size(&self) -> (usize, usize);
+    /// Returns the pixels, which are as many as the width times the height.
+    fn pixels(&mut self) -> &mut [u32];
+    /// Shows the pixels painted since the last time.
+    fn present(&mut self);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurfaceError {
+    /// The file of the window can't be opened.
+    Open,
+    /// The file of the window can't be mapped into the memory.
+    Map,
+}
+
+/// The window of the process.
+#[derive(Debug)]
+pub struct Window {
+    file_buf: *mut u8,
+    file_size: usize,
+    bmp_buf: *mut u8,
+    width: usize,
+    height: usize,
+}
+
+impl Window {
+    /// Creates the window of `width` x `height` px, whose pixels are black at first. A process
+    /// has only one window on liumOS, and a BMP file which any image viewer opens on Linux.
+    pub fn create(width: usize, height: usize) -> Result<Self, SurfaceError> {
+        let fd = open(WINDOW_PATH, O_RDWR | O_CREAT, 0o664).ok_or(SurfaceError::Open)?;
+
+        // resize the file to store the bmp data
+        let header_size_with_padding =
+            (size_of::<BMPFileHeader>() + size_of::<BMPInfoV3Header>() + 0xF) & !0xF; /* header size aligned to 16-byte boundary */
+        let file_size = header_size_with_padding + width * height * 4;
+
+        ftruncate(&fd, file_size);
+        let file_buf = mmap(null_mut(), file_size, PROT_WRITE, MAP_SHARED, &fd, 0);
+
+        if file_buf == MAP_FAILED {
+            return Err(SurfaceError::Map);
+        }
+
+        let file_header = BMPFileHeader {
+            signature: [b'B', b'M'],
+            file_size: file_size as u32,
+            offset_to_data: header_size_with_padding as u32,
+            reserved: 0,
+        };
+
+        let info_header: BMPInfoV3Header = BMPInfoV3Header {
+            info_size: size_of::<BMPInfoV3Header>() as u32,
+            xsize: width as i32,
+            ysize: -(height as i32),
+            planes: 1,
+            bpp: 32,
+            compression_type: 3,
+            image_data_size: file_header.file_size
+                - size_of::<BMPFileHeader>() as u32
+                - size_of::<BMPInfoV3Header>() as u32,
+            pixel_per_meter_y: 0x2E23, /* 300ppi */
+            pixel_per_meter_x: 0x2E23, /* 300ppi */
+            padding: 0,
+            r_mask: 0xFF0000,
+            g_mask: 0x00FF00,
+            b_mask: 0x0000FF,
+        };
+
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                &file_header as *const BMPFileHeader as *const u8,
+                file_buf,
+                size_of::<BMPFileHeader>(),
+            );
+            core::ptr::copy_nonoverlapping(
+                &info_header as *const BMPInfoV3Header as *const u8,
+                file_buf.add(size_of::<BMPFileHeader>()),
+                size_of::<BMPInfoV3Header>(),
+            );
+        }
+
+        Ok(Self {
+            file_buf,
+            file_size,
+            bmp_buf: unsafe { file_buf.add(header_size_with_padding) },
+            width,
+            height,
+        })
+    }
+
+    /// Shows the whole window, since liumOS copies all the pixels of the file when it's synced.
+    fn sync(&self) {
+        msync(self.file_buf, self.file_size, MS_SYNC);
+    }
+}
+
+// Painters which write pixels through pointers paint into the window directly.
+impl BitmapImageBuffer for Window {
+    fn bytes_per_pixel(&self) -> i64 {
+        4
+    }
+    fn pixels_per_line(&self) -> i64 {
+        self.width as i64
+    }
+    fn width(&self) -> i64 {
+        self.width as i64
+    }
+    fn height(&self) -> i64 {
+        self.height as i64
+    }
+    fn buf(&self) -> *mut u8 {
+        self.bmp_buf
+    }
+    unsafe fn pixel_at(&self, x: i64, y: i64) -> *mut u8 {
+        self.buf()
+            .add(((y * self.pixels_per_line() + x) * self.bytes_per_pixel()) as usize)
+    }
+    fn flush(&self) {
+        self.sync();
+    }
+    fn is_in_x_range(&self, px: i64) -> bool {
+        0 <= px && px < self.width as i64
+    }
+    fn is_in_y_range(&self, py: i64) -> bool {
+        0 <= py && py < self.height as i64
+    }
+}
+
+impl Surface for Window {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn pixels(&mut self) -> &mut [u32] {
+        // The pixels are aligned to 16 bytes after the headers in the mapped pages.
+        unsafe {
+            core::slice::from_raw_parts_mut(self.bmp_buf as *mut u32, self.width * self.height)
+        }
+    }
+
+    fn present(&mut self) {
+        self.sync();
+    }
+}
+
+/// A surface in memory which isn't shown anywhere, e.g. to paint without a display and in tests.
+/// It keeps a copy of the pixels at the last presentation.
+#[derive(Debug, Clone)]
+pub struct MemorySurface {
+    width: usize,
+    height: usize,
+    pixels: Vec<u32>,
+    presented: Vec<u32>,
+    presentations: usize,
+}
+
+impl MemorySurface {
+    /// Creates a black surface of `width` x `height` px.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height],
+            presented: vec![0; width * height],
+            presentations: 0,
+        }
+    }
+
+    /// Returns the pixels shown at the last presentation, which are black before the first one.
+    pub fn presented(&self) -> &[u32] {
+        &self.presented
+    }
+
+    /// Returns the pixel shown at (`x`, `y`).
+    pub fn presented_pixel(&self, x: usize, y: usize) -> u32 {
+        self.presented[y * self.width + x]
+    }
+
+    /// Returns how many times the surface has been presented.
+    pub fn presentations(&self) -> usize {
+        self.presentations
+    }
+}
+
+impl Surface for MemorySurface {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn pixels(&mut self) -> &mut [u32] {
+        &mut self.pixels
+    }
+
+    fn present(&mut self) {
+        self.presented.copy_from_slice(&self.pixels);
+        self.presentations += 1;
+    }
+}
@@ -0,0 +1,128 @@
+// Clocks and sleeping. The monotonic clock counts from an arbitrary point, which is the boot on
+// liumOS, and measures durations, e.g. timeouts and the time taken by something. The wall clock
+// tells the date, which liumOS doesn't know, so it's available only on Linux.
+
+use crate::*;
+use core::ops::{Add, Sub};
+use core::time::Duration;
+
+/// The clock of the date.
+const CLOCK_REALTIME: u32 = 0;
+/// The clock which never goes back.
+const CLOCK_MONOTONIC: u32 = 1;
+
+/// https://elixir.bootlin.com/linux/v5.4.66/source/include/uapi/linux/time.h#L10
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Timespec {
+    tv_sec: i64,
+    tv_nsec: i64,
+}
+
+impl Timespec {
+    fn from_duration(duration: Duration) -> Self {
+        Self {
+            tv_sec: duration.as_secs().min(i64::MAX as u64) as i64,
+            tv_nsec: duration.subsec_nanos() as i64,
+        }
+    }
+
+    fn to_duration(self) -> Duration {
+        Duration::new(self.tv_sec.max(0) as u64, self.tv_nsec.max(0) as u32)
+    }
+}
+
+/// Reads `clock`, or returns None if the system doesn't have it.
+fn clock_gettime(clock: u32) -> Option<Duration> {
+    let mut tp = Timespec::default();
+    if unsafe { sys_clock_gettime(clock, &mut tp) } < 0 {
+        return None;
+    }
+    Some(tp.to_duration())
+}
+
+/// Returns the time of the monotonic clock since an arbitrary point, which is the boot on
+/// liumOS.
+///
+/// Panics if the system has no monotonic clock, which both liumOS and Linux have.
+pub fn uptime() -> Duration {
+    clock_gettime(CLOCK_MONOTONIC).expect("no monotonic clock")
+}
+
+/// Returns the time since the Unix epoch, or None if the system doesn't know the date, e.g.
+/// liumOS.
+pub fn wall_clock() -> Option<Duration> {
+    clock_gettime(CLOCK_REALTIME)
+}
+
+/// A point of time of the monotonic clock, which is compared with another one to measure the
+/// duration between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Instant(Duration);
+
+impl Instant {
+    pub fn now() -> Self {
+        Self(uptime())
+    }
+
+    /// Returns the duration since `earlier`, or zero if it's later than this.
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        self.0.checked_sub(earlier.0).unwrap_or_default()
+    }
+
+    /// Returns the duration since this instant.
+    pub fn elapsed(&self) -> Duration {
+        Self::now().duration_since(*self)
+    }
+
+    /// Returns the instant `duration` after this, or None if it can't be represented.
+    pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+        self.0.checked_add(duration).map(Self)
+    }
+
+    /// Returns the instant `duration` before this, or None if it's before the point the clock
+    /// counts from.
+    pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+        self.0.checked_sub(duration).map(Self)
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    /// Panics if the result can't be represented, like `Duration`.
+    fn add(self, duration: Duration) -> Instant {
+        self.checked_add(duration)
+            .expect("overflow when adding duration to instant")
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    /// Returns the duration since `earlier`, or zero if it's later.
+    fn sub(self, earlier: Instant) -> Duration {
+        self.duration_since(earlier)
+    }
+}
+
+/// Sleeps for `duration` at least. The other processes run meanwhile on liumOS.
+pub fn sleep(duration: Duration) {
+    let mut request = Timespec::from_duration(duration);
+    let mut remaining = Timespec::default();
+    // Linux wakes it up early when a signal is handled, and tells the time remaining.
+    while unsafe { sys_nanosleep(&request, &mut remaining) } < 0 {
+        if remaining.to_duration() == Duration::ZERO {
+            break;
+        }
+        request = remaining;
+    }
+}
+
+/// Sleeps until `deadline`, which returns at once if it has passed.
+pub fn sleep_until(deadline: Instant) {
+    let now = Instant::now();
+    if deadline > now {
+        sleep(deadline - now);
+    }
+}
@@ -617,6 +617,17 @@ const char* tree_aa[10] = {
     "       | |",    "      |---|",
 };
 
+// Returns true if `word` is NAME=value, which sets an environment variable of a command.
+static bool IsAssignment(const char* word) {
+  if (!word || word[0] == '=')
+    return false;
+  for (; *word; word++) {
+    if (*word == '=')
+      return true;
+  }
+  return false;
+}
+
 void Date() {
   uint8_t bcd_year = ReadCMOS(0x09);
   uint8_t bcd_month = ReadCMOS(0x08);
@@ -966,7 +977,15 @@ void Run(TextBox& tbox) {
       PutChar('\n');
     }
   } else {
-    const char* arg0 = args.GetArg(0);
+    // The words of NAME=value before a command are its environment, like sh.
+    int num_of_envs = 0;
+    while (IsAssignment(args.GetArg(num_of_envs)))
+      num_of_envs++;
+    const char* arg0 = args.GetArg(num_of_envs);
+    if (!arg0) {
+      PutString("No command after the environment\n");
+      return;
+    }
     EFIFile* file = nullptr;
     for (int i = 0; i < GetLoaderInfo().root_files_used; i++) {
       if (IsEqualString(GetLoaderInfo().root_files[i].GetFileName(), arg0)) {
@@ -980,21 +999,37 @@ void Run(TextBox& tbox) {
       tbox.putc('\n');
       return;
     }
-    int argc = args.GetNumOfArgs();
+    int argc = args.GetNumOfArgs() - num_of_envs;
     constexpr int kMaxArgvSize = 8;
     uint64_t argv[kMaxArgvSize];
+    uint64_t envp[CommandLineArgs::kMaxNumOfArgs];
     if (argc > kMaxArgvSize) {
       PutString("Too many args");
       tbox.putc('\n');
       return;
     }
     Process& proc = LoadELFAndCreateEphemeralProcess(*file);
+    for (int i = 0; i < num_of_envs; i++) {
+      const char* env = args.GetArg(i);
+      proc.GetExecutionContext().PushDataToStack(env, strlen(env) + 1);
+      envp[i] = proc.GetExecutionContext().GetRSP();
+    }
     for (int i = 0; i < argc; i++) {
-      const char* arg = args.GetArg(i);
+      const char* arg = args.GetArg(num_of_envs + i);
       proc.GetExecutionContext().PushDataToStack(arg, strlen(arg) + 1);
       argv[i] = proc.GetExecutionContext().GetRSP();
     }
     proc.GetExecutionContext().AlignStack(8);
+    // Both argv and envp end with NULL, and envp follows argv.
+    // https://uclibc.org/docs/psABI-x86_64.pdf Figure 3.9: Initial Process Stack
+    uint64_t null_pointer = 0;
+    proc.GetExecutionContext().PushDataToStack(&null_pointer,
+                                               sizeof(null_pointer));
+    for (int i = num_of_envs - 1; i >= 0; i--) {
+      proc.GetExecutionContext().PushDataToStack(&envp[i], sizeof(envp[0]));
+    }
+    proc.GetExecutionContext().PushDataToStack(&null_pointer,
+                                               sizeof(null_pointer));
     for (int i = argc - 1; i >= 0; i--) {
       proc.GetExecutionContext().PushDataToStack(&argv[i], sizeof(argv[0]));
     }
@@ -1014,6 +1049,7 @@ void Run(TextBox& tbox) {
         PutString("\nkilled.\n");
         break;
       }
+      DeliverKeyEvent(proc.GetID(), keyid);
       if (!KeyID::IsBreak(keyid)) {
         proc.GetStdIn().Push(keyid);
       }
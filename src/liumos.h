@@ -101,3 +101,5 @@ void MainForBootProcessor(void* image_handle, EFI::SystemTable* system_table);
 
 // @syscall.cc
 void EnableSyscall();
+void DeliverKeyEvent(uint64_t pid, uint16_t keyid);
+void DeliverPointerEvent(Sheet* sheet, int x, int y, uint8_t buttons);
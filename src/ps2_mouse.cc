@@ -203,6 +203,8 @@ void MouseManager() {
       focused = nullptr;
     }
     last_left_button_state = me.buttonL;
+    DeliverPointerEvent(FindWindowAtPosition(mx, my), mx, my,
+                        me.buttonL | me.buttonR << 1 | me.buttonC << 2);
 
     SheetPainter::DrawCharacter(*debug_info_sheet, me.buttonL ? 'L' : 'l',
                                 8 * 2, 32, false);
@@ -11,15 +11,27 @@ constexpr uint64_t kSyscallIndex_sys_read = 0;
 constexpr uint64_t kSyscallIndex_sys_write = 1;
 constexpr uint64_t kSyscallIndex_sys_open = 2;
 constexpr uint64_t kSyscallIndex_sys_close = 3;
+constexpr uint64_t kSyscallIndex_sys_poll = 7;
+constexpr uint64_t kSyscallIndex_sys_lseek = 8;
 constexpr uint64_t kSyscallIndex_sys_mmap = 9;
 constexpr uint64_t kSyscallIndex_sys_msync = 26;
+constexpr uint64_t kSyscallIndex_sys_nanosleep = 35;
 constexpr uint64_t kSyscallIndex_sys_socket = 41;
+constexpr uint64_t kSyscallIndex_sys_connect = 42;
+constexpr uint64_t kSyscallIndex_sys_accept = 43;
 constexpr uint64_t kSyscallIndex_sys_sendto = 44;
 constexpr uint64_t kSyscallIndex_sys_recvfrom = 45;
+constexpr uint64_t kSyscallIndex_sys_shutdown = 48;
 constexpr uint64_t kSyscallIndex_sys_bind = 49;
+constexpr uint64_t kSyscallIndex_sys_listen = 50;
+constexpr uint64_t kSyscallIndex_sys_getsockname = 51;
+constexpr uint64_t kSyscallIndex_sys_getsockopt = 55;
 constexpr uint64_t kSyscallIndex_sys_exit = 60;
+constexpr uint64_t kSyscallIndex_sys_fcntl = 72;
 constexpr uint64_t kSyscallIndex_sys_ftruncate = 77;
 constexpr uint64_t kSyscallIndex_sys_getdents64 = 217;
+constexpr uint64_t kSyscallIndex_sys_clock_gettime = 228;
+constexpr uint64_t kSyscallIndex_sys_getrandom = 318;
 constexpr uint64_t kSyscallIndex_arch_prctl = 158;
 // constexpr uint64_t kArchSetGS = 0x1001;
 constexpr uint64_t kArchSetFS = 0x1002;
@@ -28,8 +40,17 @@ constexpr uint64_t kArchSetFS = 0x1002;
 
 // https://elixir.bootlin.com/linux/v4.15/source/include/uapi/asm-generic/errno-base.h#L6
 enum ErrorNumber {
+  kNoEntry = -2,
   kBadFileDescriptor = -9,
+  kTryAgain = -11,
   kInvalid = -22,
+  kTooManyOpenFiles = -24,
+  kFileTooBig = -27,
+  kNoSpace = -28,
+  kIllegalSeek = -29,
+  kNameTooLong = -36,
+  kNotImplemented = -38,
+  kSocketTypeNotSupported = -94,
 };
 
 // c.f.
@@ -45,14 +66,269 @@ struct sockaddr_in {
 };
 typedef uint32_t socklen_t;
 
+// An input event read from kInputFileDescriptor, whose layout is shared with
+// app/liumlib/src/input.rs.
+struct InputEvent {
+  uint16_t type;
+  // KeyID of a key event, including its break bit and attributes.
+  uint16_t keyid;
+  // The position of the pointer relative to the window.
+  int16_t x;
+  int16_t y;
+  // The pressed buttons: bit 0 is the left, bit 1 is the right and bit 2 is
+  // the center.
+  uint8_t buttons;
+  uint8_t padding[7];
+};
+static_assert(sizeof(InputEvent) == 16);
+constexpr uint16_t kInputEventTypeKey = 1;
+constexpr uint16_t kInputEventTypePointer = 2;
+constexpr int kInputFileDescriptor = 6;
+constexpr const char* kInputFilePath = "/dev/liumos/input";
+
+// c.f.
+// https://elixir.bootlin.com/linux/v4.15/source/include/uapi/asm-generic/fcntl.h#L19
+constexpr int kOpenAccessModeMask = 03;
+constexpr int kOpenReadOnly = 00;
+constexpr int kOpenWriteOnly = 01;
+constexpr int kOpenReadWrite = 02;
+constexpr int kOpenCreate = 0100;
+constexpr int kOpenTruncate = 01000;
+constexpr int kOpenAppend = 02000;
+constexpr int kOpenNonBlock = 04000;
+
+// A file which a process created, e.g. the bookmarks of the browser. There is
+// no storage for processes yet, so the files are kept in the memory of the
+// kernel, shared by all processes, until liumOS shuts down.
+struct RamFile {
+  static constexpr size_t kMaxPathLen = 128;
+  static constexpr size_t kMaxSize = 16 * 1024 * 1024;
+  char path[kMaxPathLen];
+  uint8_t* data;
+  size_t size;
+  size_t capacity;
+};
+constexpr int kMaxNumOfRamFiles = 64;
+RamFile ram_files[kMaxNumOfRamFiles];
+int num_of_ram_files;
+
+// A RamFile opened by a process, whose file descriptor is
+// kFirstFileDescriptor + its index in PerProcessSyscallData::open_files.
+struct OpenFile {
+  RamFile* file;
+  size_t offset;
+  int flags;
+};
+constexpr int kFirstFileDescriptor = 7;
+constexpr int kMaxNumOfOpenFiles = 16;
+
 struct PerProcessSyscallData {
   Sheet* window_sheet;
   int num_getdents64_called;
+  bool is_input_opened;
+  RingBuffer<InputEvent, 64> input_events;
+  OpenFile open_files[kMaxNumOfOpenFiles];
+  // The flags set by fcntl on the file descriptors below kFirstFileDescriptor,
+  // e.g. O_NONBLOCK on the socket.
+  int fd_flags[kFirstFileDescriptor];
 };
 
 std::unordered_map<Process::PID, PerProcessSyscallData>
     per_process_syscall_data;
 
+static RamFile* FindRamFile(const char* path) {
+  for (int i = 0; i < num_of_ram_files; i++) {
+    if (strcmp(ram_files[i].path, path) == 0)
+      return &ram_files[i];
+  }
+  return nullptr;
+}
+
+// Makes the buffer of file hold size bytes at least. The old buffer is not
+// given back, like the rest of the kernel heap, so the capacity is doubled to
+// leave at most as much behind as the file holds.
+static bool ReserveRamFile(RamFile& file, size_t size) {
+  if (size <= file.capacity)
+    return true;
+  if (size > RamFile::kMaxSize)
+    return false;
+  size_t capacity = std::max(std::max(file.capacity * 2, size), kPageSize);
+  uint8_t* data = AllocKernelMemory<uint8_t*>(capacity);
+  memcpy(data, file.data, file.size);
+  file.data = data;
+  file.capacity = capacity;
+  return true;
+}
+
+static bool ResizeRamFile(RamFile& file, size_t size) {
+  if (!ReserveRamFile(file, size))
+    return false;
+  if (size > file.size)
+    bzero(file.data + file.size, size - file.size);
+  file.size = size;
+  return true;
+}
+
+static OpenFile* FindOpenFile(int fd) {
+  if (fd < kFirstFileDescriptor ||
+      fd >= kFirstFileDescriptor + kMaxNumOfOpenFiles)
+    return nullptr;
+  auto pid = liumos->scheduler->GetCurrentProcess().GetID();
+  OpenFile& open_file =
+      per_process_syscall_data[pid].open_files[fd - kFirstFileDescriptor];
+  return open_file.file ? &open_file : nullptr;
+}
+
+static int OpenRamFile(const char* path, int flags) {
+  size_t path_len = strlen(path);
+  if (path_len >= RamFile::kMaxPathLen)
+    return ErrorNumber::kNameTooLong;
+  auto pid = liumos->scheduler->GetCurrentProcess().GetID();
+  OpenFile* open_files = per_process_syscall_data[pid].open_files;
+  int index = 0;
+  while (index < kMaxNumOfOpenFiles && open_files[index].file) {
+    index++;
+  }
+  if (index == kMaxNumOfOpenFiles)
+    return ErrorNumber::kTooManyOpenFiles;
+  RamFile* file = FindRamFile(path);
+  if (!file) {
+    if (!(flags & kOpenCreate))
+      return ErrorNumber::kNoEntry;
+    if (num_of_ram_files == kMaxNumOfRamFiles)
+      return ErrorNumber::kNoSpace;
+    file = &ram_files[num_of_ram_files++];
+    memcpy(file->path, path, path_len + 1);
+  }
+  if ((flags & kOpenTruncate) &&
+      (flags & kOpenAccessModeMask) != kOpenReadOnly)
+    file->size = 0;
+  open_files[index] = {file, 0, flags};
+  return kFirstFileDescriptor + index;
+}
+
+static ssize_t ReadRamFile(OpenFile& open_file, void* buf, size_t count) {
+  if ((open_file.flags & kOpenAccessModeMask) == kOpenWriteOnly)
+    return ErrorNumber::kBadFileDescriptor;
+  const RamFile& file = *open_file.file;
+  if (open_file.offset >= file.size)
+    return 0;
+  size_t size = std::min(count, file.size - open_file.offset);
+  memcpy(buf, file.data + open_file.offset, size);
+  open_file.offset += size;
+  return size;
+}
+
+static ssize_t WriteRamFile(OpenFile& open_file,
+                            const void* buf,
+                            size_t count) {
+  if ((open_file.flags & kOpenAccessModeMask) == kOpenReadOnly)
+    return ErrorNumber::kBadFileDescriptor;
+  RamFile& file = *open_file.file;
+  if (open_file.flags & kOpenAppend)
+    open_file.offset = file.size;
+  if (count > RamFile::kMaxSize)
+    return ErrorNumber::kFileTooBig;
+  size_t end = open_file.offset + count;
+  if (end > file.size && !ResizeRamFile(file, end))
+    return ErrorNumber::kFileTooBig;
+  memcpy(file.data + open_file.offset, buf, count);
+  open_file.offset = end;
+  return count;
+}
+
+// c.f.
+// https://elixir.bootlin.com/linux/v4.15/source/include/uapi/asm-generic/fcntl.h#L102
+constexpr int kFcntlGetFlags = 3;
+constexpr int kFcntlSetFlags = 4;
+// The flags which F_SETFL changes, like Linux does. The others are ignored.
+constexpr int kFcntlSettableFlags = kOpenAppend | kOpenNonBlock;
+
+// Returns the flags of fd which fcntl gets and sets, or nullptr if fd is not
+// open. Those of a RamFile include its access mode.
+static int* FindFileDescriptorFlags(int fd) {
+  if (OpenFile* open_file = FindOpenFile(fd))
+    return &open_file->flags;
+  auto pid = liumos->scheduler->GetCurrentProcess().GetID();
+  auto& ppdata = per_process_syscall_data[pid];
+  bool is_open = (0 <= fd && fd <= 2) ||
+                 (fd == kInputFileDescriptor && ppdata.is_input_opened) ||
+                 Network::GetInstance().FindSocket(pid, fd).has_value();
+  return is_open ? &ppdata.fd_flags[fd] : nullptr;
+}
+
+static bool IsNonBlocking(int fd) {
+  int* flags = FindFileDescriptorFlags(fd);
+  return flags && (*flags & kOpenNonBlock);
+}
+
+static int sys_fcntl(int fd, int cmd, uint64_t arg) {
+  int* flags = FindFileDescriptorFlags(fd);
+  if (!flags)
+    return ErrorNumber::kBadFileDescriptor;
+  if (cmd == kFcntlGetFlags) {
+    if (FindOpenFile(fd))
+      return *flags & (kOpenAccessModeMask | kFcntlSettableFlags);
+    int access_mode = fd == 0                ? kOpenReadOnly
+                      : (fd == 1 || fd == 2) ? kOpenWriteOnly
+                                             : kOpenReadWrite;
+    return access_mode | *flags;
+  }
+  if (cmd == kFcntlSetFlags) {
+    *flags = (*flags & ~kFcntlSettableFlags) |
+             (static_cast<int>(arg) & kFcntlSettableFlags);
+    return 0;
+  }
+  return ErrorNumber::kInvalid;
+}
+
+// c.f.
+// https://elixir.bootlin.com/linux/v4.15/source/include/uapi/linux/fs.h#L38
+constexpr int kSeekSet = 0;
+constexpr int kSeekCur = 1;
+constexpr int kSeekEnd = 2;
+
+static int64_t sys_lseek(int fd, int64_t offset, int whence) {
+  OpenFile* open_file = FindOpenFile(fd);
+  if (!open_file)
+    return ErrorNumber::kIllegalSeek;
+  int64_t base;
+  if (whence == kSeekSet) {
+    base = 0;
+  } else if (whence == kSeekCur) {
+    base = open_file->offset;
+  } else if (whence == kSeekEnd) {
+    base = open_file->file->size;
+  } else {
+    return ErrorNumber::kInvalid;
+  }
+  if (offset < -base || offset > static_cast<int64_t>(RamFile::kMaxSize))
+    return ErrorNumber::kInvalid;
+  open_file->offset = base + offset;
+  return open_file->offset;
+}
+
+static int sys_ftruncate(int fd, int64_t size) {
+  if (fd == 5) {
+    // The window, which is mapped by mmap.
+    return 0;
+  }
+  OpenFile* open_file = FindOpenFile(fd);
+  if (!open_file)
+    return ErrorNumber::kBadFileDescriptor;
+  if ((open_file->flags & kOpenAccessModeMask) == kOpenReadOnly || size < 0)
+    return ErrorNumber::kInvalid;
+  if (!ResizeRamFile(*open_file->file, size))
+    return ErrorNumber::kFileTooBig;
+  return 0;
+}
+
+static int sys_close(int fd) {
+  if (OpenFile* open_file = FindOpenFile(fd))
+    *open_file = {};
+  return 0;
+}
+
 extern "C" uint64_t GetCurrentKernelStack(void) {
   ExecutionContext& ctx =
       liumos->scheduler->GetCurrentProcess().GetExecutionContext();
@@ -122,6 +398,7 @@ static ssize_t sys_recvfrom(int sockfd,
     return -1;
   }
   Socket::Type socket_type = (*sock_holder).type;
+  const bool nonblocking = IsNonBlocking(sockfd);
   if (socket_type == Socket::Type::kICMPDatagram) {
     for (;;) {
       while (network.HasPacketInRXBuffer()) {
@@ -136,6 +413,8 @@ static ssize_t sys_recvfrom(int sockfd,
         recv_addr->sin_addr = icmp.ip.src_ip;
         return icmp_data_size;
       }
+      if (nonblocking)
+        return ErrorNumber::kTryAgain;
       Sleep();
     }
     return -1;
@@ -152,6 +431,8 @@ static ssize_t sys_recvfrom(int sockfd,
         memcpy(buf, &packet.data[sizeof(EtherFrame)], copy_size);
         return ip_data_size;
       }
+      if (nonblocking)
+        return ErrorNumber::kTryAgain;
       Sleep();
     }
     return -1;
@@ -174,6 +455,8 @@ static ssize_t sys_recvfrom(int sockfd,
             *reinterpret_cast<uint16_t*>(&udp_packet->src_port);
         return udp_data_size;
       }
+      if (nonblocking)
+        return ErrorNumber::kTryAgain;
       Sleep();
     }
     return -1;
@@ -226,7 +509,7 @@ static int sys_socket(int domain, int type, int protocol) {
   }
   kprintf("kernel: %s: socket(%d, %d, %d) is not supported yet\n", __func__,
           domain, type, protocol);
-  return -1 /* Return -1 on error */;
+  return ErrorNumber::kSocketTypeNotSupported;
 }
 
 static int sys_bind(int sockfd, sockaddr_in* addr, socklen_t addrlen) {
@@ -248,7 +531,58 @@ static int sys_bind(int sockfd, sockaddr_in* addr, socklen_t addrlen) {
   return 0;
 }
 
+void DeliverKeyEvent(uint64_t pid, uint16_t keyid) {
+  auto it = per_process_syscall_data.find(pid);
+  if (it == per_process_syscall_data.end() || !it->second.is_input_opened)
+    return;
+  InputEvent e = {};
+  e.type = kInputEventTypeKey;
+  e.keyid = keyid;
+  it->second.input_events.Push(e);
+}
+
+void DeliverPointerEvent(Sheet* sheet, int x, int y, uint8_t buttons) {
+  if (!sheet)
+    return;
+  for (auto& [pid, ppdata] : per_process_syscall_data) {
+    if (ppdata.window_sheet != sheet || !ppdata.is_input_opened)
+      continue;
+    InputEvent e = {};
+    e.type = kInputEventTypePointer;
+    e.x = static_cast<int16_t>(x - sheet->GetX());
+    e.y = static_cast<int16_t>(y - sheet->GetY());
+    e.buttons = buttons;
+    ppdata.input_events.Push(e);
+    return;
+  }
+}
+
+// Reads as many whole events as fit in buf, waiting for one if there is none
+// unless the file descriptor is non-blocking.
+static ssize_t ReadInputEvents(void* buf, size_t count) {
+  if (count < sizeof(InputEvent))
+    return ErrorNumber::kInvalid;
+  auto pid = liumos->scheduler->GetCurrentProcess().GetID();
+  auto& events = per_process_syscall_data[pid].input_events;
+  if (events.IsEmpty() && IsNonBlocking(kInputFileDescriptor))
+    return ErrorNumber::kTryAgain;
+  while (events.IsEmpty()) {
+    StoreIntFlagAndHalt();
+  }
+  size_t size = 0;
+  while (!events.IsEmpty() && size + sizeof(InputEvent) <= count) {
+    InputEvent e = events.Pop();
+    memcpy(reinterpret_cast<uint8_t*>(buf) + size, &e, sizeof(e));
+    size += sizeof(e);
+  }
+  return size;
+}
+
 static ssize_t sys_read(int fd, void* buf, size_t count) {
+  if (fd == kInputFileDescriptor)
+    return ReadInputEvents(buf, count);
+  if (OpenFile* open_file = FindOpenFile(fd))
+    return ReadRamFile(*open_file, buf, count);
   if (fd != 0) {
     kprintf("%s: fd %d is not supported yet: only stdin is supported now.\n",
             __func__, fd);
@@ -257,6 +591,8 @@ static ssize_t sys_read(int fd, void* buf, size_t count) {
   if (count < 1)
     return ErrorNumber::kInvalid;
   auto& proc_stdin = liumos->scheduler->GetCurrentProcess().GetStdIn();
+  if (proc_stdin.IsEmpty() && IsNonBlocking(fd))
+    return ErrorNumber::kTryAgain;
   while (proc_stdin.IsEmpty()) {
     StoreIntFlagAndHalt();
   }
@@ -264,6 +600,115 @@ static ssize_t sys_read(int fd, void* buf, size_t count) {
   return 1;
 }
 
+// c.f.
+// https://elixir.bootlin.com/linux/v4.15/source/include/uapi/asm-generic/poll.h#L36
+struct pollfd {
+  int fd;
+  int16_t events;
+  int16_t revents;
+};
+constexpr int16_t kPollIn = 0x1;
+constexpr int16_t kPollOut = 0x4;
+constexpr int16_t kPollNval = 0x20;
+
+// Only stdin and the input events are really waited for. Sockets and files are
+// always ready since the buffers of sockets are not tracked, so reading a
+// socket blocks in sys_recvfrom, or fails with EAGAIN if it's non-blocking.
+static int sys_poll(struct pollfd* fds, uint64_t nfds, int timeout_ms) {
+  auto& proc = liumos->scheduler->GetCurrentProcess();
+  auto& proc_stdin = proc.GetStdIn();
+  Network& network = Network::GetInstance();
+  HPET& hpet = HPET::GetInstance();
+  const uint64_t deadline =
+      hpet.ReadMainCounterValue() +
+      hpet.GetCountPerSecond() * static_cast<uint64_t>(timeout_ms) / 1000;
+  for (;;) {
+    int num_ready = 0;
+    for (uint64_t i = 0; i < nfds; i++) {
+      struct pollfd& pfd = fds[i];
+      pfd.revents = 0;
+      if (pfd.fd < 0)
+        continue;
+      if (pfd.fd == 0) {
+        if (!proc_stdin.IsEmpty())
+          pfd.revents = pfd.events & kPollIn;
+      } else if (pfd.fd == kInputFileDescriptor) {
+        if (!per_process_syscall_data[proc.GetID()].input_events.IsEmpty())
+          pfd.revents = pfd.events & kPollIn;
+      } else if (FindOpenFile(pfd.fd)) {
+        pfd.revents = pfd.events & (kPollIn | kPollOut);
+      } else if (network.FindSocket(proc.GetID(), pfd.fd).has_value()) {
+        pfd.revents = pfd.events & (kPollIn | kPollOut);
+      } else {
+        pfd.revents = kPollNval;
+      }
+      if (pfd.revents)
+        num_ready++;
+    }
+    if (num_ready || timeout_ms == 0)
+      return num_ready;
+    if (timeout_ms > 0 && hpet.ReadMainCounterValue() >= deadline)
+      return 0;
+    StoreIntFlagAndHalt();
+  }
+}
+
+// c.f.
+// https://elixir.bootlin.com/linux/v4.15/source/include/uapi/linux/time.h#L10
+struct Timespec {
+  int64_t tv_sec;
+  int64_t tv_nsec;
+};
+constexpr int kClockMonotonic = 1;
+
+static uint64_t ReadNanoseconds() {
+  HPET& hpet = HPET::GetInstance();
+  return hpet.ReadMainCounterValue() * (hpet.GetFemtosecondPerCount() / 1000) /
+         1000;
+}
+
+// Only CLOCK_MONOTONIC is supported, which counts from the boot by the HPET.
+// There is no wall clock, so CLOCK_REALTIME fails and applications can tell.
+static int sys_clock_gettime(int clock_id, struct Timespec* tp) {
+  if (clock_id != kClockMonotonic)
+    return kInvalid;
+  const uint64_t ns = ReadNanoseconds();
+  tp->tv_sec = ns / 1'000'000'000;
+  tp->tv_nsec = ns % 1'000'000'000;
+  return 0;
+}
+
+static int sys_nanosleep(const struct Timespec* req) {
+  if (req->tv_sec < 0 || req->tv_nsec < 0 || req->tv_nsec >= 1'000'000'000)
+    return kInvalid;
+  const uint64_t deadline = ReadNanoseconds() +
+                            static_cast<uint64_t>(req->tv_sec) * 1'000'000'000 +
+                            static_cast<uint64_t>(req->tv_nsec);
+  while (ReadNanoseconds() < deadline) {
+    StoreIntFlagAndHalt();
+  }
+  return 0;
+}
+
+// liumOS has no entropy pool, so the random bytes are mixed from the counter of
+// the HPET by splitmix64. They are unpredictable enough for IDs and boundaries,
+// but not for cryptography. The flags are ignored.
+// https://prng.di.unimi.it/splitmix64.c
+static int64_t sys_getrandom(uint8_t* buf, size_t len) {
+  static uint64_t state = 0;
+  for (size_t i = 0; i < len; i += 8) {
+    state += 0x9e3779b97f4a7c15 ^ ReadNanoseconds();
+    uint64_t z = state;
+    z = (z ^ (z >> 30)) * 0xbf58476d1ce4e5b9;
+    z = (z ^ (z >> 27)) * 0x94d049bb133111eb;
+    z ^= z >> 31;
+    for (size_t k = 0; k < 8 && i + k < len; k++) {
+      buf[i + k] = static_cast<uint8_t>(z >> (k * 8));
+    }
+  }
+  return static_cast<int64_t>(len);
+}
+
 static std::optional<Network::EtherAddr> ResolveIPv4WithTimeout(
     Network::IPv4Addr dst_ip_addr,
     uint64_t timeout_ms) {
@@ -435,6 +880,10 @@ __attribute__((ms_abi)) extern "C" void SyscallHandler(uint64_t* args) {
     const uint64_t fildes = args[1];
     const uint8_t* buf = reinterpret_cast<uint8_t*>(args[2]);
     uint64_t nbyte = args[3];
+    if (OpenFile* open_file = FindOpenFile(static_cast<int>(fildes))) {
+      args[0] = WriteRamFile(*open_file, buf, nbyte);
+      return;
+    }
     if (fildes != 1) {
       kprintf("%s: fd = %d is not supported yet\n", __func__, fildes);
       args[0] = ErrorNumber::kBadFileDescriptor;
@@ -450,16 +899,52 @@ __attribute__((ms_abi)) extern "C" void SyscallHandler(uint64_t* args) {
     }
     return;
   }
+  if (idx == kSyscallIndex_sys_poll) {
+    args[0] = sys_poll(reinterpret_cast<struct pollfd*>(args[1]), args[2],
+                       static_cast<int>(args[3]));
+    return;
+  }
+  if (idx == kSyscallIndex_sys_nanosleep) {
+    args[0] = sys_nanosleep(reinterpret_cast<const struct Timespec*>(args[1]));
+    return;
+  }
+  if (idx == kSyscallIndex_sys_clock_gettime) {
+    args[0] = sys_clock_gettime(static_cast<int>(args[1]),
+                                reinterpret_cast<struct Timespec*>(args[2]));
+    return;
+  }
+  if (idx == kSyscallIndex_sys_getrandom) {
+    args[0] = sys_getrandom(reinterpret_cast<uint8_t*>(args[1]), args[2]);
+    return;
+  }
   if (idx == kSyscallIndex_sys_open) {
     auto pid = liumos->scheduler->GetCurrentProcess().GetID();
     auto& ppdata = per_process_syscall_data[pid];
+    const char* path = reinterpret_cast<const char*>(args[1]);
+    if (strcmp(path, kInputFilePath) == 0) {
+      ppdata.is_input_opened = true;
+      args[0] = kInputFileDescriptor;
+      return;
+    }
+    // The window, which is mapped by mmap, and the directory listed by
+    // getdents64 are backed by the kernel. Any other path is a RamFile.
+    if (strcmp(path, "window.bmp") != 0 && strcmp(path, ".") != 0) {
+      args[0] = OpenRamFile(path, static_cast<int>(args[2]));
+      return;
+    }
     ppdata.num_getdents64_called = 0;
 
     args[0] = 5;
     return;
   }
+  if (idx == kSyscallIndex_sys_lseek) {
+    args[0] =
+        sys_lseek(static_cast<int>(args[1]), static_cast<int64_t>(args[2]),
+                  static_cast<int>(args[3]));
+    return;
+  }
   if (idx == kSyscallIndex_sys_close) {
-    args[0] = 0;
+    args[0] = sys_close(static_cast<int>(args[1]));
     return;
   }
   if (idx == kSyscallIndex_sys_mmap) {
@@ -531,14 +1016,8 @@ __attribute__((ms_abi)) extern "C" void SyscallHandler(uint64_t* args) {
     return;
   }
   if (idx == kSyscallIndex_sys_ftruncate) {
-    uint64_t fd = args[1];
-    uint64_t size = args[2];
-    kprintf("ftruncate(fd=%d, size=%d)\n", fd, size);
-    if (fd != 5) {
-      args[0] = -1;
-      return;
-    }
-    args[0] = 0;
+    args[0] = sys_ftruncate(static_cast<int>(args[1]),
+                            static_cast<int64_t>(args[2]));
     return;
   }
   if (idx == kSyscallIndex_arch_prctl) {
@@ -577,6 +1056,20 @@ __attribute__((ms_abi)) extern "C" void SyscallHandler(uint64_t* args) {
                        static_cast<socklen_t>(args[3]));
     return;
   }
+  if (idx == kSyscallIndex_sys_connect || idx == kSyscallIndex_sys_accept ||
+      idx == kSyscallIndex_sys_shutdown || idx == kSyscallIndex_sys_listen ||
+      idx == kSyscallIndex_sys_getsockname ||
+      idx == kSyscallIndex_sys_getsockopt) {
+    // There are no TCP sockets yet, which these are for. Fail like Linux does
+    // for a syscall it doesn't have, so that the process can handle it.
+    args[0] = ErrorNumber::kNotImplemented;
+    return;
+  }
+  if (idx == kSyscallIndex_sys_fcntl) {
+    args[0] = sys_fcntl(static_cast<int>(args[1]), static_cast<int>(args[2]),
+                        args[3]);
+    return;
+  }
   if (idx == kSyscallIndex_sys_getdents64) {
     args[0] = sys_getdents64(static_cast<int>(args[1]),
                              reinterpret_cast<void*>(args[2]), args[3]);